@@ -6,7 +6,7 @@ use tao::{
   event::{DeviceEvent, ElementState, Event, KeyEvent, WindowEvent},
   event_loop::{ControlFlow, EventLoop},
   keyboard::{Key, ModifiersState},
-  window::WindowBuilder,
+  window::{CursorGrabMode, WindowBuilder},
 };
 
 #[allow(clippy::single_match)]
@@ -41,7 +41,11 @@ fn main() {
           match key {
             Key::Escape => *control_flow = ControlFlow::Exit,
             Key::Character(ch) => match ch.to_lowercase().as_str() {
-              "g" => window.set_cursor_grab(!modifiers.shift_key()).unwrap(),
+              "g" | "l" if modifiers.shift_key() => {
+                window.set_cursor_grab(CursorGrabMode::None).unwrap()
+              }
+              "g" => window.set_cursor_grab(CursorGrabMode::Confined).unwrap(),
+              "l" => window.set_cursor_grab(CursorGrabMode::Locked).unwrap(),
               "h" => window.set_cursor_visible(modifiers.shift_key()),
               _ => (),
             },
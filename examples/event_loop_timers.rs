@@ -0,0 +1,53 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use tao::{
+  event::{Event, StartCause, WindowEvent},
+  event_loop::{ControlFlow, EventLoop},
+  window::WindowBuilder,
+};
+
+#[allow(clippy::single_match)]
+fn main() {
+  env_logger::init();
+  let event_loop = EventLoop::new();
+
+  let window = WindowBuilder::new()
+    .with_title("A fantastic window!")
+    .build(&event_loop)
+    .unwrap();
+
+  let mut blink = None;
+  let mut stop = None;
+  let mut visible = true;
+
+  event_loop.run(move |event, event_loop, control_flow| {
+    *control_flow = ControlFlow::Wait;
+
+    match event {
+      Event::NewEvents(StartCause::Init) => {
+        // Blinks the title every half second, for five seconds.
+        blink = Some(event_loop.set_timer(Duration::from_millis(500), true));
+        stop = Some(event_loop.set_timer(Duration::from_secs(5), false));
+      }
+      Event::Timer(id) if Some(id) == blink => {
+        visible = !visible;
+        window.set_title(if visible { "A fantastic window!" } else { "" });
+      }
+      Event::Timer(id) if Some(id) == stop => {
+        if let Some(blink) = blink.take() {
+          event_loop.cancel_timer(blink);
+        }
+        window.set_title("A fantastic window!");
+      }
+      Event::WindowEvent {
+        event: WindowEvent::CloseRequested,
+        ..
+      } => *control_flow = ControlFlow::Exit,
+      _ => (),
+    }
+  });
+}
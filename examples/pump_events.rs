@@ -0,0 +1,67 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+// Limit this example to only compatible platforms.
+#[cfg(any(
+  target_os = "windows",
+  target_os = "macos",
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+  target_os = "android"
+))]
+#[allow(clippy::single_match)]
+fn main() {
+  use std::{thread::sleep, time::Duration};
+
+  use tao::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    platform::pump_events::{EventLoopExtPumpEvents, PumpStatus},
+    window::WindowBuilder,
+  };
+  let mut event_loop = EventLoop::new();
+
+  env_logger::init();
+  let window = WindowBuilder::new()
+    .with_title("A fantastic window!")
+    .build(&event_loop)
+    .unwrap();
+
+  loop {
+    let status = event_loop.pump_events(Some(Duration::ZERO), |event, _, control_flow| {
+      *control_flow = ControlFlow::Wait;
+
+      if let Event::WindowEvent { event, .. } = &event {
+        // Print only Window events to reduce noise
+        println!("{:?}", event);
+      }
+
+      match event {
+        Event::WindowEvent {
+          event: WindowEvent::CloseRequested,
+          window_id,
+          ..
+        } if window_id == window.id() => *control_flow = ControlFlow::Exit,
+        _ => (),
+      }
+    });
+
+    if let PumpStatus::Exit(exit_reason) = status {
+      println!("Exited: {:?}", exit_reason);
+      break;
+    }
+
+    // Sleep for 1/60 second to simulate the rest of the application's frame
+    println!("rendering");
+    sleep(Duration::from_millis(16));
+  }
+}
+
+#[cfg(target_os = "ios")]
+fn main() {
+  println!("This platform doesn't support pump_events.");
+}
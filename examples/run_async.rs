@@ -0,0 +1,87 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{Arc, Mutex},
+  task::{Context, Poll, Waker},
+  thread,
+  time::Duration,
+};
+
+use tao::{
+  event::{Event, WindowEvent},
+  event_loop::{ControlFlow, EventLoop},
+  platform::run_async::EventLoopExtAsync,
+  window::WindowBuilder,
+};
+
+/// A future completing after `duration`, standing in for the timers of an async runtime.
+struct Delay {
+  state: Arc<Mutex<(bool, Option<Waker>)>>,
+}
+
+impl Delay {
+  fn new(duration: Duration) -> Self {
+    let state = Arc::new(Mutex::new((false, None::<Waker>)));
+    let thread_state = state.clone();
+    thread::spawn(move || {
+      thread::sleep(duration);
+      let mut state = thread_state.lock().unwrap();
+      state.0 = true;
+      if let Some(waker) = state.1.take() {
+        waker.wake();
+      }
+    });
+    Delay { state }
+  }
+}
+
+impl Future for Delay {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+    let mut state = self.state.lock().unwrap();
+    if state.0 {
+      Poll::Ready(())
+    } else {
+      state.1 = Some(cx.waker().clone());
+      Poll::Pending
+    }
+  }
+}
+
+#[allow(clippy::single_match)]
+fn main() {
+  env_logger::init();
+  let event_loop = EventLoop::new();
+
+  let window = WindowBuilder::new()
+    .with_title("A fantastic window!")
+    .build(&event_loop)
+    .unwrap();
+
+  let window = Arc::new(window);
+  let async_window = window.clone();
+  let countdown = async move {
+    for remaining in (1..=10).rev() {
+      async_window.set_title(&format!("Closing in {} seconds", remaining));
+      Delay::new(Duration::from_secs(1)).await;
+    }
+  };
+
+  event_loop.run_async(countdown, move |event, _, control_flow| {
+    *control_flow = ControlFlow::Wait;
+
+    match event {
+      Event::WindowEvent {
+        event: WindowEvent::CloseRequested,
+        window_id,
+        ..
+      } if window_id == window.id() => *control_flow = ControlFlow::Exit,
+      _ => (),
+    }
+  });
+}
@@ -0,0 +1,45 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+// Runs the checks of the `conformance` feature on the current desktop and prints their outcomes,
+// exiting with an error if any of them failed.
+//
+// Run with `cargo run --features conformance --example tao-conformance [timeout in ms]`.
+
+#[cfg(any(
+  target_os = "windows",
+  target_os = "macos",
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+fn main() {
+  use std::{env, process, time::Duration};
+
+  use tao::{conformance, event_loop::EventLoop};
+
+  let timeout = match env::args().nth(1).map(|timeout| timeout.parse()) {
+    Some(Ok(timeout)) => Duration::from_millis(timeout),
+    Some(Err(_)) => {
+      eprintln!("usage: tao-conformance [timeout in ms]");
+      process::exit(2);
+    }
+    None => Duration::from_secs(2),
+  };
+
+  env_logger::init();
+  let mut event_loop = EventLoop::new();
+  let report = conformance::run(&mut event_loop, timeout);
+  print!("{}", report);
+  if !report.passed() {
+    process::exit(1);
+  }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+fn main() {
+  println!("This platform doesn't support the conformance checks.");
+}
@@ -0,0 +1,44 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+// Replays an event log recorded with the `event-recorder` feature, printing every event at the
+// time it was originally dispatched.
+//
+// Record with `TAO_EVENT_LOG=events.log cargo run --features event-recorder --example <example>`,
+// then replay with `cargo run --features event-recorder --example tao-replay events.log`.
+
+use std::{env, process};
+
+use tao::event_recorder::Replay;
+
+fn main() {
+  let path = match env::args_os().nth(1) {
+    Some(path) => path,
+    None => {
+      eprintln!("usage: tao-replay <event log>");
+      process::exit(2);
+    }
+  };
+
+  let result = Replay::open(&path).and_then(|replay| {
+    replay.run(|recorded| match recorded.window {
+      Some(window) => println!(
+        "[{:>10.6}s] window #{}: {:?}",
+        recorded.time.as_secs_f64(),
+        window,
+        recorded.event
+      ),
+      None => println!(
+        "[{:>10.6}s] {:?}",
+        recorded.time.as_secs_f64(),
+        recorded.event
+      ),
+    })
+  });
+
+  if let Err(e) = result {
+    eprintln!("failed to replay {:?}: {}", path, e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,68 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+#[allow(clippy::single_match)]
+fn main() {
+  env_logger::init();
+  use tao::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+  };
+
+  struct Tick(u32);
+
+  struct Download {
+    progress: f32,
+  }
+
+  let event_loop = EventLoop::new();
+
+  let _window = WindowBuilder::new()
+    .with_title("A fantastic window!")
+    .build(&event_loop)
+    .unwrap();
+
+  // Each subsystem gets a proxy for its own event type, without a shared user event enum.
+  let timer_proxy = event_loop.create_typed_proxy::<Tick>();
+  let download_proxy = event_loop.create_typed_proxy::<Download>();
+
+  std::thread::spawn(move || {
+    for tick in 0.. {
+      std::thread::sleep(std::time::Duration::from_secs(1));
+      if timer_proxy.send_event(Tick(tick)).is_err() {
+        break;
+      }
+    }
+  });
+
+  std::thread::spawn(move || {
+    for step in 1..=10 {
+      std::thread::sleep(std::time::Duration::from_millis(700));
+      let progress = step as f32 / 10.0;
+      if download_proxy.send_event(Download { progress }).is_err() {
+        break;
+      }
+    }
+  });
+
+  event_loop.run(move |event, _, control_flow| {
+    *control_flow = ControlFlow::Wait;
+
+    match event {
+      Event::TypedUserEvent(event) => match event.downcast::<Tick>() {
+        Ok(tick) => println!("timer: tick {}", tick.0),
+        Err(event) => match event.downcast::<Download>() {
+          Ok(download) => println!("download: {:.0}%", download.progress * 100.0),
+          Err(event) => println!("unknown event of type {}", event.type_name()),
+        },
+      },
+      Event::WindowEvent {
+        event: WindowEvent::CloseRequested,
+        ..
+      } => *control_flow = ControlFlow::Exit,
+      _ => (),
+    }
+  });
+}
@@ -0,0 +1,361 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks of the behavior of a backend on a live desktop, enabled by the `conformance` feature.
+//!
+//! [`run`] creates windows and puts each of them through a sequence of operations, like resizing
+//! it or making it fullscreen, asserting that its state and the events it receives follow. It
+//! pumps the event loop with [`EventLoopExtPumpEvents::pump_events`] while waiting for the
+//! platform to apply an operation, so it can be run from an application's `main`, or with the
+//! `tao-conformance` example:
+//!
+//! ```sh
+//! cargo run --features conformance --example tao-conformance
+//! ```
+//!
+//! The checks need a display, and a window manager which lets applications move and resize their
+//! windows, which tiling window managers don't. A check is skipped rather than failed when the
+//! platform documents the operation as unsupported, e.g. moving windows on Wayland.
+//!
+//! [`EventLoopExtPumpEvents::pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+
+use std::{
+  collections::HashMap,
+  fmt,
+  time::{Duration, Instant},
+};
+
+use crate::{
+  dpi::{LogicalPosition, LogicalSize, PhysicalSize},
+  event::{Event, WindowEvent},
+  event_loop::{ControlFlow, EventLoop},
+  platform::pump_events::EventLoopExtPumpEvents,
+  window::{Fullscreen, Window, WindowBuilder, WindowId},
+};
+
+/// Time between two checks of the window state while waiting for the platform.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Outcome of a check.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+  Passed,
+  /// The check failed, with what was expected.
+  Failed(String),
+  /// The check didn't run, with why.
+  Skipped(String),
+}
+
+/// Outcome of one of the checks of [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+  pub name: &'static str,
+  pub outcome: Outcome,
+}
+
+/// Outcomes of all the checks of [`run`], printed one per line by its `Display` implementation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+  pub checks: Vec<CheckResult>,
+}
+
+impl Report {
+  /// Returns whether no check failed.
+  pub fn passed(&self) -> bool {
+    self
+      .checks
+      .iter()
+      .all(|check| !matches!(check.outcome, Outcome::Failed(_)))
+  }
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for check in &self.checks {
+      match &check.outcome {
+        Outcome::Passed => writeln!(f, "ok       {}", check.name)?,
+        Outcome::Failed(reason) => writeln!(f, "FAILED   {}: {}", check.name, reason)?,
+        Outcome::Skipped(reason) => writeln!(f, "skipped  {}: {}", check.name, reason)?,
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Runs every check with `event_loop`, giving the platform up to `timeout` to apply each
+/// operation.
+///
+/// Windows are created and dropped along the way, and the events they receive aren't passed on.
+/// The event loop shouldn't have exited, and `event_loop` can be used normally afterwards.
+pub fn run<T>(event_loop: &mut EventLoop<T>, timeout: Duration) -> Report {
+  let checks: [(&'static str, fn(&mut Harness<'_, T>) -> Outcome); 7] = [
+    ("create", check_create),
+    ("resize", check_resize),
+    ("move", check_move),
+    ("minimize", check_minimize),
+    ("maximize", check_maximize),
+    ("fullscreen", check_fullscreen),
+    ("scale factor", check_scale_factor),
+  ];
+
+  let mut harness = Harness {
+    event_loop,
+    timeout,
+    sizes: HashMap::new(),
+    scale_factors: HashMap::new(),
+  };
+  let mut report = Report::default();
+  for (name, check) in checks {
+    debug!("Running conformance check {}", name);
+    report.checks.push(CheckResult {
+      name,
+      outcome: check(&mut harness),
+    });
+  }
+  report
+}
+
+struct Harness<'a, T: 'static> {
+  event_loop: &'a mut EventLoop<T>,
+  timeout: Duration,
+  /// Last size each window was resized to.
+  sizes: HashMap<WindowId, PhysicalSize<u32>>,
+  /// Last scale factor each window changed to.
+  scale_factors: HashMap<WindowId, f64>,
+}
+
+impl<T> Harness<'_, T> {
+  fn create_window(&mut self) -> Result<Window, Outcome> {
+    let window = WindowBuilder::new()
+      .with_title("tao conformance")
+      .with_inner_size(LogicalSize::new(320.0, 240.0))
+      .build(self.event_loop)
+      .map_err(|e| Outcome::Failed(format!("failed to create a window: {}", e)))?;
+    if self.settle(|_| window.is_visible()) {
+      Ok(window)
+    } else {
+      Err(Outcome::Failed("the window wasn't shown".into()))
+    }
+  }
+
+  /// Pumps events until `condition` holds, returning whether it did before the timeout.
+  fn settle(&mut self, mut condition: impl FnMut(&Self) -> bool) -> bool {
+    let deadline = Instant::now() + self.timeout;
+    loop {
+      if condition(self) {
+        return true;
+      }
+      if Instant::now() >= deadline {
+        return false;
+      }
+      self.pump();
+    }
+  }
+
+  fn pump(&mut self) {
+    let sizes = &mut self.sizes;
+    let scale_factors = &mut self.scale_factors;
+    self
+      .event_loop
+      .pump_events(Some(POLL_INTERVAL), |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+          Event::WindowEvent {
+            window_id,
+            event: WindowEvent::Resized(size),
+            ..
+          } => {
+            sizes.insert(window_id, size);
+          }
+          Event::WindowEvent {
+            window_id,
+            event:
+              WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+              },
+            ..
+          } => {
+            scale_factors.insert(window_id, scale_factor);
+            sizes.insert(window_id, *new_inner_size);
+          }
+          _ => (),
+        }
+      });
+  }
+}
+
+fn check_create<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  let expected = LogicalSize::new(320.0, 240.0).to_physical::<u32>(window.scale_factor());
+  if harness.settle(|_| near(window.inner_size(), expected)) {
+    Outcome::Passed
+  } else {
+    Outcome::Failed(format!(
+      "created with an inner size of {:?}, expected {:?}",
+      window.inner_size(),
+      expected
+    ))
+  }
+}
+
+fn check_resize<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  window.set_inner_size(LogicalSize::new(400.0, 300.0));
+  let expected = LogicalSize::new(400.0, 300.0).to_physical::<u32>(window.scale_factor());
+  if !harness.settle(|_| near(window.inner_size(), expected)) {
+    return Outcome::Failed(format!(
+      "resized to an inner size of {:?}, expected {:?}",
+      window.inner_size(),
+      expected
+    ));
+  }
+  let id = window.id();
+  if harness.settle(|harness| harness.sizes.get(&id) == Some(&window.inner_size())) {
+    Outcome::Passed
+  } else {
+    Outcome::Failed(format!(
+      "last `Resized` event was {:?}, the inner size is {:?}",
+      harness.sizes.get(&id),
+      window.inner_size()
+    ))
+  }
+}
+
+fn check_move<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  if window.outer_position().is_err() {
+    return Outcome::Skipped("window positions aren't supported".into());
+  }
+  window.set_outer_position(LogicalPosition::new(100.0, 100.0));
+  let expected = LogicalPosition::new(100.0, 100.0).to_physical::<i32>(window.scale_factor());
+  let moved = harness.settle(|_| {
+    window.outer_position().map_or(false, |position| {
+      (position.x - expected.x).abs() <= 1 && (position.y - expected.y).abs() <= 1
+    })
+  });
+  if moved {
+    Outcome::Passed
+  } else {
+    Outcome::Failed(format!(
+      "moved to {:?}, expected {:?}",
+      window.outer_position(),
+      expected
+    ))
+  }
+}
+
+fn check_minimize<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  window.set_minimized(true);
+  if !harness.settle(|_| window.is_minimized()) {
+    return Outcome::Failed("`is_minimized` is false after minimizing".into());
+  }
+  window.set_minimized(false);
+  if harness.settle(|_| !window.is_minimized()) {
+    Outcome::Passed
+  } else {
+    Outcome::Failed("`is_minimized` is true after restoring".into())
+  }
+}
+
+fn check_maximize<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  window.set_maximized(true);
+  if !harness.settle(|_| window.is_maximized()) {
+    return Outcome::Failed("`is_maximized` is false after maximizing".into());
+  }
+  window.set_maximized(false);
+  if harness.settle(|_| !window.is_maximized()) {
+    Outcome::Passed
+  } else {
+    Outcome::Failed("`is_maximized` is true after restoring".into())
+  }
+}
+
+fn check_fullscreen<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  let monitor = match window.current_monitor() {
+    Some(monitor) => monitor,
+    None => return Outcome::Skipped("the window isn't on any monitor".into()),
+  };
+  window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+  let covers_monitor =
+    harness.settle(|_| window.fullscreen().is_some() && near(window.outer_size(), monitor.size()));
+  if !covers_monitor {
+    return Outcome::Failed(format!(
+      "fullscreen window is {:?}, the monitor is {:?}",
+      window.outer_size(),
+      monitor.size()
+    ));
+  }
+  window.set_fullscreen(None);
+  if harness.settle(|_| window.fullscreen().is_none()) {
+    Outcome::Passed
+  } else {
+    Outcome::Failed("`fullscreen` is still set after leaving fullscreen".into())
+  }
+}
+
+fn check_scale_factor<T>(harness: &mut Harness<'_, T>) -> Outcome {
+  let window = match harness.create_window() {
+    Ok(window) => window,
+    Err(outcome) => return outcome,
+  };
+  let scale_factor = window.scale_factor();
+  if !scale_factor.is_finite() || scale_factor <= 0.0 {
+    return Outcome::Failed(format!("invalid scale factor {}", scale_factor));
+  }
+  if let Some(monitor) = window.current_monitor() {
+    if monitor.scale_factor() != scale_factor {
+      return Outcome::Failed(format!(
+        "scale factor is {}, its monitor's is {}",
+        scale_factor,
+        monitor.scale_factor()
+      ));
+    }
+  }
+  // Scale factor changes can't be triggered, but the ones received along the way must match.
+  for (id, changed_to) in &harness.scale_factors {
+    if !changed_to.is_finite() || *changed_to <= 0.0 {
+      return Outcome::Failed(format!(
+        "`ScaleFactorChanged` to {} for {:?}",
+        changed_to, id
+      ));
+    }
+  }
+  let id = window.id();
+  match harness.scale_factors.get(&id) {
+    Some(changed_to) if *changed_to != scale_factor => Outcome::Failed(format!(
+      "scale factor is {}, the last `ScaleFactorChanged` was to {}",
+      scale_factor, changed_to
+    )),
+    _ => Outcome::Passed,
+  }
+}
+
+/// Sizes are rounded from logical units, allow them to be a pixel off.
+fn near(size: PhysicalSize<u32>, expected: PhysicalSize<u32>) -> bool {
+  let near = |a: u32, b: u32| a.max(b) - a.min(b) <= 1;
+  near(size.width, expected.width) && near(size.height, expected.height)
+}
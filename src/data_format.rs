@@ -0,0 +1,48 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Custom data formats, for moving an application's own data through the clipboard or drag and
+//! drop.
+//!
+//! A [`DataFormat`] is identified by a MIME type, and registered with the platform under the
+//! identifier its clipboard and drag and drop APIs take, see the `DataFormatExt*` traits of
+//! [`platform`](crate::platform). `tao` doesn't read or write the clipboard itself, the
+//! identifiers are meant for those APIs, or the crates wrapping them.
+//!
+//! Registering the same MIME type gives the same identifier in every process, so the data can
+//! be moved between the windows of an application as well as between its instances.
+
+use crate::{error::OsError, platform_impl};
+
+/// A custom data format, see the [module-level docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat {
+  mime_type: String,
+  pub(crate) inner: platform_impl::DataFormat,
+}
+
+impl DataFormat {
+  /// Registers the format of `mime_type` with the platform, e.g.
+  /// `application/x-myapp-items`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Registers a clipboard format named `mime_type`.
+  /// - **macOS:** Creates the dynamic UTI of `mime_type`. Declare it in the `Info.plist` of the
+  ///   application with `UTExportedTypeDeclarations` for other applications to recognize it.
+  /// - **Linux:** The format is the atom named `mime_type`, the name of drag and drop and
+  ///   selection targets.
+  /// - **iOS / Android:** Only the MIME type is available.
+  pub fn register(mime_type: &str) -> Result<DataFormat, OsError> {
+    Ok(DataFormat {
+      mime_type: mime_type.to_owned(),
+      inner: platform_impl::DataFormat::register(mime_type)?,
+    })
+  }
+
+  /// Returns the MIME type the format was registered with.
+  pub fn mime_type(&self) -> &str {
+    &self.mime_type
+  }
+}
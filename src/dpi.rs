@@ -54,6 +54,12 @@
 //! rounding properly. Note that precision loss will still occur when rounding from a float to an
 //! int, although rounding lessens the problem.
 //!
+//! Conversions between logical and physical coordinates round to the nearest integer by default.
+//! Where that isn't what's needed, e.g. to make sure a logical size covers at least a given number
+//! of physical pixels, the `*_with` variants take an explicit [`Rounding`] policy instead. Values
+//! within a rounding error of an integer are snapped to it first, so `Rounding::Floor` and
+//! `Rounding::Ceil` don't drift by a pixel on a round trip at fractional scale factors.
+//!
 //! ### Events
 //!
 //! Tao will dispatch a [`ScaleFactorChanged`](crate::event::WindowEvent::ScaleFactorChanged)
@@ -91,11 +97,51 @@
 //! [apple_2]: https://developer.apple.com/design/human-interface-guidelines/macos/icons-and-images/image-size-and-resolution/
 //! [android_1]: https://developer.android.com/training/multiscreen/screendensities
 
+/// How a fractional value is turned into an integer one by the conversions of this module.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rounding {
+  /// Rounds to the nearest integer, half away from zero. This is the default.
+  #[default]
+  Round,
+  /// Rounds towards negative infinity.
+  Floor,
+  /// Rounds towards positive infinity.
+  Ceil,
+}
+
+impl Rounding {
+  /// Values this close to an integer are considered to be that integer.
+  const EPSILON: f64 = 1e-6;
+
+  /// Rounds `f` to an integer according to this policy.
+  pub fn apply(self, f: f64) -> f64 {
+    let nearest = f.round();
+    if (f - nearest).abs() < Self::EPSILON {
+      return nearest;
+    }
+    match self {
+      Rounding::Round => nearest,
+      Rounding::Floor => f.floor(),
+      Rounding::Ceil => f.ceil(),
+    }
+  }
+}
+
 pub trait Pixel: Copy + Into<f64> {
   fn from_f64(f: f64) -> Self;
+  /// Like `from_f64`, rounding with `rounding` if `Self` is an integer type.
+  fn from_f64_with(f: f64, _rounding: Rounding) -> Self {
+    Self::from_f64(f)
+  }
   fn cast<P: Pixel>(self) -> P {
     P::from_f64(self.into())
   }
+  /// Like `cast`, rounding with `rounding` if `P` is an integer type.
+  fn cast_with<P: Pixel>(self, rounding: Rounding) -> P {
+    P::from_f64_with(self.into(), rounding)
+  }
 }
 
 macro_rules! pixel_int_impl {
@@ -104,6 +150,10 @@ macro_rules! pixel_int_impl {
           fn from_f64(f: f64) -> Self {
               f.round() as $t
           }
+
+          fn from_f64_with(f: f64, rounding: Rounding) -> Self {
+              rounding.apply(f) as $t
+          }
       }
   )*}
 }
@@ -206,9 +256,19 @@ macro_rules! dpi_type {
 
           #[inline]
           pub fn to_physical<X: Pixel>(&self, scale_factor: f64) -> $PhysicalType<X> {
+              self.to_physical_with(scale_factor, Rounding::Round)
+          }
+
+          /// Like `to_physical`, rounding with `rounding` if `X` is an integer type.
+          #[inline]
+          pub fn to_physical_with<X: Pixel>(
+              &self,
+              scale_factor: f64,
+              rounding: Rounding,
+          ) -> $PhysicalType<X> {
               assert!(validate_scale_factor(scale_factor));
               $(let $a = self.$a.into() * scale_factor;)*
-              $PhysicalType::new($($a,)*).cast()
+              $PhysicalType::new($($a,)*).cast_with(rounding)
           }
 
           #[inline]
@@ -217,6 +277,14 @@ macro_rules! dpi_type {
                   $($a: self.$a.cast(),)*
               }
           }
+
+          /// Like `cast`, rounding with `rounding` if `X` is an integer type.
+          #[inline]
+          pub fn cast_with<X: Pixel>(&self, rounding: Rounding) -> $LogicalType<X> {
+              $LogicalType {
+                  $($a: self.$a.cast_with(rounding),)*
+              }
+          }
       }
 
       from_impls!($LogicalType, $($a,)*);
@@ -246,9 +314,19 @@ macro_rules! dpi_type {
 
           #[inline]
           pub fn to_logical<X: Pixel>(&self, scale_factor: f64) -> $LogicalType<X> {
+              self.to_logical_with(scale_factor, Rounding::Round)
+          }
+
+          /// Like `to_logical`, rounding with `rounding` if `X` is an integer type.
+          #[inline]
+          pub fn to_logical_with<X: Pixel>(
+              &self,
+              scale_factor: f64,
+              rounding: Rounding,
+          ) -> $LogicalType<X> {
               assert!(validate_scale_factor(scale_factor));
               $(let $a = self.$a.into() / scale_factor;)*
-              $LogicalType::new($($a,)*).cast()
+              $LogicalType::new($($a,)*).cast_with(rounding)
           }
 
           #[inline]
@@ -257,6 +335,14 @@ macro_rules! dpi_type {
                   $($a: self.$a.cast(),)*
               }
           }
+
+          /// Like `cast`, rounding with `rounding` if `X` is an integer type.
+          #[inline]
+          pub fn cast_with<X: Pixel>(&self, rounding: Rounding) -> $PhysicalType<X> {
+              $PhysicalType {
+                  $($a: self.$a.cast_with(rounding),)*
+              }
+          }
       }
 
       from_impls!($PhysicalType, $($a,)*);
@@ -275,16 +361,34 @@ macro_rules! dpi_type {
           }
 
           pub fn to_logical<P: Pixel>(&self, scale_factor: f64) -> $LogicalType<P> {
+              self.to_logical_with(scale_factor, Rounding::Round)
+          }
+
+          /// Like `to_logical`, rounding with `rounding` if `P` is an integer type.
+          pub fn to_logical_with<P: Pixel>(
+              &self,
+              scale_factor: f64,
+              rounding: Rounding,
+          ) -> $LogicalType<P> {
               match *self {
-                  $UnifiedType::Physical(val) => val.to_logical(scale_factor),
-                  $UnifiedType::Logical(val) => val.cast(),
+                  $UnifiedType::Physical(val) => val.to_logical_with(scale_factor, rounding),
+                  $UnifiedType::Logical(val) => val.cast_with(rounding),
               }
           }
 
           pub fn to_physical<P: Pixel>(&self, scale_factor: f64) -> $PhysicalType<P> {
+              self.to_physical_with(scale_factor, Rounding::Round)
+          }
+
+          /// Like `to_physical`, rounding with `rounding` if `P` is an integer type.
+          pub fn to_physical_with<P: Pixel>(
+              &self,
+              scale_factor: f64,
+              rounding: Rounding,
+          ) -> $PhysicalType<P> {
               match *self {
-                  $UnifiedType::Physical(val) => val.cast(),
-                  $UnifiedType::Logical(val) => val.to_physical(scale_factor),
+                  $UnifiedType::Physical(val) => val.cast_with(rounding),
+                  $UnifiedType::Logical(val) => val.to_physical_with(scale_factor, rounding),
               }
           }
 
@@ -397,3 +501,38 @@ impl Size {
     PhysicalSize::new(width, height).into()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rounding_snaps_to_close_integers() {
+    // 0.7 / 0.1 is 6.999999999999999.
+    assert_eq!(Rounding::Floor.apply(0.7 / 0.1), 7.0);
+    assert_eq!(Rounding::Ceil.apply(7.0 + 1e-9), 7.0);
+    assert_eq!(Rounding::Round.apply(7.0 - 1e-9), 7.0);
+
+    assert_eq!(Rounding::Floor.apply(2.5), 2.0);
+    assert_eq!(Rounding::Ceil.apply(2.5), 3.0);
+    assert_eq!(Rounding::Round.apply(2.5), 3.0);
+    assert_eq!(Rounding::Floor.apply(-2.5), -3.0);
+    assert_eq!(Rounding::Ceil.apply(-2.5), -2.0);
+  }
+
+  #[test]
+  fn floor_and_ceil_round_trip_at_fractional_scale_factor() {
+    let scale_factor = 1.5;
+    for n in 0..1000u32 {
+      let size = LogicalSize::new(n, n);
+      let ceiled = size
+        .to_physical_with::<u32>(scale_factor, Rounding::Ceil)
+        .to_logical_with::<u32>(scale_factor, Rounding::Floor);
+      assert_eq!(ceiled, size);
+      let floored = size
+        .to_physical_with::<u32>(scale_factor, Rounding::Floor)
+        .to_logical_with::<u32>(scale_factor, Rounding::Ceil);
+      assert_eq!(floored, size);
+    }
+  }
+}
@@ -38,15 +38,27 @@
 //!
 //! [event_loop_run]: crate::event_loop::EventLoop::run
 use instant::Instant;
-use std::path::PathBuf;
+use std::{
+  any::{self, Any, TypeId},
+  fmt,
+  path::PathBuf,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  keyboard::{self, ModifiersState},
+  event_loop::TimerId,
+  global_shortcut::{AcceleratorId, GlobalShortcutId},
+  keyboard::{self, KeyboardLayout, ModifiersState},
+  monitor::MonitorHandle,
   platform_impl,
-  window::{Theme, WindowId},
+  window::{AttributesReport, Fullscreen, SystemAppearance, Theme, WindowId},
 };
 
+#[cfg(feature = "notifications")]
+use crate::notification::NotificationId;
+
 /// Describes a generic event.
 ///
 /// See the module-level docs for more information on the event loop manages each event.
@@ -78,10 +90,32 @@ pub enum Event<'a, T: 'static> {
   /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
   UserEvent(T),
 
+  /// Emitted when an event is sent from a proxy created by
+  /// [`EventLoop::create_typed_proxy`](crate::event_loop::EventLoop::create_typed_proxy).
+  TypedUserEvent(TypedUserEvent),
+
+  /// Emitted when a timer started with
+  /// [`EventLoopWindowTarget::set_timer`](crate::event_loop::EventLoopWindowTarget::set_timer)
+  /// fires.
+  Timer(TimerId),
+
   /// Emitted when the application has been suspended.
+  ///
+  /// Renderers should release their swapchains until `Resumed`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux:** Emitted when the system goes to sleep or the user session is
+  ///   locked, or switched away from on macOS.
+  /// - **Linux:** Needs logind, through D-Bus.
   Suspended,
 
   /// Emitted when the application has been resumed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux:** Emitted once the system woke up and the user session is
+  ///   unlocked.
   Resumed,
 
   /// Emitted when all of the event loop's input events have been processed and redraw processing
@@ -120,6 +154,18 @@ pub enum Event<'a, T: 'static> {
   /// [`WidgetExt`]: https://gtk-rs.org/gtk3-rs/stable/latest/docs/gtk/prelude/trait.WidgetExt.html
   RedrawRequested(WindowId),
 
+  /// Emitted when frames of a window were presented later than the display refresh they were
+  /// drawn for, with the number of refreshes missed and how long they lasted.
+  ///
+  /// Only emitted for windows with [`Window::set_jank_detection`] enabled.
+  ///
+  /// [`Window::set_jank_detection`]: crate::window::Window::set_jank_detection
+  FrameMissed {
+    window_id: WindowId,
+    count: u32,
+    duration: Duration,
+  },
+
   /// Emitted after all `RedrawRequested` events have been processed and control flow is about to
   /// be taken away from the program. If there are no `RedrawRequested` events, it is emitted
   /// immediately after `MainEventsCleared`.
@@ -132,10 +178,168 @@ pub enum Event<'a, T: 'static> {
   ///
   /// This is irreversable - if this event is emitted, it is guaranteed to be the last event that
   /// gets emitted. You generally want to treat this as an "do on quit" event.
+  ///
+  /// Once the handler returns, it is dropped before
+  /// [`EventLoop::run`](crate::event_loop::EventLoop::run) exits the process, so the values it
+  /// owns, like GPU contexts or open files, are dropped too.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux:** Comes after the `WindowEvent::Destroyed` of every window.
+  /// - **iOS:** Only emitted when the system terminates the application.
   LoopDestroyed,
 
   /// Emitted when the app is open by external resources, like opening a file or deeplink.
   Opened { urls: Vec<url::Url> },
+
+  /// Emitted when the application's dock icon is hidden or shown because of
+  /// `WindowExtMacOS::set_skip_taskbar`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / Android / iOS:** Unsupported.
+  DockIconVisibilityChanged { visible: bool },
+
+  /// Emitted once, after `NewEvents(StartCause::Init)`, when the application was launched from an
+  /// entry of its `JumpList`, with the arguments of that entry.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / Android / iOS:** Unsupported.
+  JumpListItemActivated { arguments: Vec<String> },
+
+  /// Emitted before an event carrying a malformed input from the platform, which was clamped, or
+  /// in its place if it was dropped.
+  ///
+  /// Only emitted when the `harden` feature is enabled.
+  Anomaly(AnomalyReport),
+
+  /// Emitted when a monitor was connected.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported.
+  MonitorConnected(MonitorHandle),
+
+  /// Emitted when a monitor was disconnected.
+  ///
+  /// The monitor doesn't exist anymore, so its handle is only good for comparing with the ones
+  /// kept from before.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported.
+  MonitorDisconnected(MonitorHandle),
+
+  /// Emitted when the resolution, scale factor or arrangement of the monitors changed, or
+  /// monitors were connected or disconnected. Windows should query their monitor again.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported.
+  MonitorConfigurationChanged,
+
+  /// Emitted when the refresh rate of a monitor changed, with the new one in millihertz, e.g.
+  /// because the user chose another display mode or the system lowered it to save power.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only changes of the display mode are reported, not the dynamic refresh rate
+  ///   of Windows 11.
+  /// - **Linux(Wayland):** Only emitted if the compositor sends a new mode for the output.
+  /// - **Android / iOS:** Unsupported.
+  MonitorRefreshRateChanged {
+    monitor: MonitorHandle,
+    refresh_rate_millihertz: u32,
+  },
+
+  /// Emitted when the ICC profile of a monitor changed, see [`MonitorHandle::icc_profile`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only checked when the display settings or the system settings change, so a
+  ///   profile changed from the color management control panel may be reported late.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported.
+  MonitorIccProfileChanged(MonitorHandle),
+
+  /// Emitted when the power state of the system changed, e.g. so that the application can pause
+  /// its work before the system sleeps and reconnect its sockets after.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Only `Suspending` and `Resuming`.
+  /// - **Linux:** Needs logind for `Suspending` and `Resuming`, and UPower for the others, through
+  ///   D-Bus.
+  /// - **Android / iOS:** Unsupported.
+  PowerEvent(PowerEvent),
+
+  /// Emitted when the appearance preferences of the system changed, with the new ones.
+  ///
+  /// [`WindowEvent::ThemeChanged`] is still emitted to the windows when switching between the light
+  /// and the dark themes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  SystemAppearanceChanged(SystemAppearance),
+
+  /// Emitted when the locale of the user changed, with its new BCP 47 language tag, e.g.
+  /// `"en-US"`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The locale of the regional format settings, the display language only changes
+  ///   on the next sign in.
+  /// - **Linux:** The `LANG` of the system locale, through the `org.freedesktop.locale1` D-Bus
+  ///   service.
+  /// - **Android / iOS:** Unsupported.
+  LocaleChanged { locale: String },
+
+  /// Emitted when the active keyboard input language changed, with the identifier
+  /// [`KeyEvent::input_locale`] reports for it, e.g. to switch the spellcheck dictionary or the
+  /// shortcut hints.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Emitted before the first key press in the new layout group, as GTK doesn't report
+  ///   switching the group.
+  /// - **Android / iOS:** Unsupported.
+  InputLocaleChanged { input_locale: String },
+
+  /// Emitted when the system clock was set or the time zone changed, so that the displayed and
+  /// scheduled times should be computed again.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Only emitted when the time zone changed, through the `org.freedesktop.timedate1`
+  ///   D-Bus service.
+  /// - **Android / iOS:** Unsupported.
+  SystemTimeChanged,
+
+  /// Emitted when a shortcut registered with
+  /// [`GlobalShortcutManager::register`](crate::global_shortcut::GlobalShortcutManager::register)
+  /// is pressed, whichever application is focused.
+  GlobalShortcut(GlobalShortcutId),
+
+  /// Emitted when the user clicks a notification shown with
+  /// [`Notifier::show`](crate::notification::Notifier::show). `window_id` is the window the
+  /// notification was shown for, which the application usually focuses.
+  #[cfg(feature = "notifications")]
+  NotificationActivated {
+    id: NotificationId,
+    window_id: Option<WindowId>,
+  },
+
+  /// Emitted when the user switched to another keyboard layout, so that labels derived from
+  /// [`EventLoopWindowTarget::key_for_code`](crate::event_loop::EventLoopWindowTarget::key_for_code)
+  /// should be computed again.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Emitted for each window, when it's focused.
+  /// - **Linux:** Only emitted on the first key press in the new layout.
+  /// - **Android / iOS:** Unsupported.
+  KeyboardLayoutChanged(KeyboardLayout),
 }
 
 impl<T: Clone> Clone for Event<'static, T> {
@@ -147,6 +351,8 @@ impl<T: Clone> Clone for Event<'static, T> {
         event: event.clone(),
       },
       UserEvent(event) => UserEvent(event.clone()),
+      TypedUserEvent(event) => TypedUserEvent(event.clone()),
+      Timer(id) => Timer(*id),
       DeviceEvent { device_id, event } => DeviceEvent {
         device_id: *device_id,
         event: event.clone(),
@@ -154,30 +360,106 @@ impl<T: Clone> Clone for Event<'static, T> {
       NewEvents(cause) => NewEvents(*cause),
       MainEventsCleared => MainEventsCleared,
       RedrawRequested(wid) => RedrawRequested(*wid),
+      FrameMissed {
+        window_id,
+        count,
+        duration,
+      } => FrameMissed {
+        window_id: *window_id,
+        count: *count,
+        duration: *duration,
+      },
       RedrawEventsCleared => RedrawEventsCleared,
       LoopDestroyed => LoopDestroyed,
       Suspended => Suspended,
       Resumed => Resumed,
       Opened { urls } => Opened { urls: urls.clone() },
+      DockIconVisibilityChanged { visible } => DockIconVisibilityChanged { visible: *visible },
+      JumpListItemActivated { arguments } => JumpListItemActivated {
+        arguments: arguments.clone(),
+      },
+      Anomaly(report) => Anomaly(report.clone()),
+      MonitorConnected(monitor) => MonitorConnected(monitor.clone()),
+      MonitorDisconnected(monitor) => MonitorDisconnected(monitor.clone()),
+      MonitorConfigurationChanged => MonitorConfigurationChanged,
+      MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      } => MonitorRefreshRateChanged {
+        monitor: monitor.clone(),
+        refresh_rate_millihertz: *refresh_rate_millihertz,
+      },
+      MonitorIccProfileChanged(monitor) => MonitorIccProfileChanged(monitor.clone()),
+      PowerEvent(event) => PowerEvent(*event),
+      SystemAppearanceChanged(appearance) => SystemAppearanceChanged(*appearance),
+      LocaleChanged { locale } => LocaleChanged {
+        locale: locale.clone(),
+      },
+      InputLocaleChanged { input_locale } => InputLocaleChanged {
+        input_locale: input_locale.clone(),
+      },
+      SystemTimeChanged => SystemTimeChanged,
+      GlobalShortcut(id) => GlobalShortcut(*id),
+      #[cfg(feature = "notifications")]
+      NotificationActivated { id, window_id } => NotificationActivated {
+        id: *id,
+        window_id: *window_id,
+      },
+      KeyboardLayoutChanged(layout) => KeyboardLayoutChanged(layout.clone()),
     }
   }
 }
 
 impl<'a, T> Event<'a, T> {
+  #[allow(clippy::result_large_err)]
   pub fn map_nonuser_event<U>(self) -> Result<Event<'a, U>, Event<'a, T>> {
     use self::Event::*;
     match self {
       UserEvent(_) => Err(self),
+      TypedUserEvent(event) => Ok(TypedUserEvent(event)),
+      Timer(id) => Ok(Timer(id)),
       WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
       DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
       NewEvents(cause) => Ok(NewEvents(cause)),
       MainEventsCleared => Ok(MainEventsCleared),
       RedrawRequested(wid) => Ok(RedrawRequested(wid)),
+      FrameMissed {
+        window_id,
+        count,
+        duration,
+      } => Ok(FrameMissed {
+        window_id,
+        count,
+        duration,
+      }),
       RedrawEventsCleared => Ok(RedrawEventsCleared),
       LoopDestroyed => Ok(LoopDestroyed),
       Suspended => Ok(Suspended),
       Resumed => Ok(Resumed),
       Opened { urls } => Ok(Opened { urls }),
+      DockIconVisibilityChanged { visible } => Ok(DockIconVisibilityChanged { visible }),
+      JumpListItemActivated { arguments } => Ok(JumpListItemActivated { arguments }),
+      Anomaly(report) => Ok(Anomaly(report)),
+      MonitorConnected(monitor) => Ok(MonitorConnected(monitor)),
+      MonitorDisconnected(monitor) => Ok(MonitorDisconnected(monitor)),
+      MonitorConfigurationChanged => Ok(MonitorConfigurationChanged),
+      MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      } => Ok(MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      }),
+      MonitorIccProfileChanged(monitor) => Ok(MonitorIccProfileChanged(monitor)),
+      PowerEvent(event) => Ok(PowerEvent(event)),
+      SystemAppearanceChanged(appearance) => Ok(SystemAppearanceChanged(appearance)),
+      LocaleChanged { locale } => Ok(LocaleChanged { locale }),
+      InputLocaleChanged { input_locale } => Ok(InputLocaleChanged { input_locale }),
+      SystemTimeChanged => Ok(SystemTimeChanged),
+      GlobalShortcut(id) => Ok(GlobalShortcut(id)),
+      #[cfg(feature = "notifications")]
+      NotificationActivated { id, window_id } => Ok(NotificationActivated { id, window_id }),
+      KeyboardLayoutChanged(layout) => Ok(KeyboardLayoutChanged(layout)),
     }
   }
 
@@ -190,17 +472,153 @@ impl<'a, T> Event<'a, T> {
         .to_static()
         .map(|event| WindowEvent { window_id, event }),
       UserEvent(event) => Some(UserEvent(event)),
+      TypedUserEvent(event) => Some(TypedUserEvent(event)),
+      Timer(id) => Some(Timer(id)),
       DeviceEvent { device_id, event } => Some(DeviceEvent { device_id, event }),
       NewEvents(cause) => Some(NewEvents(cause)),
       MainEventsCleared => Some(MainEventsCleared),
       RedrawRequested(wid) => Some(RedrawRequested(wid)),
+      FrameMissed {
+        window_id,
+        count,
+        duration,
+      } => Some(FrameMissed {
+        window_id,
+        count,
+        duration,
+      }),
       RedrawEventsCleared => Some(RedrawEventsCleared),
       LoopDestroyed => Some(LoopDestroyed),
       Suspended => Some(Suspended),
       Resumed => Some(Resumed),
       Opened { urls } => Some(Opened { urls }),
+      DockIconVisibilityChanged { visible } => Some(DockIconVisibilityChanged { visible }),
+      JumpListItemActivated { arguments } => Some(JumpListItemActivated { arguments }),
+      Anomaly(report) => Some(Anomaly(report)),
+      MonitorConnected(monitor) => Some(MonitorConnected(monitor)),
+      MonitorDisconnected(monitor) => Some(MonitorDisconnected(monitor)),
+      MonitorConfigurationChanged => Some(MonitorConfigurationChanged),
+      MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      } => Some(MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      }),
+      MonitorIccProfileChanged(monitor) => Some(MonitorIccProfileChanged(monitor)),
+      PowerEvent(event) => Some(PowerEvent(event)),
+      SystemAppearanceChanged(appearance) => Some(SystemAppearanceChanged(appearance)),
+      LocaleChanged { locale } => Some(LocaleChanged { locale }),
+      InputLocaleChanged { input_locale } => Some(InputLocaleChanged { input_locale }),
+      SystemTimeChanged => Some(SystemTimeChanged),
+      GlobalShortcut(id) => Some(GlobalShortcut(id)),
+      #[cfg(feature = "notifications")]
+      NotificationActivated { id, window_id } => Some(NotificationActivated { id, window_id }),
+      KeyboardLayoutChanged(layout) => Some(KeyboardLayoutChanged(layout)),
+    }
+  }
+}
+
+/// An event of any type sent from a proxy created by
+/// [`EventLoop::create_typed_proxy`](crate::event_loop::EventLoop::create_typed_proxy), see
+/// [`Event::TypedUserEvent`].
+///
+/// Clones share the event, which only one of them can take with [`TypedUserEvent::downcast`].
+#[derive(Clone)]
+pub struct TypedUserEvent {
+  type_id: TypeId,
+  type_name: &'static str,
+  event: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
+}
+
+impl TypedUserEvent {
+  pub(crate) fn new<T: Send + 'static>(event: T) -> Self {
+    Self {
+      type_id: TypeId::of::<T>(),
+      type_name: any::type_name::<T>(),
+      event: Arc::new(Mutex::new(Some(Box::new(event)))),
     }
   }
+
+  /// Returns whether the event is a `T`.
+  pub fn is<T: 'static>(&self) -> bool {
+    self.type_id == TypeId::of::<T>()
+  }
+
+  /// Returns the name of the type of the event, for diagnostics.
+  pub fn type_name(&self) -> &'static str {
+    self.type_name
+  }
+
+  /// Takes the event out if it is a `T`, otherwise returns `self` back. It's also returned back if
+  /// the event was taken from a clone already.
+  pub fn downcast<T: 'static>(self) -> Result<T, Self> {
+    if !self.is::<T>() {
+      return Err(self);
+    }
+    let event = self.event.lock().unwrap().take();
+    match event.map(|event| event.downcast::<T>()) {
+      Some(Ok(event)) => Ok(*event),
+      _ => Err(self),
+    }
+  }
+}
+
+impl fmt::Debug for TypedUserEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("TypedUserEvent")
+      .field(&self.type_name)
+      .finish()
+  }
+}
+
+impl PartialEq for TypedUserEvent {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.event, &other.event)
+  }
+}
+
+/// Describes a malformed input from the platform, see [`Event::Anomaly`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyReport {
+  /// The window the input was sent to, or `None` for device events.
+  pub window_id: Option<WindowId>,
+  pub kind: AnomalyKind,
+  /// Whether the event was dropped, rather than clamped and passed on.
+  pub dropped: bool,
+}
+
+/// What was wrong with an input, see [`AnomalyReport`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnomalyKind {
+  /// A size was negative, which shows as more than `i32::MAX` pixels. It is clamped to `0`.
+  NegativeSize,
+  /// A scale factor was NaN, infinite or not positive. The event is dropped.
+  InvalidScaleFactor(f64),
+  /// A position or delta was NaN or infinite, and the event is dropped. Pressures and forces are
+  /// cleared instead.
+  NonFiniteValue,
+  /// A touch moved or ended without having started. The event is dropped.
+  UnknownTouchId(u64),
+}
+
+/// Describes a change of the power state of the system, see [`Event::PowerEvent`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PowerEvent {
+  /// The system is about to go to sleep.
+  Suspending,
+  /// The system woke up from sleep.
+  Resuming,
+  /// The battery charge became low.
+  BatteryLow,
+  /// The lid was closed.
+  LidClosed,
+  /// The lid was opened.
+  LidOpened,
 }
 
 /// Describes the reason the event loop is resuming.
@@ -224,6 +642,19 @@ pub enum StartCause {
     requested_resume: Option<Instant>,
   },
 
+  /// Sent instead of `WaitCancelled` if the wait was cancelled by an event sent through an
+  /// `EventLoopProxy`. Contains the moment the wait was requested and the resume time, if
+  /// requested.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Unsupported, `WaitCancelled` is sent instead.
+  #[non_exhaustive]
+  UserEvent {
+    start: Instant,
+    requested_resume: Option<Instant>,
+  },
+
   /// Sent if the event loop is being resumed after the loop's control flow was set to
   /// `ControlFlow::Poll`.
   Poll,
@@ -249,6 +680,32 @@ pub enum WindowEvent<'a> {
   /// The window has been requested to close.
   CloseRequested,
 
+  /// The window has been requested to minimize by the user.
+  ///
+  /// This is only emitted when [`Window::set_intercept_minimize`] is enabled, in which case the
+  /// window is not minimized until [`Window::set_minimized`] is called.
+  ///
+  /// [`Window::set_intercept_minimize`]: crate::window::Window::set_intercept_minimize
+  /// [`Window::set_minimized`]: crate::window::Window::set_minimized
+  MinimizeRequested,
+
+  /// The user started peeking at the window by hovering its taskbar thumbnail.
+  ///
+  /// Apps can pause expensive rendering until [`WindowEvent::ThumbnailPeekEnded`] is received.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only emitted while a custom bitmap is set with [`WindowExtWindows::set_peek_bitmap`].
+  /// - **macOS / Linux / iOS / Android:** Unsupported.
+  ///
+  /// [`WindowExtWindows::set_peek_bitmap`]: crate::platform::windows::WindowExtWindows::set_peek_bitmap
+  ThumbnailPeekStarted,
+
+  /// The user stopped peeking at the window from its taskbar thumbnail.
+  ///
+  /// See [`WindowEvent::ThumbnailPeekStarted`] for details.
+  ThumbnailPeekEnded,
+
   /// The window has been destroyed.
   ///
   /// ## Platform-specific
@@ -261,12 +718,23 @@ pub enum WindowEvent<'a> {
   ///
   /// When the user drops multiple files at once, this event will be emitted for each file
   /// separately.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Virtual files, which only exist in the dragged data like the attachments
+  ///   dragged from Outlook, are saved in a new folder of the temporary directory first, and the
+  ///   application is responsible for removing them.
   DroppedFile(PathBuf),
 
   /// A file is being hovered over the window.
   ///
   /// When the user hovers multiple files at once, this event will be emitted for each file
   /// separately.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Virtual files, see [`WindowEvent::DroppedFile`], only have their name since
+  ///   they aren't saved yet.
   HoveredFile(PathBuf),
 
   /// A file was hovered, but has exited the window.
@@ -278,6 +746,16 @@ pub enum WindowEvent<'a> {
   /// The window received a unicode character.
   ReceivedImeText(String),
 
+  /// The IME of the window composed text.
+  ///
+  /// Text committed by the IME is also reported by `ReceivedImeText`, so applications which don't
+  /// show the composition can ignore this event.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  Ime(Ime),
+
   /// The window gained or lost focus.
   ///
   /// The parameter is true if the window has gained focus, and false if it has lost focus.
@@ -328,19 +806,37 @@ pub enum WindowEvent<'a> {
   CursorLeft { device_id: DeviceId },
 
   /// A mouse wheel movement or touchpad scroll occurred.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `phase` is always `Moved` and `momentum` always `false`. Scrolls of less than a
+  ///   notch of the wheel, which precision touchpads and free-spinning wheels report, are
+  ///   `PixelDelta`s from [`ScrollSource::Touchpad`].
+  /// - **Linux:** `momentum` is always `false`.
   MouseWheel {
     device_id: DeviceId,
     delta: MouseScrollDelta,
     phase: TouchPhase,
+    /// Whether the scroll goes on by inertia after the fingers left the touchpad. The momentum of
+    /// a scroll has its own `Started`, `Moved` and `Ended` phases, following the `Ended` phase of
+    /// the scroll.
+    momentum: bool,
+    /// The kind of device which scrolled.
+    source: ScrollSource,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
   },
 
   /// An mouse button press has been received.
+  ///
+  /// `click_count` is how many times in a row `button` was pressed within the double-click time
+  /// and distance set in the system, 2 for a double click and 3 for a triple click. A release has
+  /// the count of its press.
   MouseInput {
     device_id: DeviceId,
     state: ElementState,
     button: MouseButton,
+    click_count: u32,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
   },
@@ -356,6 +852,74 @@ pub enum WindowEvent<'a> {
     stage: i64,
   },
 
+  /// A pinch gesture on a touchpad, usually to zoom.
+  ///
+  /// `delta` is the change of the magnification since the last event, positive when the fingers
+  /// spread apart: the content is scaled by `1.0 + delta`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Unsupported, precision touchpads report pinches as `MouseWheel` events with
+  ///   Control held.
+  /// - **iOS / Android:** Unsupported, pinches are reported as `Touch` events.
+  TouchpadMagnify {
+    device_id: DeviceId,
+    delta: f64,
+    phase: TouchPhase,
+  },
+
+  /// A double tap with two fingers on a touchpad, usually to toggle zooming on what's under the
+  /// cursor.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Only emitted with the "Smart zoom" setting of the touchpad on.
+  /// - **Windows / Linux / iOS / Android:** Unsupported.
+  SmartMagnify { device_id: DeviceId },
+
+  /// A rotation gesture with two fingers on a touchpad.
+  ///
+  /// `delta` is the angle rotated since the last event, in degrees, positive counterclockwise.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / iOS / Android:** Unsupported.
+  TouchpadRotate {
+    device_id: DeviceId,
+    delta: f32,
+    phase: TouchPhase,
+  },
+
+  /// A swipe with three or more fingers on a touchpad, usually to navigate back or forward.
+  ///
+  /// It's emitted once the fingers are lifted, in the direction they moved the most.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Only emitted with the "Swipe between pages" setting of the touchpad set to three
+  ///   fingers.
+  /// - **Windows / iOS / Android:** Unsupported.
+  TouchpadSwipe {
+    device_id: DeviceId,
+    direction: SwipeDirection,
+  },
+
+  /// Unaccelerated relative motion of the mouse, emitted while the window is focused and
+  /// [`Window::set_raw_mouse_motion`](crate::window::Window::set_raw_mouse_motion) is enabled.
+  ///
+  /// `delta` is in the units of the device, and keeps coming when the cursor can't move further,
+  /// e.g. at the edge of the screen or when it's grabbed with `CursorGrabMode::Locked`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The deltas are accelerated.
+  /// - **Linux:** Only supported on X11.
+  /// - **iOS / Android:** Unsupported.
+  RawMouseMotion {
+    device_id: DeviceId,
+    delta: (f64, f64),
+  },
+
   /// Motion on some analog axis. May report data redundant to other, more specific events.
   AxisMotion {
     device_id: DeviceId,
@@ -366,6 +930,18 @@ pub enum WindowEvent<'a> {
   /// Touch event has been received
   Touch(Touch),
 
+  /// A pen moved over the window, touched it or was lifted from it.
+  ///
+  /// It's reported along with the `CursorMoved`, `MouseInput` or `Touch` events the pen generates
+  /// too, for drawing applications to follow its pressure and orientation.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** `twist` is only reported by the tablets whose pens have a rotation axis.
+  /// - **iOS / Android:** Unsupported, pens are only reported as `Touch` events with
+  ///   [`PointerKind::Pen`].
+  PenInput(PenInput),
+
   /// The window's scale factor has changed.
   ///
   /// The following user actions can cause DPI changes:
@@ -394,6 +970,70 @@ pub enum WindowEvent<'a> {
   /// - **Linux / Android / iOS:** Unsupported
   ThemeChanged(Theme),
 
+  /// The system cursor theme or the user's cursor size setting has changed.
+  ///
+  /// A cursor set with [`Window::set_custom_cursor`](crate::window::Window::set_custom_cursor)
+  /// is updated automatically, but applications drawing their own cursor images might wish to
+  /// regenerate them at the new size.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Android / iOS:** Unsupported
+  CursorThemeChanged,
+
+  /// An accelerator added with [`Window::add_accelerator`](crate::window::Window::add_accelerator)
+  /// was pressed.
+  Accelerator(AcceleratorId),
+
+  /// The fullscreen state of the window was changed by the user or the system, for example with
+  /// the green button on macOS or the window manager on Linux.
+  ///
+  /// Contains the new state, as returned by [`Window::fullscreen`](crate::window::Window::fullscreen).
+  /// It isn't emitted for changes made with
+  /// [`Window::set_fullscreen`](crate::window::Window::set_fullscreen).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only emitted when a fullscreen window is moved to another monitor, for example
+  ///   with <kbd>Win</kbd>+<kbd>Shift</kbd>+<kbd>Arrow</kbd>, since Windows has no fullscreen
+  ///   gesture of its own.
+  /// - **Android / iOS:** Unsupported
+  FullscreenStateChanged(Option<Fullscreen>),
+
+  /// The window is entering or leaving fullscreen, whether by the user or with
+  /// [`Window::set_fullscreen`](crate::window::Window::set_fullscreen), so that the UI can adapt
+  /// during the animation.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** `WillEnter` and `WillExit` are emitted when the native fullscreen animation
+  ///   starts, `DidEnter` and `DidExit` when it ends. In the simple fullscreen mode, see
+  ///   [`FullscreenMode`](crate::platform::macos::FullscreenMode), they're emitted right after each
+  ///   other since there is no animation.
+  /// - **Windows / Linux / Android / iOS:** Unsupported
+  FullscreenTransition(FullscreenTransition),
+
+  /// The window has been occluded (completely hidden from view) or is visible again.
+  ///
+  /// Contains `true` if the window is occluded. Applications can use this to stop or throttle
+  /// rendering while the window can't be seen.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only reported when the window is minimized, hidden or cloaked (e.g. on
+  ///   another virtual desktop), not when it is covered by other windows.
+  /// - **Linux:** Only reported on X11.
+  /// - **Android / iOS:** Unsupported
+  Occluded(bool),
+
+  /// Some options of the [`WindowBuilder`](crate::window::WindowBuilder) couldn't be honored by
+  /// the platform. Emitted once after the window was created, only if there are any.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Unsupported
+  AttributesDowngraded(AttributesReport),
+
   /// The window decorations has been clicked.
   ///
   /// ## Platform-specific
@@ -409,11 +1049,15 @@ impl Clone for WindowEvent<'static> {
       Resized(size) => Resized(*size),
       Moved(pos) => Moved(*pos),
       CloseRequested => CloseRequested,
+      MinimizeRequested => MinimizeRequested,
+      ThumbnailPeekStarted => ThumbnailPeekStarted,
+      ThumbnailPeekEnded => ThumbnailPeekEnded,
       Destroyed => Destroyed,
       DroppedFile(file) => DroppedFile(file.clone()),
       HoveredFile(file) => HoveredFile(file.clone()),
       HoveredFileCancelled => HoveredFileCancelled,
       ReceivedImeText(c) => ReceivedImeText(c.clone()),
+      Ime(ime) => Ime(ime.clone()),
       Focused(f) => Focused(*f),
       KeyboardInput {
         device_id,
@@ -447,11 +1091,15 @@ impl Clone for WindowEvent<'static> {
         device_id,
         delta,
         phase,
+        momentum,
+        source,
         modifiers,
       } => MouseWheel {
         device_id: *device_id,
         delta: *delta,
         phase: *phase,
+        momentum: *momentum,
+        source: *source,
         modifiers: *modifiers,
       },
       #[allow(deprecated)]
@@ -459,11 +1107,13 @@ impl Clone for WindowEvent<'static> {
         device_id,
         state,
         button,
+        click_count,
         modifiers,
       } => MouseInput {
         device_id: *device_id,
         state: *state,
         button: *button,
+        click_count: *click_count,
         modifiers: *modifiers,
       },
       TouchpadPressure {
@@ -475,6 +1125,38 @@ impl Clone for WindowEvent<'static> {
         pressure: *pressure,
         stage: *stage,
       },
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadMagnify {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
+      SmartMagnify { device_id } => SmartMagnify {
+        device_id: *device_id,
+      },
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadRotate {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
+      TouchpadSwipe {
+        device_id,
+        direction,
+      } => TouchpadSwipe {
+        device_id: *device_id,
+        direction: *direction,
+      },
+      RawMouseMotion { device_id, delta } => RawMouseMotion {
+        device_id: *device_id,
+        delta: *delta,
+      },
       AxisMotion {
         device_id,
         axis,
@@ -485,7 +1167,14 @@ impl Clone for WindowEvent<'static> {
         value: *value,
       },
       Touch(touch) => Touch(*touch),
+      PenInput(pen) => PenInput(*pen),
       ThemeChanged(theme) => ThemeChanged(*theme),
+      CursorThemeChanged => CursorThemeChanged,
+      Accelerator(id) => Accelerator(*id),
+      FullscreenStateChanged(fullscreen) => FullscreenStateChanged(fullscreen.clone()),
+      FullscreenTransition(transition) => FullscreenTransition(*transition),
+      Occluded(occluded) => Occluded(*occluded),
+      AttributesDowngraded(report) => AttributesDowngraded(report.clone()),
       ScaleFactorChanged { .. } => {
         unreachable!("Static event can't be about scale factor changing")
       }
@@ -501,11 +1190,15 @@ impl<'a> WindowEvent<'a> {
       Resized(size) => Some(Resized(size)),
       Moved(position) => Some(Moved(position)),
       CloseRequested => Some(CloseRequested),
+      MinimizeRequested => Some(MinimizeRequested),
+      ThumbnailPeekStarted => Some(ThumbnailPeekStarted),
+      ThumbnailPeekEnded => Some(ThumbnailPeekEnded),
       Destroyed => Some(Destroyed),
       DroppedFile(file) => Some(DroppedFile(file)),
       HoveredFile(file) => Some(HoveredFile(file)),
       HoveredFileCancelled => Some(HoveredFileCancelled),
       ReceivedImeText(c) => Some(ReceivedImeText(c)),
+      Ime(ime) => Some(Ime(ime)),
       Focused(focused) => Some(Focused(focused)),
       KeyboardInput {
         device_id,
@@ -534,11 +1227,15 @@ impl<'a> WindowEvent<'a> {
         device_id,
         delta,
         phase,
+        momentum,
+        source,
         modifiers,
       } => Some(MouseWheel {
         device_id,
         delta,
         phase,
+        momentum,
+        source,
         modifiers,
       }),
       #[allow(deprecated)]
@@ -546,11 +1243,13 @@ impl<'a> WindowEvent<'a> {
         device_id,
         state,
         button,
+        click_count,
         modifiers,
       } => Some(MouseInput {
         device_id,
         state,
         button,
+        click_count,
         modifiers,
       }),
       TouchpadPressure {
@@ -562,6 +1261,33 @@ impl<'a> WindowEvent<'a> {
         pressure,
         stage,
       }),
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      }),
+      SmartMagnify { device_id } => Some(SmartMagnify { device_id }),
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      }),
+      TouchpadSwipe {
+        device_id,
+        direction,
+      } => Some(TouchpadSwipe {
+        device_id,
+        direction,
+      }),
+      RawMouseMotion { device_id, delta } => Some(RawMouseMotion { device_id, delta }),
       AxisMotion {
         device_id,
         axis,
@@ -572,7 +1298,14 @@ impl<'a> WindowEvent<'a> {
         value,
       }),
       Touch(touch) => Some(Touch(touch)),
+      PenInput(pen) => Some(PenInput(pen)),
       ThemeChanged(theme) => Some(ThemeChanged(theme)),
+      CursorThemeChanged => Some(CursorThemeChanged),
+      Accelerator(id) => Some(Accelerator(id)),
+      FullscreenStateChanged(fullscreen) => Some(FullscreenStateChanged(fullscreen)),
+      FullscreenTransition(transition) => Some(FullscreenTransition(transition)),
+      Occluded(occluded) => Some(Occluded(occluded)),
+      AttributesDowngraded(report) => Some(AttributesDowngraded(report)),
       ScaleFactorChanged { .. } => None,
       DecorationsClick => Some(DecorationsClick),
     }
@@ -651,6 +1384,69 @@ pub enum DeviceEvent {
   Text {
     codepoint: char,
   },
+
+  /// A button on a pen tablet was pressed or released, such as a pen barrel button or an
+  /// express key on the tablet pad.
+  ///
+  /// `id` identifies the button on its device and may be shared between the pen and the pad.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only the pen barrel button is reported, with an `id` of `0`.
+  /// - **macOS:** Pen buttons are reported from tablet point events, where `id` is the bit index
+  ///   in the event's button mask.
+  /// - **Linux:** Only tablet pad buttons are reported. Pen buttons are reported as [`WindowEvent::MouseInput`].
+  /// - **iOS / Android:** Unsupported.
+  #[non_exhaustive]
+  TabletButton {
+    id: u32,
+    state: ElementState,
+  },
+
+  /// The tool used on a pen tablet changed, for example when the pen was flipped to its eraser
+  /// end or a different tool was brought into proximity.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only [`TabletTool::Pen`] and [`TabletTool::Eraser`] are reported.
+  /// - **iOS / Android:** Unsupported.
+  #[non_exhaustive]
+  TabletToolChanged {
+    tool: TabletTool,
+  },
+}
+
+/// A step of an IME composition, see [`WindowEvent::Ime`].
+///
+/// Position the candidate window of the IME next to the composed text with
+/// [`Window::set_ime_cursor_area`](crate::window::Window::set_ime_cursor_area).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ime {
+  /// The text being composed changed, to be drawn in place of the cursor until it's committed.
+  ///
+  /// The second field is the byte range of the cursor in the text, or of the selected part of the
+  /// text, `None` if the cursor should be hidden. An empty text ends the composition, whether it
+  /// was committed or cancelled.
+  Preedit(String, Option<(usize, usize)>),
+  /// The composed text was committed, to be inserted at the cursor.
+  Commit(String),
+}
+
+/// The kind of tool used on a pen tablet.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletTool {
+  Pen,
+  Eraser,
+  Brush,
+  Pencil,
+  Airbrush,
+  Mouse,
+  Lens,
+  Unknown,
 }
 
 /// Describes a keyboard input as a raw device event.
@@ -720,6 +1516,34 @@ pub struct KeyEvent {
   pub state: ElementState,
   pub repeat: bool,
 
+  /// How many times the key repeated since it was pressed: `0` for the press and the release,
+  /// `n` for its `n`-th repeat.
+  ///
+  /// Editors which repeat keys themselves can align with the system using
+  /// [`EventLoopWindowTarget::key_repeat_settings`](crate::event_loop::EventLoopWindowTarget::key_repeat_settings).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux:** Counted for the key held last, pressing another key restarts
+  ///   the count like it stops the repeats.
+  pub repeat_count: u32,
+
+  /// Whether a dead key was pressed and waits for the next key to combine with, e.g. to draw the
+  /// pending accent under the cursor.
+  ///
+  /// It's set by the press of the dead key, whose `logical_key` is [`Key::Dead`], and stays set
+  /// until a key press types text: the combined character, or the accent followed by the
+  /// character if they don't combine. Modifiers don't clear it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Dead keys combined by the IME, like the ones of compose sequences, aren't
+  ///   tracked.
+  /// - **iOS / Android:** Unsupported, always `false`.
+  ///
+  /// [`Key::Dead`]: keyboard::Key::Dead
+  pub dead_key_pending: bool,
+
   pub(crate) platform_specific: platform_impl::KeyEventExtra,
 }
 
@@ -745,6 +1569,22 @@ impl KeyEvent {
   pub fn key_without_modifiers(&self) -> keyboard::Key<'static> {
     self.platform_specific.key_without_modifiers.clone()
   }
+
+  /// Identifies the input locale (keyboard layout or input source) that was active when this
+  /// event was generated.
+  ///
+  /// This is useful to switch fonts or spellcheck dictionaries as the user switches between
+  /// languages. The identifier is platform specific, so it should be compared against values
+  /// previously returned by this function rather than parsed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The input locale identifier (`HKL`) in hexadecimal, e.g. `"04090409"`.
+  /// - **macOS:** The input source identifier, e.g. `"com.apple.keylayout.US"`.
+  /// - **Linux:** The XKB layout group index, e.g. `"0"`.
+  pub fn input_locale(&self) -> Option<&str> {
+    self.platform_specific.input_locale
+  }
 }
 
 #[cfg(any(target_os = "android", target_os = "ios"))]
@@ -771,6 +1611,28 @@ pub enum TouchPhase {
   Cancelled,
 }
 
+/// The step of a fullscreen transition, see [`WindowEvent::FullscreenTransition`].
+#[non_exhaustive]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FullscreenTransition {
+  WillEnter,
+  DidEnter,
+  WillExit,
+  DidExit,
+}
+
+/// The direction the fingers moved in a [`WindowEvent::TouchpadSwipe`].
+#[non_exhaustive]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SwipeDirection {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
 /// Represents a touch event
 ///
 /// Every time the user touches the screen, a new `Start` event with an unique
@@ -801,6 +1663,51 @@ pub struct Touch {
   pub force: Option<Force>,
   /// Unique identifier of a finger.
   pub id: u64,
+  /// The kind of pointer which touched the screen, to tell a pen from a finger.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** [`PointerKind::Mouse`] for the mouse input which goes through `WM_POINTER*`.
+  /// - **Linux:** Touches are reported for touchscreens and pens which GDK reports touch events
+  ///   for, pens usually move the cursor instead.
+  /// - **macOS:** Touches are never reported.
+  pub pointer_kind: PointerKind,
+}
+
+/// The state of a pen over a window, see [`WindowEvent::PenInput`].
+///
+/// The values the pen doesn't report are `0`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenInput {
+  pub device_id: DeviceId,
+  /// Position of the tip, relative to the top-left corner of the window.
+  pub position: PhysicalPosition<f64>,
+  /// Whether the tip touches the surface.
+  pub in_contact: bool,
+  /// Pressure of the tip, from `0.0` to `1.0`.
+  pub pressure: f64,
+  /// Tilt of the pen towards the right edge of the surface, in degrees from `-90.0` to `90.0`.
+  pub tilt_x: f64,
+  /// Tilt of the pen towards the bottom edge of the surface, in degrees from `-90.0` to `90.0`.
+  pub tilt_y: f64,
+  /// Clockwise rotation of the pen around its axis, in degrees from `0.0` to `360.0`.
+  pub twist: f64,
+  /// Whether the eraser end of the pen is used.
+  pub eraser: bool,
+  /// The barrel buttons held, the first one in the lowest bit.
+  pub barrel_buttons: u32,
+}
+
+/// The kind of device a pointer event comes from.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PointerKind {
+  Mouse,
+  Touch,
+  Pen,
+  Unknown,
 }
 
 /// Describes the force of a touch event
@@ -877,6 +1784,8 @@ pub enum ElementState {
 }
 
 /// Describes a button of a mouse controller.
+///
+/// The buttons are numbered the same way on all platforms, see [`MouseButton::index`].
 #[non_exhaustive]
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -884,9 +1793,47 @@ pub enum MouseButton {
   Left,
   Right,
   Middle,
+  /// The button navigating back, usually the first button on the side of the mouse.
+  Back,
+  /// The button navigating forward, usually the second button on the side of the mouse.
+  Forward,
+  /// Another button, numbered from 5 in the order of the platform.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Never emitted, Windows only reports five buttons.
+  /// - **macOS:** `n` is the `buttonNumber` of the `NSEvent`.
+  /// - **Linux:** `n` is the X11 button number minus 5, since X11 buttons 4 to 7 are the wheel.
   Other(u16),
 }
 
+impl MouseButton {
+  /// Returns the index of the button: 0 for `Left`, 1 for `Right`, 2 for `Middle`, 3 for `Back`,
+  /// 4 for `Forward` and `n` for `Other(n)`.
+  pub fn index(self) -> u16 {
+    match self {
+      MouseButton::Left => 0,
+      MouseButton::Right => 1,
+      MouseButton::Middle => 2,
+      MouseButton::Back => 3,
+      MouseButton::Forward => 4,
+      MouseButton::Other(n) => n,
+    }
+  }
+
+  /// Returns the button of `index`, see [`MouseButton::index`].
+  pub fn from_index(index: u16) -> Self {
+    match index {
+      0 => MouseButton::Left,
+      1 => MouseButton::Right,
+      2 => MouseButton::Middle,
+      3 => MouseButton::Back,
+      4 => MouseButton::Forward,
+      n => MouseButton::Other(n),
+    }
+  }
+}
+
 /// Describes a difference in the mouse scroll wheel state.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -906,3 +1853,16 @@ pub enum MouseScrollDelta {
   /// platform.
   PixelDelta(PhysicalPosition<f64>),
 }
+
+/// The kind of device which emitted a [`WindowEvent::MouseWheel`].
+#[non_exhaustive]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScrollSource {
+  /// A mouse wheel, which scrolls by notches.
+  Wheel,
+  /// A touchpad, or any other device which scrolls continuously.
+  Touchpad,
+  /// The platform didn't tell.
+  Unknown,
+}
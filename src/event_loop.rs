@@ -14,11 +14,23 @@
 //! [event_loop_proxy]: crate::event_loop::EventLoopProxy
 //! [send_event]: crate::event_loop::EventLoopProxy::send_event
 use instant::Instant;
-use std::{error, fmt, marker::PhantomData, ops::Deref};
+use std::{
+  error, fmt,
+  marker::PhantomData,
+  ops::Deref,
+  sync::atomic::{AtomicU64, Ordering},
+  time::Duration,
+};
 
 use crate::{
-  dpi::PhysicalPosition, error::ExternalError, event::Event, monitor::MonitorHandle, platform_impl,
-  window::ProgressBarState,
+  dpi::PhysicalPosition,
+  error::ExternalError,
+  event::{DeviceId, Event, TypedUserEvent},
+  global_shortcut::GlobalShortcutManager,
+  keyboard::{Key, KeyCode, KeyRepeatSettings, KeyboardLayout},
+  monitor::{self, MonitorHandle},
+  platform_impl,
+  window::{ProgressBarState, SystemAppearance},
 };
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
@@ -36,6 +48,11 @@ use crate::{
 ///
 pub struct EventLoop<T: 'static> {
   pub(crate) event_loop: platform_impl::EventLoop<T>,
+  #[cfg(feature = "event-recorder")]
+  pub(crate) recorder:
+    Option<crate::event_recorder::EventRecorder<std::io::BufWriter<std::fs::File>>>,
+  #[cfg(feature = "harden")]
+  pub(crate) sanitizer: crate::harden::Sanitizer,
   pub(crate) _marker: ::std::marker::PhantomData<*mut ()>, // Not Send nor Sync
 }
 
@@ -89,6 +106,29 @@ impl<T> EventLoopBuilder<T> {
       _p: PhantomData,
     }
   }
+
+  /// Sets a callback which receives the raw events of the platform before `tao` translates them.
+  ///
+  /// The callback gets a pointer to the native event and returns whether it consumed it, in which
+  /// case `tao` neither translates nor dispatches it. This is meant for native libraries which
+  /// need to see the events first, like accelerator tables or input methods.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** A `*const MSG`, before `TranslateMessage` and `DispatchMessageW`.
+  /// - **macOS:** An `NSEvent*`, before `-[NSApplication sendEvent:]`.
+  /// - **iOS:** A `UIEvent*`, before `-[UIApplication sendEvent:]`.
+  /// - **Linux:** A `*const GdkEvent`, before `gtk_main_do_event`.
+  /// - **Android:** An `AInputEvent*`, after the input method had its chance at it.
+  #[inline]
+  pub fn with_msg_hook<F>(&mut self, callback: F) -> &mut Self
+  where
+    F: FnMut(*const std::ffi::c_void) -> bool + 'static,
+  {
+    self.platform_specific.msg_hook = Some(Box::new(callback));
+    self
+  }
+
   /// Builds a new event loop.
   ///
   /// ***For cross-platform compatibility, the `EventLoop` must be created on the main thread.***
@@ -110,11 +150,26 @@ impl<T> EventLoopBuilder<T> {
   pub fn build(&mut self) -> EventLoop<T> {
     EventLoop {
       event_loop: platform_impl::EventLoop::new(&mut self.platform_specific),
+      #[cfg(feature = "event-recorder")]
+      recorder: crate::event_recorder::recorder_from_env(),
+      #[cfg(feature = "harden")]
+      sanitizer: Default::default(),
       _marker: PhantomData,
     }
   }
 }
 
+/// See [`EventLoop::shutdown_timeout`].
+#[cfg(any(
+  target_os = "windows",
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Set by the user callback given to the `EventLoop::run` method.
 ///
 /// Indicates the desired behavior of the event loop after [`Event::RedrawEventsCleared`][events_cleared]
@@ -137,8 +192,20 @@ pub enum ControlFlow {
   Wait,
   /// When the current loop iteration finishes, suspend the thread until either another event
   /// arrives or the given time is reached.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The loop resumes within about 0.5ms of the given time with the
+  ///   high-resolution waitable timers of Windows 10 1803 and later. Earlier versions are bound
+  ///   to the system timer resolution, 15.6ms unless an application raised it with
+  ///   `timeBeginPeriod`.
+  /// - **macOS:** The loop resumes within about 0.1ms of the given time.
+  /// - **Linux:** The loop resumes within about 1ms of the given time, the granularity of the
+  ///   GLib main loop.
   WaitUntil(Instant),
-  /// Send a `LoopDestroyed` event and stop the event loop. This variant is *sticky* - once set,
+  /// Send a `LoopDestroyed` event and stop the event loop, destroying the remaining windows first
+  /// when it was started by [`EventLoop::run`], see [`EventLoop::shutdown_timeout`]. This variant
+  /// is *sticky* - once set,
   /// `control_flow` cannot be changed from `ExitWithCode`, and any future attempts to do so will
   /// result in the `control_flow` parameter being reset to `ExitWithCode`.
   ///
@@ -147,7 +214,7 @@ pub enum ControlFlow {
   ///
   /// ## Platform-specific
   ///
-  /// - **Android / iOS / WASM**: The supplied exit code is unused.
+  /// - **iOS**: Ignored, an iOS application can't quit by itself.
   /// - **Unix**: On most Unix-like platforms, only the 8 least significant bits will be used,
   ///   which can cause surprises with negative exit values (`-42` would end up as `214`). See
   ///   [`std::process::exit`].
@@ -195,7 +262,11 @@ impl<T> EventLoop<T> {
   /// See the [`ControlFlow`] docs for information on how changes to `&mut ControlFlow` impact the
   /// event loop's behavior.
   ///
-  /// Any values not passed to this function will *not* be dropped.
+  /// Any values not passed to this function will *not* be dropped. The ones owned by
+  /// `event_handler` are dropped after `Event::LoopDestroyed`, then the process exits with the
+  /// code of `ControlFlow::ExitWithCode`. Use
+  /// [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return) to get the code
+  /// back instead.
   ///
   /// ## Platform-specific
   ///
@@ -208,15 +279,78 @@ impl<T> EventLoop<T> {
   where
     F: 'static + FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
   {
+    #[cfg(feature = "event-recorder")]
+    let event_handler = crate::event_recorder::record(self.recorder, event_handler);
+    #[cfg(feature = "harden")]
+    let event_handler = crate::harden::sanitize(self.sanitizer, event_handler);
+    #[cfg(feature = "synthetic-input")]
+    let event_handler = crate::synthetic_input::inject(event_handler);
+    #[cfg(feature = "notifications")]
+    let event_handler = crate::notification::deliver(event_handler);
     self.event_loop.run(event_handler)
   }
 
+  /// Sets how long the event loop keeps processing the teardown of the remaining windows when
+  /// [`run`](Self::run) exits, 1 second by default.
+  ///
+  /// Once `ControlFlow::ExitWithCode` is set, or the platform quits the application, every window
+  /// that is still alive is destroyed and receives `WindowEvent::Destroyed` before
+  /// `Event::LoopDestroyed`, so that none outlives `run`, which exits the process. The events
+  /// caused by the teardown are dispatched until no window is left or `timeout` elapsed.
+  ///
+  /// The windows are kept when `run_return` or `pump_events` exit, for the next call to use them.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Windows are closed synchronously, `timeout` is unused.
+  /// - **iOS / Android:** Unsupported.
+  pub fn shutdown_timeout(&mut self, timeout: Duration) {
+    self.event_loop.set_shutdown_timeout(timeout);
+  }
+
   /// Creates an `EventLoopProxy` that can be used to dispatch user events to the main event loop.
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
-      event_loop_proxy: self.event_loop.create_proxy(),
+      event_loop_proxy: ProxyChannel::User(self.event_loop.create_proxy()),
+    }
+  }
+
+  /// Creates an `EventLoopProxy` for events of another type than the event loop's, which are
+  /// emitted as [`Event::TypedUserEvent`].
+  ///
+  /// This lets the subsystems of an application each post their own type of events, without
+  /// adding all of them to the user event type. The events of all the typed proxies go through
+  /// one queue, and are received in the order they were sent.
+  ///
+  /// [`Event::TypedUserEvent`]: crate::event::Event::TypedUserEvent
+  pub fn create_typed_proxy<U: Send + 'static>(&self) -> EventLoopProxy<U> {
+    EventLoopProxy {
+      event_loop_proxy: ProxyChannel::Typed(
+        self.event_loop.create_typed_proxy(),
+        TypedUserEvent::new::<U>,
+      ),
+    }
+  }
+
+  /// Creates an [`InputInjector`] which sends synthetic input to this event loop, for tests.
+  ///
+  /// [`InputInjector`]: crate::synthetic_input::InputInjector
+  #[cfg(feature = "synthetic-input")]
+  pub fn create_input_injector(&self) -> crate::synthetic_input::InputInjector {
+    crate::synthetic_input::InputInjector {
+      proxy: self.create_typed_proxy(),
     }
   }
+
+  /// Creates a [`Notifier`] which shows notifications, whose clicks are emitted by this event loop
+  /// as [`Event::NotificationActivated`].
+  ///
+  /// [`Notifier`]: crate::notification::Notifier
+  /// [`Event::NotificationActivated`]: crate::event::Event::NotificationActivated
+  #[cfg(feature = "notifications")]
+  pub fn create_notifier(&self) -> crate::notification::Notifier {
+    crate::notification::Notifier::new(self.event_loop.window_target(), self.create_typed_proxy())
+  }
 }
 
 impl<T> Deref for EventLoop<T> {
@@ -228,13 +362,11 @@ impl<T> Deref for EventLoop<T> {
 
 impl<T> EventLoopWindowTarget<T> {
   /// Returns the list of all the monitors available on the system.
+  ///
+  /// The primary monitor comes first, then the others from left to right and from top to bottom.
   #[inline]
   pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
-    self
-      .p
-      .available_monitors()
-      .into_iter()
-      .map(|inner| MonitorHandle { inner })
+    monitor::sorted(self.p.available_monitors())
   }
 
   /// Returns the primary monitor of the system.
@@ -274,6 +406,37 @@ impl<T> EventLoopWindowTarget<T> {
     self.p.set_device_event_filter(_filter);
   }
 
+  /// Restricts the [`DeviceEvent`]s which are reported to the given kinds. All of them are by
+  /// default.
+  ///
+  /// The devices of the kinds which aren't subscribed to aren't listened to at all, e.g. raw
+  /// keyboard input isn't registered unless [`DeviceEventKinds::KEYBOARD`] is set, which also
+  /// saves the work of receiving their input. `DeviceEvent::Added` and `DeviceEvent::Removed` are
+  /// only reported for the devices which are listened to.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux / macOS / iOS / Android:** Unsupported.
+  ///
+  /// [`DeviceEvent`]: crate::event::DeviceEvent
+  pub fn set_device_event_kinds(&self, _kinds: DeviceEventKinds) {
+    #[cfg(target_os = "windows")]
+    self.p.set_device_event_kinds(_kinds);
+  }
+
+  /// Restricts the [`DeviceEvent`]s which are reported to the ones of the given devices, or
+  /// reports the ones of all devices if `None`, which is the default.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux / macOS / iOS / Android:** Unsupported.
+  ///
+  /// [`DeviceEvent`]: crate::event::DeviceEvent
+  pub fn set_device_event_devices(&self, _devices: Option<&[DeviceId]>) {
+    #[cfg(target_os = "windows")]
+    self.p.set_device_event_devices(_devices);
+  }
+
   /// Returns the current cursor position
   ///
   /// ## Platform-specific
@@ -284,6 +447,88 @@ impl<T> EventLoopWindowTarget<T> {
     self.p.cursor_position()
   }
 
+  /// Returns the appearance preferences of the system, whose changes are reported by
+  /// [`Event::SystemAppearanceChanged`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** `accent_color` is the selection color of the GTK theme and `high_contrast`
+  ///   whether it's a high contrast theme. `reduce_transparency` is always `false`.
+  /// - **iOS / Android:** Unsupported, returns the default.
+  #[inline]
+  pub fn system_appearance(&self) -> SystemAppearance {
+    self.p.system_appearance()
+  }
+
+  /// Returns the number of buttons of the mouse, not counting the wheel, or `None` if it's
+  /// unknown. The buttons can be told apart in [`WindowEvent::MouseInput`] with
+  /// [`MouseButton::index`], which is lower than this count.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The most buttons of the mice plugged in, `None` if there's none.
+  /// - **Linux:** The buttons of the X11 core pointer, which often reports more buttons than the
+  ///   mice have.
+  /// - **macOS / iOS / Android / Linux(Wayland):** Unsupported, returns `None`.
+  ///
+  /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+  /// [`MouseButton::index`]: crate::event::MouseButton::index
+  #[inline]
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    self.p.mouse_button_count()
+  }
+
+  /// Returns the keyboard layout the user currently types with, whose changes are reported by
+  /// [`Event::KeyboardLayoutChanged`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** `language` is always `None`.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported, returns the default.
+  #[inline]
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    self.p.keyboard_layout()
+  }
+
+  /// Returns the key which `code` produces with the current keyboard layout and no modifiers, e.g.
+  /// to label a shortcut with the character printed on the key: [`KeyCode::KeyZ`] produces `"y"`
+  /// on a German layout.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, returns [`Key::Unidentified`].
+  #[inline]
+  pub fn key_for_code(&self, code: KeyCode) -> Key<'static> {
+    self.p.key_for_code(code)
+  }
+
+  /// Returns the physical key which produces `key` with the current keyboard layout and no
+  /// modifiers, the reverse of [`EventLoopWindowTarget::key_for_code`], e.g. to store a binding
+  /// chosen by its label as a position: `"y"` is produced by [`KeyCode::KeyZ`] on a German layout.
+  ///
+  /// If several keys produce `key`, the one with the lowest scancode is returned, e.g. `Enter`
+  /// rather than `NumpadEnter`. Returns `None` if no key produces it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, returns `None`.
+  #[inline]
+  pub fn code_for_key(&self, key: &Key<'_>) -> Option<KeyCode> {
+    self.p.code_for_key(key)
+  }
+
+  /// Returns how the system repeats a held key, or `None` if keys don't repeat.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `interval` is rounded to milliseconds.
+  /// - **Linux:** Only supported on X11.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported, returns `None`.
+  #[inline]
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    self.p.key_repeat_settings()
+  }
+
   /// Sets the progress bar state
   ///
   /// ## Platform-specific
@@ -296,6 +541,54 @@ impl<T> EventLoopWindowTarget<T> {
     #[cfg(any(target_os = "linux", target_os = "macos"))]
     self.p.set_progress_bar(_progress)
   }
+
+  /// Starts a timer which emits [`Event::Timer`] with the returned id on the event loop's thread
+  /// once `interval` elapsed, then every `interval` if `repeating`, until it's cancelled with
+  /// [`EventLoopWindowTarget::cancel_timer`].
+  ///
+  /// Timers suit periodic work like blinking a caret or polling a device: they fire once the
+  /// events dispatched before them were handled, and don't catch up on the intervals they missed.
+  /// Use [`ControlFlow::WaitUntil`] to schedule frames precisely.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `interval` is rounded up to milliseconds, and to 10ms at least.
+  /// - **Linux:** `interval` is rounded down to milliseconds.
+  /// - **iOS / Android:** Unsupported, the timer never fires.
+  pub fn set_timer(&self, interval: Duration, repeating: bool) -> TimerId {
+    let id = TimerId::next();
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    self.p.set_timer(id, interval, repeating);
+    #[cfg(any(target_os = "ios", target_os = "android"))]
+    let _ = (interval, repeating);
+    id
+  }
+
+  /// Stops the timer `id` started with [`EventLoopWindowTarget::set_timer`]. It doesn't fire
+  /// anymore, even if it was due already. Does nothing if it was a timer which isn't `repeating`
+  /// and fired already, or if it was cancelled already.
+  pub fn cancel_timer(&self, _id: TimerId) {
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    self.p.cancel_timer(_id)
+  }
+
+  /// Returns the manager of the system-wide shortcuts of this event loop, which emit
+  /// [`Event::GlobalShortcut`] when pressed.
+  pub fn global_shortcut_manager(&self) -> GlobalShortcutManager<'_, T> {
+    GlobalShortcutManager { target: self }
+  }
+}
+
+/// Identifier of a timer started with [`EventLoopWindowTarget::set_timer`], unique for the
+/// lifetime of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(pub(crate) u64);
+
+impl TimerId {
+  fn next() -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+  }
 }
 
 #[cfg(feature = "rwh_05")]
@@ -330,25 +623,50 @@ impl<T> rwh_06::HasDisplayHandle for EventLoopWindowTarget<T> {
 
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
-  event_loop_proxy: platform_impl::EventLoopProxy<T>,
+  event_loop_proxy: ProxyChannel<T>,
+}
+
+/// The channel an `EventLoopProxy` sends its events through.
+enum ProxyChannel<T: 'static> {
+  /// Events are emitted as `Event::UserEvent`.
+  User(platform_impl::EventLoopProxy<T>),
+  /// Events are emitted as `Event::TypedUserEvent`, wrapped by the function, which requires
+  /// them to be `Send`.
+  Typed(
+    platform_impl::EventLoopProxy<TypedUserEvent>,
+    fn(T) -> TypedUserEvent,
+  ),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
   fn clone(&self) -> Self {
-    Self {
-      event_loop_proxy: self.event_loop_proxy.clone(),
-    }
+    let event_loop_proxy = match &self.event_loop_proxy {
+      ProxyChannel::User(proxy) => ProxyChannel::User(proxy.clone()),
+      ProxyChannel::Typed(proxy, wrap) => ProxyChannel::Typed(proxy.clone(), *wrap),
+    };
+    Self { event_loop_proxy }
   }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
   /// Send an event to the `EventLoop` from which this proxy was created. This emits a
   /// `UserEvent(event)` event in the event loop, where `event` is the value passed to this
-  /// function.
+  /// function, or a `TypedUserEvent` wrapping it for the proxies created by
+  /// [`EventLoop::create_typed_proxy`].
   ///
   /// Returns an `Err` if the associated `EventLoop` no longer exists.
   pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
-    self.event_loop_proxy.send_event(event)
+    match &self.event_loop_proxy {
+      ProxyChannel::User(proxy) => proxy.send_event(event),
+      ProxyChannel::Typed(proxy, wrap) => {
+        proxy
+          .send_event(wrap(event))
+          .map_err(|EventLoopClosed(event)| match event.downcast() {
+            Ok(event) => EventLoopClosed(event),
+            Err(_) => unreachable!(),
+          })
+      }
+    }
   }
 }
 
@@ -387,3 +705,59 @@ impl Default for DeviceEventFilter {
     Self::Unfocused
   }
 }
+
+bitflags! {
+  /// Kinds of device events, see [`EventLoopWindowTarget::set_device_event_kinds`].
+  pub struct DeviceEventKinds: u32 {
+    /// `DeviceEvent::MouseMotion` and `DeviceEvent::Motion` of mice.
+    const MOUSE_MOTION = 1 << 0;
+    /// `DeviceEvent::MouseWheel`.
+    const MOUSE_WHEEL = 1 << 1;
+    /// `DeviceEvent::Button`.
+    const MOUSE_BUTTON = 1 << 2;
+    /// `DeviceEvent::Key`.
+    const KEYBOARD = 1 << 3;
+    /// The kinds coming from mice.
+    const MOUSE = Self::MOUSE_MOTION.bits | Self::MOUSE_WHEEL.bits | Self::MOUSE_BUTTON.bits;
+  }
+}
+
+impl Default for DeviceEventKinds {
+  fn default() -> Self {
+    Self::all()
+  }
+}
+
+/// Whether the application is suspended on desktop platforms, where the reasons for it can
+/// overlap, see `Event::Suspended`.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[derive(Debug, Default)]
+pub(crate) struct Suspension {
+  sleeping: bool,
+  locked: bool,
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+impl Suspension {
+  /// Records whether the system is going to sleep, returning the event to emit if the application
+  /// got suspended or resumed.
+  pub(crate) fn set_sleeping<T>(&mut self, sleeping: bool) -> Option<Event<'static, T>> {
+    self.update(|suspension| suspension.sleeping = sleeping)
+  }
+
+  /// Records whether the session is locked, returning the event to emit if the application got
+  /// suspended or resumed.
+  pub(crate) fn set_locked<T>(&mut self, locked: bool) -> Option<Event<'static, T>> {
+    self.update(|suspension| suspension.locked = locked)
+  }
+
+  fn update<T>(&mut self, f: impl FnOnce(&mut Self)) -> Option<Event<'static, T>> {
+    let was_suspended = self.sleeping || self.locked;
+    f(self);
+    match (was_suspended, self.sleeping || self.locked) {
+      (false, true) => Some(Event::Suspended),
+      (true, false) => Some(Event::Resumed),
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,542 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording and replaying of the events dispatched by an [`EventLoop`].
+//!
+//! When the `event-recorder` feature is enabled, [`EventLoopBuilder::build`] creates the file named
+//! by the `TAO_EVENT_LOG` environment variable, if it's set. Once the event loop is started,
+//! [`EventLoop::run`], [`EventLoopExtRunReturn::run_return`] and
+//! [`EventLoopExtPumpEvents::pump_events`] write every event they dispatch to that file, along
+//! with the time it was dispatched at. A user can then submit the file with a bug report, and it
+//! can be replayed with [`Replay`], or the `tao-replay` example, to feed the same events to an
+//! event handler in the same order.
+//!
+//! The log is a compact binary format made of a header followed by one record per event. Only
+//! events which can be rebuilt without a live window are recorded: window and device input,
+//! lifecycle and redraw events. Keyboard input, IME, user events and platform-specific events are
+//! skipped.
+//!
+//! Window ids can't be recreated on replay, so windows are numbered in the order their first event
+//! was dispatched and replayed events carry a dummy [`WindowId`] along with that number.
+//!
+//! [`EventLoop`]: crate::event_loop::EventLoop
+//! [`EventLoopBuilder::build`]: crate::event_loop::EventLoopBuilder::build
+//! [`EventLoop::run`]: crate::event_loop::EventLoop::run
+//! [`EventLoopExtRunReturn::run_return`]: crate::platform::run_return::EventLoopExtRunReturn::run_return
+//! [`EventLoopExtPumpEvents::pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+
+use std::{
+  borrow::BorrowMut,
+  collections::HashMap,
+  env,
+  fs::File,
+  io::{self, BufReader, BufWriter, Read, Write},
+  path::Path,
+  time::Duration,
+};
+
+use instant::Instant;
+
+use crate::{
+  dpi::{PhysicalPosition, PhysicalSize},
+  event::{
+    DeviceEvent, DeviceId, ElementState, Event, MouseButton, MouseScrollDelta, ScrollSource,
+    TouchPhase, WindowEvent,
+  },
+  event_loop::{ControlFlow, EventLoopWindowTarget},
+  keyboard::ModifiersState,
+  window::WindowId,
+};
+
+/// Environment variable holding the path of the log to record events to.
+pub const EVENT_LOG_ENV: &str = "TAO_EVENT_LOG";
+
+const MAGIC: &[u8; 6] = b"TAOEVT";
+const VERSION: u8 = 1;
+const NO_WINDOW: u32 = u32::MAX;
+
+const EVENT_WINDOW: u8 = 1;
+const EVENT_DEVICE: u8 = 2;
+const EVENT_SUSPENDED: u8 = 3;
+const EVENT_RESUMED: u8 = 4;
+const EVENT_MAIN_EVENTS_CLEARED: u8 = 5;
+const EVENT_REDRAW_REQUESTED: u8 = 6;
+const EVENT_REDRAW_EVENTS_CLEARED: u8 = 7;
+const EVENT_LOOP_DESTROYED: u8 = 8;
+
+const WINDOW_RESIZED: u8 = 1;
+const WINDOW_MOVED: u8 = 2;
+const WINDOW_CLOSE_REQUESTED: u8 = 3;
+const WINDOW_DESTROYED: u8 = 4;
+const WINDOW_FOCUSED: u8 = 5;
+const WINDOW_MODIFIERS_CHANGED: u8 = 6;
+const WINDOW_CURSOR_MOVED: u8 = 7;
+const WINDOW_CURSOR_ENTERED: u8 = 8;
+const WINDOW_CURSOR_LEFT: u8 = 9;
+const WINDOW_MOUSE_WHEEL: u8 = 10;
+const WINDOW_MOUSE_INPUT: u8 = 11;
+
+const DEVICE_ADDED: u8 = 1;
+const DEVICE_REMOVED: u8 = 2;
+const DEVICE_MOUSE_MOTION: u8 = 3;
+const DEVICE_MOUSE_WHEEL: u8 = 4;
+const DEVICE_BUTTON: u8 = 5;
+
+/// Writes dispatched events to a log that can be read back with [`Replay`].
+pub struct EventRecorder<W: Write> {
+  writer: W,
+  start: Instant,
+  windows: HashMap<WindowId, u32>,
+}
+
+impl EventRecorder<BufWriter<File>> {
+  /// Creates a recorder writing to the file at `path`, replacing it if it exists.
+  pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    Self::new(BufWriter::new(File::create(path)?))
+  }
+}
+
+impl<W: Write> EventRecorder<W> {
+  /// Creates a recorder writing to `writer`, timing events from now.
+  pub fn new(mut writer: W) -> io::Result<Self> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    Ok(Self {
+      writer,
+      start: Instant::now(),
+      windows: HashMap::new(),
+    })
+  }
+
+  /// Appends `event` to the log, skipping it if it can't be recorded.
+  pub fn record<T>(&mut self, event: &Event<'_, T>) -> io::Result<()> {
+    let mut payload = Vec::new();
+    let window_id = match encode_event(event, &mut payload) {
+      Some(window_id) => window_id,
+      None => return Ok(()),
+    };
+    let window = match window_id {
+      Some(id) => {
+        let next = self.windows.len() as u32;
+        *self.windows.entry(id).or_insert(next)
+      }
+      None => NO_WINDOW,
+    };
+
+    let time = self.start.elapsed().as_micros() as u64;
+    self.writer.write_all(&time.to_le_bytes())?;
+    self.writer.write_all(&window.to_le_bytes())?;
+    self.writer.write_all(&payload)?;
+
+    if let Event::LoopDestroyed = event {
+      self.writer.flush()?;
+    }
+    Ok(())
+  }
+
+  /// Writes out any buffered records.
+  pub fn flush(&mut self) -> io::Result<()> {
+    self.writer.flush()
+  }
+}
+
+/// An event read back from a log.
+#[derive(Debug)]
+pub struct RecordedEvent {
+  /// Time elapsed between the start of the recording and the dispatch of the event.
+  pub time: Duration,
+  /// Number of the window the event was dispatched to, in the order windows first appeared.
+  pub window: Option<u32>,
+  /// The event, with dummy window and device ids.
+  pub event: Event<'static, ()>,
+}
+
+/// Reads the events of a log written by an [`EventRecorder`].
+pub struct Replay<R: Read> {
+  reader: R,
+}
+
+impl Replay<BufReader<File>> {
+  /// Opens the log at `path`.
+  pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    Self::new(BufReader::new(File::open(path)?))
+  }
+}
+
+impl<R: Read> Replay<R> {
+  /// Reads a log from `reader`, failing if it doesn't start with a supported header.
+  pub fn new(mut reader: R) -> io::Result<Self> {
+    let mut header = [0; 7];
+    reader.read_exact(&mut header)?;
+    if &header[..6] != MAGIC || header[6] != VERSION {
+      return Err(invalid_data("not a tao event log"));
+    }
+    Ok(Self { reader })
+  }
+
+  /// Calls `event_handler` with every event of the log, waiting between them as long as they
+  /// were apart when recorded, like a real event loop would.
+  pub fn run<F>(self, mut event_handler: F) -> io::Result<()>
+  where
+    F: FnMut(RecordedEvent),
+  {
+    let start = Instant::now();
+    for event in self {
+      let event = event?;
+      if let Some(delay) = event.time.checked_sub(start.elapsed()) {
+        std::thread::sleep(delay);
+      }
+      event_handler(event);
+    }
+    Ok(())
+  }
+
+  fn read_event(&mut self) -> io::Result<Option<RecordedEvent>> {
+    let mut time = [0; 8];
+    match self.reader.read_exact(&mut time) {
+      Ok(()) => (),
+      Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+      Err(e) => return Err(e),
+    }
+    let window = read_u32(&mut self.reader)?;
+    let event = decode_event(&mut self.reader)?;
+    Ok(Some(RecordedEvent {
+      time: Duration::from_micros(u64::from_le_bytes(time)),
+      window: if window == NO_WINDOW {
+        None
+      } else {
+        Some(window)
+      },
+      event,
+    }))
+  }
+}
+
+impl<R: Read> Iterator for Replay<R> {
+  type Item = io::Result<RecordedEvent>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.read_event().transpose()
+  }
+}
+
+/// Creates a recorder writing to the log named by [`EVENT_LOG_ENV`], if set.
+pub(crate) fn recorder_from_env() -> Option<EventRecorder<BufWriter<File>>> {
+  env::var_os(EVENT_LOG_ENV).and_then(|path| {
+    EventRecorder::create(&path)
+      .map_err(|e| warn!("Failed to create event log {:?}: {}", path, e))
+      .ok()
+  })
+}
+
+/// Wraps `event_handler` so that it records the events it receives with `recorder`, if any.
+pub(crate) fn record<T, F, R>(
+  mut recorder: R,
+  mut event_handler: F,
+) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+where
+  F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+  R: BorrowMut<Option<EventRecorder<BufWriter<File>>>>,
+{
+  move |event, target, control_flow| {
+    let recorder = recorder.borrow_mut();
+    if let Some(r) = recorder.as_mut() {
+      if let Err(e) = r.record(&event) {
+        warn!("Failed to record event, stopping the recording: {}", e);
+        *recorder = None;
+      }
+    }
+    event_handler(event, target, control_flow)
+  }
+}
+
+/// Encodes the kind and data of `event`, returning the window it targets, or `None` if the event
+/// isn't recorded.
+fn encode_event<T>(event: &Event<'_, T>, out: &mut Vec<u8>) -> Option<Option<WindowId>> {
+  match event {
+    Event::WindowEvent { window_id, event } => {
+      out.push(EVENT_WINDOW);
+      encode_window_event(event, out)?;
+      Some(Some(*window_id))
+    }
+    Event::DeviceEvent { event, .. } => {
+      out.push(EVENT_DEVICE);
+      encode_device_event(event, out)?;
+      Some(None)
+    }
+    Event::Suspended => {
+      out.push(EVENT_SUSPENDED);
+      Some(None)
+    }
+    Event::Resumed => {
+      out.push(EVENT_RESUMED);
+      Some(None)
+    }
+    Event::MainEventsCleared => {
+      out.push(EVENT_MAIN_EVENTS_CLEARED);
+      Some(None)
+    }
+    Event::RedrawRequested(window_id) => {
+      out.push(EVENT_REDRAW_REQUESTED);
+      Some(Some(*window_id))
+    }
+    Event::RedrawEventsCleared => {
+      out.push(EVENT_REDRAW_EVENTS_CLEARED);
+      Some(None)
+    }
+    Event::LoopDestroyed => {
+      out.push(EVENT_LOOP_DESTROYED);
+      Some(None)
+    }
+    _ => None,
+  }
+}
+
+#[allow(deprecated)]
+fn encode_window_event(event: &WindowEvent<'_>, out: &mut Vec<u8>) -> Option<()> {
+  match event {
+    WindowEvent::Resized(size) => {
+      out.push(WINDOW_RESIZED);
+      out.extend_from_slice(&size.width.to_le_bytes());
+      out.extend_from_slice(&size.height.to_le_bytes());
+    }
+    WindowEvent::Moved(position) => {
+      out.push(WINDOW_MOVED);
+      out.extend_from_slice(&position.x.to_le_bytes());
+      out.extend_from_slice(&position.y.to_le_bytes());
+    }
+    WindowEvent::CloseRequested => out.push(WINDOW_CLOSE_REQUESTED),
+    WindowEvent::Destroyed => out.push(WINDOW_DESTROYED),
+    WindowEvent::Focused(focused) => {
+      out.push(WINDOW_FOCUSED);
+      out.push(*focused as u8);
+    }
+    WindowEvent::ModifiersChanged(modifiers) => {
+      out.push(WINDOW_MODIFIERS_CHANGED);
+      out.extend_from_slice(&modifiers.bits().to_le_bytes());
+    }
+    WindowEvent::CursorMoved { position, .. } => {
+      out.push(WINDOW_CURSOR_MOVED);
+      out.extend_from_slice(&position.x.to_le_bytes());
+      out.extend_from_slice(&position.y.to_le_bytes());
+    }
+    WindowEvent::CursorEntered { .. } => out.push(WINDOW_CURSOR_ENTERED),
+    WindowEvent::CursorLeft { .. } => out.push(WINDOW_CURSOR_LEFT),
+    WindowEvent::MouseWheel {
+      delta,
+      phase,
+      momentum,
+      source,
+      ..
+    } => {
+      out.push(WINDOW_MOUSE_WHEEL);
+      encode_scroll_delta(delta, out)?;
+      out.push(match phase {
+        TouchPhase::Started => 0,
+        TouchPhase::Moved => 1,
+        TouchPhase::Ended => 2,
+        TouchPhase::Cancelled => 3,
+      });
+      out.push(*momentum as u8);
+      out.push(match source {
+        ScrollSource::Wheel => 0,
+        ScrollSource::Touchpad => 1,
+        _ => 2,
+      });
+    }
+    WindowEvent::MouseInput {
+      state,
+      button,
+      click_count,
+      ..
+    } => {
+      out.push(WINDOW_MOUSE_INPUT);
+      encode_element_state(*state, out);
+      out.extend_from_slice(&(button.index() as u32).to_le_bytes());
+      out.extend_from_slice(&click_count.to_le_bytes());
+    }
+    _ => return None,
+  }
+  Some(())
+}
+
+fn encode_device_event(event: &DeviceEvent, out: &mut Vec<u8>) -> Option<()> {
+  match event {
+    DeviceEvent::Added => out.push(DEVICE_ADDED),
+    DeviceEvent::Removed => out.push(DEVICE_REMOVED),
+    DeviceEvent::MouseMotion { delta } => {
+      out.push(DEVICE_MOUSE_MOTION);
+      out.extend_from_slice(&delta.0.to_le_bytes());
+      out.extend_from_slice(&delta.1.to_le_bytes());
+    }
+    DeviceEvent::MouseWheel { delta } => {
+      out.push(DEVICE_MOUSE_WHEEL);
+      encode_scroll_delta(delta, out)?;
+    }
+    DeviceEvent::Button { button, state } => {
+      out.push(DEVICE_BUTTON);
+      out.extend_from_slice(&button.to_le_bytes());
+      encode_element_state(*state, out);
+    }
+    _ => return None,
+  }
+  Some(())
+}
+
+fn encode_scroll_delta(delta: &MouseScrollDelta, out: &mut Vec<u8>) -> Option<()> {
+  match delta {
+    MouseScrollDelta::LineDelta(x, y) => {
+      out.push(0);
+      out.extend_from_slice(&(*x as f64).to_le_bytes());
+      out.extend_from_slice(&(*y as f64).to_le_bytes());
+    }
+    MouseScrollDelta::PixelDelta(position) => {
+      out.push(1);
+      out.extend_from_slice(&position.x.to_le_bytes());
+      out.extend_from_slice(&position.y.to_le_bytes());
+    }
+    #[allow(unreachable_patterns)]
+    _ => return None,
+  }
+  Some(())
+}
+
+fn encode_element_state(state: ElementState, out: &mut Vec<u8>) {
+  out.push(match state {
+    ElementState::Pressed => 0,
+    ElementState::Released => 1,
+  });
+}
+
+fn decode_event<R: Read>(reader: &mut R) -> io::Result<Event<'static, ()>> {
+  let event = match read_u8(reader)? {
+    EVENT_WINDOW => Event::WindowEvent {
+      window_id: unsafe { WindowId::dummy() },
+      event: decode_window_event(reader)?,
+    },
+    EVENT_DEVICE => Event::DeviceEvent {
+      device_id: unsafe { DeviceId::dummy() },
+      event: decode_device_event(reader)?,
+    },
+    EVENT_SUSPENDED => Event::Suspended,
+    EVENT_RESUMED => Event::Resumed,
+    EVENT_MAIN_EVENTS_CLEARED => Event::MainEventsCleared,
+    EVENT_REDRAW_REQUESTED => Event::RedrawRequested(unsafe { WindowId::dummy() }),
+    EVENT_REDRAW_EVENTS_CLEARED => Event::RedrawEventsCleared,
+    EVENT_LOOP_DESTROYED => Event::LoopDestroyed,
+    _ => return Err(invalid_data("unknown event")),
+  };
+  Ok(event)
+}
+
+#[allow(deprecated)]
+fn decode_window_event<R: Read>(reader: &mut R) -> io::Result<WindowEvent<'static>> {
+  let device_id = unsafe { DeviceId::dummy() };
+  let modifiers = ModifiersState::empty();
+  let event = match read_u8(reader)? {
+    WINDOW_RESIZED => WindowEvent::Resized(PhysicalSize::new(read_u32(reader)?, read_u32(reader)?)),
+    WINDOW_MOVED => WindowEvent::Moved(PhysicalPosition::new(
+      read_u32(reader)? as i32,
+      read_u32(reader)? as i32,
+    )),
+    WINDOW_CLOSE_REQUESTED => WindowEvent::CloseRequested,
+    WINDOW_DESTROYED => WindowEvent::Destroyed,
+    WINDOW_FOCUSED => WindowEvent::Focused(read_u8(reader)? != 0),
+    WINDOW_MODIFIERS_CHANGED => {
+      WindowEvent::ModifiersChanged(ModifiersState::from_bits_truncate(read_u32(reader)?))
+    }
+    WINDOW_CURSOR_MOVED => WindowEvent::CursorMoved {
+      device_id,
+      position: PhysicalPosition::new(read_f64(reader)?, read_f64(reader)?),
+      modifiers,
+    },
+    WINDOW_CURSOR_ENTERED => WindowEvent::CursorEntered { device_id },
+    WINDOW_CURSOR_LEFT => WindowEvent::CursorLeft { device_id },
+    WINDOW_MOUSE_WHEEL => WindowEvent::MouseWheel {
+      device_id,
+      delta: decode_scroll_delta(reader)?,
+      phase: match read_u8(reader)? {
+        0 => TouchPhase::Started,
+        1 => TouchPhase::Moved,
+        2 => TouchPhase::Ended,
+        3 => TouchPhase::Cancelled,
+        _ => return Err(invalid_data("unknown touch phase")),
+      },
+      momentum: read_u8(reader)? != 0,
+      source: match read_u8(reader)? {
+        0 => ScrollSource::Wheel,
+        1 => ScrollSource::Touchpad,
+        _ => ScrollSource::Unknown,
+      },
+      modifiers,
+    },
+    WINDOW_MOUSE_INPUT => WindowEvent::MouseInput {
+      device_id,
+      state: decode_element_state(reader)?,
+      button: MouseButton::from_index(read_u32(reader)? as u16),
+      click_count: read_u32(reader)?,
+      modifiers,
+    },
+    _ => return Err(invalid_data("unknown window event")),
+  };
+  Ok(event)
+}
+
+fn decode_device_event<R: Read>(reader: &mut R) -> io::Result<DeviceEvent> {
+  let event = match read_u8(reader)? {
+    DEVICE_ADDED => DeviceEvent::Added,
+    DEVICE_REMOVED => DeviceEvent::Removed,
+    DEVICE_MOUSE_MOTION => DeviceEvent::MouseMotion {
+      delta: (read_f64(reader)?, read_f64(reader)?),
+    },
+    DEVICE_MOUSE_WHEEL => DeviceEvent::MouseWheel {
+      delta: decode_scroll_delta(reader)?,
+    },
+    DEVICE_BUTTON => DeviceEvent::Button {
+      button: read_u32(reader)?,
+      state: decode_element_state(reader)?,
+    },
+    _ => return Err(invalid_data("unknown device event")),
+  };
+  Ok(event)
+}
+
+fn decode_scroll_delta<R: Read>(reader: &mut R) -> io::Result<MouseScrollDelta> {
+  let delta = match read_u8(reader)? {
+    0 => MouseScrollDelta::LineDelta(read_f64(reader)? as f32, read_f64(reader)? as f32),
+    1 => MouseScrollDelta::PixelDelta(PhysicalPosition::new(read_f64(reader)?, read_f64(reader)?)),
+    _ => return Err(invalid_data("unknown scroll delta")),
+  };
+  Ok(delta)
+}
+
+fn decode_element_state<R: Read>(reader: &mut R) -> io::Result<ElementState> {
+  match read_u8(reader)? {
+    0 => Ok(ElementState::Pressed),
+    1 => Ok(ElementState::Released),
+    _ => Err(invalid_data("unknown element state")),
+  }
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+  let mut bytes = [0; 1];
+  reader.read_exact(&mut bytes)?;
+  Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+  let mut bytes = [0; 4];
+  reader.read_exact(&mut bytes)?;
+  Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+  let mut bytes = [0; 8];
+  reader.read_exact(&mut bytes)?;
+  Ok(f64::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
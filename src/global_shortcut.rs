@@ -0,0 +1,134 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! System-wide keyboard shortcuts, which emit [`Event::GlobalShortcut`] even when none of the
+//! windows of the application is focused.
+//!
+//! ```no_run
+//! use tao::{
+//!   event::Event,
+//!   event_loop::{ControlFlow, EventLoop},
+//!   global_shortcut::Accelerator,
+//!   keyboard::{KeyCode, ModifiersState},
+//! };
+//!
+//! let event_loop = EventLoop::new();
+//! let shortcut = event_loop
+//!   .global_shortcut_manager()
+//!   .register(Accelerator::new(ModifiersState::SUPER | ModifiersState::SHIFT, KeyCode::KeyD))
+//!   .unwrap();
+//!
+//! event_loop.run(move |event, _, control_flow| {
+//!   *control_flow = ControlFlow::Wait;
+//!   if let Event::GlobalShortcut(id) = event {
+//!     if id == shortcut {
+//!       println!("Shortcut pressed");
+//!     }
+//!   }
+//! });
+//! ```
+//!
+//! [`Event::GlobalShortcut`]: crate::event::Event::GlobalShortcut
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{
+  error::ExternalError,
+  event_loop::EventLoopWindowTarget,
+  keyboard::{KeyCode, ModifiersState},
+};
+
+/// A key combined with modifiers, pressed to trigger a global shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Accelerator {
+  /// Modifiers which must be held, and no other.
+  pub mods: ModifiersState,
+  /// Physical key to press.
+  pub key: KeyCode,
+}
+
+impl Accelerator {
+  pub fn new(mods: ModifiersState, key: KeyCode) -> Self {
+    Self { mods, key }
+  }
+}
+
+/// Identifier of a global shortcut registered with [`GlobalShortcutManager::register`], unique for
+/// the lifetime of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlobalShortcutId(pub(crate) u64);
+
+impl GlobalShortcutId {
+  fn next() -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// Identifier of an accelerator added to a window with
+/// [`Window::add_accelerator`](crate::window::Window::add_accelerator), unique for the lifetime of
+/// the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AcceleratorId(pub(crate) u64);
+
+impl AcceleratorId {
+  pub(crate) fn next() -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// Registers the global shortcuts of an event loop, returned by
+/// [`EventLoopWindowTarget::global_shortcut_manager`].
+///
+/// Shortcuts stay registered until they're unregistered or the event loop is dropped.
+pub struct GlobalShortcutManager<'a, T: 'static> {
+  #[cfg_attr(any(target_os = "ios", target_os = "android"), allow(dead_code))]
+  pub(crate) target: &'a EventLoopWindowTarget<T>,
+}
+
+impl<T> GlobalShortcutManager<'_, T> {
+  /// Registers `accelerator` system-wide, so that pressing it emits [`Event::GlobalShortcut`] with
+  /// the returned id.
+  ///
+  /// Fails with [`ExternalError::Os`] if another application grabbed `accelerator` already, or if
+  /// `key` doesn't exist on the current keyboard layout.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Holding `accelerator` doesn't repeat the event.
+  /// - **Linux:** Only supported on X11, with the grab taken on the root window. Accelerators with
+  ///   Num Lock or Caps Lock on are grabbed as well.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported.
+  ///
+  /// [`Event::GlobalShortcut`]: crate::event::Event::GlobalShortcut
+  pub fn register(&self, accelerator: Accelerator) -> Result<GlobalShortcutId, ExternalError> {
+    let id = GlobalShortcutId::next();
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    {
+      self.target.p.register_global_shortcut(id, accelerator)?;
+      Ok(id)
+    }
+    #[cfg(any(target_os = "ios", target_os = "android"))]
+    {
+      let _ = (id, accelerator);
+      Err(ExternalError::NotSupported(
+        crate::error::NotSupportedError::new(),
+      ))
+    }
+  }
+
+  /// Unregisters the shortcut `id`. Does nothing if it was unregistered already.
+  pub fn unregister(&self, _id: GlobalShortcutId) {
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    self.target.p.unregister_global_shortcut(_id)
+  }
+
+  /// Unregisters every shortcut registered on this event loop.
+  pub fn unregister_all(&self) {
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    self.target.p.unregister_all_global_shortcuts()
+  }
+}
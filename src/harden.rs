@@ -0,0 +1,200 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of the inputs received from the platform, enabled by the `harden` feature.
+//!
+//! [`EventLoop::run`], [`EventLoopExtRunReturn::run_return`] and
+//! [`EventLoopExtPumpEvents::pump_events`] check every event before handing it over. Malformed values are clamped when there is a sane replacement, otherwise the event is
+//! dropped, and an [`Event::Anomaly`] tells the application about it either way.
+//!
+//! [`EventLoop::run`]: crate::event_loop::EventLoop::run
+//! [`EventLoopExtRunReturn::run_return`]: crate::platform::run_return::EventLoopExtRunReturn::run_return
+//! [`EventLoopExtPumpEvents::pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+
+use std::{borrow::BorrowMut, collections::HashSet};
+
+use crate::{
+  dpi::{PhysicalPosition, PhysicalSize},
+  event::{
+    AnomalyKind, AnomalyReport, DeviceEvent, DeviceId, Event, Force, MouseScrollDelta, Touch,
+    TouchPhase, WindowEvent,
+  },
+  event_loop::{ControlFlow, EventLoopWindowTarget},
+};
+
+/// What the validation keeps track of between events.
+#[derive(Default)]
+pub(crate) struct Sanitizer {
+  /// Touches which started and haven't ended yet.
+  touches: HashSet<(DeviceId, u64)>,
+}
+
+/// Wraps `event_handler` so that it only receives well-formed events, preceded by an
+/// `Event::Anomaly` for each one that wasn't.
+pub(crate) fn sanitize<T, F, S>(
+  mut sanitizer: S,
+  mut event_handler: F,
+) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+where
+  F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+  S: BorrowMut<Sanitizer>,
+{
+  move |mut event, target, control_flow| {
+    if let Some(report) = check_event(&mut event, &mut sanitizer.borrow_mut().touches) {
+      warn!("Malformed input from the platform: {:?}", report);
+      let dropped = report.dropped;
+      event_handler(Event::Anomaly(report), target, control_flow);
+      if dropped {
+        return;
+      }
+    }
+    event_handler(event, target, control_flow)
+  }
+}
+
+/// Clamps the malformed values of `event`, returning what was wrong with it, if anything.
+fn check_event<T>(
+  event: &mut Event<'_, T>,
+  touches: &mut HashSet<(DeviceId, u64)>,
+) -> Option<AnomalyReport> {
+  let (window_id, (kind, dropped)) = match event {
+    Event::WindowEvent { window_id, event } => {
+      (Some(*window_id), check_window_event(event, touches)?)
+    }
+    Event::DeviceEvent { event, .. } => (None, check_device_event(event)?),
+    _ => return None,
+  };
+  Some(AnomalyReport {
+    window_id,
+    kind,
+    dropped,
+  })
+}
+
+fn check_window_event(
+  event: &mut WindowEvent<'_>,
+  touches: &mut HashSet<(DeviceId, u64)>,
+) -> Option<(AnomalyKind, bool)> {
+  match event {
+    WindowEvent::Resized(size) => clamp_size(size).then(|| (AnomalyKind::NegativeSize, false)),
+    WindowEvent::ScaleFactorChanged {
+      scale_factor,
+      new_inner_size,
+    } => {
+      if !scale_factor.is_finite() || *scale_factor <= 0.0 {
+        Some((AnomalyKind::InvalidScaleFactor(*scale_factor), true))
+      } else {
+        clamp_size(new_inner_size).then(|| (AnomalyKind::NegativeSize, false))
+      }
+    }
+    WindowEvent::CursorMoved { position, .. } => {
+      (!is_finite(*position)).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::MouseWheel { delta, .. } => {
+      (!is_finite_delta(delta)).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::AxisMotion { value, .. } => {
+      (!value.is_finite()).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::TouchpadPressure { pressure, .. } => {
+      if pressure.is_finite() {
+        None
+      } else {
+        *pressure = 0.0;
+        Some((AnomalyKind::NonFiniteValue, false))
+      }
+    }
+    WindowEvent::TouchpadMagnify { delta, .. } => {
+      (!delta.is_finite()).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::TouchpadRotate { delta, .. } => {
+      (!delta.is_finite()).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::RawMouseMotion { delta: (x, y), .. } => {
+      (!x.is_finite() || !y.is_finite()).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    WindowEvent::Touch(touch) => check_touch(touch, touches),
+    WindowEvent::PenInput(pen) => {
+      let finite = [pen.pressure, pen.tilt_x, pen.tilt_y, pen.twist]
+        .iter()
+        .all(|value| value.is_finite());
+      (!is_finite(pen.position) || !finite).then(|| (AnomalyKind::NonFiniteValue, true))
+    }
+    _ => None,
+  }
+}
+
+fn check_touch(
+  touch: &mut Touch,
+  touches: &mut HashSet<(DeviceId, u64)>,
+) -> Option<(AnomalyKind, bool)> {
+  let key = (touch.device_id, touch.id);
+  let known = match touch.phase {
+    TouchPhase::Started => {
+      touches.insert(key);
+      true
+    }
+    TouchPhase::Moved => touches.contains(&key),
+    TouchPhase::Ended | TouchPhase::Cancelled => touches.remove(&key),
+  };
+  if !known {
+    return Some((AnomalyKind::UnknownTouchId(touch.id), true));
+  }
+  if !is_finite(touch.location) {
+    return Some((AnomalyKind::NonFiniteValue, true));
+  }
+  let finite_force = match touch.force {
+    Some(Force::Calibrated {
+      force,
+      max_possible_force,
+      altitude_angle,
+    }) => {
+      force.is_finite()
+        && max_possible_force.is_finite()
+        && altitude_angle.map_or(true, f64::is_finite)
+    }
+    Some(Force::Normalized(force)) => force.is_finite(),
+    None => true,
+  };
+  if finite_force {
+    None
+  } else {
+    touch.force = None;
+    Some((AnomalyKind::NonFiniteValue, false))
+  }
+}
+
+fn check_device_event(event: &DeviceEvent) -> Option<(AnomalyKind, bool)> {
+  let finite = match event {
+    DeviceEvent::MouseMotion { delta: (x, y) } => x.is_finite() && y.is_finite(),
+    DeviceEvent::MouseWheel { delta } => is_finite_delta(delta),
+    DeviceEvent::Motion { value, .. } => value.is_finite(),
+    _ => true,
+  };
+  (!finite).then(|| (AnomalyKind::NonFiniteValue, true))
+}
+
+/// Clamps the dimensions of `size` which wrapped around from a negative value to `0`, returning
+/// whether there were any.
+fn clamp_size(size: &mut PhysicalSize<u32>) -> bool {
+  let mut clamped = false;
+  for dimension in [&mut size.width, &mut size.height] {
+    if *dimension > i32::MAX as u32 {
+      *dimension = 0;
+      clamped = true;
+    }
+  }
+  clamped
+}
+
+fn is_finite(position: PhysicalPosition<f64>) -> bool {
+  position.x.is_finite() && position.y.is_finite()
+}
+
+fn is_finite_delta(delta: &MouseScrollDelta) -> bool {
+  match delta {
+    MouseScrollDelta::LineDelta(x, y) => x.is_finite() && y.is_finite(),
+    MouseScrollDelta::PixelDelta(position) => is_finite(*position),
+  }
+}
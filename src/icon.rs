@@ -3,7 +3,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::platform_impl::PlatformIcon;
-use std::{error::Error, fmt, io, mem};
+#[cfg(feature = "icon-loader")]
+use std::path::Path;
+#[cfg(target_os = "windows")]
+use std::path::PathBuf;
+use std::{error::Error, fmt, io, mem, time::Duration};
 
 #[repr(C)]
 #[derive(Debug)]
@@ -35,6 +39,17 @@ pub enum BadIcon {
   },
   /// Produced when underlying OS functionality failed to create the icon
   OsError(io::Error),
+  /// Produced when an icon file couldn't be read.
+  Io(io::Error),
+  /// Produced when an icon file isn't a valid `.png`, `.ico` or `.icns` file, or only contains
+  /// images in an unsupported encoding.
+  #[non_exhaustive]
+  Decode { reason: String },
+  /// Produced when an animated cursor is created without frames.
+  NoFrames,
+  /// Produced when an image which must have pixels has a zero width or height.
+  #[non_exhaustive]
+  ZeroSize { width: u32, height: u32 },
 }
 
 impl fmt::Display for BadIcon {
@@ -54,6 +69,13 @@ impl fmt::Display for BadIcon {
                 width, height, pixel_count, width_x_height,
             ),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
+            BadIcon::Io(e) => write!(f, "Failed to read the icon file: {:?}", e),
+            BadIcon::Decode { reason } => write!(f, "Failed to decode the icon: {}", reason),
+            BadIcon::NoFrames => write!(f, "An animated cursor needs at least one frame."),
+            BadIcon::ZeroSize { width, height } => write!(f,
+                "The specified dimensions ({:?}x{:?}) leave the image without any pixels.",
+                width, height,
+            ),
         }
   }
 }
@@ -114,6 +136,11 @@ mod constructors {
       let _ = RgbaIcon::from_rgba(rgba, width, height)?;
       Ok(NoIcon)
     }
+
+    pub fn add_variant(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+      let _ = RgbaIcon::from_rgba(rgba, width, height)?;
+      Ok(())
+    }
   }
 }
 
@@ -139,4 +166,179 @@ impl Icon {
       inner: PlatformIcon::from_rgba(rgba, width, height)?,
     })
   }
+
+  /// Adds an image of the same icon at another size, for example a 16x16 image for the titlebar
+  /// and a 48x48 image for the taskbar. The image closest to the size the system asks for is used.
+  pub fn with_variant(mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+    self.inner.add_variant(rgba, width, height)?;
+    Ok(self)
+  }
+
+  /// Loads an icon from a `.png`, `.ico` or `.icns` file, keeping every size the file contains.
+  ///
+  /// Images in `.ico` files can be PNG or uncompressed bitmaps, while only the PNG encoded images
+  /// of `.icns` files are loaded.
+  #[cfg(feature = "icon-loader")]
+  pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, BadIcon> {
+    let bytes = std::fs::read(path).map_err(BadIcon::Io)?;
+    Self::from_bytes(&bytes)
+  }
+
+  /// Loads an icon from the contents of a `.png`, `.ico` or `.icns` file, for example one
+  /// embedded with `include_bytes!`. See [`Icon::from_file`].
+  #[cfg(feature = "icon-loader")]
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, BadIcon> {
+    let mut images = crate::icon_loader::decode(bytes)?.into_iter();
+    // `decode` never returns an empty list.
+    let RgbaIcon {
+      rgba,
+      width,
+      height,
+    } = images.next().unwrap();
+    let mut icon = Icon::from_rgba(rgba, width, height)?;
+    for image in images {
+      icon = icon.with_variant(image.rgba, image.width, image.height)?;
+    }
+    Ok(icon)
+  }
+}
+
+/// A cursor image created from 32bpp RGBA data, used with
+/// [`Window::set_custom_cursor`](crate::window::Window::set_custom_cursor).
+///
+/// A cursor can hold several images of the same cursor drawn for different pixel densities. The
+/// first image defines the cursor's size at a scale factor of 1, and the image that best matches
+/// the window's scale factor and the user's cursor size setting is picked when the cursor is shown.
+///
+/// An animated cursor, created with [`CustomCursor::from_frames`], shows several cursors in a loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomCursor {
+  pub(crate) images: Vec<CursorImage>,
+  /// Frames of an animated cursor and how long each is shown, empty for a still cursor.
+  pub(crate) frames: Vec<(CustomCursor, Duration)>,
+  /// The `.cur` or `.ani` file the cursor is loaded from, see `CustomCursorExtWindows::from_path`.
+  #[cfg(target_os = "windows")]
+  pub(crate) path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CursorImage {
+  pub(crate) rgba: RgbaIcon,
+  pub(crate) hotspot_x: u32,
+  pub(crate) hotspot_y: u32,
+}
+
+impl CursorImage {
+  fn from_rgba(
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+  ) -> Result<Self, BadIcon> {
+    let rgba = RgbaIcon::from_rgba(rgba, width, height)?;
+    Ok(CursorImage {
+      hotspot_x: hotspot_x.min(width.saturating_sub(1)),
+      hotspot_y: hotspot_y.min(height.saturating_sub(1)),
+      rgba,
+    })
+  }
+}
+
+impl CustomCursor {
+  /// Creates a `CustomCursor` from 32bpp RGBA data, drawn for a scale factor of 1.
+  ///
+  /// The hotspot is the pixel of the image that points at the cursor position. The length of
+  /// `rgba` must be divisible by 4, and `width * height` must equal `rgba.len() / 4`. Otherwise,
+  /// this will return a `BadIcon` error.
+  pub fn from_rgba(
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+  ) -> Result<Self, BadIcon> {
+    Ok(CustomCursor {
+      images: vec![CursorImage::from_rgba(
+        rgba, width, height, hotspot_x, hotspot_y,
+      )?],
+      frames: Vec::new(),
+      #[cfg(target_os = "windows")]
+      path: None,
+    })
+  }
+
+  /// Creates an animated cursor, which shows each of `frames` for its delay, in a loop. The frames
+  /// of animated `frames` are shown in their place.
+  ///
+  /// Fails with [`BadIcon::NoFrames`] if `frames` is empty.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux:** The frames are swapped by a timer of the event loop, so a busy
+  ///   event loop delays the animation.
+  pub fn from_frames(frames: Vec<(CustomCursor, Duration)>) -> Result<Self, BadIcon> {
+    let frames: Vec<_> = frames
+      .into_iter()
+      .flat_map(|(cursor, delay)| match cursor.frames.is_empty() {
+        true => vec![(cursor, delay)],
+        false => cursor.frames,
+      })
+      .collect();
+    let first = frames.first().ok_or(BadIcon::NoFrames)?.0.clone();
+    Ok(CustomCursor {
+      images: first.images,
+      frames,
+      #[cfg(target_os = "windows")]
+      path: first.path,
+    })
+  }
+
+  /// Adds an image of the same cursor drawn for a higher pixel density, for example a 64x64 image
+  /// for a 32x32 cursor shown at a scale factor of 2. The hotspot is in the pixels of this image.
+  pub fn with_variant(
+    mut self,
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+  ) -> Result<Self, BadIcon> {
+    self.images.push(CursorImage::from_rgba(
+      rgba, width, height, hotspot_x, hotspot_y,
+    )?);
+    Ok(self)
+  }
+
+  /// Returns frame `index` of an animated cursor, or the cursor itself if it's still.
+  #[allow(dead_code)]
+  pub(crate) fn frame(&self, index: usize) -> &CustomCursor {
+    self.frames.get(index).map_or(self, |(frame, _)| frame)
+  }
+
+  /// Returns how long frame `index` of an animated cursor is shown, or `None` if it's still.
+  #[allow(dead_code)]
+  pub(crate) fn frame_delay(&self, index: usize) -> Option<Duration> {
+    self.frames.get(index).map(|(_, delay)| *delay)
+  }
+
+  /// Width of the cursor at a scale factor of 1.
+  #[allow(dead_code)]
+  pub(crate) fn base_width(&self) -> u32 {
+    self.images[0].rgba.width
+  }
+
+  /// Returns the smallest image at least as large as the cursor at `scale`, or the largest image
+  /// if none is.
+  #[allow(dead_code)]
+  pub(crate) fn image_for_scale(&self, scale: f64) -> &CursorImage {
+    let target = (self.base_width() as f64 * scale).round() as u32;
+    self
+      .images
+      .iter()
+      .filter(|image| image.rgba.width >= target)
+      .min_by_key(|image| image.rgba.width)
+      .or_else(|| self.images.iter().max_by_key(|image| image.rgba.width))
+      .unwrap()
+  }
 }
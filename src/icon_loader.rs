@@ -0,0 +1,318 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of `.png`, `.ico` and `.icns` files for [`Icon::from_file`](crate::window::Icon::from_file).
+
+use std::{cmp::Reverse, convert::TryInto};
+
+use crate::icon::{BadIcon, RgbaIcon};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const ICO_SIGNATURE: &[u8] = &[0, 0, 1, 0];
+const ICNS_SIGNATURE: &[u8] = b"icns";
+
+/// Largest width and height of an `.ico` bitmap, the format itself stops at 256 pixels.
+const MAX_BMP_DIMENSION: usize = 1024;
+
+/// Decodes every image of an icon file, largest first. The returned list is never empty.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Vec<RgbaIcon>, BadIcon> {
+  let mut images = if bytes.starts_with(PNG_SIGNATURE) {
+    vec![decode_png(bytes)?]
+  } else if bytes.starts_with(ICO_SIGNATURE) {
+    decode_ico(bytes)?
+  } else if bytes.starts_with(ICNS_SIGNATURE) {
+    decode_icns(bytes)?
+  } else {
+    return Err(decode_error("unrecognized file format"));
+  };
+  if images.is_empty() {
+    return Err(decode_error("the file doesn't contain any supported image"));
+  }
+  images.sort_by_key(|i| Reverse(i.width));
+  Ok(images)
+}
+
+fn decode_error(reason: impl ToString) -> BadIcon {
+  BadIcon::Decode {
+    reason: reason.to_string(),
+  }
+}
+
+fn truncated() -> BadIcon {
+  decode_error("the file is truncated")
+}
+
+fn too_large() -> BadIcon {
+  decode_error("the image is too large")
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> Result<u16, BadIcon> {
+  let bytes = bytes.get(offset..offset + 2).ok_or_else(truncated)?;
+  Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Result<u32, BadIcon> {
+  let bytes = bytes.get(offset..offset + 4).ok_or_else(truncated)?;
+  Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> Result<u32, BadIcon> {
+  let bytes = bytes.get(offset..offset + 4).ok_or_else(truncated)?;
+  Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn decode_png(bytes: &[u8]) -> Result<RgbaIcon, BadIcon> {
+  let mut decoder = png::Decoder::new(bytes);
+  decoder.set_transformations(png::Transformations::normalize_to_color8());
+  let mut reader = decoder.read_info().map_err(decode_error)?;
+  let mut buf = vec![0; reader.output_buffer_size()];
+  let info = reader.next_frame(&mut buf).map_err(decode_error)?;
+  buf.truncate(info.buffer_size());
+
+  let rgba = match info.color_type {
+    png::ColorType::Rgba => buf,
+    png::ColorType::Rgb => buf
+      .chunks_exact(3)
+      .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+      .collect(),
+    png::ColorType::GrayscaleAlpha => buf
+      .chunks_exact(2)
+      .flat_map(|p| [p[0], p[0], p[0], p[1]])
+      .collect(),
+    png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, u8::MAX]).collect(),
+    // Palettes are expanded by `normalize_to_color8`.
+    png::ColorType::Indexed => return Err(decode_error("unexpected indexed PNG")),
+  };
+  RgbaIcon::from_rgba(rgba, info.width, info.height)
+}
+
+/// Decodes an `.ico` file, whose images are either PNG files or bitmaps without a file header.
+fn decode_ico(bytes: &[u8]) -> Result<Vec<RgbaIcon>, BadIcon> {
+  let count = read_u16_le(bytes, 4)? as usize;
+  let mut images = Vec::with_capacity(count);
+  for i in 0..count {
+    let entry = 6 + i * 16;
+    let size = read_u32_le(bytes, entry + 8)? as usize;
+    let offset = read_u32_le(bytes, entry + 12)? as usize;
+    let data = offset
+      .checked_add(size)
+      .and_then(|end| bytes.get(offset..end))
+      .ok_or_else(truncated)?;
+    if data.starts_with(PNG_SIGNATURE) {
+      images.push(decode_png(data)?);
+    } else {
+      images.push(decode_bmp(data)?);
+    }
+  }
+  Ok(images)
+}
+
+/// Decodes the `BITMAPINFOHEADER` based bitmap of an `.ico` entry, which is followed by the color
+/// table, the bottom-up color rows and a 1bpp transparency mask.
+fn decode_bmp(bytes: &[u8]) -> Result<RgbaIcon, BadIcon> {
+  let header_size = read_u32_le(bytes, 0)? as usize;
+  let width = read_u32_le(bytes, 4)? as i32;
+  // The height covers both the color rows and the mask.
+  let height = read_u32_le(bytes, 8)? as i32 / 2;
+  let bit_count = read_u16_le(bytes, 14)?;
+  let compression = read_u32_le(bytes, 16)?;
+  let colors_used = read_u32_le(bytes, 32)? as usize;
+  if width <= 0 || height <= 0 {
+    return Err(decode_error("invalid bitmap dimensions"));
+  }
+  if width as usize > MAX_BMP_DIMENSION || height as usize > MAX_BMP_DIMENSION {
+    return Err(too_large());
+  }
+  if compression != 0 {
+    return Err(decode_error("compressed bitmaps aren't supported"));
+  }
+  let (width, height) = (width as usize, height as usize);
+
+  let palette_len = match bit_count {
+    1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+    1 | 4 | 8 => colors_used,
+    24 | 32 => 0,
+    _ => return Err(decode_error(format!("unsupported bit count {}", bit_count))),
+  };
+  // The dimensions are capped, only the offsets and lengths read from the file can overflow.
+  let palette = palette_len
+    .checked_mul(4)
+    .and_then(|len| header_size.checked_add(len))
+    .and_then(|end| bytes.get(header_size..end))
+    .ok_or_else(truncated)?;
+  let color_stride = (width * bit_count as usize + 31) / 32 * 4;
+  let color_start = header_size + palette.len();
+  let mask_stride = (width + 31) / 32 * 4;
+  let mask_start = color_stride
+    .checked_mul(height)
+    .and_then(|len| color_start.checked_add(len))
+    .ok_or_else(too_large)?;
+  if bytes.len() < mask_start {
+    return Err(truncated());
+  }
+  // Some 32bpp bitmaps leave out the mask since their alpha channel already holds it.
+  let mask = mask_start
+    .checked_add(mask_stride * height)
+    .and_then(|end| bytes.get(mask_start..end));
+
+  let mut rgba = vec![0u8; width * height * 4];
+  for y in 0..height {
+    // Rows are stored bottom-up.
+    let row = &bytes[color_start + (height - 1 - y) * color_stride..][..color_stride];
+    for x in 0..width {
+      let [b, g, r, a] = match bit_count {
+        32 => [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]],
+        24 => [row[x * 3], row[x * 3 + 1], row[x * 3 + 2], u8::MAX],
+        _ => {
+          let bit = x * bit_count as usize;
+          let shift = 8 - bit_count as usize - bit % 8;
+          let index = (row[bit / 8] >> shift) as usize & ((1 << bit_count) - 1);
+          let color = palette
+            .get(index * 4..index * 4 + 3)
+            .ok_or_else(truncated)?;
+          [color[0], color[1], color[2], u8::MAX]
+        }
+      };
+      let pixel = &mut rgba[(y * width + x) * 4..][..4];
+      pixel.copy_from_slice(&[r, g, b, a]);
+    }
+  }
+
+  // The mask is only authoritative when the bitmap has no alpha channel of its own.
+  let has_alpha = bit_count == 32 && rgba.chunks_exact(4).any(|p| p[3] != 0);
+  if let (false, Some(mask)) = (has_alpha, mask) {
+    for y in 0..height {
+      let row = &mask[(height - 1 - y) * mask_stride..][..mask_stride];
+      for x in 0..width {
+        let transparent = row[x / 8] & (0x80 >> (x % 8)) != 0;
+        rgba[(y * width + x) * 4 + 3] = if transparent { 0 } else { u8::MAX };
+      }
+    }
+  }
+
+  RgbaIcon::from_rgba(rgba, width as u32, height as u32)
+}
+
+/// Decodes the PNG encoded images of an `.icns` file. The older run-length encoded and JPEG 2000
+/// images are skipped.
+fn decode_icns(bytes: &[u8]) -> Result<Vec<RgbaIcon>, BadIcon> {
+  let len = (read_u32_be(bytes, 4)? as usize).min(bytes.len());
+  let mut images = Vec::new();
+  let mut offset = 8;
+  while offset + 8 <= len {
+    let element_len = read_u32_be(bytes, offset + 4)? as usize;
+    if element_len < 8 {
+      return Err(decode_error("invalid icns element"));
+    }
+    let data = offset
+      .checked_add(element_len)
+      .and_then(|end| bytes.get(offset + 8..end))
+      .ok_or_else(truncated)?;
+    if data.starts_with(PNG_SIGNATURE) {
+      let image = decode_png(data)?;
+      // The same image is often stored twice, as a 2x variant of the next smaller size.
+      if !images.iter().any(|i: &RgbaIcon| i.width == image.width) {
+        images.push(image);
+      }
+    }
+    offset += element_len;
+  }
+  Ok(images)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn decode_reason(bytes: &[u8]) -> String {
+    match decode(bytes) {
+      Err(BadIcon::Decode { reason }) => reason,
+      result => panic!("expected a decode error, got {:?}", result),
+    }
+  }
+
+  /// Builds an `.ico` file holding a single 32bpp bitmap, from BGRA pixels listed top-down.
+  fn ico_with_bmp(width: u32, height: u32, pixels: &[[u8; 4]]) -> Vec<u8> {
+    let mut bmp = Vec::new();
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&width.to_le_bytes());
+    bmp.extend_from_slice(&(height * 2).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&32u16.to_le_bytes());
+    bmp.extend_from_slice(&[0; 24]);
+    for row in pixels.chunks(width as usize).rev() {
+      row.iter().for_each(|pixel| bmp.extend_from_slice(pixel));
+    }
+    bmp.resize(
+      bmp.len() + (width as usize + 31) / 32 * 4 * height as usize,
+      0,
+    );
+
+    let mut ico = ICO_SIGNATURE.to_vec();
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&[width as u8, height as u8, 0, 0]);
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&32u16.to_le_bytes());
+    ico.extend_from_slice(&(bmp.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(&bmp);
+    ico
+  }
+
+  #[test]
+  fn decodes_ico_bitmap() {
+    let ico = ico_with_bmp(
+      2,
+      2,
+      &[
+        [0, 0, 255, 255],
+        [0, 255, 0, 255],
+        [255, 0, 0, 255],
+        [0, 0, 0, 0],
+      ],
+    );
+    let images = decode(&ico).unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!((images[0].width, images[0].height), (2, 2));
+    assert_eq!(
+      images[0].rgba,
+      [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 0, 0, 0, 0]
+    );
+  }
+
+  #[test]
+  fn decodes_png() {
+    let mut png = Vec::new();
+    {
+      let mut encoder = png::Encoder::new(&mut png, 1, 1);
+      encoder.set_color(png::ColorType::Rgb);
+      let mut writer = encoder.write_header().unwrap();
+      writer.write_image_data(&[1, 2, 3]).unwrap();
+    }
+    let images = decode(&png).unwrap();
+    assert_eq!(images[0].rgba, [1, 2, 3, 255]);
+  }
+
+  #[test]
+  fn rejects_truncated_headers() {
+    assert_eq!(decode_reason(ICO_SIGNATURE), "the file is truncated");
+    assert_eq!(decode_reason(ICNS_SIGNATURE), "the file is truncated");
+
+    let ico = ico_with_bmp(1, 1, &[[0; 4]]);
+    assert_eq!(decode_reason(&ico[..20]), "the file is truncated");
+    assert_eq!(decode_reason(&ico[..30]), "the file is truncated");
+    assert_eq!(
+      decode_reason(&ico[..ico.len() - 1]),
+      "the file is truncated"
+    );
+  }
+
+  #[test]
+  fn rejects_oversized_dimensions() {
+    let mut ico = ico_with_bmp(1, 1, &[[0; 4]]);
+    let width = (MAX_BMP_DIMENSION as u32 + 1).to_le_bytes();
+    ico[22 + 4..22 + 8].copy_from_slice(&width);
+    assert_eq!(decode_reason(&ico), "the image is too large");
+  }
+}
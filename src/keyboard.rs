@@ -73,7 +73,7 @@
 //
 // --------- END OF W3C SHORT NOTICE ---------------------------------------------------------------
 
-use std::{fmt, str::FromStr};
+use std::{fmt, str::FromStr, time::Duration};
 
 use crate::{
   error::OsError,
@@ -660,10 +660,22 @@ pub enum KeyCode {
 
 impl KeyCode {
   /// Return platform specific scancode.
+  ///
+  /// Returns `None` for keys which have no scancode on the platform, like
+  /// [`KeyCode::Unidentified`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Set 1 scancode, with `0xE000` added for extended keys.
+  /// - **macOS:** Virtual key code, e.g. `kVK_ANSI_A`.
+  /// - **Linux:** X11 keycode, the evdev code plus 8.
+  /// - **iOS / Android:** Unsupported, always returns `None`.
   pub fn to_scancode(self) -> Option<u32> {
     platform_keycode_to_scancode(self)
   }
-  /// Return `KeyCode` from platform scancode.
+  /// Return `KeyCode` from platform scancode, the reverse of [`KeyCode::to_scancode`].
+  ///
+  /// Unknown scancodes map to [`KeyCode::Unidentified`].
   pub fn from_scancode(scancode: u32) -> KeyCode {
     platform_keycode_from_scancode(scancode)
   }
@@ -1594,3 +1606,90 @@ pub enum KeyLocation {
   Right,
   Numpad,
 }
+
+/// The keyboard layout the user types with, returned by
+/// [`EventLoopWindowTarget::keyboard_layout`](crate::event_loop::EventLoopWindowTarget::keyboard_layout).
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyboardLayout {
+  /// Name of the layout as shown to the user, e.g. `"German"`.
+  pub name: String,
+  /// BCP 47 tag of the language the layout is for, e.g. `"de-DE"`.
+  pub language: Option<String>,
+}
+
+/// How the system repeats a held key, returned by
+/// [`EventLoopWindowTarget::key_repeat_settings`](crate::event_loop::EventLoopWindowTarget::key_repeat_settings).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyRepeatSettings {
+  /// Time a key is held before it starts repeating.
+  pub delay: Duration,
+  /// Time between two repeats.
+  pub interval: Duration,
+}
+
+/// Counts the repeats of the key held last, for [`KeyEvent::repeat_count`].
+///
+/// A press of the key which is held already is a repeat, so that platforms which don't flag
+/// repeats report them as well.
+///
+/// [`KeyEvent::repeat_count`]: crate::event::KeyEvent::repeat_count
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[derive(Debug, Default)]
+pub(crate) struct RepeatCounter {
+  held: Option<KeyCode>,
+  count: u32,
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+impl RepeatCounter {
+  /// Sets `repeat` and `repeat_count` of `event`.
+  pub(crate) fn count(&mut self, event: &mut crate::event::KeyEvent) {
+    let key = event.physical_key;
+    match event.state {
+      crate::event::ElementState::Pressed => {
+        let repeat = event.repeat || self.held == Some(key);
+        self.count = match (repeat, self.held == Some(key)) {
+          (true, true) => self.count.saturating_add(1),
+          (true, false) => 1,
+          (false, _) => 0,
+        };
+        self.held = Some(key);
+        event.repeat = repeat;
+        event.repeat_count = self.count;
+      }
+      crate::event::ElementState::Released => {
+        if self.held == Some(key) {
+          self.held = None;
+        }
+        event.repeat_count = 0;
+      }
+    }
+  }
+}
+
+/// Tracks whether a dead key waits for the next key, for [`KeyEvent::dead_key_pending`].
+///
+/// [`KeyEvent::dead_key_pending`]: crate::event::KeyEvent::dead_key_pending
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[derive(Debug, Default)]
+pub(crate) struct DeadKeyState {
+  pending: bool,
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+impl DeadKeyState {
+  /// Sets `dead_key_pending` of `event`, where `typed_text` tells whether the key typed text.
+  pub(crate) fn update(&mut self, event: &mut crate::event::KeyEvent, typed_text: bool) {
+    if event.state == crate::event::ElementState::Pressed {
+      if typed_text {
+        self.pending = false;
+      } else if let Key::Dead(_) = event.logical_key {
+        self.pending = true;
+      }
+    }
+    event.dead_key_pending = self.pending;
+  }
+}
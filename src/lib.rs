@@ -172,12 +172,37 @@ extern crate objc;
 pub mod dpi;
 #[macro_use]
 pub mod error;
+#[cfg(all(
+  feature = "conformance",
+  any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+  )
+))]
+pub mod conformance;
+pub mod data_format;
 pub mod event;
 pub mod event_loop;
+#[cfg(feature = "event-recorder")]
+pub mod event_recorder;
+pub mod global_shortcut;
+#[cfg(feature = "harden")]
+mod harden;
 mod icon;
+#[cfg(feature = "icon-loader")]
+mod icon_loader;
 pub mod keyboard;
 pub mod monitor;
+#[cfg(feature = "notifications")]
+pub mod notification;
 mod platform_impl;
+#[cfg(feature = "synthetic-input")]
+pub mod synthetic_input;
 
 pub mod window;
 
@@ -13,9 +13,13 @@
 //! [monitor_handle]: crate::monitor::MonitorHandle
 //! [loop_get]: crate::event_loop::EventLoopWindowTarget::available_monitors
 //! [window_get]: crate::window::Window::available_monitors
+use std::path::PathBuf;
+
 use crate::{
-  dpi::{PhysicalPosition, PhysicalSize},
+  dpi::{LogicalPosition, PhysicalPosition, PhysicalSize, Position},
+  error::{ExternalError, NotSupportedError},
   platform_impl,
+  window::Rect,
 };
 
 /// Describes a fullscreen video mode of a monitor.
@@ -127,6 +131,15 @@ impl MonitorHandle {
     self.inner.name()
   }
 
+  /// Returns whether this is the primary monitor of the system, the one
+  /// [`EventLoopWindowTarget::primary_monitor`] returns.
+  ///
+  /// [`EventLoopWindowTarget::primary_monitor`]: crate::event_loop::EventLoopWindowTarget::primary_monitor
+  #[inline]
+  pub fn is_primary(&self) -> bool {
+    self.inner.is_primary()
+  }
+
   /// Returns the monitor's resolution.
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
@@ -140,6 +153,48 @@ impl MonitorHandle {
     self.inner.position()
   }
 
+  /// Returns the area of the monitor where windows can be placed without overlapping the system
+  /// UI, i.e. without the taskbar, the Dock or the menu bar.
+  ///
+  /// The position and size of the rect are physical, the position being relative to the larger
+  /// full screen area like [`MonitorHandle::position`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux(Wayland):** The compositor doesn't tell the work area, the whole monitor is returned.
+  /// - **iOS / Android:** Returns the whole monitor.
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    self.inner.work_area()
+  }
+
+  /// Converts `position`, in the physical coordinates of the larger full screen area like
+  /// [`MonitorHandle::position`], to logical coordinates relative to the top-left corner of the
+  /// monitor, using the monitor's own scale factor.
+  ///
+  /// Mixed DPI setups don't have a single scale factor for the whole screen area, so a global
+  /// position can't be converted to logical coordinates directly without landing on the wrong
+  /// monitor.
+  #[inline]
+  pub fn to_local(&self, position: PhysicalPosition<i32>) -> LogicalPosition<f64> {
+    let origin = self.position();
+    PhysicalPosition::new(position.x - origin.x, position.y - origin.y)
+      .to_logical(self.scale_factor())
+  }
+
+  /// Converts `position`, relative to the top-left corner of the monitor, to physical coordinates
+  /// of the larger full screen area, which can be passed to
+  /// [`Window::set_outer_position`](crate::window::Window::set_outer_position). A logical
+  /// `position` is scaled with the monitor's own scale factor.
+  ///
+  /// This is the inverse of [`MonitorHandle::to_local`].
+  #[inline]
+  pub fn to_global<P: Into<Position>>(&self, position: P) -> PhysicalPosition<i32> {
+    let origin = self.position();
+    let position = position.into().to_physical::<i32>(self.scale_factor());
+    PhysicalPosition::new(origin.x + position.x, origin.y + position.y)
+  }
+
   /// Returns the scale factor that can be used to map logical pixels to physical pixels, and vice versa.
   ///
   /// See the [`dpi`](crate::dpi) module for more information.
@@ -152,6 +207,36 @@ impl MonitorHandle {
     self.inner.scale_factor()
   }
 
+  /// Returns the current refresh rate of the monitor, in millihertz.
+  ///
+  /// [`Event::MonitorRefreshRateChanged`] is emitted when it changes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS:** Returns the maximum refresh rate of the screen.
+  /// - **Android:** Unsupported.
+  ///
+  /// [`Event::MonitorRefreshRateChanged`]: crate::event::Event::MonitorRefreshRateChanged
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    self.inner.refresh_rate_millihertz()
+  }
+
+  /// Returns whether the monitor refreshes at a variable rate, following the frames presented
+  /// to it, like with G-Sync, FreeSync or ProMotion. `None` if it's unknown.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Needs macOS 12, compares the minimum and maximum refresh intervals of the screen.
+  /// - **iOS:** `true` for ProMotion screens, whose maximum refresh rate is above 60 Hz.
+  /// - **Linux:** Only on X11, whether the RandR output is `vrr_capable`, not whether the
+  ///   compositor enabled it.
+  /// - **Windows / Android / Linux(Wayland):** Unsupported.
+  #[inline]
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    self.inner.variable_refresh_rate()
+  }
+
   /// Returns all fullscreen video modes supported by this monitor.
   ///
   /// ## Platform-specific
@@ -160,4 +245,181 @@ impl MonitorHandle {
   pub fn video_modes(&self) -> impl Iterator<Item = VideoMode> {
     self.inner.video_modes()
   }
+
+  /// Switches the monitor to `video_mode`, one of its [`MonitorHandle::video_modes`], without
+  /// putting a window into exclusive fullscreen.
+  ///
+  /// The mode is only changed for the application: the previous one is restored by
+  /// [`MonitorHandle::reset_video_mode`], or by the system when the application exits.
+  ///
+  /// Fails with [`ExternalError::NotSupported`] if `video_mode` is one of another monitor.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS:** Sets the `currentMode` of the screen, the system picks the mode again once the
+  ///   application exits.
+  /// - **Android / Linux:** Unsupported.
+  #[inline]
+  pub fn set_video_mode(&self, video_mode: &VideoMode) -> Result<(), ExternalError> {
+    if video_mode.monitor() != *self {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    self.inner.set_video_mode(&video_mode.video_mode)
+  }
+
+  /// Restores the video mode the monitor had before [`MonitorHandle::set_video_mode`]. Does
+  /// nothing if it wasn't changed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / Linux:** Unsupported.
+  #[inline]
+  pub fn reset_video_mode(&self) -> Result<(), ExternalError> {
+    self.inner.reset_video_mode()
+  }
+
+  /// Returns the color capabilities of the monitor: its bit depth, whether HDR is enabled, its
+  /// peak luminance and the color space it's driven in.
+  ///
+  /// The information may change while the application runs, e.g. when the user turns HDR on or
+  /// off, so it should be queried again when it's needed rather than cached.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Read from the DXGI output of the monitor. HDR is reported as enabled when the
+  ///   output is driven in the Rec. 2020 PQ color space, and `hdr_headroom` is `None`.
+  /// - **macOS:** `hdr_enabled` is `true` when the screen can show extended dynamic range, and
+  ///   `hdr_headroom` is `maximumExtendedDynamicRangeColorComponentValue`. `max_luminance` is
+  ///   `None`.
+  /// - **iOS:** `bits_per_color` and `max_luminance` are `None`. HDR is only reported since iOS 16,
+  ///   with `hdr_headroom` being `currentEDRHeadroom`.
+  /// - **Linux:** Only `bits_per_color` is reported, from the system visual.
+  /// - **Android:** Unsupported, returns the default `ColorInfo`.
+  #[inline]
+  pub fn color_info(&self) -> ColorInfo {
+    self.inner.color_info()
+  }
+
+  /// Returns the ICC profile the system assigned to the monitor, for color-managed rendering.
+  ///
+  /// [`Event::MonitorIccProfileChanged`] is emitted when it changes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The path of the profile, from `GetICMProfileW`.
+  /// - **macOS:** The data of the profile of the screen's color space.
+  /// - **Linux:** The data of the `_ICC_PROFILE` property of the root window, set by the color
+  ///   management daemon of the desktop.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported.
+  ///
+  /// [`Event::MonitorIccProfileChanged`]: crate::event::Event::MonitorIccProfileChanged
+  #[inline]
+  pub fn icc_profile(&self) -> Option<IccProfile> {
+    self.inner.icc_profile()
+  }
+
+  /// Returns the three-letter PNP ID of the monitor's manufacturer, e.g. `"DEL"` or `"APP"`.
+  ///
+  /// Together with [`MonitorHandle::model`] and [`MonitorHandle::serial_number`], it identifies
+  /// the physical monitor, unlike [`MonitorHandle::name`] which may change when monitors are
+  /// plugged in or out.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Read from the display configuration of the monitor.
+  /// - **macOS:** Read from `CGDisplayVendorNumber`.
+  /// - **Linux:** Read from the EDID of the RandR output.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported.
+  #[inline]
+  pub fn manufacturer(&self) -> Option<String> {
+    self.inner.manufacturer()
+  }
+
+  /// Returns the product code the manufacturer assigned to the monitor's model.
+  ///
+  /// ## Platform-specific
+  ///
+  /// See [`MonitorHandle::manufacturer`].
+  #[inline]
+  pub fn model(&self) -> Option<u32> {
+    self.inner.model()
+  }
+
+  /// Returns the serial number of the monitor, `None` if its manufacturer didn't set one.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Read from the EDID the system stored in the registry.
+  /// - **macOS:** Read from `CGDisplaySerialNumber`, so only numeric serial numbers are reported.
+  /// - **Linux:** Read from the EDID of the RandR output.
+  /// - **iOS / Android / Linux(Wayland):** Unsupported.
+  #[inline]
+  pub fn serial_number(&self) -> Option<String> {
+    self.inner.serial_number()
+  }
+}
+
+/// Color capabilities of a monitor, returned by [`MonitorHandle::color_info`].
+///
+/// Fields which the platform doesn't report are `None`, `false` or [`ColorSpace::Unknown`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ColorInfo {
+  /// Number of bits per color component the monitor is driven with, e.g. 8 or 10.
+  pub bits_per_color: Option<u32>,
+  /// Whether the monitor currently shows high dynamic range content.
+  pub hdr_enabled: bool,
+  /// Peak luminance of the monitor, in nits.
+  pub max_luminance: Option<f32>,
+  /// Largest value of a color component the monitor can show right now, where 1.0 is the
+  /// brightness of standard dynamic range white.
+  pub hdr_headroom: Option<f32>,
+  /// Color space the monitor is driven in.
+  pub color_space: ColorSpace,
+}
+
+/// Color space of a monitor, see [`ColorInfo::color_space`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+  /// sRGB, the standard gamut of most monitors.
+  Srgb,
+  /// Display P3, the wide gamut of most Apple displays.
+  DisplayP3,
+  /// Rec. 2020, the wide gamut of HDR video.
+  Rec2020,
+  /// The platform doesn't report it, or it's none of the above.
+  Unknown,
+}
+
+impl Default for ColorSpace {
+  fn default() -> Self {
+    ColorSpace::Unknown
+  }
+}
+
+/// ICC profile of a monitor, returned by [`MonitorHandle::icc_profile`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IccProfile {
+  /// Path of the profile on disk.
+  Path(PathBuf),
+  /// Content of the profile.
+  Data(Vec<u8>),
+}
+
+/// Orders `monitors` the same way on every platform: the primary monitor first, then the others
+/// from left to right and from top to bottom.
+pub(crate) fn sorted(
+  monitors: impl IntoIterator<Item = platform_impl::MonitorHandle>,
+) -> impl Iterator<Item = MonitorHandle> {
+  let mut monitors: Vec<_> = monitors
+    .into_iter()
+    .map(|inner| MonitorHandle { inner })
+    .collect();
+  monitors.sort_by_cached_key(|monitor| {
+    let position = monitor.position();
+    (!monitor.is_primary(), position.x, position.y)
+  });
+  monitors.into_iter()
 }
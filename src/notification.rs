@@ -0,0 +1,192 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Native desktop notifications, enabled by the `notifications` feature.
+//!
+//! A [`Notifier`] shows notifications from any thread, and clicking one of them emits
+//! [`Event::NotificationActivated`] on the event loop the notifier was created by, with the window
+//! the notification was shown for.
+//!
+//! ```no_run
+//! use tao::{
+//!   event::Event,
+//!   event_loop::{ControlFlow, EventLoop},
+//!   notification::Notification,
+//!   window::WindowBuilder,
+//! };
+//!
+//! let event_loop = EventLoop::new();
+//! let window = WindowBuilder::new().build(&event_loop).unwrap();
+//! let notifier = event_loop.create_notifier();
+//! notifier
+//!   .show(&Notification::new("Download finished").with_body("tao.zip").with_window(window.id()))
+//!   .unwrap();
+//!
+//! event_loop.run(move |event, _, control_flow| {
+//!   *control_flow = ControlFlow::Wait;
+//!   if let Event::NotificationActivated { window_id: Some(window_id), .. } = event {
+//!     if window_id == window.id() {
+//!       window.set_focus();
+//!     }
+//!   }
+//! });
+//! ```
+//!
+//! ## Platform-specific
+//!
+//! - **Windows:** Shown as toasts. The application user model id of the process is used if it set
+//!   one, the one of PowerShell otherwise, since toasts need one which is registered.
+//! - **macOS:** Shown with `NSUserNotificationCenter`, which is only available to applications
+//!   running from a bundle.
+//! - **Linux:** Shown through the `org.freedesktop.Notifications` D-Bus service.
+//! - **iOS / Android:** Unsupported.
+//!
+//! [`Event::NotificationActivated`]: crate::event::Event::NotificationActivated
+
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+};
+
+use crate::{
+  error::ExternalError,
+  event::Event,
+  event_loop::{ControlFlow, EventLoopProxy, EventLoopWindowTarget},
+  platform_impl,
+  window::WindowId,
+};
+
+/// A notification to show with [`Notifier::show`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+  pub(crate) title: String,
+  pub(crate) body: String,
+  pub(crate) window_id: Option<WindowId>,
+}
+
+impl Notification {
+  pub fn new<S: Into<String>>(title: S) -> Self {
+    Self {
+      title: title.into(),
+      body: String::new(),
+      window_id: None,
+    }
+  }
+
+  /// Sets the text shown below the title.
+  pub fn with_body<S: Into<String>>(mut self, body: S) -> Self {
+    self.body = body.into();
+    self
+  }
+
+  /// Sets the window the notification is about, reported by [`Event::NotificationActivated`].
+  ///
+  /// [`Event::NotificationActivated`]: crate::event::Event::NotificationActivated
+  pub fn with_window(mut self, window_id: WindowId) -> Self {
+    self.window_id = Some(window_id);
+    self
+  }
+}
+
+/// Identifier of a notification shown with [`Notifier::show`], unique for the lifetime of the
+/// process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NotificationId(pub(crate) u64);
+
+impl NotificationId {
+  fn next() -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// Called by the platform, from any thread, with the notification the user clicked.
+pub(crate) type ActivationHandler = Box<dyn Fn(NotificationId) + Send + Sync>;
+
+/// A click on a notification, carried to the event loop as a typed user event.
+pub(crate) struct Activated {
+  id: NotificationId,
+  window_id: Option<WindowId>,
+}
+
+/// Shows notifications, created with [`EventLoop::create_notifier`].
+///
+/// [`EventLoop::create_notifier`]: crate::event_loop::EventLoop::create_notifier
+#[derive(Clone)]
+pub struct Notifier {
+  inner: Arc<platform_impl::Notifier>,
+  /// The window of each notification which wasn't clicked yet.
+  windows: Arc<Mutex<HashMap<NotificationId, Option<WindowId>>>>,
+}
+
+impl Notifier {
+  pub(crate) fn new<T>(
+    target: &EventLoopWindowTarget<T>,
+    proxy: EventLoopProxy<Activated>,
+  ) -> Self {
+    let windows: Arc<Mutex<HashMap<NotificationId, Option<WindowId>>>> = Default::default();
+    let windows_ = windows.clone();
+    let proxy = Mutex::new(proxy);
+    let inner = platform_impl::Notifier::new(
+      &target.p,
+      Box::new(move |id: NotificationId| {
+        if let Some(window_id) = windows_.lock().unwrap().remove(&id) {
+          let _ = proxy
+            .lock()
+            .unwrap()
+            .send_event(Activated { id, window_id });
+        }
+      }),
+    );
+    Self {
+      inner: Arc::new(inner),
+      windows,
+    }
+  }
+
+  /// Shows `notification`, returning the id [`Event::NotificationActivated`] reports when it's
+  /// clicked.
+  ///
+  /// Fails with [`ExternalError::NotSupported`] where notifications are unavailable, and with
+  /// [`ExternalError::Os`] if the system refused to show it.
+  ///
+  /// [`Event::NotificationActivated`]: crate::event::Event::NotificationActivated
+  pub fn show(&self, notification: &Notification) -> Result<NotificationId, ExternalError> {
+    let id = NotificationId::next();
+    self
+      .windows
+      .lock()
+      .unwrap()
+      .insert(id, notification.window_id);
+    if let Err(e) = self.inner.show(id, notification) {
+      self.windows.lock().unwrap().remove(&id);
+      return Err(e);
+    }
+    Ok(id)
+  }
+}
+
+/// Wraps `event_handler` so that the clicks sent by the notifiers are dispatched as
+/// `Event::NotificationActivated`.
+pub(crate) fn deliver<T, F>(
+  mut event_handler: F,
+) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+where
+  F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+{
+  move |event, target, control_flow| match event {
+    Event::TypedUserEvent(event) => match event.downcast::<Activated>() {
+      Ok(Activated { id, window_id }) => event_handler(
+        Event::NotificationActivated { id, window_id },
+        target,
+        control_flow,
+      ),
+      Err(event) => event_handler(Event::TypedUserEvent(event), target, control_flow),
+    },
+    event => event_handler(event, target, control_flow),
+  }
+}
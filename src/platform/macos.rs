@@ -7,6 +7,7 @@
 use std::os::raw::c_void;
 
 use crate::{
+  data_format::DataFormat,
   dpi::{LogicalSize, Position},
   event_loop::{EventLoop, EventLoopWindowTarget},
   monitor::MonitorHandle,
@@ -43,6 +44,24 @@ pub trait WindowExtMacOS {
   /// space or taking control over the entire monitor.
   fn set_simple_fullscreen(&self, fullscreen: bool) -> bool;
 
+  /// Returns how [`Window::set_fullscreen`] puts the window in borderless fullscreen.
+  fn fullscreen_mode(&self) -> FullscreenMode;
+
+  /// Sets how [`Window::set_fullscreen`] puts the window in borderless fullscreen. The new mode
+  /// applies the next time the window enters fullscreen.
+  fn set_fullscreen_mode(&self, mode: FullscreenMode);
+
+  /// Sets the level of the window, which windows of a higher level are always above.
+  ///
+  /// Windows above [`WindowLevel::Normal`] can also be shown over the fullscreen windows of other
+  /// applications, which makes overlays like volume HUDs or capture toolbars possible. To follow
+  /// the user to the Space of such a window, combine it with
+  /// [`Window::set_visible_on_all_workspaces`].
+  ///
+  /// This is the same level [`Window::set_always_on_top`] and [`Window::set_always_on_bottom`]
+  /// set, the last call wins.
+  fn set_window_level(&self, level: WindowLevel);
+
   /// Returns whether or not the window has shadow.
   fn has_shadow(&self) -> bool;
 
@@ -74,6 +93,62 @@ pub trait WindowExtMacOS {
 
   /// Returns the window's tabbing identifier.
   fn tabbing_identifier(&self) -> String;
+
+  /// Whether to hide the window from the dock, the Window menu and window cycling.
+  ///
+  /// The dock icon belongs to the application, so it is only hidden while every window skips the
+  /// taskbar, by switching the activation policy to [`ActivationPolicy::Accessory`].
+  /// [`Event::DockIconVisibilityChanged`] is emitted whenever tao shows or hides it.
+  ///
+  /// [`Event::DockIconVisibilityChanged`]: crate::event::Event::DockIconVisibilityChanged
+  fn set_skip_taskbar(&self, skip: bool);
+}
+
+/// How a window enters borderless fullscreen, see [`WindowExtMacOS::set_fullscreen_mode`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FullscreenMode {
+  /// The window moves to a Space of its own with the system animation, like with its green
+  /// button.
+  Native,
+  /// The window covers the screen in the current Space right away, without its title bar and with
+  /// the dock and menu bar hidden, see [`WindowExtMacOS::set_simple_fullscreen`].
+  /// [`Fullscreen::Exclusive`] and [`Fullscreen::Spanning`] aren't affected.
+  ///
+  /// [`Fullscreen::Exclusive`]: crate::window::Fullscreen::Exclusive
+  /// [`Fullscreen::Spanning`]: crate::window::Fullscreen::Spanning
+  Simple,
+}
+
+impl Default for FullscreenMode {
+  fn default() -> Self {
+    Self::Native
+  }
+}
+
+/// The level of a window, see [`WindowExtMacOS::set_window_level`]. Each level is above the ones
+/// before it.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowLevel {
+  /// The level of the windows of applications.
+  Normal,
+  /// The level of floating palettes, the one of [`Window::set_always_on_top`].
+  Floating,
+  /// The level of modal panels.
+  ModalPanel,
+  /// The level of the status items of the menu bar.
+  Status,
+  /// The level of pop-up menus.
+  PopUpMenu,
+  /// The level of screen savers, above almost everything.
+  ScreenSaver,
+}
+
+impl Default for WindowLevel {
+  fn default() -> Self {
+    Self::Normal
+  }
 }
 
 impl WindowExtMacOS for Window {
@@ -97,6 +172,21 @@ impl WindowExtMacOS for Window {
     self.window.set_simple_fullscreen(fullscreen)
   }
 
+  #[inline]
+  fn fullscreen_mode(&self) -> FullscreenMode {
+    self.window.fullscreen_mode()
+  }
+
+  #[inline]
+  fn set_fullscreen_mode(&self, mode: FullscreenMode) {
+    self.window.set_fullscreen_mode(mode)
+  }
+
+  #[inline]
+  fn set_window_level(&self, level: WindowLevel) {
+    self.window.set_window_level(level)
+  }
+
   #[inline]
   fn has_shadow(&self) -> bool {
     self.window.has_shadow()
@@ -141,6 +231,11 @@ impl WindowExtMacOS for Window {
   fn tabbing_identifier(&self) -> String {
     self.window.tabbing_identifier()
   }
+
+  #[inline]
+  fn set_skip_taskbar(&self, skip: bool) {
+    self.window.set_skip_taskbar(skip)
+  }
 }
 
 /// Corresponds to `NSApplicationActivationPolicy`.
@@ -187,10 +282,14 @@ pub trait WindowBuilderExtMacOS {
   /// Enables click-and-drag behavior for the entire window, not just the titlebar.
   fn with_movable_by_window_background(self, movable_by_window_background: bool) -> WindowBuilder;
   /// Makes the titlebar transparent and allows the content to appear behind it.
+  ///
+  /// See also [`WindowBuilder::with_title_bar_style`], which does the same on every platform.
   fn with_titlebar_transparent(self, titlebar_transparent: bool) -> WindowBuilder;
   /// Hides the window title.
   fn with_title_hidden(self, title_hidden: bool) -> WindowBuilder;
   /// Hides the window titlebar.
+  ///
+  /// See also [`WindowBuilder::with_title_bar_style`], which does the same on every platform.
   fn with_titlebar_hidden(self, titlebar_hidden: bool) -> WindowBuilder;
   /// Hides the window titlebar buttons.
   fn with_titlebar_buttons_hidden(self, titlebar_buttons_hidden: bool) -> WindowBuilder;
@@ -209,6 +308,11 @@ pub trait WindowBuilderExtMacOS {
   ///
   /// [tabbing identifier]: <https://developer.apple.com/documentation/appkit/nswindow/1644704-tabbingidentifier>
   fn with_tabbing_identifier(self, identifier: &str) -> WindowBuilder;
+  /// Whether to hide the window from the dock. See [`WindowExtMacOS::set_skip_taskbar`].
+  fn with_skip_taskbar(self, skip: bool) -> WindowBuilder;
+  /// Sets how the window enters borderless fullscreen. See
+  /// [`WindowExtMacOS::set_fullscreen_mode`].
+  fn with_fullscreen_mode(self, mode: FullscreenMode) -> WindowBuilder;
 }
 
 impl WindowBuilderExtMacOS for WindowBuilder {
@@ -295,6 +399,18 @@ impl WindowBuilderExtMacOS for WindowBuilder {
       .replace(tabbing_identifier.into());
     self
   }
+
+  #[inline]
+  fn with_skip_taskbar(mut self, skip: bool) -> WindowBuilder {
+    self.platform_specific.skip_taskbar = skip;
+    self
+  }
+
+  #[inline]
+  fn with_fullscreen_mode(mut self, mode: FullscreenMode) -> WindowBuilder {
+    self.platform_specific.fullscreen_mode = mode;
+    self
+  }
 }
 
 pub trait EventLoopExtMacOS {
@@ -317,6 +433,19 @@ pub trait EventLoopExtMacOS {
   /// [`run`](crate::event_loop::EventLoop::run) or
   /// [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
   fn set_activate_ignoring_other_apps(&mut self, ignore: bool);
+
+  /// Sets whether the event loop delivers events in every run loop mode marked as common, or
+  /// only in `NSDefaultRunLoopMode`. It is `true` by default.
+  ///
+  /// The observers and timers of the event loop are added to the common modes, which include
+  /// `NSEventTrackingRunLoopMode` and `NSModalPanelRunLoopMode`. When disabled, they don't deliver
+  /// events while the run loop is in another mode than `NSDefaultRunLoopMode`, and events queue
+  /// up until it goes back to it, which can be useful for applications whose event handler must
+  /// not run from within a nested loop.
+  ///
+  /// Nested loops running in modes which aren't common aren't observed either way, so events are
+  /// only delivered once they return.
+  fn set_common_modes(&mut self, enabled: bool);
 }
 
 impl<T> EventLoopExtMacOS for EventLoop<T> {
@@ -333,6 +462,11 @@ impl<T> EventLoopExtMacOS for EventLoop<T> {
       get_aux_state_mut(&**self.event_loop.delegate).activate_ignoring_other_apps = ignore;
     }
   }
+
+  #[inline]
+  fn set_common_modes(&mut self, enabled: bool) {
+    self.event_loop.set_common_modes(enabled);
+  }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to MacOS.
@@ -354,6 +488,20 @@ impl MonitorHandleExtMacOS for MonitorHandle {
   }
 }
 
+/// Additional methods on `DataFormat` that are specific to MacOS.
+pub trait DataFormatExtMacOS {
+  /// Returns the UTI of the format, the type of pasteboard items for the clipboard and drag and
+  /// drop.
+  fn uti(&self) -> &str;
+}
+
+impl DataFormatExtMacOS for DataFormat {
+  #[inline]
+  fn uti(&self) -> &str {
+    self.inner.uti()
+  }
+}
+
 /// Additional methods on `EventLoopWindowTarget` that are specific to macOS.
 pub trait EventLoopWindowTargetExtMacOS {
   /// Hide the entire application. In most applications this is typically triggered with Command-H.
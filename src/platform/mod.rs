@@ -16,6 +16,8 @@
 //! And the following platform-specific module:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `run_async` (available on all platforms)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
@@ -23,6 +25,8 @@ pub mod android;
 pub mod ios;
 pub mod linux;
 pub mod macos;
+pub mod pump_events;
+pub mod run_async;
 pub mod run_return;
 pub mod unix;
 pub mod windows;
@@ -0,0 +1,73 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(not(target_os = "ios"))]
+
+use std::time::Duration;
+
+use crate::{
+  event::Event,
+  event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+  platform::run_return::ExitReason,
+};
+
+/// Additional methods on `EventLoop` for applications which own their main loop.
+pub trait EventLoopExtPumpEvents {
+  /// A type provided by the user that can be passed through `Event::UserEvent`.
+  type UserEvent;
+
+  /// Processes the pending events of the `tao` event loop and returns control to the caller.
+  ///
+  /// Each call dispatches at most one iteration of the event loop, from `Event::NewEvents` to
+  /// `Event::RedrawEventsCleared`. If no event is pending, it waits for one for up to `timeout`,
+  /// forever if `None`, and returns without dispatching anything if none came. The first call
+  /// starts with `Event::NewEvents(StartCause::Init)`.
+  ///
+  /// `control_flow` carries over from one call to the next. `ControlFlow::Poll` and an elapsed
+  /// `ControlFlow::WaitUntil` end the wait early, like they do with `run`. Once it's set to
+  /// `ControlFlow::ExitWithCode`, `Event::LoopDestroyed` is dispatched and
+  /// [`PumpStatus::Exit`] is returned. The event loop can be pumped again after that, in which case
+  /// it starts over.
+  ///
+  /// Like [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return), this
+  /// doesn't return while a window is being resized or moved on Windows and macOS.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** A `timeout` shorter than the one set by `control_flow` dispatches an iteration
+  ///   with `StartCause::WaitCancelled` when it elapses.
+  /// - **iOS:** Unavailable, `UIApplicationMain` never returns. Use `run` instead.
+  fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow);
+}
+
+/// What [`EventLoopExtPumpEvents::pump_events`] ended with.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PumpStatus {
+  /// The event loop keeps running, call `pump_events` again.
+  Continue,
+  /// The event loop exited.
+  Exit(ExitReason),
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+  type UserEvent = T;
+
+  fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
+  {
+    #[cfg(feature = "event-recorder")]
+    let event_handler = crate::event_recorder::record(&mut self.recorder, event_handler);
+    #[cfg(feature = "harden")]
+    let event_handler = crate::harden::sanitize(&mut self.sanitizer, event_handler);
+    #[cfg(feature = "synthetic-input")]
+    let event_handler = crate::synthetic_input::inject(event_handler);
+    #[cfg(feature = "notifications")]
+    let event_handler = crate::notification::deliver(event_handler);
+    self.event_loop.pump_events(timeout, event_handler)
+  }
+}
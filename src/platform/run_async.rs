@@ -0,0 +1,105 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  task::{Context, Wake, Waker},
+};
+
+use crate::{
+  event::{Event, StartCause},
+  event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget},
+};
+
+/// Additional methods on `EventLoop` to drive a future on the event loop's thread.
+pub trait EventLoopExtAsync {
+  /// A type provided by the user that can be passed through `Event::UserEvent`.
+  type UserEvent;
+
+  /// Same as `run`, but also polls `future` on the event loop's thread.
+  ///
+  /// `future` is first polled after `Event::NewEvents(StartCause::Init)` was dispatched, then
+  /// every time its waker is woken, between the events of the platform. The waker sends an event
+  /// through an `EventLoopProxy`, so the event loop can keep waiting with `ControlFlow::Wait`
+  /// rather than polling. Once `future` completes, `control_flow` is set to `ControlFlow::Exit`.
+  ///
+  /// Futures relying on a reactor, like the I/O and timers of `tokio`, need it to run on another
+  /// thread, e.g. by entering the handle of a multi-threaded runtime before calling this.
+  fn run_async<Fut, F>(self, future: Fut, event_handler: F) -> !
+  where
+    Fut: 'static + Future<Output = ()>,
+    F: 'static
+      + FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow);
+}
+
+/// Sent by a `ProxyWaker` to have the future polled.
+struct Wakeup;
+
+/// Wakes the future of `run_async` up through the event loop.
+struct ProxyWaker {
+  proxy: EventLoopProxy<Wakeup>,
+  /// Whether a `Wakeup` is pending already, so that only one is sent until the next poll.
+  woken: AtomicBool,
+}
+
+impl Wake for ProxyWaker {
+  fn wake(self: Arc<Self>) {
+    self.wake_by_ref();
+  }
+
+  fn wake_by_ref(self: &Arc<Self>) {
+    if !self.woken.swap(true, Ordering::AcqRel) {
+      let _ = self.proxy.send_event(Wakeup);
+    }
+  }
+}
+
+impl<T> EventLoopExtAsync for EventLoop<T> {
+  type UserEvent = T;
+
+  fn run_async<Fut, F>(self, future: Fut, mut event_handler: F) -> !
+  where
+    Fut: 'static + Future<Output = ()>,
+    F: 'static
+      + FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
+  {
+    let proxy_waker = Arc::new(ProxyWaker {
+      proxy: self.create_typed_proxy(),
+      woken: AtomicBool::new(false),
+    });
+    let waker = Waker::from(proxy_waker.clone());
+    let mut future: Option<Pin<Box<dyn Future<Output = ()>>>> = Some(Box::pin(future));
+
+    self.run(move |event, event_loop, control_flow| {
+      let poll = match event {
+        Event::TypedUserEvent(event) if event.is::<Wakeup>() => true,
+        Event::NewEvents(StartCause::Init) => {
+          event_handler(event, event_loop, control_flow);
+          true
+        }
+        event => {
+          event_handler(event, event_loop, control_flow);
+          false
+        }
+      };
+
+      if let (true, Some(pending)) = (poll, future.as_mut()) {
+        proxy_waker.woken.store(false, Ordering::Release);
+        if pending
+          .as_mut()
+          .poll(&mut Context::from_waker(&waker))
+          .is_ready()
+        {
+          future = None;
+          *control_flow = ControlFlow::Exit;
+        }
+      }
+    })
+  }
+}
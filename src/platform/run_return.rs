@@ -19,6 +19,12 @@ pub trait EventLoopExtRunReturn {
   /// Unlike `run`, this function accepts non-`'static` (i.e. non-`move`) closures and returns
   /// control flow to the caller when `control_flow` is set to `ControlFlow::Exit`.
   ///
+  /// It can be called again once it returned, windows and pending user events are kept in between.
+  /// Every call starts with `Event::NewEvents(StartCause::Init)` and ends with
+  /// `Event::LoopDestroyed`, and `control_flow` starts over from `ControlFlow::Poll`.
+  ///
+  /// Returns the exit code of [`run_return_with_reason`](Self::run_return_with_reason).
+  ///
   /// # Caveats
   /// Despite its appearance at first glance, this is *not* a perfect replacement for
   /// `poll_events`. For example, this function will not return on Windows or macOS while a
@@ -36,6 +42,38 @@ pub trait EventLoopExtRunReturn {
   fn run_return<F>(&mut self, event_handler: F) -> i32
   where
     F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow);
+
+  /// Same as [`run_return`](Self::run_return), but tells why the event loop returned.
+  fn run_return_with_reason<F>(&mut self, event_handler: F) -> ExitReason
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow);
+}
+
+/// Why [`EventLoopExtRunReturn::run_return_with_reason`] returned.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitReason {
+  /// `control_flow` was set to `ControlFlow::ExitWithCode`, `ControlFlow::Exit` being code `0`.
+  Requested(i32),
+  /// The system ended the event loop with the given exit code.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** A `WM_QUIT` message was posted to the thread, e.g. with `PostQuitMessage`.
+  ///   The exit code is `0`, whatever the code of the message.
+  /// - **Linux:** The connection to the display server was lost, or the main context was
+  ///   acquired by another thread. The exit code is `1`.
+  /// - **macOS / Android:** Never happens.
+  Quit(i32),
+}
+
+impl ExitReason {
+  /// Returns the exit code, which `EventLoop::run` exits the process with.
+  pub fn exit_code(self) -> i32 {
+    match self {
+      ExitReason::Requested(code) | ExitReason::Quit(code) => code,
+    }
+  }
 }
 
 impl<T> EventLoopExtRunReturn for EventLoop<T> {
@@ -45,6 +83,21 @@ impl<T> EventLoopExtRunReturn for EventLoop<T> {
   where
     F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
   {
+    self.run_return_with_reason(event_handler).exit_code()
+  }
+
+  fn run_return_with_reason<F>(&mut self, event_handler: F) -> ExitReason
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
+  {
+    #[cfg(feature = "event-recorder")]
+    let event_handler = crate::event_recorder::record(&mut self.recorder, event_handler);
+    #[cfg(feature = "harden")]
+    let event_handler = crate::harden::sanitize(&mut self.sanitizer, event_handler);
+    #[cfg(feature = "synthetic-input")]
+    let event_handler = crate::synthetic_input::inject(event_handler);
+    #[cfg(feature = "notifications")]
+    let event_handler = crate::notification::deliver(event_handler);
     self.event_loop.run_return(event_handler)
   }
 }
@@ -12,12 +12,15 @@
 
 use std::{os::raw::c_int, sync::Arc};
 
+use gtk::gdk;
+
 // XConnection utilities
 #[doc(hidden)]
 pub use crate::platform_impl::x11;
 
 pub use crate::platform_impl::EventLoop as UnixEventLoop;
 use crate::{
+  data_format::DataFormat,
   event_loop::{EventLoopBuilder, EventLoopWindowTarget},
   platform_impl::{x11::xdisplay::XError, Parent},
   window::{Window, WindowBuilder},
@@ -38,6 +41,18 @@ pub trait EventLoopBuilderExtUnix {
   /// terminates. Attempting to use a `Window` after its parent thread terminates has
   /// unspecified, although explicitly not undefined, behavior.
   fn with_any_thread(&mut self, any_thread: bool) -> &mut Self;
+
+  /// Runs the event loop on `context` instead of the global default main context, so that
+  /// applications already driving a `glib::MainContext` (e.g. for GStreamer or gio) can share it
+  /// with tao rather than running two competing main loops.
+  ///
+  /// The context is made the thread-default one while the event loop is created and running,
+  /// tao's own sources are attached to it and the event loop waits on it.
+  ///
+  /// GTK always reads windowing events from the global default context. If `context` is a
+  /// different one, tao polls the file descriptors of the default context from `context` and
+  /// dispatches it whenever they are ready or one of its timeouts expires.
+  fn with_main_context(&mut self, context: gtk::glib::MainContext) -> &mut Self;
 }
 
 impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
@@ -46,6 +61,12 @@ impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
     self.platform_specific.any_thread = any_thread;
     self
   }
+
+  #[inline]
+  fn with_main_context(&mut self, context: gtk::glib::MainContext) -> &mut Self {
+    self.platform_specific.main_context = Some(context);
+    self
+  }
 }
 
 /// Additional methods on `Window` that are specific to Unix.
@@ -232,3 +253,17 @@ unsafe extern "C" fn x_error_callback(
   // Fun fact: this return value is completely ignored.
   0
 }
+
+/// Additional methods on `DataFormat` that are specific to Unix.
+pub trait DataFormatExtUnix {
+  /// Returns the atom named after the MIME type of the format, the target of selections and
+  /// drag and drop.
+  fn atom(&self) -> gdk::Atom;
+}
+
+impl DataFormatExtUnix for DataFormat {
+  #[inline]
+  fn atom(&self) -> gdk::Atom {
+    gdk::Atom::intern(self.mime_type())
+  }
+}
@@ -4,17 +4,19 @@
 
 #![cfg(target_os = "windows")]
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::{
+  data_format::DataFormat,
   dpi::PhysicalSize,
+  error::OsError,
   event::DeviceId,
   event_loop::EventLoopBuilder,
+  icon::RgbaIcon,
   monitor::MonitorHandle,
-  platform_impl::{Parent, WinIcon},
-  window::{BadIcon, Icon, Theme, Window, WindowBuilder},
+  platform_impl::{set_jump_list, Parent, WinIcon},
+  window::{BadIcon, CustomCursor, Icon, Theme, Window, WindowBuilder},
 };
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
 pub type HWND = isize;
 pub type HMENU = isize;
@@ -26,6 +28,12 @@ pub trait EventLoopBuilderExtWindows {
   /// By default, the window is only allowed to be created on the main
   /// thread, to make platform compatibility easier.
   ///
+  /// The thread the event loop is created on becomes its UI thread, which windows are created
+  /// and the event loop is run on. This lets applications which don't own the process main
+  /// thread, like DLL plugins or test runners, run `tao` on a thread they created. Window classes
+  /// are registered with the module `tao` is linked into, so that a DLL can be unloaded once its
+  /// windows are destroyed.
+  ///
   /// # `Window` caveats
   ///
   /// Note that any `Window` created on the new thread will be destroyed when the thread
@@ -54,11 +62,23 @@ pub trait EventLoopBuilderExtWindows {
   /// let event_loop = builder.build();
   /// # }
   /// ```
+  ///
+  /// `true` is the same as [`DpiAwareness::PerMonitorV2`], see
+  /// [`EventLoopBuilderExtWindows::with_dpi_awareness`] to choose another mode.
   fn with_dpi_aware(&mut self, dpi_aware: bool) -> &mut Self;
 
+  /// Sets the process-wide DPI awareness mode, enabled when the event loop is built. Defaults to
+  /// [`DpiAwareness::PerMonitorV2`].
+  ///
+  /// The mode can only be set once per process, so this has no effect if it was set already, e.g.
+  /// by the application manifest or by an earlier event loop.
+  fn with_dpi_awareness(&mut self, dpi_awareness: DpiAwareness) -> &mut Self;
+
   /// A callback to be executed before dispatching a win32 message to the window procedure.
   /// Return true to disable tao's internal message dispatching.
   ///
+  /// Same as [`EventLoopBuilder::with_msg_hook`](crate::event_loop::EventLoopBuilder::with_msg_hook).
+  ///
   /// # Example
   ///
   /// ```
@@ -99,6 +119,13 @@ pub trait EventLoopBuilderExtWindows {
   /// or use `None` so it automatically uses the theme of this method
   /// or falls back to the system preference.
   fn with_theme(&mut self, theme: Option<Theme>) -> &mut Self;
+
+  /// Sets how tao initializes COM on the event loop thread. Defaults to
+  /// [`ComInit::ApartmentThreaded`].
+  ///
+  /// Use this when the application initializes COM itself, or needs the multi-threaded apartment
+  /// for example for WinRT components, since a thread can only be in one apartment.
+  fn with_com_init(&mut self, com_init: ComInit) -> &mut Self;
 }
 
 impl<T> EventLoopBuilderExtWindows for EventLoopBuilder<T> {
@@ -110,7 +137,17 @@ impl<T> EventLoopBuilderExtWindows for EventLoopBuilder<T> {
 
   #[inline]
   fn with_dpi_aware(&mut self, dpi_aware: bool) -> &mut Self {
-    self.platform_specific.dpi_aware = dpi_aware;
+    self.platform_specific.dpi_awareness = if dpi_aware {
+      Some(DpiAwareness::PerMonitorV2)
+    } else {
+      None
+    };
+    self
+  }
+
+  #[inline]
+  fn with_dpi_awareness(&mut self, dpi_awareness: DpiAwareness) -> &mut Self {
+    self.platform_specific.dpi_awareness = Some(dpi_awareness);
     self
   }
 
@@ -129,6 +166,126 @@ impl<T> EventLoopBuilderExtWindows for EventLoopBuilder<T> {
     self.platform_specific.preferred_theme = theme;
     self
   }
+
+  #[inline]
+  fn with_com_init(&mut self, com_init: ComInit) -> &mut Self {
+    self.platform_specific.com_init = com_init;
+    self
+  }
+}
+
+/// How tao initializes COM on the event loop thread, see
+/// [`EventLoopBuilderExtWindows::with_com_init`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComInit {
+  /// Calls `OleInitialize`, which enters a single-threaded apartment, when the first window with
+  /// drag and drop is created.
+  ApartmentThreaded,
+  /// Calls `CoInitializeEx` with `COINIT_MULTITHREADED` when the event loop is created.
+  ///
+  /// Drag and drop needs a single-threaded apartment, so it is disabled for every window.
+  MultiThreaded,
+  /// tao doesn't initialize COM, the application must do so before creating windows. Drag and
+  /// drop only works if the application called `OleInitialize`.
+  None,
+}
+
+impl Default for ComInit {
+  fn default() -> Self {
+    ComInit::ApartmentThreaded
+  }
+}
+
+/// The DPI awareness mode of the process, see [`EventLoopBuilderExtWindows::with_dpi_awareness`],
+/// or of a window, see [`WindowBuilderExtWindows::with_dpi_awareness`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DpiAwareness {
+  /// Windows are scaled by the application to the DPI of the monitor they're on, including their
+  /// non-client area. Falls back to [`DpiAwareness::PerMonitor`] before Windows 10 1703.
+  PerMonitorV2,
+  /// Windows are scaled by the application to the DPI of the monitor they're on, and
+  /// `WindowEvent::ScaleFactorChanged` is emitted when it changes. Falls back to
+  /// [`DpiAwareness::System`] before Windows 8.1.
+  PerMonitor,
+  /// Windows are scaled by the application to the DPI of the primary monitor when the session
+  /// started, and stretched by the system on monitors with another DPI.
+  System,
+  /// Windows aren't scaled by the application, they're stretched by the system on monitors with a
+  /// DPI other than 96.
+  Unaware,
+  /// Like [`DpiAwareness::Unaware`], except that the text and shapes drawn with GDI are rendered
+  /// at the DPI of the monitor by the system, so they stay sharp. Falls back to
+  /// [`DpiAwareness::Unaware`] before Windows 10 1809.
+  UnawareGdiScaled,
+}
+
+/// The tasks and recent items shown in the menu of the application's taskbar button.
+///
+/// Entries launch a new instance of the application, which receives the entry's arguments through
+/// [`Event::JumpListItemActivated`]. The jump list belongs to the application rather than to a
+/// window, and is kept by the shell until it is replaced.
+///
+/// [`Event::JumpListItemActivated`]: crate::event::Event::JumpListItemActivated
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JumpList {
+  pub(crate) tasks: Vec<JumpListItem>,
+  pub(crate) recent_items: Vec<JumpListItem>,
+}
+
+impl JumpList {
+  /// Creates an empty jump list, applying it removes the current one.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Adds an entry to the "Tasks" section.
+  #[inline]
+  pub fn with_task(mut self, item: JumpListItem) -> Self {
+    self.tasks.push(item);
+    self
+  }
+
+  /// Adds an entry to the "Recent" section, most recent first.
+  ///
+  /// The user can remove recent items from the menu, those are skipped the next time the jump list
+  /// is applied.
+  #[inline]
+  pub fn with_recent_item(mut self, item: JumpListItem) -> Self {
+    self.recent_items.push(item);
+    self
+  }
+
+  /// Replaces the application's jump list.
+  pub fn apply(&self) -> Result<(), OsError> {
+    set_jump_list(self)
+  }
+}
+
+/// An entry of a [`JumpList`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpListItem {
+  /// Text of the entry.
+  pub title: String,
+  /// Arguments the application is launched with.
+  pub arguments: Vec<String>,
+  /// Tooltip of the entry.
+  pub description: Option<String>,
+  /// File holding the icon of the entry and index of the icon in the file. The executable's icon
+  /// is used if `None`.
+  pub icon: Option<(PathBuf, i32)>,
+}
+
+impl JumpListItem {
+  pub fn new<S: Into<String>>(title: S, arguments: Vec<String>) -> Self {
+    Self {
+      title: title.into(),
+      arguments,
+      description: None,
+      icon: None,
+    }
+  }
 }
 
 /// Additional methods on `Window` that are specific to Windows.
@@ -153,14 +310,53 @@ pub trait WindowExtWindows {
   ///
   /// For more information, see <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-enablewindow#remarks>
   /// and <https://docs.microsoft.com/en-us/windows/win32/winmsg/window-features#disabled-windows>
+  ///
+  /// This is the same as [`Window::set_enabled`].
   fn set_enable(&self, enabled: bool);
 
   /// This sets `ICON_BIG`. A good ceiling here is 256x256.
   fn set_taskbar_icon(&self, taskbar_icon: Option<Icon>);
 
+  /// Sets the bitmap shown instead of the window contents when the user hovers its taskbar
+  /// thumbnail, which is also scaled down to be used as the thumbnail itself.
+  ///
+  /// `rgba` is expected to hold `width * height` pixels, like [`Icon::from_rgba`], and fails with
+  /// [`BadIcon::ZeroSize`] if there are none. While a bitmap is set,
+  /// [`WindowEvent::ThumbnailPeekStarted`] and [`WindowEvent::ThumbnailPeekEnded`] are emitted.
+  ///
+  /// [`WindowEvent::ThumbnailPeekStarted`]: crate::event::WindowEvent::ThumbnailPeekStarted
+  /// [`WindowEvent::ThumbnailPeekEnded`]: crate::event::WindowEvent::ThumbnailPeekEnded
+  fn set_peek_bitmap(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon>;
+
+  /// Removes the bitmap set with [`WindowExtWindows::set_peek_bitmap`], restoring the live
+  /// thumbnail and peek.
+  fn clear_peek_bitmap(&self);
+
+  /// Layers a small status icon, such as an online, away or error badge, over the window's taskbar
+  /// button. `description` is read by screen readers. `None` removes the overlay.
+  ///
+  /// The 16x16 size of the icon is used, see [`Icon::with_variant`].
+  fn set_overlay_icon(&self, icon: Option<Icon>, description: &str);
+
   /// Returns the current window theme.
   fn theme(&self) -> Theme;
 
+  /// Switches the window to `theme`, or back to the theme of the event loop or the system with
+  /// `None`, updating the title bar and emitting `WindowEvent::ThemeChanged` if the theme changed.
+  ///
+  /// This is the runtime counterpart of [`WindowBuilder::with_theme`].
+  fn set_theme(&self, theme: Option<Theme>);
+
+  /// Sets the colors of the caption, border and title text of the window. The colors set to `None`
+  /// go back to those of the system, which follow the theme of the window.
+  ///
+  /// Only supported from Windows 11 on, does nothing before.
+  fn set_title_bar_colors(&self, colors: TitleBarColors);
+
+  /// Returns whether the window was created with a redirection surface, i.e. without
+  /// [`WindowBuilderExtWindows::with_no_redirection_bitmap`].
+  fn has_redirection_bitmap(&self) -> bool;
+
   /// Reset the dead key state of the keyboard.
   ///
   /// This is useful when a dead key is bound to trigger an action. Then
@@ -174,6 +370,14 @@ pub trait WindowExtWindows {
   /// Whether to show the window icon in the taskbar or not.
   fn set_skip_taskbar(&self, skip: bool);
 
+  /// Sets the application user model id of the window, which the taskbar groups its buttons,
+  /// pins and jump lists by, instead of the id of the process. An empty `app_id` goes back to the
+  /// id of the process.
+  ///
+  /// This lets an application with several profiles split their windows into separate taskbar
+  /// groups. Setting it before the window is visible avoids its button moving between groups.
+  fn set_app_user_model_id(&self, app_id: &str) -> Result<(), OsError>;
+
   /// Shows or hides the background drop shadow for undecorated windows.
   ///
   /// Enabling the shadow causes a thin 1px line to appear on the top of the window.
@@ -183,8 +387,58 @@ pub trait WindowExtWindows {
   ///
   /// Enabling this mainly flips the orientation of menus and title bar buttons
   fn set_rtl(&self, rtl: bool);
+
+  /// Returns the current `WS_*` style of the window, as returned by `GetWindowLongW(GWL_STYLE)`.
+  fn style(&self) -> u32;
+
+  /// Returns the current `WS_EX_*` extended style of the window, as returned by
+  /// `GetWindowLongW(GWL_EXSTYLE)`.
+  fn ex_style(&self) -> u32;
+
+  /// Sets the bits of `mask` in the `WS_*` style of the window to those of `style`, e.g.
+  /// `set_style(0, WS_MAXIMIZEBOX.0)` to remove the maximize button.
+  ///
+  /// The bits stay set when tao changes the style of the window itself, e.g. when the window is
+  /// made resizable or fullscreen, so overriding the bits tao relies on like `WS_VISIBLE` or
+  /// `WS_CHILD` leaves its state out of sync with the window.
+  fn set_style(&self, style: u32, mask: u32);
+
+  /// Sets the bits of `mask` in the `WS_EX_*` extended style of the window to those of
+  /// `ex_style`, e.g. `set_ex_style(WS_EX_TOOLWINDOW.0, WS_EX_TOOLWINDOW.0)`.
+  ///
+  /// See [`WindowExtWindows::set_style`].
+  fn set_ex_style(&self, ex_style: u32, mask: u32);
+
+  /// Adds a hook called with the `HWND`, message, `WPARAM` and `LPARAM` of each message the window
+  /// receives, before tao handles it. Returning `Some(result)` makes the window procedure return
+  /// `result` right away, skipping tao and the hooks added after this one.
+  ///
+  /// The hooks are called on the thread of the event loop. A hook isn't called again for the
+  /// messages sent to the window while it runs, e.g. by `SendMessageW`.
+  fn add_message_hook<F>(&self, hook: F) -> MessageHookId
+  where
+    F: FnMut(HWND, u32, usize, isize) -> Option<isize> + Send + 'static;
+
+  /// Removes the hook `id`. Does nothing if it was removed already.
+  fn remove_message_hook(&self, id: MessageHookId);
+}
+
+/// Colors of the title bar and border of a window, as RGB, set with
+/// [`WindowExtWindows::set_title_bar_colors`]. `None` keeps the color of the system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TitleBarColors {
+  /// Background of the title bar.
+  pub caption: Option<[u8; 3]>,
+  /// Border around the window.
+  pub border: Option<[u8; 3]>,
+  /// Title of the window.
+  pub text: Option<[u8; 3]>,
 }
 
+/// Identifier of a hook added with [`WindowExtWindows::add_message_hook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MessageHookId(u64);
+
 impl WindowExtWindows for Window {
   #[inline]
   fn hinstance(&self) -> isize {
@@ -198,9 +452,7 @@ impl WindowExtWindows for Window {
 
   #[inline]
   fn set_enable(&self, enabled: bool) {
-    unsafe {
-      EnableWindow(self.window.hwnd(), enabled);
-    }
+    self.window.set_enabled(enabled)
   }
 
   #[inline]
@@ -208,11 +460,46 @@ impl WindowExtWindows for Window {
     self.window.set_taskbar_icon(taskbar_icon)
   }
 
+  #[inline]
+  fn set_peek_bitmap(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+    if width == 0 || height == 0 {
+      return Err(BadIcon::ZeroSize { width, height });
+    }
+    let bitmap = RgbaIcon::from_rgba(rgba, width, height)?;
+    self.window.set_peek_bitmap(Some(bitmap));
+    Ok(())
+  }
+
+  #[inline]
+  fn clear_peek_bitmap(&self) {
+    self.window.set_peek_bitmap(None)
+  }
+
+  #[inline]
+  fn set_overlay_icon(&self, icon: Option<Icon>, description: &str) {
+    self.window.set_overlay_icon(icon, description)
+  }
+
   #[inline]
   fn theme(&self) -> Theme {
     self.window.theme()
   }
 
+  #[inline]
+  fn set_theme(&self, theme: Option<Theme>) {
+    self.window.set_theme(theme)
+  }
+
+  #[inline]
+  fn set_title_bar_colors(&self, colors: TitleBarColors) {
+    self.window.set_title_bar_colors(colors)
+  }
+
+  #[inline]
+  fn has_redirection_bitmap(&self) -> bool {
+    self.window.has_redirection_bitmap()
+  }
+
   #[inline]
   fn reset_dead_keys(&self) {
     self.window.reset_dead_keys();
@@ -223,6 +510,11 @@ impl WindowExtWindows for Window {
     self.window.begin_resize_drag(edge, button, x, y)
   }
 
+  #[inline]
+  fn set_app_user_model_id(&self, app_id: &str) -> Result<(), OsError> {
+    self.window.set_app_user_model_id(app_id)
+  }
+
   #[inline]
   fn set_skip_taskbar(&self, skip: bool) {
     self.window.set_skip_taskbar(skip);
@@ -237,6 +529,39 @@ impl WindowExtWindows for Window {
   fn set_rtl(&self, rtl: bool) {
     self.window.set_rtl(rtl)
   }
+
+  #[inline]
+  fn style(&self) -> u32 {
+    self.window.style()
+  }
+
+  #[inline]
+  fn ex_style(&self) -> u32 {
+    self.window.ex_style()
+  }
+
+  #[inline]
+  fn set_style(&self, style: u32, mask: u32) {
+    self.window.set_style(style, mask)
+  }
+
+  #[inline]
+  fn set_ex_style(&self, ex_style: u32, mask: u32) {
+    self.window.set_ex_style(ex_style, mask)
+  }
+
+  #[inline]
+  fn add_message_hook<F>(&self, hook: F) -> MessageHookId
+  where
+    F: FnMut(HWND, u32, usize, isize) -> Option<isize> + Send + 'static,
+  {
+    MessageHookId(self.window.add_message_hook(Box::new(hook)))
+  }
+
+  #[inline]
+  fn remove_message_hook(&self, id: MessageHookId) {
+    self.window.remove_message_hook(id.0)
+  }
 }
 
 /// Additional methods on `WindowBuilder` that are specific to Windows.
@@ -274,14 +599,23 @@ pub trait WindowBuilderExtWindows {
   /// This sets `ICON_BIG`. A good ceiling here is 256x256.
   fn with_taskbar_icon(self, taskbar_icon: Option<Icon>) -> WindowBuilder;
 
-  /// This sets `WS_EX_NOREDIRECTIONBITMAP`.
+  /// This sets `WS_EX_NOREDIRECTIONBITMAP`, creating the window without a redirection surface.
+  ///
+  /// Nothing drawn with GDI or to the window's DC shows up, only the content of a DirectComposition
+  /// visual tree or of a DXGI swap chain created with `CreateSwapChainForComposition` attached to
+  /// the window. With [`WindowBuilder::with_transparent`], the transparent parts of that content
+  /// show what is behind the window without the flicker of a redirection surface being resized,
+  /// and tao doesn't enable the DWM blur behind the window.
+  ///
+  /// The flag can't be changed once the window is created.
   fn with_no_redirection_bitmap(self, flag: bool) -> WindowBuilder;
 
   /// Enables or disables drag and drop support (enabled by default). Will interfere with other crates
   /// that use multi-threaded COM API (`CoInitializeEx` with `COINIT_MULTITHREADED` instead of
   /// `COINIT_APARTMENTTHREADED`) on the same thread. Note that tao may still attempt to initialize
   /// COM API regardless of this option. Currently only fullscreen mode does that, but there may be more in the future.
-  /// If you need COM API with `COINIT_MULTITHREADED` you must initialize it before calling any tao functions.
+  /// If you need COM API with `COINIT_MULTITHREADED` you must initialize it before calling any tao functions,
+  /// or use [`EventLoopBuilderExtWindows::with_com_init`].
   /// See <https://docs.microsoft.com/en-us/windows/win32/api/objbase/nf-objbase-coinitialize#remarks> for more information.
   fn with_drag_and_drop(self, flag: bool) -> WindowBuilder;
 
@@ -289,8 +623,38 @@ pub trait WindowBuilderExtWindows {
   fn with_skip_taskbar(self, skip: bool) -> WindowBuilder;
 
   /// Customize the window class name.
+  ///
+  /// The class is registered by the first window created with its name, the class style and icon
+  /// of the windows created with the same name afterwards are ignored.
   fn with_window_classname<S: Into<String>>(self, classname: S) -> WindowBuilder;
 
+  /// Sets the `CS_*` style of the window class, e.g. adding `CS_NOCLOSE.0` to disable the close
+  /// item of the window menu.
+  /// Defaults to `CS_HREDRAW | CS_VREDRAW | CS_OWNDC`.
+  ///
+  /// See [`WindowBuilderExtWindows::with_window_classname`].
+  fn with_window_class_style(self, style: u32) -> WindowBuilder;
+
+  /// Sets the icon of the window class, shown by the windows without an icon of their own. The
+  /// large and small icons are picked from the variants of `icon`, see [`Icon::with_variant`].
+  ///
+  /// The icon is kept as long as the process runs, since the class can't be unregistered while
+  /// tao's windows are using it. See [`WindowBuilderExtWindows::with_window_classname`].
+  fn with_window_class_icon(self, icon: Icon) -> WindowBuilder;
+
+  /// Creates the window with the DPI awareness `dpi_awareness` rather than the one of the process,
+  /// e.g. [`DpiAwareness::UnawareGdiScaled`] for a legacy plugin view that doesn't handle scaling,
+  /// while the rest of the application stays per-monitor aware.
+  ///
+  /// A window created with this option may also host child windows of another awareness, so a
+  /// child window with its own awareness needs a parent created with this option too, e.g. with
+  /// [`DpiAwareness::PerMonitorV2`]. The scale factor of the window follows its awareness, it's
+  /// always 1 for an unaware window.
+  ///
+  /// Only supported from Windows 10 1607 on, and mixed awareness from Windows 10 1803 on. The
+  /// awareness can't be changed once the window is created.
+  fn with_dpi_awareness(self, dpi_awareness: DpiAwareness) -> WindowBuilder;
+
   /// Shows or hides the background drop shadow for undecorated windows.
   ///
   /// The shadow is hidden by default.
@@ -299,6 +663,15 @@ pub trait WindowBuilderExtWindows {
 
   /// Sets right-to-left layout.
   fn with_rtl(self, rtl: bool) -> WindowBuilder;
+
+  /// Sets the bits of `mask` in the `WS_*` style of the window to be created to those of
+  /// `style`. See [`WindowExtWindows::set_style`].
+  fn with_style(self, style: u32, mask: u32) -> WindowBuilder;
+
+  /// Sets the bits of `mask` in the `WS_EX_*` extended style of the window to be created to those
+  /// of `ex_style`, e.g. `WS_EX_NOACTIVATE` for a window which isn't activated when clicked. See
+  /// [`WindowExtWindows::set_ex_style`].
+  fn with_ex_style(self, ex_style: u32, mask: u32) -> WindowBuilder;
 }
 
 impl WindowBuilderExtWindows for WindowBuilder {
@@ -350,6 +723,24 @@ impl WindowBuilderExtWindows for WindowBuilder {
     self
   }
 
+  #[inline]
+  fn with_window_class_style(mut self, style: u32) -> WindowBuilder {
+    self.platform_specific.window_class_style = style;
+    self
+  }
+
+  #[inline]
+  fn with_window_class_icon(mut self, icon: Icon) -> WindowBuilder {
+    self.platform_specific.window_class_icon = Some(icon);
+    self
+  }
+
+  #[inline]
+  fn with_dpi_awareness(mut self, dpi_awareness: DpiAwareness) -> WindowBuilder {
+    self.platform_specific.dpi_awareness = Some(dpi_awareness);
+    self
+  }
+
   #[inline]
   fn with_undecorated_shadow(mut self, shadow: bool) -> WindowBuilder {
     self.platform_specific.decoration_shadow = shadow;
@@ -361,6 +752,21 @@ impl WindowBuilderExtWindows for WindowBuilder {
     self.platform_specific.rtl = rtl;
     self
   }
+
+  #[inline]
+  fn with_style(mut self, style: u32, mask: u32) -> WindowBuilder {
+    self.platform_specific.custom_styles.set_style(style, mask);
+    self
+  }
+
+  #[inline]
+  fn with_ex_style(mut self, ex_style: u32, mask: u32) -> WindowBuilder {
+    self
+      .platform_specific
+      .custom_styles
+      .set_ex_style(ex_style, mask);
+    self
+  }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to Windows.
@@ -384,6 +790,19 @@ impl MonitorHandleExtWindows for MonitorHandle {
   }
 }
 
+/// Additional methods on `DataFormat` that are specific to Windows.
+pub trait DataFormatExtWindows {
+  /// Returns the registered clipboard format, which OLE drag and drop uses as well.
+  fn clipboard_format(&self) -> u32;
+}
+
+impl DataFormatExtWindows for DataFormat {
+  #[inline]
+  fn clipboard_format(&self) -> u32 {
+    self.inner.clipboard_format()
+  }
+}
+
 /// Additional methods on `DeviceId` that are specific to Windows.
 pub trait DeviceIdExtWindows {
   /// Returns an identifier that persistently refers to this specific device.
@@ -431,3 +850,25 @@ impl IconExtWindows for Icon {
     Ok(Icon { inner: win_icon })
   }
 }
+
+/// Additional methods on `CustomCursor` that are specific to Windows.
+pub trait CustomCursorExtWindows: Sized {
+  /// Loads a cursor from a `.cur` file, or an animated cursor from a `.ani` file, which the
+  /// system animates.
+  ///
+  /// The cursor is loaded at the size of the user's cursor size setting, and again from the file
+  /// each time it's shown.
+  fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, BadIcon>;
+}
+
+impl CustomCursorExtWindows for CustomCursor {
+  fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, BadIcon> {
+    // Fails early on files which aren't cursors.
+    WinIcon::cursor_from_path(path.as_ref())?;
+    Ok(CustomCursor {
+      images: Vec::new(),
+      frames: Vec::new(),
+      path: Some(path.as_ref().to_path_buf()),
+    })
+  }
+}
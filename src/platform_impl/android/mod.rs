@@ -7,9 +7,11 @@ use crate::{
   dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   error, event,
   event_loop::{self, ControlFlow},
-  keyboard::{Key, KeyCode, KeyLocation, NativeKeyCode},
+  global_shortcut::{Accelerator, AcceleratorId},
+  keyboard::{Key, KeyCode, KeyLocation, KeyRepeatSettings, KeyboardLayout, NativeKeyCode},
   monitor,
-  window::{self, ResizeDirection, Theme, WindowSizeConstraints},
+  platform::{pump_events::PumpStatus, run_return},
+  window::{self, ResizeDirection, SystemAppearance, Theme, WindowSizeConstraints},
 };
 use crossbeam_channel::{Receiver, Sender};
 use ndk::{
@@ -21,6 +23,7 @@ use ndk_sys::AKeyEvent_getKeyCode;
 use std::{
   collections::VecDeque,
   convert::TryInto,
+  ffi::c_void,
   sync::RwLock,
   time::{Duration, Instant},
 };
@@ -58,14 +61,21 @@ pub struct EventLoop<T: 'static> {
   window_target: event_loop::EventLoopWindowTarget<T>,
   receiver: Receiver<T>,
   sender_to_clone: Sender<T>,
+  typed_receiver: Receiver<event::TypedUserEvent>,
+  typed_sender_to_clone: Sender<event::TypedUserEvent>,
   first_event: Option<EventSource>,
   start_cause: event::StartCause,
   looper: ThreadLooper,
   running: bool,
+  msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+  /// Control flow of the last iteration dispatched by `pump_events`
+  pump_control_flow: Option<ControlFlow>,
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct PlatformSpecificEventLoopAttributes {}
+#[derive(Default)]
+pub(crate) struct PlatformSpecificEventLoopAttributes {
+  pub(crate) msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+}
 
 macro_rules! call_event_handler {
   ( $event_handler:expr, $window_target:expr, $cf:expr, $event:expr ) => {{
@@ -78,8 +88,9 @@ macro_rules! call_event_handler {
 }
 
 impl<T: 'static> EventLoop<T> {
-  pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> Self {
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> Self {
     let (sender, receiver) = crossbeam_channel::unbounded();
+    let (typed_sender, typed_receiver) = crossbeam_channel::unbounded();
 
     Self {
       window_target: event_loop::EventLoopWindowTarget {
@@ -90,10 +101,14 @@ impl<T: 'static> EventLoop<T> {
       },
       sender_to_clone: sender,
       receiver,
+      typed_sender_to_clone: typed_sender,
+      typed_receiver,
       first_event: None,
       start_cause: event::StartCause::Init,
       looper: ThreadLooper::for_thread().unwrap(),
       running: false,
+      msg_hook: attributes.msg_hook.take(),
+      pump_control_flow: None,
     }
   }
 
@@ -102,299 +117,381 @@ impl<T: 'static> EventLoop<T> {
     F:
       'static + FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
   {
-    let exit_code = self.run_return(event_handler);
+    let exit_code = self.run_return(event_handler).exit_code();
     ::std::process::exit(exit_code);
   }
 
-  pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
+  pub fn run_return<F>(&mut self, mut event_handler: F) -> run_return::ExitReason
   where
     F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
   {
+    // Every call starts over with `StartCause::Init`, even after `pump_events`.
+    if self.pump_control_flow.take().is_some() {
+      self.start_cause = event::StartCause::Init;
+    }
     let mut control_flow = ControlFlow::default();
 
-    'event_loop: loop {
-      call_event_handler!(
-        event_handler,
-        self.window_target(),
-        control_flow,
-        event::Event::NewEvents(self.start_cause)
-      );
-
-      let mut redraw = false;
-      let mut resized = false;
-
-      match self.first_event.take() {
-        Some(EventSource::Callback) => match ndk_glue::poll_events().unwrap() {
-          Event::Resume => {
-            call_event_handler!(
-              event_handler,
-              self.window_target(),
-              control_flow,
-              event::Event::Resumed
-            );
-          }
-          Event::WindowResized => resized = true,
-          Event::WindowRedrawNeeded => redraw = true,
-          Event::Pause => {
-            call_event_handler!(
-              event_handler,
-              self.window_target(),
-              control_flow,
-              event::Event::Suspended
-            );
-          }
-          Event::Stop => self.running = false,
-          Event::Start => self.running = true,
-          Event::ConfigChanged => {
-            // #[allow(deprecated)] // TODO: use ndk-context instead
-            // let am = ndk_glue::native_activity().asset_manager();
-            // let config = Configuration::from_asset_manager(&am);
-            // let old_scale_factor = MonitorHandle.scale_factor();
-            // *CONFIG.write().unwrap() = config;
-            // let scale_factor = MonitorHandle.scale_factor();
-            // if (scale_factor - old_scale_factor).abs() < f64::EPSILON {
-            //   let mut size = MonitorHandle.size();
-            //   let event = event::Event::WindowEvent {
-            //     window_id: window::WindowId(WindowId),
-            //     event: event::WindowEvent::ScaleFactorChanged {
-            //       new_inner_size: &mut size,
-            //       scale_factor,
-            //     },
-            //   };
-            //   call_event_handler!(event_handler, self.window_target(), control_flow, event);
-            // }
-          }
-          Event::WindowHasFocus => {
-            call_event_handler!(
-              event_handler,
-              self.window_target(),
-              control_flow,
-              event::Event::WindowEvent {
-                window_id: window::WindowId(WindowId),
-                event: event::WindowEvent::Focused(true),
-              }
-            );
-          }
-          Event::WindowLostFocus => {
-            call_event_handler!(
-              event_handler,
-              self.window_target(),
-              control_flow,
-              event::Event::WindowEvent {
-                window_id: window::WindowId(WindowId),
-                event: event::WindowEvent::Focused(false),
+    loop {
+      control_flow = self.iterate(&mut event_handler, control_flow);
+      if let ControlFlow::ExitWithCode(code) = control_flow {
+        self.exit(&mut event_handler, control_flow);
+        break run_return::ExitReason::Requested(code);
+      }
+      self.wait(control_flow, None);
+    }
+  }
+
+  pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut event_handler: F) -> PumpStatus
+  where
+    F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
+  {
+    // The first call dispatches the `StartCause::Init` iteration without waiting.
+    let control_flow = match self.pump_control_flow.take() {
+      Some(control_flow) => {
+        if !self.wait(control_flow, timeout) {
+          self.pump_control_flow = Some(control_flow);
+          return PumpStatus::Continue;
+        }
+        control_flow
+      }
+      None => ControlFlow::default(),
+    };
+
+    let control_flow = self.iterate(&mut event_handler, control_flow);
+    if let ControlFlow::ExitWithCode(code) = control_flow {
+      self.exit(&mut event_handler, control_flow);
+      return PumpStatus::Exit(run_return::ExitReason::Requested(code));
+    }
+    self.pump_control_flow = Some(control_flow);
+    PumpStatus::Continue
+  }
+
+  /// Dispatches one iteration of the event loop, from `Event::NewEvents` to
+  /// `Event::RedrawEventsCleared`, returning the control flow it ended with.
+  fn iterate<F>(&mut self, mut event_handler: F, mut control_flow: ControlFlow) -> ControlFlow
+  where
+    F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
+  {
+    call_event_handler!(
+      event_handler,
+      self.window_target(),
+      control_flow,
+      event::Event::NewEvents(self.start_cause)
+    );
+
+    let mut redraw = false;
+    let mut resized = false;
+
+    match self.first_event.take() {
+      Some(EventSource::Callback) => match ndk_glue::poll_events().unwrap() {
+        Event::Resume => {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::Resumed
+          );
+        }
+        Event::WindowResized => resized = true,
+        Event::WindowRedrawNeeded => redraw = true,
+        Event::Pause => {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::Suspended
+          );
+        }
+        Event::Stop => self.running = false,
+        Event::Start => self.running = true,
+        Event::ConfigChanged => {
+          // #[allow(deprecated)] // TODO: use ndk-context instead
+          // let am = ndk_glue::native_activity().asset_manager();
+          // let config = Configuration::from_asset_manager(&am);
+          // let old_scale_factor = MonitorHandle.scale_factor();
+          // *CONFIG.write().unwrap() = config;
+          // let scale_factor = MonitorHandle.scale_factor();
+          // if (scale_factor - old_scale_factor).abs() < f64::EPSILON {
+          //   let mut size = MonitorHandle.size();
+          //   let event = event::Event::WindowEvent {
+          //     window_id: window::WindowId(WindowId),
+          //     event: event::WindowEvent::ScaleFactorChanged {
+          //       new_inner_size: &mut size,
+          //       scale_factor,
+          //     },
+          //   };
+          //   call_event_handler!(event_handler, self.window_target(), control_flow, event);
+          // }
+        }
+        Event::WindowHasFocus => {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::WindowEvent {
+              window_id: window::WindowId(WindowId),
+              event: event::WindowEvent::Focused(true),
+            }
+          );
+        }
+        Event::WindowLostFocus => {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::WindowEvent {
+              window_id: window::WindowId(WindowId),
+              event: event::WindowEvent::Focused(false),
+            }
+          );
+        }
+        _ => {}
+      },
+      Some(EventSource::InputQueue) => {
+        if let Some(input_queue) = ndk_glue::input_queue().as_ref() {
+          while let Ok(Some(event)) = input_queue.get_event() {
+            if let Some(event) = input_queue.pre_dispatch(event) {
+              if let Some(callback) = self.msg_hook.as_deref_mut() {
+                if callback(event.ptr().as_ptr() as *const c_void) {
+                  input_queue.finish_event(event, true);
+                  continue;
+                }
               }
-            );
-          }
-          _ => {}
-        },
-        Some(EventSource::InputQueue) => {
-          if let Some(input_queue) = ndk_glue::input_queue().as_ref() {
-            while let Ok(Some(event)) = input_queue.get_event() {
-              if let Some(event) = input_queue.pre_dispatch(event) {
-                let mut handled = true;
-                let window_id = window::WindowId(WindowId);
-                let device_id = event::DeviceId(DeviceId);
-                match &event {
-                  InputEvent::MotionEvent(motion_event) => {
-                    let phase = match motion_event.action() {
-                      MotionAction::Down | MotionAction::PointerDown => {
-                        Some(event::TouchPhase::Started)
+              let mut handled = true;
+              let window_id = window::WindowId(WindowId);
+              let device_id = event::DeviceId(DeviceId);
+              match &event {
+                InputEvent::MotionEvent(motion_event) => {
+                  let phase = match motion_event.action() {
+                    MotionAction::Down | MotionAction::PointerDown => {
+                      Some(event::TouchPhase::Started)
+                    }
+                    MotionAction::Up | MotionAction::PointerUp => Some(event::TouchPhase::Ended),
+                    MotionAction::Move => Some(event::TouchPhase::Moved),
+                    MotionAction::Cancel => Some(event::TouchPhase::Cancelled),
+                    _ => {
+                      handled = false;
+                      None // TODO mouse events
+                    }
+                  };
+                  if let Some(phase) = phase {
+                    let pointers: Box<dyn Iterator<Item = ndk::event::Pointer<'_>>> = match phase {
+                      event::TouchPhase::Started | event::TouchPhase::Ended => {
+                        Box::new(std::iter::once(
+                          motion_event.pointer_at_index(motion_event.pointer_index()),
+                        ))
                       }
-                      MotionAction::Up | MotionAction::PointerUp => Some(event::TouchPhase::Ended),
-                      MotionAction::Move => Some(event::TouchPhase::Moved),
-                      MotionAction::Cancel => Some(event::TouchPhase::Cancelled),
-                      _ => {
-                        handled = false;
-                        None // TODO mouse events
+                      event::TouchPhase::Moved | event::TouchPhase::Cancelled => {
+                        Box::new(motion_event.pointers())
                       }
                     };
-                    if let Some(phase) = phase {
-                      let pointers: Box<dyn Iterator<Item = ndk::event::Pointer<'_>>> = match phase
-                      {
-                        event::TouchPhase::Started | event::TouchPhase::Ended => {
-                          Box::new(std::iter::once(
-                            motion_event.pointer_at_index(motion_event.pointer_index()),
-                          ))
-                        }
-                        event::TouchPhase::Moved | event::TouchPhase::Cancelled => {
-                          Box::new(motion_event.pointers())
-                        }
-                      };
 
-                      for pointer in pointers {
-                        let location = PhysicalPosition {
-                          x: pointer.x() as _,
-                          y: pointer.y() as _,
-                        };
-                        let event = event::Event::WindowEvent {
-                          window_id,
-                          event: event::WindowEvent::Touch(event::Touch {
-                            device_id,
-                            phase,
-                            location,
-                            id: pointer.pointer_id() as u64,
-                            force: None,
-                          }),
-                        };
-                        call_event_handler!(
-                          event_handler,
-                          self.window_target(),
-                          control_flow,
-                          event
-                        );
-                      }
+                    for pointer in pointers {
+                      let location = PhysicalPosition {
+                        x: pointer.x() as _,
+                        y: pointer.y() as _,
+                      };
+                      let event = event::Event::WindowEvent {
+                        window_id,
+                        event: event::WindowEvent::Touch(event::Touch {
+                          device_id,
+                          phase,
+                          location,
+                          id: pointer.pointer_id() as u64,
+                          force: None,
+                          pointer_kind: pointer_kind(motion_event, pointer.pointer_index()),
+                        }),
+                      };
+                      call_event_handler!(event_handler, self.window_target(), control_flow, event);
                     }
                   }
-                  InputEvent::KeyEvent(key) => {
-                    let state = match key.action() {
-                      KeyAction::Down => event::ElementState::Pressed,
-                      KeyAction::Up => event::ElementState::Released,
-                      _ => event::ElementState::Released,
-                    };
-
-                    // We use the unsafe function directly because
-                    // we want to forward the keycode value even if it doesn't have a variant
-                    // defined in the ndk crate.
-                    let keycode_u32 = unsafe { AKeyEvent_getKeyCode(key.ptr().as_ptr()) as u32 };
-                    let keycode = keycode_u32
-                      .try_into()
-                      .unwrap_or(ndk::event::Keycode::Unknown);
-                    let physical_key =
-                      KeyCode::Unidentified(NativeKeyCode::Android(keycode.into()));
-                    let native = NativeKeyCode::Android(keycode_u32);
-                    let logical_key = keycode_to_logical(keycode, native);
-                    // TODO: maybe use getUnicodeChar to get the logical key
-
-                    let event = event::Event::WindowEvent {
-                      window_id,
-                      event: event::WindowEvent::KeyboardInput {
-                        device_id,
-                        event: event::KeyEvent {
-                          state,
-                          physical_key,
-                          logical_key,
-                          location: keycode_to_location(keycode),
-                          repeat: key.repeat_count() > 0,
-                          text: None,
-                          platform_specific: KeyEventExtra {},
-                        },
-                        is_synthetic: false,
+                }
+                InputEvent::KeyEvent(key) => {
+                  let state = match key.action() {
+                    KeyAction::Down => event::ElementState::Pressed,
+                    KeyAction::Up => event::ElementState::Released,
+                    _ => event::ElementState::Released,
+                  };
+
+                  // We use the unsafe function directly because
+                  // we want to forward the keycode value even if it doesn't have a variant
+                  // defined in the ndk crate.
+                  let keycode_u32 = unsafe { AKeyEvent_getKeyCode(key.ptr().as_ptr()) as u32 };
+                  let keycode = keycode_u32
+                    .try_into()
+                    .unwrap_or(ndk::event::Keycode::Unknown);
+                  let physical_key = KeyCode::Unidentified(NativeKeyCode::Android(keycode.into()));
+                  let native = NativeKeyCode::Android(keycode_u32);
+                  let logical_key = keycode_to_logical(keycode, native);
+                  // TODO: maybe use getUnicodeChar to get the logical key
+
+                  let event = event::Event::WindowEvent {
+                    window_id,
+                    event: event::WindowEvent::KeyboardInput {
+                      device_id,
+                      event: event::KeyEvent {
+                        state,
+                        physical_key,
+                        logical_key,
+                        location: keycode_to_location(keycode),
+                        repeat: key.repeat_count() > 0,
+                        repeat_count: key.repeat_count() as u32,
+                        dead_key_pending: false,
+                        text: None,
+                        platform_specific: KeyEventExtra {},
                       },
-                    };
-                    call_event_handler!(event_handler, self.window_target(), control_flow, event);
-                  }
-                };
-                input_queue.finish_event(event, handled);
-              }
+                      is_synthetic: false,
+                    },
+                  };
+                  call_event_handler!(event_handler, self.window_target(), control_flow, event);
+                }
+              };
+              input_queue.finish_event(event, handled);
             }
           }
         }
-        Some(EventSource::User) => {
-          while let Ok(event) = self.receiver.try_recv() {
-            call_event_handler!(
-              event_handler,
-              self.window_target(),
-              control_flow,
-              event::Event::UserEvent(event)
-            );
-          }
+      }
+      Some(EventSource::User) => {
+        while let Ok(event) = self.receiver.try_recv() {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::UserEvent(event)
+          );
+        }
+        while let Ok(event) = self.typed_receiver.try_recv() {
+          call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::TypedUserEvent(event)
+          );
         }
-        None => {}
       }
+      None => {}
+    }
 
-      call_event_handler!(
-        event_handler,
-        self.window_target(),
-        control_flow,
-        event::Event::MainEventsCleared
-      );
-
-      if resized && self.running {
-        let size = MonitorHandle.size();
-        let event = event::Event::WindowEvent {
-          window_id: window::WindowId(WindowId),
-          event: event::WindowEvent::Resized(size),
-        };
-        call_event_handler!(event_handler, self.window_target(), control_flow, event);
-      }
+    call_event_handler!(
+      event_handler,
+      self.window_target(),
+      control_flow,
+      event::Event::MainEventsCleared
+    );
+
+    if resized && self.running {
+      let size = MonitorHandle.size();
+      let event = event::Event::WindowEvent {
+        window_id: window::WindowId(WindowId),
+        event: event::WindowEvent::Resized(size),
+      };
+      call_event_handler!(event_handler, self.window_target(), control_flow, event);
+    }
 
-      if redraw && self.running {
-        let event = event::Event::RedrawRequested(window::WindowId(WindowId));
-        call_event_handler!(event_handler, self.window_target(), control_flow, event);
-      }
+    if redraw && self.running {
+      let event = event::Event::RedrawRequested(window::WindowId(WindowId));
+      call_event_handler!(event_handler, self.window_target(), control_flow, event);
+    }
 
-      call_event_handler!(
-        event_handler,
-        self.window_target(),
-        control_flow,
-        event::Event::RedrawEventsCleared
-      );
-
-      match control_flow {
-        ControlFlow::ExitWithCode(code) => {
-          self.first_event = poll(
-            self
-              .looper
-              .poll_once_timeout(Duration::from_millis(0))
-              .unwrap(),
-          );
-          self.start_cause = event::StartCause::WaitCancelled {
-            start: Instant::now(),
-            requested_resume: None,
-          };
-          break 'event_loop code;
+    call_event_handler!(
+      event_handler,
+      self.window_target(),
+      control_flow,
+      event::Event::RedrawEventsCleared
+    );
+
+    control_flow
+  }
+
+  /// Dispatches `Event::LoopDestroyed`, after which the event loop starts over.
+  fn exit<F>(&mut self, mut event_handler: F, mut control_flow: ControlFlow)
+  where
+    F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
+  {
+    call_event_handler!(
+      event_handler,
+      self.window_target(),
+      control_flow,
+      event::Event::LoopDestroyed
+    );
+    self.first_event = poll(
+      self
+        .looper
+        .poll_once_timeout(Duration::from_millis(0))
+        .unwrap(),
+    );
+    // The next `run_return` starts over, like the first one.
+    self.start_cause = event::StartCause::Init;
+  }
+
+  /// Waits for events as `control_flow` asks, for up to `timeout`, and sets the cause of the next
+  /// iteration. Returns `false` if `timeout` elapsed first.
+  fn wait(&mut self, control_flow: ControlFlow, timeout: Option<Duration>) -> bool {
+    let start = Instant::now();
+    match control_flow {
+      ControlFlow::Poll | ControlFlow::ExitWithCode(_) => {
+        self.first_event = poll(
+          self
+            .looper
+            .poll_all_timeout(Duration::from_millis(0))
+            .unwrap(),
+        );
+        self.start_cause = event::StartCause::Poll;
+      }
+      ControlFlow::Wait => {
+        self.first_event = match timeout {
+          Some(timeout) => poll(self.looper.poll_all_timeout(timeout).unwrap()),
+          None => poll(self.looper.poll_all().unwrap()),
+        };
+        if self.first_event.is_none() && timeout.is_some() {
+          return false;
         }
-        ControlFlow::Poll => {
-          self.first_event = poll(
-            self
-              .looper
-              .poll_all_timeout(Duration::from_millis(0))
-              .unwrap(),
-          );
-          self.start_cause = event::StartCause::Poll;
+        self.start_cause = event::StartCause::WaitCancelled {
+          start,
+          requested_resume: None,
         }
-        ControlFlow::Wait => {
-          self.first_event = poll(self.looper.poll_all().unwrap());
-          self.start_cause = event::StartCause::WaitCancelled {
-            start: Instant::now(),
-            requested_resume: None,
+      }
+      ControlFlow::WaitUntil(instant) => {
+        let duration = instant.saturating_duration_since(start);
+        let duration = timeout.map_or(duration, |timeout| timeout.min(duration));
+        self.first_event = poll(self.looper.poll_all_timeout(duration).unwrap());
+        self.start_cause = if self.first_event.is_some() {
+          event::StartCause::WaitCancelled {
+            start,
+            requested_resume: Some(instant),
           }
-        }
-        ControlFlow::WaitUntil(instant) => {
-          let start = Instant::now();
-          let duration = if instant <= start {
-            Duration::default()
-          } else {
-            instant - start
-          };
-          self.first_event = poll(self.looper.poll_all_timeout(duration).unwrap());
-          self.start_cause = if self.first_event.is_some() {
-            event::StartCause::WaitCancelled {
-              start,
-              requested_resume: Some(instant),
-            }
-          } else {
-            event::StartCause::ResumeTimeReached {
-              start,
-              requested_resume: instant,
-            }
+        } else if Instant::now() >= instant {
+          event::StartCause::ResumeTimeReached {
+            start,
+            requested_resume: instant,
           }
+        } else {
+          return false;
         }
       }
     }
+    true
   }
 
   pub fn window_target(&self) -> &event_loop::EventLoopWindowTarget<T> {
     &self.window_target
   }
 
+  pub fn set_shutdown_timeout(&mut self, _timeout: Duration) {}
+
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
       queue: self.sender_to_clone.clone(),
       looper: ForeignLooper::for_thread().expect("called from event loop thread"),
     }
   }
+
+  pub fn create_typed_proxy(&self) -> EventLoopProxy<event::TypedUserEvent> {
+    EventLoopProxy {
+      queue: self.typed_sender_to_clone.clone(),
+      looper: ForeignLooper::for_thread().expect("called from event loop thread"),
+    }
+  }
 }
 
 pub struct EventLoopProxy<T: 'static> {
@@ -461,6 +558,30 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     debug!("`EventLoopWindowTarget::cursor_position` is ignored on Android");
     Ok((0, 0).into())
   }
+
+  pub fn system_appearance(&self) -> SystemAppearance {
+    SystemAppearance::default()
+  }
+
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    None
+  }
+
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    KeyboardLayout::default()
+  }
+
+  pub fn key_for_code(&self, _code: KeyCode) -> Key<'static> {
+    Key::Unidentified(NativeKeyCode::Unidentified)
+  }
+
+  pub fn code_for_key(&self, _key: &Key<'_>) -> Option<KeyCode> {
+    None
+  }
+
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    None
+  }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -486,6 +607,67 @@ pub struct PlatformSpecificWindowBuilderAttributes;
 
 pub struct Window;
 
+#[derive(Clone)]
+pub struct CloseToken;
+
+impl CloseToken {
+  pub fn proceed(self) {}
+}
+
+/// The format is only known by its MIME type, see `crate::data_format::DataFormat`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat;
+
+impl DataFormat {
+  pub fn register(_mime_type: &str) -> Result<Self, error::OsError> {
+    Ok(Self)
+  }
+}
+
+#[cfg(feature = "notifications")]
+pub struct Notifier;
+
+#[cfg(feature = "notifications")]
+impl Notifier {
+  pub fn new<T>(
+    _target: &EventLoopWindowTarget<T>,
+    _on_activated: crate::notification::ActivationHandler,
+  ) -> Self {
+    Self
+  }
+
+  pub fn show(
+    &self,
+    _id: crate::notification::NotificationId,
+    _notification: &crate::notification::Notification,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+}
+
+pub enum SurfaceRegion {}
+
+impl SurfaceRegion {
+  pub fn set_rect(&self, _rect: window::Rect) {
+    match *self {}
+  }
+
+  pub fn rect(&self) -> window::Rect {
+    match *self {}
+  }
+
+  pub fn set_visible(&self, _visible: bool) {
+    match *self {}
+  }
+
+  #[cfg(feature = "rwh_06")]
+  pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+    match *self {}
+  }
+}
+
 impl Window {
   pub fn new<T: 'static>(
     _el: &EventLoopWindowTarget<T>,
@@ -528,10 +710,54 @@ impl Window {
     MonitorHandle.scale_factor()
   }
 
+  pub fn set_scale_factor_override(&self, _scale_factor: Option<f64>) {}
+
   pub fn request_redraw(&self) {
     // TODO
   }
 
+  pub fn set_frame_pacing(&self, _enabled: bool) {}
+
+  pub fn set_jank_detection(&self, _enabled: bool) {}
+
+  pub fn applied_attributes_report(&self) -> window::AttributesReport {
+    window::AttributesReport::default()
+  }
+
+  pub fn run_modal(&self, _handler: &mut dyn FnMut(&mut ControlFlow)) {}
+
+  pub fn close(&self) {}
+
+  pub fn destroy(&self) {}
+
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken
+  }
+
+  pub fn create_surface_region(
+    &self,
+    _rect: window::Rect,
+  ) -> Result<SurfaceRegion, error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn sendable_handle(&self) -> Result<u64, error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  /// Never called, `sendable_handle` is unsupported.
+  pub fn detach(self) {}
+
+  pub fn adopt_from_handle(&self, _handle: u64) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
   pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, error::NotSupportedError> {
     Err(error::NotSupportedError::new())
   }
@@ -567,6 +793,12 @@ impl Window {
 
   pub fn set_visible(&self, _visibility: bool) {}
 
+  pub fn set_enabled(&self, _enabled: bool) {}
+
+  pub fn is_enabled(&self) -> bool {
+    true
+  }
+
   pub fn set_focus(&self) {
     //FIXME: implementation goes here
     warn!("set_focus not yet implemented on Android");
@@ -577,6 +809,14 @@ impl Window {
     false
   }
 
+  pub fn raise(&self) {}
+
+  pub fn focus_without_raise(&self) {}
+
+  pub fn set_focus_policy(&self, _policy: window::FocusPolicy) {}
+
+  pub fn set_drop_policy(&self, _policy: window::DropPolicy) {}
+
   pub fn set_resizable(&self, _resizeable: bool) {
     warn!("`Window::set_resizable` is ignored on Android")
   }
@@ -593,6 +833,10 @@ impl Window {
     warn!("`Window::set_closable` is ignored on Android")
   }
 
+  pub fn set_intercept_minimize(&self, _intercept: bool) {}
+
+  pub fn set_system_gestures_enabled(&self, _gestures: window::SystemGestures) {}
+
   pub fn set_minimized(&self, _minimized: bool) {}
 
   pub fn set_maximized(&self, _maximized: bool) {}
@@ -651,19 +895,67 @@ impl Window {
 
   pub fn set_window_icon(&self, _window_icon: Option<crate::icon::Icon>) {}
 
-  pub fn set_ime_position(&self, _position: Position) {}
+  pub fn set_window_icon_frames(&self, _frames: Vec<crate::icon::Icon>, _interval: Duration) {}
+
+  pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+  pub fn set_ime_allowed(&self, _allowed: bool) {}
+
+  pub fn set_ime_purpose(&self, _purpose: window::ImePurpose) {}
+
+  pub fn add_accelerator(
+    &self,
+    _id: AcceleratorId,
+    _accelerator: Accelerator,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn remove_accelerator(&self, _id: AcceleratorId) {}
 
   pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
   pub fn set_cursor_icon(&self, _: window::CursorIcon) {}
 
+  pub fn set_custom_cursor(&self, _: crate::icon::CustomCursor) {}
+
   pub fn set_cursor_position(&self, _: Position) -> Result<(), error::ExternalError> {
     Err(error::ExternalError::NotSupported(
       error::NotSupportedError::new(),
     ))
   }
 
-  pub fn set_cursor_grab(&self, _: bool) -> Result<(), error::ExternalError> {
+  pub fn set_cursor_grab(&self, _: window::CursorGrabMode) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn set_raw_mouse_motion(&self, _: bool) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn set_pointer_acceleration(
+    &self,
+    _: Option<window::AccelerationProfile>,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn set_touch_keyboard_visible(&self, visible: bool) -> Result<(), error::ExternalError> {
+    set_soft_input_visible(visible).map_err(|e| {
+      log::warn!("Failed to toggle the soft input: {}", e);
+      error::ExternalError::Os(os_error!(OsError))
+    })
+  }
+
+  pub fn set_keyboard_grab(&self, _: bool) -> Result<(), error::ExternalError> {
     Err(error::ExternalError::NotSupported(
       error::NotSupportedError::new(),
     ))
@@ -771,6 +1063,64 @@ impl Display for OsError {
 
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
+fn pointer_kind(motion_event: &ndk::event::MotionEvent, index: usize) -> event::PointerKind {
+  let tool_type =
+    unsafe { ndk_sys::AMotionEvent_getToolType(motion_event.ptr().as_ptr(), index as _) } as u32;
+  match tool_type {
+    ndk_sys::AMOTION_EVENT_TOOL_TYPE_FINGER | ndk_sys::AMOTION_EVENT_TOOL_TYPE_PALM => {
+      event::PointerKind::Touch
+    }
+    ndk_sys::AMOTION_EVENT_TOOL_TYPE_STYLUS | ndk_sys::AMOTION_EVENT_TOOL_TYPE_ERASER => {
+      event::PointerKind::Pen
+    }
+    ndk_sys::AMOTION_EVENT_TOOL_TYPE_MOUSE => event::PointerKind::Mouse,
+    _ => event::PointerKind::Unknown,
+  }
+}
+
+/// Shows or hides the soft input through the `InputMethodManager` of the activity, for the
+/// decor view of its window.
+fn set_soft_input_visible(visible: bool) -> jni::errors::Result<()> {
+  let ctx = ndk_context::android_context();
+  let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }?;
+  let mut env = vm.attach_current_thread()?;
+  let activity = unsafe { jni::objects::JObject::from_raw(ctx.context().cast()) };
+  let window = env
+    .call_method(&activity, "getWindow", "()Landroid/view/Window;", &[])?
+    .l()?;
+  let view = env
+    .call_method(&window, "getDecorView", "()Landroid/view/View;", &[])?
+    .l()?;
+  let service_name = env.new_string("input_method")?;
+  let input_method_manager = env
+    .call_method(
+      &activity,
+      "getSystemService",
+      "(Ljava/lang/String;)Ljava/lang/Object;",
+      &[(&service_name).into()],
+    )?
+    .l()?;
+  if visible {
+    env.call_method(
+      &input_method_manager,
+      "showSoftInput",
+      "(Landroid/view/View;I)Z",
+      &[(&view).into(), 0.into()],
+    )?;
+  } else {
+    let token = env
+      .call_method(&view, "getWindowToken", "()Landroid/os/IBinder;", &[])?
+      .l()?;
+    env.call_method(
+      &input_method_manager,
+      "hideSoftInputFromWindow",
+      "(Landroid/os/IBinder;I)Z",
+      &[(&token).into(), 0.into()],
+    )?;
+  }
+  Ok(())
+}
+
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct MonitorHandle;
 
@@ -779,6 +1129,10 @@ impl MonitorHandle {
     Some("Android Device".to_owned())
   }
 
+  pub fn is_primary(&self) -> bool {
+    true
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     // TODO decide how to get JNIENV
     if let Some(w) = ndk_glue::window_manager() {
@@ -821,6 +1175,13 @@ impl MonitorHandle {
     (0, 0).into()
   }
 
+  pub fn work_area(&self) -> window::Rect {
+    window::Rect {
+      position: self.position().into(),
+      size: self.size().into(),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     let config = CONFIG.read().unwrap();
     config
@@ -844,6 +1205,46 @@ impl MonitorHandle {
     });
     v.into_iter()
   }
+
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    None
+  }
+
+  pub fn manufacturer(&self) -> Option<String> {
+    None
+  }
+
+  pub fn model(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn serial_number(&self) -> Option<String> {
+    None
+  }
+
+  pub fn color_info(&self) -> monitor::ColorInfo {
+    monitor::ColorInfo::default()
+  }
+
+  pub fn icc_profile(&self) -> Option<monitor::IccProfile> {
+    None
+  }
+
+  pub fn set_video_mode(&self, _video_mode: &VideoMode) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn reset_video_mode(&self) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -0,0 +1,75 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsing of the EDID block a monitor identifies itself with.
+
+/// Fixed pattern the base EDID block starts with.
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+/// Tag of the display descriptor holding the serial number as text.
+const SERIAL_NUMBER_TAG: u8 = 0xFF;
+
+/// Identification of a monitor, read from its base EDID block.
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+pub struct Edid {
+  pub manufacturer: Option<String>,
+  pub product_code: u16,
+  pub serial_number: Option<String>,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+impl Edid {
+  pub fn parse(bytes: &[u8]) -> Option<Self> {
+    if bytes.len() < 128 || bytes[..8] != HEADER {
+      return None;
+    }
+    let manufacturer = manufacturer_id(u16::from_be_bytes([bytes[8], bytes[9]]));
+    let product_code = u16::from_le_bytes([bytes[10], bytes[11]]);
+    // Many monitors leave the numeric serial number empty and only have the text descriptor.
+    let serial_number = bytes[54..126]
+      .chunks(18)
+      .find(|descriptor| descriptor[..3] == [0, 0, 0] && descriptor[3] == SERIAL_NUMBER_TAG)
+      .map(|descriptor| descriptor_text(&descriptor[5..]))
+      .filter(|text| !text.is_empty())
+      .or_else(|| {
+        serial_number(u32::from_le_bytes([
+          bytes[12], bytes[13], bytes[14], bytes[15],
+        ]))
+      });
+    Some(Edid {
+      manufacturer,
+      product_code,
+      serial_number,
+    })
+  }
+}
+
+/// Decodes the three-letter PNP ID of a manufacturer, packed in 5 bits per letter.
+pub fn manufacturer_id(id: u16) -> Option<String> {
+  let letters = [(id >> 10) & 0x1F, (id >> 5) & 0x1F, id & 0x1F];
+  if letters.iter().any(|letter| *letter == 0 || *letter > 26) {
+    return None;
+  }
+  Some(
+    letters
+      .iter()
+      .map(|letter| (b'A' + *letter as u8 - 1) as char)
+      .collect(),
+  )
+}
+
+/// Formats a numeric serial number, `None` if the manufacturer didn't set one.
+pub fn serial_number(serial: u32) -> Option<String> {
+  match serial {
+    0 | u32::MAX => None,
+    serial => Some(serial.to_string()),
+  }
+}
+
+/// Text of a display descriptor, ended by a line feed and padded with spaces.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn descriptor_text(text: &[u8]) -> String {
+  let end = text.iter().position(|c| *c == b'\n').unwrap_or(text.len());
+  String::from_utf8_lossy(&text[..end]).trim().to_string()
+}
@@ -8,6 +8,7 @@ use std::{
   fmt::{self, Debug},
   marker::PhantomData,
   mem, ptr,
+  time::Duration,
 };
 
 use crossbeam_channel::{self as channel, Receiver, Sender};
@@ -15,10 +16,12 @@ use crossbeam_channel::{self as channel, Receiver, Sender};
 use crate::{
   dpi::{LogicalSize, PhysicalPosition},
   error::ExternalError,
-  event::Event,
+  event::{Event, TypedUserEvent},
   event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget},
+  keyboard::{Key, KeyCode, KeyRepeatSettings, KeyboardLayout, NativeKeyCode},
   monitor::MonitorHandle as RootMonitorHandle,
   platform::ios::Idiom,
+  window::SystemAppearance,
 };
 
 use crate::platform_impl::platform::{
@@ -55,6 +58,8 @@ pub enum EventProxy {
 pub struct EventLoopWindowTarget<T: 'static> {
   receiver: Receiver<T>,
   sender_to_clone: Sender<T>,
+  typed_receiver: Receiver<TypedUserEvent>,
+  typed_sender_to_clone: Sender<TypedUserEvent>,
 }
 
 impl<T: 'static> EventLoopWindowTarget<T> {
@@ -94,17 +99,44 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     debug!("`EventLoopWindowTarget::cursor_position` is ignored on iOS");
     Ok((0, 0).into())
   }
+
+  pub fn system_appearance(&self) -> SystemAppearance {
+    SystemAppearance::default()
+  }
+
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    None
+  }
+
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    KeyboardLayout::default()
+  }
+
+  pub fn key_for_code(&self, _code: KeyCode) -> Key<'static> {
+    Key::Unidentified(NativeKeyCode::Unidentified)
+  }
+
+  pub fn code_for_key(&self, _key: &Key<'_>) -> Option<KeyCode> {
+    None
+  }
+
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    None
+  }
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct PlatformSpecificEventLoopAttributes {}
+#[derive(Default)]
+pub(crate) struct PlatformSpecificEventLoopAttributes {
+  pub(crate) msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+}
 
 pub struct EventLoop<T: 'static> {
   window_target: RootEventLoopWindowTarget<T>,
+  application_class: Option<&'static str>,
 }
 
 impl<T: 'static> EventLoop<T> {
-  pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
     static mut SINGLETON_INIT: bool = false;
     unsafe {
       assert_main_thread!("`EventLoop` can only be created on the main thread on iOS");
@@ -116,8 +148,13 @@ impl<T: 'static> EventLoop<T> {
       SINGLETON_INIT = true;
       view::create_delegate_class();
     }
+    let application_class = attributes
+      .msg_hook
+      .take()
+      .map(view::create_application_class);
 
     let (sender_to_clone, receiver) = channel::unbounded();
+    let (typed_sender_to_clone, typed_receiver) = channel::unbounded();
 
     // this line sets up the main run loop before `UIApplicationMain`
     setup_control_flow_observers();
@@ -127,9 +164,12 @@ impl<T: 'static> EventLoop<T> {
         p: EventLoopWindowTarget {
           receiver,
           sender_to_clone,
+          typed_receiver,
+          typed_sender_to_clone,
         },
         _marker: PhantomData,
       },
+      application_class,
     }
   }
 
@@ -151,10 +191,14 @@ impl<T: 'static> EventLoop<T> {
         event_loop: self.window_target,
       }));
 
+      let principal_class = match self.application_class {
+        Some(name) => NSStringRust::alloc(nil).init_str(name),
+        None => nil,
+      };
       UIApplicationMain(
         0,
         ptr::null(),
-        nil,
+        principal_class,
         NSStringRust::alloc(nil).init_str("AppDelegate"),
       );
       unreachable!()
@@ -165,9 +209,15 @@ impl<T: 'static> EventLoop<T> {
     EventLoopProxy::new(self.window_target.p.sender_to_clone.clone())
   }
 
+  pub fn create_typed_proxy(&self) -> EventLoopProxy<TypedUserEvent> {
+    EventLoopProxy::new(self.window_target.p.typed_sender_to_clone.clone())
+  }
+
   pub fn window_target(&self) -> &RootEventLoopWindowTarget<T> {
     &self.window_target
   }
+
+  pub fn set_shutdown_timeout(&mut self, _timeout: Duration) {}
 }
 
 // EventLoopExtIOS
@@ -369,6 +419,9 @@ where
     for event in self.event_loop.p.receiver.try_iter() {
       (self.f)(Event::UserEvent(event), &self.event_loop, control_flow);
     }
+    for event in self.event_loop.p.typed_receiver.try_iter() {
+      (self.f)(Event::TypedUserEvent(event), &self.event_loop, control_flow);
+    }
   }
 }
 
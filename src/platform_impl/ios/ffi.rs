@@ -108,6 +108,7 @@ pub enum UITouchType {
   Direct = 0,
   Indirect,
   Pencil,
+  IndirectPointer,
 }
 
 #[repr(C)]
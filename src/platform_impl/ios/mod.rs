@@ -88,11 +88,44 @@ pub use self::{
   event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
   keycode::{keycode_from_scancode, keycode_to_scancode},
   monitor::{MonitorHandle, VideoMode},
-  window::{PlatformSpecificWindowBuilderAttributes, Window, WindowId},
+  window::{CloseToken, PlatformSpecificWindowBuilderAttributes, SurfaceRegion, Window, WindowId},
 };
 
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
+/// The format is only known by its MIME type, see `crate::data_format::DataFormat`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat;
+
+impl DataFormat {
+  pub fn register(_mime_type: &str) -> Result<Self, crate::error::OsError> {
+    Ok(Self)
+  }
+}
+
+#[cfg(feature = "notifications")]
+pub struct Notifier;
+
+#[cfg(feature = "notifications")]
+impl Notifier {
+  pub fn new<T>(
+    _target: &EventLoopWindowTarget<T>,
+    _on_activated: crate::notification::ActivationHandler,
+  ) -> Self {
+    Self
+  }
+
+  pub fn show(
+    &self,
+    _id: crate::notification::NotificationId,
+    _notification: &crate::notification::Notification,
+  ) -> Result<(), crate::error::ExternalError> {
+    Err(crate::error::ExternalError::NotSupported(
+      crate::error::NotSupportedError::new(),
+    ))
+  }
+}
+
 // todo: implement iOS keyboard event
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KeyEventExtra {}
@@ -8,13 +8,20 @@ use std::{
   ops::{Deref, DerefMut},
 };
 
+use objc::runtime::{BOOL, YES};
+
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  error::ExternalError,
+  monitor::{
+    ColorInfo, ColorSpace, IccProfile, MonitorHandle as RootMonitorHandle,
+    VideoMode as RootVideoMode,
+  },
   platform_impl::platform::{
     app_state,
     ffi::{id, nil, CGFloat, CGRect, CGSize, NSInteger, NSUInteger},
   },
+  window::Rect,
 };
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -217,6 +224,10 @@ impl Inner {
     }
   }
 
+  pub fn is_primary(&self) -> bool {
+    unsafe { self.uiscreen == main_uiscreen().uiscreen }
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     unsafe {
       let bounds: CGRect = msg_send![self.ui_screen(), nativeBounds];
@@ -231,6 +242,13 @@ impl Inner {
     }
   }
 
+  pub fn work_area(&self) -> Rect {
+    Rect {
+      position: self.position().into(),
+      size: self.size().into(),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     unsafe {
       let scale: CGFloat = msg_send![self.ui_screen(), nativeScale];
@@ -254,6 +272,75 @@ impl Inner {
 
     modes.into_iter()
   }
+
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    // Devices without `maximumFramesPerSecond` all refresh at 60 Hz, see `VideoMode`.
+    let max_fps: NSInteger = if app_state::os_capabilities().maximum_frames_per_second {
+      unsafe { msg_send![self.uiscreen, maximumFramesPerSecond] }
+    } else {
+      60
+    };
+    Some(max_fps as u32 * 1000)
+  }
+
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    self
+      .refresh_rate_millihertz()
+      .map(|refresh_rate| refresh_rate > 60_000)
+  }
+
+  pub fn manufacturer(&self) -> Option<String> {
+    None
+  }
+
+  pub fn model(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn serial_number(&self) -> Option<String> {
+    None
+  }
+
+  pub fn color_info(&self) -> ColorInfo {
+    let mut info = ColorInfo::default();
+    unsafe {
+      let traits: id = msg_send![self.uiscreen, traitCollection];
+      let gamut: NSInteger = msg_send![traits, displayGamut];
+      info.color_space = match gamut {
+        0 => ColorSpace::Srgb,
+        1 => ColorSpace::DisplayP3,
+        _ => ColorSpace::Unknown,
+      };
+      // Available since iOS 16.
+      let has_edr: BOOL = msg_send![self.uiscreen, respondsToSelector: sel!(potentialEDRHeadroom)];
+      if has_edr == YES {
+        let potential: CGFloat = msg_send![self.uiscreen, potentialEDRHeadroom];
+        let current: CGFloat = msg_send![self.uiscreen, currentEDRHeadroom];
+        info.hdr_enabled = potential > 1.0;
+        info.hdr_headroom = Some(current as f32);
+      }
+    }
+    info
+  }
+
+  pub fn icc_profile(&self) -> Option<IccProfile> {
+    None
+  }
+
+  pub fn set_video_mode(&self, video_mode: &VideoMode) -> Result<(), ExternalError> {
+    unsafe {
+      let () = msg_send![self.uiscreen, setCurrentMode: video_mode.screen_mode.0];
+    }
+    Ok(())
+  }
+
+  pub fn reset_video_mode(&self) -> Result<(), ExternalError> {
+    unsafe {
+      let mode: id = msg_send![self.uiscreen, preferredMode];
+      let () = msg_send![self.uiscreen, setCurrentMode: mode];
+    }
+    Ok(())
+  }
 }
 
 // MonitorHandleExtIOS
@@ -2,16 +2,16 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashMap, ffi::c_char};
+use std::{cell::RefCell, collections::HashMap, ffi::c_void, os::raw::c_char};
 
 use objc::{
   declare::ClassDecl,
-  runtime::{Class, Object, Sel, BOOL, NO, YES},
+  runtime::{Class, Object, Protocol, Sel, BOOL, NO, YES},
 };
 
 use crate::{
   dpi::PhysicalPosition,
-  event::{DeviceId as RootDeviceId, Event, Force, Touch, TouchPhase, WindowEvent},
+  event::{DeviceId as RootDeviceId, Event, Force, PointerKind, Touch, TouchPhase, WindowEvent},
   platform::ios::MonitorHandleExtIOS,
   platform_impl::platform::{
     app_state::{self, OSCapabilities},
@@ -23,9 +23,14 @@ use crate::{
     window::PlatformSpecificWindowBuilderAttributes,
     DeviceId,
   },
-  window::{Fullscreen, WindowAttributes, WindowId as RootWindowId},
+  window::{Fullscreen, ImePurpose, WindowAttributes, WindowId as RootWindowId},
 };
 
+/// Ivar holding the `UIKeyboardType` of the view, set by `set_ime_purpose`.
+const KEYBOARD_TYPE: &str = "taoKeyboardType";
+/// Ivar holding whether the view hides the text typed, set by `set_ime_purpose`.
+const SECURE_TEXT_ENTRY: &str = "taoSecureTextEntry";
+
 macro_rules! add_property {
     (
         $decl:ident,
@@ -276,6 +281,12 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
               location: physical_location,
               force,
               phase,
+              pointer_kind: match touch_type {
+                UITouchType::Direct => PointerKind::Touch,
+                UITouchType::Pencil => PointerKind::Pen,
+                UITouchType::IndirectPointer => PointerKind::Mouse,
+                UITouchType::Indirect => PointerKind::Unknown,
+              },
             }),
           }));
         }
@@ -283,9 +294,48 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
       }
     }
 
+    // `UIKeyInput`, so that the view brings up the touch keyboard once it's the first responder.
+    extern "C" fn can_become_first_responder(_: &Object, _: Sel) -> BOOL {
+      YES
+    }
+
+    extern "C" fn has_text(_: &Object, _: Sel) -> BOOL {
+      YES
+    }
+
+    extern "C" fn insert_text(object: &Object, _: Sel, text: id) {
+      unsafe {
+        let utf8: *const c_char = msg_send![text, UTF8String];
+        if utf8.is_null() {
+          return;
+        }
+        let text = std::ffi::CStr::from_ptr(utf8)
+          .to_string_lossy()
+          .into_owned();
+        send_ime_text(object, text);
+      }
+    }
+
+    extern "C" fn delete_backward(object: &Object, _: Sel) {
+      send_ime_text(object, "\u{8}".to_owned());
+    }
+
+    extern "C" fn keyboard_type(object: &Object, _: Sel) -> isize {
+      unsafe { *object.get_ivar::<isize>(KEYBOARD_TYPE) }
+    }
+
+    extern "C" fn is_secure_text_entry(object: &Object, _: Sel) -> BOOL {
+      unsafe { *object.get_ivar::<BOOL>(SECURE_TEXT_ENTRY) }
+    }
+
     let mut decl = ClassDecl::new(&format!("TaoUIView{}", ID), root_view_class)
       .expect("Failed to declare class `TaoUIView`");
     ID += 1;
+    if let Some(protocol) = Protocol::get("UIKeyInput") {
+      decl.add_protocol(protocol);
+    }
+    decl.add_ivar::<isize>(KEYBOARD_TYPE);
+    decl.add_ivar::<BOOL>(SECURE_TEXT_ENTRY);
     decl.add_method(
       sel!(drawRect:),
       draw_rect as extern "C" fn(&Object, Sel, CGRect),
@@ -316,10 +366,68 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
       handle_touches as extern "C" fn(this: &Object, _: Sel, _: id, _: id),
     );
 
+    decl.add_method(
+      sel!(canBecomeFirstResponder),
+      can_become_first_responder as extern "C" fn(&Object, Sel) -> BOOL,
+    );
+    decl.add_method(
+      sel!(hasText),
+      has_text as extern "C" fn(&Object, Sel) -> BOOL,
+    );
+    decl.add_method(
+      sel!(insertText:),
+      insert_text as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(deleteBackward),
+      delete_backward as extern "C" fn(&Object, Sel),
+    );
+    decl.add_method(
+      sel!(keyboardType),
+      keyboard_type as extern "C" fn(&Object, Sel) -> isize,
+    );
+    decl.add_method(
+      sel!(isSecureTextEntry),
+      is_secure_text_entry as extern "C" fn(&Object, Sel) -> BOOL,
+    );
+
     decl.register()
   })
 }
 
+fn send_ime_text(view: &Object, text: String) {
+  unsafe {
+    let window: id = msg_send![view, window];
+    if window.is_null() {
+      return;
+    }
+    app_state::handle_nonuser_event(EventWrapper::StaticEvent(Event::WindowEvent {
+      window_id: RootWindowId(window.into()),
+      event: WindowEvent::ReceivedImeText(text),
+    }));
+  }
+}
+
+/// Picks the touch keyboard shown for `view`, reloaded right away if it's up already.
+// requires main thread
+pub unsafe fn set_ime_purpose(view: id, purpose: ImePurpose) {
+  // `UIKeyboardType`
+  let keyboard_type: isize = match purpose {
+    ImePurpose::Url => 3,
+    ImePurpose::Number => 4,
+    ImePurpose::Email => 7,
+    _ => 0,
+  };
+  let secure = if purpose == ImePurpose::Password {
+    YES
+  } else {
+    NO
+  };
+  (*view).set_ivar::<isize>(KEYBOARD_TYPE, keyboard_type);
+  (*view).set_ivar::<BOOL>(SECURE_TEXT_ENTRY, secure);
+  let () = msg_send![view, reloadInputViews];
+}
+
 // requires main thread
 unsafe fn get_view_controller_class() -> &'static Class {
   static mut CLASS: Option<&'static Class> = None;
@@ -541,7 +649,7 @@ pub unsafe fn create_window(
 
       msg_send![window, setScreen: uiscreen]
     }
-    None => (),
+    Some(Fullscreen::Spanning(_)) | None => (),
   }
 
   window
@@ -661,3 +769,41 @@ pub fn create_delegate_class() {
     decl.register();
   }
 }
+
+thread_local! {
+  static MSG_HOOK: RefCell<Option<Box<dyn FnMut(*const c_void) -> bool>>> = RefCell::new(None);
+}
+
+/// Declares the `UIApplication` subclass which passes every event to `msg_hook` before sending
+/// it, returning its name.
+pub fn create_application_class(
+  msg_hook: Box<dyn FnMut(*const c_void) -> bool + 'static>,
+) -> &'static str {
+  extern "C" fn send_event(object: &Object, _: Sel, event: id) {
+    // A nested `sendEvent:` from within the hook isn't filtered again.
+    let consumed = MSG_HOOK.with(|hook| match hook.try_borrow_mut() {
+      Ok(mut hook) => hook
+        .as_deref_mut()
+        .map_or(false, |hook| hook(event as *const c_void)),
+      Err(_) => false,
+    });
+    if !consumed {
+      unsafe {
+        let () = msg_send![super(object, class!(UIApplication)), sendEvent: event];
+      }
+    }
+  }
+
+  MSG_HOOK.with(|hook| *hook.borrow_mut() = Some(msg_hook));
+
+  let mut decl = ClassDecl::new("TaoUIApplication", class!(UIApplication))
+    .expect("Failed to declare class `TaoUIApplication`");
+  unsafe {
+    decl.add_method(
+      sel!(sendEvent:),
+      send_event as extern "C" fn(&Object, Sel, id),
+    );
+  }
+  decl.register();
+  "TaoUIApplication"
+}
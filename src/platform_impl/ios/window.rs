@@ -13,7 +13,9 @@ use crate::{
   dpi::{self, LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
   event::{Event, WindowEvent},
-  icon::Icon,
+  event_loop::ControlFlow,
+  global_shortcut::{Accelerator, AcceleratorId},
+  icon::{CustomCursor, Icon},
   monitor::MonitorHandle as RootMonitorHandle,
   platform::ios::{MonitorHandleExtIOS, ScreenEdge, ValidOrientations},
   platform_impl::platform::{
@@ -26,8 +28,9 @@ use crate::{
     monitor, view, EventLoopWindowTarget, MonitorHandle,
   },
   window::{
-    CursorIcon, Fullscreen, ResizeDirection, Theme, UserAttentionType, WindowAttributes,
-    WindowId as RootWindowId, WindowSizeConstraints,
+    AccelerationProfile, AttributesReport, CursorGrabMode, CursorIcon, DropPolicy, FocusPolicy,
+    Fullscreen, ImePurpose, Rect, ResizeDirection, SystemGestures, Theme, UserAttentionType,
+    WindowAttributes, WindowId as RootWindowId, WindowSizeConstraints,
   },
 };
 
@@ -67,6 +70,19 @@ impl Inner {
     }
   }
 
+  pub fn set_enabled(&self, enabled: bool) {
+    unsafe {
+      let () = msg_send![self.window, setUserInteractionEnabled: enabled];
+    }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    unsafe {
+      let enabled: BOOL = msg_send![self.window, isUserInteractionEnabled];
+      enabled == YES
+    }
+  }
+
   pub fn set_focus(&self) {
     //FIXME: implementation goes here
     warn!("set_focus not yet implemented on iOS");
@@ -77,6 +93,22 @@ impl Inner {
     false
   }
 
+  pub fn raise(&self) {
+    warn!("`Window::raise` is ignored on iOS")
+  }
+
+  pub fn focus_without_raise(&self) {
+    warn!("`Window::focus_without_raise` is ignored on iOS")
+  }
+
+  pub fn set_focus_policy(&self, _policy: FocusPolicy) {
+    warn!("`Window::set_focus_policy` is ignored on iOS")
+  }
+
+  pub fn set_drop_policy(&self, _policy: DropPolicy) {
+    warn!("`Window::set_drop_policy` is ignored on iOS")
+  }
+
   pub fn request_redraw(&self) {
     unsafe {
       if self.gl_or_metal_backed {
@@ -95,6 +127,30 @@ impl Inner {
     }
   }
 
+  pub fn close(&self) {
+    warn!("`Window::close` is ignored on iOS")
+  }
+
+  pub fn destroy(&self) {
+    warn!("`Window::destroy` is ignored on iOS")
+  }
+
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken
+  }
+
+  pub fn create_surface_region(&self, _rect: Rect) -> Result<SurfaceRegion, ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn sendable_handle(&self) -> Result<u64, ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn adopt_from_handle(&self, _handle: u64) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
     unsafe {
       let safe_area = self.safe_area_screen_space();
@@ -197,15 +253,68 @@ impl Inner {
     }
   }
 
+  pub fn set_scale_factor_override(&self, _scale_factor: Option<f64>) {
+    debug!("`Window::set_scale_factor_override` ignored on iOS")
+  }
+
   pub fn set_cursor_icon(&self, _cursor: CursorIcon) {
     debug!("`Window::set_cursor_icon` ignored on iOS")
   }
 
+  pub fn set_frame_pacing(&self, _enabled: bool) {
+    warn!("`Window::set_frame_pacing` is ignored on iOS")
+  }
+
+  pub fn set_jank_detection(&self, _enabled: bool) {
+    warn!("`Window::set_jank_detection` is ignored on iOS")
+  }
+
+  pub fn applied_attributes_report(&self) -> AttributesReport {
+    AttributesReport::default()
+  }
+
+  pub fn run_modal(&self, _handler: &mut dyn FnMut(&mut ControlFlow)) {
+    warn!("`Window::run_modal` is ignored on iOS")
+  }
+
+  pub fn set_custom_cursor(&self, _cursor: CustomCursor) {
+    debug!("`Window::set_custom_cursor` ignored on iOS")
+  }
+
   pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
-  pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+  pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn set_raw_mouse_motion(&self, _enabled: bool) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn set_pointer_acceleration(
+    &self,
+    _profile: Option<AccelerationProfile>,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn set_touch_keyboard_visible(&self, visible: bool) -> Result<(), ExternalError> {
+    unsafe {
+      let changed: BOOL = if visible {
+        msg_send![self.view, becomeFirstResponder]
+      } else {
+        msg_send![self.view, resignFirstResponder]
+      };
+      if changed == NO {
+        warn!("The view refused to change the first responder");
+      }
+    }
+    Ok(())
+  }
+
+  pub fn set_keyboard_grab(&self, _grab: bool) -> Result<(), ExternalError> {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
@@ -230,6 +339,14 @@ impl Inner {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
+  pub fn set_intercept_minimize(&self, _intercept: bool) {
+    warn!("`Window::set_intercept_minimize` is ignored on iOS")
+  }
+
+  pub fn set_system_gestures_enabled(&self, _gestures: SystemGestures) {
+    warn!("`Window::set_system_gestures_enabled` is ignored on iOS")
+  }
+
   pub fn set_minimized(&self, _minimized: bool) {
     warn!("`Window::set_minimized` is ignored on iOS")
   }
@@ -289,6 +406,10 @@ impl Inner {
         Some(Fullscreen::Borderless(monitor)) => monitor
           .unwrap_or_else(|| self.current_monitor_inner())
           .ui_screen() as id,
+        Some(Fullscreen::Spanning(_)) => {
+          warn!("`Fullscreen::Spanning` is ignored on iOS");
+          return;
+        }
         None => {
           warn!("`Window::set_fullscreen(None)` ignored on iOS");
           return;
@@ -350,10 +471,32 @@ impl Inner {
     warn!("`Window::set_window_icon` is ignored on iOS")
   }
 
-  pub fn set_ime_position(&self, _position: Position) {
-    warn!("`Window::set_ime_position` is ignored on iOS")
+  pub fn set_window_icon_frames(&self, _frames: Vec<Icon>, _interval: std::time::Duration) {
+    warn!("`Window::set_window_icon_frames` is ignored on iOS")
+  }
+
+  pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
+    warn!("`Window::set_ime_cursor_area` is ignored on iOS")
+  }
+
+  pub fn set_ime_allowed(&self, _allowed: bool) {
+    warn!("`Window::set_ime_allowed` is ignored on iOS")
+  }
+
+  pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+    unsafe { view::set_ime_purpose(self.view, purpose) }
   }
 
+  pub fn add_accelerator(
+    &self,
+    _id: AcceleratorId,
+    _accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn remove_accelerator(&self, _id: AcceleratorId) {}
+
   pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {
     warn!("`Window::request_user_attention` is ignored on iOS")
   }
@@ -487,7 +630,9 @@ impl Window {
           video_mode.video_mode.monitor.ui_screen() as id
         }
         Some(Fullscreen::Borderless(Some(ref monitor))) => monitor.inner.ui_screen(),
-        Some(Fullscreen::Borderless(None)) | None => monitor::main_uiscreen().ui_screen() as id,
+        Some(Fullscreen::Borderless(None)) | Some(Fullscreen::Spanning(_)) | None => {
+          monitor::main_uiscreen().ui_screen() as id
+        }
       };
 
       let screen_bounds: CGRect = msg_send![screen, bounds];
@@ -568,6 +713,9 @@ impl Window {
       Ok(result)
     }
   }
+
+  /// Never called, `sendable_handle` is unsupported.
+  pub fn detach(self) {}
 }
 
 // WindowExtIOS
@@ -712,6 +860,36 @@ impl Inner {
   }
 }
 
+#[derive(Clone)]
+pub struct CloseToken;
+
+impl CloseToken {
+  pub fn proceed(self) {
+    warn!("`CloseToken::proceed` is ignored on iOS")
+  }
+}
+
+pub enum SurfaceRegion {}
+
+impl SurfaceRegion {
+  pub fn set_rect(&self, _rect: Rect) {
+    match *self {}
+  }
+
+  pub fn rect(&self) -> Rect {
+    match *self {}
+  }
+
+  pub fn set_visible(&self, _visible: bool) {
+    match *self {}
+  }
+
+  #[cfg(feature = "rwh_06")]
+  pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+    match *self {}
+  }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct WindowId {
   window: id,
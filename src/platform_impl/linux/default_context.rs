@@ -0,0 +1,158 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Source forwarding the global default context, which GTK reads windowing events from, to the
+//! context an event loop runs on, see `EventLoopBuilderExtUnix::with_main_context`.
+//!
+//! Each iteration of the event loop context prepares the default context, polls its file
+//! descriptors along with its own and dispatches it when they are ready or its timeout expired,
+//! which is what `MainContext::iteration` does, without any wake-up of its own. The default context
+//! is owned from its prepare to its dispatch, so no other thread iterates it in between.
+
+use std::{mem, ptr};
+
+use gtk::glib::{ffi, translate::ToGlibPtr, MainContext};
+
+#[repr(C)]
+struct DefaultContextSource {
+  source: ffi::GSource,
+  /// File descriptors of the default context, polled by the source.
+  fds: Vec<ffi::GPollFD>,
+  /// Highest priority of the default context sources ready at the last prepare.
+  priority: i32,
+  /// Whether the source owns the default context, from its prepare to its dispatch.
+  owned: bool,
+  /// Whether the default context was checked since the last prepare.
+  checked: bool,
+}
+
+static FUNCS: ffi::GSourceFuncs = ffi::GSourceFuncs {
+  prepare: Some(prepare),
+  check: Some(check),
+  dispatch: Some(dispatch),
+  finalize: Some(finalize),
+  closure_callback: None,
+  closure_marshal: None,
+};
+
+/// Attaches to `context` a source dispatching the default context whenever it has work.
+pub fn forward_default_context(context: &MainContext) {
+  unsafe {
+    let source = ffi::g_source_new(
+      &FUNCS as *const _ as *mut _,
+      mem::size_of::<DefaultContextSource>() as u32,
+    );
+    let data = source as *mut DefaultContextSource;
+    ptr::addr_of_mut!((*data).fds).write(Vec::new());
+    ptr::addr_of_mut!((*data).priority).write(0);
+    ptr::addr_of_mut!((*data).owned).write(false);
+    ptr::addr_of_mut!((*data).checked).write(false);
+    ffi::g_source_attach(source, context.to_glib_none().0);
+    ffi::g_source_unref(source);
+  }
+}
+
+unsafe extern "C" fn prepare(source: *mut ffi::GSource, timeout: *mut i32) -> ffi::gboolean {
+  let data = &mut *(source as *mut DefaultContextSource);
+  let context = ffi::g_main_context_default();
+  *timeout = -1;
+  data.checked = false;
+  for fd in &mut data.fds {
+    ffi::g_source_remove_poll(source, fd);
+  }
+  // The default context is still owned when a higher priority source was dispatched instead.
+  if !data.owned {
+    // Another thread iterates the default context, nothing to forward.
+    if ffi::g_main_context_acquire(context) == ffi::GFALSE {
+      data.fds.clear();
+      return ffi::GFALSE;
+    }
+    data.owned = true;
+  }
+
+  let ready = ffi::g_main_context_prepare(context, &mut data.priority);
+  loop {
+    let n_fds = ffi::g_main_context_query(
+      context,
+      data.priority,
+      timeout,
+      data.fds.as_mut_ptr(),
+      data.fds.len() as i32,
+    ) as usize;
+    if n_fds <= data.fds.len() {
+      data.fds.truncate(n_fds);
+      break;
+    }
+    data.fds.resize(
+      n_fds,
+      ffi::GPollFD {
+        fd: 0,
+        events: 0,
+        revents: 0,
+      },
+    );
+  }
+  // `fds` isn't resized before the next prepare, the pointers stay valid while polling.
+  for fd in &mut data.fds {
+    fd.revents = 0;
+    ffi::g_source_add_poll(source, fd);
+  }
+
+  if ready != ffi::GFALSE {
+    *timeout = 0;
+  }
+  ready
+}
+
+unsafe extern "C" fn check(source: *mut ffi::GSource) -> ffi::gboolean {
+  let data = &mut *(source as *mut DefaultContextSource);
+  let ready = check_default_context(data);
+  if ready == ffi::GFALSE {
+    release_default_context(data);
+  }
+  ready
+}
+
+unsafe extern "C" fn dispatch(
+  source: *mut ffi::GSource,
+  _callback: ffi::GSourceFunc,
+  _user_data: ffi::gpointer,
+) -> ffi::gboolean {
+  let data = &mut *(source as *mut DefaultContextSource);
+  // `check` isn't called when `prepare` found the default context ready.
+  if !data.checked {
+    check_default_context(data);
+  }
+  if data.owned {
+    ffi::g_main_context_dispatch(ffi::g_main_context_default());
+  }
+  release_default_context(data);
+  ffi::GTRUE
+}
+
+unsafe extern "C" fn finalize(source: *mut ffi::GSource) {
+  let data = source as *mut DefaultContextSource;
+  release_default_context(&mut *data);
+  ptr::drop_in_place(ptr::addr_of_mut!((*data).fds));
+}
+
+unsafe fn check_default_context(data: &mut DefaultContextSource) -> ffi::gboolean {
+  if !data.owned {
+    return ffi::GFALSE;
+  }
+  data.checked = true;
+  ffi::g_main_context_check(
+    ffi::g_main_context_default(),
+    data.priority,
+    data.fds.as_mut_ptr(),
+    data.fds.len() as i32,
+  )
+}
+
+unsafe fn release_default_context(data: &mut DefaultContextSource) {
+  if data.owned {
+    data.owned = false;
+    ffi::g_main_context_release(ffi::g_main_context_default());
+  }
+}
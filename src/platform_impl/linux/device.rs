@@ -1,6 +1,10 @@
 use std::{
   os::raw::{c_int, c_uchar},
   ptr,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
 };
 
 use gtk::glib;
@@ -10,15 +14,23 @@ use crate::event::{DeviceEvent, ElementState, RawKeyEvent};
 
 use super::keycode_from_scancode;
 
+/// Input sent by the device event thread.
+pub enum DeviceInput {
+  Event(DeviceEvent),
+  /// Unaccelerated relative motion of a mouse, only sent while `raw_mouse_motion` is set.
+  RawMouseMotion((f64, f64)),
+}
+
 /// Spawn Device event thread. Only works on x11 since wayland doesn't have such global events.
-pub fn spawn(device_tx: glib::Sender<DeviceEvent>) {
+pub fn spawn(device_tx: glib::Sender<DeviceInput>, raw_mouse_motion: Arc<AtomicBool>) {
   std::thread::spawn(move || unsafe {
     let xlib = xlib::Xlib::open().unwrap();
     let xinput2 = xinput2::XInput2::open().unwrap();
     let display = (xlib.XOpenDisplay)(ptr::null());
     let root = (xlib.XDefaultRootWindow)(display);
     // TODO Add more device event mask
-    let mask = xinput2::XI_RawKeyPressMask | xinput2::XI_RawKeyReleaseMask;
+    let mask =
+      xinput2::XI_RawKeyPressMask | xinput2::XI_RawKeyReleaseMask | xinput2::XI_RawMotionMask;
     let mut event_mask = xinput2::XIEventMask {
       deviceid: xinput2::XIAllMasterDevices,
       mask: &mask as *const _ as *mut c_uchar,
@@ -64,11 +76,37 @@ pub fn spawn(device_tx: glib::Sender<DeviceEvent>) {
                   state,
                 };
 
-                if let Err(e) = device_tx.send(DeviceEvent::Key(event)) {
+                if let Err(e) = device_tx.send(DeviceInput::Event(DeviceEvent::Key(event))) {
                   log::info!("Failed to send device event {} since receiver is closed. Closing x11 thread along with it", e);
                   break;
                 }
               }
+              xinput2::XI_RawMotion if raw_mouse_motion.load(Ordering::Relaxed) => {
+                let xev: &xinput2::XIRawEvent = &*(xev.data as *const _);
+                // The values of the valuators set in the mask follow each other, the first two
+                // are the X and Y axes of mice.
+                let mask =
+                  std::slice::from_raw_parts(xev.valuators.mask, xev.valuators.mask_len as usize);
+                let mut value = xev.raw_values;
+                let mut delta = (0.0, 0.0);
+                for axis in 0..(mask.len() * 8) as i32 {
+                  if xinput2::XIMaskIsSet(mask, axis) {
+                    match axis {
+                      0 => delta.0 = *value,
+                      1 => delta.1 = *value,
+                      _ => {}
+                    }
+                    value = value.add(1);
+                  }
+                }
+
+                if delta != (0.0, 0.0) {
+                  if let Err(e) = device_tx.send(DeviceInput::RawMouseMotion(delta)) {
+                    log::info!("Failed to send device event {} since receiver is closed. Closing x11 thread along with it", e);
+                    break;
+                  }
+                }
+              }
               _ => {}
             }
           }
@@ -3,18 +3,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  cell::RefCell,
-  collections::{HashSet, VecDeque},
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet, VecDeque},
   error::Error,
+  ffi::c_void,
   process,
   rc::Rc,
-  sync::atomic::{AtomicBool, Ordering},
-  time::Instant,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::{Duration, Instant},
 };
 
 use cairo::{RectangleInt, Region};
 use crossbeam_channel::SendError;
-use gdk::{Cursor, CursorType, EventKey, EventMask, ScrollDirection, WindowEdge, WindowState};
+use gdk::{
+  Cursor, CursorType, DeviceToolType, EventKey, EventMask, EventPadButton, ScrollDirection,
+  WindowEdge, WindowState,
+};
 use gio::Cancellable;
 use glib::{source::Priority, MainContext};
 use gtk::{cairo, gdk, gio, glib, prelude::*};
@@ -23,18 +30,35 @@ use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
   error::ExternalError,
   event::{
-    ElementState, Event, MouseButton, MouseScrollDelta, StartCause, TouchPhase, WindowEvent,
+    DeviceEvent, ElementState, Event, Ime, MouseScrollDelta, PenInput, PointerKind, PowerEvent,
+    ScrollSource, StartCause, SwipeDirection, TabletTool, Touch, TouchPhase, TypedUserEvent,
+    WindowEvent,
+  },
+  event_loop::{
+    ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, Suspension, TimerId,
+  },
+  global_shortcut::{Accelerator, AcceleratorId, GlobalShortcutId},
+  keyboard::{
+    DeadKeyState, Key, KeyCode, KeyRepeatSettings, KeyboardLayout, ModifiersState, RepeatCounter,
   },
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-  keyboard::ModifiersState,
   monitor::MonitorHandle as RootMonitorHandle,
-  platform_impl::platform::{device, DEVICE_ID},
-  window::{CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, WindowId as RootWindowId},
+  platform::{pump_events::PumpStatus, run_return::ExitReason},
+  platform_impl::platform::{
+    device::{self, DeviceInput},
+    DEVICE_ID,
+  },
+  window::{
+    CursorGrabMode, CursorIcon, Fullscreen, ImePurpose, ProgressBarState, ResizeDirection,
+    SystemAppearance, WindowId as RootWindowId,
+  },
 };
 
 use super::{
+  default_context,
+  global_shortcut::{self, GlobalShortcuts},
   keyboard,
   monitor::{self, MonitorHandle},
+  surface_region::{Surface, SurfaceRegionRequest},
   taskbar, util,
   window::{WindowId, WindowRequest},
 };
@@ -42,17 +66,26 @@ use super::{
 use taskbar::TaskbarIndicator;
 
 #[derive(Clone)]
-pub struct EventLoopWindowTarget<T> {
+pub struct EventLoopWindowTarget<T: 'static> {
   /// Gdk display
   pub(crate) display: gdk::Display,
   /// Gtk application
   pub(crate) app: gtk::Application,
-  /// Window Ids of the application
-  pub(crate) windows: Rc<RefCell<HashSet<WindowId>>>,
+  /// Windows of the application by id, so that finding the target of a window request doesn't
+  /// walk the list of `gtk::Application`
+  pub(crate) windows: Rc<RefCell<HashMap<WindowId, gtk::ApplicationWindow>>>,
   /// Window requests sender
   pub(crate) window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
   /// Draw event sender
   pub(crate) draw_tx: crossbeam_channel::Sender<WindowId>,
+  /// Event sender of the timers and of the events emitted when creating a window
+  pub(crate) event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+  /// Main context the timers are attached to
+  context: MainContext,
+  /// Sources of the timers of `set_timer`, destroyed once they don't fire anymore
+  timers: Rc<RefCell<HashMap<TimerId, glib::Source>>>,
+  /// Keys grabbed for `GlobalShortcutManager::register`
+  global_shortcuts: Rc<RefCell<GlobalShortcuts>>,
   _marker: std::marker::PhantomData<T>,
 }
 
@@ -61,6 +94,80 @@ impl<T> EventLoopWindowTarget<T> {
   pub fn monitor_from_point(&self, x: f64, y: f64) -> Option<MonitorHandle> {
     monitor::from_point(&self.display, x, y)
   }
+
+  pub fn set_timer(&self, id: TimerId, interval: Duration, repeating: bool) {
+    let event_tx = self.event_tx.clone();
+    let source = self.context.with_thread_default(|| {
+      let source_id = glib::timeout_add_local(interval, move || {
+        if let Err(e) = event_tx.send(Event::Timer(id)) {
+          log::warn!("Failed to send timer event to event channel: {}", e);
+        }
+        if repeating {
+          glib::ControlFlow::Continue
+        } else {
+          glib::ControlFlow::Break
+        }
+      });
+      self.context.find_source_by_id(&source_id)
+    });
+    let mut timers = self.timers.borrow_mut();
+    timers.retain(|_, source| !source.is_destroyed());
+    if let Ok(Some(source)) = source {
+      timers.insert(id, source);
+    }
+  }
+
+  pub fn cancel_timer(&self, id: TimerId) {
+    if let Some(source) = self.timers.borrow_mut().remove(&id) {
+      source.destroy();
+    }
+  }
+
+  #[inline]
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    keyboard::keyboard_layout(&self.display)
+  }
+
+  #[inline]
+  pub fn key_for_code(&self, code: KeyCode) -> Key<'static> {
+    keyboard::key_for_code(&self.display, code)
+  }
+
+  #[inline]
+  pub fn code_for_key(&self, key: &Key<'_>) -> Option<KeyCode> {
+    keyboard::code_for_key(&self.display, key)
+  }
+
+  #[inline]
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    keyboard::key_repeat_settings(&self.display)
+  }
+
+  pub fn register_global_shortcut(
+    &self,
+    id: GlobalShortcutId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    self
+      .global_shortcuts
+      .borrow_mut()
+      .register(&self.display, id, accelerator)
+  }
+
+  pub fn unregister_global_shortcut(&self, id: GlobalShortcutId) {
+    self
+      .global_shortcuts
+      .borrow_mut()
+      .unregister(&self.display, id)
+  }
+
+  pub fn unregister_all_global_shortcuts(&self) {
+    self
+      .global_shortcuts
+      .borrow_mut()
+      .unregister_all(&self.display)
+  }
+
   #[inline]
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
     let mut handles = VecDeque::new();
@@ -143,6 +250,16 @@ impl<T> EventLoopWindowTarget<T> {
     util::cursor_position(self.is_wayland())
   }
 
+  #[inline]
+  pub fn system_appearance(&self) -> SystemAppearance {
+    util::system_appearance(&self.display)
+  }
+
+  #[inline]
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    util::mouse_button_count(&self.display)
+  }
+
   #[inline]
   pub fn set_progress_bar(&self, progress: ProgressBarState) {
     if let Err(e) = self
@@ -161,45 +278,147 @@ pub struct EventLoop<T: 'static> {
   pub(crate) user_event_tx: crossbeam_channel::Sender<Event<'static, T>>,
   /// Event queue of EventLoop
   events: crossbeam_channel::Receiver<Event<'static, T>>,
+  /// Typed user event sender for EventLoopProxy
+  typed_user_event_tx: crossbeam_channel::Sender<Event<'static, TypedUserEvent>>,
+  /// Typed user event queue of EventLoop
+  typed_events: crossbeam_channel::Receiver<Event<'static, TypedUserEvent>>,
   /// Draw queue of EventLoop
   draws: crossbeam_channel::Receiver<WindowId>,
   /// Boolean to control device event thread
   run_device_thread: Option<Rc<AtomicBool>>,
+  /// Main context the event loop runs on
+  context: MainContext,
+  /// State of the event loop between two `pump_events`
+  pump_state: Option<LoopState<T>>,
+  /// How long the windows left are given to be destroyed when the event loop exits
+  shutdown_timeout: Duration,
+  /// System bus connection holding the logind and UPower subscriptions
+  _system_bus: Option<gio::DBusConnection>,
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// State of the event loop logic, see `EventLoop::run_return`.
+struct LoopState<T: 'static> {
+  event_state: EventState,
+  control_flow: ControlFlow,
+  /// Whether the display server connection was lost.
+  quit: bool,
+  /// When the last iteration ended, i.e. when the event loop started waiting.
+  wait_start: Instant,
+  /// Event received to find out why the event loop woke up, dispatched first by `EventQueue`.
+  pending: Option<Event<'static, T>>,
+  /// Whether the windows left are destroyed on exit, only by `run` which exits the process.
+  destroy_windows: bool,
+}
+
+impl<T> Default for LoopState<T> {
+  fn default() -> Self {
+    Self {
+      event_state: EventState::NewStart,
+      control_flow: ControlFlow::default(),
+      quit: false,
+      wait_start: Instant::now(),
+      pending: None,
+      destroy_windows: false,
+    }
+  }
+}
+
+enum EventState {
+  NewStart,
+  EventQueue,
+  DrawQueue,
+}
+
+fn state_exit_reason(code: i32, quit: bool) -> ExitReason {
+  if quit {
+    ExitReason::Quit(code)
+  } else {
+    ExitReason::Requested(code)
+  }
+}
+
+/// Blocks on `context` until an event arrives, the resume time of `control_flow` is reached, or
+/// `deadline` elapsed.
+pub(crate) fn wait(context: &MainContext, control_flow: ControlFlow, deadline: Option<Instant>) {
+  let resume = match control_flow {
+    ControlFlow::WaitUntil(requested_resume) => {
+      Some(deadline.map_or(requested_resume, |deadline| deadline.min(requested_resume)))
+    }
+    _ => deadline,
+  };
+  // GLib sleeps for whole milliseconds, round up so that the wait doesn't end before `resume`.
+  let timeout_source = resume.map(|resume| {
+    let timeout = resume.saturating_duration_since(Instant::now()) + Duration::from_nanos(999_999);
+    let source = glib::timeout_source_new(timeout, None, Priority::default(), || {
+      glib::ControlFlow::Break
+    });
+    source.attach(Some(context));
+    source
+  });
+  context.iteration(true);
+  if let Some(source) = timeout_source {
+    source.destroy();
+  }
+}
+
+#[derive(Default)]
 pub(crate) struct PlatformSpecificEventLoopAttributes {
   pub(crate) any_thread: bool,
+  pub(crate) main_context: Option<MainContext>,
+  pub(crate) msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
 }
 
 impl<T: 'static> EventLoop<T> {
-  pub(crate) fn new(attrs: &PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
+  pub(crate) fn new(attrs: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
     if !attrs.any_thread {
       assert_is_main_thread("new_any_thread");
     }
 
-    let context = MainContext::default();
+    let context = attrs
+      .main_context
+      .clone()
+      .unwrap_or_else(MainContext::default);
+    let msg_hook = attrs.msg_hook.take();
     context
-      .with_thread_default(|| EventLoop::new_gtk().expect("Failed to initialize gtk backend!"))
+      .with_thread_default(|| {
+        EventLoop::new_gtk(context.clone(), msg_hook).expect("Failed to initialize gtk backend!")
+      })
       .expect("Failed to initialize gtk backend!")
   }
 
-  fn new_gtk() -> Result<EventLoop<T>, Box<dyn Error>> {
-    let context = MainContext::default();
+  fn new_gtk(
+    context: MainContext,
+    msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+  ) -> Result<EventLoop<T>, Box<dyn Error>> {
+    // GTK reads windowing events from the global default context. When running on another one,
+    // dispatch it whenever it has work.
+    if context != MainContext::default() {
+      default_context::forward_default_context(&context);
+    }
+
     let app = gtk::Application::new(None, gio::ApplicationFlags::empty());
-    let app_ = app.clone();
     let cancellable: Option<&Cancellable> = None;
     app.register(cancellable)?;
 
-    // Send StartCause::Init event
+    if let Some(msg_hook) = msg_hook {
+      let msg_hook = RefCell::new(msg_hook);
+      gdk::Event::set_handler(Some(move |event: &mut gdk::Event| {
+        // A nested event from within the hook isn't filtered again.
+        let consumed = match msg_hook.try_borrow_mut() {
+          Ok(mut hook) => hook(event.as_ptr() as *const c_void),
+          Err(_) => false,
+        };
+        if !consumed {
+          gtk::main_do_event(event);
+        }
+      }));
+    }
+
     let (event_tx, event_rx) = crossbeam_channel::unbounded();
+    let (typed_user_event_tx, typed_event_rx) = crossbeam_channel::unbounded();
     let (draw_tx, draw_rx) = crossbeam_channel::unbounded();
-    let event_tx_ = event_tx.clone();
-    app.connect_activate(move |_| {
-      if let Err(e) = event_tx_.send(Event::NewEvents(StartCause::Init)) {
-        log::warn!("Failed to send init event to event channel: {}", e);
-      }
-    });
+    // `StartCause::Init` is sent by `EventLoop::start`, ahead of the events already queued.
+    app.connect_activate(|_| {});
     let draw_tx_ = draw_tx.clone();
     let user_event_tx = event_tx.clone();
 
@@ -207,28 +426,66 @@ impl<T: 'static> EventLoop<T> {
     let (window_requests_tx, window_requests_rx) = glib::MainContext::channel(Priority::default());
     let display = gdk::Display::default()
       .expect("GdkDisplay not found. This usually means `gkt_init` hasn't called yet.");
+    watch_monitors(&display, user_event_tx.clone());
+    watch_icc_profiles(&display, user_event_tx.clone());
+    watch_appearance(&display, user_event_tx.clone());
     let window_target = EventLoopWindowTarget {
       display,
       app,
-      windows: Rc::new(RefCell::new(HashSet::new())),
+      windows: Rc::new(RefCell::new(HashMap::new())),
       window_requests_tx,
       draw_tx: draw_tx_,
+      event_tx: event_tx.clone(),
+      context: context.clone(),
+      timers: Rc::new(RefCell::new(HashMap::new())),
+      global_shortcuts: Default::default(),
       _marker: std::marker::PhantomData,
     };
 
+    // Windows with `Window::set_raw_mouse_motion`, and whether there are any so the device thread
+    // reports the raw motion.
+    let raw_mouse_motion: Rc<RefCell<HashSet<WindowId>>> = Rc::default();
+    let raw_mouse_motion_enabled = Arc::new(AtomicBool::new(false));
+
     // Spawn x11 thread to receive Device events.
     let run_device_thread = if window_target.is_x11() {
+      global_shortcut::install_filter(
+        window_target.global_shortcuts.clone(),
+        user_event_tx.clone(),
+      );
       let (device_tx, device_rx) = glib::MainContext::channel(glib::Priority::default());
       let user_event_tx = user_event_tx.clone();
       let run_device_thread = Rc::new(AtomicBool::new(true));
       let run = run_device_thread.clone();
-      device::spawn(device_tx);
-      device_rx.attach(Some(&context), move |event| {
-        if let Err(e) = user_event_tx.send(Event::DeviceEvent {
-          device_id: DEVICE_ID,
-          event,
-        }) {
-          log::warn!("Fail to send device event to event channel: {}", e);
+      let windows = window_target.windows.clone();
+      let raw_mouse_motion = raw_mouse_motion.clone();
+      device::spawn(device_tx, raw_mouse_motion_enabled.clone());
+      device_rx.attach(Some(&context), move |input| {
+        let event = match input {
+          DeviceInput::Event(event) => Some(Event::DeviceEvent {
+            device_id: DEVICE_ID,
+            event,
+          }),
+          DeviceInput::RawMouseMotion(delta) => {
+            let windows = windows.borrow();
+            let focused = raw_mouse_motion
+              .borrow()
+              .iter()
+              .copied()
+              .find(|id| windows.get(id).map_or(false, |window| window.is_active()));
+            focused.map(|window_id| Event::WindowEvent {
+              window_id: RootWindowId(window_id),
+              event: WindowEvent::RawMouseMotion {
+                device_id: DEVICE_ID,
+                delta,
+              },
+            })
+          }
+        };
+        if let Some(event) = event {
+          if let Err(e) = user_event_tx.send(event) {
+            log::warn!("Fail to send device event to event channel: {}", e);
+          }
         }
         if run.load(Ordering::Relaxed) {
           glib::ControlFlow::Continue
@@ -242,13 +499,40 @@ impl<T: 'static> EventLoop<T> {
     };
 
     let mut taskbar: Option<TaskbarIndicator> = None;
+    // Frame clocks of the windows with jank detection, and their `after-paint` handler.
+    let mut jank_detectors: HashMap<WindowId, (gdk::FrameClock, glib::SignalHandlerId)> =
+      HashMap::new();
+    // Input method of each window and whether it's allowed, see `Window::set_ime_cursor_area` and
+    // `Window::set_ime_allowed`.
+    let mut imes: HashMap<WindowId, (gtk::IMContextSimple, Rc<Cell<bool>>)> = HashMap::new();
+    // Cursor grab of each window and the root position it's locked at, see
+    // `Window::set_cursor_grab`.
+    let mut cursor_grabs: HashMap<WindowId, Rc<Cell<(CursorGrabMode, Option<(i32, i32)>)>>> =
+      HashMap::new();
+    // Accel group of each window with the closures of its accelerators, see
+    // `Window::add_accelerator`.
+    let mut accel_groups: HashMap<
+      WindowId,
+      (gtk::AccelGroup, HashMap<AcceleratorId, glib::Closure>),
+    > = HashMap::new();
+    // Surfaces of the regions of each window, see `Window::create_surface_region`.
+    let mut surface_regions: HashMap<WindowId, HashMap<u64, Surface>> = HashMap::new();
     let supports_unity = util::is_unity();
+    // XKB layout group of the last key event of any window, see `Event::InputLocaleChanged`.
+    let input_group: Rc<Cell<Option<u8>>> = Rc::default();
 
     // Window Request
+    let windows = window_target.windows.clone();
     window_requests_rx.attach(Some(&context), move |(id, request)| {
-      if let Some(window) = app_.window_by_id(id.0) {
+      let window = windows.borrow().get(&id).cloned();
+      if let Some(window) = window {
         match request {
-          WindowRequest::Title(title) => window.set_title(&title),
+          WindowRequest::Title(pending_title) => {
+            let title = pending_title.lock().take();
+            if let Some(title) = title {
+              window.set_title(&title);
+            }
+          }
           WindowRequest::Position((x, y)) => window.move_(x, y),
           WindowRequest::Size((w, h)) => window.resize(w, h),
           WindowRequest::SizeConstraints(constraints) => {
@@ -264,8 +548,77 @@ impl<T: 'static> EventLoop<T> {
           WindowRequest::Focus => {
             window.present_with_time(gdk::ffi::GDK_CURRENT_TIME as _);
           }
+          WindowRequest::Raise => {
+            if let Some(gdk_window) = window.window() {
+              gdk_window.raise();
+            }
+          }
+          WindowRequest::FocusWithoutRaise => {
+            if let Some(gdk_window) = window.window() {
+              gdk_window.focus(gdk::ffi::GDK_CURRENT_TIME as _);
+            }
+          }
+          WindowRequest::AcceptFocus(accept) => {
+            window.set_accept_focus(accept);
+            window.set_focus_on_map(accept);
+          }
           WindowRequest::Resizable(resizable) => window.set_resizable(resizable),
           WindowRequest::Closable(closable) => window.set_deletable(closable),
+          WindowRequest::Close => window.close(),
+          WindowRequest::Destroy => {
+            jank_detectors.remove(&id);
+            imes.remove(&id);
+            cursor_grabs.remove(&id);
+            accel_groups.remove(&id);
+            surface_regions.remove(&id);
+            let mut raw_mouse_motion = raw_mouse_motion.borrow_mut();
+            raw_mouse_motion.remove(&id);
+            raw_mouse_motion_enabled.store(!raw_mouse_motion.is_empty(), Ordering::Relaxed);
+            unsafe { window.destroy() }
+          }
+          WindowRequest::Adopt(handle) => {
+            window.realize();
+            let parent: Option<gdk::Window> = unsafe {
+              glib::translate::from_glib_full(gdk_x11_sys::gdk_x11_window_foreign_new_for_display(
+                window.display().as_ptr() as *mut _,
+                handle as _,
+              ))
+            };
+            match (window.window(), parent) {
+              (Some(gdk_window), Some(parent)) => gdk_window.reparent(&parent, 0, 0),
+              // The handle isn't a window.
+              _ => log::warn!("Failed to adopt window {:#x}", handle),
+            }
+          }
+          WindowRequest::SurfaceRegion(region, request) => match request {
+            SurfaceRegionRequest::Create(state, default_vbox) => {
+              match Surface::new(&window, state, default_vbox) {
+                Some(surface) => {
+                  surface_regions
+                    .entry(id)
+                    .or_default()
+                    .insert(region, surface);
+                }
+                None => log::warn!("Failed to create the surface of a surface region"),
+              }
+            }
+            SurfaceRegionRequest::Rect => {
+              if let Some(surface) = surface_regions.get(&id).and_then(|s| s.get(&region)) {
+                surface.update_rect();
+              }
+            }
+            SurfaceRegionRequest::Visible(visible) => {
+              if let Some(surface) = surface_regions.get(&id).and_then(|s| s.get(&region)) {
+                surface.set_visible(visible);
+              }
+            }
+            SurfaceRegionRequest::Destroy => {
+              if let Some(surfaces) = surface_regions.get_mut(&id) {
+                surfaces.remove(&region);
+              }
+            }
+          },
+          WindowRequest::Enabled(enabled) => window.set_sensitive(enabled),
           WindowRequest::Minimized(minimized) => {
             if minimized {
               window.iconify();
@@ -306,9 +659,20 @@ impl<T: 'static> EventLoop<T> {
               );
             }
           }
-          WindowRequest::Fullscreen(fullscreen) => match fullscreen {
-            Some(f) => {
-              if let Fullscreen::Borderless(m) = f {
+          WindowRequest::Fullscreen(fullscreen) => {
+            // The fullscreen mode is a property of the GDK window, so make sure it exists.
+            window.realize();
+            if let Some(gdk_window) = window.window() {
+              gdk_window.set_fullscreen_mode(
+                if matches!(fullscreen, Some(Fullscreen::Spanning(_))) {
+                  gdk::FullscreenMode::AllMonitors
+                } else {
+                  gdk::FullscreenMode::CurrentMonitor
+                },
+              );
+            }
+            match fullscreen {
+              Some(Fullscreen::Borderless(m)) => {
                 if let Some(monitor) = m {
                   let display = window.display();
                   let monitor = monitor.inner;
@@ -324,19 +688,20 @@ impl<T: 'static> EventLoop<T> {
                   window.fullscreen();
                 }
               }
+              Some(Fullscreen::Spanning(_)) => window.fullscreen(),
+              Some(_) => {}
+              None => window.unfullscreen(),
             }
-            None => window.unfullscreen(),
-          },
+          }
           WindowRequest::Decorations(decorations) => window.set_decorated(decorations),
           WindowRequest::AlwaysOnBottom(always_on_bottom) => {
             window.set_keep_below(always_on_bottom)
           }
           WindowRequest::AlwaysOnTop(always_on_top) => window.set_keep_above(always_on_top),
-          WindowRequest::WindowIcon(window_icon) => {
-            if let Some(icon) = window_icon {
-              window.set_icon(Some(&icon.inner.into()));
-            }
-          }
+          WindowRequest::WindowIcon(window_icon) => match window_icon {
+            Some(icon) => window.set_icon_list(&icon.inner.into_pixbufs()),
+            None => window.set_icon(None),
+          },
           WindowRequest::UserAttention(request_type) => {
             window.set_urgency_hint(request_type.is_some())
           }
@@ -363,6 +728,59 @@ impl<T: 'static> EventLoop<T> {
               }
             };
           }
+          WindowRequest::CustomCursor(cursor) => {
+            if let Some(gdk_window) = window.window() {
+              gdk_window.set_cursor(util::create_custom_cursor(&window, &cursor).as_ref());
+            }
+          }
+          WindowRequest::QueueDraw => window.queue_draw(),
+          WindowRequest::JankDetection(enabled) => {
+            if let Some((frame_clock, handler_id)) = jank_detectors.remove(&id) {
+              frame_clock.disconnect(handler_id);
+            }
+            // The frame clock belongs to the GDK window, so make sure it exists.
+            if enabled {
+              window.realize();
+            }
+            if let (true, Some(frame_clock)) = (enabled, window.frame_clock()) {
+              let tx_clone = event_tx.clone();
+              let checked = Cell::new(frame_clock.frame_counter());
+              let handler_id = frame_clock.connect_after_paint(move |frame_clock| {
+                // A frame is only presented after a later one started, check the ones since.
+                for frame in checked.get() + 1..frame_clock.frame_counter() {
+                  let timings = match frame_clock.timings(frame) {
+                    Some(timings) if !timings.is_complete() => break,
+                    Some(timings) => timings,
+                    None => {
+                      checked.set(frame);
+                      continue;
+                    }
+                  };
+                  checked.set(frame);
+                  if let (Some(presented), Some(interval)) =
+                    (timings.presentation_time(), timings.refresh_interval())
+                  {
+                    // The frame is expected on the refresh after the one it started on.
+                    let late = presented
+                      .get()
+                      .saturating_sub(timings.frame_time() as u64)
+                      .saturating_sub(interval.get());
+                    let count = late / interval.get();
+                    if count > 0 {
+                      if let Err(e) = tx_clone.send(Event::FrameMissed {
+                        window_id: RootWindowId(id),
+                        count: count.min(u32::MAX as u64) as u32,
+                        duration: Duration::from_micros(late),
+                      }) {
+                        log::warn!("Failed to send frame missed event to event channel: {}", e);
+                      }
+                    }
+                  }
+                }
+              });
+              jank_detectors.insert(id, (frame_clock, handler_id));
+            }
+          }
           WindowRequest::CursorPosition((x, y)) => {
             if let Some(cursor) = window
               .display()
@@ -385,10 +803,120 @@ impl<T: 'static> EventLoop<T> {
               window.input_shape_combine_region(None)
             };
           }
+          WindowRequest::AddAccelerator(accelerator_id, keyval, mods) => {
+            let (accel_group, closures) = accel_groups.entry(id).or_insert_with(|| {
+              let accel_group = gtk::AccelGroup::new();
+              window.add_accel_group(&accel_group);
+              (accel_group, HashMap::new())
+            });
+            let tx_clone = event_tx.clone();
+            let closure = accel_group.connect_accel_group(
+              keyval,
+              mods,
+              gtk::AccelFlags::VISIBLE,
+              move |_, _, _, _| {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Accelerator(accelerator_id),
+                }) {
+                  log::warn!("Failed to send accelerator event to event channel: {}", e);
+                }
+                true
+              },
+            );
+            closures.insert(accelerator_id, closure);
+          }
+          WindowRequest::RemoveAccelerator(accelerator_id) => {
+            if let Some((accel_group, closures)) = accel_groups.get_mut(&id) {
+              if let Some(closure) = closures.remove(&accelerator_id) {
+                AccelGroupExt::disconnect(&*accel_group, Some(&closure));
+              }
+            }
+          }
+          WindowRequest::ImeCursorArea((x, y, width, height)) => {
+            if let Some((ime, _)) = imes.get(&id) {
+              ime.set_cursor_location(&gdk::Rectangle::new(x, y, width, height));
+            }
+          }
+          WindowRequest::ImeAllowed(allowed) => {
+            if let Some((ime, ime_allowed)) = imes.get(&id) {
+              if ime_allowed.replace(allowed) != allowed {
+                if allowed {
+                  ime.focus_in();
+                } else {
+                  // Cancels the current composition.
+                  ime.reset();
+                  ime.focus_out();
+                }
+              }
+            }
+          }
+          WindowRequest::ImePurpose(purpose) => {
+            if let Some((ime, _)) = imes.get(&id) {
+              let (purpose, hints) = match purpose {
+                ImePurpose::Password => {
+                  (gtk::InputPurpose::Password, gtk::InputHints::NO_SPELLCHECK)
+                }
+                ImePurpose::Email => (gtk::InputPurpose::Email, gtk::InputHints::NO_SPELLCHECK),
+                ImePurpose::Number => (gtk::InputPurpose::Number, gtk::InputHints::NONE),
+                ImePurpose::Url => (gtk::InputPurpose::Url, gtk::InputHints::NO_SPELLCHECK),
+                _ => (gtk::InputPurpose::FreeForm, gtk::InputHints::NONE),
+              };
+              ime.set_input_purpose(purpose);
+              ime.set_input_hints(hints);
+            }
+          }
+          WindowRequest::CursorGrab(mode) => {
+            if let Some(cursor_grab) = cursor_grabs.get(&id) {
+              // The grab is taken when the window is focused otherwise.
+              let lock = match window.is_active() {
+                true => grab_cursor(&window, mode),
+                false => None,
+              };
+              cursor_grab.set((mode, lock));
+            }
+          }
+          WindowRequest::RawMouseMotion(enabled) => {
+            let mut raw_mouse_motion = raw_mouse_motion.borrow_mut();
+            if enabled {
+              raw_mouse_motion.insert(id);
+            } else {
+              raw_mouse_motion.remove(&id);
+            }
+            raw_mouse_motion_enabled.store(!raw_mouse_motion.is_empty(), Ordering::Relaxed);
+          }
+          WindowRequest::KeyboardGrab(grab) => {
+            if let (Some(seat), Some(gdk_window)) =
+              (window.display().default_seat(), window.window())
+            {
+              if grab {
+                // On Wayland, GDK also inhibits the compositor shortcuts for keyboard grabs.
+                let status = seat.grab(
+                  &gdk_window,
+                  gdk::SeatCapabilities::KEYBOARD,
+                  false,
+                  None,
+                  None,
+                  None,
+                );
+                if status != gdk::GrabStatus::Success {
+                  log::warn!("Fail to grab the keyboard: {:?}", status);
+                }
+              } else if let Some(keyboard) = seat.keyboard() {
+                // `seat.ungrab()` would release a pointer grab of the cursor too.
+                unsafe {
+                  gdk::ffi::gdk_device_ungrab(keyboard.as_ptr(), gdk::ffi::GDK_CURRENT_TIME as _)
+                };
+              }
+            }
+          }
           WindowRequest::ProgressBarState(_) => unreachable!(),
           WindowRequest::WireUpEvents {
             transparent,
             cursor_moved,
+            intercept_minimize,
+            allow_minimize,
+            fullscreen,
           } => {
             window.add_events(
               EventMask::POINTER_MOTION_MASK
@@ -397,7 +925,11 @@ impl<T: 'static> EventLoop<T> {
                 | EventMask::TOUCH_MASK
                 | EventMask::STRUCTURE_MASK
                 | EventMask::FOCUS_CHANGE_MASK
-                | EventMask::SCROLL_MASK,
+                | EventMask::SCROLL_MASK
+                | EventMask::TOUCHPAD_GESTURE_MASK
+                | EventMask::PROXIMITY_IN_MASK
+                | EventMask::TABLET_PAD_MASK
+                | EventMask::VISIBILITY_NOTIFY_MASK,
             );
 
             // Allow resizing unmaximized borderless window
@@ -503,6 +1035,57 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Stop
             });
 
+            let tx_clone = event_tx.clone();
+            window.connect_window_state_event(move |window, event| {
+              // GTK has no way to veto a minimize request, so undo it instead.
+              if event.changed_mask().contains(WindowState::ICONIFIED)
+                && event.new_window_state().contains(WindowState::ICONIFIED)
+              {
+                let allowed = allow_minimize.swap(false, Ordering::AcqRel);
+                if intercept_minimize.load(Ordering::Acquire) && !allowed {
+                  window.deiconify();
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::MinimizeRequested,
+                  }) {
+                    log::warn!(
+                      "Failed to send window minimize requested event to event channel: {}",
+                      e
+                    );
+                  }
+                }
+              }
+
+              // Only report changes that weren't requested through `set_fullscreen`.
+              if event.changed_mask().contains(WindowState::FULLSCREEN) {
+                let is_fullscreen = event.new_window_state().contains(WindowState::FULLSCREEN);
+                let mut fullscreen = fullscreen.lock();
+                if fullscreen.is_some() != is_fullscreen {
+                  *fullscreen = if is_fullscreen {
+                    let monitor = window
+                      .window()
+                      .and_then(|gdk_window| window.display().monitor_at_window(&gdk_window))
+                      .map(|monitor| RootMonitorHandle {
+                        inner: MonitorHandle { monitor },
+                      });
+                    Some(Fullscreen::Borderless(monitor))
+                  } else {
+                    None
+                  };
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::FullscreenStateChanged(fullscreen.clone()),
+                  }) {
+                    log::warn!(
+                      "Failed to send fullscreen state changed event to event channel: {}",
+                      e
+                    );
+                  }
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
             let tx_clone = event_tx.clone();
             window.connect_configure_event(move |window, event| {
               let scale_factor = window.scale_factor();
@@ -532,8 +1115,16 @@ impl<T: 'static> EventLoop<T> {
               false
             });
 
+            let cursor_grab = Rc::new(Cell::new((CursorGrabMode::None, None)));
+            cursor_grabs.insert(id, cursor_grab.clone());
+
             let tx_clone = event_tx.clone();
-            window.connect_focus_in_event(move |_, _| {
+            let cursor_grab_ = cursor_grab.clone();
+            window.connect_focus_in_event(move |window, _| {
+              let (mode, _) = cursor_grab_.get();
+              if mode != CursorGrabMode::None {
+                cursor_grab_.set((mode, grab_cursor(window, mode)));
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Focused(true),
@@ -547,7 +1138,12 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_focus_out_event(move |_, _| {
+            let cursor_grab_ = cursor_grab.clone();
+            window.connect_focus_out_event(move |window, _| {
+              let (mode, _) = cursor_grab_.get();
+              if mode != CursorGrabMode::None {
+                cursor_grab_.set((mode, grab_cursor(window, CursorGrabMode::None)));
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Focused(false),
@@ -561,7 +1157,9 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let windows_ = windows.clone();
             window.connect_destroy(move |_| {
+              windows_.borrow_mut().remove(&id);
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Destroyed,
@@ -591,6 +1189,12 @@ impl<T: 'static> EventLoop<T> {
 
             let tx_clone = event_tx.clone();
             window.connect_motion_notify_event(move |window, motion| {
+              if let (_, Some((x, y))) = cursor_grab.get() {
+                let display = window.display();
+                if let Some(pointer) = display.default_seat().and_then(|seat| seat.pointer()) {
+                  pointer.warp(&display.default_screen(), x, y);
+                }
+              }
               if cursor_moved {
                 if let Some(cursor) = motion.device() {
                   let scale_factor = window.scale_factor();
@@ -625,17 +1229,23 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let last_click: Rc<Cell<Option<util::Click>>> = Rc::default();
+            let last_click_clone = last_click.clone();
             window.connect_button_press_event(move |_, event| {
-              let button = event.button();
+              // GDK follows the second and third presses of a multiple click with an extra
+              // `DoubleButtonPress` and `TripleButtonPress`.
+              if event.event_type() != gdk::EventType::ButtonPress {
+                return glib::Propagation::Proceed;
+              }
+              let button = match util::mouse_button(event.button()) {
+                Some(button) => button,
+                None => return glib::Propagation::Proceed,
+              };
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
-                  button: match button {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => MouseButton::Other(button as u16),
-                  },
+                  button,
+                  click_count: util::click_count(&last_click_clone, event),
                   state: ElementState::Pressed,
                   device_id: DEVICE_ID,
                   // this field is depracted so it is fine to pass empty state
@@ -652,16 +1262,15 @@ impl<T: 'static> EventLoop<T> {
 
             let tx_clone = event_tx.clone();
             window.connect_button_release_event(move |_, event| {
-              let button = event.button();
+              let button = match util::mouse_button(event.button()) {
+                Some(button) => button,
+                None => return glib::Propagation::Proceed,
+              };
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
-                  button: match button {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => MouseButton::Other(button as u16),
-                  },
+                  button,
+                  click_count: util::click_count(&last_click, event),
                   state: ElementState::Released,
                   device_id: DEVICE_ID,
                   // this field is depracted so it is fine to pass empty state
@@ -676,6 +1285,71 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Proceed
             });
 
+            let tx_clone = event_tx.clone();
+            window.connect_touch_event(move |window, event| {
+              let phase = match event.event_type() {
+                gdk::EventType::TouchBegin => TouchPhase::Started,
+                gdk::EventType::TouchUpdate => TouchPhase::Moved,
+                gdk::EventType::TouchEnd => TouchPhase::Ended,
+                gdk::EventType::TouchCancel => TouchPhase::Cancelled,
+                _ => return glib::Propagation::Proceed,
+              };
+              if let (Some(sequence), Some((x, y))) = (event.event_sequence(), event.coords()) {
+                let pointer_kind = match event.source_device().map(|device| device.source()) {
+                  Some(gdk::InputSource::Touchscreen) => PointerKind::Touch,
+                  Some(gdk::InputSource::Pen) | Some(gdk::InputSource::Eraser) => PointerKind::Pen,
+                  Some(gdk::InputSource::Mouse) => PointerKind::Mouse,
+                  _ => PointerKind::Unknown,
+                };
+                // The sequence stays the same from the beginning of a touch to its end.
+                let touch_id =
+                  glib::translate::ToGlibPtr::<*mut gdk::ffi::GdkEventSequence>::to_glib_none(
+                    &sequence,
+                  )
+                  .0 as u64;
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Touch(Touch {
+                    device_id: DEVICE_ID,
+                    phase,
+                    location: LogicalPosition::new(x, y).to_physical(window.scale_factor() as f64),
+                    force: None,
+                    id: touch_id,
+                    pointer_kind,
+                  }),
+                }) {
+                  log::warn!("Failed to send touch event to event channel: {}", e);
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
+            let tx_clone = event_tx.clone();
+            let send_pen_input = move |scale_factor: i32, event: &gdk::Event| {
+              if let Some(pen) = pen_input(event, scale_factor as f64) {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::PenInput(pen),
+                }) {
+                  log::warn!("Failed to send pen input event to event channel: {}", e);
+                }
+              }
+            };
+            let send_clone = send_pen_input.clone();
+            window.connect_motion_notify_event(move |window, event| {
+              send_clone(window.scale_factor(), event);
+              glib::Propagation::Proceed
+            });
+            let send_clone = send_pen_input.clone();
+            window.connect_button_press_event(move |window, event| {
+              send_clone(window.scale_factor(), event);
+              glib::Propagation::Proceed
+            });
+            window.connect_button_release_event(move |window, event| {
+              send_pen_input(window.scale_factor(), event);
+              glib::Propagation::Proceed
+            });
+
             let tx_clone = event_tx.clone();
             window.connect_scroll_event(move |_, event| {
               let (x, y) = event.delta();
@@ -685,9 +1359,18 @@ impl<T: 'static> EventLoop<T> {
                   device_id: DEVICE_ID,
                   delta: MouseScrollDelta::LineDelta(-x as f32, -y as f32),
                   phase: match event.direction() {
-                    ScrollDirection::Smooth => TouchPhase::Moved,
+                    // Touchpads send an empty scroll once the fingers are lifted.
+                    ScrollDirection::Smooth if !event.is_stop() => TouchPhase::Moved,
                     _ => TouchPhase::Ended,
                   },
+                  momentum: false,
+                  source: match event.source_device().map(|device| device.source()) {
+                    Some(gdk::InputSource::Touchpad) | Some(gdk::InputSource::Trackpoint) => {
+                      ScrollSource::Touchpad
+                    }
+                    Some(gdk::InputSource::Mouse) => ScrollSource::Wheel,
+                    _ => ScrollSource::Unknown,
+                  },
                   modifiers: ModifiersState::empty(),
                 },
               }) {
@@ -697,6 +1380,148 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            // GDK reports the cumulative scale of a pinch and the total motion of a swipe.
+            let last_scale = Cell::new(1.0);
+            let swipe_delta = Cell::new((0.0, 0.0));
+            window.connect_event(move |_, event| {
+              let mut events = Vec::new();
+              if let Some(pinch) = event.downcast_ref::<gdk::EventTouchpadPinch>() {
+                let raw: &gdk::ffi::GdkEventTouchpadPinch = pinch.as_ref();
+                let phase = touchpad_phase(raw.phase);
+                let scale = pinch.scale();
+                if phase == TouchPhase::Started {
+                  last_scale.set(1.0);
+                }
+                events.push(WindowEvent::TouchpadMagnify {
+                  device_id: DEVICE_ID,
+                  delta: scale / last_scale.replace(scale) - 1.0,
+                  phase,
+                });
+                events.push(WindowEvent::TouchpadRotate {
+                  device_id: DEVICE_ID,
+                  // GDK measures the angle clockwise, in radians.
+                  delta: -pinch.angle_delta().to_degrees() as f32,
+                  phase,
+                });
+              } else if let Some(swipe) = event.downcast_ref::<gdk::EventTouchpadSwipe>() {
+                let raw: &gdk::ffi::GdkEventTouchpadSwipe = swipe.as_ref();
+                let (dx, dy) = swipe.delta();
+                match touchpad_phase(raw.phase) {
+                  TouchPhase::Started => swipe_delta.set((0.0, 0.0)),
+                  TouchPhase::Moved => {
+                    let (x, y) = swipe_delta.get();
+                    swipe_delta.set((x + dx, y + dy));
+                  }
+                  TouchPhase::Ended => {
+                    let (x, y) = swipe_delta.take();
+                    let direction = if x.abs() >= y.abs() {
+                      if x < 0.0 {
+                        SwipeDirection::Left
+                      } else {
+                        SwipeDirection::Right
+                      }
+                    } else if y < 0.0 {
+                      SwipeDirection::Up
+                    } else {
+                      SwipeDirection::Down
+                    };
+                    if x != 0.0 || y != 0.0 {
+                      events.push(WindowEvent::TouchpadSwipe {
+                        device_id: DEVICE_ID,
+                        direction,
+                      });
+                    }
+                  }
+                  _ => swipe_delta.set((0.0, 0.0)),
+                }
+              }
+              for event in events {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event,
+                }) {
+                  log::warn!(
+                    "Failed to send touchpad gesture event to event channel: {}",
+                    e
+                  );
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
+            if let Some(settings) = gtk::Settings::default() {
+              let tx_clone = event_tx.clone();
+              let send_theme_changed = move |_: &gtk::Settings| {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::CursorThemeChanged,
+                }) {
+                  log::warn!(
+                    "Failed to send cursor theme changed event to event channel: {}",
+                    e
+                  );
+                }
+              };
+              let handlers = RefCell::new(vec![
+                settings.connect_gtk_cursor_theme_name_notify(send_theme_changed.clone()),
+                settings.connect_gtk_cursor_theme_size_notify(send_theme_changed),
+              ]);
+              window.connect_destroy(move |_| {
+                for handler in handlers.take() {
+                  settings.disconnect(handler);
+                }
+              });
+            }
+
+            let tx_clone = event_tx.clone();
+            window.connect_proximity_in_event(move |_, event| {
+              if let Some(tool) = event.device_tool() {
+                let tool = match tool.tool_type() {
+                  DeviceToolType::Pen => TabletTool::Pen,
+                  DeviceToolType::Eraser => TabletTool::Eraser,
+                  DeviceToolType::Brush => TabletTool::Brush,
+                  DeviceToolType::Pencil => TabletTool::Pencil,
+                  DeviceToolType::Airbrush => TabletTool::Airbrush,
+                  DeviceToolType::Mouse => TabletTool::Mouse,
+                  DeviceToolType::Lens => TabletTool::Lens,
+                  _ => TabletTool::Unknown,
+                };
+                if let Err(e) = tx_clone.send(Event::DeviceEvent {
+                  device_id: DEVICE_ID,
+                  event: DeviceEvent::TabletToolChanged { tool },
+                }) {
+                  log::warn!("Failed to send tablet tool event to event channel: {}", e);
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
+            let tx_clone = event_tx.clone();
+            window.connect_event(move |_, event| {
+              if let Some(pad_button) = event.downcast_ref::<EventPadButton>() {
+                let state = match event.event_type() {
+                  gdk::EventType::PadButtonPress => ElementState::Pressed,
+                  _ => ElementState::Released,
+                };
+                if let Err(e) = tx_clone.send(Event::DeviceEvent {
+                  device_id: DEVICE_ID,
+                  event: DeviceEvent::TabletButton {
+                    id: pad_button.button(),
+                    state,
+                  },
+                }) {
+                  log::warn!("Failed to send tablet button event to event channel: {}", e);
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
+            let tx_clone = event_tx.clone();
+            let input_group = input_group.clone();
+            let display = window.display();
+            // GTK doesn't flag the key presses it repeats.
+            let repeats = RefCell::new(RepeatCounter::default());
+            let dead_keys = RefCell::new(DeadKeyState::default());
             let keyboard_handler = Rc::new(move |event_key: EventKey, element_state| {
               // if we have a modifier lets send it
               let mut mods = keyboard::get_modifiers(event_key.clone());
@@ -721,10 +1546,31 @@ impl<T: 'static> EventLoop<T> {
                 }
               }
 
-              // todo: implement repeat?
+              let group = event_key.group();
+              if input_group
+                .replace(Some(group))
+                .map_or(false, |previous| previous != group)
+              {
+                if let Err(e) = tx_clone.send(Event::InputLocaleChanged {
+                  input_locale: group.to_string(),
+                }) {
+                  log::warn!("Failed to send input locale event to event channel: {}", e);
+                }
+                let layout = keyboard::keyboard_layout(&display);
+                if let Err(e) = tx_clone.send(Event::KeyboardLayoutChanged(layout)) {
+                  log::warn!(
+                    "Failed to send keyboard layout event to event channel: {}",
+                    e
+                  );
+                }
+              }
+
               let event = keyboard::make_key_event(&event_key, false, None, element_state);
 
-              if let Some(event) = event {
+              if let Some(mut event) = event {
+                repeats.borrow_mut().count(&mut event);
+                let typed_text = event.text.is_some();
+                dead_keys.borrow_mut().update(&mut event, typed_text);
                 if let Err(e) = tx_clone.send(Event::WindowEvent {
                   window_id: RootWindowId(id),
                   event: WindowEvent::KeyboardInput {
@@ -745,21 +1591,62 @@ impl<T: 'static> EventLoop<T> {
             ime.set_client_window(window.window().as_ref());
             ime.focus_in();
             ime.connect_commit(move |_, s| {
+              for event in [
+                WindowEvent::Ime(Ime::Commit(s.to_string())),
+                WindowEvent::ReceivedImeText(s.to_string()),
+              ] {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event,
+                }) {
+                  log::warn!(
+                    "Failed to send received IME text event to event channel: {}",
+                    e
+                  );
+                }
+              }
+            });
+
+            let tx_clone = event_tx.clone();
+            ime.connect_preedit_changed(move |ime| {
+              let (text, _, cursor) = ime.preedit_string();
+              // The cursor position is in characters.
+              let cursor = usize::try_from(cursor).ok().map(|cursor| {
+                let cursor = text
+                  .char_indices()
+                  .nth(cursor)
+                  .map_or(text.len(), |(i, _)| i);
+                (cursor, cursor)
+              });
+              let cursor = cursor.filter(|_| !text.is_empty());
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
-                event: WindowEvent::ReceivedImeText(s.to_string()),
+                event: WindowEvent::Ime(Ime::Preedit(text.to_string(), cursor)),
               }) {
-                log::warn!(
-                  "Failed to send received IME text event to event channel: {}",
-                  e
-                );
+                log::warn!("Failed to send IME preedit event to event channel: {}", e);
               }
             });
+            let ime_allowed = Rc::new(Cell::new(true));
+            imes.insert(id, (ime.clone(), ime_allowed.clone()));
 
             let handler = keyboard_handler.clone();
+            let tx_clone = event_tx.clone();
             window.connect_key_press_event(move |_, event_key| {
               handler(event_key.to_owned(), ElementState::Pressed);
-              ime.filter_keypress(event_key);
+              if ime_allowed.get() {
+                ime.filter_keypress(event_key);
+              } else if let Some(c) = event_key.keyval().to_unicode().filter(|c| !c.is_control()) {
+                // The IME doesn't commit the typed text while it's disallowed.
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::ReceivedImeText(c.to_string()),
+                }) {
+                  log::warn!(
+                    "Failed to send received IME text event to event channel: {}",
+                    e
+                  );
+                }
+              }
 
               glib::Propagation::Proceed
             });
@@ -802,6 +1689,28 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Proceed
             });
 
+            // Only X11 sends visibility notifications, and it sends one whenever the window goes
+            // from fully to partially visible or back, which doesn't change whether it is occluded.
+            let tx_clone = event_tx.clone();
+            let occluded = Cell::new(false);
+            window.connect_event(move |_, event| {
+              if event.event_type() != gdk::EventType::VisibilityNotify {
+                return glib::Propagation::Proceed;
+              }
+              if let Some(visibility) = event.downcast_ref::<gdk::EventVisibility>() {
+                let is_occluded = visibility.state() == gdk::VisibilityState::FullyObscured;
+                if occluded.replace(is_occluded) != is_occluded {
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::Occluded(is_occluded),
+                  }) {
+                    log::warn!("Failed to send occluded event to event channel: {}", e);
+                  }
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
             // Receive draw events of the window.
             let draw_clone = draw_tx.clone();
             window.connect_draw(move |_, cr| {
@@ -843,6 +1752,23 @@ impl<T: 'static> EventLoop<T> {
       glib::ControlFlow::Continue
     });
 
+    let system_bus = match gio::bus_get_sync(gio::BusType::System, None::<&Cancellable>) {
+      Ok(bus) => {
+        watch_logind(&bus, user_event_tx.clone());
+        watch_upower(&bus, user_event_tx.clone());
+        watch_locale(&bus, user_event_tx.clone());
+        watch_timedate(&bus, user_event_tx.clone());
+        Some(bus)
+      }
+      Err(e) => {
+        log::warn!(
+          "Failed to connect to the system bus, power events won't be emitted: {}",
+          e
+        );
+        None
+      }
+    };
+
     // Create event loop itself.
     let event_loop = Self {
       window_target: RootELW {
@@ -851,8 +1777,14 @@ impl<T: 'static> EventLoop<T> {
       },
       user_event_tx,
       events: event_rx,
+      typed_user_event_tx,
+      typed_events: typed_event_rx,
       draws: draw_rx,
       run_device_thread,
+      context,
+      pump_state: None,
+      shutdown_timeout: crate::event_loop::DEFAULT_SHUTDOWN_TIMEOUT,
+      _system_bus: system_bus,
     };
 
     Ok(event_loop)
@@ -863,7 +1795,7 @@ impl<T: 'static> EventLoop<T> {
   where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow) + 'static,
   {
-    let exit_code = self.run_return(callback);
+    let exit_code = self.run_inner(callback, true).exit_code();
     process::exit(exit_code)
   }
 
@@ -896,129 +1828,284 @@ impl<T: 'static> EventLoop<T> {
   /// current control flow is sent.
   /// - On `EventQueue` to `DrawQueue`, a `MainEventsCleared` event is sent.
   /// - On `DrawQueue` back to `NewStart`, a `RedrawEventsCleared` event is sent.
-  pub(crate) fn run_return<F>(&mut self, mut callback: F) -> i32
+  pub(crate) fn run_return<F>(&mut self, callback: F) -> ExitReason
   where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
   {
-    enum EventState {
-      NewStart,
-      EventQueue,
-      DrawQueue,
-    }
+    self.run_inner(callback, false)
+  }
 
-    let context = MainContext::default();
-    let run_device_thread = self.run_device_thread.clone();
+  /// Runs `run_return`, destroying the windows left once it exits if `destroy_windows` is set,
+  /// which is only the case for `run` since the windows are kept in between two `run_return`.
+  fn run_inner<F>(&mut self, mut callback: F, destroy_windows: bool) -> ExitReason
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let context = self.context.clone();
+    self.pump_state = None;
 
     context
       .with_thread_default(|| {
-        let mut control_flow = ControlFlow::default();
-        let window_target = &self.window_target;
-        let events = &self.events;
-        let draws = &self.draws;
-
-        window_target.p.app.activate();
-
-        let mut state = EventState::NewStart;
-        let exit_code = loop {
-          let mut blocking = false;
-          match state {
-            EventState::NewStart => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break code;
-              }
-              ControlFlow::Wait => {
-                if !events.is_empty() {
-                  callback(
-                    Event::NewEvents(StartCause::WaitCancelled {
-                      start: Instant::now(),
-                      requested_resume: None,
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else {
-                  blocking = true;
-                }
-              }
-              ControlFlow::WaitUntil(requested_resume) => {
-                let start = Instant::now();
-                if start >= requested_resume {
-                  callback(
-                    Event::NewEvents(StartCause::ResumeTimeReached {
-                      start,
-                      requested_resume,
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else if !events.is_empty() {
-                  callback(
-                    Event::NewEvents(StartCause::WaitCancelled {
-                      start,
-                      requested_resume: Some(requested_resume),
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else {
-                  blocking = true;
-                }
-              }
-              _ => {
-                callback(
-                  Event::NewEvents(StartCause::Poll),
-                  window_target,
-                  &mut control_flow,
-                );
-                state = EventState::EventQueue;
-              }
-            },
-            EventState::EventQueue => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break (code);
-              }
-              _ => match events.try_recv() {
-                Ok(event) => match event {
-                  Event::LoopDestroyed => control_flow = ControlFlow::ExitWithCode(1),
-                  _ => callback(event, window_target, &mut control_flow),
-                },
-                Err(_) => {
-                  callback(Event::MainEventsCleared, window_target, &mut control_flow);
-                  state = EventState::DrawQueue;
-                }
-              },
-            },
-            EventState::DrawQueue => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break code;
+        let mut state = LoopState {
+          destroy_windows,
+          ..LoopState::default()
+        };
+        self.start(&mut state, &mut callback);
+        loop {
+          match self.step(&mut state, &mut callback) {
+            Ok(false) => {
+              context.iteration(false);
+            }
+            Ok(true) => wait(&context, state.control_flow, None),
+            Err(exit_reason) => break exit_reason,
+          }
+        }
+      })
+      .unwrap_or(ExitReason::Quit(1))
+  }
+
+  /// Runs the steps of one pass through the states of `run_return`, after waiting for events for
+  /// up to `timeout` if there are none.
+  pub(crate) fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let context = self.context.clone();
+
+    context
+      .with_thread_default(|| {
+        let mut state = match self.pump_state.take() {
+          Some(state) => state,
+          None => {
+            let mut state = LoopState::default();
+            self.start(&mut state, &mut callback);
+            state
+          }
+        };
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut iterating = !matches!(state.event_state, EventState::NewStart);
+
+        loop {
+          match self.step(&mut state, &mut callback) {
+            Ok(false) => {
+              context.iteration(false);
+              if !matches!(state.event_state, EventState::NewStart) {
+                iterating = true;
+              } else if iterating {
+                self.pump_state = Some(state);
+                break PumpStatus::Continue;
               }
-              _ => {
-                if let Ok(id) = draws.try_recv() {
-                  callback(
-                    Event::RedrawRequested(RootWindowId(id)),
-                    window_target,
-                    &mut control_flow,
-                  );
-                }
-                callback(Event::RedrawEventsCleared, window_target, &mut control_flow);
-                state = EventState::NewStart;
+            }
+            Ok(true) => {
+              if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                self.pump_state = Some(state);
+                break PumpStatus::Continue;
               }
-            },
+              wait(&context, state.control_flow, deadline);
+            }
+            Err(exit_reason) => break PumpStatus::Exit(exit_reason),
           }
-          gtk::main_iteration_do(blocking);
-        };
-        if let Some(run_device_thread) = run_device_thread {
-          run_device_thread.store(false, Ordering::Relaxed);
         }
-        exit_code
       })
-      .unwrap_or(1)
+      .unwrap_or(PumpStatus::Exit(ExitReason::Quit(1)))
+  }
+
+  /// Starts a call to `run_return`, or a series of `pump_events`, with
+  /// `Event::NewEvents(StartCause::Init)`.
+  fn start<F>(&self, state: &mut LoopState<T>, callback: &mut F)
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    self.window_target.p.app.activate();
+    callback(
+      Event::NewEvents(StartCause::Init),
+      &self.window_target,
+      &mut state.control_flow,
+    );
+    state.event_state = EventState::EventQueue;
+  }
+
+  /// Moves `state` to its next state, see `run_return`. Returns whether the main context has to
+  /// wait for events before the next step, or why the event loop exited.
+  fn step<F>(&self, state: &mut LoopState<T>, callback: &mut F) -> Result<bool, ExitReason>
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let LoopState {
+      event_state,
+      control_flow,
+      quit,
+      wait_start,
+      pending,
+      destroy_windows,
+    } = state;
+    let window_target = &self.window_target;
+    let events = &self.events;
+    let typed_events = &self.typed_events;
+    let draws = &self.draws;
+    let pending_events = !events.is_empty() || !typed_events.is_empty();
+    let mut blocking = false;
+    match event_state {
+      EventState::NewStart => match *control_flow {
+        ControlFlow::ExitWithCode(code) => {
+          if *destroy_windows && !*quit {
+            self.destroy_windows(callback, control_flow);
+          }
+          callback(Event::LoopDestroyed, window_target, control_flow);
+          return Err(state_exit_reason(code, *quit));
+        }
+        ControlFlow::Wait => {
+          if pending_events {
+            let start_cause = self.wait_cancelled(pending, *wait_start, None);
+            callback(Event::NewEvents(start_cause), window_target, control_flow);
+            *event_state = EventState::EventQueue;
+          } else {
+            blocking = true;
+          }
+        }
+        ControlFlow::WaitUntil(requested_resume) => {
+          if Instant::now() >= requested_resume {
+            callback(
+              Event::NewEvents(StartCause::ResumeTimeReached {
+                start: *wait_start,
+                requested_resume,
+              }),
+              window_target,
+              control_flow,
+            );
+            *event_state = EventState::EventQueue;
+          } else if pending_events {
+            let start_cause = self.wait_cancelled(pending, *wait_start, Some(requested_resume));
+            callback(Event::NewEvents(start_cause), window_target, control_flow);
+            *event_state = EventState::EventQueue;
+          } else {
+            blocking = true;
+          }
+        }
+        _ => {
+          callback(
+            Event::NewEvents(StartCause::Poll),
+            window_target,
+            control_flow,
+          );
+          *event_state = EventState::EventQueue;
+        }
+      },
+      EventState::EventQueue => match *control_flow {
+        ControlFlow::ExitWithCode(code) if pending.is_none() => {
+          if *destroy_windows && !*quit {
+            self.destroy_windows(callback, control_flow);
+          }
+          callback(Event::LoopDestroyed, window_target, control_flow);
+          return Err(state_exit_reason(code, *quit));
+        }
+        _ => match pending.take().map_or_else(|| events.try_recv(), Ok) {
+          Ok(event) => match event {
+            Event::LoopDestroyed => {
+              *quit = true;
+              *control_flow = ControlFlow::ExitWithCode(1);
+            }
+            _ => callback(event, window_target, control_flow),
+          },
+          Err(_) => {
+            if let Ok(Event::UserEvent(event)) = typed_events.try_recv() {
+              callback(Event::TypedUserEvent(event), window_target, control_flow);
+            } else {
+              callback(Event::MainEventsCleared, window_target, control_flow);
+              *event_state = EventState::DrawQueue;
+            }
+          }
+        },
+      },
+      EventState::DrawQueue => match *control_flow {
+        ControlFlow::ExitWithCode(code) => {
+          if *destroy_windows && !*quit {
+            self.destroy_windows(callback, control_flow);
+          }
+          callback(Event::LoopDestroyed, window_target, control_flow);
+          return Err(state_exit_reason(code, *quit));
+        }
+        _ => {
+          if let Ok(id) = draws.try_recv() {
+            callback(
+              Event::RedrawRequested(RootWindowId(id)),
+              window_target,
+              control_flow,
+            );
+          }
+          callback(Event::RedrawEventsCleared, window_target, control_flow);
+          *event_state = EventState::NewStart;
+          *wait_start = Instant::now();
+        }
+      },
+    }
+    Ok(blocking)
+  }
+
+  /// Returns the `StartCause` of a wait cancelled by pending events, which is `UserEvent` if the
+  /// first of them was sent through an `EventLoopProxy`. That event is moved into `pending`.
+  fn wait_cancelled(
+    &self,
+    pending: &mut Option<Event<'static, T>>,
+    start: Instant,
+    requested_resume: Option<Instant>,
+  ) -> StartCause {
+    let user_event = match self.events.try_recv() {
+      Ok(event) => {
+        let user_event = matches!(event, Event::UserEvent(_));
+        *pending = Some(event);
+        user_event
+      }
+      // Only typed user events are pending.
+      Err(_) => true,
+    };
+    if user_event {
+      StartCause::UserEvent {
+        start,
+        requested_resume,
+      }
+    } else {
+      StartCause::WaitCancelled {
+        start,
+        requested_resume,
+      }
+    }
+  }
+
+  /// Destroys the windows which are still alive before the event loop exits, dispatching the
+  /// events queued by their teardown until none is left or `shutdown_timeout` elapsed.
+  fn destroy_windows<F>(&self, callback: &mut F, control_flow: &mut ControlFlow)
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let app = &self.window_target.p.app;
+    let deadline = Instant::now() + self.shutdown_timeout;
+    for window in app.windows() {
+      unsafe { window.destroy() };
+    }
+    loop {
+      while let Ok(event) = self.events.try_recv() {
+        if !matches!(event, Event::LoopDestroyed) {
+          callback(event, &self.window_target, control_flow);
+        }
+      }
+      let windows = app.windows().len();
+      if windows == 0 {
+        break;
+      }
+      if Instant::now() >= deadline {
+        log::warn!(
+          "{} windows left after shutting the event loop down",
+          windows
+        );
+        break;
+      }
+      self.context.iteration(false);
+    }
+  }
+
+  pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+    self.shutdown_timeout = timeout;
   }
 
   #[inline]
@@ -1030,6 +2117,24 @@ impl<T: 'static> EventLoop<T> {
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
       user_event_tx: self.user_event_tx.clone(),
+      context: self.context.clone(),
+    }
+  }
+
+  /// Creates an `EventLoopProxy` whose events are emitted as `Event::TypedUserEvent`.
+  pub fn create_typed_proxy(&self) -> EventLoopProxy<TypedUserEvent> {
+    EventLoopProxy {
+      user_event_tx: self.typed_user_event_tx.clone(),
+      context: self.context.clone(),
+    }
+  }
+}
+
+impl<T: 'static> Drop for EventLoop<T> {
+  fn drop(&mut self) {
+    // Device events keep coming in between two `run_return`s.
+    if let Some(run_device_thread) = &self.run_device_thread {
+      run_device_thread.store(false, Ordering::Relaxed);
     }
   }
 }
@@ -1038,12 +2143,14 @@ impl<T: 'static> EventLoop<T> {
 #[derive(Debug)]
 pub struct EventLoopProxy<T: 'static> {
   user_event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+  context: MainContext,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
   fn clone(&self) -> Self {
     Self {
       user_event_tx: self.user_event_tx.clone(),
+      context: self.context.clone(),
     }
   }
 }
@@ -1066,13 +2173,454 @@ impl<T: 'static> EventLoopProxy<T> {
         }
       })?;
 
-    let context = MainContext::default();
-    context.wakeup();
+    self.context.wakeup();
 
     Ok(())
   }
 }
 
+/// Sends `Event::MonitorConnected`, `Event::MonitorDisconnected`,
+/// `Event::MonitorConfigurationChanged` and `Event::MonitorRefreshRateChanged` through `event_tx`
+/// as the monitors of `display` change.
+fn watch_monitors<T: 'static>(
+  display: &gdk::Display,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let send = Rc::new(move |event: Event<'static, T>| {
+    if let Err(e) = event_tx.send(event) {
+      log::warn!("Failed to send monitor event to event channel: {}", e);
+    }
+  });
+
+  let watch_refresh_rate = {
+    let send = send.clone();
+    move |monitor: &gdk::Monitor| {
+      let send = send.clone();
+      monitor.connect_refresh_rate_notify(move |monitor| {
+        if monitor.refresh_rate() > 0 {
+          send(Event::MonitorRefreshRateChanged {
+            monitor: RootMonitorHandle {
+              inner: MonitorHandle {
+                monitor: monitor.clone(),
+              },
+            },
+            refresh_rate_millihertz: monitor.refresh_rate() as u32,
+          });
+        }
+      });
+    }
+  };
+  for i in 0..display.n_monitors() {
+    if let Some(monitor) = display.monitor(i) {
+      watch_refresh_rate(&monitor);
+    }
+  }
+
+  let send_ = send.clone();
+  display.connect_monitor_added(move |_, monitor| {
+    watch_refresh_rate(monitor);
+    send_(Event::MonitorConnected(RootMonitorHandle {
+      inner: MonitorHandle {
+        monitor: monitor.clone(),
+      },
+    }));
+  });
+  let send_ = send.clone();
+  display.connect_monitor_removed(move |_, monitor| {
+    send_(Event::MonitorDisconnected(RootMonitorHandle {
+      inner: MonitorHandle {
+        monitor: monitor.clone(),
+      },
+    }));
+  });
+  display
+    .default_screen()
+    .connect_monitors_changed(move |_| send(Event::MonitorConfigurationChanged));
+}
+
+/// What the filter of the root window needs to emit `Event::MonitorIccProfileChanged`.
+struct IccProfileFilterData<T: 'static> {
+  display: gdk::Display,
+  xlib: x11_dl::xlib::Xlib,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+}
+
+/// Sends `Event::MonitorIccProfileChanged` through `event_tx` when the `_ICC_PROFILE` properties
+/// of the root window change, only on X11. The filter stays installed for the lifetime of the
+/// process.
+fn watch_icc_profiles<T: 'static>(
+  display: &gdk::Display,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  use x11_dl::xlib;
+
+  unsafe extern "C" fn filter<T: 'static>(
+    xevent: *mut gdk::ffi::GdkXEvent,
+    _event: *mut gdk::ffi::GdkEvent,
+    data: glib::ffi::gpointer,
+  ) -> gdk::ffi::GdkFilterReturn {
+    let xevent = &*(xevent as *const xlib::XEvent);
+    if xevent.get_type() != xlib::PropertyNotify {
+      return gdk::ffi::GDK_FILTER_CONTINUE;
+    }
+    let data = &*(data as *const IccProfileFilterData<T>);
+    let property = xevent.property;
+    let name = (data.xlib.XGetAtomName)(property.display, property.atom);
+    if name.is_null() {
+      return gdk::ffi::GDK_FILTER_CONTINUE;
+    }
+    let index = monitor::icc_profile_index(std::ffi::CStr::from_ptr(name).to_bytes());
+    (data.xlib.XFree)(name as *mut _);
+    if let Some(monitor) = index.and_then(|index| data.display.monitor(index)) {
+      let event = Event::MonitorIccProfileChanged(RootMonitorHandle {
+        inner: MonitorHandle { monitor },
+      });
+      if let Err(e) = data.event_tx.send(event) {
+        log::warn!("Failed to send monitor event to event channel: {}", e);
+      }
+    }
+    gdk::ffi::GDK_FILTER_CONTINUE
+  }
+
+  if !display.backend().is_x11() {
+    return;
+  }
+  let (root, xlib) = match (display.default_screen().root_window(), xlib::Xlib::open()) {
+    (Some(root), Ok(xlib)) => (root, xlib),
+    _ => return,
+  };
+  root.set_events(root.events() | EventMask::PROPERTY_CHANGE_MASK);
+  let data = Box::new(IccProfileFilterData {
+    display: display.clone(),
+    xlib,
+    event_tx,
+  });
+  unsafe {
+    gdk::ffi::gdk_window_add_filter(
+      root.as_ptr(),
+      Some(filter::<T>),
+      Box::into_raw(data) as glib::ffi::gpointer,
+    );
+  }
+}
+
+/// Sends `Event::SystemAppearanceChanged` through `event_tx` when the GTK settings it's read from
+/// change.
+fn watch_appearance<T: 'static>(
+  display: &gdk::Display,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let settings = match gtk::Settings::for_screen(&display.default_screen()) {
+    Some(settings) => settings,
+    None => return,
+  };
+  let appearance = Rc::new(Cell::new(util::system_appearance(display)));
+  let display = display.clone();
+  let changed = Rc::new(move || {
+    let new_appearance = util::system_appearance(&display);
+    if appearance.replace(new_appearance) != new_appearance {
+      if let Err(e) = event_tx.send(Event::SystemAppearanceChanged(new_appearance)) {
+        log::warn!("Failed to send appearance event to event channel: {}", e);
+      }
+    }
+  });
+
+  let changed_ = changed.clone();
+  settings.connect_gtk_theme_name_notify(move |_| changed_());
+  settings.connect_gtk_enable_animations_notify(move |_| changed());
+}
+
+const LOGIND: &str = "org.freedesktop.login1";
+const UPOWER: &str = "org.freedesktop.UPower";
+const LOCALE1: &str = "org.freedesktop.locale1";
+const TIMEDATE1: &str = "org.freedesktop.timedate1";
+
+/// Sends the events through `event_tx`, logging when it fails.
+fn sender<T: 'static>(
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) -> Rc<dyn Fn(Option<Event<'static, T>>)> {
+  Rc::new(move |event: Option<Event<'static, T>>| {
+    if let Some(event) = event {
+      if let Err(e) = event_tx.send(event) {
+        log::warn!("Failed to send power event to event channel: {}", e);
+      }
+    }
+  })
+}
+
+/// Reads the new value of `property` from the parameters of a `PropertiesChanged` signal.
+fn changed_property<V: glib::FromVariant>(parameters: &glib::Variant, property: &str) -> Option<V> {
+  let (_, changed, _) =
+    parameters.get::<(String, HashMap<String, glib::Variant>, Vec<String>)>()?;
+  changed.get(property)?.get::<V>()
+}
+
+/// Subscribes to logind for the system going to sleep and the session getting locked, sending
+/// `Event::Suspended`, `Event::Resumed` and the matching `Event::PowerEvent` through `event_tx`.
+fn watch_logind<T: 'static>(
+  bus: &gio::DBusConnection,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let suspension = Rc::new(RefCell::new(Suspension::default()));
+  let send = sender(event_tx);
+
+  let (suspension_, send_) = (suspension.clone(), send.clone());
+  bus.signal_subscribe(
+    Some(LOGIND),
+    Some("org.freedesktop.login1.Manager"),
+    Some("PrepareForSleep"),
+    Some("/org/freedesktop/login1"),
+    None,
+    gio::DBusSignalFlags::NONE,
+    move |_, _, _, _, _, parameters| {
+      if let Some((sleeping,)) = parameters.get::<(bool,)>() {
+        send_(Some(Event::PowerEvent(if sleeping {
+          PowerEvent::Suspending
+        } else {
+          PowerEvent::Resuming
+        })));
+        send_(suspension_.borrow_mut().set_sleeping(sleeping));
+      }
+    },
+  );
+
+  let session = bus
+    .call_sync(
+      Some(LOGIND),
+      "/org/freedesktop/login1",
+      "org.freedesktop.login1.Manager",
+      "GetSessionByPID",
+      Some(&(std::process::id(),).to_variant()),
+      glib::VariantTy::new("(o)").ok(),
+      gio::DBusCallFlags::NONE,
+      -1,
+      None::<&Cancellable>,
+    )
+    .map(|reply| reply.child_value(0));
+  match session.as_ref().map(|session| session.str()) {
+    Ok(Some(session)) => {
+      bus.signal_subscribe(
+        Some(LOGIND),
+        Some("org.freedesktop.DBus.Properties"),
+        Some("PropertiesChanged"),
+        Some(session),
+        Some("org.freedesktop.login1.Session"),
+        gio::DBusSignalFlags::NONE,
+        move |_, _, _, _, _, parameters| {
+          if let Some(locked) = changed_property::<bool>(parameters, "LockedHint") {
+            send(suspension.borrow_mut().set_locked(locked));
+          }
+        },
+      );
+    }
+    Ok(None) => (),
+    Err(e) => log::warn!(
+      "Failed to find the logind session, its lock won't suspend the application: {}",
+      e
+    ),
+  }
+}
+
+/// Subscribes to UPower for the lid and the battery level, sending `Event::PowerEvent` through
+/// `event_tx`.
+fn watch_upower<T: 'static>(
+  bus: &gio::DBusConnection,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let send = sender(event_tx);
+
+  let send_ = send.clone();
+  bus.signal_subscribe(
+    Some(UPOWER),
+    Some("org.freedesktop.DBus.Properties"),
+    Some("PropertiesChanged"),
+    Some("/org/freedesktop/UPower"),
+    Some(UPOWER),
+    gio::DBusSignalFlags::NONE,
+    move |_, _, _, _, _, parameters| {
+      if let Some(closed) = changed_property::<bool>(parameters, "LidIsClosed") {
+        send_(Some(Event::PowerEvent(if closed {
+          PowerEvent::LidClosed
+        } else {
+          PowerEvent::LidOpened
+        })));
+      }
+    },
+  );
+
+  // Levels of the `WarningLevel` property, 3 and up are low, critical and action.
+  const WARNING_LEVEL_LOW: u32 = 3;
+  let warning_level = Cell::new(0);
+  bus.signal_subscribe(
+    Some(UPOWER),
+    Some("org.freedesktop.DBus.Properties"),
+    Some("PropertiesChanged"),
+    Some("/org/freedesktop/UPower/devices/DisplayDevice"),
+    Some("org.freedesktop.UPower.Device"),
+    gio::DBusSignalFlags::NONE,
+    move |_, _, _, _, _, parameters| {
+      if let Some(level) = changed_property::<u32>(parameters, "WarningLevel") {
+        let was_low = warning_level.replace(level) >= WARNING_LEVEL_LOW;
+        if level >= WARNING_LEVEL_LOW && !was_low {
+          send(Some(Event::PowerEvent(PowerEvent::BatteryLow)));
+        }
+      }
+    },
+  );
+}
+
+/// Subscribes to locale1 for the system locale, sending `Event::LocaleChanged` through `event_tx`.
+fn watch_locale<T: 'static>(
+  bus: &gio::DBusConnection,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  bus.signal_subscribe(
+    Some(LOCALE1),
+    Some("org.freedesktop.DBus.Properties"),
+    Some("PropertiesChanged"),
+    Some("/org/freedesktop/locale1"),
+    Some(LOCALE1),
+    gio::DBusSignalFlags::NONE,
+    move |_, _, _, _, _, parameters| {
+      // Variables like `LANG=en_US.UTF-8`.
+      let lang = changed_property::<Vec<String>>(parameters, "Locale").and_then(|variables| {
+        variables
+          .iter()
+          .find_map(|variable| variable.strip_prefix("LANG=").map(str::to_owned))
+      });
+      if let Some(lang) = lang {
+        let locale = lang
+          .split(|c| c == '.' || c == '@')
+          .next()
+          .unwrap_or_default();
+        if let Err(e) = event_tx.send(Event::LocaleChanged {
+          locale: locale.replace('_', "-"),
+        }) {
+          log::warn!("Failed to send locale event to event channel: {}", e);
+        }
+      }
+    },
+  );
+}
+
+/// The state of a pen from the axes of a motion or button event, `None` for the other devices.
+/// Grabs the pointer of `window` with `mode`, or releases it with `CursorGrabMode::None`. Returns the
+/// root position the cursor is locked at with `CursorGrabMode::Locked`, which the motion handler
+/// moves it back to.
+fn grab_cursor(window: &gtk::ApplicationWindow, mode: CursorGrabMode) -> Option<(i32, i32)> {
+  let gdk_window = window.window()?;
+  unsafe { gdk::ffi::gdk_pointer_ungrab(gdk::ffi::GDK_CURRENT_TIME as _) };
+  if mode == CursorGrabMode::None {
+    return None;
+  }
+
+  // Unlike the grabs of seats, the deprecated pointer grabs can confine the pointer to a window.
+  let status = unsafe {
+    gdk::ffi::gdk_pointer_grab(
+      gdk_window.as_ptr(),
+      glib::ffi::GTRUE,
+      (EventMask::POINTER_MOTION_MASK
+        | EventMask::BUTTON_PRESS_MASK
+        | EventMask::BUTTON_RELEASE_MASK)
+        .bits(),
+      gdk_window.as_ptr(),
+      std::ptr::null_mut(),
+      gdk::ffi::GDK_CURRENT_TIME as _,
+    )
+  };
+  if status != gdk::ffi::GDK_GRAB_SUCCESS {
+    log::warn!("Fail to grab the cursor: {}", status);
+    return None;
+  }
+  if mode != CursorGrabMode::Locked {
+    return None;
+  }
+
+  let pointer = window.display().default_seat()?.pointer()?;
+  let (_, x, y) = pointer.position();
+  let (_, left, top) = gdk_window.origin();
+  Some((
+    x.clamp(left, left + gdk_window.width() - 1),
+    y.clamp(top, top + gdk_window.height() - 1),
+  ))
+}
+
+/// Converts the phase of a touchpad gesture, which the bindings of GDK don't expose.
+fn touchpad_phase(phase: i8) -> TouchPhase {
+  match phase as gdk::ffi::GdkTouchpadGesturePhase {
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_BEGIN => TouchPhase::Started,
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_END => TouchPhase::Ended,
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_CANCEL => TouchPhase::Cancelled,
+    _ => TouchPhase::Moved,
+  }
+}
+
+fn pen_input(event: &gdk::Event, scale_factor: f64) -> Option<PenInput> {
+  let source = event.source_device()?.source();
+  if !matches!(source, gdk::InputSource::Pen | gdk::InputSource::Eraser) {
+    return None;
+  }
+  let (x, y) = event.coords()?;
+
+  // The state of a button event is the one from before the button changed.
+  let state = event.state().unwrap_or_else(gdk::ModifierType::empty);
+  let mut buttons = 0;
+  for (bit, mask) in [
+    gdk::ModifierType::BUTTON1_MASK,
+    gdk::ModifierType::BUTTON2_MASK,
+    gdk::ModifierType::BUTTON3_MASK,
+  ]
+  .iter()
+  .enumerate()
+  {
+    if state.contains(*mask) {
+      buttons |= 1 << bit;
+    }
+  }
+  match (event.event_type(), event.button()) {
+    (gdk::EventType::ButtonPress, Some(button @ 1..=3)) => buttons |= 1 << (button - 1),
+    (gdk::EventType::ButtonRelease, Some(button @ 1..=3)) => buttons &= !(1 << (button - 1)),
+    _ => (),
+  }
+
+  // GDK scales the tilt to `-1.0..=1.0` and the other axes to `0.0..=1.0`.
+  let axis = |axis_use| event.axis(axis_use).unwrap_or(0.0);
+  Some(PenInput {
+    device_id: DEVICE_ID,
+    position: LogicalPosition::new(x, y).to_physical(scale_factor),
+    in_contact: buttons & 1 != 0,
+    pressure: axis(gdk::AxisUse::Pressure),
+    tilt_x: axis(gdk::AxisUse::Xtilt) * 90.0,
+    tilt_y: axis(gdk::AxisUse::Ytilt) * 90.0,
+    twist: axis(gdk::AxisUse::Rotation) * 360.0,
+    eraser: source == gdk::InputSource::Eraser,
+    barrel_buttons: buttons >> 1,
+  })
+}
+
+/// Subscribes to timedate1 for the time zone, sending `Event::SystemTimeChanged` through
+/// `event_tx`.
+fn watch_timedate<T: 'static>(
+  bus: &gio::DBusConnection,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  bus.signal_subscribe(
+    Some(TIMEDATE1),
+    Some("org.freedesktop.DBus.Properties"),
+    Some("PropertiesChanged"),
+    Some("/org/freedesktop/timedate1"),
+    Some(TIMEDATE1),
+    gio::DBusSignalFlags::NONE,
+    move |_, _, _, _, _, parameters| {
+      if changed_property::<String>(parameters, "Timezone").is_some() {
+        if let Err(e) = event_tx.send(Event::SystemTimeChanged) {
+          log::warn!("Failed to send time event to event channel: {}", e);
+        }
+      }
+    },
+  );
+}
+
 fn assert_is_main_thread(suggested_method: &str) {
   assert!(
     is_main_thread(),
@@ -0,0 +1,188 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  os::raw::{c_int, c_uint},
+  rc::Rc,
+};
+
+use gtk::{gdk, glib, prelude::*};
+use x11_dl::xlib;
+
+use crate::{
+  error::{ExternalError, NotSupportedError},
+  event::Event,
+  global_shortcut::{Accelerator, GlobalShortcutId},
+  keyboard::ModifiersState,
+};
+
+use super::OsError;
+
+/// Locks which are ignored when matching a shortcut, so a key is grabbed once for each of their
+/// combinations: Caps Lock and Num Lock.
+const IGNORED_MASKS: [c_uint; 4] = [
+  0,
+  xlib::LockMask,
+  xlib::Mod2Mask,
+  xlib::LockMask | xlib::Mod2Mask,
+];
+
+/// Keys grabbed on the root window for `GlobalShortcutManager::register`.
+#[derive(Default)]
+pub struct GlobalShortcuts {
+  xlib: Option<xlib::Xlib>,
+  /// X keycode and modifier mask of each shortcut.
+  grabs: HashMap<GlobalShortcutId, (c_int, c_uint)>,
+}
+
+impl GlobalShortcuts {
+  pub fn register(
+    &mut self,
+    display: &gdk::Display,
+    id: GlobalShortcutId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    if !display.backend().is_x11() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    let keycode = accelerator
+      .key
+      .to_scancode()
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))? as c_int;
+    let mut modifiers = 0;
+    for (modifier, mask) in [
+      (ModifiersState::SHIFT, xlib::ShiftMask),
+      (ModifiersState::CONTROL, xlib::ControlMask),
+      (ModifiersState::ALT, xlib::Mod1Mask),
+      (ModifiersState::SUPER, xlib::Mod4Mask),
+    ] {
+      if accelerator.mods.contains(modifier) {
+        modifiers |= mask;
+      }
+    }
+
+    if self.xlib.is_none() {
+      self.xlib = Some(xlib::Xlib::open().map_err(|_| ExternalError::Os(os_error!(OsError)))?);
+    }
+    let xlib = self.xlib.as_ref().unwrap();
+    let x11_display = display.as_ptr() as *mut gdk_x11_sys::GdkX11Display;
+    unsafe {
+      let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(x11_display) as *mut xlib::Display;
+      let root = (xlib.XDefaultRootWindow)(xdisplay);
+      // Grabbing a key which another client grabbed already fails with `BadAccess`.
+      gdk_x11_sys::gdk_x11_display_error_trap_push(x11_display);
+      for mask in IGNORED_MASKS {
+        (xlib.XGrabKey)(
+          xdisplay,
+          keycode,
+          modifiers | mask,
+          root,
+          xlib::False,
+          xlib::GrabModeAsync,
+          xlib::GrabModeAsync,
+        );
+      }
+      if gdk_x11_sys::gdk_x11_display_error_trap_pop(x11_display) != 0 {
+        ungrab(xlib, xdisplay, keycode, modifiers);
+        return Err(ExternalError::Os(os_error!(OsError)));
+      }
+    }
+    self.grabs.insert(id, (keycode, modifiers));
+    Ok(())
+  }
+
+  pub fn unregister(&mut self, display: &gdk::Display, id: GlobalShortcutId) {
+    if let (Some(xlib), Some((keycode, modifiers))) = (&self.xlib, self.grabs.remove(&id)) {
+      unsafe {
+        let x11_display = display.as_ptr() as *mut gdk_x11_sys::GdkX11Display;
+        let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(x11_display) as *mut xlib::Display;
+        ungrab(xlib, xdisplay, keycode, modifiers);
+      }
+    }
+  }
+
+  pub fn unregister_all(&mut self, display: &gdk::Display) {
+    let ids: Vec<_> = self.grabs.keys().copied().collect();
+    for id in ids {
+      self.unregister(display, id);
+    }
+  }
+
+  fn find(&self, keycode: c_int, state: c_uint) -> Option<GlobalShortcutId> {
+    let state = state & (xlib::ShiftMask | xlib::ControlMask | xlib::Mod1Mask | xlib::Mod4Mask);
+    self
+      .grabs
+      .iter()
+      .find(|(_, grab)| **grab == (keycode, state))
+      .map(|(id, _)| *id)
+  }
+}
+
+unsafe fn ungrab(
+  xlib: &xlib::Xlib,
+  xdisplay: *mut xlib::Display,
+  keycode: c_int,
+  modifiers: c_uint,
+) {
+  let root = (xlib.XDefaultRootWindow)(xdisplay);
+  for mask in IGNORED_MASKS {
+    (xlib.XUngrabKey)(xdisplay, keycode, modifiers | mask, root);
+  }
+}
+
+/// What the filter of the root window needs to emit `Event::GlobalShortcut`.
+struct FilterData<T: 'static> {
+  shortcuts: Rc<RefCell<GlobalShortcuts>>,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+}
+
+/// Watches the key presses the root window receives for the grabs of `shortcuts`. The filter stays
+/// installed for the lifetime of the process.
+pub fn install_filter<T: 'static>(
+  shortcuts: Rc<RefCell<GlobalShortcuts>>,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  unsafe extern "C" fn filter<T: 'static>(
+    xevent: *mut gdk::ffi::GdkXEvent,
+    _event: *mut gdk::ffi::GdkEvent,
+    data: glib::ffi::gpointer,
+  ) -> gdk::ffi::GdkFilterReturn {
+    let xevent = &*(xevent as *const xlib::XEvent);
+    if xevent.get_type() != xlib::KeyPress {
+      return gdk::ffi::GDK_FILTER_CONTINUE;
+    }
+    let data = &*(data as *const FilterData<T>);
+    let key = xevent.key;
+    match data
+      .shortcuts
+      .borrow()
+      .find(key.keycode as c_int, key.state)
+    {
+      Some(id) => {
+        if let Err(e) = data.event_tx.send(Event::GlobalShortcut(id)) {
+          log::warn!(
+            "Failed to send global shortcut event to event channel: {}",
+            e
+          );
+        }
+        gdk::ffi::GDK_FILTER_REMOVE
+      }
+      None => gdk::ffi::GDK_FILTER_CONTINUE,
+    }
+  }
+
+  let data = Box::new(FilterData {
+    shortcuts,
+    event_tx,
+  });
+  unsafe {
+    gdk::ffi::gdk_window_add_filter(
+      gdk::ffi::gdk_get_default_root_window(),
+      Some(filter::<T>),
+      Box::into_raw(data) as glib::ffi::gpointer,
+    );
+  }
+}
@@ -15,6 +15,8 @@ pub struct PlatformIcon {
   width: i32,
   height: i32,
   row_stride: i32,
+  /// Other sizes of the same icon, see `Icon::with_variant`.
+  variants: Vec<PlatformIcon>,
 }
 
 impl From<PlatformIcon> for Pixbuf {
@@ -44,9 +46,24 @@ impl PlatformIcon {
       width: width as i32,
       height: height as i32,
       row_stride,
+      variants: Vec::new(),
     })
   }
 
+  pub fn add_variant(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+    self.variants.push(Self::from_rgba(rgba, width, height)?);
+    Ok(())
+  }
+
+  /// Converts every size of the icon, for `gtk_window_set_icon_list`.
+  pub fn into_pixbufs(mut self) -> Vec<Pixbuf> {
+    let variants = std::mem::take(&mut self.variants);
+    std::iter::once(self)
+      .chain(variants)
+      .map(Pixbuf::from)
+      .collect()
+  }
+
   pub fn write_to_png(&self, path: impl AsRef<Path>) {
     let png = File::create(path).unwrap();
     let ref mut w = BufWriter::new(png);
@@ -5,19 +5,29 @@
 use super::KeyEventExtra;
 use crate::{
   event::{ElementState, KeyEvent},
-  keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NativeKeyCode},
+  keyboard::{
+    Key, KeyCode, KeyLocation, KeyRepeatSettings, KeyboardLayout, ModifiersState, NativeKeyCode,
+  },
 };
 use gtk::{
   gdk::{self, keys::constants::*, EventKey},
   glib,
+  prelude::*,
 };
 use std::{
   collections::HashSet,
-  ffi::c_void,
+  ffi::{c_void, CStr},
   os::raw::{c_int, c_uint},
   ptr, slice,
   sync::Mutex,
+  time::Duration,
 };
+use x11_dl::xlib;
+
+/// `XkbUseCoreKbd`, the device of the core keyboard.
+const XKB_USE_CORE_KBD: c_uint = 0x0100;
+/// `XkbGroupNamesMask`, to fetch the names of the groups.
+const XKB_GROUP_NAMES_MASK: c_uint = 1 << 12;
 
 pub type RawKey = gdk::keys::Key;
 
@@ -176,38 +186,20 @@ pub(crate) fn make_key_event(
   // a keyval (keysym in X) is a "logical" key name, such as GDK_Enter, GDK_a, GDK_space, etc.
   let keyval_without_modifiers = key.keyval();
   let keyval_with_modifiers =
-    hardware_keycode_to_keyval(scancode).unwrap_or_else(|| keyval_without_modifiers.clone());
-  // get unicode value, with and without modifiers
+    hardware_keycode_to_keyval(scancode, 0).unwrap_or_else(|| keyval_without_modifiers.clone());
+  // get unicode value without modifiers
   let text_without_modifiers = keyval_with_modifiers.to_unicode();
-  let text_with_modifiers = keyval_without_modifiers.to_unicode();
   // get physical key from the scancode (keycode)
   let physical_key = key_override.unwrap_or_else(|| KeyCode::from_scancode(scancode as u32));
 
-  // extract key without modifier
-  let key_without_modifiers = raw_key_to_key(keyval_with_modifiers.clone()).unwrap_or_else(|| {
-    if let Some(key) = text_without_modifiers {
-      if key >= ' ' && key != '\x7f' {
-        Key::Character(insert_or_get_key_str(key.to_string()))
-      } else {
-        Key::Unidentified(NativeKeyCode::Gtk(scancode))
-      }
-    } else {
-      Key::Unidentified(NativeKeyCode::Gtk(scancode))
-    }
-  });
+  // extract key without modifier, with dead keys as their accent like on other platforms
+  let key_without_modifiers = match keyval_to_key(keyval_with_modifiers.clone(), scancode) {
+    Key::Dead(Some(accent)) => Key::Character(insert_or_get_key_str(accent.to_string())),
+    key => key,
+  };
 
   // extract the logical key
-  let logical_key = raw_key_to_key(keyval_without_modifiers).unwrap_or_else(|| {
-    if let Some(key) = text_with_modifiers {
-      if key >= ' ' && key != '\x7f' {
-        Key::Character(insert_or_get_key_str(key.to_string()))
-      } else {
-        Key::Unidentified(NativeKeyCode::Gtk(scancode))
-      }
-    } else {
-      Key::Unidentified(NativeKeyCode::Gtk(scancode))
-    }
-  });
+  let logical_key = keyval_to_key(keyval_without_modifiers, scancode);
 
   // make sure we have a valid key
   if !matches!(key_without_modifiers, Key::Unidentified(_)) {
@@ -219,11 +211,14 @@ pub(crate) fn make_key_event(
       logical_key,
       physical_key,
       repeat: is_repeat,
+      repeat_count: 0,
+      dead_key_pending: false,
       state,
       text: text_with_all_modifiers,
       platform_specific: KeyEventExtra {
         text_with_all_modifiers,
         key_without_modifiers,
+        input_locale: Some(insert_or_get_key_str(key.group().to_string())),
       },
     });
   } else {
@@ -233,9 +228,150 @@ pub(crate) fn make_key_event(
   None
 }
 
+/// Returns the key of `keyval`, the character it types if it's not a named key.
+fn keyval_to_key(keyval: RawKey, scancode: u16) -> Key<'static> {
+  if let Some(accent) = dead_key_to_accent(&keyval) {
+    return Key::Dead(accent);
+  }
+  let text = keyval.to_unicode();
+  raw_key_to_key(keyval).unwrap_or_else(|| match text {
+    Some(key) if key >= ' ' && key != '\x7f' => {
+      Key::Character(insert_or_get_key_str(key.to_string()))
+    }
+    _ => Key::Unidentified(NativeKeyCode::Gtk(scancode)),
+  })
+}
+
+/// Returns `Some` with the accent of the dead key `keyval`, if it is one and the accent has a
+/// spacing character.
+#[allow(clippy::just_underscores_and_digits, non_upper_case_globals)]
+fn dead_key_to_accent(keyval: &RawKey) -> Option<Option<char>> {
+  // The dead keysyms are in the domain [0xfe50, 0xfe8f].
+  if !(0xfe50..=0xfe8f).contains(&**keyval) {
+    return None;
+  }
+  let accent = match *keyval {
+    dead_grave => '`',
+    dead_acute => '´',
+    dead_circumflex => '^',
+    dead_tilde => '~',
+    dead_macron => '¯',
+    dead_breve => '˘',
+    dead_abovedot => '˙',
+    dead_diaeresis => '¨',
+    dead_abovering => '˚',
+    dead_doubleacute => '˝',
+    dead_caron => 'ˇ',
+    dead_cedilla => '¸',
+    dead_ogonek => '˛',
+    _ => return Some(None),
+  };
+  Some(Some(accent))
+}
+
+/// Returns the key `code` produces without modifiers on the XKB group the user types with.
+pub(crate) fn key_for_code(display: &gdk::Display, code: KeyCode) -> Key<'static> {
+  let scancode = match code.to_scancode() {
+    Some(scancode) => scancode as u16,
+    None => return Key::Unidentified(NativeKeyCode::Unidentified),
+  };
+  let group = with_xkb_group(display, |_, _, group| group).unwrap_or(0);
+  key_for_scancode(scancode, c_int::from(group))
+}
+
+/// Returns the key with the lowest X keycode which produces `key` on the current layout.
+pub(crate) fn code_for_key(display: &gdk::Display, key: &Key<'_>) -> Option<KeyCode> {
+  if let Key::Unidentified(_) = key {
+    return None;
+  }
+  let group = with_xkb_group(display, |_, _, group| group).unwrap_or(0);
+  // X keycodes are in the domain [8, 255].
+  (8..256)
+    .find(|scancode| key_for_scancode(*scancode, c_int::from(group)) == *key)
+    .map(|scancode| KeyCode::from_scancode(u32::from(scancode)))
+}
+
+fn key_for_scancode(scancode: u16, group: c_int) -> Key<'static> {
+  match hardware_keycode_to_keyval(scancode, group) {
+    Some(keyval) => keyval_to_key(keyval, scancode),
+    None => Key::Unidentified(NativeKeyCode::Gtk(scancode)),
+  }
+}
+
+/// Returns the XKB group the user types with, named after its layout.
+pub(crate) fn keyboard_layout(display: &gdk::Display) -> KeyboardLayout {
+  let name = with_xkb_group(display, |xlib, xdisplay, group| unsafe {
+    let desc = (xlib.XkbAllocKeyboard)();
+    if desc.is_null() {
+      return None;
+    }
+    let mut name = None;
+    if (xlib.XkbGetNames)(xdisplay, XKB_GROUP_NAMES_MASK, desc) == 0 && !(*desc).names.is_null() {
+      let atom = (*(*desc).names).groups[usize::from(group)];
+      let atom_name = if atom == 0 {
+        ptr::null_mut()
+      } else {
+        (xlib.XGetAtomName)(xdisplay, atom)
+      };
+      if !atom_name.is_null() {
+        name = Some(CStr::from_ptr(atom_name).to_string_lossy().into_owned());
+        (xlib.XFree)(atom_name as *mut c_void);
+      }
+    }
+    (xlib.XkbFreeKeyboard)(desc, 0, xlib::True);
+    name
+  })
+  .flatten();
+  KeyboardLayout {
+    name: name.unwrap_or_default(),
+    language: None,
+  }
+}
+
+/// Returns the auto-repeat delay and interval of the core keyboard.
+pub(crate) fn key_repeat_settings(display: &gdk::Display) -> Option<KeyRepeatSettings> {
+  with_xkb_group(display, |xlib, xdisplay, _| unsafe {
+    let (mut delay, mut interval) = (0, 0);
+    if (xlib.XkbGetAutoRepeatRate)(xdisplay, XKB_USE_CORE_KBD, &mut delay, &mut interval) == 0 {
+      return None;
+    }
+    Some(KeyRepeatSettings {
+      delay: Duration::from_millis(u64::from(delay)),
+      interval: Duration::from_millis(u64::from(interval)),
+    })
+  })
+  .flatten()
+}
+
+/// Calls `func` with the X display of `display` and its current XKB group, on X11.
+fn with_xkb_group<R>(
+  display: &gdk::Display,
+  func: impl FnOnce(&xlib::Xlib, *mut xlib::Display, u8) -> R,
+) -> Option<R> {
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let xlib = xlib::Xlib::open().ok()?;
+  unsafe {
+    let xdisplay =
+      gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _) as *mut xlib::Display;
+    let mut state = std::mem::zeroed::<xlib::XkbStateRec>();
+    if (xlib.XkbGetState)(xdisplay, XKB_USE_CORE_KBD, &mut state) != 0 {
+      return None;
+    }
+    Some(func(&xlib, xdisplay, state.group))
+  }
+}
+
+/// Returns the keyval of `code` in the first group of the keymap, as matched by accelerators.
+pub(crate) fn keycode_to_keyval(code: KeyCode) -> Option<RawKey> {
+  let keycode = code.to_scancode()?;
+  hardware_keycode_to_keyval(keycode as u16, 0)
+}
+
 /// Map a hardware keycode to a keyval by performing a lookup in the keymap and finding the
-/// keyval with the lowest group and level
-fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
+/// keyval of `group` with the lowest level
+fn hardware_keycode_to_keyval(keycode: u16, group: c_int) -> Option<RawKey> {
   use glib::translate::FromGlib;
   unsafe {
     let keymap = gdk::ffi::gdk_keymap_get_default();
@@ -258,7 +394,7 @@ fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
       let keys_slice = slice::from_raw_parts(keys, nkeys as usize);
 
       let resolved_keyval = keys_slice.iter().enumerate().find_map(|(id, gdk_keymap)| {
-        if gdk_keymap.group == 0 && gdk_keymap.level == 0 {
+        if gdk_keymap.group == group && gdk_keymap.level == 0 {
           Some(RawKey::from_glib(keyvals_slice[id]))
         } else {
           None
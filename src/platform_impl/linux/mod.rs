@@ -10,12 +10,18 @@
   target_os = "openbsd"
 ))]
 
+mod default_context;
 mod device;
 mod event_loop;
+mod global_shortcut;
 mod icon;
 mod keyboard;
 mod keycode;
 mod monitor;
+#[cfg(feature = "notifications")]
+mod notification;
+mod osk;
+mod surface_region;
 mod util;
 mod window;
 
@@ -27,14 +33,28 @@ pub(crate) use event_loop::PlatformSpecificEventLoopAttributes;
 pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 pub use icon::PlatformIcon;
 pub use monitor::{MonitorHandle, VideoMode};
-pub use window::{Window, WindowId};
+#[cfg(feature = "notifications")]
+pub(crate) use notification::Notifier;
+pub use surface_region::SurfaceRegion;
+pub use window::{CloseToken, Window, WindowId};
 
-use crate::{event::DeviceId as RootDeviceId, keyboard::Key};
+use crate::{error::OsError as RootOsError, event::DeviceId as RootDeviceId, keyboard::Key};
+
+/// The format is the atom named after its MIME type, see `DataFormatExtUnix`, see `crate::data_format::DataFormat`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat;
+
+impl DataFormat {
+  pub fn register(_mime_type: &str) -> Result<Self, RootOsError> {
+    Ok(Self)
+  }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KeyEventExtra {
   pub text_with_all_modifiers: Option<&'static str>,
   pub key_without_modifiers: Key<'static>,
+  pub input_locale: Option<&'static str>,
 }
 
 #[non_exhaustive]
@@ -2,11 +2,30 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use gtk::gdk::{self, prelude::MonitorExt, Display};
+use std::{
+  ffi::CString,
+  os::raw::{c_char, c_int, c_long},
+  ptr, slice,
+};
+
+use gtk::{
+  gdk::{
+    self,
+    prelude::{DisplayExtManual, MonitorExt},
+    Display,
+  },
+  glib::object::ObjectType,
+};
+use x11_dl::{xlib, xrandr};
 
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  error::{ExternalError, NotSupportedError},
+  monitor::{
+    ColorInfo, IccProfile, MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode,
+  },
+  platform_impl::edid::Edid,
+  window::Rect,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -25,6 +44,11 @@ impl MonitorHandle {
     self.monitor.model().map(|s| s.as_str().to_string())
   }
 
+  #[inline]
+  pub fn is_primary(&self) -> bool {
+    self.monitor.is_primary()
+  }
+
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
     let rect = self.monitor.geometry();
@@ -45,6 +69,20 @@ impl MonitorHandle {
     .to_physical(self.scale_factor())
   }
 
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    let rect = self.monitor.workarea();
+    let scale_factor = self.scale_factor();
+    Rect {
+      position: LogicalPosition::new(rect.x(), rect.y())
+        .to_physical::<i32>(scale_factor)
+        .into(),
+      size: LogicalSize::new(rect.width() as u32, rect.height() as u32)
+        .to_physical::<u32>(scale_factor)
+        .into(),
+    }
+  }
+
   #[inline]
   pub fn scale_factor(&self) -> f64 {
     self.monitor.scale_factor() as f64
@@ -54,6 +92,195 @@ impl MonitorHandle {
   pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
     Box::new(Vec::new().into_iter())
   }
+
+  #[inline]
+  pub fn set_video_mode(&self, _video_mode: &VideoMode) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  #[inline]
+  pub fn reset_video_mode(&self) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    match self.monitor.refresh_rate() {
+      0 => None,
+      refresh_rate => Some(refresh_rate as u32),
+    }
+  }
+
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    self.output_property(b"vrr_capable\0", 1, |format, data, count| {
+      match (format, count) {
+        (32, 1) => Some(unsafe { *(data as *const c_long) } != 0),
+        _ => None,
+      }
+    })
+  }
+
+  pub fn manufacturer(&self) -> Option<String> {
+    self.edid()?.manufacturer
+  }
+
+  pub fn model(&self) -> Option<u32> {
+    self.edid().map(|edid| u32::from(edid.product_code))
+  }
+
+  pub fn serial_number(&self) -> Option<String> {
+    self.edid()?.serial_number
+  }
+
+  /// Reads the base EDID block of the RandR output the monitor is connected to, only on X11.
+  fn edid(&self) -> Option<Edid> {
+    // The length is in 32-bit units, 32 of them cover the base block.
+    self.output_property(b"EDID\0", 32, |format, data, count| match format {
+      8 => Edid::parse(unsafe { slice::from_raw_parts(data, count) }),
+      _ => None,
+    })
+  }
+
+  /// Reads the property `name` of the RandR output the monitor is connected to, only on X11.
+  /// `parse` gets the format of the items, 8, 16 or 32 bits stored in a `c_long`, and the items.
+  fn output_property<R>(
+    &self,
+    name: &[u8],
+    length: c_long,
+    parse: impl FnOnce(c_int, *const u8, usize) -> Option<R>,
+  ) -> Option<R> {
+    let display = self.monitor.display()?;
+    if !display.backend().is_x11() {
+      return None;
+    }
+    let xlib = xlib::Xlib::open().ok()?;
+    let xrandr = xrandr::Xrandr::open().ok()?;
+    unsafe {
+      let x11_display = display.as_ptr() as *mut gdk_x11_sys::GdkX11Display;
+      let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(x11_display) as *mut xlib::Display;
+      let output = gdk_x11_sys::gdk_x11_monitor_get_output(self.monitor.as_ptr());
+      let atom = (xlib.XInternAtom)(xdisplay, name.as_ptr() as *const c_char, xlib::True);
+      if atom == 0 {
+        return None;
+      }
+
+      let mut actual_type = 0;
+      let mut actual_format = 0;
+      let mut item_count = 0;
+      let mut bytes_after = 0;
+      let mut data = ptr::null_mut();
+      let status = (xrandr.XRRGetOutputProperty)(
+        xdisplay,
+        output,
+        atom,
+        0,
+        length,
+        xlib::False,
+        xlib::False,
+        xlib::AnyPropertyType as _,
+        &mut actual_type,
+        &mut actual_format,
+        &mut item_count,
+        &mut bytes_after,
+        &mut data,
+      );
+      if status != xlib::Success as c_int || data.is_null() {
+        return None;
+      }
+      let value = parse(actual_format, data, item_count as usize);
+      (xlib.XFree)(data as *mut _);
+      value
+    }
+  }
+
+  #[inline]
+  pub fn color_info(&self) -> ColorInfo {
+    let mut info = ColorInfo::default();
+    // GDK has a single visual for all the monitors of a screen.
+    if let Some(visual) = self
+      .monitor
+      .display()
+      .and_then(|display| display.default_screen().system_visual())
+    {
+      let (_, _, precision) = visual.red_pixel_details();
+      if precision > 0 {
+        info.bits_per_color = Some(precision as u32);
+      }
+    }
+    info
+  }
+
+  /// Reads the `_ICC_PROFILE` property of the root window for the monitor, only on X11. See the
+  /// ICC Profiles in X Specification.
+  pub fn icc_profile(&self) -> Option<IccProfile> {
+    let display = self.monitor.display()?;
+    if !display.backend().is_x11() {
+      return None;
+    }
+    let index =
+      (0..display.n_monitors()).find(|i| display.monitor(*i).as_ref() == Some(&self.monitor))?;
+    let name = CString::new(icc_profile_atom_name(index)).ok()?;
+    let xlib = xlib::Xlib::open().ok()?;
+    unsafe {
+      let x11_display = display.as_ptr() as *mut gdk_x11_sys::GdkX11Display;
+      let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(x11_display) as *mut xlib::Display;
+      let atom = (xlib.XInternAtom)(xdisplay, name.as_ptr(), xlib::True);
+      if atom == 0 {
+        return None;
+      }
+
+      let mut actual_type = 0;
+      let mut actual_format = 0;
+      let mut item_count = 0;
+      let mut bytes_after = 0;
+      let mut data = ptr::null_mut();
+      let status = (xlib.XGetWindowProperty)(
+        xdisplay,
+        (xlib.XDefaultRootWindow)(xdisplay),
+        atom,
+        0,
+        // In 32-bit units, enough for any profile.
+        c_long::from(i32::MAX) / 4,
+        xlib::False,
+        xlib::AnyPropertyType as _,
+        &mut actual_type,
+        &mut actual_format,
+        &mut item_count,
+        &mut bytes_after,
+        &mut data,
+      );
+      if status != xlib::Success as c_int || data.is_null() {
+        return None;
+      }
+      let profile = match (actual_format, item_count) {
+        (8, count) if count > 0 => Some(IccProfile::Data(
+          slice::from_raw_parts(data, count as usize).to_vec(),
+        )),
+        _ => None,
+      };
+      (xlib.XFree)(data as *mut _);
+      profile
+    }
+  }
+}
+
+/// Name of the root window property holding the ICC profile of the monitor `index`.
+fn icc_profile_atom_name(index: i32) -> String {
+  match index {
+    0 => "_ICC_PROFILE".into(),
+    index => format!("_ICC_PROFILE_{}", index),
+  }
+}
+
+/// Index of the monitor whose ICC profile is held by the root window property `name`, if it's one
+/// of them.
+pub fn icc_profile_index(name: &[u8]) -> Option<i32> {
+  match name.strip_prefix(b"_ICC_PROFILE")? {
+    b"" => Some(0),
+    suffix => std::str::from_utf8(suffix.strip_prefix(b"_")?)
+      .ok()?
+      .parse()
+      .ok(),
+  }
 }
 
 unsafe impl Send for MonitorHandle {}
@@ -0,0 +1,145 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notifications of the `org.freedesktop.Notifications` D-Bus service, see `Notifier::show`.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use gio::Cancellable;
+use gtk::{gio, glib, prelude::*};
+
+use super::{EventLoopWindowTarget, OsError};
+use crate::{
+  error::ExternalError,
+  notification::{ActivationHandler, Notification, NotificationId},
+};
+
+const NOTIFICATIONS: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+/// Key of the action invoked by clicking the notification itself.
+const DEFAULT_ACTION: &str = "default";
+
+pub struct Notifier {
+  /// `None` when the session bus is unavailable.
+  bus: Option<gio::DBusConnection>,
+  subscriptions: Vec<gio::SignalSubscriptionId>,
+  /// The id of each notification which is shown, by the id the server gave it.
+  ids: Arc<Mutex<HashMap<u32, NotificationId>>>,
+}
+
+impl Notifier {
+  pub fn new<T>(_target: &EventLoopWindowTarget<T>, on_activated: ActivationHandler) -> Self {
+    let ids: Arc<Mutex<HashMap<u32, NotificationId>>> = Default::default();
+    let bus = match gio::bus_get_sync(gio::BusType::Session, None::<&Cancellable>) {
+      Ok(bus) => bus,
+      Err(e) => {
+        log::warn!(
+          "Failed to connect to the session bus, notifications won't be shown: {}",
+          e
+        );
+        return Self {
+          bus: None,
+          subscriptions: Vec::new(),
+          ids,
+        };
+      }
+    };
+
+    // The signals are dispatched by the main context of the event loop, which created the notifier.
+    let ids_ = ids.clone();
+    let action_invoked = bus.signal_subscribe(
+      Some(NOTIFICATIONS),
+      Some(NOTIFICATIONS),
+      Some("ActionInvoked"),
+      Some(NOTIFICATIONS_PATH),
+      None,
+      gio::DBusSignalFlags::NONE,
+      move |_, _, _, _, _, parameters| {
+        if let Some((server_id, action)) = parameters.get::<(u32, String)>() {
+          if action != DEFAULT_ACTION {
+            return;
+          }
+          let id = ids_.lock().unwrap().remove(&server_id);
+          if let Some(id) = id {
+            on_activated(id);
+          }
+        }
+      },
+    );
+    let ids_ = ids.clone();
+    let closed = bus.signal_subscribe(
+      Some(NOTIFICATIONS),
+      Some(NOTIFICATIONS),
+      Some("NotificationClosed"),
+      Some(NOTIFICATIONS_PATH),
+      None,
+      gio::DBusSignalFlags::NONE,
+      move |_, _, _, _, _, parameters| {
+        if let Some((server_id, _reason)) = parameters.get::<(u32, u32)>() {
+          ids_.lock().unwrap().remove(&server_id);
+        }
+      },
+    );
+
+    Self {
+      bus: Some(bus),
+      subscriptions: vec![action_invoked, closed],
+      ids,
+    }
+  }
+
+  pub fn show(&self, id: NotificationId, notification: &Notification) -> Result<(), ExternalError> {
+    let bus = self
+      .bus
+      .as_ref()
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))?;
+    let app_name = glib::application_name()
+      .map(|name| name.to_string())
+      .unwrap_or_default();
+    let parameters = (
+      app_name,
+      0u32,
+      "",
+      notification.title.as_str(),
+      notification.body.as_str(),
+      vec![DEFAULT_ACTION, ""],
+      HashMap::<String, glib::Variant>::new(),
+      -1i32,
+    )
+      .to_variant();
+    let reply = bus
+      .call_sync(
+        Some(NOTIFICATIONS),
+        NOTIFICATIONS_PATH,
+        NOTIFICATIONS,
+        "Notify",
+        Some(&parameters),
+        glib::VariantTy::new("(u)").ok(),
+        gio::DBusCallFlags::NONE,
+        -1,
+        None::<&Cancellable>,
+      )
+      .map_err(|e| {
+        log::warn!("Failed to show a notification: {}", e);
+        ExternalError::Os(os_error!(OsError))
+      })?;
+    if let Some((server_id,)) = reply.get::<(u32,)>() {
+      self.ids.lock().unwrap().insert(server_id, id);
+    }
+    Ok(())
+  }
+}
+
+impl Drop for Notifier {
+  fn drop(&mut self) {
+    if let Some(bus) = &self.bus {
+      for subscription in self.subscriptions.drain(..) {
+        bus.signal_unsubscribe(subscription);
+      }
+    }
+  }
+}
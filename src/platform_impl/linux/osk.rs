@@ -0,0 +1,20 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-screen keyboard control through the `sm.puri.OSK0` D-Bus interface, as implemented by
+//! squeekboard.
+
+use zbus::{blocking::Connection, Result};
+
+pub fn set_visible(visible: bool) -> Result<()> {
+  let conn = Connection::session()?;
+  conn.call_method(
+    Some("sm.puri.OSK0"),
+    "/sm/puri/OSK0",
+    Some("sm.puri.OSK0"),
+    "SetVisible",
+    &(visible,),
+  )?;
+  Ok(())
+}
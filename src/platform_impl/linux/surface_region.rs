@@ -0,0 +1,222 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Child `GdkWindow`s reserved for embedders, see `Window::create_surface_region`.
+//!
+//! A `SurfaceRegion` can be used from any thread, so it only holds the rect and the handle of its
+//! surface, while the `GdkWindow` lives on the GTK thread and is driven through
+//! `WindowRequest::SurfaceRegion`.
+//!
+//! GDK coordinates are in application pixels, which already follow the scale factor, so only
+//! rects given in physical pixels are converted, with the scale factor of the moment. The rect is
+//! applied again when it changes, for physical rects to keep their size in physical pixels.
+
+use std::sync::{
+  atomic::{AtomicBool, AtomicU64, Ordering},
+  Arc,
+};
+
+use gtk::{gdk, glib, prelude::*};
+use parking_lot::Mutex;
+
+use crate::{error::ExternalError, window::Rect};
+
+use super::{
+  window::{WindowId, WindowRequest},
+  Window,
+};
+
+/// State shared by a region and its surface.
+pub struct SurfaceRegionState {
+  rect: Mutex<Rect>,
+  /// X11 window of the surface, `0` until the surface is created.
+  xid: AtomicU64,
+  wayland: AtomicBool,
+}
+
+pub enum SurfaceRegionRequest {
+  Create(Arc<SurfaceRegionState>, Option<gtk::Box>),
+  /// Applies the rect of the state.
+  Rect,
+  Visible(bool),
+  Destroy,
+}
+
+pub struct SurfaceRegion {
+  id: u64,
+  window_id: WindowId,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  state: Arc<SurfaceRegionState>,
+}
+
+impl SurfaceRegion {
+  pub fn new(window: &Window, rect: Rect) -> Result<Self, ExternalError> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let region = Self {
+      id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+      window_id: window.window_id,
+      window_requests_tx: window.window_requests_tx.clone(),
+      state: Arc::new(SurfaceRegionState {
+        rect: Mutex::new(rect),
+        xid: AtomicU64::new(0),
+        wayland: AtomicBool::new(false),
+      }),
+    };
+    region.send(SurfaceRegionRequest::Create(
+      region.state.clone(),
+      window.default_vbox.clone(),
+    ));
+    Ok(region)
+  }
+
+  fn send(&self, request: SurfaceRegionRequest) {
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::SurfaceRegion(self.id, request),
+    )) {
+      log::warn!("Fail to send surface region request: {}", e);
+    }
+  }
+
+  pub fn set_rect(&self, rect: Rect) {
+    *self.state.rect.lock() = rect;
+    self.send(SurfaceRegionRequest::Rect);
+  }
+
+  pub fn rect(&self) -> Rect {
+    *self.state.rect.lock()
+  }
+
+  pub fn set_visible(&self, visible: bool) {
+    self.send(SurfaceRegionRequest::Visible(visible));
+  }
+
+  #[cfg(feature = "rwh_06")]
+  pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+    if self.state.wayland.load(Ordering::Acquire) {
+      // Child windows are drawn into their toplevel's surface on Wayland.
+      return Err(rwh_06::HandleError::NotSupported);
+    }
+    match self.state.xid.load(Ordering::Acquire) {
+      // The event loop hasn't created the surface yet.
+      0 => Err(rwh_06::HandleError::Unavailable),
+      xid => Ok(rwh_06::RawWindowHandle::Xlib(
+        rwh_06::XlibWindowHandle::new(xid as _),
+      )),
+    }
+  }
+}
+
+impl Drop for SurfaceRegion {
+  fn drop(&mut self) {
+    self.send(SurfaceRegionRequest::Destroy);
+  }
+}
+
+/// The `GdkWindow` of a region, on the GTK thread.
+pub struct Surface {
+  gdk_window: gdk::Window,
+  window: gtk::ApplicationWindow,
+  default_vbox: Option<gtk::Box>,
+  state: Arc<SurfaceRegionState>,
+  scale_factor_handler: Option<glib::SignalHandlerId>,
+}
+
+impl Surface {
+  pub fn new(
+    window: &gtk::ApplicationWindow,
+    state: Arc<SurfaceRegionState>,
+    default_vbox: Option<gtk::Box>,
+  ) -> Option<Self> {
+    window.realize();
+    let parent = window.window()?;
+    let gdk_window = gdk::Window::new(
+      Some(&parent),
+      &gdk::WindowAttr {
+        window_type: gdk::WindowType::Child,
+        wclass: gdk::WindowWindowClass::InputOutput,
+        ..Default::default()
+      },
+    );
+    // Embedders need a surface of their own to render into.
+    gdk_window.ensure_native();
+    gdk_window.show();
+    gdk_window.lower();
+
+    if gdk_window.display().backend().is_wayland() {
+      state.wayland.store(true, Ordering::Release);
+    } else {
+      let xid = unsafe { gdk_x11_sys::gdk_x11_window_get_xid(gdk_window.as_ptr() as *mut _) };
+      state.xid.store(xid as u64, Ordering::Release);
+    }
+
+    let scale_factor_handler = {
+      let gdk_window = gdk_window.clone();
+      let default_vbox = default_vbox.clone();
+      let state = state.clone();
+      window.connect_scale_factor_notify(move |window| {
+        let rect = *state.rect.lock();
+        apply_rect(&gdk_window, window, default_vbox.as_ref(), rect);
+      })
+    };
+
+    let surface = Self {
+      gdk_window,
+      window: window.clone(),
+      default_vbox,
+      state,
+      scale_factor_handler: Some(scale_factor_handler),
+    };
+    surface.update_rect();
+    Some(surface)
+  }
+
+  pub fn update_rect(&self) {
+    let rect = *self.state.rect.lock();
+    apply_rect(
+      &self.gdk_window,
+      &self.window,
+      self.default_vbox.as_ref(),
+      rect,
+    );
+  }
+
+  pub fn set_visible(&self, visible: bool) {
+    if visible {
+      self.gdk_window.show();
+      self.gdk_window.lower();
+    } else {
+      self.gdk_window.hide();
+    }
+  }
+}
+
+impl Drop for Surface {
+  fn drop(&mut self) {
+    if let Some(handler) = self.scale_factor_handler.take() {
+      self.window.disconnect(handler);
+    }
+    self.gdk_window.destroy();
+  }
+}
+
+fn apply_rect(
+  gdk_window: &gdk::Window,
+  window: &gtk::ApplicationWindow,
+  default_vbox: Option<&gtk::Box>,
+  rect: Rect,
+) {
+  let scale_factor = window.scale_factor() as f64;
+  let position = rect.position.to_logical::<i32>(scale_factor);
+  let size = rect.size.to_logical::<i32>(scale_factor);
+  // The client area starts at the content box, past the client-side decorations.
+  let (x, y) = default_vbox
+    .map(|vbox| {
+      let allocation = vbox.allocation();
+      (allocation.x(), allocation.y())
+    })
+    .unwrap_or_default();
+  gdk_window.move_resize(x + position.x, y + position.y, size.width, size.height);
+}
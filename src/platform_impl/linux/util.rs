@@ -1,16 +1,104 @@
+use std::cell::Cell;
+
 use gtk::gdk::{
   self,
-  prelude::{DeviceExt, SeatExt},
+  prelude::{DeviceExt, DisplayExtManual, SeatExt},
   Display,
 };
-use gtk::traits::{GtkWindowExt, WidgetExt};
+use gtk::{
+  cairo,
+  glib::object::ObjectType,
+  prelude::{GtkSettingsExt, StyleContextExt},
+  traits::{GtkWindowExt, WidgetExt},
+};
 
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
   error::ExternalError,
-  window::WindowSizeConstraints,
+  event::MouseButton,
+  icon::CustomCursor,
+  window::{SystemAppearance, WindowSizeConstraints},
 };
 
+/// Maps an X11 button number to a `MouseButton`, or `None` for buttons 4 to 7, which are the
+/// wheel and are reported as scroll events.
+pub fn mouse_button(button: u32) -> Option<MouseButton> {
+  match button {
+    1 => Some(MouseButton::Left),
+    2 => Some(MouseButton::Middle),
+    3 => Some(MouseButton::Right),
+    8 => Some(MouseButton::Back),
+    9 => Some(MouseButton::Forward),
+    // Starts at `Other(5)`, after the indices of the named buttons.
+    10.. => Some(MouseButton::Other((button - 5).min(u16::MAX as u32) as u16)),
+    _ => None,
+  }
+}
+
+/// Last press of a mouse button, see `click_count`.
+#[derive(Clone, Copy)]
+pub struct Click {
+  button: u32,
+  time: u32,
+  position: (f64, f64),
+  count: u32,
+}
+
+/// Returns how many times in a row the button of `event` was pressed within the double-click time
+/// and distance of the GTK settings, this press included. A release gets the count of its press.
+pub fn click_count(last_click: &Cell<Option<Click>>, event: &gdk::EventButton) -> u32 {
+  let button = event.button();
+  let last = last_click.get().filter(|click| click.button == button);
+  if event.event_type() == gdk::EventType::ButtonRelease {
+    return last.map_or(1, |click| click.count);
+  }
+
+  let (interval, distance) = gtk::Settings::default().map_or((400, 5), |settings| {
+    (
+      settings.gtk_double_click_time(),
+      settings.gtk_double_click_distance(),
+    )
+  });
+  let time = event.time();
+  let position = event.root();
+  let count = match last {
+    Some(click)
+      if time.wrapping_sub(click.time) <= interval as u32
+        && (position.0 - click.position.0).abs() <= distance as f64
+        && (position.1 - click.position.1).abs() <= distance as f64 =>
+    {
+      click.count + 1
+    }
+    _ => 1,
+  };
+  last_click.set(Some(Click {
+    button,
+    time,
+    position,
+    count,
+  }));
+  count
+}
+
+/// Returns the number of buttons of the core pointer, not counting the wheel, or `None` if it
+/// can't be queried.
+pub fn mouse_button_count(display: &Display) -> Option<u16> {
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let xlib = x11_dl::xlib::Xlib::open().ok()?;
+  let count = unsafe {
+    let x11_display = display.as_ptr() as *mut gdk_x11_sys::GdkX11Display;
+    let xdisplay =
+      gdk_x11_sys::gdk_x11_display_get_xdisplay(x11_display) as *mut x11_dl::xlib::Display;
+    (xlib.XGetPointerMapping)(xdisplay, std::ptr::null_mut(), 0)
+  };
+  match count {
+    count if count <= 0 => None,
+    count => Some((count.min(3) + (count - 7).max(0)) as u16),
+  }
+}
+
 #[inline]
 pub fn cursor_position(is_wayland: bool) -> Result<PhysicalPosition<f64>, ExternalError> {
   if is_wayland {
@@ -79,3 +167,85 @@ pub fn is_unity() -> bool {
     })
     .unwrap_or(false)
 }
+
+/// Reads the appearance preferences from the GTK settings and theme of `display`.
+pub fn system_appearance(display: &Display) -> SystemAppearance {
+  let settings = gtk::Settings::for_screen(&display.default_screen());
+  let high_contrast = settings
+    .as_ref()
+    .and_then(|settings| settings.gtk_theme_name())
+    .map_or(false, |theme| theme.contains("HighContrast"));
+  let reduce_motion = settings
+    .as_ref()
+    .map_or(false, |settings| !settings.is_gtk_enable_animations());
+
+  let style_context = gtk::StyleContext::new();
+  style_context.set_screen(&display.default_screen());
+  let accent_color = style_context
+    .lookup_color("theme_selected_bg_color")
+    .map(|color| {
+      [color.red(), color.green(), color.blue(), color.alpha()]
+        .map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8)
+    });
+
+  SystemAppearance {
+    accent_color,
+    high_contrast,
+    reduce_motion,
+    reduce_transparency: false,
+  }
+}
+
+/// Ratio between the user's cursor size setting and the usual default cursor size of 24.
+pub fn cursor_size_scale<W: WidgetExt>(window: &W) -> f64 {
+  let size = window
+    .settings()
+    .map(|settings| settings.gtk_cursor_theme_size())
+    .filter(|size| *size > 0)
+    .map(|size| size as u32)
+    .unwrap_or_else(|| window.display().default_cursor_size());
+  size as f64 / 24.0
+}
+
+/// Creates a GDK cursor from the image of `cursor` best matching the window's scale factor and
+/// the cursor size setting.
+pub fn create_custom_cursor<W: WidgetExt>(
+  window: &W,
+  cursor: &CustomCursor,
+) -> Option<gdk::Cursor> {
+  let size_scale = cursor_size_scale(window);
+  let image = cursor.image_for_scale(window.scale_factor() as f64 * size_scale);
+  let width = image.rgba.width as i32;
+  let height = image.rgba.height as i32;
+
+  // Cairo expects premultiplied ARGB in native endianness.
+  let data: Vec<u8> = image
+    .rgba
+    .rgba
+    .chunks_exact(4)
+    .flat_map(|p| {
+      let premultiply = |c: u8| (c as u32 * p[3] as u32 / 255) as u8;
+      u32::from_be_bytes([
+        p[3],
+        premultiply(p[0]),
+        premultiply(p[1]),
+        premultiply(p[2]),
+      ])
+      .to_ne_bytes()
+    })
+    .collect();
+  let surface =
+    cairo::ImageSurface::create_for_data(data, cairo::Format::ARgb32, width, height, width * 4)
+      .ok()?;
+
+  // Shown at the size of the base image times the cursor size setting.
+  let device_scale = image.rgba.width as f64 / (cursor.base_width() as f64 * size_scale);
+  surface.set_device_scale(device_scale, device_scale);
+
+  Some(gdk::Cursor::from_surface(
+    &window.display(),
+    &surface,
+    image.hotspot_x as f64 / device_scale,
+    image.hotspot_y as f64 / device_scale,
+  ))
+}
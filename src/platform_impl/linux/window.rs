@@ -2,37 +2,51 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use parking_lot::Mutex;
 use std::{
   cell::RefCell,
   collections::VecDeque,
   rc::Rc,
   sync::{
-    atomic::{AtomicBool, AtomicI32, Ordering},
+    atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering},
     Arc,
   },
+  time::Duration,
 };
 
 use gtk::{
-  gdk::WindowState,
-  glib::{self, translate::ToGlibPtr},
+  gdk::{self, WindowState},
+  glib::{
+    self,
+    translate::{IntoGlib, ToGlibPtr},
+  },
 };
 use gtk::{prelude::*, Settings};
 
 use crate::{
-  dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+  dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Rounding, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
-  icon::Icon,
+  event::{Event, WindowEvent},
+  event_loop::ControlFlow,
+  global_shortcut::{Accelerator, AcceleratorId},
+  icon::{CustomCursor, Icon},
+  keyboard::ModifiersState,
   monitor::MonitorHandle as RootMonitorHandle,
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, UserAttentionType,
-    WindowAttributes, WindowSizeConstraints,
+    AccelerationProfile, AttributeDowngrade, AttributesReport, CursorGrabMode, CursorIcon,
+    DropPolicy, FocusPolicy, Fullscreen, ImePurpose, ProgressBarState, Rect, ResizeDirection,
+    SystemGestures, Theme, TitleBarStyle, UserAttentionType, WindowAttributes,
+    WindowId as RootWindowId, WindowSizeConstraints,
   },
 };
 
 use super::{
-  event_loop::EventLoopWindowTarget,
+  event_loop::{self, EventLoopWindowTarget},
+  keyboard,
   monitor::{self, MonitorHandle},
-  util, Parent, PlatformSpecificWindowBuilderAttributes,
+  osk,
+  surface_region::{SurfaceRegion, SurfaceRegionRequest},
+  util, OsError, Parent, PlatformSpecificWindowBuilderAttributes,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -56,16 +70,34 @@ pub struct Window {
   pub(crate) default_vbox: Option<gtk::Box>,
   /// Window requests sender
   pub(crate) window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  /// Title set by `set_title` which its `WindowRequest::Title` hasn't applied yet.
+  pending_title: Arc<Mutex<Option<String>>>,
   scale_factor: Rc<AtomicI32>,
   position: Rc<(AtomicI32, AtomicI32)>,
   size: Rc<(AtomicI32, AtomicI32)>,
   maximized: Rc<AtomicBool>,
   minimized: Rc<AtomicBool>,
-  fullscreen: RefCell<Option<Fullscreen>>,
+  intercept_minimize: Arc<AtomicBool>,
+  /// Set by `set_minimized` so the next iconify isn't intercepted.
+  allow_minimize: Arc<AtomicBool>,
+  fullscreen: Arc<Mutex<Option<Fullscreen>>>,
+  /// Re-applied when the scale factor or the cursor size setting changes.
+  custom_cursor: Arc<Mutex<Option<CustomCursor>>>,
   inner_size_constraints: RefCell<WindowSizeConstraints>,
   /// Draw event Sender
   draw_tx: crossbeam_channel::Sender<WindowId>,
+  frame_pacing: AtomicBool,
+  /// Restored when the icon frames stop cycling.
+  window_icon: Mutex<Option<Icon>>,
+  /// Generation of the icon frames animation, bumped to stop the timer of the previous one.
+  icon_animation: Arc<AtomicU32>,
+  /// Generation of the animated cursor, bumped each time the cursor changes.
+  cursor_animation: Arc<AtomicU32>,
   preferred_theme: Option<Theme>,
+  /// The options which couldn't be honored when creating the window.
+  attributes_report: AttributesReport,
+  /// Set by `detach`, the native window then outlives the `Window`.
+  detached: bool,
 }
 
 impl Window {
@@ -85,13 +117,17 @@ impl Window {
     event_loop_window_target
       .windows
       .borrow_mut()
-      .insert(window_id);
+      .insert(window_id, window.clone());
 
     // Set Width/Height & Resizable
     let win_scale_factor = window.scale_factor();
     let (width, height) = attributes
       .inner_size
-      .map(|size| size.to_logical::<f64>(win_scale_factor as f64).into())
+      .map(|size| {
+        size
+          .to_logical_with::<i32>(win_scale_factor as f64, Rounding::Ceil)
+          .into()
+      })
       .unwrap_or((800, 600));
     window.set_default_size(1, 1);
     window.resize(width, height);
@@ -112,6 +148,8 @@ impl Window {
       window.move_(x, y);
     }
 
+    let mut attributes_report = AttributesReport::default();
+
     // Set GDK Visual
     if pl_attribs.rgba_visual || attributes.transparent {
       if let Some(screen) = GtkWindowExt::screen(&window) {
@@ -120,6 +158,16 @@ impl Window {
         }
       }
     }
+    if attributes.transparent {
+      let composited = GtkWindowExt::screen(&window).map_or(false, |screen| {
+        screen.is_composited() && screen.rgba_visual().is_some()
+      });
+      if !composited {
+        attributes_report
+          .downgrades
+          .push(AttributeDowngrade::Transparency);
+      }
+    }
 
     if pl_attribs.app_paintable || attributes.transparent {
       // Set a few attributes to make the window can be painted.
@@ -147,6 +195,11 @@ impl Window {
 
     // Rest attributes
     window.set_title(&attributes.title);
+    if let Some(Fullscreen::Exclusive(_)) = &attributes.fullscreen {
+      attributes_report
+        .downgrades
+        .push(AttributeDowngrade::ExclusiveFullscreen);
+    }
     if let Some(Fullscreen::Borderless(m)) = &attributes.fullscreen {
       if let Some(monitor) = m {
         let display = window.display();
@@ -166,6 +219,17 @@ impl Window {
     window.set_visible(attributes.visible);
     window.set_decorated(attributes.decorations);
 
+    if matches!(
+      attributes.title_bar_style,
+      TitleBarStyle::Overlay | TitleBarStyle::Hidden
+    ) {
+      // An empty header bar replaces the title bar, keeping the borders and shadows of the
+      // client-side decorations.
+      let titlebar = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+      titlebar.set_no_show_all(true);
+      window.set_titlebar(Some(&titlebar));
+    }
+
     if attributes.always_on_bottom {
       window.set_keep_below(attributes.always_on_bottom);
     }
@@ -178,8 +242,8 @@ impl Window {
       window.stick();
     }
 
-    if let Some(icon) = attributes.window_icon {
-      window.set_icon(Some(&icon.inner.into()));
+    if let Some(icon) = attributes.window_icon.clone() {
+      window.set_icon_list(&icon.inner.into_pixbufs());
     }
 
     let preferred_theme = if let Some(settings) = Settings::default() {
@@ -264,46 +328,112 @@ impl Window {
       glib::Propagation::Proceed
     });
 
+    let custom_cursor: Arc<Mutex<Option<CustomCursor>>> = Default::default();
+    let refresh_custom_cursor = {
+      let custom_cursor = custom_cursor.clone();
+      let window_requests_tx = window_requests_tx.clone();
+      move || {
+        let cursor = custom_cursor.lock().clone();
+        if let Some(cursor) = cursor {
+          if let Err(e) = window_requests_tx.send((window_id, WindowRequest::CustomCursor(cursor)))
+          {
+            log::warn!("Fail to send custom cursor request: {}", e);
+          }
+        }
+      }
+    };
+
     let scale_factor: Rc<AtomicI32> = Rc::new(win_scale_factor.into());
     let scale_factor_clone = scale_factor.clone();
+    let refresh = refresh_custom_cursor.clone();
     window.connect_scale_factor_notify(move |window| {
       scale_factor_clone.store(window.scale_factor(), Ordering::Release);
+      refresh();
     });
 
+    if let Some(settings) = Settings::default() {
+      let refresh = refresh_custom_cursor.clone();
+      let handler = settings.connect_gtk_cursor_theme_size_notify(move |_| refresh());
+      let handler = RefCell::new(Some(handler));
+      window.connect_destroy(move |_| {
+        if let Some(handler) = handler.take() {
+          settings.disconnect(handler);
+        }
+      });
+    }
+
     // Check if we should paint the transparent background ourselves.
     let mut transparent = false;
     if attributes.transparent && pl_attribs.auto_transparent {
       transparent = true;
     }
+    let fullscreen = Arc::new(Mutex::new(attributes.fullscreen.clone()));
     let cursor_moved = pl_attribs.cursor_moved;
+    let intercept_minimize = Arc::new(AtomicBool::new(attributes.intercept_minimize));
+    let allow_minimize = Arc::new(AtomicBool::new(false));
     if let Err(e) = window_requests_tx.send((
       window_id,
       WindowRequest::WireUpEvents {
         transparent,
         cursor_moved,
+        intercept_minimize: intercept_minimize.clone(),
+        allow_minimize: allow_minimize.clone(),
+        fullscreen: fullscreen.clone(),
       },
     )) {
       log::warn!("Fail to send wire up events request: {}", e);
     }
 
+    // Spanning needs the GDK window, which doesn't exist yet.
+    if let Some(fullscreen @ Fullscreen::Spanning(_)) = &attributes.fullscreen {
+      if let Err(e) = window_requests_tx.send((
+        window_id,
+        WindowRequest::Fullscreen(Some(fullscreen.clone())),
+      )) {
+        log::warn!("Fail to send fullscreen request: {}", e);
+      }
+    }
+
     if let Err(e) = draw_tx.send(window_id) {
       log::warn!("Failed to send redraw event to event channel: {}", e);
     }
 
+    if !attributes_report.is_empty() {
+      if let Err(e) = event_loop_window_target.event_tx.send(Event::WindowEvent {
+        window_id: RootWindowId(window_id),
+        event: WindowEvent::AttributesDowngraded(attributes_report.clone()),
+      }) {
+        log::warn!(
+          "Failed to send attributes downgraded event to event channel: {}",
+          e
+        );
+      }
+    }
+
     let win = Self {
       window_id,
       window,
       default_vbox,
       window_requests_tx,
+      pending_title: Default::default(),
       draw_tx,
+      frame_pacing: AtomicBool::new(false),
       scale_factor,
       position,
       size,
       maximized,
       minimized,
-      fullscreen: RefCell::new(attributes.fullscreen),
+      intercept_minimize,
+      allow_minimize,
+      fullscreen,
+      custom_cursor,
       inner_size_constraints: RefCell::new(attributes.inner_size_constraints),
+      window_icon: Mutex::new(attributes.window_icon),
+      icon_animation: Arc::new(AtomicU32::new(0)),
+      cursor_animation: Arc::new(AtomicU32::new(0)),
       preferred_theme,
+      attributes_report,
+      detached: false,
     };
 
     win.set_skip_taskbar(pl_attribs.skip_taskbar);
@@ -319,12 +449,77 @@ impl Window {
     self.scale_factor.load(Ordering::Acquire) as f64
   }
 
+  pub fn set_scale_factor_override(&self, _scale_factor: Option<f64>) {}
+
   pub fn request_redraw(&self) {
+    // Let the frame clock trigger the redraw from the `draw` signal.
+    if self.frame_pacing.load(Ordering::Acquire) {
+      if let Err(e) = self
+        .window_requests_tx
+        .send((self.window_id, WindowRequest::QueueDraw))
+      {
+        log::warn!("Fail to send queue draw request: {}", e);
+      }
+      return;
+    }
     if let Err(e) = self.draw_tx.send(self.window_id) {
       log::warn!("Failed to send redraw event to event channel: {}", e);
     }
   }
 
+  pub fn set_frame_pacing(&self, enabled: bool) {
+    self.frame_pacing.store(enabled, Ordering::Release);
+  }
+
+  pub fn set_jank_detection(&self, enabled: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::JankDetection(enabled)))
+    {
+      log::warn!("Fail to send jank detection request: {}", e);
+    }
+  }
+
+  pub fn applied_attributes_report(&self) -> AttributesReport {
+    self.attributes_report.clone()
+  }
+
+  pub fn run_modal(&self, handler: &mut dyn FnMut(&mut ControlFlow)) {
+    // The event loop made its context the thread default while it runs.
+    let context = glib::MainContext::ref_thread_default();
+    let mut control_flow = ControlFlow::Wait;
+    loop {
+      handler(&mut control_flow);
+      match control_flow {
+        ControlFlow::ExitWithCode(_) => break,
+        ControlFlow::Poll => {
+          context.iteration(false);
+        }
+        control_flow => event_loop::wait(&context, control_flow, None),
+      }
+    }
+  }
+
+  pub fn close(&self) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Close))
+    {
+      log::warn!("Fail to send close request: {}", e);
+    }
+  }
+
+  pub fn destroy(&self) {
+    self.close_token().proceed()
+  }
+
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken {
+      window_id: self.window_id,
+      window_requests_tx: self.window_requests_tx.clone(),
+    }
+  }
+
   pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
     let (x, y) = &*self.position;
     Ok(
@@ -366,7 +561,11 @@ impl Window {
   }
 
   pub fn set_inner_size<S: Into<Size>>(&self, size: S) {
-    let (width, height) = size.into().to_logical::<i32>(self.scale_factor()).into();
+    // Rounding up keeps the physical size covered, see `Rounding`.
+    let (width, height) = size
+      .into()
+      .to_logical_with::<i32>(self.scale_factor(), Rounding::Ceil)
+      .into();
 
     if let Err(e) = self
       .window_requests_tx
@@ -415,15 +614,22 @@ impl Window {
   }
 
   pub fn set_title(&self, title: &str) {
-    if let Err(e) = self
-      .window_requests_tx
-      .send((self.window_id, WindowRequest::Title(title.to_string())))
-    {
+    let pending = self.pending_title.lock().replace(title.to_string());
+    if pending.is_some() {
+      return;
+    }
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::Title(self.pending_title.clone()),
+    )) {
       log::warn!("Fail to send title request: {}", e);
     }
   }
 
   pub fn title(&self) -> String {
+    if let Some(title) = &*self.pending_title.lock() {
+      return title.clone();
+    }
     self
       .window
       .title()
@@ -455,6 +661,34 @@ impl Window {
     self.window.is_active()
   }
 
+  pub fn raise(&self) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Raise))
+    {
+      log::warn!("Fail to send raise request: {}", e);
+    }
+  }
+
+  pub fn focus_without_raise(&self) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::FocusWithoutRaise))
+    {
+      log::warn!("Fail to send focus request: {}", e);
+    }
+  }
+
+  pub fn set_focus_policy(&self, policy: FocusPolicy) {
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::AcceptFocus(policy != FocusPolicy::Never),
+    )) {
+      log::warn!("Fail to send focus policy request: {}", e);
+    }
+  }
+  pub fn set_drop_policy(&self, _policy: DropPolicy) {}
+
   pub fn set_resizable(&self, resizable: bool) {
     if let Err(e) = self
       .window_requests_tx
@@ -464,6 +698,15 @@ impl Window {
     }
   }
 
+  pub fn set_enabled(&self, enabled: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Enabled(enabled)))
+    {
+      log::warn!("Fail to send enabled request: {}", e);
+    }
+  }
+
   pub fn set_minimizable(&self, _minimizable: bool) {}
 
   pub fn set_maximizable(&self, _maximizable: bool) {}
@@ -477,7 +720,16 @@ impl Window {
     }
   }
 
+  pub fn set_intercept_minimize(&self, intercept: bool) {
+    self.intercept_minimize.store(intercept, Ordering::Release);
+  }
+
+  pub fn set_system_gestures_enabled(&self, _gestures: SystemGestures) {}
+
   pub fn set_minimized(&self, minimized: bool) {
+    if minimized {
+      self.allow_minimize.store(true, Ordering::Release);
+    }
     if let Err(e) = self
       .window_requests_tx
       .send((self.window_id, WindowRequest::Minimized(minimized)))
@@ -519,6 +771,10 @@ impl Window {
     self.window.is_deletable()
   }
 
+  pub fn is_enabled(&self) -> bool {
+    self.window.is_sensitive()
+  }
+
   pub fn is_decorated(&self) -> bool {
     self.window.is_decorated()
   }
@@ -549,7 +805,7 @@ impl Window {
   }
 
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
-    self.fullscreen.replace(fullscreen.clone());
+    *self.fullscreen.lock() = fullscreen.clone();
     if let Err(e) = self
       .window_requests_tx
       .send((self.window_id, WindowRequest::Fullscreen(fullscreen)))
@@ -559,7 +815,7 @@ impl Window {
   }
 
   pub fn fullscreen(&self) -> Option<Fullscreen> {
-    self.fullscreen.borrow().clone()
+    self.fullscreen.lock().clone()
   }
 
   pub fn set_decorations(&self, decorations: bool) {
@@ -590,6 +846,7 @@ impl Window {
   }
 
   pub fn set_window_icon(&self, window_icon: Option<Icon>) {
+    *self.window_icon.lock() = window_icon.clone();
     if let Err(e) = self
       .window_requests_tx
       .send((self.window_id, WindowRequest::WindowIcon(window_icon)))
@@ -598,8 +855,104 @@ impl Window {
     }
   }
 
-  pub fn set_ime_position<P: Into<Position>>(&self, _position: P) {
-    //TODO
+  pub fn set_window_icon_frames(&self, frames: Vec<Icon>, interval: Duration) {
+    let generation = self
+      .icon_animation
+      .fetch_add(1, Ordering::AcqRel)
+      .wrapping_add(1);
+
+    let first_frame = match frames.first() {
+      Some(frame) => frame.clone(),
+      None => {
+        let window_icon = self.window_icon.lock().clone();
+        self.set_window_icon(window_icon);
+        return;
+      }
+    };
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::WindowIcon(Some(first_frame))))
+    {
+      log::warn!("Fail to send window icon request: {}", e);
+    }
+
+    let window = self.window.downgrade();
+    let animation = self.icon_animation.clone();
+    let mut frame = 0;
+    glib::timeout_add_local(interval, move || match window.upgrade() {
+      Some(window) if animation.load(Ordering::Acquire) == generation => {
+        frame = (frame + 1) % frames.len();
+        window.set_icon_list(&frames[frame].inner.clone().into_pixbufs());
+        glib::ControlFlow::Continue
+      }
+      _ => glib::ControlFlow::Break,
+    });
+  }
+
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    let scale_factor = self.scale_factor();
+    let (x, y): (i32, i32) = position.to_logical::<i32>(scale_factor).into();
+    let (width, height): (i32, i32) = size.to_logical::<i32>(scale_factor).into();
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::ImeCursorArea((x, y, width, height)),
+    )) {
+      log::warn!("Fail to send IME cursor area request: {}", e);
+    }
+  }
+
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::ImeAllowed(allowed)))
+    {
+      log::warn!("Fail to send IME allowed request: {}", e);
+    }
+  }
+
+  pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::ImePurpose(purpose)))
+    {
+      log::warn!("Fail to send IME purpose request: {}", e);
+    }
+  }
+
+  pub fn add_accelerator(
+    &self,
+    id: AcceleratorId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    let keyval = keyboard::keycode_to_keyval(accelerator.key)
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))?;
+    let mut mods = gdk::ModifierType::empty();
+    for (modifier, mask) in [
+      (ModifiersState::SHIFT, gdk::ModifierType::SHIFT_MASK),
+      (ModifiersState::CONTROL, gdk::ModifierType::CONTROL_MASK),
+      (ModifiersState::ALT, gdk::ModifierType::MOD1_MASK),
+      (ModifiersState::SUPER, gdk::ModifierType::SUPER_MASK),
+    ] {
+      if accelerator.mods.contains(modifier) {
+        mods |= mask;
+      }
+    }
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::AddAccelerator(id, keyval.into_glib(), mods),
+    )) {
+      log::warn!("Fail to send add accelerator request: {}", e);
+    }
+    Ok(())
+  }
+
+  pub fn remove_accelerator(&self, id: AcceleratorId) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::RemoveAccelerator(id)))
+    {
+      log::warn!("Fail to send remove accelerator request: {}", e);
+    }
   }
 
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
@@ -619,7 +972,44 @@ impl Window {
       log::warn!("Fail to send visible on all workspaces request: {}", e);
     }
   }
+
+  pub fn create_surface_region(&self, rect: Rect) -> Result<SurfaceRegion, ExternalError> {
+    SurfaceRegion::new(self, rect)
+  }
+
+  pub fn sendable_handle(&self) -> Result<u64, ExternalError> {
+    if self.is_wayland() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    let window = self
+      .window
+      .window()
+      .ok_or(ExternalError::Os(os_error!(OsError)))?;
+    Ok(unsafe { gdk_x11_sys::gdk_x11_window_get_xid(window.as_ptr() as *mut _) } as u64)
+  }
+
+  /// Releases the `Window` without destroying the native window, which the event loop keeps
+  /// driving until it's closed.
+  pub fn detach(mut self) {
+    self.detached = true;
+  }
+
+  pub fn adopt_from_handle(&self, handle: u64) -> Result<(), ExternalError> {
+    if self.is_wayland() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Adopt(handle)))
+    {
+      log::warn!("Fail to send adopt request: {}", e);
+    }
+    Ok(())
+  }
+
   pub fn set_cursor_icon(&self, cursor: CursorIcon) {
+    *self.custom_cursor.lock() = None;
+    self.cursor_animation.fetch_add(1, Ordering::AcqRel);
     if let Err(e) = self
       .window_requests_tx
       .send((self.window_id, WindowRequest::CursorIcon(Some(cursor))))
@@ -628,6 +1018,34 @@ impl Window {
     }
   }
 
+  pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+    *self.custom_cursor.lock() = Some(cursor.clone());
+    let generation = self
+      .cursor_animation
+      .fetch_add(1, Ordering::AcqRel)
+      .wrapping_add(1);
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::CustomCursor(cursor.frame(0).clone()),
+    )) {
+      log::warn!("Fail to send custom cursor request: {}", e);
+    }
+    if let Some(delay) = cursor.frame_delay(0) {
+      animate_cursor(
+        CursorAnimation {
+          window: self.window.downgrade(),
+          window_id: self.window_id,
+          window_requests_tx: self.window_requests_tx.clone(),
+          animation: self.cursor_animation.clone(),
+          generation,
+          cursor,
+        },
+        0,
+        delay,
+      );
+    }
+  }
+
   pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
     let inner_pos = self.inner_position().unwrap_or_default();
     let (x, y): (i32, i32) = position
@@ -645,10 +1063,59 @@ impl Window {
     Ok(())
   }
 
-  pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    if !self.window.display().backend().is_x11() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::CursorGrab(mode)))
+    {
+      log::warn!("Fail to send cursor grab request: {}", e);
+    }
+
     Ok(())
   }
 
+  pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), ExternalError> {
+    if !self.window.display().backend().is_x11() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::RawMouseMotion(enabled)))
+    {
+      log::warn!("Fail to send raw mouse motion request: {}", e);
+    }
+
+    Ok(())
+  }
+
+  pub fn set_pointer_acceleration(
+    &self,
+    _profile: Option<AccelerationProfile>,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn set_keyboard_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::KeyboardGrab(grab)))
+    {
+      log::warn!("Fail to send keyboard grab request: {}", e);
+    }
+
+    Ok(())
+  }
+
+  pub fn set_touch_keyboard_visible(&self, visible: bool) -> Result<(), ExternalError> {
+    osk::set_visible(visible).map_err(|e| {
+      log::warn!("Failed to set on-screen keyboard visibility: {}", e);
+      ExternalError::Os(os_error!(OsError))
+    })
+  }
+
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
     if let Err(e) = self
       .window_requests_tx
@@ -872,6 +1339,38 @@ impl Window {
   }
 }
 
+/// An animated cursor shown by `animate_cursor` until the window is gone or its cursor changes.
+struct CursorAnimation {
+  window: glib::WeakRef<gtk::ApplicationWindow>,
+  window_id: WindowId,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  animation: Arc<AtomicU32>,
+  generation: u32,
+  cursor: CustomCursor,
+}
+
+/// Shows the frame after `frame` once `delay` elapsed. The delay of each frame differs, so the
+/// timer is armed again for each of them.
+fn animate_cursor(animation: CursorAnimation, frame: usize, delay: Duration) {
+  glib::timeout_add_local_once(delay, move || {
+    if animation.window.upgrade().is_none()
+      || animation.animation.load(Ordering::Acquire) != animation.generation
+    {
+      return;
+    }
+    let frame = (frame + 1) % animation.cursor.frames.len();
+    if let Err(e) = animation.window_requests_tx.send((
+      animation.window_id,
+      WindowRequest::CustomCursor(animation.cursor.frame(frame).clone()),
+    )) {
+      log::warn!("Fail to send custom cursor request: {}", e);
+    }
+    if let Some(delay) = animation.cursor.frame_delay(frame) {
+      animate_cursor(animation, frame, delay);
+    }
+  });
+}
+
 // We need GtkWindow to initialize WebView, so we have to keep it in the field.
 // It is called on any method.
 unsafe impl Send for Window {}
@@ -879,14 +1378,21 @@ unsafe impl Sync for Window {}
 
 #[non_exhaustive]
 pub enum WindowRequest {
-  Title(String),
+  /// Applies the title pending in the shared slot, if it wasn't already.
+  Title(Arc<Mutex<Option<String>>>),
   Position((i32, i32)),
   Size((i32, i32)),
   SizeConstraints(WindowSizeConstraints),
   Visible(bool),
   Focus,
+  Raise,
+  FocusWithoutRaise,
+  AcceptFocus(bool),
   Resizable(bool),
   Closable(bool),
+  Close,
+  Destroy,
+  Enabled(bool),
   Minimized(bool),
   Maximized(bool),
   DragWindow,
@@ -899,18 +1405,58 @@ pub enum WindowRequest {
   UserAttention(Option<UserAttentionType>),
   SetSkipTaskbar(bool),
   CursorIcon(Option<CursorIcon>),
+  CustomCursor(CustomCursor),
+  QueueDraw,
+  JankDetection(bool),
   CursorPosition((i32, i32)),
   CursorIgnoreEvents(bool),
+  ImeCursorArea((i32, i32, i32, i32)),
+  ImeAllowed(bool),
+  ImePurpose(ImePurpose),
+  /// Connects the accelerator to the keyval and modifiers in the accel group of the window.
+  AddAccelerator(AcceleratorId, u32, gdk::ModifierType),
+  RemoveAccelerator(AcceleratorId),
+  CursorGrab(CursorGrabMode),
+  RawMouseMotion(bool),
+  KeyboardGrab(bool),
   WireUpEvents {
     transparent: bool,
     cursor_moved: bool,
+    intercept_minimize: Arc<AtomicBool>,
+    allow_minimize: Arc<AtomicBool>,
+    fullscreen: Arc<Mutex<Option<Fullscreen>>>,
   },
   SetVisibleOnAllWorkspaces(bool),
   ProgressBarState(ProgressBarState),
+  /// Reparents the window into the X11 window behind the handle, see `Window::adopt_from_handle`.
+  Adopt(u64),
+  /// Drives the surface of the region with the given id, see `Window::create_surface_region`.
+  SurfaceRegion(u64, SurfaceRegionRequest),
+}
+
+/// Destroys a window from any thread, see `Window::close_token`.
+#[derive(Clone)]
+pub struct CloseToken {
+  window_id: WindowId,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+}
+
+impl CloseToken {
+  pub fn proceed(self) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Destroy))
+    {
+      log::warn!("Fail to send destroy request: {}", e);
+    }
+  }
 }
 
 impl Drop for Window {
   fn drop(&mut self) {
+    if self.detached {
+      return;
+    }
     unsafe {
       self.window.destroy();
     }
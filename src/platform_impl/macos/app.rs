@@ -2,7 +2,7 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::VecDeque;
+use std::{cell::RefCell, collections::VecDeque, ffi::c_void};
 
 use cocoa::{
   appkit::{self, NSEvent},
@@ -34,10 +34,33 @@ lazy_static! {
   };
 }
 
+thread_local! {
+  static MSG_HOOK: RefCell<Option<Box<dyn FnMut(*const c_void) -> bool>>> = RefCell::new(None);
+}
+
+/// Sets the callback `sendEvent:` passes every event to first.
+pub fn set_msg_hook(msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>) {
+  MSG_HOOK.with(|hook| *hook.borrow_mut() = msg_hook);
+}
+
+/// Returns whether the hook consumed `event`. A nested `sendEvent:` from within the hook isn't
+/// filtered again.
+fn call_msg_hook(event: id) -> bool {
+  MSG_HOOK.with(|hook| match hook.try_borrow_mut() {
+    Ok(mut hook) => hook
+      .as_deref_mut()
+      .map_or(false, |hook| hook(event as *const c_void)),
+    Err(_) => false,
+  })
+}
+
 // Normally, holding Cmd + any key never sends us a `keyUp` event for that key.
 // Overriding `sendEvent:` like this fixes that. (https://stackoverflow.com/a/15294196)
 // Fun fact: Firefox still has this bug! (https://bugzilla.mozilla.org/show_bug.cgi?id=1299553)
 extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
+  if call_msg_hook(event) {
+    return;
+  }
   unsafe {
     // For posterity, there are some undocumented event types
     // (https://github.com/servo/cocoa-rs/issues/155)
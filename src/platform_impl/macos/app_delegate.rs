@@ -2,21 +2,39 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{platform::macos::ActivationPolicy, platform_impl::platform::app_state::AppState};
+use crate::{
+  event::PowerEvent,
+  monitor::MonitorHandle as RootMonitorHandle,
+  platform::macos::ActivationPolicy,
+  platform_impl::platform::{
+    app_state::AppState,
+    ffi::{
+      self, kCGDisplayAddFlag, kCGDisplayBeginConfigurationFlag, kCGDisplayRemoveFlag,
+      kCGDisplaySetModeFlag, CGDisplayChangeSummaryFlags,
+    },
+    monitor::MonitorHandle,
+    util,
+  },
+};
 
-use cocoa::base::id;
-use cocoa::foundation::NSString;
+use cocoa::{
+  appkit::NSScreen,
+  base::{id, nil},
+  foundation::{NSString, NSUInteger},
+};
 use objc::{
   declare::ClassDecl,
   runtime::{Class, Object, Sel, BOOL},
 };
 use std::{
   cell::{RefCell, RefMut},
+  collections::HashMap,
   os::raw::c_void,
 };
 
 use cocoa::foundation::NSArray;
 use cocoa::foundation::NSURL;
+use core_graphics::display::CGDirectDisplayID;
 use std::ffi::CStr;
 
 static AUX_DELEGATE_STATE_NAME: &str = "auxState";
@@ -58,6 +76,46 @@ lazy_static! {
       sel!(applicationSupportsSecureRestorableState:),
       application_supports_secure_restorable_state as extern "C" fn(&Object, Sel, id) -> BOOL,
     );
+    decl.add_method(
+      sel!(applicationDidChangeScreenParameters:),
+      application_did_change_screen_parameters as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(systemAppearanceDidChange:),
+      system_appearance_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(screenColorSpaceDidChange:),
+      screen_color_space_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(localeDidChange:),
+      locale_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(inputSourceDidChange:),
+      input_source_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(systemTimeDidChange:),
+      system_time_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(workspaceWillSleep:),
+      workspace_will_sleep as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(workspaceDidWake:),
+      workspace_did_wake as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(sessionDidResignActive:),
+      session_did_resign_active as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(sessionDidBecomeActive:),
+      session_did_become_active as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_ivar::<*mut c_void>(AUX_DELEGATE_STATE_NAME);
 
     AppDelegateClass(decl.register())
@@ -88,6 +146,15 @@ extern "C" fn new(class: &Class, _: Sel) -> id {
 
 extern "C" fn dealloc(this: &Object, _: Sel) {
   unsafe {
+    let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+    let center: id = msg_send![workspace, notificationCenter];
+    let _: () = msg_send![center, removeObserver: this];
+    let center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+    let _: () = msg_send![center, removeObserver: this];
+    let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+    let _: () = msg_send![center, removeObserver: this];
+    ffi::CGDisplayRemoveReconfigurationCallback(display_reconfigured, std::ptr::null_mut());
+
     let state_ptr: *mut c_void = *(this.get_ivar(AUX_DELEGATE_STATE_NAME));
     // As soon as the box is constructed it is immediately dropped, releasing the underlying
     // memory
@@ -98,12 +165,219 @@ extern "C" fn dealloc(this: &Object, _: Sel) {
 extern "C" fn did_finish_launching(this: &Object, _: Sel, _: id) {
   trace!("Triggered `applicationDidFinishLaunching`");
   AppState::launched(this);
+  unsafe {
+    observe_suspension(this);
+    observe_appearance(this);
+    observe_locale(this);
+    observe_color_space(this);
+    ffi::CGDisplayRegisterReconfigurationCallback(display_reconfigured, std::ptr::null_mut());
+  }
   trace!("Completed `applicationDidFinishLaunching`");
 }
 
+extern "C" fn application_did_change_screen_parameters(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `applicationDidChangeScreenParameters`");
+  AppState::monitors_changed();
+  trace!("Completed `applicationDidChangeScreenParameters`");
+}
+
+thread_local! {
+  /// Refresh rates of the displays being reconfigured, as of before the reconfiguration.
+  static REFRESH_RATES: RefCell<HashMap<CGDirectDisplayID, u32>> = RefCell::new(HashMap::new());
+}
+
+/// Called for each display affected by a reconfiguration, once before and once after it.
+extern "C" fn display_reconfigured(
+  display: CGDirectDisplayID,
+  flags: CGDisplayChangeSummaryFlags,
+  _: *mut c_void,
+) {
+  let monitor = MonitorHandle::new(display);
+  if flags & kCGDisplayBeginConfigurationFlag != 0 {
+    if let Some(refresh_rate) = monitor.refresh_rate_millihertz() {
+      REFRESH_RATES.with(|rates| rates.borrow_mut().insert(display, refresh_rate));
+    }
+    return;
+  }
+  let previous = REFRESH_RATES.with(|rates| rates.borrow_mut().remove(&display));
+  if flags & kCGDisplayAddFlag != 0 {
+    AppState::monitor_connected(RootMonitorHandle { inner: monitor });
+  } else if flags & kCGDisplayRemoveFlag != 0 {
+    AppState::monitor_disconnected(RootMonitorHandle { inner: monitor });
+  } else if flags & kCGDisplaySetModeFlag != 0 {
+    match (previous, monitor.refresh_rate_millihertz()) {
+      (Some(previous), Some(refresh_rate)) if previous != refresh_rate => {
+        AppState::monitor_refresh_rate_changed(RootMonitorHandle { inner: monitor }, refresh_rate)
+      }
+      _ => (),
+    }
+  }
+}
+
+/// Registers the delegate for the notifications which suspend or resume the application, see
+/// `Event::Suspended`.
+unsafe fn observe_suspension(this: &Object) {
+  let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+  let center: id = msg_send![workspace, notificationCenter];
+  for (selector, name) in [
+    (
+      sel!(workspaceWillSleep:),
+      "NSWorkspaceWillSleepNotification",
+    ),
+    (sel!(workspaceDidWake:), "NSWorkspaceDidWakeNotification"),
+    (
+      sel!(sessionDidResignActive:),
+      "NSWorkspaceSessionDidResignActiveNotification",
+    ),
+    (
+      sel!(sessionDidBecomeActive:),
+      "NSWorkspaceSessionDidBecomeActiveNotification",
+    ),
+  ] {
+    let name = NSString::alloc(nil).init_str(name);
+    let _: () = msg_send![center, addObserver: this selector: selector name: name object: nil];
+    let _: () = msg_send![name, release];
+  }
+
+  // The screen lock isn't a workspace notification, it's only distributed system-wide.
+  let center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+  for (selector, name) in [
+    (sel!(sessionDidResignActive:), "com.apple.screenIsLocked"),
+    (sel!(sessionDidBecomeActive:), "com.apple.screenIsUnlocked"),
+  ] {
+    let name = NSString::alloc(nil).init_str(name);
+    let _: () = msg_send![center, addObserver: this selector: selector name: name object: nil];
+    let _: () = msg_send![name, release];
+  }
+}
+
+/// Registers the delegate for the notifications which change the `SystemAppearance`.
+unsafe fn observe_appearance(this: &Object) {
+  let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+  let workspace_center: id = msg_send![workspace, notificationCenter];
+  let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+  for (center, name) in [
+    (
+      workspace_center,
+      "NSWorkspaceAccessibilityDisplayOptionsDidChangeNotification",
+    ),
+    (center, "NSSystemColorsDidChangeNotification"),
+  ] {
+    let name = NSString::alloc(nil).init_str(name);
+    let _: () = msg_send![center, addObserver: this selector: sel!(systemAppearanceDidChange:) name: name object: nil];
+    let _: () = msg_send![name, release];
+  }
+}
+
+/// Registers the delegate for the notifications of the locale, the keyboard input source and the
+/// system time changing.
+unsafe fn observe_locale(this: &Object) {
+  let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+  for (selector, name) in [
+    (
+      sel!(localeDidChange:),
+      "kCFLocaleCurrentLocaleDidChangeNotification",
+    ),
+    (
+      sel!(inputSourceDidChange:),
+      "NSTextInputContextKeyboardSelectionDidChangeNotification",
+    ),
+    (
+      sel!(systemTimeDidChange:),
+      "NSSystemClockDidChangeNotification",
+    ),
+    (
+      sel!(systemTimeDidChange:),
+      "NSSystemTimeZoneDidChangeNotification",
+    ),
+  ] {
+    let name = NSString::alloc(nil).init_str(name);
+    let _: () = msg_send![center, addObserver: this selector: selector name: name object: nil];
+    let _: () = msg_send![name, release];
+  }
+}
+
+/// Registers the delegate for the notification of the color space of a screen changing, see
+/// `Event::MonitorIccProfileChanged`.
+unsafe fn observe_color_space(this: &Object) {
+  let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+  let name = NSString::alloc(nil).init_str("NSScreenColorSpaceDidChangeNotification");
+  let _: () = msg_send![center, addObserver: this selector: sel!(screenColorSpaceDidChange:) name: name object: nil];
+  let _: () = msg_send![name, release];
+}
+
+extern "C" fn screen_color_space_did_change(_: &Object, _: Sel, notification: id) {
+  trace!("Triggered `screenColorSpaceDidChange`");
+  unsafe {
+    let screen: id = msg_send![notification, object];
+    if screen != nil {
+      let desc = NSScreen::deviceDescription(screen);
+      let key = util::ns_string_id_ref("NSScreenNumber");
+      let value: id = msg_send![desc, objectForKey:*key];
+      if value != nil {
+        let display_id: NSUInteger = msg_send![value, unsignedIntegerValue];
+        AppState::monitor_icc_profile_changed(RootMonitorHandle {
+          inner: MonitorHandle::new(display_id as CGDirectDisplayID),
+        });
+      }
+    }
+  }
+  trace!("Completed `screenColorSpaceDidChange`");
+}
+
+extern "C" fn locale_did_change(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `localeDidChange`");
+  AppState::locale_changed();
+  trace!("Completed `localeDidChange`");
+}
+
+extern "C" fn input_source_did_change(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `inputSourceDidChange`");
+  AppState::input_locale_changed();
+  trace!("Completed `inputSourceDidChange`");
+}
+
+extern "C" fn system_time_did_change(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `systemTimeDidChange`");
+  AppState::system_time_changed();
+  trace!("Completed `systemTimeDidChange`");
+}
+
+extern "C" fn system_appearance_did_change(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `systemAppearanceDidChange`");
+  AppState::system_appearance_changed();
+  trace!("Completed `systemAppearanceDidChange`");
+}
+
+extern "C" fn workspace_will_sleep(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `workspaceWillSleep`");
+  AppState::power_event(PowerEvent::Suspending);
+  AppState::set_sleeping(true);
+  trace!("Completed `workspaceWillSleep`");
+}
+
+extern "C" fn workspace_did_wake(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `workspaceDidWake`");
+  AppState::power_event(PowerEvent::Resuming);
+  AppState::set_sleeping(false);
+  trace!("Completed `workspaceDidWake`");
+}
+
+extern "C" fn session_did_resign_active(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `sessionDidResignActive`");
+  AppState::set_locked(true);
+  trace!("Completed `sessionDidResignActive`");
+}
+
+extern "C" fn session_did_become_active(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `sessionDidBecomeActive`");
+  AppState::set_locked(false);
+  trace!("Completed `sessionDidBecomeActive`");
+}
+
 extern "C" fn application_will_terminate(_: &Object, _: Sel, _: id) {
   trace!("Triggered `applicationWillTerminate`");
-  AppState::exit();
+  AppState::exit(true);
   trace!("Completed `applicationWillTerminate`");
 }
 
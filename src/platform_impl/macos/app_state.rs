@@ -4,7 +4,7 @@
 
 use std::{
   cell::{RefCell, RefMut},
-  collections::VecDeque,
+  collections::{HashSet, VecDeque},
   fmt::{self, Debug},
   hint::unreachable_unchecked,
   mem,
@@ -13,7 +13,7 @@ use std::{
     atomic::{AtomicBool, Ordering},
     Mutex, MutexGuard,
   },
-  time::Instant,
+  time::{Duration, Instant},
 };
 
 use cocoa::{
@@ -21,24 +21,28 @@ use cocoa::{
   base::{id, nil},
   foundation::{NSAutoreleasePool, NSSize},
 };
-use objc::runtime::{Object, NO, YES};
+use objc::runtime::{Class, Object, BOOL, NO, YES};
 
 use crate::{
   dpi::LogicalSize,
-  event::{Event, StartCause, WindowEvent},
-  event_loop::{ControlFlow, EventLoopWindowTarget as RootWindowTarget},
+  event::{Event, PowerEvent, StartCause, WindowEvent},
+  event_loop::{ControlFlow, EventLoopWindowTarget as RootWindowTarget, Suspension},
+  global_shortcut::GlobalShortcutId,
+  keyboard::KeyboardLayout,
+  monitor::MonitorHandle as RootMonitorHandle,
   platform::macos::ActivationPolicy,
   platform_impl::{
     get_aux_state_mut,
     platform::{
-      event::{EventProxy, EventWrapper},
+      event::{self, EventProxy, EventWrapper},
       event_loop::{post_dummy_event, PanicInfo},
+      frame_pacing,
       observer::{CFRunLoopGetMain, CFRunLoopWakeUp, EventLoopWaker},
       util::{self, IdRef, Never},
-      window::get_window_id,
+      window::{self, get_window_id},
     },
   },
-  window::WindowId,
+  window::{SystemAppearance, WindowId},
 };
 
 lazy_static! {
@@ -117,6 +121,18 @@ impl<T> EventHandler for EventLoopHandler<T> {
           (callback)(Event::UserEvent(event), &this.window_target, control_flow);
         }
       }
+      for event in this.window_target.p.typed_receiver.try_iter() {
+        if let ControlFlow::ExitWithCode(code) = *control_flow {
+          let dummy = &mut ControlFlow::ExitWithCode(code);
+          (callback)(Event::TypedUserEvent(event), &this.window_target, dummy);
+        } else {
+          (callback)(
+            Event::TypedUserEvent(event),
+            &this.window_target,
+            control_flow,
+          );
+        }
+      }
     });
   }
 }
@@ -125,13 +141,43 @@ impl<T> EventHandler for EventLoopHandler<T> {
 struct Handler {
   ready: AtomicBool,
   in_callback: AtomicBool,
+  pumping: AtomicBool,
   control_flow: Mutex<ControlFlow>,
   control_flow_prev: Mutex<ControlFlow>,
   start_time: Mutex<Option<Instant>>,
   callback: Mutex<Option<Box<dyn EventHandler>>>,
   pending_events: Mutex<VecDeque<EventWrapper>>,
-  pending_redraw: Mutex<Vec<WindowId>>,
+  pending_redraw: Mutex<RedrawQueue>,
   waker: Mutex<EventLoopWaker>,
+  suspension: Mutex<Suspension>,
+  /// Appearance as of the last notification, to only emit `SystemAppearanceChanged` when it changed.
+  appearance: Mutex<SystemAppearance>,
+  /// Keyboard layout as of the last input source change, which may only have selected an input
+  /// method.
+  keyboard_layout: Mutex<KeyboardLayout>,
+}
+
+/// Windows waiting to be redrawn, in the order they asked for it. Queueing a window is constant
+/// time, however many windows there are.
+#[derive(Default)]
+pub struct RedrawQueue {
+  order: Vec<WindowId>,
+  queued: HashSet<WindowId>,
+}
+
+impl RedrawQueue {
+  /// Queues `window_id`, unless it's queued already.
+  pub fn push(&mut self, window_id: WindowId) {
+    if self.queued.insert(window_id) {
+      self.order.push(window_id);
+    }
+  }
+
+  /// Empties the queue, returning the windows in order.
+  pub fn take(&mut self) -> Vec<WindowId> {
+    self.queued.clear();
+    mem::take(&mut self.order)
+  }
 }
 
 unsafe impl Send for Handler {}
@@ -142,7 +188,7 @@ impl Handler {
     self.pending_events.lock().unwrap()
   }
 
-  fn redraw<'a>(&'a self) -> MutexGuard<'a, Vec<WindowId>> {
+  fn redraw<'a>(&'a self) -> MutexGuard<'a, RedrawQueue> {
     self.pending_redraw.lock().unwrap()
   }
 
@@ -158,6 +204,24 @@ impl Handler {
     self.ready.store(true, Ordering::Release);
   }
 
+  fn is_pumping(&self) -> bool {
+    self.pumping.load(Ordering::Acquire)
+  }
+
+  fn set_pumping(&self, pumping: bool) {
+    self.pumping.store(pumping, Ordering::Release);
+  }
+
+  /// Sets the waker up for the current control flow.
+  fn reset_waker(&self) {
+    match *self.control_flow.lock().unwrap() {
+      ControlFlow::Wait => self.waker().stop(),
+      ControlFlow::WaitUntil(instant) => self.waker().start_at(instant),
+      ControlFlow::Poll => self.waker().start(),
+      ControlFlow::ExitWithCode(_) => (),
+    }
+  }
+
   fn should_exit(&self) -> bool {
     matches!(
       *self.control_flow.lock().unwrap(),
@@ -190,7 +254,7 @@ impl Handler {
   }
 
   fn should_redraw(&self) -> Vec<WindowId> {
-    mem::take(&mut *self.redraw())
+    self.redraw().take()
   }
 
   fn get_in_callback(&self) -> bool {
@@ -201,6 +265,19 @@ impl Handler {
     self.in_callback.store(in_callback, Ordering::Release);
   }
 
+  /// Emits `RedrawRequested`, preceded by `FrameMissed` when jank detection found missed frames.
+  fn handle_redraw(&self, window_id: WindowId) {
+    if let Some((count, duration)) = frame_pacing::redraw_requested(window_id) {
+      self.handle_nonuser_event(EventWrapper::StaticEvent(Event::FrameMissed {
+        window_id,
+        count,
+        duration,
+      }));
+    }
+    self.handle_nonuser_event(EventWrapper::StaticEvent(Event::RedrawRequested(window_id)));
+    frame_pacing::redraw_finished(window_id);
+  }
+
   fn handle_nonuser_event(&self, wrapper: EventWrapper) {
     if let Some(ref mut callback) = *self.callback.lock().unwrap() {
       match wrapper {
@@ -269,16 +346,65 @@ impl AppState {
     }));
   }
 
-  pub fn exit() -> i32 {
+  /// Ends the event loop. `close_windows` is set when the process is about to exit, for no window
+  /// to outlive it and their `Destroyed` events to come before `LoopDestroyed`.
+  pub fn exit(close_windows: bool) -> i32 {
     HANDLER.set_in_callback(true);
+    if close_windows {
+      unsafe { self::close_windows(NSApp()) };
+      for event in HANDLER.take_events() {
+        HANDLER.handle_nonuser_event(event);
+      }
+    }
     HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::LoopDestroyed));
     HANDLER.set_in_callback(false);
     HANDLER.callback.lock().unwrap().take();
-    if let ControlFlow::ExitWithCode(code) = HANDLER.get_old_and_new_control_flow().1 {
+    let code = if let ControlFlow::ExitWithCode(code) = HANDLER.get_old_and_new_control_flow().1 {
       code
     } else {
       0
+    };
+    // The next `run_return` starts over, like the first one.
+    *HANDLER.control_flow.lock().unwrap() = ControlFlow::default();
+    *HANDLER.control_flow_prev.lock().unwrap() = ControlFlow::default();
+    code
+  }
+
+  /// Makes the application stop after the next iteration, at the latest `timeout` from now.
+  pub fn start_pump(timeout: Option<Duration>) {
+    HANDLER.set_pumping(true);
+    if let Some(timeout) = timeout {
+      let deadline = Instant::now() + timeout;
+      match *HANDLER.control_flow.lock().unwrap() {
+        ControlFlow::Wait => HANDLER.waker().start_at(deadline),
+        ControlFlow::WaitUntil(instant) => HANDLER.waker().start_at(instant.min(deadline)),
+        ControlFlow::Poll | ControlFlow::ExitWithCode(_) => (),
+      }
+    }
+  }
+
+  /// Ends a `pump_events`, returning the exit code if the event loop exited.
+  pub fn stop_pump() -> Option<i32> {
+    HANDLER.set_pumping(false);
+    if HANDLER.should_exit() {
+      Some(AppState::exit(false))
+    } else {
+      HANDLER.reset_waker();
+      None
+    }
+  }
+
+  /// Starts a `run_return` after the first one, which `launched` doesn't get called for.
+  pub fn restart() {
+    if !HANDLER.is_ready() {
+      return;
     }
+    HANDLER.waker().start();
+    HANDLER.set_in_callback(true);
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::NewEvents(
+      StartCause::Init,
+    )));
+    HANDLER.set_in_callback(false);
   }
 
   pub fn launched(app_delegate: &Object) {
@@ -293,6 +419,8 @@ impl AppState {
       };
       ns_app.activateIgnoringOtherApps_(ignore);
     };
+    *HANDLER.appearance.lock().unwrap() = util::system_appearance();
+    *HANDLER.keyboard_layout.lock().unwrap() = event::keyboard_layout();
     HANDLER.set_ready();
     HANDLER.waker().start();
     HANDLER.set_in_callback(true);
@@ -306,6 +434,96 @@ impl AppState {
     HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::Opened { urls }));
   }
 
+  pub fn monitor_connected(monitor: RootMonitorHandle) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::MonitorConnected(monitor)));
+  }
+
+  pub fn monitor_disconnected(monitor: RootMonitorHandle) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::MonitorDisconnected(
+      monitor,
+    )));
+  }
+
+  pub fn monitor_refresh_rate_changed(monitor: RootMonitorHandle, refresh_rate_millihertz: u32) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(
+      Event::MonitorRefreshRateChanged {
+        monitor,
+        refresh_rate_millihertz,
+      },
+    ));
+  }
+
+  pub fn monitor_icc_profile_changed(monitor: RootMonitorHandle) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::MonitorIccProfileChanged(
+      monitor,
+    )));
+  }
+
+  pub fn monitors_changed() {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(
+      Event::MonitorConfigurationChanged,
+    ));
+  }
+
+  pub fn system_appearance_changed() {
+    let appearance = util::system_appearance();
+    if mem::replace(&mut *HANDLER.appearance.lock().unwrap(), appearance) != appearance {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::SystemAppearanceChanged(
+        appearance,
+      )));
+    }
+  }
+
+  pub fn locale_changed() {
+    if let Some(locale) = util::current_locale() {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::LocaleChanged { locale }));
+    }
+  }
+
+  pub fn input_locale_changed() {
+    if let Some(input_locale) = event::get_input_source_id() {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::InputLocaleChanged {
+        input_locale: input_locale.to_owned(),
+      }));
+    }
+    let layout = event::keyboard_layout();
+    let previous = mem::replace(
+      &mut *HANDLER.keyboard_layout.lock().unwrap(),
+      layout.clone(),
+    );
+    if previous != layout {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::KeyboardLayoutChanged(
+        layout,
+      )));
+    }
+  }
+
+  pub fn system_time_changed() {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::SystemTimeChanged));
+  }
+
+  pub fn global_shortcut(id: GlobalShortcutId) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::GlobalShortcut(id)));
+  }
+
+  pub fn power_event(event: PowerEvent) {
+    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::PowerEvent(event)));
+  }
+
+  pub fn set_sleeping(sleeping: bool) {
+    let event = HANDLER.suspension.lock().unwrap().set_sleeping(sleeping);
+    if let Some(event) = event {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(event));
+    }
+  }
+
+  pub fn set_locked(locked: bool) {
+    let event = HANDLER.suspension.lock().unwrap().set_locked(locked);
+    if let Some(event) = event {
+      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(event));
+    }
+  }
+
   pub fn wakeup(panic_info: Weak<PanicInfo>) {
     let panic_info = panic_info
       .upgrade()
@@ -343,10 +561,7 @@ impl AppState {
 
   // This is called from multiple threads at present
   pub fn queue_redraw(window_id: WindowId) {
-    let mut pending_redraw = HANDLER.redraw();
-    if !pending_redraw.contains(&window_id) {
-      pending_redraw.push(window_id);
-    }
+    HANDLER.redraw().push(window_id);
     unsafe {
       let rl = CFRunLoopGetMain();
       CFRunLoopWakeUp(rl);
@@ -354,7 +569,12 @@ impl AppState {
   }
 
   pub fn handle_redraw(window_id: WindowId) {
-    HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::RedrawRequested(window_id)));
+    // Drawn by a nested event loop like `Window::run_modal`, the callback can't be re-entered.
+    if HANDLER.get_in_callback() {
+      HANDLER.redraw().push(window_id);
+      return;
+    }
+    HANDLER.handle_redraw(window_id);
   }
 
   pub fn queue_event(wrapper: EventWrapper) {
@@ -386,11 +606,11 @@ impl AppState {
     }
     HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::MainEventsCleared));
     for window_id in HANDLER.should_redraw() {
-      HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::RedrawRequested(window_id)));
+      HANDLER.handle_redraw(window_id);
     }
     HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::RedrawEventsCleared));
     HANDLER.set_in_callback(false);
-    if HANDLER.should_exit() {
+    if HANDLER.should_exit() || HANDLER.is_pumping() {
       unsafe {
         let app: id = NSApp();
         let pool = NSAutoreleasePool::new(nil);
@@ -441,6 +661,34 @@ unsafe fn window_activation_hack(ns_app: id) {
     }
   }
 }
+
+/// Closes the windows created by tao which are still open, `windowWillClose:` queues their
+/// `WindowEvent::Destroyed`.
+unsafe fn close_windows(ns_app: id) {
+  let delegate_class = match Class::get("TaoWindowDelegate") {
+    Some(class) => class,
+    // No window was ever created.
+    None => return,
+  };
+  let ns_windows: id = msg_send![ns_app, windows];
+  let ns_enumerator: id = msg_send![ns_windows, objectEnumerator];
+  loop {
+    let ns_window: id = msg_send![ns_enumerator, nextObject];
+    if ns_window == nil {
+      break;
+    }
+    // The delegate is removed once the window is closed.
+    let delegate: id = msg_send![ns_window, delegate];
+    if delegate == nil {
+      continue;
+    }
+    let is_tao_window: BOOL = msg_send![delegate, isKindOfClass: delegate_class];
+    if is_tao_window == YES {
+      ns_window.close();
+    }
+  }
+}
+
 fn apply_activation_policy(app_delegate: &Object) {
   unsafe {
     use cocoa::appkit::NSApplicationActivationPolicy::*;
@@ -455,4 +703,6 @@ fn apply_activation_policy(app_delegate: &Object) {
       ActivationPolicy::Prohibited => NSApplicationActivationPolicyProhibited,
     });
   }
+  // Windows created before launching finished may have asked to hide the dock icon.
+  window::update_dock_icon_visibility(None);
 }
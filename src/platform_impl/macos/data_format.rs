@@ -0,0 +1,49 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use core_foundation::{
+  base::TCFType,
+  string::{CFString, CFStringRef},
+};
+
+use crate::{
+  error::OsError as RootOsError,
+  platform_impl::platform::{
+    ffi::{kUTTagClassMIMEType, UTTypeCreatePreferredIdentifierForTag},
+    OsError,
+  },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat {
+  uti: String,
+}
+
+impl DataFormat {
+  pub fn register(mime_type: &str) -> Result<Self, RootOsError> {
+    let mime_type = CFString::new(mime_type);
+    // Gives the UTI declared for `mime_type`, or a dynamic `dyn.` one derived from it, which is
+    // the same in every process.
+    let uti = unsafe {
+      UTTypeCreatePreferredIdentifierForTag(
+        kUTTagClassMIMEType,
+        mime_type.as_concrete_TypeRef(),
+        std::ptr::null() as CFStringRef,
+      )
+    };
+    if uti.is_null() {
+      return Err(os_error!(OsError::CreationError(
+        "Failed to create the UTI of the MIME type"
+      )));
+    }
+    let uti = unsafe { CFString::wrap_under_create_rule(uti) };
+    Ok(Self {
+      uti: uti.to_string(),
+    })
+  }
+
+  pub fn uti(&self) -> &str {
+    &self.uti
+  }
+}
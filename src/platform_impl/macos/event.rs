@@ -7,6 +7,7 @@ use std::{collections::HashSet, ffi::c_void, os::raw::c_ushort, sync::Mutex};
 use cocoa::{
   appkit::{NSEvent, NSEventModifierFlags},
   base::id,
+  foundation::NSArray,
 };
 
 use core_foundation::{base::CFRelease, data::CFDataGetBytePtr};
@@ -14,7 +15,7 @@ use core_foundation::{base::CFRelease, data::CFDataGetBytePtr};
 use crate::{
   dpi::LogicalSize,
   event::{ElementState, Event, KeyEvent},
-  keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NativeKeyCode},
+  keyboard::{Key, KeyCode, KeyLocation, KeyboardLayout, ModifiersState, NativeKeyCode},
   platform_impl::platform::{
     ffi,
     util::{ns_string_to_rust, IdRef, Never},
@@ -57,6 +58,7 @@ pub enum EventProxy {
 pub struct KeyEventExtra {
   pub text_with_all_modifiers: Option<&'static str>,
   pub key_without_modifiers: Key<'static>,
+  pub input_locale: Option<&'static str>,
 }
 
 pub fn get_modifierless_char(scancode: u16) -> Key<'static> {
@@ -115,6 +117,71 @@ pub fn get_modifierless_char(scancode: u16) -> Key<'static> {
   Key::Character(insert_or_get_key_str(chars))
 }
 
+/// Returns the identifier of the selected input source, which unlike the keyboard layout also
+/// reflects input methods.
+pub fn get_input_source_id() -> Option<&'static str> {
+  unsafe {
+    let input_source = ffi::TISCopyCurrentKeyboardInputSource();
+    if input_source.is_null() {
+      return None;
+    }
+    let source_id = ffi::TISGetInputSourceProperty(input_source, ffi::kTISPropertyInputSourceID);
+    let source_id = if source_id.is_null() {
+      None
+    } else {
+      Some(insert_or_get_key_str(ns_string_to_rust(source_id as id)))
+    };
+    CFRelease(input_source as *mut c_void);
+    source_id
+  }
+}
+
+/// Returns the selected keyboard layout, which input methods type with.
+pub fn keyboard_layout() -> KeyboardLayout {
+  let mut layout = KeyboardLayout::default();
+  unsafe {
+    let input_source = ffi::TISCopyCurrentKeyboardLayoutInputSource();
+    if input_source.is_null() {
+      return layout;
+    }
+    let name = ffi::TISGetInputSourceProperty(input_source, ffi::kTISPropertyLocalizedName);
+    if !name.is_null() {
+      layout.name = ns_string_to_rust(name as id);
+    }
+    // Ordered by how well the layout supports them.
+    let languages: id =
+      ffi::TISGetInputSourceProperty(input_source, ffi::kTISPropertyInputSourceLanguages) as id;
+    if !languages.is_null() && NSArray::count(languages) > 0 {
+      layout.language = Some(ns_string_to_rust(NSArray::objectAtIndex(languages, 0)));
+    }
+    CFRelease(input_source as *mut c_void);
+  }
+  layout
+}
+
+/// Returns the key `code` produces without modifiers on the selected keyboard layout.
+pub fn key_for_code(code: KeyCode) -> Key<'static> {
+  let scancode = match code.to_scancode() {
+    Some(scancode) => scancode as u16,
+    None => return Key::Unidentified(NativeKeyCode::Unidentified),
+  };
+  match code_to_key(code, scancode) {
+    Key::Unidentified(_) => get_modifierless_char(scancode),
+    key => key,
+  }
+}
+
+/// Returns the key with the lowest virtual key code which produces `key` on the current layout.
+pub fn code_for_key(key: &Key<'_>) -> Option<KeyCode> {
+  if let Key::Unidentified(_) = key {
+    return None;
+  }
+  // Virtual key codes are in the domain [0, 127].
+  (0..0x80)
+    .map(KeyCode::from_scancode)
+    .find(|code| key_for_code(*code) == *key)
+}
+
 fn get_logical_key_char(ns_event: id, modifierless_chars: &str) -> Key<'static> {
   let characters: id = unsafe { msg_send![ns_event, charactersIgnoringModifiers] };
   let string = unsafe { ns_string_to_rust(characters) };
@@ -190,11 +257,14 @@ pub fn create_key_event(
     logical_key,
     physical_key,
     repeat: is_repeat,
+    repeat_count: 0,
+    dead_key_pending: false,
     state,
     text,
     platform_specific: KeyEventExtra {
       text_with_all_modifiers,
       key_without_modifiers,
+      input_locale: get_input_source_id(),
     },
   }
 }
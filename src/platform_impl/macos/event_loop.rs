@@ -5,17 +5,20 @@
 use std::{
   any::Any,
   cell::{Cell, RefCell},
-  collections::VecDeque,
+  collections::{HashMap, VecDeque},
   marker::PhantomData,
   mem,
   os::raw::c_void,
   panic::{catch_unwind, resume_unwind, RefUnwindSafe, UnwindSafe},
   process, ptr,
   rc::{Rc, Weak},
+  time::Duration,
 };
 
 use cocoa::{
-  appkit::{NSApp, NSEventModifierFlags, NSEventSubtype, NSEventType::NSApplicationDefined},
+  appkit::{
+    NSApp, NSEvent, NSEventModifierFlags, NSEventSubtype, NSEventType::NSApplicationDefined,
+  },
   base::{id, nil, YES},
   foundation::{NSAutoreleasePool, NSInteger, NSPoint, NSTimeInterval},
 };
@@ -25,19 +28,24 @@ use scopeguard::defer;
 use crate::{
   dpi::PhysicalPosition,
   error::ExternalError,
-  event::Event,
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
+  event::{Event, TypedUserEvent},
+  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget, TimerId},
+  global_shortcut::{Accelerator, GlobalShortcutId},
+  keyboard::{Key, KeyCode, KeyRepeatSettings, KeyboardLayout},
   monitor::MonitorHandle as RootMonitorHandle,
+  platform::{pump_events::PumpStatus, run_return::ExitReason},
   platform_impl::platform::{
-    app::APP_CLASS,
+    app::{self, APP_CLASS},
     app_delegate::APP_DELEGATE_CLASS,
     app_state::AppState,
+    event,
+    global_shortcut::GlobalShortcut,
     monitor::{self, MonitorHandle},
     observer::*,
     util::{self, IdRef},
   },
   platform_impl::set_progress_indicator,
-  window::ProgressBarState,
+  window::{ProgressBarState, SystemAppearance},
 };
 
 #[derive(Default)]
@@ -72,12 +80,25 @@ impl PanicInfo {
 pub struct EventLoopWindowTarget<T: 'static> {
   pub sender: Sender<T>, // this is only here to be cloned elsewhere
   pub receiver: Receiver<T>,
+  pub typed_sender: Sender<TypedUserEvent>,
+  pub typed_receiver: Receiver<TypedUserEvent>,
+  /// Timers of `set_timer`, dropped once they don't fire anymore.
+  timers: Rc<RefCell<HashMap<TimerId, Timer>>>,
+  global_shortcuts: Rc<RefCell<HashMap<GlobalShortcutId, GlobalShortcut>>>,
 }
 
 impl<T> Default for EventLoopWindowTarget<T> {
   fn default() -> Self {
     let (sender, receiver) = channel::unbounded();
-    EventLoopWindowTarget { sender, receiver }
+    let (typed_sender, typed_receiver) = channel::unbounded();
+    EventLoopWindowTarget {
+      sender,
+      receiver,
+      typed_sender,
+      typed_receiver,
+      timers: Default::default(),
+      global_shortcuts: Default::default(),
+    }
   }
 }
 
@@ -87,6 +108,61 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     monitor::available_monitors()
   }
 
+  pub fn set_timer(&self, id: TimerId, interval: Duration, repeating: bool) {
+    let mut timers = self.timers.borrow_mut();
+    timers.retain(|_, timer| timer.is_valid());
+    timers.insert(id, Timer::new(id, interval, repeating));
+  }
+
+  #[inline]
+  pub fn cancel_timer(&self, id: TimerId) {
+    self.timers.borrow_mut().remove(&id);
+  }
+
+  #[inline]
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    event::keyboard_layout()
+  }
+
+  #[inline]
+  pub fn key_for_code(&self, code: KeyCode) -> Key<'static> {
+    event::key_for_code(code)
+  }
+
+  #[inline]
+  pub fn code_for_key(&self, key: &Key<'_>) -> Option<KeyCode> {
+    event::code_for_key(key)
+  }
+
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    unsafe {
+      Some(KeyRepeatSettings {
+        delay: Duration::from_secs_f64(NSEvent::keyRepeatDelay(nil)),
+        interval: Duration::from_secs_f64(NSEvent::keyRepeatInterval(nil)),
+      })
+    }
+  }
+
+  pub fn register_global_shortcut(
+    &self,
+    id: GlobalShortcutId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    let shortcut = GlobalShortcut::new(id, accelerator)?;
+    self.global_shortcuts.borrow_mut().insert(id, shortcut);
+    Ok(())
+  }
+
+  #[inline]
+  pub fn unregister_global_shortcut(&self, id: GlobalShortcutId) {
+    self.global_shortcuts.borrow_mut().remove(&id);
+  }
+
+  #[inline]
+  pub fn unregister_all_global_shortcuts(&self) {
+    self.global_shortcuts.borrow_mut().clear();
+  }
+
   #[inline]
   pub fn monitor_from_point(&self, x: f64, y: f64) -> Option<MonitorHandle> {
     monitor::from_point(x, y)
@@ -121,6 +197,16 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     }
   }
 
+  #[inline]
+  pub fn system_appearance(&self) -> SystemAppearance {
+    util::system_appearance()
+  }
+
+  #[inline]
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    None
+  }
+
   #[inline]
   pub fn set_progress_bar(&self, progress: ProgressBarState) {
     set_progress_indicator(progress);
@@ -140,13 +226,18 @@ pub struct EventLoop<T: 'static> {
   /// into a strong reference in order to call the callback but then the
   /// strong reference should be dropped as soon as possible.
   _callback: Option<Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>>,
+
+  /// Whether `pump_events` was called since the event loop last exited.
+  pumping: bool,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
-pub(crate) struct PlatformSpecificEventLoopAttributes {}
+#[derive(Default)]
+pub(crate) struct PlatformSpecificEventLoopAttributes {
+  pub(crate) msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+}
 
 impl<T> EventLoop<T> {
-  pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> Self {
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> Self {
     let panic_info: Rc<PanicInfo> = Default::default();
     setup_control_flow_observers(Rc::downgrade(&panic_info));
 
@@ -167,6 +258,7 @@ impl<T> EventLoop<T> {
       let _: () = msg_send![pool, drain];
       delegate
     };
+    app::set_msg_hook(attributes.msg_hook.take());
 
     EventLoop {
       delegate,
@@ -176,22 +268,38 @@ impl<T> EventLoop<T> {
       }),
       panic_info,
       _callback: None,
+      pumping: false,
     }
   }
 
+  pub fn set_shutdown_timeout(&mut self, _timeout: Duration) {}
+
   pub fn window_target(&self) -> &RootWindowTarget<T> {
     &self.window_target
   }
 
+  pub fn set_common_modes(&self, enabled: bool) {
+    set_common_modes(enabled);
+  }
+
   pub fn run<F>(mut self, callback: F) -> !
   where
     F: 'static + FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
   {
-    let exit_code = self.run_return(callback);
+    let exit_code = self.run_inner(callback, true).exit_code();
     process::exit(exit_code);
   }
 
-  pub fn run_return<F>(&mut self, callback: F) -> i32
+  pub fn run_return<F>(&mut self, callback: F) -> ExitReason
+  where
+    F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
+  {
+    self.run_inner(callback, false)
+  }
+
+  /// Runs the event loop, closing the windows left once it exits if `close_windows` is set, which
+  /// is only the case for `run` since the windows are kept in between two `run_return`.
+  fn run_inner<F>(&mut self, callback: F, close_windows: bool) -> ExitReason
   where
     F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
   {
@@ -220,22 +328,77 @@ impl<T> EventLoop<T> {
       mem::drop(callback);
 
       AppState::set_callback(weak_cb, Rc::clone(&self.window_target));
+      AppState::restart();
+      let () = msg_send![app, run];
+
+      if let Some(panic) = self.panic_info.take() {
+        drop(self._callback.take());
+        resume_unwind(panic);
+      }
+      AppState::exit(close_windows)
+    };
+    drop(self._callback.take());
+
+    ExitReason::Requested(exit_code)
+  }
+
+  pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
+  {
+    // See `run_return`, the callback is dropped before returning.
+    let callback = unsafe {
+      mem::transmute::<
+        Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>,
+        Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>,
+      >(Rc::new(RefCell::new(callback)))
+    };
+
+    self._callback = Some(Rc::clone(&callback));
+
+    let exit_code = unsafe {
+      let pool = NSAutoreleasePool::new(nil);
+      defer!(pool.drain());
+      let app = NSApp();
+      assert_ne!(app, nil);
+
+      let weak_cb: Weak<_> = Rc::downgrade(&callback);
+      mem::drop(callback);
+
+      AppState::set_callback(weak_cb, Rc::clone(&self.window_target));
+      if !self.pumping {
+        AppState::restart();
+        self.pumping = true;
+      }
+      // `AppState::cleared` stops the application at the end of the iteration.
+      AppState::start_pump(timeout);
       let () = msg_send![app, run];
+      let exit_code = AppState::stop_pump();
 
       if let Some(panic) = self.panic_info.take() {
         drop(self._callback.take());
         resume_unwind(panic);
       }
-      AppState::exit()
+      exit_code
     };
     drop(self._callback.take());
 
-    exit_code
+    match exit_code {
+      Some(exit_code) => {
+        self.pumping = false;
+        PumpStatus::Exit(ExitReason::Requested(exit_code))
+      }
+      None => PumpStatus::Continue,
+    }
   }
 
   pub fn create_proxy(&self) -> Proxy<T> {
     Proxy::new(self.window_target.p.sender.clone())
   }
+
+  pub fn create_typed_proxy(&self) -> Proxy<TypedUserEvent> {
+    Proxy::new(self.window_target.p.typed_sender.clone())
+  }
 }
 
 #[inline]
@@ -14,7 +14,7 @@
 use std::ffi::c_void;
 
 use cocoa::{
-  appkit::CGPoint,
+  appkit::{CGPoint, NSWindowDepth},
   base::id,
   foundation::{NSInteger, NSUInteger},
 };
@@ -134,6 +134,24 @@ pub type CGDisplayFadeInterval = f32;
 pub type CGDisplayReservationInterval = f32;
 pub type CGDisplayBlendFraction = f32;
 
+// NSPointingDeviceType
+pub const NSUnknownPointingDevice: NSUInteger = 0;
+pub const NSPenPointingDevice: NSUInteger = 1;
+pub const NSCursorPointingDevice: NSUInteger = 2;
+pub const NSEraserPointingDevice: NSUInteger = 3;
+
+// NSEventSubtype
+pub const NSTabletPointEventSubtype: i16 = 1;
+
+// NSBitmapFormat
+pub const NSBitmapFormatAlphaNonpremultiplied: NSUInteger = 1 << 1;
+
+// NSEventButtonMask
+pub const NSPenTipMask: NSUInteger = 1;
+
+// NSWindowOcclusionState
+pub const NSWindowOcclusionStateVisible: NSUInteger = 1 << 1;
+
 pub const kCGDisplayBlendNormal: f32 = 0.0;
 pub const kCGDisplayBlendSolidColor: f32 = 1.0;
 
@@ -175,6 +193,21 @@ pub const IO8BitOverlayPixels: &str = "O8";
 pub type CGWindowLevel = i32;
 pub type CGDisplayModeRef = *mut libc::c_void;
 
+pub type CGConfigureOption = u32;
+pub const kCGConfigureForAppOnly: CGConfigureOption = 0;
+
+pub type CGDisplayChangeSummaryFlags = u32;
+pub const kCGDisplayBeginConfigurationFlag: CGDisplayChangeSummaryFlags = 1 << 0;
+pub const kCGDisplaySetModeFlag: CGDisplayChangeSummaryFlags = 1 << 3;
+pub const kCGDisplayAddFlag: CGDisplayChangeSummaryFlags = 1 << 4;
+pub const kCGDisplayRemoveFlag: CGDisplayChangeSummaryFlags = 1 << 5;
+
+pub type CGDisplayReconfigurationCallBack = extern "C" fn(
+  display: CGDirectDisplayID,
+  flags: CGDisplayChangeSummaryFlags,
+  user_info: *mut c_void,
+);
+
 // `CGDisplayCreateUUIDFromDisplayID` comes from the `ColorSync` framework.
 // However, that framework was only introduced "publicly" in macOS 10.13.
 //
@@ -187,6 +220,24 @@ extern "C" {
   pub fn CGDisplayCreateUUIDFromDisplayID(display: CGDirectDisplayID) -> CFUUIDRef;
 }
 
+pub const NSDisplayGamutP3: NSInteger = 2;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+  pub fn NSBitsPerSampleFromDepth(depth: NSWindowDepth) -> NSInteger;
+}
+
+#[link(name = "CoreServices", kind = "framework")]
+extern "C" {
+  pub static kUTTagClassMIMEType: CFStringRef;
+
+  pub fn UTTypeCreatePreferredIdentifierForTag(
+    inTagClass: CFStringRef,
+    inTag: CFStringRef,
+    inConformingToUTI: CFStringRef,
+  ) -> CFStringRef;
+}
+
 #[link(name = "CoreGraphics", kind = "framework")]
 extern "C" {
   pub fn CGRestorePermanentDisplayConfiguration();
@@ -217,11 +268,25 @@ extern "C" {
   pub fn CGRectContainsPoint(rect: CGRect, point: CGPoint) -> boolean_t;
   pub fn CGReleaseDisplayFadeReservation(token: CGDisplayFadeReservationToken) -> CGError;
   pub fn CGShieldingWindowLevel() -> CGWindowLevel;
+  pub fn CGWindowLevelForKey(key: i32) -> CGWindowLevel;
   pub fn CGDisplaySetDisplayMode(
     display: CGDirectDisplayID,
     mode: CGDisplayModeRef,
     options: CFDictionaryRef,
   ) -> CGError;
+  pub fn CGDisplayCopyDisplayMode(display: CGDirectDisplayID) -> CGDisplayModeRef;
+  pub fn CGBeginDisplayConfiguration(config: *mut CGDisplayConfigRef) -> CGError;
+  pub fn CGConfigureDisplayWithDisplayMode(
+    config: CGDisplayConfigRef,
+    display: CGDirectDisplayID,
+    mode: CGDisplayModeRef,
+    options: CFDictionaryRef,
+  ) -> CGError;
+  pub fn CGCompleteDisplayConfiguration(
+    config: CGDisplayConfigRef,
+    option: CGConfigureOption,
+  ) -> CGError;
+  pub fn CGCancelDisplayConfiguration(config: CGDisplayConfigRef) -> CGError;
   pub fn CGDisplayCopyAllDisplayModes(
     display: CGDirectDisplayID,
     options: CFDictionaryRef,
@@ -232,6 +297,57 @@ extern "C" {
   pub fn CGDisplayModeCopyPixelEncoding(mode: CGDisplayModeRef) -> CFStringRef;
   pub fn CGDisplayModeRetain(mode: CGDisplayModeRef);
   pub fn CGDisplayModeRelease(mode: CGDisplayModeRef);
+  pub fn CGDisplayRegisterReconfigurationCallback(
+    callback: CGDisplayReconfigurationCallBack,
+    user_info: *mut c_void,
+  ) -> CGError;
+  pub fn CGDisplayRemoveReconfigurationCallback(
+    callback: CGDisplayReconfigurationCallBack,
+    user_info: *mut c_void,
+  ) -> CGError;
+}
+
+pub type CGEventTapProxy = *mut c_void;
+pub type CGEventTapCallBack = extern "C" fn(
+  proxy: CGEventTapProxy,
+  event_type: u32,
+  event: *mut c_void,
+  user_info: *mut c_void,
+) -> *mut c_void;
+
+pub const kCGSessionEventTap: u32 = 1;
+pub const kCGHeadInsertEventTap: u32 = 0;
+pub const kCGEventTapOptionDefault: u32 = 0;
+pub const kCGEventTapDisabledByTimeout: u32 = 0xFFFFFFFE;
+pub const kCGEventKeyDown: u32 = 10;
+pub const kCGEventKeyUp: u32 = 11;
+pub const kCGEventFlagsChanged: u32 = 12;
+/// `NSEventTypeSystemDefined`, which carries the media keys.
+pub const kCGEventSystemDefined: u32 = 14;
+/// `NSEvent.subtype` of the system defined events of the media keys.
+pub const NX_SUBTYPE_AUX_CONTROL_BUTTONS: i16 = 8;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+  pub fn CGEventTapCreate(
+    tap: u32,
+    place: u32,
+    options: u32,
+    events_of_interest: u64,
+    callback: CGEventTapCallBack,
+    user_info: *mut c_void,
+  ) -> *mut c_void;
+  pub fn CGEventTapEnable(tap: *mut c_void, enable: bool);
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+  pub fn CFMachPortCreateRunLoopSource(
+    allocator: *const c_void,
+    port: *mut c_void,
+    order: isize,
+  ) -> *mut c_void;
+  pub fn CFMachPortInvalidate(port: *mut c_void);
 }
 
 #[repr(transparent)]
@@ -254,6 +370,9 @@ pub const kUCKeyTranslateNoDeadKeysMask: OptionBits = 1;
 #[link(name = "Carbon", kind = "framework")]
 extern "C" {
   pub static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+  pub static kTISPropertyInputSourceID: CFStringRef;
+  pub static kTISPropertyLocalizedName: CFStringRef;
+  pub static kTISPropertyInputSourceLanguages: CFStringRef;
 
   #[allow(non_snake_case)]
   pub fn TISGetInputSourceProperty(
@@ -263,6 +382,8 @@ extern "C" {
 
   pub fn TISCopyCurrentKeyboardLayoutInputSource() -> TISInputSourceRef;
 
+  pub fn TISCopyCurrentKeyboardInputSource() -> TISInputSourceRef;
+
   pub fn LMGetKbdType() -> u8;
 
   #[allow(non_snake_case)]
@@ -280,6 +401,85 @@ extern "C" {
   ) -> OSStatus;
 }
 
+// Carbon Event Manager, which still is the only way to register system-wide hot keys.
+pub type EventTargetRef = *mut c_void;
+pub type EventHandlerRef = *mut c_void;
+pub type EventHandlerCallRef = *mut c_void;
+pub type EventRef = *mut c_void;
+pub type EventHotKeyRef = *mut c_void;
+pub type EventHandlerProcPtr =
+  extern "C" fn(call_ref: EventHandlerCallRef, event: EventRef, user_data: *mut c_void) -> OSStatus;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventHotKeyID {
+  pub signature: u32,
+  pub id: u32,
+}
+
+#[repr(C)]
+pub struct EventTypeSpec {
+  pub event_class: u32,
+  pub event_kind: u32,
+}
+
+#[allow(non_upper_case_globals)]
+pub const kEventClassKeyboard: u32 = u32::from_be_bytes(*b"keyb");
+#[allow(non_upper_case_globals)]
+pub const kEventHotKeyPressed: u32 = 5;
+#[allow(non_upper_case_globals)]
+pub const kEventParamDirectObject: u32 = u32::from_be_bytes(*b"----");
+#[allow(non_upper_case_globals)]
+pub const typeEventHotKeyID: u32 = u32::from_be_bytes(*b"hkid");
+#[allow(non_upper_case_globals)]
+pub const eventNotHandledErr: OSStatus = -9874;
+#[allow(non_upper_case_globals)]
+pub const cmdKey: u32 = 1 << 8;
+#[allow(non_upper_case_globals)]
+pub const shiftKey: u32 = 1 << 9;
+#[allow(non_upper_case_globals)]
+pub const optionKey: u32 = 1 << 11;
+#[allow(non_upper_case_globals)]
+pub const controlKey: u32 = 1 << 12;
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+  pub fn GetApplicationEventTarget() -> EventTargetRef;
+
+  #[allow(non_snake_case)]
+  pub fn InstallEventHandler(
+    inTarget: EventTargetRef,
+    inHandler: EventHandlerProcPtr,
+    inNumTypes: std::os::raw::c_ulong,
+    inList: *const EventTypeSpec,
+    inUserData: *mut c_void,
+    outRef: *mut EventHandlerRef,
+  ) -> OSStatus;
+
+  #[allow(non_snake_case)]
+  pub fn GetEventParameter(
+    inEvent: EventRef,
+    inName: u32,
+    inDesiredType: u32,
+    outActualType: *mut u32,
+    inBufferSize: std::os::raw::c_ulong,
+    outActualSize: *mut std::os::raw::c_ulong,
+    outData: *mut c_void,
+  ) -> OSStatus;
+
+  #[allow(non_snake_case)]
+  pub fn RegisterEventHotKey(
+    inHotKeyCode: u32,
+    inHotKeyModifiers: u32,
+    inHotKeyID: EventHotKeyID,
+    inTarget: EventTargetRef,
+    inOptions: OptionBits,
+    outRef: *mut EventHotKeyRef,
+  ) -> OSStatus;
+
+  pub fn UnregisterEventHotKey(hot_key: EventHotKeyRef) -> OSStatus;
+}
+
 mod core_video {
   use super::*;
 
@@ -307,6 +507,14 @@ mod core_video {
   // CVDisplayLink.h
 
   pub type CVDisplayLinkRef = *mut c_void;
+  pub type CVDisplayLinkOutputCallback = extern "C" fn(
+    displayLink: CVDisplayLinkRef,
+    inNow: *const c_void,
+    inOutputTime: *const c_void,
+    flagsIn: u64,
+    flagsOut: *mut u64,
+    displayLinkContext: *mut c_void,
+  ) -> CVReturn;
 
   extern "C" {
     pub fn CVDisplayLinkCreateWithCGDisplay(
@@ -316,6 +524,16 @@ mod core_video {
     pub fn CVDisplayLinkGetNominalOutputVideoRefreshPeriod(displayLink: CVDisplayLinkRef)
       -> CVTime;
     pub fn CVDisplayLinkRelease(displayLink: CVDisplayLinkRef);
+    pub fn CVDisplayLinkCreateWithActiveCGDisplays(
+      displayLinkOut: *mut CVDisplayLinkRef,
+    ) -> CVReturn;
+    pub fn CVDisplayLinkSetOutputCallback(
+      displayLink: CVDisplayLinkRef,
+      callback: CVDisplayLinkOutputCallback,
+      userInfo: *mut c_void,
+    ) -> CVReturn;
+    pub fn CVDisplayLinkStart(displayLink: CVDisplayLinkRef) -> CVReturn;
+    pub fn CVDisplayLinkStop(displayLink: CVDisplayLinkRef) -> CVReturn;
   }
 }
 
@@ -0,0 +1,174 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Redraws aligned with the display refresh.
+//!
+//! Windows with frame pacing enabled don't redraw when requested, but are queued until the next
+//! callback of a `CVDisplayLink` for the active displays. The display link is created on the
+//! first paced redraw and keeps running afterwards; idle callbacks only check the queue.
+//!
+//! Windows with jank detection enabled compare the time between their redraws with the refresh
+//! period of their display, as reported by `CVDisplayLink`.
+
+use std::{
+  collections::HashMap,
+  ffi::c_void,
+  ptr,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use super::{
+  app_state::{AppState, RedrawQueue},
+  ffi,
+  monitor::MonitorHandle,
+};
+use crate::window::WindowId;
+
+struct DisplayLink(ffi::CVDisplayLinkRef);
+
+unsafe impl Send for DisplayLink {}
+
+impl Drop for DisplayLink {
+  fn drop(&mut self) {
+    unsafe {
+      ffi::CVDisplayLinkStop(self.0);
+      ffi::CVDisplayLinkRelease(self.0);
+    }
+  }
+}
+
+#[derive(Default)]
+struct FramePacing {
+  display_link: Option<DisplayLink>,
+  pending_redraws: RedrawQueue,
+}
+
+lazy_static! {
+  static ref FRAME_PACING: Mutex<FramePacing> = Mutex::new(FramePacing::default());
+  static ref JANK_DETECTORS: Mutex<HashMap<WindowId, JankDetector>> = Default::default();
+}
+
+/// Redraws `window_id` at the next display refresh.
+pub fn request_redraw(window_id: WindowId) {
+  let mut pacing = FRAME_PACING.lock().unwrap();
+  pacing.pending_redraws.push(window_id);
+  if pacing.display_link.is_none() {
+    pacing.display_link = unsafe { start_display_link() };
+  }
+}
+
+unsafe fn start_display_link() -> Option<DisplayLink> {
+  let mut display_link = ptr::null_mut();
+  if ffi::CVDisplayLinkCreateWithActiveCGDisplays(&mut display_link) != ffi::kCVReturnSuccess {
+    warn!("Failed to create a display link, redraws won't be paced");
+    return None;
+  }
+  ffi::CVDisplayLinkSetOutputCallback(display_link, display_link_callback, ptr::null_mut());
+  ffi::CVDisplayLinkStart(display_link);
+  Some(DisplayLink(display_link))
+}
+
+extern "C" fn display_link_callback(
+  _display_link: ffi::CVDisplayLinkRef,
+  _in_now: *const c_void,
+  _in_output_time: *const c_void,
+  _flags_in: u64,
+  _flags_out: *mut u64,
+  _context: *mut c_void,
+) -> ffi::CVReturn {
+  let pending_redraws = FRAME_PACING.lock().unwrap().pending_redraws.take();
+  for window_id in pending_redraws {
+    AppState::queue_redraw(window_id);
+  }
+  ffi::kCVReturnSuccess
+}
+
+/// Counts the refresh periods a window missed between two of its redraws.
+///
+/// A redraw is only compared with the previous one when it was requested before a refresh period
+/// went by after the previous one finished, so a window which stops redrawing for a while doesn't
+/// report the pause as missed frames.
+struct JankDetector {
+  monitor: MonitorHandle,
+  /// Refresh period of `monitor`, queried at the first redraw on it.
+  refresh_period: Option<Duration>,
+  previous_redraw: Option<Instant>,
+  redraw_finished: Option<Instant>,
+  /// First `request_redraw` since the previous redraw.
+  requested_at: Option<Instant>,
+}
+
+/// Enables jank detection for `window_id`, shown on `monitor`, or disables it with `None`.
+pub fn set_jank_detection(window_id: WindowId, monitor: Option<MonitorHandle>) {
+  let mut jank_detectors = JANK_DETECTORS.lock().unwrap();
+  match monitor {
+    Some(monitor) => {
+      jank_detectors
+        .entry(window_id)
+        .or_insert_with(|| JankDetector {
+          monitor,
+          refresh_period: None,
+          previous_redraw: None,
+          redraw_finished: None,
+          requested_at: None,
+        });
+    }
+    None => {
+      jank_detectors.remove(&window_id);
+    }
+  }
+}
+
+/// Updates the monitor `window_id` is shown on.
+pub fn set_monitor(window_id: WindowId, monitor: MonitorHandle) {
+  if let Some(jank_detector) = JANK_DETECTORS.lock().unwrap().get_mut(&window_id) {
+    if jank_detector.monitor.native_identifier() != monitor.native_identifier() {
+      jank_detector.monitor = monitor;
+      jank_detector.refresh_period = None;
+    }
+  }
+}
+
+pub fn jank_request_redraw(window_id: WindowId) {
+  if let Some(jank_detector) = JANK_DETECTORS.lock().unwrap().get_mut(&window_id) {
+    jank_detector.requested_at.get_or_insert_with(Instant::now);
+  }
+}
+
+/// Returns how many frames `window_id` missed since its previous redraw, and how long they lasted.
+pub fn redraw_requested(window_id: WindowId) -> Option<(u32, Duration)> {
+  let now = Instant::now();
+  let mut jank_detectors = JANK_DETECTORS.lock().unwrap();
+  let jank_detector = jank_detectors.get_mut(&window_id)?;
+  let previous_redraw = jank_detector.previous_redraw.replace(now);
+  let requested_at = jank_detector.requested_at.take();
+  if jank_detector.refresh_period.is_none() {
+    jank_detector.refresh_period = jank_detector
+      .monitor
+      .refresh_rate_millihertz()
+      .filter(|&millihertz| millihertz > 0)
+      .map(|millihertz| Duration::from_nanos(1_000_000_000_000 / u64::from(millihertz)));
+  }
+  let period = jank_detector.refresh_period?;
+  if requested_at? > jank_detector.redraw_finished? + period {
+    return None;
+  }
+
+  let elapsed = now.duration_since(previous_redraw?);
+  let frames = (elapsed.as_secs_f64() / period.as_secs_f64()).round() as u64;
+  if frames <= 1 {
+    return None;
+  }
+  Some((
+    (frames - 1).min(u32::MAX as u64) as u32,
+    elapsed.saturating_sub(period),
+  ))
+}
+
+pub fn redraw_finished(window_id: WindowId) {
+  if let Some(jank_detector) = JANK_DETECTORS.lock().unwrap().get_mut(&window_id) {
+    jank_detector.redraw_finished = Some(Instant::now());
+  }
+}
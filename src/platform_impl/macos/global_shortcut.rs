@@ -0,0 +1,114 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{mem, os::raw::c_void, ptr, sync::Once};
+
+use crate::{
+  error::ExternalError,
+  global_shortcut::{Accelerator, GlobalShortcutId},
+  keyboard::ModifiersState,
+  platform_impl::platform::{app_state::AppState, ffi, OsError},
+};
+
+/// Signature of the hot keys registered by tao, `'tao '`.
+const SIGNATURE: u32 = u32::from_be_bytes(*b"tao ");
+
+/// A hot key registered with the Carbon Event Manager, unregistered when dropped.
+pub struct GlobalShortcut {
+  hot_key: ffi::EventHotKeyRef,
+}
+
+impl Drop for GlobalShortcut {
+  fn drop(&mut self) {
+    unsafe {
+      ffi::UnregisterEventHotKey(self.hot_key);
+    }
+  }
+}
+
+impl GlobalShortcut {
+  pub fn new(id: GlobalShortcutId, accelerator: Accelerator) -> Result<Self, ExternalError> {
+    install_handler();
+
+    let key_code = accelerator
+      .key
+      .to_scancode()
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError::CreationError("Unsupported key"))))?;
+    let mut modifiers = 0;
+    for (modifier, mask) in [
+      (ModifiersState::SHIFT, ffi::shiftKey),
+      (ModifiersState::CONTROL, ffi::controlKey),
+      (ModifiersState::ALT, ffi::optionKey),
+      (ModifiersState::SUPER, ffi::cmdKey),
+    ] {
+      if accelerator.mods.contains(modifier) {
+        modifiers |= mask;
+      }
+    }
+
+    let hot_key_id = ffi::EventHotKeyID {
+      signature: SIGNATURE,
+      id: id.0 as u32,
+    };
+    let mut hot_key = ptr::null_mut();
+    let status = unsafe {
+      ffi::RegisterEventHotKey(
+        key_code,
+        modifiers,
+        hot_key_id,
+        ffi::GetApplicationEventTarget(),
+        0,
+        &mut hot_key,
+      )
+    };
+    if status != 0 || hot_key.is_null() {
+      return Err(ExternalError::Os(os_error!(OsError::CGError(status))));
+    }
+    Ok(GlobalShortcut { hot_key })
+  }
+}
+
+/// Installs the handler of `kEventHotKeyPressed` on the application the first time a shortcut is
+/// registered. It stays installed for the lifetime of the process.
+fn install_handler() {
+  static INSTALL: Once = Once::new();
+  INSTALL.call_once(|| unsafe {
+    let event_type = ffi::EventTypeSpec {
+      event_class: ffi::kEventClassKeyboard,
+      event_kind: ffi::kEventHotKeyPressed,
+    };
+    ffi::InstallEventHandler(
+      ffi::GetApplicationEventTarget(),
+      hot_key_pressed,
+      1,
+      &event_type,
+      ptr::null_mut(),
+      ptr::null_mut(),
+    );
+  });
+}
+
+extern "C" fn hot_key_pressed(
+  _call_ref: ffi::EventHandlerCallRef,
+  event: ffi::EventRef,
+  _user_data: *mut c_void,
+) -> ffi::OSStatus {
+  let mut hot_key_id = ffi::EventHotKeyID::default();
+  let status = unsafe {
+    ffi::GetEventParameter(
+      event,
+      ffi::kEventParamDirectObject,
+      ffi::typeEventHotKeyID,
+      ptr::null_mut(),
+      mem::size_of::<ffi::EventHotKeyID>() as _,
+      ptr::null_mut(),
+      &mut hot_key_id as *mut _ as *mut c_void,
+    )
+  };
+  if status != 0 || hot_key_id.signature != SIGNATURE {
+    return ffi::eventNotHandledErr;
+  }
+  AppState::global_shortcut(GlobalShortcutId(u64::from(hot_key_id.id)));
+  0
+}
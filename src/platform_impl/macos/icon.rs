@@ -11,4 +11,10 @@ impl PlatformIcon {
   pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
     Ok(PlatformIcon(RgbaIcon::from_rgba(rgba, width, height)?))
   }
+
+  pub fn add_variant(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+    // Window icons aren't supported on macOS, so the other sizes are only validated.
+    let _ = RgbaIcon::from_rgba(rgba, width, height)?;
+    Ok(())
+  }
 }
@@ -0,0 +1,104 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keyboard capture for the keyboard grab.
+//!
+//! While the keyboard is grabbed by the key window, an event tap takes the key events and the
+//! media keys out of the session before the system hotkeys (Cmd+Tab, Mission Control, Spotlight,
+//! ...) see them, and posts them to the application. Key events then reach the window as usual,
+//! and the media keys reach the message hook of the event loop as `NSSystemDefined` events.
+//! Creating the tap needs the permission to monitor input.
+
+use std::{ffi::c_void, ptr, sync::Mutex};
+
+use crate::error::ExternalError;
+
+use cocoa::{
+  appkit::NSApp,
+  base::{id, NO},
+};
+
+use super::{
+  ffi,
+  observer::{CFRelease, CFRunLoopAddSource, CFRunLoopGetMain, CFRunLoopSourceInvalidate},
+};
+
+struct KeyboardTap {
+  port: *mut c_void,
+  source: *mut c_void,
+}
+
+unsafe impl Send for KeyboardTap {}
+
+lazy_static! {
+  static ref KEYBOARD_TAP: Mutex<Option<KeyboardTap>> = Mutex::new(None);
+}
+
+/// Starts or stops taking the keyboard from the system.
+pub fn set_keyboard_grabbed(grab: bool) -> Result<(), ExternalError> {
+  let mut tap = KEYBOARD_TAP.lock().unwrap();
+  if grab == tap.is_some() {
+    return Ok(());
+  }
+  unsafe {
+    if let Some(KeyboardTap { port, source }) = tap.take() {
+      ffi::CGEventTapEnable(port, false);
+      CFRunLoopSourceInvalidate(source as _);
+      ffi::CFMachPortInvalidate(port);
+      CFRelease(source);
+      CFRelease(port);
+      return Ok(());
+    }
+
+    let port = ffi::CGEventTapCreate(
+      ffi::kCGSessionEventTap,
+      ffi::kCGHeadInsertEventTap,
+      ffi::kCGEventTapOptionDefault,
+      1 << ffi::kCGEventKeyDown
+        | 1 << ffi::kCGEventKeyUp
+        | 1 << ffi::kCGEventFlagsChanged
+        | 1 << ffi::kCGEventSystemDefined,
+      keyboard_tap,
+      ptr::null_mut(),
+    );
+    if port.is_null() {
+      warn!("Failed to create the keyboard event tap, input monitoring may not be allowed");
+      return Err(ExternalError::Os(os_error!(super::OsError::CGError(0))));
+    }
+    let source = ffi::CFMachPortCreateRunLoopSource(ptr::null(), port, 0);
+    CFRunLoopAddSource(
+      CFRunLoopGetMain(),
+      source as _,
+      super::observer::kCFRunLoopCommonModes,
+    );
+    ffi::CGEventTapEnable(port, true);
+    *tap = Some(KeyboardTap { port, source });
+  }
+  Ok(())
+}
+
+extern "C" fn keyboard_tap(
+  _proxy: ffi::CGEventTapProxy,
+  event_type: u32,
+  event: *mut c_void,
+  _user_info: *mut c_void,
+) -> *mut c_void {
+  unsafe {
+    if event_type == ffi::kCGEventTapDisabledByTimeout {
+      if let Some(tap) = &*KEYBOARD_TAP.lock().unwrap() {
+        ffi::CGEventTapEnable(tap.port, true);
+      }
+      return event;
+    }
+    let ns_event: id = msg_send![class!(NSEvent), eventWithCGEvent: event];
+    if event_type == ffi::kCGEventSystemDefined {
+      let subtype: i16 = msg_send![ns_event, subtype];
+      if subtype != ffi::NX_SUBTYPE_AUX_CONTROL_BUTTONS {
+        return event;
+      }
+    }
+    let () = msg_send![NSApp(), postEvent: ns_event atStart: NO];
+    ptr::null_mut()
+  }
+}
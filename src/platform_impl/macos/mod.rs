@@ -7,14 +7,21 @@
 mod app;
 mod app_delegate;
 mod app_state;
+mod data_format;
 mod event;
 mod event_loop;
 mod ffi;
+mod frame_pacing;
+mod global_shortcut;
 mod icon;
+mod keyboard_grab;
 mod keycode;
 mod monitor;
+#[cfg(feature = "notifications")]
+mod notification;
 mod observer;
 mod progress_bar;
+mod surface_region;
 mod util;
 mod view;
 mod window;
@@ -25,18 +32,24 @@ use std::{fmt, ops::Deref, sync::Arc};
 pub(crate) use self::event_loop::PlatformSpecificEventLoopAttributes;
 pub use self::{
   app_delegate::{get_aux_state_mut, AuxDelegateState},
+  data_format::DataFormat,
   event::KeyEventExtra,
   event_loop::{EventLoop, EventLoopWindowTarget, Proxy as EventLoopProxy},
   keycode::{keycode_from_scancode, keycode_to_scancode},
   monitor::{MonitorHandle, VideoMode},
   progress_bar::set_progress_indicator,
-  window::{Id as WindowId, Parent, PlatformSpecificWindowBuilderAttributes, UnownedWindow},
+  surface_region::SurfaceRegion,
+  window::{
+    CloseToken, Id as WindowId, Parent, PlatformSpecificWindowBuilderAttributes, UnownedWindow,
+  },
 };
 use crate::{
   error::OsError as RootOsError, event::DeviceId as RootDeviceId, window::WindowAttributes,
 };
 
 pub(crate) use icon::PlatformIcon;
+#[cfg(feature = "notifications")]
+pub(crate) use notification::Notifier;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DeviceId;
@@ -84,6 +97,9 @@ impl Window {
     let (window, delegate) = UnownedWindow::new(attributes, pl_attribs)?;
     Ok(Window { window, delegate })
   }
+
+  /// Never called, `sendable_handle` is unsupported.
+  pub fn detach(self) {}
 }
 
 impl fmt::Display for OsError {
@@ -2,19 +2,29 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::VecDeque, fmt};
+use std::{
+  collections::{hash_map::Entry, HashMap, VecDeque},
+  fmt, ptr, slice,
+  sync::Mutex,
+};
 
 use super::{
   ffi::{self, CGRectContainsPoint},
-  util,
+  util, OsError,
 };
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  error::ExternalError,
+  monitor::{
+    ColorInfo, ColorSpace, IccProfile, MonitorHandle as RootMonitorHandle,
+    VideoMode as RootVideoMode,
+  },
+  platform_impl::edid,
+  window::Rect,
 };
 use cocoa::{
-  appkit::{CGPoint, NSScreen},
-  base::{id, nil},
+  appkit::{CGFloat, CGPoint, NSScreen},
+  base::{id, nil, BOOL, YES},
   foundation::NSUInteger,
 };
 use core_foundation::{
@@ -85,6 +95,12 @@ impl Clone for NativeDisplayMode {
   }
 }
 
+lazy_static! {
+  /// Modes the displays had before `MonitorHandle::set_video_mode` changed them.
+  static ref ORIGINAL_MODES: Mutex<HashMap<CGDirectDisplayID, NativeDisplayMode>> =
+    Mutex::new(HashMap::new());
+}
+
 impl VideoMode {
   pub fn size(&self) -> PhysicalSize<u32> {
     self.size.into()
@@ -199,6 +215,10 @@ impl fmt::Debug for MonitorHandle {
   }
 }
 
+/// Returned by `CGDisplayVendorNumber` and `CGDisplayModelNumber` when the EDID can't be read,
+/// `'unkn'`.
+const UNKNOWN_ID: u32 = u32::from_be_bytes(*b"unkn");
+
 impl MonitorHandle {
   pub fn new(id: CGDirectDisplayID) -> Self {
     MonitorHandle(id)
@@ -215,6 +235,62 @@ impl MonitorHandle {
     self.0
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    let refresh_rate = CGDisplay::new(self.0).display_mode()?.refresh_rate();
+    if refresh_rate > 0.0 {
+      return Some((refresh_rate * 1000.0).round() as u32);
+    }
+    // Built-in displays have no refresh rate in their mode, see `video_modes`.
+    unsafe {
+      let mut display_link = std::ptr::null_mut();
+      if ffi::CVDisplayLinkCreateWithCGDisplay(self.0, &mut display_link) != ffi::kCVReturnSuccess {
+        return None;
+      }
+      let time = ffi::CVDisplayLinkGetNominalOutputVideoRefreshPeriod(display_link);
+      ffi::CVDisplayLinkRelease(display_link);
+      if time.flags & ffi::kCVTimeIsIndefinite != 0 || time.time_value <= 0 {
+        return None;
+      }
+      Some((i64::from(time.time_scale) * 1000 / time.time_value) as u32)
+    }
+  }
+
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    let screen = self.ns_screen()?;
+    unsafe {
+      // Available since macOS 12.
+      let has_intervals: BOOL = msg_send![screen, respondsToSelector: sel!(minimumRefreshInterval)];
+      if has_intervals != YES {
+        return None;
+      }
+      let min_interval: f64 = msg_send![screen, minimumRefreshInterval];
+      let max_interval: f64 = msg_send![screen, maximumRefreshInterval];
+      Some(max_interval > min_interval)
+    }
+  }
+
+  pub fn manufacturer(&self) -> Option<String> {
+    match CGDisplay::new(self.0).vendor_number() {
+      UNKNOWN_ID => None,
+      vendor => edid::manufacturer_id(u16::try_from(vendor).ok()?),
+    }
+  }
+
+  pub fn model(&self) -> Option<u32> {
+    match CGDisplay::new(self.0).model_number() {
+      UNKNOWN_ID => None,
+      model => Some(model),
+    }
+  }
+
+  pub fn serial_number(&self) -> Option<String> {
+    edid::serial_number(CGDisplay::new(self.0).serial_number())
+  }
+
+  pub fn is_primary(&self) -> bool {
+    CGDisplay::new(self.0).is_main()
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     let MonitorHandle(display_id) = *self;
     let display = CGDisplay::new(display_id);
@@ -232,6 +308,31 @@ impl MonitorHandle {
     )
   }
 
+  pub fn work_area(&self) -> Rect {
+    let frame = match self.ns_screen() {
+      Some(screen) => unsafe { NSScreen::visibleFrame(screen) },
+      None => {
+        return Rect {
+          position: self.position().into(),
+          size: self.size().into(),
+        }
+      }
+    };
+    let scale_factor = self.scale_factor();
+    Rect {
+      position: PhysicalPosition::<i32>::from_logical::<_, f64>(
+        (frame.origin.x, util::bottom_left_to_top_left(frame)),
+        scale_factor,
+      )
+      .into(),
+      size: PhysicalSize::<u32>::from_logical::<_, f64>(
+        (frame.size.width, frame.size.height),
+        scale_factor,
+      )
+      .into(),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     let screen = match self.ns_screen() {
       Some(screen) => screen,
@@ -313,6 +414,82 @@ impl MonitorHandle {
     }
   }
 
+  pub fn color_info(&self) -> ColorInfo {
+    let mut info = ColorInfo::default();
+    let screen = match self.ns_screen() {
+      Some(screen) => screen,
+      None => return info,
+    };
+    unsafe {
+      let bits = ffi::NSBitsPerSampleFromDepth(NSScreen::depth(screen));
+      if bits > 0 {
+        info.bits_per_color = Some(bits as u32);
+      }
+      let can_show_p3: BOOL = msg_send![screen, canRepresentDisplayGamut: ffi::NSDisplayGamutP3];
+      info.color_space = if can_show_p3 == YES {
+        ColorSpace::DisplayP3
+      } else {
+        ColorSpace::Srgb
+      };
+      // Available since macOS 10.15.
+      let has_edr: BOOL = msg_send![
+        screen,
+        respondsToSelector: sel!(maximumPotentialExtendedDynamicRangeColorComponentValue)
+      ];
+      if has_edr == YES {
+        let potential: CGFloat = msg_send![
+          screen,
+          maximumPotentialExtendedDynamicRangeColorComponentValue
+        ];
+        let current: CGFloat = msg_send![screen, maximumExtendedDynamicRangeColorComponentValue];
+        info.hdr_enabled = potential > 1.0;
+        info.hdr_headroom = Some(current as f32);
+      }
+    }
+    info
+  }
+
+  pub fn set_video_mode(&self, video_mode: &VideoMode) -> Result<(), ExternalError> {
+    let mut original_modes = ORIGINAL_MODES.lock().unwrap();
+    if let Entry::Vacant(entry) = original_modes.entry(self.0) {
+      let mode = unsafe { ffi::CGDisplayCopyDisplayMode(self.0) };
+      if !mode.is_null() {
+        entry.insert(NativeDisplayMode(mode));
+      }
+    }
+    configure_display_mode(self.0, &video_mode.native_mode)
+  }
+
+  pub fn reset_video_mode(&self) -> Result<(), ExternalError> {
+    let mode = ORIGINAL_MODES.lock().unwrap().remove(&self.0);
+    match mode {
+      Some(mode) => configure_display_mode(self.0, &mode),
+      None => Ok(()),
+    }
+  }
+
+  pub fn icc_profile(&self) -> Option<IccProfile> {
+    let screen = self.ns_screen()?;
+    unsafe {
+      let color_space: id = msg_send![screen, colorSpace];
+      if color_space == nil {
+        return None;
+      }
+      let data: id = msg_send![color_space, ICCProfileData];
+      if data == nil {
+        return None;
+      }
+      let length: NSUInteger = msg_send![data, length];
+      let bytes: *const u8 = msg_send![data, bytes];
+      if bytes.is_null() || length == 0 {
+        return None;
+      }
+      Some(IccProfile::Data(
+        slice::from_raw_parts(bytes, length as usize).to_vec(),
+      ))
+    }
+  }
+
   pub(crate) fn ns_screen(&self) -> Option<id> {
     unsafe {
       let uuid = ffi::CGDisplayCreateUUIDFromDisplayID(self.0);
@@ -336,3 +513,32 @@ impl MonitorHandle {
     }
   }
 }
+
+/// Switches `display` to `mode` for the application only, so that the system restores the mode of
+/// the user when it exits.
+fn configure_display_mode(
+  display: CGDirectDisplayID,
+  mode: &NativeDisplayMode,
+) -> Result<(), ExternalError> {
+  let check = |status| match status {
+    ffi::kCGErrorSuccess => Ok(()),
+    status => Err(ExternalError::Os(os_error!(OsError::CGError(status)))),
+  };
+  unsafe {
+    let mut config = ptr::null_mut();
+    check(ffi::CGBeginDisplayConfiguration(&mut config))?;
+    if let Err(e) = check(ffi::CGConfigureDisplayWithDisplayMode(
+      config,
+      display,
+      mode.0,
+      ptr::null(),
+    )) {
+      ffi::CGCancelDisplayConfiguration(config);
+      return Err(e);
+    }
+    check(ffi::CGCompleteDisplayConfiguration(
+      config,
+      ffi::kCGConfigureForAppOnly,
+    ))
+  }
+}
@@ -0,0 +1,141 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notifications of `NSUserNotificationCenter`, see `Notifier::show`.
+
+use std::os::raw::c_void;
+
+use cocoa::base::{id, nil};
+use objc::{
+  declare::ClassDecl,
+  runtime::{Class, Object, Sel, BOOL, YES},
+};
+
+use crate::{
+  error::{ExternalError, NotSupportedError},
+  notification::{ActivationHandler, Notification, NotificationId},
+  platform_impl::platform::{
+    util::{self, IdRef},
+    EventLoopWindowTarget,
+  },
+};
+
+/// Key of the id of the notification in its user info.
+const ID_KEY: &str = "taoNotificationId";
+
+struct DelegateClass(*const Class);
+unsafe impl Send for DelegateClass {}
+unsafe impl Sync for DelegateClass {}
+
+lazy_static! {
+  static ref DELEGATE_CLASS: DelegateClass = unsafe {
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("TaoNotificationDelegate", superclass).unwrap();
+    decl.add_method(
+      sel!(userNotificationCenter:didActivateNotification:),
+      did_activate_notification as extern "C" fn(&Object, Sel, id, id),
+    );
+    decl.add_method(
+      sel!(userNotificationCenter:shouldPresentNotification:),
+      should_present_notification as extern "C" fn(&Object, Sel, id, id) -> BOOL,
+    );
+    decl.add_ivar::<*mut c_void>("taoOnActivated");
+    DelegateClass(decl.register())
+  };
+}
+
+pub struct Notifier {
+  /// `nil` when the application doesn't run from a bundle.
+  center: id,
+  delegate: IdRef,
+  /// Pointed to by the delegate.
+  _on_activated: Box<ActivationHandler>,
+}
+
+// `NSUserNotificationCenter` can be used from any thread.
+unsafe impl Send for Notifier {}
+unsafe impl Sync for Notifier {}
+
+impl Notifier {
+  pub fn new<T>(_target: &EventLoopWindowTarget<T>, on_activated: ActivationHandler) -> Self {
+    let on_activated = Box::new(on_activated);
+    unsafe {
+      let center: id = msg_send![
+        class!(NSUserNotificationCenter),
+        defaultUserNotificationCenter
+      ];
+      let delegate: id = msg_send![DELEGATE_CLASS.0, new];
+      (*delegate).set_ivar(
+        "taoOnActivated",
+        &*on_activated as *const ActivationHandler as *mut c_void,
+      );
+      if center != nil {
+        let _: () = msg_send![center, setDelegate: delegate];
+      }
+      Self {
+        center,
+        delegate: IdRef::new(delegate),
+        _on_activated: on_activated,
+      }
+    }
+  }
+
+  pub fn show(&self, id: NotificationId, notification: &Notification) -> Result<(), ExternalError> {
+    if self.center == nil {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    unsafe {
+      let ns_notification = IdRef::new(msg_send![class!(NSUserNotification), new]);
+      let title = util::ns_string_id_ref(&notification.title);
+      let _: () = msg_send![*ns_notification, setTitle: *title];
+      let body = util::ns_string_id_ref(&notification.body);
+      let _: () = msg_send![*ns_notification, setInformativeText: *body];
+      let number: id = msg_send![class!(NSNumber), numberWithUnsignedLongLong: id.0];
+      let key = util::ns_string_id_ref(ID_KEY);
+      let user_info: id =
+        msg_send![class!(NSDictionary), dictionaryWithObject: number forKey: *key];
+      let _: () = msg_send![*ns_notification, setUserInfo: user_info];
+      let _: () = msg_send![self.center, deliverNotification: *ns_notification];
+    }
+    Ok(())
+  }
+}
+
+impl Drop for Notifier {
+  fn drop(&mut self) {
+    unsafe {
+      let delegate: id = msg_send![self.center, delegate];
+      if self.center != nil && delegate == *self.delegate {
+        let _: () = msg_send![self.center, setDelegate: nil];
+      }
+    }
+  }
+}
+
+extern "C" fn did_activate_notification(this: &Object, _sel: Sel, _center: id, notification: id) {
+  unsafe {
+    let user_info: id = msg_send![notification, userInfo];
+    if user_info == nil {
+      return;
+    }
+    let key = util::ns_string_id_ref(ID_KEY);
+    let number: id = msg_send![user_info, objectForKey: *key];
+    if number == nil {
+      return;
+    }
+    let value: u64 = msg_send![number, unsignedLongLongValue];
+    let on_activated = *this.get_ivar::<*mut c_void>("taoOnActivated") as *const ActivationHandler;
+    (*on_activated)(NotificationId(value));
+  }
+}
+
+// Show the notifications while the application is active too.
+extern "C" fn should_present_notification(
+  _this: &Object,
+  _sel: Sel,
+  _center: id,
+  _notification: id,
+) -> BOOL {
+  YES
+}
@@ -8,21 +8,29 @@ use std::{
   panic::{AssertUnwindSafe, UnwindSafe},
   ptr,
   rc::Weak,
-  time::Instant,
+  sync::atomic::{AtomicBool, Ordering},
+  time::{Duration, Instant},
 };
 
-use crate::platform_impl::platform::{
-  app_state::AppState,
-  event_loop::{stop_app_on_panic, PanicInfo},
-  ffi,
+use crate::{
+  event::Event,
+  event_loop::TimerId,
+  platform_impl::platform::{
+    app_state::AppState,
+    event::EventWrapper,
+    event_loop::{stop_app_on_panic, PanicInfo},
+    ffi,
+  },
 };
 
 #[link(name = "CoreFoundation", kind = "framework")]
 extern "C" {
   pub static kCFRunLoopCommonModes: CFRunLoopMode;
+  pub static kCFRunLoopDefaultMode: CFRunLoopMode;
 
   pub fn CFRunLoopGetMain() -> CFRunLoopRef;
   pub fn CFRunLoopWakeUp(rl: CFRunLoopRef);
+  pub fn CFRunLoopCopyCurrentMode(rl: CFRunLoopRef) -> CFRunLoopMode;
 
   pub fn CFRunLoopObserverCreate(
     allocator: CFAllocatorRef,
@@ -50,6 +58,8 @@ extern "C" {
   pub fn CFRunLoopAddTimer(rl: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFRunLoopMode);
   pub fn CFRunLoopTimerSetNextFireDate(timer: CFRunLoopTimerRef, fireDate: CFAbsoluteTime);
   pub fn CFRunLoopTimerInvalidate(time: CFRunLoopTimerRef);
+  pub fn CFRunLoopTimerIsValid(timer: CFRunLoopTimerRef) -> ffi::Boolean;
+  pub fn CFRunLoopTimerSetTolerance(timer: CFRunLoopTimerRef, tolerance: CFTimeInterval);
 
   pub fn CFRunLoopSourceCreate(
     allocator: CFAllocatorRef,
@@ -62,6 +72,7 @@ extern "C" {
   pub fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
 
   pub fn CFAbsoluteTimeGetCurrent() -> CFAbsoluteTime;
+  pub fn CFEqual(cf1: *const c_void, cf2: *const c_void) -> ffi::Boolean;
   pub fn CFRelease(cftype: *const c_void);
 }
 
@@ -100,7 +111,17 @@ pub type CFRunLoopObserverCallBack =
   extern "C" fn(observer: CFRunLoopObserverRef, activity: CFRunLoopActivity, info: *mut c_void);
 pub type CFRunLoopTimerCallBack = extern "C" fn(timer: CFRunLoopTimerRef, info: *mut c_void);
 
-pub enum CFRunLoopTimerContext {}
+/// This mirrors the struct with the same name from Core Foundation.
+/// https://developer.apple.com/documentation/corefoundation/cfrunlooptimercontext?language=objc
+#[allow(non_snake_case)]
+#[repr(C)]
+pub struct CFRunLoopTimerContext {
+  pub version: CFIndex,
+  pub info: *mut c_void,
+  pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+  pub release: Option<extern "C" fn(info: *const c_void)>,
+  pub copyDescription: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+}
 
 /// This mirrors the struct with the same name from Core Foundation.
 /// https://developer.apple.com/documentation/corefoundation/cfrunloopobservercontext?language=objc
@@ -129,6 +150,28 @@ pub struct CFRunLoopSourceContext {
   pub perform: Option<extern "C" fn(*mut c_void)>,
 }
 
+/// Whether events are processed in every common run loop mode the observers are added to, or only
+/// in the default mode.
+static COMMON_MODES: AtomicBool = AtomicBool::new(true);
+
+pub fn set_common_modes(enabled: bool) {
+  COMMON_MODES.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether events should be processed in the mode the main run loop is currently running in.
+unsafe fn is_current_mode_enabled() -> bool {
+  if COMMON_MODES.load(Ordering::Relaxed) {
+    return true;
+  }
+  let mode = CFRunLoopCopyCurrentMode(CFRunLoopGetMain());
+  if mode.is_null() {
+    return true;
+  }
+  let is_default = CFEqual(mode as _, kCFRunLoopDefaultMode as _) != ffi::FALSE;
+  CFRelease(mode as _);
+  is_default
+}
+
 unsafe fn control_flow_handler<F>(panic_info: *mut c_void, f: F)
 where
   F: FnOnce(Weak<PanicInfo>) + UnwindSafe,
@@ -143,6 +186,11 @@ where
   // However we want to keep that weak reference around after the function.
   std::mem::forget(info_from_raw);
 
+  // Events stay queued until the run loop is back in the default mode.
+  if !is_current_mode_enabled() {
+    return;
+  }
+
   stop_app_on_panic(Weak::clone(&panic_info), move || {
     let _ = &panic_info;
     f(panic_info.0)
@@ -274,6 +322,9 @@ impl Default for EventLoopWaker {
         wakeup_main_loop,
         ptr::null_mut(),
       );
+      // Don't let the system coalesce the timer with others, `ControlFlow::WaitUntil` is used to
+      // schedule animation frames.
+      CFRunLoopTimerSetTolerance(timer, 0.0);
       CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
       EventLoopWaker { timer }
     }
@@ -303,3 +354,62 @@ impl EventLoopWaker {
     }
   }
 }
+
+/// A timer of `EventLoopWindowTarget::set_timer`, which stops firing when dropped.
+pub struct Timer {
+  timer: CFRunLoopTimerRef,
+}
+
+impl Drop for Timer {
+  fn drop(&mut self) {
+    unsafe {
+      CFRunLoopTimerInvalidate(self.timer);
+      CFRelease(self.timer as _);
+    }
+  }
+}
+
+impl Timer {
+  pub fn new(id: TimerId, interval: Duration, repeating: bool) -> Timer {
+    extern "C" fn fire(_timer: CFRunLoopTimerRef, info: *mut c_void) {
+      let id = unsafe { *(info as *const TimerId) };
+      AppState::queue_event(EventWrapper::StaticEvent(Event::Timer(id)));
+    }
+    extern "C" fn release(info: *const c_void) {
+      drop(unsafe { Box::from_raw(info as *mut TimerId) });
+    }
+
+    let interval = interval.as_secs_f64();
+    let mut context = CFRunLoopTimerContext {
+      version: 0,
+      info: Box::into_raw(Box::new(id)) as *mut c_void,
+      retain: None,
+      release: Some(release),
+      copyDescription: None,
+    };
+    unsafe {
+      // A timer with an interval of 0 fires once, like the waker it shouldn't be shorter than
+      // 0.1µs when repeating.
+      let timer = CFRunLoopTimerCreate(
+        ptr::null_mut(),
+        CFAbsoluteTimeGetCurrent() + interval,
+        if repeating {
+          interval.max(0.000_000_1)
+        } else {
+          0.0
+        },
+        0,
+        0,
+        fire,
+        &mut context,
+      );
+      CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
+      Timer { timer }
+    }
+  }
+
+  /// Returns whether the timer will fire again.
+  pub fn is_valid(&self) -> bool {
+    unsafe { CFRunLoopTimerIsValid(self.timer) != ffi::FALSE }
+  }
+}
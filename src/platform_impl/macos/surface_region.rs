@@ -0,0 +1,98 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Child views reserved for embedders, see `Window::create_surface_region`.
+//!
+//! Views are sized in points, which already follow the backing scale factor, so only rects given
+//! in physical pixels are converted, with the scale factor of the moment.
+
+use std::cell::Cell;
+
+use cocoa::{
+  appkit::{NSViewMinYMargin, NSWindowOrderingMode},
+  base::{id, nil, NO, YES},
+  foundation::{NSPoint, NSRect, NSSize},
+};
+
+use crate::{platform_impl::platform::util::IdRef, window::Rect};
+
+pub struct SurfaceRegion {
+  ns_view: IdRef,
+  rect: Cell<Rect>,
+}
+
+impl SurfaceRegion {
+  pub fn new(parent: id, rect: Rect) -> Self {
+    let ns_view = unsafe {
+      let ns_view: id = msg_send![class!(NSView), alloc];
+      let ns_view = IdRef::new(msg_send![ns_view, initWithFrame: NSRect::new(
+        NSPoint::new(0.0, 0.0),
+        NSSize::new(0.0, 0.0),
+      )]);
+      let _: () = msg_send![*ns_view, setWantsLayer: YES];
+      // The parent isn't flipped, keep the distance to its top edge when it is resized.
+      let _: () = msg_send![*ns_view, setAutoresizingMask: NSViewMinYMargin];
+      let _: () = msg_send![
+        parent,
+        addSubview: *ns_view
+        positioned: NSWindowOrderingMode::NSWindowBelow
+        relativeTo: nil
+      ];
+      ns_view
+    };
+    let region = Self {
+      ns_view,
+      rect: Cell::new(rect),
+    };
+    region.set_rect(rect);
+    region
+  }
+
+  pub fn set_rect(&self, rect: Rect) {
+    self.rect.set(rect);
+    unsafe {
+      let parent: id = msg_send![*self.ns_view, superview];
+      let window: id = msg_send![parent, window];
+      let scale_factor: f64 = msg_send![window, backingScaleFactor];
+      let parent_frame: NSRect = msg_send![parent, frame];
+      let position = rect.position.to_logical::<f64>(scale_factor);
+      let size = rect.size.to_logical::<f64>(scale_factor);
+      let frame = NSRect::new(
+        NSPoint::new(
+          position.x,
+          parent_frame.size.height - position.y - size.height,
+        ),
+        NSSize::new(size.width, size.height),
+      );
+      let _: () = msg_send![*self.ns_view, setFrame: frame];
+    }
+  }
+
+  pub fn rect(&self) -> Rect {
+    self.rect.get()
+  }
+
+  pub fn set_visible(&self, visible: bool) {
+    unsafe {
+      let _: () = msg_send![*self.ns_view, setHidden: if visible { NO } else { YES }];
+    }
+  }
+
+  #[cfg(feature = "rwh_06")]
+  pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+    let window_handle = rwh_06::AppKitWindowHandle::new({
+      let ptr = *self.ns_view as *mut std::ffi::c_void;
+      std::ptr::NonNull::new(ptr).expect("Id<T> should never be null")
+    });
+    Ok(rwh_06::RawWindowHandle::AppKit(window_handle))
+  }
+}
+
+impl Drop for SurfaceRegion {
+  fn drop(&mut self) {
+    unsafe {
+      let _: () = msg_send![*self.ns_view, removeFromSuperview];
+    }
+  }
+}
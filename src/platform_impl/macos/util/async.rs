@@ -5,6 +5,7 @@
 use std::{
   ops::Deref,
   sync::{Mutex, Weak},
+  time::Duration,
 };
 
 use cocoa::{
@@ -14,13 +15,18 @@ use cocoa::{
 };
 use dispatch::Queue;
 use objc::{
-  rc::autoreleasepool,
+  rc::{autoreleasepool, WeakPtr},
   runtime::{BOOL, NO, YES},
 };
 
 use crate::{
   dpi::LogicalSize,
-  platform_impl::platform::{ffi, util::IdRef, window::SharedState},
+  platform_impl::platform::{
+    ffi,
+    util::{Cursor, IdRef},
+    view::CursorState,
+    window::SharedState,
+  },
 };
 
 pub fn is_main_thread() -> bool {
@@ -101,9 +107,14 @@ pub unsafe fn set_frame_top_left_point_async(ns_window: id, point: NSPoint) {
 
 // `setFrameTopLeftPoint:` isn't thread-safe, and fails silently.
 pub unsafe fn set_level_async(ns_window: id, level: ffi::NSWindowLevel) {
+  set_raw_level_async(ns_window, level as _);
+}
+
+/// Sets a level which `ffi::NSWindowLevel` doesn't have, e.g. from `CGWindowLevelForKey`.
+pub unsafe fn set_raw_level_async(ns_window: id, level: i64) {
   let ns_window = MainThreadSafe(ns_window);
   Queue::main().exec_async(move || {
-    ns_window.setLevel_(level as _);
+    ns_window.setLevel_(level);
   });
 }
 
@@ -250,6 +261,37 @@ pub unsafe fn close_async(ns_window: IdRef) {
   });
 }
 
+// Only loads the weak reference on the main thread, so the window is neither kept alive nor
+// released on another thread, and does nothing if it was already deallocated.
+pub fn close_weak_async(ns_window: WeakPtr) {
+  let ns_window = MainThreadSafe(ns_window);
+  let close = move || {
+    autoreleasepool(move || {
+      let ns_window = ns_window.load();
+      if !ns_window.is_null() {
+        unsafe { ns_window.close() };
+      }
+    });
+  };
+  if is_main_thread() {
+    close();
+  } else {
+    Queue::main().exec_async(close);
+  }
+}
+
+// Goes through `windowShouldClose:` rather than `performClose:`, which only works (and
+// otherwise beeps) when the window has a close button.
+pub unsafe fn request_close_async(ns_window: IdRef) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    let delegate: id = msg_send![**ns_window, delegate];
+    if delegate != nil {
+      let _: BOOL = msg_send![delegate, windowShouldClose: **ns_window];
+    }
+  });
+}
+
 // `setIgnoresMouseEvents_:` isn't thread-safe, and fails silently.
 pub unsafe fn set_ignore_mouse_events(ns_window: id, ignore: bool) {
   let ns_window = MainThreadSafe(ns_window);
@@ -257,3 +299,40 @@ pub unsafe fn set_ignore_mouse_events(ns_window: id, ignore: bool) {
     ns_window.setIgnoresMouseEvents_(if ignore { YES } else { NO });
   });
 }
+
+// Shows the next frame of an animated cursor after `delay`, as long as the cursor wasn't changed
+// since animation `animation` started.
+pub unsafe fn animate_cursor(
+  cursor_state: Weak<Mutex<CursorState>>,
+  ns_view: id,
+  animation: usize,
+  delay: Duration,
+) {
+  let ns_view = MainThreadSafe(ns_view);
+  Queue::main().exec_after(delay, move || {
+    // The view owns the cursor state, so it's gone with the view.
+    let cursor_access = match cursor_state.upgrade() {
+      Some(cursor_access) => cursor_access,
+      None => return,
+    };
+    let mut state = cursor_access.lock().unwrap();
+    if state.animation != animation {
+      return;
+    }
+    let delay = match &mut state.cursor {
+      Cursor::Custom(cursor, frame) if !cursor.frames.is_empty() => {
+        *frame = (*frame + 1) % cursor.frames.len();
+        cursor.frame_delay(*frame)
+      }
+      _ => None,
+    };
+    drop(state);
+    if let Some(delay) = delay {
+      let ns_window: id = msg_send![*ns_view, window];
+      if ns_window != nil {
+        let _: () = msg_send![ns_window, invalidateCursorRectsForView: *ns_view];
+      }
+      animate_cursor(cursor_state, *ns_view, animation, delay);
+    }
+  });
+}
@@ -5,15 +5,18 @@
 use cocoa::{
   appkit::NSImage,
   base::{id, nil},
-  foundation::{NSDictionary, NSPoint, NSString},
+  foundation::{NSDictionary, NSInteger, NSPoint, NSSize, NSString},
 };
-use objc::runtime::{Sel, NO};
+use objc::runtime::{Sel, NO, YES};
 use std::{cell::RefCell, ptr::null_mut};
 
-use crate::window::CursorIcon;
+use super::super::ffi::NSBitmapFormatAlphaNonpremultiplied;
+use crate::{icon::CustomCursor, window::CursorIcon};
 
 pub enum Cursor {
   Default,
+  /// A custom cursor and the frame shown if it's animated.
+  Custom(CustomCursor, usize),
   Native(&'static str),
   Undocumented(&'static str),
   WebKit(&'static str),
@@ -100,6 +103,7 @@ impl Cursor {
         msg_send![class, performSelector: sel]
       }
       Cursor::WebKit(cursor_name) => load_webkit_cursor(cursor_name),
+      Cursor::Custom(cursor, frame) => load_custom_cursor(cursor.frame(*frame)),
     }
   }
 }
@@ -131,6 +135,53 @@ pub unsafe fn load_webkit_cursor(cursor_name: &str) -> id {
   ]
 }
 
+/// Creates an autoreleased `NSCursor` whose image holds a representation for every image of
+/// `cursor`, letting AppKit pick the one matching the screen and the cursor size setting.
+pub unsafe fn load_custom_cursor(cursor: &CustomCursor) -> id {
+  let base = &cursor.images[0];
+  let size = NSSize::new(base.rgba.width as f64, base.rgba.height as f64);
+  let ns_image: id = msg_send![class!(NSImage), alloc];
+  let ns_image: id = msg_send![ns_image, initWithSize: size];
+  let color_space = NSString::alloc(nil).init_str("NSDeviceRGBColorSpace");
+
+  for image in &cursor.images {
+    let (width, height) = (
+      image.rgba.width as NSInteger,
+      image.rgba.height as NSInteger,
+    );
+    let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let rep: id = msg_send![rep,
+        initWithBitmapDataPlanes:null_mut::<*mut u8>()
+        pixelsWide:width
+        pixelsHigh:height
+        bitsPerSample:8 as NSInteger
+        samplesPerPixel:4 as NSInteger
+        hasAlpha:YES
+        isPlanar:NO
+        colorSpaceName:color_space
+        bitmapFormat:NSBitmapFormatAlphaNonpremultiplied
+        bytesPerRow:width * 4
+        bitsPerPixel:32 as NSInteger
+    ];
+    if rep == nil {
+      continue;
+    }
+    let data: *mut u8 = msg_send![rep, bitmapData];
+    std::ptr::copy_nonoverlapping(image.rgba.rgba.as_ptr(), data, image.rgba.rgba.len());
+    let _: () = msg_send![rep, setSize: size];
+    let _: () = msg_send![ns_image, addRepresentation: rep];
+    let _: () = msg_send![rep, release];
+  }
+
+  let _: () = msg_send![color_space, release];
+
+  let hotspot = NSPoint::new(base.hotspot_x as f64, base.hotspot_y as f64);
+  let ns_cursor: id = msg_send![class!(NSCursor), alloc];
+  let ns_cursor: id = msg_send![ns_cursor, initWithImage:ns_image hotSpot:hotspot];
+  let _: () = msg_send![ns_image, release];
+  msg_send![ns_cursor, autorelease]
+}
+
 pub unsafe fn invisible_cursor() -> id {
   // 16x16 GIF data for invisible cursor
   // You can reproduce this via ImageMagick.
@@ -18,13 +18,17 @@ use cocoa::{
   foundation::{NSAutoreleasePool, NSPoint, NSRect, NSString, NSUInteger},
 };
 use core_graphics::{
+  base::CGFloat,
   display::CGDisplay,
   event::CGEvent,
   event_source::{CGEventSource, CGEventSourceStateID},
 };
-use objc::runtime::{Class, Object, Sel, BOOL, YES};
+use objc::runtime::{Class, Object, BOOL, NO, YES};
 
-use crate::{dpi::LogicalPosition, error::ExternalError, platform_impl::platform::ffi};
+use crate::{
+  dpi::LogicalPosition, error::ExternalError, platform_impl::platform::ffi,
+  window::SystemAppearance,
+};
 
 // Replace with `!` once stable
 #[derive(Debug)]
@@ -150,10 +154,6 @@ pub unsafe fn open_emoji_picker() {
   let () = msg_send![NSApp(), orderFrontCharacterPalette: nil];
 }
 
-pub extern "C" fn yes(_: &Object, _: Sel) -> BOOL {
-  YES
-}
-
 pub unsafe fn toggle_style_mask(window: id, view: id, mask: NSWindowStyleMask, on: bool) {
   use cocoa::appkit::NSWindow;
 
@@ -167,3 +167,57 @@ pub unsafe fn toggle_style_mask(window: id, view: id, mask: NSWindowStyleMask, o
   // If we don't do this, key handling will break. Therefore, never call `setStyleMask` directly!
   window.makeFirstResponder_(view);
 }
+
+/// Reads the appearance preferences of the system, which change along with
+/// `NSWorkspaceAccessibilityDisplayOptionsDidChangeNotification` and
+/// `NSSystemColorsDidChangeNotification`.
+pub fn system_appearance() -> SystemAppearance {
+  unsafe {
+    let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+    let high_contrast: BOOL = msg_send![workspace, accessibilityDisplayShouldIncreaseContrast];
+    let reduce_motion: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+    let reduce_transparency: BOOL =
+      msg_send![workspace, accessibilityDisplayShouldReduceTransparency];
+    SystemAppearance {
+      accent_color: accent_color(),
+      high_contrast: high_contrast == YES,
+      reduce_motion: reduce_motion == YES,
+      reduce_transparency: reduce_transparency == YES,
+    }
+  }
+}
+
+unsafe fn accent_color() -> Option<[u8; 4]> {
+  // `controlAccentColor` is only available since macOS 10.14.
+  let responds: BOOL = msg_send![class!(NSColor), respondsToSelector: sel!(controlAccentColor)];
+  if responds == NO {
+    return None;
+  }
+  let color: id = msg_send![class!(NSColor), controlAccentColor];
+  let srgb: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+  let color: id = msg_send![color, colorUsingColorSpace: srgb];
+  if color == nil {
+    return None;
+  }
+  let (mut r, mut g, mut b, mut a): (CGFloat, CGFloat, CGFloat, CGFloat) = (0.0, 0.0, 0.0, 0.0);
+  let () = msg_send![color, getRed: &mut r green: &mut g blue: &mut b alpha: &mut a];
+  Some([r, g, b, a].map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+/// BCP 47 language tag of the current locale, which changes along with
+/// `NSCurrentLocaleDidChangeNotification`.
+pub fn current_locale() -> Option<String> {
+  unsafe {
+    let locale: id = msg_send![class!(NSLocale), currentLocale];
+    let identifier: id = msg_send![locale, localeIdentifier];
+    if identifier == nil {
+      return None;
+    }
+    // Identifiers look like `en_US@calendar=gregorian`.
+    let identifier = ns_string_to_rust(identifier);
+    identifier
+      .split('@')
+      .next()
+      .map(|identifier| identifier.replace('_', "-"))
+  }
+}
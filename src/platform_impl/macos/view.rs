@@ -11,26 +11,31 @@ use std::{
 };
 
 use cocoa::{
-  appkit::{NSApp, NSEvent, NSEventModifierFlags, NSEventPhase, NSView, NSWindow, NSWindowButton},
+  appkit::{
+    NSApp, NSEvent, NSEventModifierFlags, NSEventPhase, NSEventType, NSView, NSWindow,
+    NSWindowButton,
+  },
   base::{id, nil},
   foundation::{NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger},
 };
+use core_graphics::{display::CGDisplay, geometry::CGPoint};
 use objc::{
   declare::ClassDecl,
   runtime::{Class, Object, Protocol, Sel, BOOL, NO, YES},
 };
 
 use crate::{
-  dpi::LogicalPosition,
+  dpi::{LogicalPosition, LogicalSize},
   event::{
-    DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+    DeviceEvent, ElementState, Event, Ime, MouseButton, MouseScrollDelta, PenInput, ScrollSource,
+    SwipeDirection, TabletTool, TouchPhase, WindowEvent,
   },
-  keyboard::{KeyCode, ModifiersState},
+  keyboard::{DeadKeyState, KeyCode, ModifiersState, RepeatCounter},
   platform_impl::platform::{
     app_state::AppState,
     event::{code_to_key, create_key_event, event_mods, get_scancode, EventWrapper},
     ffi::*,
-    util::{self, IdRef},
+    util::{self, ns_string_to_rust, IdRef},
     window::get_window_id,
     DEVICE_ID,
   },
@@ -40,6 +45,12 @@ use crate::{
 pub struct CursorState {
   pub visible: bool,
   pub cursor: util::Cursor,
+  /// Whether the cursor is grabbed with `CursorGrabMode::Confined`.
+  pub confined: bool,
+  /// Whether `WindowEvent::RawMouseMotion` is emitted while the window is key.
+  pub raw_mouse_motion: bool,
+  /// Incremented each time the cursor changes, which stops the animation of the previous one.
+  pub animation: usize,
 }
 
 impl Default for CursorState {
@@ -47,6 +58,9 @@ impl Default for CursorState {
     Self {
       visible: true,
       cursor: Default::default(),
+      confined: false,
+      raw_mouse_motion: false,
+      animation: 0,
     }
   }
 }
@@ -54,7 +68,7 @@ impl Default for CursorState {
 pub(super) struct ViewState {
   ns_window: id,
   pub cursor_state: Arc<Mutex<CursorState>>,
-  ime_spot: Option<(f64, f64)>,
+  ime_area: Option<NSRect>,
 
   /// This is true when we are currently modifying a marked text
   /// using ime. When the text gets commited, this is set to false.
@@ -64,6 +78,9 @@ pub(super) struct ViewState {
   /// If a key-press does not cause an ime event, that means
   /// that the key-press cancelled the ime session. (Except arrow keys)
   key_triggered_ime: bool,
+  /// False while `Window::set_ime_allowed` disallows the IME, so that key presses aren't
+  /// interpreted by the input context.
+  ime_allowed: bool,
   // Not Needed Anymore
   //raw_characters: Option<String>,
   is_key_down: bool,
@@ -71,6 +88,12 @@ pub(super) struct ViewState {
   phys_modifiers: HashSet<KeyCode>,
   tracking_rect: Option<NSInteger>,
   pub(super) traffic_light_inset: Option<LogicalPosition<f64>>,
+  /// Pen buttons pressed in the last tablet point event.
+  tablet_buttons: NSUInteger,
+  /// Whether the tool which entered proximity last is an eraser.
+  tablet_eraser: bool,
+  repeats: RepeatCounter,
+  dead_keys: DeadKeyState,
 }
 
 impl ViewState {
@@ -85,14 +108,19 @@ pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
   let state = ViewState {
     ns_window,
     cursor_state,
-    ime_spot: None,
+    ime_area: None,
     in_ime_preedit: false,
     key_triggered_ime: false,
+    ime_allowed: true,
     is_key_down: false,
     modifiers: Default::default(),
     phys_modifiers: Default::default(),
     tracking_rect: None,
     traffic_light_inset: None,
+    tablet_buttons: 0,
+    tablet_eraser: false,
+    repeats: Default::default(),
+    dead_keys: Default::default(),
   };
   unsafe {
     // This is free'd in `dealloc`
@@ -105,17 +133,36 @@ pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
   }
 }
 
-pub unsafe fn set_ime_position(ns_view: id, input_context: id, x: f64, y: f64) {
+pub unsafe fn set_ime_cursor_area(
+  ns_view: id,
+  input_context: id,
+  position: LogicalPosition<f64>,
+  size: LogicalSize<f64>,
+) {
   let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("taoState");
   let state = &mut *(state_ptr as *mut ViewState);
   let content_rect =
     NSWindow::contentRectForFrameRect_(state.ns_window, NSWindow::frame(state.ns_window));
   let base_x = content_rect.origin.x as f64;
   let base_y = (content_rect.origin.y + content_rect.size.height) as f64;
-  state.ime_spot = Some((base_x + x, base_y - y));
+  // Screen coordinates have their origin at the bottom left.
+  state.ime_area = Some(NSRect::new(
+    NSPoint::new(base_x + position.x, base_y - position.y - size.height),
+    NSSize::new(size.width, size.height),
+  ));
   let _: () = msg_send![input_context, invalidateCharacterCoordinates];
 }
 
+pub unsafe fn set_ime_allowed(ns_view: id, allowed: bool) {
+  let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("taoState");
+  let state = &mut *(state_ptr as *mut ViewState);
+  state.ime_allowed = allowed;
+  if !allowed && state.in_ime_preedit {
+    state.in_ime_preedit = false;
+    let () = msg_send![ns_view, unmarkText];
+  }
+}
+
 fn is_arrow_key(keycode: KeyCode) -> bool {
   matches!(
     keycode,
@@ -277,10 +324,34 @@ lazy_static! {
       sel!(scrollWheel:),
       scroll_wheel as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(magnifyWithEvent:),
+      magnify_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(smartMagnifyWithEvent:),
+      smart_magnify_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(rotateWithEvent:),
+      rotate_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(swipeWithEvent:),
+      swipe_with_event as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(pressureChangeWithEvent:),
       pressure_change_with_event as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(tabletProximity:),
+      tablet_proximity as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(tabletPoint:),
+      tablet_point as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(_wantsKeyDownForEvent:),
       wants_key_down_for_event as extern "C" fn(&Object, Sel, id) -> BOOL,
@@ -467,33 +538,69 @@ extern "C" fn set_marked_text(
   this: &mut Object,
   _sel: Sel,
   string: id,
-  _selected_range: NSRange,
+  selected_range: NSRange,
   _replacement_range: NSRange,
 ) {
   trace!("Triggered `setMarkedText`");
   unsafe {
     let marked_text_ref = clear_marked_text(this);
     let has_attr: BOOL = msg_send![string, isKindOfClass: class!(NSAttributedString)];
-    if has_attr != NO {
+    let characters = if has_attr != NO {
       marked_text_ref.initWithAttributedString(string);
+      msg_send![string, string]
     } else {
       marked_text_ref.initWithString(string);
+      string
     };
 
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
     state.in_ime_preedit = true;
     state.key_triggered_ime = true;
+
+    let text = ns_string_to_rust(characters);
+    let cursor = if selected_range.location == NSNotFound as NSUInteger {
+      None
+    } else {
+      let start = selected_range.location as usize;
+      Some((
+        utf16_to_byte_index(&text, start),
+        utf16_to_byte_index(&text, start + selected_range.length as usize),
+      ))
+    };
+    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::Ime(Ime::Preedit(text, cursor)),
+    }));
   }
   trace!("Completed `setMarkedText`");
 }
 
+/// Returns the byte index in `text` of the UTF-16 `index`, clamped to its length.
+fn utf16_to_byte_index(text: &str, index: usize) -> usize {
+  let mut units = 0;
+  for (i, c) in text.char_indices() {
+    if units >= index {
+      return i;
+    }
+    units += c.len_utf16();
+  }
+  text.len()
+}
+
 extern "C" fn unmark_text(this: &mut Object, _sel: Sel) {
   trace!("Triggered `unmarkText`");
   unsafe {
     clear_marked_text(this);
     let input_context: id = msg_send![this, inputContext];
     let _: () = msg_send![input_context, discardMarkedText];
+
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+    }));
   }
   trace!("Completed `unmarkText`");
 }
@@ -531,15 +638,15 @@ extern "C" fn first_rect_for_character_range(
     trace!("Triggered `firstRectForCharacterRange`");
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
-    let (x, y) = state.ime_spot.unwrap_or_else(|| {
+    let rect = state.ime_area.unwrap_or_else(|| {
       let content_rect =
         NSWindow::contentRectForFrameRect_(state.ns_window, NSWindow::frame(state.ns_window));
       let x = content_rect.origin.x;
       let y = util::bottom_left_to_top_left(content_rect);
-      (x, y)
+      NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
     });
     trace!("Completed `firstRectForCharacterRange`");
-    NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
+    rect
   }
 }
 
@@ -568,14 +675,23 @@ extern "C" fn insert_text(this: &Object, _sel: Sel, string: id, _replacement_ran
     // We don't need this now, but it's here if that changes.
     //let event: id = msg_send![NSApp(), currentEvent];
 
-    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
-      window_id: WindowId(get_window_id(state.ns_window)),
-      event: WindowEvent::ReceivedImeText(string),
-    }));
+    let window_id = WindowId(get_window_id(state.ns_window));
     if state.in_ime_preedit {
       state.in_ime_preedit = false;
       state.key_triggered_ime = true;
+      AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+        window_id,
+        event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+      }));
+      AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+        window_id,
+        event: WindowEvent::Ime(Ime::Commit(string.clone())),
+      }));
     }
+    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+      window_id,
+      event: WindowEvent::ReceivedImeText(string),
+    }));
   }
   trace!("Completed `insertText`");
 }
@@ -708,7 +824,7 @@ extern "C" fn key_down(this: &mut Object, _sel: Sel, event: id) {
     update_potentially_stale_modifiers(state, event);
 
     let pass_along = !is_repeat || !state.is_key_down;
-    if pass_along {
+    if pass_along && state.ime_allowed {
       // See below for why we do this.
       clear_marked_text(this);
       state.key_triggered_ime = false;
@@ -722,9 +838,26 @@ extern "C" fn key_down(this: &mut Object, _sel: Sel, event: id) {
     // The `interpretKeyEvents` above, may invoke `set_marked_text` or `insert_text`,
     // if the event corresponds to an IME event.
     let in_ime = state.key_triggered_ime;
-    let key_event = create_key_event(event, true, is_repeat, in_ime, None);
+    let mut key_event = create_key_event(event, true, is_repeat, in_ime, None);
+    state.repeats.count(&mut key_event);
+    // Dead keys set the marked text, which `insertText` commits with the combined character.
+    let typed_text = key_event.text.is_some() || (in_ime && !state.in_ime_preedit);
+    state.dead_keys.update(&mut key_event, typed_text);
     let is_arrow_key = is_arrow_key(key_event.physical_key);
-    if pass_along {
+    if pass_along && !state.ime_allowed {
+      // Without `interpretKeyEvents`, `insertText` isn't called with the typed text.
+      if let Some(text) = key_event.text.filter(|text| {
+        !text
+          .chars()
+          .any(|c| c.is_control() || is_corporate_character(c))
+      }) {
+        AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+          window_id,
+          event: WindowEvent::ReceivedImeText(text.to_string()),
+        }));
+      }
+    }
+    if pass_along && state.ime_allowed {
       // The `interpretKeyEvents` above, may invoke `set_marked_text` or `insert_text`,
       // if the event corresponds to an IME event.
       // If `set_marked_text` or `insert_text` were not invoked, then the IME was deactivated,
@@ -761,11 +894,14 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
 
     update_potentially_stale_modifiers(state, event);
 
+    let mut key_event = create_key_event(event, false, false, false, None);
+    state.repeats.count(&mut key_event);
+    state.dead_keys.update(&mut key_event, false);
     let window_event = Event::WindowEvent {
       window_id: WindowId(get_window_id(state.ns_window)),
       event: WindowEvent::KeyboardInput {
         device_id: DEVICE_ID,
-        event: create_key_event(event, false, false, false, None),
+        event: key_event,
         is_synthetic: false,
       },
     };
@@ -787,7 +923,8 @@ extern "C" fn flags_changed(this: &Object, _sel: Sel, ns_event: id) {
     let scancode = get_scancode(ns_event);
 
     // We'll correct the `is_press` and the `key_override` below.
-    let event = create_key_event(ns_event, false, false, false, Some(KeyCode::SuperLeft));
+    let mut event = create_key_event(ns_event, false, false, false, Some(KeyCode::SuperLeft));
+    state.dead_keys.update(&mut event, false);
     let mut events = VecDeque::with_capacity(4);
 
     macro_rules! process_event {
@@ -937,11 +1074,13 @@ extern "C" fn cancel_operation(this: &Object, _sel: Sel, _sender: id) {
     let key = KeyCode::from_scancode(scancode);
     debug_assert_eq!(key, KeyCode::Period);
 
+    let mut key_event = create_key_event(event, true, false, false, Some(key));
+    state.dead_keys.update(&mut key_event, false);
     let window_event = Event::WindowEvent {
       window_id: WindowId(get_window_id(state.ns_window)),
       event: WindowEvent::KeyboardInput {
         device_id: DEVICE_ID,
-        event: create_key_event(event, true, false, false, Some(key)),
+        event: key_event,
         is_synthetic: false,
       },
     };
@@ -956,6 +1095,7 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
     let state = &mut *(state_ptr as *mut ViewState);
 
     update_potentially_stale_modifiers(state, event);
+    update_tablet_point(this, state, event);
 
     let window_event = Event::WindowEvent {
       window_id: WindowId(get_window_id(state.ns_window)),
@@ -963,6 +1103,7 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
         device_id: DEVICE_ID,
         state: button_state,
         button,
+        click_count: event.clickCount().max(1) as u32,
         modifiers: event_mods(event),
       },
     };
@@ -993,12 +1134,14 @@ extern "C" fn right_mouse_up(this: &Object, _sel: Sel, event: id) {
 
 extern "C" fn other_mouse_down(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
-  mouse_click(this, event, MouseButton::Middle, ElementState::Pressed);
+  let button = MouseButton::from_index(unsafe { event.buttonNumber() } as u16);
+  mouse_click(this, event, button, ElementState::Pressed);
 }
 
 extern "C" fn other_mouse_up(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
-  mouse_click(this, event, MouseButton::Middle, ElementState::Released);
+  let button = MouseButton::from_index(unsafe { event.buttonNumber() } as u16);
+  mouse_click(this, event, button, ElementState::Released);
 }
 
 fn mouse_motion(this: &Object, event: id) {
@@ -1009,8 +1152,22 @@ fn mouse_motion(this: &Object, event: id) {
     // We have to do this to have access to the `NSView` trait...
     let view: id = this as *const _ as *mut _;
 
+    let raw_mouse_motion = state.cursor_state.lock().unwrap().raw_mouse_motion;
+    if raw_mouse_motion && state.ns_window.isKeyWindow() == YES {
+      let delta = (event.deltaX(), event.deltaY());
+      if delta != (0.0, 0.0) {
+        AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+          window_id: WindowId(get_window_id(state.ns_window)),
+          event: WindowEvent::RawMouseMotion {
+            device_id: DEVICE_ID,
+            delta,
+          },
+        }));
+      }
+    }
+
     let window_point = event.locationInWindow();
-    let view_point = view.convertPoint_fromView_(window_point, nil);
+    let mut view_point = view.convertPoint_fromView_(window_point, nil);
     let view_rect = NSView::frame(view);
 
     if view_point.x.is_sign_negative()
@@ -1018,10 +1175,32 @@ fn mouse_motion(this: &Object, event: id) {
       || view_point.x > view_rect.size.width
       || view_point.y > view_rect.size.height
     {
-      let mouse_buttons_down: NSUInteger = msg_send![class!(NSEvent), pressedMouseButtons];
-      if mouse_buttons_down == 0 {
-        // Point is outside of the client area (view) and no buttons are pressed
-        return;
+      let confined = state.cursor_state.lock().unwrap().confined;
+      if confined {
+        // The key window gets the moves of the cursor outside of it too, so it's moved back in.
+        view_point.x = view_point.x.max(0.0).min(view_rect.size.width);
+        view_point.y = view_point.y.max(0.0).min(view_rect.size.height);
+        let window_point: NSPoint = msg_send![view, convertPoint: view_point toView: nil];
+        let window_frame = NSWindow::frame(state.ns_window);
+        let screen_rect = NSRect::new(
+          NSPoint::new(
+            window_frame.origin.x + window_point.x,
+            window_frame.origin.y + window_point.y,
+          ),
+          NSSize::new(0.0, 0.0),
+        );
+        let _ = CGDisplay::warp_mouse_cursor_position(CGPoint::new(
+          screen_rect.origin.x,
+          util::bottom_left_to_top_left(screen_rect),
+        ));
+        // Warping suppresses the moves of the mouse for a moment otherwise.
+        let _ = CGDisplay::associate_mouse_and_mouse_cursor_position(true);
+      } else {
+        let mouse_buttons_down: NSUInteger = msg_send![class!(NSEvent), pressedMouseButtons];
+        if mouse_buttons_down == 0 {
+          // Point is outside of the client area (view) and no buttons are pressed
+          return;
+        }
       }
     }
 
@@ -1030,6 +1209,7 @@ fn mouse_motion(this: &Object, event: id) {
     let logical_position = LogicalPosition::new(x, y);
 
     update_potentially_stale_modifiers(state, event);
+    update_tablet_point(this, state, event);
 
     let window_event = Event::WindowEvent {
       window_id: WindowId(get_window_id(state.ns_window)),
@@ -1105,19 +1285,28 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
 
+    let precise = event.hasPreciseScrollingDeltas() == YES;
     let delta = {
       // macOS horizontal sign convention is the inverse of tao.
       let (x, y) = (event.scrollingDeltaX() * -1.0, event.scrollingDeltaY());
-      if event.hasPreciseScrollingDeltas() == YES {
+      if precise {
         let delta = LogicalPosition::new(x, y).to_physical(state.get_scale_factor());
         MouseScrollDelta::PixelDelta(delta)
       } else {
         MouseScrollDelta::LineDelta(x as f32, y as f32)
       }
     };
-    let phase = match event.phase() {
+    // The momentum phases follow the phases of the scroll, which are `None` in the meantime.
+    let momentum_phase = event.momentumPhase();
+    let momentum = momentum_phase != NSEventPhase::NSEventPhaseNone;
+    let phase = match if momentum {
+      momentum_phase
+    } else {
+      event.phase()
+    } {
       NSEventPhase::NSEventPhaseMayBegin | NSEventPhase::NSEventPhaseBegan => TouchPhase::Started,
       NSEventPhase::NSEventPhaseEnded => TouchPhase::Ended,
+      NSEventPhase::NSEventPhaseCancelled => TouchPhase::Cancelled,
       _ => TouchPhase::Moved,
     };
 
@@ -1137,6 +1326,12 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         device_id: DEVICE_ID,
         delta,
         phase,
+        momentum,
+        source: if precise {
+          ScrollSource::Touchpad
+        } else {
+          ScrollSource::Wheel
+        },
         modifiers: event_mods(event),
       },
     };
@@ -1147,6 +1342,106 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
   trace!("Completed `scrollWheel`");
 }
 
+fn gesture_phase(event: id) -> TouchPhase {
+  match unsafe { event.phase() } {
+    NSEventPhase::NSEventPhaseBegan => TouchPhase::Started,
+    NSEventPhase::NSEventPhaseEnded => TouchPhase::Ended,
+    NSEventPhase::NSEventPhaseCancelled => TouchPhase::Cancelled,
+    _ => TouchPhase::Moved,
+  }
+}
+
+extern "C" fn magnify_with_event(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `magnifyWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::TouchpadMagnify {
+        device_id: DEVICE_ID,
+        delta: event.magnification(),
+        phase: gesture_phase(event),
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `magnifyWithEvent`");
+}
+
+extern "C" fn smart_magnify_with_event(this: &Object, _sel: Sel, _event: id) {
+  trace!("Triggered `smartMagnifyWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::SmartMagnify {
+        device_id: DEVICE_ID,
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `smartMagnifyWithEvent`");
+}
+
+extern "C" fn rotate_with_event(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `rotateWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::TouchpadRotate {
+        device_id: DEVICE_ID,
+        delta: event.rotation(),
+        phase: gesture_phase(event),
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `rotateWithEvent`");
+}
+
+extern "C" fn swipe_with_event(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `swipeWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    // `deltaX` is positive when swiping to the left, `deltaY` when swiping up.
+    let (dx, dy) = (event.deltaX(), event.deltaY());
+    let direction = if dx > 0.0 {
+      SwipeDirection::Left
+    } else if dx < 0.0 {
+      SwipeDirection::Right
+    } else if dy > 0.0 {
+      SwipeDirection::Up
+    } else if dy < 0.0 {
+      SwipeDirection::Down
+    } else {
+      return;
+    };
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::TouchpadSwipe {
+        device_id: DEVICE_ID,
+        direction,
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `swipeWithEvent`");
+}
+
 extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
   trace!("Triggered `pressureChangeWithEvent`");
 
@@ -1173,6 +1468,112 @@ extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
   trace!("Completed `pressureChangeWithEvent`");
 }
 
+extern "C" fn tablet_proximity(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `tabletProximity`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let entering: BOOL = msg_send![event, isEnteringProximity];
+    if entering == YES {
+      let device_type: NSUInteger = msg_send![event, pointingDeviceType];
+      state.tablet_eraser = device_type == NSEraserPointingDevice;
+      let tool = if device_type == NSPenPointingDevice {
+        TabletTool::Pen
+      } else if device_type == NSEraserPointingDevice {
+        TabletTool::Eraser
+      } else if device_type == NSCursorPointingDevice {
+        TabletTool::Mouse
+      } else {
+        TabletTool::Unknown
+      };
+
+      let device_event = Event::DeviceEvent {
+        device_id: DEVICE_ID,
+        event: DeviceEvent::TabletToolChanged { tool },
+      };
+
+      AppState::queue_event(EventWrapper::StaticEvent(device_event));
+    }
+  }
+  trace!("Completed `tabletProximity`");
+}
+
+extern "C" fn tablet_point(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `tabletPoint`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    update_tablet_point(this, state, event);
+  }
+  trace!("Completed `tabletPoint`");
+}
+
+/// Emits `TabletButton` events for the pen buttons that changed since the last tablet point event,
+/// and a `PenInput` event with the state of the pen.
+unsafe fn update_tablet_point(this: &Object, state: &mut ViewState, event: id) {
+  let subtype: i16 = msg_send![event, subtype];
+  let event_type: NSUInteger = msg_send![event, type];
+  let is_tablet_point =
+    event_type == NSEventType::NSTabletPoint as NSUInteger || subtype == NSTabletPointEventSubtype;
+  if !is_tablet_point {
+    return;
+  }
+
+  // The pen tip is already reported as the left mouse button.
+  let buttons: NSUInteger = msg_send![event, buttonMask];
+  let buttons = buttons & !NSPenTipMask;
+  let changed = buttons ^ state.tablet_buttons;
+  state.tablet_buttons = buttons;
+
+  for id in 0..NSUInteger::BITS {
+    if changed & (1 << id) != 0 {
+      let device_event = Event::DeviceEvent {
+        device_id: DEVICE_ID,
+        event: DeviceEvent::TabletButton {
+          id,
+          state: if buttons & (1 << id) != 0 {
+            ElementState::Pressed
+          } else {
+            ElementState::Released
+          },
+        },
+      };
+
+      AppState::queue_event(EventWrapper::StaticEvent(device_event));
+    }
+  }
+
+  // Tablet coordinates are in the same space as the mouse, the tilt is in `-1.0..=1.0`.
+  let view: id = this as *const _ as *mut _;
+  let view_point = view.convertPoint_fromView_(event.locationInWindow(), nil);
+  let view_rect = NSView::frame(view);
+  let position = LogicalPosition::new(
+    view_point.x as f64,
+    view_rect.size.height as f64 - view_point.y as f64,
+  );
+  let all_buttons: NSUInteger = msg_send![event, buttonMask];
+  let pressure: f32 = msg_send![event, pressure];
+  let tilt: NSPoint = msg_send![event, tilt];
+  let rotation: f32 = msg_send![event, rotation];
+  let pen_event = Event::WindowEvent {
+    window_id: WindowId(get_window_id(state.ns_window)),
+    event: WindowEvent::PenInput(PenInput {
+      device_id: DEVICE_ID,
+      position: position.to_physical(state.get_scale_factor()),
+      in_contact: all_buttons & NSPenTipMask != 0,
+      pressure: pressure as f64,
+      tilt_x: tilt.x as f64 * 90.0,
+      tilt_y: -tilt.y as f64 * 90.0,
+      twist: rotation as f64,
+      eraser: state.tablet_eraser,
+      barrel_buttons: (buttons >> 1) as u32,
+    }),
+  };
+  AppState::queue_event(EventWrapper::StaticEvent(pen_event));
+}
+
 // Allows us to receive Ctrl-Tab and Ctrl-Esc.
 // Note that this *doesn't* help with any missing Cmd inputs.
 // https://github.com/chromium/chromium/blob/a86a8a6bcfa438fa3ac2eba6f02b3ad1f8e0756f/ui/views/cocoa/bridged_content_view.mm#L816
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  collections::VecDeque,
+  collections::{HashMap, VecDeque},
   convert::TryInto,
   f64,
   os::raw::c_void,
@@ -11,6 +11,7 @@ use std::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex, Weak,
   },
+  time::Instant,
 };
 
 use crate::{
@@ -18,13 +19,19 @@ use crate::{
     LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
   },
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
-  icon::Icon,
+  event::{Event, FullscreenTransition, WindowEvent},
+  event_loop::ControlFlow,
+  global_shortcut::{Accelerator, AcceleratorId},
+  icon::{CustomCursor, Icon},
+  keyboard::KeyCode,
   monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-  platform::macos::WindowExtMacOS,
+  platform::macos::{FullscreenMode, WindowExtMacOS, WindowLevel},
   platform_impl::platform::{
     app_state::AppState,
-    ffi,
+    event::{event_mods, get_scancode, EventWrapper},
+    ffi, frame_pacing, keyboard_grab,
     monitor::{self, MonitorHandle, VideoMode},
+    surface_region::SurfaceRegion,
     util::{self, IdRef},
     view::{self, new_view, CursorState},
     window_delegate::new_delegate,
@@ -32,26 +39,30 @@ use crate::{
   },
   platform_impl::set_progress_indicator,
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, UserAttentionType,
-    WindowAttributes, WindowId as RootWindowId, WindowSizeConstraints,
+    AccelerationProfile, AttributesReport, CursorGrabMode, CursorIcon, DropPolicy, FocusPolicy,
+    Fullscreen, ImePurpose, ProgressBarState, Rect, ResizeDirection, SystemGestures, Theme,
+    TitleBarStyle, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    WindowSizeConstraints,
   },
 };
 use cocoa::{
   appkit::{
-    self, CGFloat, NSApp, NSApplication, NSApplicationPresentationOptions, NSColor, NSEvent,
-    NSEventModifierFlags, NSEventSubtype, NSEventType, NSRequestUserAttentionType, NSScreen,
-    NSView, NSWindow, NSWindowButton, NSWindowCollectionBehavior, NSWindowOrderingMode,
-    NSWindowStyleMask,
+    self, CGFloat, NSApp, NSApplication, NSApplicationActivationPolicy,
+    NSApplicationPresentationOptions, NSColor, NSEvent, NSEventModifierFlags, NSEventSubtype,
+    NSEventType, NSRequestUserAttentionType, NSScreen, NSView, NSWindow, NSWindowButton,
+    NSWindowCollectionBehavior, NSWindowOrderingMode, NSWindowStyleMask,
   },
   base::{id, nil},
   foundation::{
-    NSArray, NSAutoreleasePool, NSDictionary, NSInteger, NSPoint, NSRect, NSSize, NSString,
-    NSTimeInterval, NSUInteger,
+    NSArray, NSAutoreleasePool, NSDefaultRunLoopMode, NSDictionary, NSInteger, NSPoint, NSRect,
+    NSSize, NSString, NSTimeInterval, NSUInteger,
   },
 };
 use core_graphics::display::{CGDisplay, CGDisplayMode};
+use dispatch::Queue;
 use objc::{
   declare::ClassDecl,
+  rc::WeakPtr,
   runtime::{Class, Object, Sel, BOOL, NO, YES},
 };
 
@@ -94,6 +105,8 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub traffic_light_inset: Option<Position>,
   pub automatic_tabbing: bool,
   pub tabbing_identifier: Option<String>,
+  pub skip_taskbar: bool,
+  pub fullscreen_mode: FullscreenMode,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -113,6 +126,8 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       traffic_light_inset: None,
       automatic_tabbing: true,
       tabbing_identifier: None,
+      skip_taskbar: false,
+      fullscreen_mode: FullscreenMode::Native,
     }
   }
 }
@@ -148,6 +163,24 @@ unsafe fn create_view(
   })
 }
 
+/// Returns the bounding frame of the screens of `monitors`.
+fn spanning_frame(monitors: &[RootMonitorHandle]) -> Option<NSRect> {
+  monitors
+    .iter()
+    .filter_map(|monitor| monitor.inner.ns_screen())
+    .map(|screen| unsafe { NSScreen::frame(screen) })
+    .reduce(|a, b| {
+      let min_x = a.origin.x.min(b.origin.x);
+      let min_y = a.origin.y.min(b.origin.y);
+      let max_x = (a.origin.x + a.size.width).max(b.origin.x + b.size.width);
+      let max_y = (a.origin.y + a.size.height).max(b.origin.y + b.size.height);
+      NSRect::new(
+        NSPoint::new(min_x, min_y),
+        NSSize::new(max_x - min_x, max_y - min_y),
+      )
+    })
+}
+
 fn create_window(
   attrs: &WindowAttributes,
   pl_attrs: &PlatformSpecificWindowBuilderAttributes,
@@ -163,7 +196,8 @@ fn create_window(
         Some(monitor_screen.unwrap_or_else(|| appkit::NSScreen::mainScreen(nil)))
       }
       Some(Fullscreen::Borderless(None)) => Some(appkit::NSScreen::mainScreen(nil)),
-      None => None,
+      // The frame is set when entering the fullscreen mode.
+      Some(Fullscreen::Spanning(_)) | None => None,
     };
     let frame = match screen {
       Some(screen) => NSScreen::frame(screen),
@@ -364,23 +398,87 @@ lazy_static! {
     let mut decl = ClassDecl::new("TaoWindow", window_superclass).unwrap();
     decl.add_method(
       sel!(canBecomeMainWindow),
-      util::yes as extern "C" fn(&Object, Sel) -> BOOL,
+      can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL,
     );
     decl.add_method(
       sel!(canBecomeKeyWindow),
-      util::yes as extern "C" fn(&Object, Sel) -> BOOL,
+      can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL,
     );
     decl.add_method(
       sel!(sendEvent:),
       send_event as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(performKeyEquivalent:),
+      perform_key_equivalent as extern "C" fn(&Object, Sel, id) -> BOOL,
+    );
+    decl.add_method(
+      sel!(miniaturize:),
+      miniaturize as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_ivar::<BOOL>("taoInputDisabled");
+    decl.add_ivar::<BOOL>("taoInterceptMinimize");
+    decl.add_ivar::<BOOL>("taoTitlebarDoubleClickDisabled");
+    decl.add_ivar::<BOOL>("taoFocusDisabled");
+    decl.add_ivar::<BOOL>("taoSkipTaskbar");
     WindowClass(decl.register())
   };
+  /// The accelerators of each window, see `Window::add_accelerator`.
+  static ref ACCELERATORS: Mutex<HashMap<Id, Vec<(AcceleratorId, Accelerator)>>> =
+    Default::default();
+}
+
+extern "C" fn can_become_key_window(this: &Object, _sel: Sel) -> BOOL {
+  let focus_disabled: BOOL = unsafe { *this.get_ivar("taoFocusDisabled") };
+  if focus_disabled == YES {
+    NO
+  } else {
+    YES
+  }
 }
 
 extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
   unsafe {
     let event_type = event.eventType();
+
+    // A disabled window stays visible but drops mouse and keyboard input,
+    // without letting clicks through like `setIgnoresMouseEvents:` would.
+    let input_disabled: BOOL = *this.get_ivar("taoInputDisabled");
+    if input_disabled == YES {
+      match event_type {
+        appkit::NSLeftMouseDown
+        | appkit::NSLeftMouseUp
+        | appkit::NSLeftMouseDragged
+        | appkit::NSRightMouseDown
+        | appkit::NSRightMouseUp
+        | appkit::NSRightMouseDragged
+        | appkit::NSOtherMouseDown
+        | appkit::NSOtherMouseUp
+        | appkit::NSOtherMouseDragged
+        | appkit::NSScrollWheel
+        | appkit::NSKeyDown
+        | appkit::NSKeyUp
+        | appkit::NSFlagsChanged => return,
+        _ => (),
+      }
+    }
+
+    if handle_accelerator(this, event) {
+      return;
+    }
+
+    // The title bar zooms or minimizes the window from double-clicks, so keep them from it.
+    let double_click_disabled: BOOL = *this.get_ivar("taoTitlebarDoubleClickDisabled");
+    if double_click_disabled == YES
+      && matches!(event_type, appkit::NSLeftMouseDown | appkit::NSLeftMouseUp)
+      && event.clickCount() >= 2
+    {
+      let content_rect: NSRect = msg_send![this, contentLayoutRect];
+      if event.locationInWindow().y > content_rect.origin.y + content_rect.size.height {
+        return;
+      }
+    }
+
     match event_type {
       appkit::NSLeftMouseDown => {
         // When wkwebview is set on NSWindow, `WindowBuilder::with_movable_by_window_background` is not working.
@@ -397,6 +495,67 @@ extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
   }
 }
 
+// The key presses with Command are offered to the key window as key equivalents before the main
+// menu, and only sent to the window if neither handled them.
+extern "C" fn perform_key_equivalent(this: &Object, _sel: Sel, event: id) -> BOOL {
+  unsafe {
+    if handle_accelerator(this, event) {
+      return YES;
+    }
+    let superclass = util::superclass(this);
+    msg_send![super(this, superclass), performKeyEquivalent: event]
+  }
+}
+
+/// Emits `WindowEvent::Accelerator` if `event` is a key press matching an accelerator of the
+/// window, returning whether it did.
+unsafe fn handle_accelerator(this: &Object, event: id) -> bool {
+  let input_disabled: BOOL = *this.get_ivar("taoInputDisabled");
+  if input_disabled == YES || event.eventType() != appkit::NSKeyDown {
+    return false;
+  }
+  let window_id = get_window_id(this as *const Object as id);
+  let key = KeyCode::from_scancode(u32::from(get_scancode(event)));
+  let mods = event_mods(event);
+  let accelerator = ACCELERATORS
+    .lock()
+    .unwrap()
+    .get(&window_id)
+    .and_then(|accelerators| {
+      accelerators
+        .iter()
+        .find(|(_, accelerator)| accelerator.key == key && accelerator.mods == mods)
+        .map(|(id, _)| *id)
+    });
+  match accelerator {
+    Some(id) => {
+      AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+        window_id: RootWindowId(window_id),
+        event: WindowEvent::Accelerator(id),
+      }));
+      true
+    }
+    None => false,
+  }
+}
+
+extern "C" fn miniaturize(this: &Object, _sel: Sel, sender: id) {
+  unsafe {
+    // `set_minimized` calls `NSWindow`'s implementation directly, so this is only
+    // reached from the minimize button, the "Minimize" menu item or a titlebar double-click.
+    let intercept_minimize: BOOL = *this.get_ivar("taoInterceptMinimize");
+    if intercept_minimize == YES {
+      AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+        window_id: RootWindowId(get_window_id(this as *const Object as id)),
+        event: WindowEvent::MinimizeRequested,
+      }));
+      return;
+    }
+    let superclass = util::superclass(this);
+    let _: () = msg_send![super(this, superclass), miniaturize: sender];
+  }
+}
+
 #[derive(Default)]
 pub struct SharedState {
   pub resizable: bool,
@@ -420,6 +579,14 @@ pub struct SharedState {
   save_presentation_opts: Option<NSApplicationPresentationOptions>,
   pub saved_desktop_display_mode: Option<(CGDisplay, CGDisplayMode)>,
   pub current_theme: Theme,
+  /// Whether system hotkeys should be disabled while this window is key.
+  pub keyboard_grab: bool,
+  /// Whether `request_redraw` waits for the next display refresh, see `frame_pacing.rs`.
+  pub frame_pacing: bool,
+  /// Checked by the window delegate when something is dragged over the window.
+  pub drop_policy: DropPolicy,
+  /// How `set_fullscreen` enters borderless fullscreen.
+  pub fullscreen_mode: FullscreenMode,
 }
 
 impl SharedState {
@@ -460,16 +627,48 @@ pub struct UnownedWindow {
 unsafe impl Send for UnownedWindow {}
 unsafe impl Sync for UnownedWindow {}
 
+/// Closes a window from any thread, see `Window::close_token`.
+///
+/// Only holds a weak reference to the window, which doesn't keep it alive.
+#[derive(Clone)]
+pub struct CloseToken(WeakPtr);
+
+// Weak references can be loaded and destroyed from any thread, and are only loaded on the main
+// thread by `close_weak_async`.
+unsafe impl Send for CloseToken {}
+unsafe impl Sync for CloseToken {}
+
+impl CloseToken {
+  pub fn proceed(self) {
+    util::close_weak_async(self.0);
+  }
+}
+
 impl UnownedWindow {
   pub fn new(
     mut win_attribs: WindowAttributes,
-    pl_attribs: PlatformSpecificWindowBuilderAttributes,
+    mut pl_attribs: PlatformSpecificWindowBuilderAttributes,
   ) -> Result<(Arc<Self>, IdRef), RootOsError> {
     if !util::is_main_thread() {
       panic!("Windows can only be created on the main thread on macOS");
     }
     trace!("Creating new window");
 
+    match win_attribs.title_bar_style {
+      TitleBarStyle::Visible => (),
+      TitleBarStyle::Transparent => {
+        pl_attribs.titlebar_transparent = true;
+        pl_attribs.titlebar_hidden = false;
+      }
+      TitleBarStyle::Overlay => {
+        pl_attribs.titlebar_transparent = true;
+        pl_attribs.titlebar_hidden = false;
+        pl_attribs.title_hidden = true;
+        pl_attribs.fullsize_content_view = true;
+      }
+      TitleBarStyle::Hidden => pl_attribs.titlebar_hidden = true,
+    }
+
     let pool = unsafe { NSAutoreleasePool::new(nil) };
     let ns_window = create_window(&win_attribs, &pl_attribs).ok_or_else(|| {
       unsafe { pool.drain() };
@@ -529,6 +728,9 @@ impl UnownedWindow {
     let focused = win_attribs.focused;
     let decorations = win_attribs.decorations;
     let visible_on_all_workspaces = win_attribs.visible_on_all_workspaces;
+    let intercept_minimize = win_attribs.intercept_minimize;
+    let skip_taskbar = pl_attribs.skip_taskbar;
+    let fullscreen_mode = pl_attribs.fullscreen_mode;
     let inner_rect = win_attribs
       .inner_size
       .map(|size| size.to_physical(scale_factor));
@@ -545,6 +747,8 @@ impl UnownedWindow {
       inner_rect,
     });
 
+    window.shared_state.lock().unwrap().fullscreen_mode = fullscreen_mode;
+
     match cloned_preferred_theme {
       Some(theme) => {
         set_ns_theme(theme);
@@ -562,6 +766,8 @@ impl UnownedWindow {
     // Set fullscreen mode after we setup everything
     window.set_fullscreen(fullscreen);
     window.set_visible_on_all_workspaces(visible_on_all_workspaces);
+    window.set_intercept_minimize(intercept_minimize);
+    window.set_skip_taskbar(skip_taskbar);
 
     // Setting the window as key has to happen *after* we set the fullscreen
     // state, since otherwise we'll briefly see the window at normal size
@@ -636,8 +842,118 @@ impl UnownedWindow {
     }
   }
 
+  #[inline]
+  pub fn raise(&self) {
+    unsafe {
+      let _: () = msg_send![*self.ns_window, orderFrontRegardless];
+    }
+  }
+
+  #[inline]
+  pub fn focus_without_raise(&self) {
+    unsafe {
+      let _: () = msg_send![*self.ns_window, makeKeyWindow];
+    }
+  }
+
+  #[inline]
+  pub fn set_drop_policy(&self, policy: DropPolicy) {
+    let forward = policy == DropPolicy::Forward;
+    let was_forward = {
+      let mut shared_state = self.shared_state.lock().unwrap();
+      std::mem::replace(&mut shared_state.drop_policy, policy) == DropPolicy::Forward
+    };
+    if forward != was_forward {
+      unsafe {
+        if forward {
+          let () = msg_send![*self.ns_window, unregisterDraggedTypes];
+        } else {
+          let () = msg_send![
+            *self.ns_window,
+            registerForDraggedTypes: NSArray::arrayWithObject(nil, appkit::NSFilenamesPboardType)
+          ];
+        }
+      }
+    }
+  }
+
+  #[inline]
+  pub fn set_focus_policy(&self, policy: FocusPolicy) {
+    let disabled = policy == FocusPolicy::Never;
+    unsafe {
+      let ns_window: &mut Object = &mut **self.ns_window;
+      ns_window.set_ivar::<BOOL>("taoFocusDisabled", if disabled { YES } else { NO });
+    }
+  }
+
   pub fn request_redraw(&self) {
-    AppState::queue_redraw(RootWindowId(self.id()));
+    frame_pacing::jank_request_redraw(RootWindowId(self.id()));
+    let frame_pacing = self.shared_state.lock().unwrap().frame_pacing;
+    if frame_pacing {
+      frame_pacing::request_redraw(RootWindowId(self.id()));
+    } else {
+      AppState::queue_redraw(RootWindowId(self.id()));
+    }
+  }
+
+  pub fn set_frame_pacing(&self, enabled: bool) {
+    trace!("Locked shared state in `set_frame_pacing`");
+    self.shared_state.lock().unwrap().frame_pacing = enabled;
+    trace!("Unlocked shared state in `set_frame_pacing`");
+  }
+
+  pub fn set_jank_detection(&self, enabled: bool) {
+    frame_pacing::set_jank_detection(
+      RootWindowId(self.id()),
+      enabled.then(|| self.current_monitor_inner().inner),
+    );
+  }
+
+  pub fn applied_attributes_report(&self) -> AttributesReport {
+    AttributesReport::default()
+  }
+
+  pub fn run_modal(&self, handler: &mut dyn FnMut(&mut ControlFlow)) {
+    let mut control_flow = ControlFlow::Wait;
+    loop {
+      handler(&mut control_flow);
+      unsafe {
+        let until: id = match control_flow {
+          ControlFlow::ExitWithCode(_) => break,
+          ControlFlow::Poll => msg_send![class!(NSDate), distantPast],
+          ControlFlow::Wait => msg_send![class!(NSDate), distantFuture],
+          ControlFlow::WaitUntil(resume) => {
+            let interval = resume.saturating_duration_since(Instant::now());
+            msg_send![class!(NSDate), dateWithTimeIntervalSinceNow: interval.as_secs_f64()]
+          }
+        };
+        let pool = NSAutoreleasePool::new(nil);
+        let app = NSApp();
+        let event: id = msg_send![
+          app,
+          nextEventMatchingMask: appkit::NSEventMask::NSAnyEventMask.bits()
+          untilDate: until
+          inMode: NSDefaultRunLoopMode
+          dequeue: YES
+        ];
+        if event != nil {
+          let () = msg_send![app, sendEvent: event];
+        }
+        pool.drain();
+      }
+    }
+  }
+
+  pub fn close(&self) {
+    unsafe { util::request_close_async(self.ns_window.clone()) };
+  }
+
+  pub fn destroy(&self) {
+    self.close_token().proceed()
+  }
+
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken(unsafe { WeakPtr::new(*self.ns_window) })
   }
 
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
@@ -769,6 +1085,43 @@ impl UnownedWindow {
     }
   }
 
+  #[inline]
+  pub fn set_enabled(&self, enabled: bool) {
+    unsafe {
+      let ns_window: &mut Object = &mut **self.ns_window;
+      ns_window.set_ivar::<BOOL>("taoInputDisabled", if enabled { NO } else { YES });
+    }
+  }
+
+  #[inline]
+  pub fn set_intercept_minimize(&self, intercept: bool) {
+    unsafe {
+      let ns_window: &mut Object = &mut **self.ns_window;
+      ns_window.set_ivar::<BOOL>("taoInterceptMinimize", if intercept { YES } else { NO });
+    }
+  }
+
+  #[inline]
+  pub fn set_system_gestures_enabled(&self, gestures: SystemGestures) {
+    let disabled = !gestures.contains(SystemGestures::TITLEBAR_DOUBLE_CLICK);
+    unsafe {
+      let ns_window: &mut Object = &mut **self.ns_window;
+      ns_window.set_ivar::<BOOL>(
+        "taoTitlebarDoubleClickDisabled",
+        if disabled { YES } else { NO },
+      );
+    }
+  }
+
+  #[inline]
+  pub fn is_enabled(&self) -> bool {
+    unsafe {
+      let ns_window: &Object = &*(*self.ns_window as *const Object);
+      let input_disabled: BOOL = *ns_window.get_ivar("taoInputDisabled");
+      input_disabled == NO
+    }
+  }
+
   #[inline]
   pub fn set_closable(&self, closable: bool) {
     let mut mask = unsafe { self.ns_window.styleMask() };
@@ -781,24 +1134,104 @@ impl UnownedWindow {
   }
 
   pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-    let cursor = util::Cursor::from(cursor);
-    if let Some(cursor_access) = self.cursor_state.upgrade() {
-      cursor_access.lock().unwrap().cursor = cursor;
+    self.set_cursor(util::Cursor::from(cursor));
+  }
+
+  pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+    let delay = cursor.frame_delay(0);
+    let animation = self.set_cursor(util::Cursor::Custom(cursor, 0));
+    if let (Some(delay), Some(animation)) = (delay, animation) {
+      unsafe {
+        util::animate_cursor(self.cursor_state.clone(), *self.ns_view, animation, delay);
+      }
     }
+  }
+
+  // Returns the animation the cursor can be animated with.
+  fn set_cursor(&self, cursor: util::Cursor) -> Option<usize> {
+    let animation = self.cursor_state.upgrade().map(|cursor_access| {
+      let mut cursor_state = cursor_access.lock().unwrap();
+      cursor_state.cursor = cursor;
+      cursor_state.animation = cursor_state.animation.wrapping_add(1);
+      cursor_state.animation
+    });
     unsafe {
       let _: () = msg_send![*self.ns_window,
           invalidateCursorRectsForView:*self.ns_view
       ];
     }
+    animation
   }
 
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-    // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
-    CGDisplay::associate_mouse_and_mouse_cursor_position(!grab)
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    if let Some(cursor_access) = self.cursor_state.upgrade() {
+      cursor_access.lock().unwrap().confined = mode == CursorGrabMode::Confined;
+    }
+    // Dissociating the cursor from the mouse keeps it in place, which locks it.
+    CGDisplay::associate_mouse_and_mouse_cursor_position(mode != CursorGrabMode::Locked)
       .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
   }
 
+  #[inline]
+  pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), ExternalError> {
+    if let Some(cursor_access) = self.cursor_state.upgrade() {
+      cursor_access.lock().unwrap().raw_mouse_motion = enabled;
+    }
+    Ok(())
+  }
+
+  pub fn set_pointer_acceleration(
+    &self,
+    _profile: Option<AccelerationProfile>,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  #[inline]
+  pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+
+  pub fn add_accelerator(
+    &self,
+    id: AcceleratorId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    ACCELERATORS
+      .lock()
+      .unwrap()
+      .entry(self.id())
+      .or_default()
+      .push((id, accelerator));
+    Ok(())
+  }
+
+  pub fn remove_accelerator(&self, id: AcceleratorId) {
+    if let Some(accelerators) = ACCELERATORS.lock().unwrap().get_mut(&self.id()) {
+      accelerators.retain(|(accelerator_id, _)| *accelerator_id != id);
+    }
+  }
+
+  #[inline]
+  pub fn set_touch_keyboard_visible(&self, _visible: bool) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    trace!("Locked shared state in `set_keyboard_grab`");
+    let mut shared_state_lock = self.shared_state.lock().unwrap();
+    shared_state_lock.keyboard_grab = grab;
+    trace!("Unlocked shared state in `set_keyboard_grab`");
+    drop(shared_state_lock);
+
+    // The keyboard is grabbed again by the window delegate when the key status changes.
+    if self.is_focused() {
+      keyboard_grab::set_keyboard_grabbed(grab)
+    } else {
+      Ok(())
+    }
+  }
+
   #[inline]
   pub fn set_cursor_visible(&self, visible: bool) {
     if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -830,6 +1263,9 @@ impl UnownedWindow {
     unsafe { NSWindow::backingScaleFactor(*self.ns_window) as _ }
   }
 
+  #[inline]
+  pub fn set_scale_factor_override(&self, _scale_factor: Option<f64>) {}
+
   #[inline]
   pub fn set_cursor_position(&self, cursor_position: Position) -> Result<(), ExternalError> {
     let physical_window_position = self.inner_position().unwrap();
@@ -955,7 +1391,8 @@ impl UnownedWindow {
 
     if minimized {
       unsafe {
-        NSWindow::miniaturize_(*self.ns_window, *self.ns_window);
+        let ns_window: &Object = &*(*self.ns_window as *const Object);
+        let _: () = msg_send![super(ns_window, class!(NSWindow)), miniaturize: *self.ns_window];
       }
     } else {
       unsafe {
@@ -983,6 +1420,12 @@ impl UnownedWindow {
   #[inline]
   pub fn fullscreen(&self) -> Option<Fullscreen> {
     let shared_state_lock = self.shared_state.lock().unwrap();
+    if shared_state_lock.is_simple_fullscreen
+      && shared_state_lock.fullscreen_mode == FullscreenMode::Simple
+    {
+      drop(shared_state_lock);
+      return Some(Fullscreen::Borderless(Some(self.current_monitor_inner())));
+    }
     shared_state_lock.fullscreen.clone()
   }
 
@@ -1042,8 +1485,20 @@ impl UnownedWindow {
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
     trace!("Locked shared state in `set_fullscreen`");
     let mut shared_state_lock = self.shared_state.lock().unwrap();
+    let fullscreen_mode = shared_state_lock.fullscreen_mode;
     if shared_state_lock.is_simple_fullscreen {
       trace!("Unlocked shared state in `set_fullscreen`");
+      drop(shared_state_lock);
+      // In the simple mode, simple fullscreen is the borderless fullscreen, and it's left for any
+      // other state.
+      if fullscreen_mode == FullscreenMode::Simple
+        && !matches!(fullscreen, Some(Fullscreen::Borderless(_)))
+      {
+        self.set_simple_fullscreen(false);
+        if fullscreen.is_some() {
+          self.set_fullscreen(fullscreen);
+        }
+      }
       return;
     }
     if shared_state_lock.in_fullscreen_transition {
@@ -1061,6 +1516,23 @@ impl UnownedWindow {
     trace!("Unlocked shared state in `set_fullscreen`");
     drop(shared_state_lock);
 
+    if let (FullscreenMode::Simple, None, Some(Fullscreen::Borderless(monitor))) =
+      (fullscreen_mode, &old_fullscreen, &fullscreen)
+    {
+      let screen = monitor
+        .as_ref()
+        .and_then(|monitor| monitor.inner.ns_screen());
+      self.set_simple_fullscreen_on(true, screen);
+      return;
+    }
+
+    if matches!(old_fullscreen, Some(Fullscreen::Spanning(_)))
+      || matches!(fullscreen, Some(Fullscreen::Spanning(_)))
+    {
+      self.set_spanning_fullscreen(old_fullscreen, fullscreen);
+      return;
+    }
+
     // If the fullscreen is on a different monitor, we must move the window
     // to that monitor before we toggle fullscreen (as `toggleFullScreen`
     // does not take a screen parameter, but uses the current screen)
@@ -1075,6 +1547,7 @@ impl UnownedWindow {
         Fullscreen::Exclusive(RootVideoMode {
           video_mode: VideoMode { ref monitor, .. },
         }) => monitor.clone(),
+        Fullscreen::Spanning(_) => unreachable!(),
       }
       .ns_screen()
       .unwrap();
@@ -1241,6 +1714,173 @@ impl UnownedWindow {
     trace!("Unlocked shared state in `set_fullscreen`");
   }
 
+  /// Native fullscreen is limited to a single screen, so spanning several screens is done by
+  /// covering them with a borderless window instead, much like `set_simple_fullscreen`.
+  fn set_spanning_fullscreen(
+    &self,
+    old_fullscreen: Option<Fullscreen>,
+    fullscreen: Option<Fullscreen>,
+  ) {
+    match (&old_fullscreen, &fullscreen) {
+      // Leave native fullscreen first, spanning is applied once the transition is over.
+      (Some(Fullscreen::Borderless(_) | Fullscreen::Exclusive(_)), _) => {
+        self.set_fullscreen(None);
+        trace!("Locked shared state in `set_spanning_fullscreen`");
+        self.shared_state.lock().unwrap().target_fullscreen = Some(fullscreen);
+        trace!("Unlocked shared state in `set_spanning_fullscreen`");
+      }
+      (_, Some(Fullscreen::Spanning(monitors))) => unsafe {
+        let frame = match spanning_frame(monitors) {
+          Some(frame) => frame,
+          None => return,
+        };
+
+        trace!("Locked shared state in `set_spanning_fullscreen`");
+        let mut shared_state_lock = self.shared_state.lock().unwrap();
+        if old_fullscreen.is_none() {
+          let app = NSApp();
+          shared_state_lock.standard_frame = Some(NSWindow::frame(*self.ns_window));
+          shared_state_lock.saved_style = Some(self.ns_window.styleMask());
+          shared_state_lock.save_presentation_opts = Some(app.presentationOptions_());
+          app.setPresentationOptions_(
+            NSApplicationPresentationOptions::NSApplicationPresentationHideDock
+              | NSApplicationPresentationOptions::NSApplicationPresentationHideMenuBar,
+          );
+          self.set_style_mask_sync(NSWindowStyleMask::NSBorderlessWindowMask);
+        }
+        shared_state_lock.fullscreen = fullscreen.clone();
+        trace!("Unlocked shared state in `set_spanning_fullscreen`");
+        drop(shared_state_lock);
+
+        NSWindow::setFrame_display_(*self.ns_window, frame, YES);
+      },
+      (Some(Fullscreen::Spanning(_)), _) => unsafe {
+        trace!("Locked shared state in `set_spanning_fullscreen`");
+        let mut shared_state_lock = self.shared_state.lock().unwrap();
+        let new_mask = self.saved_style(&mut shared_state_lock);
+        if let Some(presentation_opts) = shared_state_lock.save_presentation_opts.take() {
+          NSApp().setPresentationOptions_(presentation_opts);
+        }
+        let frame = shared_state_lock.standard_frame.take();
+        shared_state_lock.fullscreen = None;
+        trace!("Unlocked shared state in `set_spanning_fullscreen`");
+        drop(shared_state_lock);
+
+        self.set_style_mask_sync(new_mask);
+        if let Some(frame) = frame {
+          NSWindow::setFrame_display_(*self.ns_window, frame, YES);
+        }
+        if fullscreen.is_some() {
+          self.set_fullscreen(fullscreen);
+        }
+      },
+      _ => {}
+    }
+  }
+
+  /// Enters or leaves simple fullscreen, on `screen` or the screen of the window, emitting the
+  /// fullscreen transition right away since there is no animation.
+  fn set_simple_fullscreen_on(&self, fullscreen: bool, screen: Option<id>) -> bool {
+    let mut shared_state_lock = self.shared_state.lock().unwrap();
+
+    let changed = unsafe {
+      let app = NSApp();
+      let is_native_fullscreen = shared_state_lock.fullscreen.is_some();
+      let is_simple_fullscreen = shared_state_lock.is_simple_fullscreen;
+
+      // Do nothing if native fullscreen is active.
+      if is_native_fullscreen
+        || (fullscreen && is_simple_fullscreen)
+        || (!fullscreen && !is_simple_fullscreen)
+      {
+        return false;
+      }
+
+      if fullscreen {
+        // Remember the original window's settings
+        // Exclude title bar
+        shared_state_lock.standard_frame = Some(NSWindow::contentRectForFrameRect_(
+          *self.ns_window,
+          NSWindow::frame(*self.ns_window),
+        ));
+        shared_state_lock.saved_style = Some(self.ns_window.styleMask());
+        shared_state_lock.save_presentation_opts = Some(app.presentationOptions_());
+
+        // Tell our window's state that we're in fullscreen
+        shared_state_lock.is_simple_fullscreen = true;
+
+        // Simulate pre-Lion fullscreen by hiding the dock and menu bar
+        let presentation_options =
+          NSApplicationPresentationOptions::NSApplicationPresentationAutoHideDock
+            | NSApplicationPresentationOptions::NSApplicationPresentationAutoHideMenuBar;
+        app.setPresentationOptions_(presentation_options);
+
+        // Hide the titlebar
+        util::toggle_style_mask(
+          *self.ns_window,
+          *self.ns_view,
+          NSWindowStyleMask::NSTitledWindowMask,
+          false,
+        );
+
+        // Set the window frame to the screen frame size
+        let screen = screen.unwrap_or_else(|| self.ns_window.screen());
+        let screen_frame = NSScreen::frame(screen);
+        NSWindow::setFrame_display_(*self.ns_window, screen_frame, YES);
+
+        // Fullscreen windows can't be resized, minimized, or moved
+        util::toggle_style_mask(
+          *self.ns_window,
+          *self.ns_view,
+          NSWindowStyleMask::NSMiniaturizableWindowMask,
+          false,
+        );
+        util::toggle_style_mask(
+          *self.ns_window,
+          *self.ns_view,
+          NSWindowStyleMask::NSResizableWindowMask,
+          false,
+        );
+        NSWindow::setMovable_(*self.ns_window, NO);
+
+        true
+      } else {
+        let new_mask = self.saved_style(&mut *shared_state_lock);
+        self.set_style_mask_async(new_mask);
+        shared_state_lock.is_simple_fullscreen = false;
+
+        if let Some(presentation_opts) = shared_state_lock.save_presentation_opts {
+          app.setPresentationOptions_(presentation_opts);
+        }
+
+        let frame = shared_state_lock.saved_standard_frame();
+        NSWindow::setFrame_display_(*self.ns_window, frame, YES);
+        NSWindow::setMovable_(*self.ns_window, YES);
+
+        true
+      }
+    };
+
+    let transitions = if fullscreen {
+      [
+        FullscreenTransition::WillEnter,
+        FullscreenTransition::DidEnter,
+      ]
+    } else {
+      [
+        FullscreenTransition::WillExit,
+        FullscreenTransition::DidExit,
+      ]
+    };
+    for transition in transitions {
+      AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+        window_id: RootWindowId(self.id()),
+        event: WindowEvent::FullscreenTransition(transition),
+      }));
+    }
+    changed
+  }
+
   #[inline]
   pub fn set_decorations(&self, decorations: bool) {
     if decorations != self.decorations.load(Ordering::Acquire) {
@@ -1313,19 +1953,26 @@ impl UnownedWindow {
   }
 
   #[inline]
-  pub fn set_ime_position(&self, spot: Position) {
+  pub fn set_window_icon_frames(&self, _frames: Vec<Icon>, _interval: std::time::Duration) {}
+
+  #[inline]
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
     let scale_factor = self.scale_factor();
-    let logical_spot = spot.to_logical(scale_factor);
     unsafe {
-      view::set_ime_position(
+      view::set_ime_cursor_area(
         *self.ns_view,
         *self.input_context,
-        logical_spot.x,
-        logical_spot.y,
+        position.to_logical(scale_factor),
+        size.to_logical(scale_factor),
       );
     }
   }
 
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    unsafe { view::set_ime_allowed(*self.ns_view, allowed) }
+  }
+
   #[inline]
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
     let ns_request_type = request_type.map(|ty| match ty {
@@ -1442,6 +2089,21 @@ impl UnownedWindow {
     }
   }
 
+  #[inline]
+  pub fn create_surface_region(&self, rect: Rect) -> Result<SurfaceRegion, ExternalError> {
+    Ok(SurfaceRegion::new(*self.ns_view, rect))
+  }
+
+  #[inline]
+  pub fn sendable_handle(&self) -> Result<u64, ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  #[inline]
+  pub fn adopt_from_handle(&self, _handle: u64) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   pub fn set_progress_bar(&self, progress: ProgressBarState) {
     set_progress_indicator(progress);
   }
@@ -1466,84 +2128,43 @@ impl WindowExtMacOS for UnownedWindow {
 
   #[inline]
   fn set_simple_fullscreen(&self, fullscreen: bool) -> bool {
-    let mut shared_state_lock = self.shared_state.lock().unwrap();
-
-    unsafe {
-      let app = NSApp();
-      let is_native_fullscreen = shared_state_lock.fullscreen.is_some();
-      let is_simple_fullscreen = shared_state_lock.is_simple_fullscreen;
-
-      // Do nothing if native fullscreen is active.
-      if is_native_fullscreen
-        || (fullscreen && is_simple_fullscreen)
-        || (!fullscreen && !is_simple_fullscreen)
-      {
-        return false;
-      }
-
-      if fullscreen {
-        // Remember the original window's settings
-        // Exclude title bar
-        shared_state_lock.standard_frame = Some(NSWindow::contentRectForFrameRect_(
-          *self.ns_window,
-          NSWindow::frame(*self.ns_window),
-        ));
-        shared_state_lock.saved_style = Some(self.ns_window.styleMask());
-        shared_state_lock.save_presentation_opts = Some(app.presentationOptions_());
-
-        // Tell our window's state that we're in fullscreen
-        shared_state_lock.is_simple_fullscreen = true;
-
-        // Simulate pre-Lion fullscreen by hiding the dock and menu bar
-        let presentation_options =
-          NSApplicationPresentationOptions::NSApplicationPresentationAutoHideDock
-            | NSApplicationPresentationOptions::NSApplicationPresentationAutoHideMenuBar;
-        app.setPresentationOptions_(presentation_options);
+    self.set_simple_fullscreen_on(fullscreen, None)
+  }
 
-        // Hide the titlebar
-        util::toggle_style_mask(
-          *self.ns_window,
-          *self.ns_view,
-          NSWindowStyleMask::NSTitledWindowMask,
-          false,
-        );
+  #[inline]
+  fn fullscreen_mode(&self) -> FullscreenMode {
+    self.shared_state.lock().unwrap().fullscreen_mode
+  }
 
-        // Set the window frame to the screen frame size
-        let screen = self.ns_window.screen();
-        let screen_frame = NSScreen::frame(screen);
-        NSWindow::setFrame_display_(*self.ns_window, screen_frame, YES);
+  #[inline]
+  fn set_fullscreen_mode(&self, mode: FullscreenMode) {
+    self.shared_state.lock().unwrap().fullscreen_mode = mode;
+  }
 
-        // Fullscreen windows can't be resized, minimized, or moved
-        util::toggle_style_mask(
-          *self.ns_window,
-          *self.ns_view,
-          NSWindowStyleMask::NSMiniaturizableWindowMask,
-          false,
-        );
-        util::toggle_style_mask(
-          *self.ns_window,
-          *self.ns_view,
-          NSWindowStyleMask::NSResizableWindowMask,
-          false,
-        );
-        NSWindow::setMovable_(*self.ns_window, NO);
+  #[inline]
+  fn set_window_level(&self, level: WindowLevel) {
+    let key = match level {
+      WindowLevel::Normal => ffi::kCGNormalWindowLevelKey,
+      WindowLevel::Floating => ffi::kCGFloatingWindowLevelKey,
+      WindowLevel::ModalPanel => ffi::kCGModalPanelWindowLevelKey,
+      WindowLevel::Status => ffi::kCGStatusWindowLevelKey,
+      WindowLevel::PopUpMenu => ffi::kCGPopUpMenuWindowLevelKey,
+      WindowLevel::ScreenSaver => ffi::kCGScreenSaverWindowLevelKey,
+    };
+    unsafe {
+      let raw_level = ffi::CGWindowLevelForKey(key as i32);
+      util::set_raw_level_async(*self.ns_window, raw_level as i64);
 
-        true
+      // Lets the window be shown in the Space of a fullscreen window.
+      let mut collection_behavior = self.ns_window.collectionBehavior();
+      if level == WindowLevel::Normal {
+        collection_behavior &=
+          !NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary;
       } else {
-        let new_mask = self.saved_style(&mut *shared_state_lock);
-        self.set_style_mask_async(new_mask);
-        shared_state_lock.is_simple_fullscreen = false;
-
-        if let Some(presentation_opts) = shared_state_lock.save_presentation_opts {
-          app.setPresentationOptions_(presentation_opts);
-        }
-
-        let frame = shared_state_lock.saved_standard_frame();
-        NSWindow::setFrame_display_(*self.ns_window, frame, YES);
-        NSWindow::setMovable_(*self.ns_window, YES);
-
-        true
+        collection_behavior |=
+          NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary;
       }
+      self.ns_window.setCollectionBehavior_(collection_behavior);
     }
   }
 
@@ -1618,13 +2239,112 @@ impl WindowExtMacOS for UnownedWindow {
       ns_string_to_rust(tabbing_identifier)
     }
   }
+
+  #[inline]
+  fn set_skip_taskbar(&self, skip: bool) {
+    if skip {
+      DOCK_ICON_MANAGED.store(true, Ordering::Release);
+    }
+    unsafe {
+      let ns_window: &mut Object = &mut **self.ns_window;
+      ns_window.set_ivar::<BOOL>("taoSkipTaskbar", if skip { YES } else { NO });
+      let _: () =
+        msg_send![*self.ns_window, setExcludedFromWindowsMenu: if skip { YES } else { NO }];
+
+      let mut collection_behavior = self.ns_window.collectionBehavior();
+      if skip {
+        collection_behavior |= NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle;
+      } else {
+        collection_behavior &= !NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle;
+      }
+      self.ns_window.setCollectionBehavior_(collection_behavior);
+    }
+    update_dock_icon_visibility(None);
+  }
+}
+
+/// Whether a window ever skipped the taskbar. Until then the activation policy is left to the
+/// application.
+static DOCK_ICON_MANAGED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the dock icon was hidden by [`update_dock_icon_visibility`], as opposed to the
+/// application having chosen a non-regular activation policy itself.
+static DOCK_ICON_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Hides the dock icon while every tao window skips the taskbar, and shows it again once one of
+/// them doesn't.
+///
+/// The dock icon belongs to the application rather than to a window, so this switches between the
+/// regular and accessory activation policies. It only ever undoes a switch it made itself, and does
+/// nothing until a window skipped the taskbar. `closing` is a window which is about to close and
+/// doesn't count anymore.
+pub(crate) fn update_dock_icon_visibility(closing: Option<id>) {
+  if !DOCK_ICON_MANAGED.load(Ordering::Acquire) {
+    return;
+  }
+  // Only the address is sent, to be compared with the windows of the application.
+  let closing = closing.map_or(0, |window| window as usize);
+  let update = move || unsafe { update_dock_icon_visibility_on_main(closing as id) };
+  if util::is_main_thread() {
+    update();
+  } else {
+    Queue::main().exec_async(update);
+  }
+}
+
+unsafe fn update_dock_icon_visibility_on_main(closing: id) {
+  let windows: id = msg_send![NSApp(), windows];
+  let mut has_windows = false;
+  let mut visible = false;
+  for i in 0..NSArray::count(windows) {
+    let window = NSArray::objectAtIndex(windows, i);
+    let is_tao_window: BOOL = msg_send![window, isKindOfClass: WINDOW_CLASS.0];
+    if is_tao_window == NO || window == closing {
+      continue;
+    }
+    has_windows = true;
+    let skip_taskbar: BOOL = *(*window).get_ivar("taoSkipTaskbar");
+    if skip_taskbar == NO {
+      visible = true;
+      break;
+    }
+  }
+  let visible = visible || !has_windows;
+
+  let policy: NSInteger = msg_send![NSApp(), activationPolicy];
+  let regular = NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular as NSInteger;
+  let accessory =
+    NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory as NSInteger;
+  if !visible && policy == regular {
+    let _ = NSApp()
+      .setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory);
+    DOCK_ICON_HIDDEN.store(true, Ordering::Release);
+  } else if visible && policy == accessory && DOCK_ICON_HIDDEN.load(Ordering::Acquire) {
+    let _ = NSApp()
+      .setActivationPolicy_(NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular);
+    DOCK_ICON_HIDDEN.store(false, Ordering::Release);
+  } else {
+    return;
+  }
+  AppState::queue_event(EventWrapper::StaticEvent(
+    Event::DockIconVisibilityChanged {
+      visible: !DOCK_ICON_HIDDEN.load(Ordering::Acquire),
+    },
+  ));
 }
 
 impl Drop for UnownedWindow {
   fn drop(&mut self) {
     trace!("Dropping `UnownedWindow` ({:?})", self as *mut _);
+    ACCELERATORS.lock().unwrap().remove(&self.id());
+    // Give the keyboard back if this window was holding it.
+    if self.shared_state.lock().unwrap().keyboard_grab && self.is_focused() {
+      let _ = keyboard_grab::set_keyboard_grabbed(false);
+    }
     // Close the window if it has not yet been closed.
     if *self.ns_window != nil {
+      // The window only goes away once closed, so stop it from keeping the dock icon visible now.
+      update_dock_icon_visibility(Some(*self.ns_window));
       unsafe { util::close_async(self.ns_window.clone()) };
     }
   }
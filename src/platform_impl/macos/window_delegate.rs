@@ -5,6 +5,7 @@
 use std::{
   f64,
   os::raw::c_void,
+  path::PathBuf,
   sync::{Arc, Weak},
 };
 
@@ -20,11 +21,12 @@ use objc::{
 
 use crate::{
   dpi::{LogicalPosition, LogicalSize},
-  event::{Event, WindowEvent},
+  event::{Event, FullscreenTransition, WindowEvent},
   keyboard::ModifiersState,
   platform_impl::platform::{
     app_state::AppState,
     event::{EventProxy, EventWrapper},
+    ffi, frame_pacing, keyboard_grab,
     util::{self, IdRef},
     view::ViewState,
     window::{get_ns_theme, get_window_id, UnownedWindow},
@@ -56,6 +58,14 @@ pub struct WindowDelegateState {
   // Used to prevent resized events from being fired
   // when we are using our workaround in the `is_zoomed` function.
   is_checking_zoomed_in: bool,
+
+  // Set when the user entered or exited native fullscreen, so that `FullscreenStateChanged` is
+  // emitted once the transition is done.
+  external_fullscreen_change: bool,
+
+  // Whether the files dragged over the window are accepted by its `DropPolicy`, so that
+  // `HoveredFileCancelled` is only emitted after `HoveredFile`.
+  hovered_is_valid: bool,
 }
 
 impl WindowDelegateState {
@@ -69,6 +79,8 @@ impl WindowDelegateState {
       previous_position: None,
       previous_scale_factor: scale_factor,
       is_checking_zoomed_in: false,
+      external_fullscreen_change: false,
+      hovered_is_valid: false,
     };
     if (scale_factor - 1.0).abs() > f64::EPSILON {
       delegate_state.emit_static_scale_factor_changed_event();
@@ -191,6 +203,10 @@ lazy_static! {
       sel!(windowDidMove:),
       window_did_move as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(windowDidChangeScreen:),
+      window_did_change_screen as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(windowDidChangeBackingProperties:),
       window_did_change_backing_properties as extern "C" fn(&Object, Sel, id),
@@ -250,6 +266,10 @@ lazy_static! {
       sel!(windowDidFailToEnterFullScreen:),
       window_did_fail_to_enter_fullscreen as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(windowDidChangeOcclusionState:),
+      window_did_change_occlusion_state as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(effectiveAppearanceDidChange:),
       effective_appearance_did_change as extern "C" fn(&Object, Sel, id),
@@ -333,6 +353,7 @@ extern "C" fn window_will_close(this: &Object, _: Sel, _: id) {
     // be called after the window closes.
     let () = msg_send![*state.ns_window, setDelegate: nil];
     pool.drain();
+    frame_pacing::set_jank_detection(WindowId(get_window_id(*state.ns_window)), None);
     state.emit_event(WindowEvent::Destroyed);
   });
   trace!("Completed `windowWillClose:`");
@@ -358,6 +379,16 @@ extern "C" fn window_did_move(this: &Object, _: Sel, _: id) {
   trace!("Completed `windowDidMove:`");
 }
 
+extern "C" fn window_did_change_screen(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidChangeScreen:`");
+  with_state(this, |state| {
+    state.with_window(|window| {
+      frame_pacing::set_monitor(WindowId(window.id()), window.current_monitor_inner().inner)
+    });
+  });
+  trace!("Completed `windowDidChangeScreen:`");
+}
+
 extern "C" fn window_did_change_backing_properties(this: &Object, _: Sel, _: id) {
   trace!("Triggered `windowDidChangeBackingProperties:`");
   with_state(this, |state| {
@@ -371,6 +402,11 @@ extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) {
   with_state(this, |state| {
     // TODO: center the cursor if the window had mouse grab when it
     // lost focus
+    state.with_window(|window| {
+      if window.shared_state.lock().unwrap().keyboard_grab {
+        let _ = keyboard_grab::set_keyboard_grabbed(true);
+      }
+    });
     state.emit_event(WindowEvent::Focused(true));
   });
   trace!("Completed `windowDidBecomeKey:`");
@@ -402,36 +438,65 @@ extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
       state.emit_event(WindowEvent::ModifiersChanged(view_state.modifiers));
     }
 
+    state.with_window(|window| {
+      if window.shared_state.lock().unwrap().keyboard_grab {
+        let _ = keyboard_grab::set_keyboard_grabbed(false);
+      }
+    });
+
     state.emit_event(WindowEvent::Focused(false));
   });
   trace!("Completed `windowDidResignKey:`");
 }
 
-/// Invoked when the dragged image enters destination bounds or frame
-extern "C" fn dragging_entered(this: &Object, _: Sel, sender: id) -> BOOL {
-  trace!("Triggered `draggingEntered:`");
-
+/// Returns the dragged files if the window's `DropPolicy` accepts them.
+fn accepted_filenames(state: &mut WindowDelegateState, sender: id) -> Option<Vec<PathBuf>> {
   use cocoa::{appkit::NSPasteboard, foundation::NSFastEnumeration};
-  use std::path::PathBuf;
+  use std::ffi::CStr;
 
   let pb: id = unsafe { msg_send![sender, draggingPasteboard] };
   let filenames = unsafe { NSPasteboard::propertyListForType(pb, appkit::NSFilenamesPboardType) };
+  if filenames == nil {
+    return None;
+  }
 
-  for file in unsafe { filenames.iter() } {
-    use std::ffi::CStr;
-
-    unsafe {
+  let filenames: Vec<PathBuf> = unsafe { filenames.iter() }
+    .map(|file| unsafe {
       let f = NSString::UTF8String(file);
-      let path = CStr::from_ptr(f).to_string_lossy().into_owned();
+      PathBuf::from(CStr::from_ptr(f).to_string_lossy().into_owned())
+    })
+    .collect();
+  let accepted = state
+    .with_window(|window| {
+      let shared_state = window.shared_state.lock().unwrap();
+      shared_state.drop_policy.accepts(&filenames)
+    })
+    .unwrap_or(false);
+  if accepted {
+    Some(filenames)
+  } else {
+    None
+  }
+}
 
-      with_state(this, |state| {
-        state.emit_event(WindowEvent::HoveredFile(PathBuf::from(path)));
-      });
+/// Invoked when the dragged image enters destination bounds or frame
+extern "C" fn dragging_entered(this: &Object, _: Sel, sender: id) -> BOOL {
+  trace!("Triggered `draggingEntered:`");
+
+  let mut accepted = NO;
+  with_state(this, |state| {
+    let filenames = accepted_filenames(state, sender);
+    state.hovered_is_valid = filenames.is_some();
+    if state.hovered_is_valid {
+      accepted = YES;
     }
-  }
+    for filename in filenames.into_iter().flatten() {
+      state.emit_event(WindowEvent::HoveredFile(filename));
+    }
+  });
 
   trace!("Completed `draggingEntered:`");
-  YES
+  accepted
 }
 
 /// Invoked when the image is released
@@ -445,27 +510,20 @@ extern "C" fn prepare_for_drag_operation(_: &Object, _: Sel, _: id) -> BOOL {
 extern "C" fn perform_drag_operation(this: &Object, _: Sel, sender: id) -> BOOL {
   trace!("Triggered `performDragOperation:`");
 
-  use cocoa::{appkit::NSPasteboard, foundation::NSFastEnumeration};
-  use std::path::PathBuf;
-
-  let pb: id = unsafe { msg_send![sender, draggingPasteboard] };
-  let filenames = unsafe { NSPasteboard::propertyListForType(pb, appkit::NSFilenamesPboardType) };
-
-  for file in unsafe { filenames.iter() } {
-    use std::ffi::CStr;
-
-    unsafe {
-      let f = NSString::UTF8String(file);
-      let path = CStr::from_ptr(f).to_string_lossy().into_owned();
-
-      with_state(this, |state| {
-        state.emit_event(WindowEvent::DroppedFile(PathBuf::from(path)));
-      });
+  let mut accepted = NO;
+  with_state(this, |state| {
+    state.hovered_is_valid = false;
+    let filenames = accepted_filenames(state, sender);
+    if filenames.is_some() {
+      accepted = YES;
     }
-  }
+    for filename in filenames.into_iter().flatten() {
+      state.emit_event(WindowEvent::DroppedFile(filename));
+    }
+  });
 
   trace!("Completed `performDragOperation:`");
-  YES
+  accepted
 }
 
 /// Invoked when the dragging operation is complete
@@ -478,7 +536,9 @@ extern "C" fn conclude_drag_operation(_: &Object, _: Sel, _: id) {
 extern "C" fn dragging_exited(this: &Object, _: Sel, _: id) {
   trace!("Triggered `draggingExited:`");
   with_state(this, |state| {
-    state.emit_event(WindowEvent::HoveredFileCancelled)
+    if std::mem::replace(&mut state.hovered_is_valid, false) {
+      state.emit_event(WindowEvent::HoveredFileCancelled)
+    }
   });
   trace!("Completed `draggingExited:`");
 }
@@ -488,7 +548,7 @@ extern "C" fn window_will_enter_fullscreen(this: &Object, _: Sel, _: id) {
   trace!("Triggered `windowWillEnterFullscreen:`");
 
   with_state(this, |state| {
-    state.with_window(|window| {
+    let external = state.with_window(|window| {
       trace!("Locked shared state in `window_will_enter_fullscreen`");
       let mut shared_state = window.shared_state.lock().unwrap();
       shared_state.maximized = window.is_zoomed();
@@ -501,16 +561,27 @@ extern "C" fn window_will_enter_fullscreen(this: &Object, _: Sel, _: id) {
         // in fullscreen, so we must've reached here by `set_fullscreen`
         // as it updates the state
         Some(Fullscreen::Borderless(_)) => (),
+        // Spanning isn't a native fullscreen, so it must have been left by `set_fullscreen`
+        // before getting here.
+        Some(Fullscreen::Spanning(_)) => (),
         // Otherwise, we must've reached fullscreen by the user clicking
         // on the green fullscreen button. Update state!
         None => {
           let current_monitor = Some(window.current_monitor_inner());
-          shared_state.fullscreen = Some(Fullscreen::Borderless(current_monitor))
+          shared_state.fullscreen = Some(Fullscreen::Borderless(current_monitor));
+          shared_state.in_fullscreen_transition = true;
+          trace!("Unlocked shared state in `window_will_enter_fullscreen`");
+          return true;
         }
       }
       shared_state.in_fullscreen_transition = true;
       trace!("Unlocked shared state in `window_will_enter_fullscreen`");
-    })
+      false
+    });
+    state.external_fullscreen_change = external.unwrap_or(false);
+    state.emit_event(WindowEvent::FullscreenTransition(
+      FullscreenTransition::WillEnter,
+    ));
   });
   trace!("Completed `windowWillEnterFullscreen:`");
 }
@@ -520,12 +591,18 @@ extern "C" fn window_will_exit_fullscreen(this: &Object, _: Sel, _: id) {
   trace!("Triggered `windowWillExitFullScreen:`");
 
   with_state(this, |state| {
-    state.with_window(|window| {
+    let external = state.with_window(|window| {
       trace!("Locked shared state in `window_will_exit_fullscreen`");
       let mut shared_state = window.shared_state.lock().unwrap();
       shared_state.in_fullscreen_transition = true;
       trace!("Unlocked shared state in `window_will_exit_fullscreen`");
+      // `set_fullscreen` clears the state before exiting, so the user must have exited.
+      shared_state.fullscreen.is_some()
     });
+    state.external_fullscreen_change = external.unwrap_or(false);
+    state.emit_event(WindowEvent::FullscreenTransition(
+      FullscreenTransition::WillExit,
+    ));
   });
   trace!("Completed `windowWillExitFullScreen:`");
 }
@@ -580,6 +657,13 @@ extern "C" fn window_did_enter_fullscreen(this: &Object, _: Sel, _: id) {
     });
     state.emit_resize_event();
     state.emit_move_event();
+    if std::mem::take(&mut state.external_fullscreen_change) {
+      let fullscreen = state.with_window(|window| window.fullscreen()).flatten();
+      state.emit_event(WindowEvent::FullscreenStateChanged(fullscreen));
+    }
+    state.emit_event(WindowEvent::FullscreenTransition(
+      FullscreenTransition::DidEnter,
+    ));
   });
   trace!("Completed `windowDidEnterFullscreen:`");
 }
@@ -602,6 +686,12 @@ extern "C" fn window_did_exit_fullscreen(this: &Object, _: Sel, _: id) {
     });
     state.emit_resize_event();
     state.emit_move_event();
+    if std::mem::take(&mut state.external_fullscreen_change) {
+      state.emit_event(WindowEvent::FullscreenStateChanged(None));
+    }
+    state.emit_event(WindowEvent::FullscreenTransition(
+      FullscreenTransition::DidExit,
+    ));
   });
   trace!("Completed `windowDidExitFullscreen:`");
 }
@@ -632,6 +722,7 @@ extern "C" fn window_did_fail_to_enter_fullscreen(this: &Object, _: Sel, _: id)
       shared_state.target_fullscreen = None;
       trace!("Unlocked shared state in `window_did_fail_to_enter_fullscreen`");
     });
+    state.external_fullscreen_change = false;
     if state.initial_fullscreen {
       let _: () = unsafe {
         msg_send![*state.ns_window,
@@ -647,6 +738,18 @@ extern "C" fn window_did_fail_to_enter_fullscreen(this: &Object, _: Sel, _: id)
   trace!("Completed `windowDidFailToEnterFullscreen:`");
 }
 
+/// Invoked when the window is covered by other windows, minimized or moved to another space,
+/// and when it becomes visible again.
+extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidChangeOcclusionState:`");
+  with_state(this, |state| {
+    let occlusion_state: NSUInteger = unsafe { msg_send![*state.ns_window, occlusionState] };
+    let visible = occlusion_state & ffi::NSWindowOcclusionStateVisible != 0;
+    state.emit_event(WindowEvent::Occluded(!visible));
+  });
+  trace!("Completed `windowDidChangeOcclusionState:`");
+}
+
 // Observe theme change
 extern "C" fn effective_appearance_did_change(this: &Object, _: Sel, _: id) {
   trace!("Triggered `effectiveAppearDidChange:`");
@@ -26,6 +26,17 @@ mod platform;
 
 pub use platform::*;
 
+#[cfg(any(
+  target_os = "windows",
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+  target_os = "macos"
+))]
+pub(crate) mod edid;
+
 #[cfg(all(
   not(target_os = "ios"),
   not(target_os = "windows"),
@@ -0,0 +1,158 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Accelerator tables of the windows, see `Window::add_accelerator`.
+//!
+//! The event loop translates the key presses of a window and of its child windows with the table
+//! of the top-level window, which then receives a `WM_COMMAND` with the command id of the
+//! accelerator. A table is rebuilt each time an accelerator is added or removed, with the command
+//! ids following the order of the accelerators.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use windows::Win32::{
+  Foundation::HWND,
+  UI::{
+    Input::KeyboardAndMouse::{GetKeyboardLayout, VIRTUAL_KEY},
+    WindowsAndMessaging::*,
+  },
+};
+
+use crate::{
+  error::{ExternalError, NotSupportedError},
+  global_shortcut::{Accelerator, AcceleratorId},
+  keyboard::ModifiersState,
+  platform_impl::platform::{keyboard_layout::keycode_to_vkey, OsError},
+};
+
+/// Command id of the first accelerator of a window, above the ids menus usually use.
+const FIRST_COMMAND_ID: u16 = 0xA000;
+/// Number of accelerators a window can have.
+const MAX_ACCELERATORS: usize = 0x1000;
+
+struct WindowAccelerators {
+  accelerators: Vec<(AcceleratorId, ACCEL)>,
+  table: HACCEL,
+}
+
+static ACCELERATORS: Lazy<Mutex<HashMap<isize, WindowAccelerators>>> = Lazy::new(Default::default);
+
+pub fn add(hwnd: HWND, id: AcceleratorId, accelerator: Accelerator) -> Result<(), ExternalError> {
+  if accelerator.mods.contains(ModifiersState::SUPER) {
+    return Err(ExternalError::NotSupported(NotSupportedError::new()));
+  }
+  let vkey = keycode_to_vkey(accelerator.key, unsafe { GetKeyboardLayout(0) });
+  if vkey == VIRTUAL_KEY::default() {
+    return Err(ExternalError::Os(os_error!(OsError::IoError(
+      std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "the key isn't on the keyboard layout",
+      )
+    ))));
+  }
+  let mut flags = FVIRTKEY;
+  for (modifier, flag) in [
+    (ModifiersState::SHIFT, FSHIFT),
+    (ModifiersState::CONTROL, FCONTROL),
+    (ModifiersState::ALT, FALT),
+  ] {
+    if accelerator.mods.contains(modifier) {
+      flags |= flag;
+    }
+  }
+
+  let mut windows = ACCELERATORS.lock();
+  let window = windows.entry(hwnd.0).or_insert_with(|| WindowAccelerators {
+    accelerators: Vec::new(),
+    table: HACCEL::default(),
+  });
+  if window.accelerators.len() >= MAX_ACCELERATORS {
+    return Err(ExternalError::Os(os_error!(OsError::IoError(
+      std::io::Error::new(std::io::ErrorKind::Other, "too many accelerators")
+    ))));
+  }
+  window.accelerators.push((
+    id,
+    ACCEL {
+      fVirt: flags,
+      key: vkey.0,
+      cmd: 0,
+    },
+  ));
+  window.rebuild();
+  Ok(())
+}
+
+pub fn remove(hwnd: HWND, id: AcceleratorId) {
+  let mut windows = ACCELERATORS.lock();
+  if let Some(window) = windows.get_mut(&hwnd.0) {
+    window
+      .accelerators
+      .retain(|(accelerator_id, _)| *accelerator_id != id);
+    window.rebuild();
+  }
+}
+
+/// Destroys the table of a window, when it's destroyed.
+pub fn remove_window(hwnd: HWND) {
+  if let Some(mut window) = ACCELERATORS.lock().remove(&hwnd.0) {
+    window.destroy_table();
+  }
+}
+
+/// Translates `msg` with the accelerators of its top-level window, returning whether it was
+/// translated to a `WM_COMMAND` and must not be dispatched.
+pub unsafe fn translate(msg: &MSG) -> bool {
+  if !matches!(msg.message, WM_KEYDOWN | WM_SYSKEYDOWN) {
+    return false;
+  }
+  let root = GetAncestor(msg.hwnd, GA_ROOT);
+  // The lock is released first, the `WM_COMMAND` is sent to the window right away.
+  let table = match ACCELERATORS.lock().get(&root.0) {
+    Some(window) if !window.table.is_invalid() => window.table,
+    _ => return false,
+  };
+  TranslateAcceleratorW(root, table, msg) != 0
+}
+
+/// Returns the accelerator of `hwnd` which sent the `WM_COMMAND` with `command_id`.
+pub fn find(hwnd: HWND, command_id: u16) -> Option<AcceleratorId> {
+  let index = usize::from(command_id.checked_sub(FIRST_COMMAND_ID)?);
+  let windows = ACCELERATORS.lock();
+  windows
+    .get(&hwnd.0)?
+    .accelerators
+    .get(index)
+    .map(|(id, _)| *id)
+}
+
+impl WindowAccelerators {
+  fn rebuild(&mut self) {
+    self.destroy_table();
+    if self.accelerators.is_empty() {
+      return;
+    }
+    let entries: Vec<ACCEL> = self
+      .accelerators
+      .iter()
+      .enumerate()
+      .map(|(index, (_, accel))| ACCEL {
+        cmd: FIRST_COMMAND_ID + index as u16,
+        ..*accel
+      })
+      .collect();
+    self.table = unsafe { CreateAcceleratorTableW(&entries) }.unwrap_or_default();
+  }
+
+  fn destroy_table(&mut self) {
+    if !self.table.is_invalid() {
+      unsafe {
+        let _ = DestroyAcceleratorTable(self.table);
+      }
+      self.table = HACCEL::default();
+    }
+  }
+}
@@ -10,14 +10,21 @@ use windows::{
   core::{s, w, PCSTR, PSTR},
   Win32::{
     Foundation::{BOOL, HANDLE, HMODULE, HWND},
-    System::LibraryLoader::*,
+    Graphics::Dwm::*,
+    System::{
+      LibraryLoader::*,
+      Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD},
+    },
     UI::{Accessibility::*, WindowsAndMessaging::*},
   },
 };
 
-use std::ffi::c_void;
+use std::{ffi::c_void, mem};
 
-use crate::window::Theme;
+use crate::{
+  platform::windows::TitleBarColors,
+  window::{SystemAppearance, Theme},
+};
 
 static HUXTHEME: Lazy<HMODULE> =
   Lazy::new(|| unsafe { LoadLibraryA(s!("uxtheme.dll")).unwrap_or_default() });
@@ -207,6 +214,45 @@ fn refresh_titlebar_theme_color(hwnd: HWND, is_dark_mode: bool) {
       };
       unsafe { set_window_composition_attribute(hwnd, &mut data as *mut _) };
     }
+
+    // The documented attribute, which the title bar also follows when the theme is switched after
+    // the window was shown.
+    if ver >= 18985 {
+      let value = BOOL::from(is_dark_mode);
+      unsafe {
+        let _ = DwmSetWindowAttribute(
+          hwnd,
+          DWMWA_USE_IMMERSIVE_DARK_MODE,
+          &value as *const BOOL as _,
+          mem::size_of::<BOOL>() as u32,
+        );
+      }
+    }
+  }
+}
+
+/// Sets the colors of the title bar and border of the window, from Windows 11 on.
+pub fn set_title_bar_colors(hwnd: HWND, colors: TitleBarColors) {
+  if !WIN10_BUILD_VERSION.map_or(false, |ver| ver >= 22000) {
+    return;
+  }
+  for (attribute, color) in [
+    (DWMWA_CAPTION_COLOR, colors.caption),
+    (DWMWA_BORDER_COLOR, colors.border),
+    (DWMWA_TEXT_COLOR, colors.text),
+  ] {
+    // `COLORREF` is laid out as 0x00BBGGRR.
+    let value = color.map_or(DWMWA_COLOR_DEFAULT, |[r, g, b]| {
+      u32::from_le_bytes([r, g, b, 0])
+    });
+    unsafe {
+      let _ = DwmSetWindowAttribute(
+        hwnd,
+        attribute,
+        &value as *const u32 as _,
+        mem::size_of::<u32>() as u32,
+      );
+    }
   }
 }
 
@@ -254,3 +300,52 @@ fn is_high_contrast() -> bool {
 
   ok.is_ok() && (HCF_HIGHCONTRASTON & hc.dwFlags.0) != 0
 }
+
+/// Reads the appearance preferences of the system, which change along with `WM_SETTINGCHANGE` and
+/// `WM_DWMCOLORIZATIONCOLORCHANGED`.
+pub fn system_appearance() -> SystemAppearance {
+  SystemAppearance {
+    accent_color: accent_color(),
+    high_contrast: is_high_contrast(),
+    reduce_motion: !client_area_animation(),
+    reduce_transparency: !transparency_enabled(),
+  }
+}
+
+fn accent_color() -> Option<[u8; 4]> {
+  let mut color = 0u32;
+  let mut opaque = BOOL::default();
+  unsafe { DwmGetColorizationColor(&mut color, &mut opaque) }.ok()?;
+  let [a, r, g, b] = color.to_be_bytes();
+  Some([r, g, b, a])
+}
+
+fn client_area_animation() -> bool {
+  let mut enabled = BOOL(1);
+  let ok = unsafe {
+    SystemParametersInfoW(
+      SPI_GETCLIENTAREAANIMATION,
+      0,
+      Some(&mut enabled as *mut _ as _),
+      Default::default(),
+    )
+  };
+  ok.is_err() || enabled.as_bool()
+}
+
+fn transparency_enabled() -> bool {
+  let mut enabled = 1u32;
+  let mut len = mem::size_of::<u32>() as u32;
+  let result = unsafe {
+    RegGetValueW(
+      HKEY_CURRENT_USER,
+      w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+      w!("EnableTransparency"),
+      RRF_RT_REG_DWORD,
+      None,
+      Some(&mut enabled as *mut u32 as _),
+      Some(&mut len),
+    )
+  };
+  result.is_err() || enabled != 0
+}
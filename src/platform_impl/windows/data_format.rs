@@ -0,0 +1,34 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+
+use windows::{core::PCWSTR, Win32::System::DataExchange::RegisterClipboardFormatW};
+
+use crate::{
+  error::OsError as RootOsError,
+  platform_impl::platform::{util, OsError},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataFormat {
+  clipboard_format: u32,
+}
+
+impl DataFormat {
+  pub fn register(mime_type: &str) -> Result<Self, RootOsError> {
+    let name = util::encode_wide(mime_type);
+    // Formats are shared by all processes of the session, registering a name twice gives the
+    // same format.
+    let clipboard_format = unsafe { RegisterClipboardFormatW(PCWSTR::from_raw(name.as_ptr())) };
+    if clipboard_format == 0 {
+      return Err(os_error!(OsError::IoError(io::Error::last_os_error())));
+    }
+    Ok(Self { clipboard_format })
+  }
+
+  pub fn clipboard_format(&self) -> u32 {
+    self.clipboard_format
+  }
+}
@@ -12,33 +12,118 @@ use windows::Win32::{
   UI::{HiDpi::*, WindowsAndMessaging::*},
 };
 
-use crate::platform_impl::platform::util::{
-  ENABLE_NON_CLIENT_DPI_SCALING, GET_DPI_FOR_MONITOR, GET_DPI_FOR_WINDOW, SET_PROCESS_DPI_AWARE,
-  SET_PROCESS_DPI_AWARENESS, SET_PROCESS_DPI_AWARENESS_CONTEXT,
+use crate::{
+  platform::windows::DpiAwareness,
+  platform_impl::platform::util::{
+    ENABLE_NON_CLIENT_DPI_SCALING, GET_DPI_FOR_MONITOR, GET_DPI_FOR_WINDOW, SET_PROCESS_DPI_AWARE,
+    SET_PROCESS_DPI_AWARENESS, SET_PROCESS_DPI_AWARENESS_CONTEXT, SET_THREAD_DPI_AWARENESS_CONTEXT,
+    SET_THREAD_DPI_HOSTING_BEHAVIOR,
+  },
 };
 
-pub fn become_dpi_aware() {
+fn awareness_context(dpi_awareness: DpiAwareness) -> DPI_AWARENESS_CONTEXT {
+  match dpi_awareness {
+    DpiAwareness::PerMonitorV2 => DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    DpiAwareness::PerMonitor => DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
+    DpiAwareness::System => DPI_AWARENESS_CONTEXT_SYSTEM_AWARE,
+    DpiAwareness::Unaware => DPI_AWARENESS_CONTEXT_UNAWARE,
+    DpiAwareness::UnawareGdiScaled => DPI_AWARENESS_CONTEXT_UNAWARE_GDISCALED,
+  }
+}
+
+/// The context to use when the one of `dpi_awareness` is unknown to the system.
+fn fallback_awareness_context(dpi_awareness: DpiAwareness) -> Option<DPI_AWARENESS_CONTEXT> {
+  match dpi_awareness {
+    // V2 only works with Windows 10 Creators Update (1703).
+    DpiAwareness::PerMonitorV2 => Some(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE),
+    // GDI scaling only works with Windows 10 October 2018 Update (1809).
+    DpiAwareness::UnawareGdiScaled => Some(DPI_AWARENESS_CONTEXT_UNAWARE),
+    _ => None,
+  }
+}
+
+pub fn become_dpi_aware(dpi_awareness: DpiAwareness) {
   static ENABLE_DPI_AWARENESS: Once = Once::new();
   ENABLE_DPI_AWARENESS.call_once(|| {
     unsafe {
       if let Some(SetProcessDpiAwarenessContext) = *SET_PROCESS_DPI_AWARENESS_CONTEXT {
         // We are on Windows 10 Anniversary Update (1607) or later.
-        if !SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2).as_bool() {
-          // V2 only works with Windows 10 Creators Update (1703). Try using the older
-          // V1 if we can't set V2.
-          SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE);
+        if !SetProcessDpiAwarenessContext(awareness_context(dpi_awareness)).as_bool() {
+          if let Some(context) = fallback_awareness_context(dpi_awareness) {
+            SetProcessDpiAwarenessContext(context);
+          }
         }
       } else if let Some(SetProcessDpiAwareness) = *SET_PROCESS_DPI_AWARENESS {
         // We are on Windows 8.1 or later.
-        let _ = SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE);
+        let awareness = match dpi_awareness {
+          DpiAwareness::PerMonitorV2 | DpiAwareness::PerMonitor => PROCESS_PER_MONITOR_DPI_AWARE,
+          DpiAwareness::System => PROCESS_SYSTEM_DPI_AWARE,
+          DpiAwareness::Unaware | DpiAwareness::UnawareGdiScaled => PROCESS_DPI_UNAWARE,
+        };
+        let _ = SetProcessDpiAwareness(awareness);
       } else if let Some(SetProcessDPIAware) = *SET_PROCESS_DPI_AWARE {
-        // We are on Vista or later.
-        SetProcessDPIAware();
+        // We are on Vista or later, which only knows about system awareness.
+        if !matches!(
+          dpi_awareness,
+          DpiAwareness::Unaware | DpiAwareness::UnawareGdiScaled
+        ) {
+          SetProcessDPIAware();
+        }
       }
     }
   });
 }
 
+/// Makes the thread create windows with the DPI awareness `dpi_awareness`, and allows them to host
+/// child windows of another awareness, until it's dropped.
+///
+/// Does nothing before Windows 10 1607, where the awareness is the one of the process.
+pub struct ThreadDpiContext {
+  previous_context: Option<DPI_AWARENESS_CONTEXT>,
+  previous_hosting: Option<DPI_HOSTING_BEHAVIOR>,
+}
+
+impl ThreadDpiContext {
+  pub fn enter(dpi_awareness: DpiAwareness) -> Self {
+    unsafe {
+      let previous_context = SET_THREAD_DPI_AWARENESS_CONTEXT.and_then(|set_context| {
+        let previous = set_context(awareness_context(dpi_awareness));
+        let previous = if previous.0 == 0 {
+          set_context(fallback_awareness_context(dpi_awareness)?)
+        } else {
+          previous
+        };
+        Some(previous).filter(|previous| previous.0 != 0)
+      });
+      // The mixed hosting behavior is only available from Windows 10 1803 on.
+      let previous_hosting = SET_THREAD_DPI_HOSTING_BEHAVIOR
+        .map(|set_hosting| set_hosting(DPI_HOSTING_BEHAVIOR_MIXED))
+        .filter(|previous| *previous != DPI_HOSTING_BEHAVIOR_INVALID);
+      Self {
+        previous_context,
+        previous_hosting,
+      }
+    }
+  }
+}
+
+impl Drop for ThreadDpiContext {
+  fn drop(&mut self) {
+    unsafe {
+      if let (Some(set_context), Some(previous)) =
+        (*SET_THREAD_DPI_AWARENESS_CONTEXT, self.previous_context)
+      {
+        set_context(previous);
+      }
+      if let (Some(set_hosting), Some(previous)) =
+        (*SET_THREAD_DPI_HOSTING_BEHAVIOR, self.previous_hosting)
+      {
+        set_hosting(previous);
+      }
+    }
+  }
+}
+
 pub fn enable_non_client_dpi_scaling(hwnd: HWND) {
   unsafe {
     if let Some(EnableNonClientDpiScaling) = *ENABLE_NON_CLIENT_DPI_SCALING {
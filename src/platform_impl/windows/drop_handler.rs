@@ -2,16 +2,37 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{cell::UnsafeCell, ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf, ptr};
+use std::{
+  cell::{RefCell, UnsafeCell},
+  env,
+  ffi::OsString,
+  fs::{self, File},
+  io::{self, Write},
+  os::windows::ffi::OsStringExt,
+  path::{Component, Path, PathBuf},
+  process, ptr, slice,
+  sync::atomic::{AtomicU32, Ordering},
+};
 
+use once_cell::sync::Lazy;
 use windows::Win32::{
   Foundation::{self as win32f, HWND, POINTL},
   System::{
-    Com::{IDataObject, DVASPECT_CONTENT, FORMATETC, TYMED_HGLOBAL},
-    Ole::{IDropTarget, IDropTarget_Impl, CF_HDROP, DROPEFFECT, DROPEFFECT_COPY, DROPEFFECT_NONE},
+    Com::{
+      IDataObject, DVASPECT_CONTENT, FORMATETC, STGMEDIUM, TYMED, TYMED_HGLOBAL, TYMED_ISTREAM,
+    },
+    DataExchange::RegisterClipboardFormatW,
+    Memory::{GlobalLock, GlobalSize, GlobalUnlock},
+    Ole::{
+      IDropTarget, IDropTarget_Impl, ReleaseStgMedium, CF_HDROP, DROPEFFECT, DROPEFFECT_COPY,
+      DROPEFFECT_NONE,
+    },
     SystemServices::MODIFIERKEYS_FLAGS,
   },
-  UI::Shell::{DragFinish, DragQueryFileW, HDROP},
+  UI::Shell::{
+    DragFinish, DragQueryFileW, CFSTR_FILECONTENTS, CFSTR_FILEDESCRIPTORW, FD_ATTRIBUTES,
+    FD_FILESIZE, FILEGROUPDESCRIPTORW, HDROP,
+  },
 };
 
 use windows_implement::implement;
@@ -20,24 +41,75 @@ use crate::platform_impl::platform::WindowId;
 
 use crate::{event::Event, window::WindowId as SuperWindowId};
 
+/// Clipboard formats of the virtual files, which only exist in the data object that describes them
+/// and streams their contents, like the attachments dragged from Outlook.
+static FILE_DESCRIPTOR_FORMAT: Lazy<u16> =
+  Lazy::new(|| unsafe { RegisterClipboardFormatW(CFSTR_FILEDESCRIPTORW) } as u16);
+static FILE_CONTENTS_FORMAT: Lazy<u16> =
+  Lazy::new(|| unsafe { RegisterClipboardFormatW(CFSTR_FILECONTENTS) } as u16);
+
+/// `FILE_ATTRIBUTE_DIRECTORY` of `Win32_Storage_FileSystem`.
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+/// A virtual file described by a data object.
+struct VirtualFile {
+  /// Index of the file in the group descriptor, by which its contents are requested.
+  index: usize,
+  /// Path relative to the folder the files are dropped in, the files of a dragged folder have the
+  /// folder in their path.
+  path: PathBuf,
+  is_directory: bool,
+  size: Option<u64>,
+}
+
 #[implement(IDropTarget)]
 pub struct FileDropHandler {
   window: HWND,
+  accepts: Box<dyn Fn(&[PathBuf]) -> bool>,
   send_event: Box<dyn Fn(Event<'static, ()>)>,
   cursor_effect: UnsafeCell<DROPEFFECT>,
   hovered_is_valid: UnsafeCell<bool>, /* If the currently hovered item is not valid there must not be any `HoveredFileCancelled` emitted */
 }
 
 impl FileDropHandler {
-  pub fn new(window: HWND, send_event: Box<dyn Fn(Event<'static, ()>)>) -> FileDropHandler {
+  pub fn new(
+    window: HWND,
+    accepts: Box<dyn Fn(&[PathBuf]) -> bool>,
+    send_event: Box<dyn Fn(Event<'static, ()>)>,
+  ) -> FileDropHandler {
     Self {
       window,
+      accepts,
       send_event,
       cursor_effect: DROPEFFECT_NONE.into(),
       hovered_is_valid: false.into(),
     }
   }
 
+  /// Returns the dragged files if the window's `DropPolicy` accepts them.
+  ///
+  /// The virtual files, dragged without an `HDROP`, are named by their relative path since they
+  /// aren't saved before being dropped.
+  unsafe fn accepted_filenames(
+    &self,
+    data_obj: Option<&IDataObject>,
+  ) -> (Option<HDROP>, Option<Vec<PathBuf>>) {
+    let filenames = RefCell::new(Vec::new());
+    let hdrop = Self::iterate_filenames(data_obj, |filename| filenames.borrow_mut().push(filename));
+    let mut filenames = filenames.into_inner();
+    if hdrop.is_none() {
+      filenames = data_obj
+        .and_then(|data_obj| virtual_files(data_obj))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(VirtualFile::is_top_level)
+        .map(|file| file.path)
+        .collect();
+    }
+    let accepted = (hdrop.is_some() || !filenames.is_empty()) && (self.accepts)(&filenames);
+    (hdrop, if accepted { Some(filenames) } else { None })
+  }
+
   unsafe fn iterate_filenames<F>(data_obj: Option<&IDataObject>, callback: F) -> Option<HDROP>
   where
     F: Fn(PathBuf),
@@ -109,13 +181,14 @@ impl IDropTarget_Impl for FileDropHandler {
   ) -> windows::core::Result<()> {
     use crate::event::WindowEvent::HoveredFile;
     unsafe {
-      let hdrop = Self::iterate_filenames(pDataObj, |filename| {
+      let (_, filenames) = self.accepted_filenames(pDataObj);
+      let hovered_is_valid = filenames.is_some();
+      for filename in filenames.into_iter().flatten() {
         (self.send_event)(Event::WindowEvent {
           window_id: SuperWindowId(WindowId(self.window.0)),
           event: HoveredFile(filename),
         });
-      });
-      let hovered_is_valid = hdrop.is_some();
+      }
       let cursor_effect = if hovered_is_valid {
         DROPEFFECT_COPY
       } else {
@@ -160,12 +233,16 @@ impl IDropTarget_Impl for FileDropHandler {
   ) -> windows::core::Result<()> {
     use crate::event::WindowEvent::DroppedFile;
     unsafe {
-      let hdrop = Self::iterate_filenames(pDataObj, |filename| {
+      let (hdrop, mut filenames) = self.accepted_filenames(pDataObj);
+      if let (None, Some(_), Some(data_obj)) = (hdrop, &filenames, pDataObj) {
+        filenames = Some(save_virtual_files(data_obj));
+      }
+      for filename in filenames.into_iter().flatten() {
         (self.send_event)(Event::WindowEvent {
           window_id: SuperWindowId(WindowId(self.window.0)),
           event: DroppedFile(filename),
         });
-      });
+      }
       if let Some(hdrop) = hdrop {
         DragFinish(hdrop);
       }
@@ -173,3 +250,165 @@ impl IDropTarget_Impl for FileDropHandler {
     Ok(())
   }
 }
+
+impl VirtualFile {
+  fn is_top_level(&self) -> bool {
+    self.path.components().count() == 1
+  }
+}
+
+/// Returns the virtual files of `data_obj`, or `None` if it has none.
+unsafe fn virtual_files(data_obj: &IDataObject) -> Option<Vec<VirtualFile>> {
+  let format = FORMATETC {
+    cfFormat: *FILE_DESCRIPTOR_FORMAT,
+    ptd: ptr::null_mut(),
+    dwAspect: DVASPECT_CONTENT.0,
+    lindex: -1,
+    tymed: TYMED_HGLOBAL.0 as u32,
+  };
+  let mut medium = data_obj.GetData(&format).ok()?;
+  let hglobal = medium.u.hGlobal;
+  let group = GlobalLock(hglobal) as *const FILEGROUPDESCRIPTORW;
+  let files = if group.is_null() {
+    None
+  } else {
+    let descriptors = slice::from_raw_parts((*group).fgd.as_ptr(), (*group).cItems as usize);
+    let files = descriptors
+      .iter()
+      .enumerate()
+      .filter_map(|(index, descriptor)| {
+        // The descriptors are packed, their fields are copied before being borrowed.
+        let file_name = descriptor.cFileName;
+        let name_len = file_name
+          .iter()
+          .position(|&c| c == 0)
+          .unwrap_or(file_name.len());
+        let path = PathBuf::from(OsString::from_wide(&file_name[..name_len]));
+        // The path comes from another process, it must not leave the folder of the drop.
+        if path.as_os_str().is_empty()
+          || !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+          return None;
+        }
+        let flags = descriptor.dwFlags as i32;
+        Some(VirtualFile {
+          index,
+          path,
+          is_directory: flags & FD_ATTRIBUTES.0 != 0
+            && descriptor.dwFileAttributes & FILE_ATTRIBUTE_DIRECTORY != 0,
+          size: if flags & FD_FILESIZE.0 != 0 {
+            Some(u64::from(descriptor.nFileSizeHigh) << 32 | u64::from(descriptor.nFileSizeLow))
+          } else {
+            None
+          },
+        })
+      })
+      .collect();
+    let _ = GlobalUnlock(hglobal);
+    Some(files)
+  };
+  ReleaseStgMedium(&mut medium);
+  files
+}
+
+/// Saves the virtual files of `data_obj` in a new temporary folder, returning the paths of the
+/// dropped files and folders, without the files of the dropped folders.
+unsafe fn save_virtual_files(data_obj: &IDataObject) -> Vec<PathBuf> {
+  static NEXT_DROP: AtomicU32 = AtomicU32::new(0);
+
+  let folder = env::temp_dir().join(format!(
+    "tao-drop-{}-{}",
+    process::id(),
+    NEXT_DROP.fetch_add(1, Ordering::Relaxed)
+  ));
+  if let Err(e) = fs::create_dir_all(&folder) {
+    warn!("Failed to create the folder of the dropped files: {}", e);
+    return Vec::new();
+  }
+
+  let mut dropped = Vec::new();
+  for file in virtual_files(data_obj).unwrap_or_default() {
+    let path = folder.join(&file.path);
+    if let Err(e) = save_virtual_file(data_obj, &file, &path) {
+      warn!("Failed to save the dropped file {:?}: {}", file.path, e);
+      continue;
+    }
+    if file.is_top_level() {
+      dropped.push(path);
+    }
+  }
+  dropped
+}
+
+unsafe fn save_virtual_file(
+  data_obj: &IDataObject,
+  file: &VirtualFile,
+  path: &Path,
+) -> io::Result<()> {
+  if file.is_directory {
+    return fs::create_dir_all(path);
+  }
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let format = FORMATETC {
+    cfFormat: *FILE_CONTENTS_FORMAT,
+    ptd: ptr::null_mut(),
+    dwAspect: DVASPECT_CONTENT.0,
+    lindex: file.index as i32,
+    tymed: (TYMED_ISTREAM.0 | TYMED_HGLOBAL.0) as u32,
+  };
+  let mut medium = data_obj.GetData(&format)?;
+  let result =
+    File::create(path).and_then(|mut output| write_medium(&medium, file.size, &mut output));
+  ReleaseStgMedium(&mut medium);
+  result
+}
+
+/// Writes the contents of a file held by `medium` to `output`.
+unsafe fn write_medium(medium: &STGMEDIUM, size: Option<u64>, output: &mut File) -> io::Result<()> {
+  match TYMED(medium.tymed as i32) {
+    TYMED_ISTREAM => {
+      let stream = (*medium.u.pstm)
+        .as_ref()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no stream"))?;
+      let mut buffer = vec![0u8; 64 * 1024];
+      loop {
+        let mut read = 0;
+        stream
+          .Read(
+            buffer.as_mut_ptr() as _,
+            buffer.len() as u32,
+            Some(&mut read),
+          )
+          .ok()?;
+        if read == 0 {
+          return Ok(());
+        }
+        output.write_all(&buffer[..read as usize])?;
+      }
+    }
+    TYMED_HGLOBAL => {
+      let hglobal = medium.u.hGlobal;
+      // The memory can be larger than the file.
+      let mut len = GlobalSize(hglobal);
+      if let Some(size) = size {
+        len = len.min(size as usize);
+      }
+      let data = GlobalLock(hglobal) as *const u8;
+      if data.is_null() {
+        return Err(io::Error::last_os_error());
+      }
+      let result = output.write_all(slice::from_raw_parts(data, len));
+      let _ = GlobalUnlock(hglobal);
+      result
+    }
+    _ => Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      "unsupported storage medium",
+    )),
+  }
+}
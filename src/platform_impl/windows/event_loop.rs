@@ -9,8 +9,8 @@ mod runner;
 use crossbeam_channel::{self as channel, Receiver, Sender};
 use parking_lot::Mutex;
 use std::{
-  cell::Cell,
-  collections::VecDeque,
+  cell::{Cell, RefCell},
+  collections::{HashMap, VecDeque},
   ffi::c_void,
   marker::PhantomData,
   mem, panic,
@@ -24,13 +24,25 @@ use windows::{
   Win32::{
     Devices::HumanInterfaceDevice::*,
     Foundation::{
-      BOOL, HANDLE, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT, WAIT_TIMEOUT, WPARAM,
+      CloseHandle, BOOL, HANDLE, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT,
+      WAIT_OBJECT_0, WAIT_TIMEOUT, WPARAM,
     },
-    Graphics::Gdi::*,
+    Graphics::{Dwm::DwmDefWindowProc, Gdi::*},
     System::{
-      LibraryLoader::GetModuleHandleW,
-      Ole::{IDropTarget, RevokeDragDrop},
-      Threading::{GetCurrentThreadId, INFINITE},
+      Com::{CoInitializeEx, COINIT_MULTITHREADED},
+      Ole::{IDropTarget, RegisterDragDrop, RevokeDragDrop},
+      Power::{
+        GetSystemPowerStatus, RegisterPowerSettingNotification, UnregisterPowerSettingNotification,
+        HPOWERNOTIFY, POWERBROADCAST_SETTING, SYSTEM_POWER_STATUS,
+      },
+      RemoteDesktop::{
+        WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+      },
+      SystemServices::GUID_LIDSWITCH_STATE_CHANGE,
+      Threading::{
+        CreateWaitableTimerExW, GetCurrentThreadId, SetWaitableTimer,
+        CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, INFINITE, TIMER_ALL_ACCESS,
+      },
     },
     UI::{
       Controls::{self as win32c, HOVER_DEFAULT},
@@ -47,23 +59,42 @@ use windows::{
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize, PixelUnit},
   error::ExternalError,
-  event::{DeviceEvent, Event, Force, RawKeyEvent, Touch, TouchPhase, WindowEvent},
-  event_loop::{ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW},
-  keyboard::{KeyCode, ModifiersState},
-  monitor::MonitorHandle as RootMonitorHandle,
+  event::{
+    DeviceEvent, DeviceId as RootDeviceId, ElementState, Event, Force, MouseButton, PenInput,
+    PointerKind, PowerEvent, RawKeyEvent, ScrollSource, TabletTool, Touch, TouchPhase,
+    TypedUserEvent, WindowEvent,
+  },
+  event_loop::{
+    ControlFlow, DeviceEventFilter, DeviceEventKinds, EventLoopClosed,
+    EventLoopWindowTarget as RootELW, Suspension, TimerId,
+  },
+  global_shortcut::{Accelerator, GlobalShortcutId},
+  keyboard::{Key, KeyCode, KeyRepeatSettings, KeyboardLayout, ModifiersState},
+  monitor::{IccProfile, MonitorHandle as RootMonitorHandle},
+  platform::{
+    pump_events::PumpStatus,
+    run_return::ExitReason,
+    windows::{ComInit, DpiAwareness},
+  },
   platform_impl::platform::{
-    dark_mode::{try_app_theme, try_window_theme},
+    accelerator,
+    dark_mode::{self, try_app_theme, try_window_theme},
     dpi::{become_dpi_aware, dpi_to_scale_factor, enable_non_client_dpi_scaling},
+    icon::{self, IconType},
     keyboard::is_msg_keyboard_related,
-    keyboard_layout::LAYOUT_CACHE,
-    minimal_ime::is_msg_ime_related,
+    keyboard_grab,
+    keyboard_layout::{keycode_to_vkey, LAYOUT_CACHE},
+    minimal_ime::{composition_events, is_msg_ime_related},
     monitor::{self, MonitorHandle},
-    raw_input, util,
-    window::set_skip_taskbar,
+    peek, raw_input, surface_region, util,
+    window::{
+      set_app_user_model_id, set_cursor_frame_timer, set_overlay_icon, set_skip_taskbar,
+      MessageHooks,
+    },
     window_state::{CursorFlags, WindowFlags, WindowState},
     wrap_device_id, WindowId, DEVICE_ID,
   },
-  window::{Fullscreen, Theme, WindowId as RootWindowId},
+  window::{Fullscreen, SystemAppearance, SystemGestures, Theme, WindowId as RootWindowId},
 };
 use runner::{EventLoopRunner, EventLoopRunnerShared};
 
@@ -104,10 +135,11 @@ lazy_static! {
 pub(crate) struct SubclassInput<T: 'static> {
   pub window_state: Arc<Mutex<WindowState>>,
   pub event_loop_runner: EventLoopRunnerShared<T>,
-  pub _file_drop_handler: Option<IDropTarget>,
+  pub file_drop_handler: Option<IDropTarget>,
   pub subclass_removed: Cell<bool>,
   pub recurse_depth: Cell<u32>,
   pub event_loop_preferred_theme: Option<Theme>,
+  pub message_hooks: MessageHooks,
 }
 
 impl<T> SubclassInput<T> {
@@ -119,8 +151,79 @@ impl<T> SubclassInput<T> {
 struct ThreadMsgTargetSubclassInput<T: 'static> {
   event_loop_runner: EventLoopRunnerShared<T>,
   user_event_receiver: Receiver<T>,
+  typed_user_event_receiver: Receiver<TypedUserEvent>,
+  timers: Timers,
+  global_shortcuts: GlobalShortcuts,
+  device_events: DeviceEvents,
+  suspension: RefCell<Suspension>,
+  /// Monitors as of the last `WM_DISPLAYCHANGE`, to tell which were connected or disconnected.
+  monitors: RefCell<VecDeque<MonitorHandle>>,
+  /// Refresh rates of `monitors`, to emit `MonitorRefreshRateChanged` when they change.
+  refresh_rates: RefCell<HashMap<MonitorHandle, u32>>,
+  /// ICC profiles of `monitors`, to emit `MonitorIccProfileChanged` when they change.
+  icc_profiles: RefCell<HashMap<MonitorHandle, IccProfile>>,
+  power: RefCell<PowerState>,
+  /// Registration for the `PBT_POWERSETTINGCHANGE` of the lid switch.
+  lid_notification: HPOWERNOTIFY,
+  /// Appearance as of the last `WM_SETTINGCHANGE`, to only emit `SystemAppearanceChanged` when it
+  /// changed.
+  appearance: Cell<SystemAppearance>,
+  /// Locale as of the last `WM_SETTINGCHANGE` for `"intl"`, which is also sent when only the
+  /// formats of the locale changed.
+  locale: RefCell<Option<String>>,
+  /// Cursor position set by `move_flat_pointer`, and the window it was moved for.
+  flat_pointer_position: Cell<Option<(HWND, POINT)>>,
+}
+
+/// What `WM_POWERBROADCAST` reported last, to only emit `Event::PowerEvent` on changes.
+#[derive(Default)]
+struct PowerState {
+  battery_low: bool,
+  /// `None` until the state of the lid is first reported, right after registering for it.
+  lid_open: Option<bool>,
+}
+
+impl PowerState {
+  /// Checks the battery after a `PBT_APMPOWERSTATUSCHANGE`.
+  fn battery_changed(&mut self) -> Option<PowerEvent> {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    // 255 is an unknown status, 2 and 4 are the low and critical flags.
+    let low = status.BatteryFlag != 255 && status.BatteryFlag & (2 | 4) != 0;
+    let was_low = mem::replace(&mut self.battery_low, low);
+    if low && !was_low {
+      Some(PowerEvent::BatteryLow)
+    } else {
+      None
+    }
+  }
+
+  /// Reads the state of the lid from a `PBT_POWERSETTINGCHANGE`.
+  fn setting_changed(&mut self, setting: &POWERBROADCAST_SETTING) -> Option<PowerEvent> {
+    if setting.PowerSetting != GUID_LIDSWITCH_STATE_CHANGE || setting.DataLength == 0 {
+      return None;
+    }
+    let open = setting.Data[0] != 0;
+    match self.lid_open.replace(open) {
+      Some(was_open) if was_open != open && open => Some(PowerEvent::LidOpened),
+      Some(was_open) if was_open != open => Some(PowerEvent::LidClosed),
+      _ => None,
+    }
+  }
 }
 
+/// Timers of `EventLoopWindowTarget::set_timer` which didn't fire for the last time yet, keyed by
+/// their id on the thread event target window, with whether they're repeating.
+type Timers = Rc<RefCell<HashMap<usize, (TimerId, bool)>>>;
+
+/// Shortcuts of `GlobalShortcutManager::register`, keyed by their hot key id on the thread event
+/// target window.
+type GlobalShortcuts = Rc<RefCell<HashMap<i32, GlobalShortcutId>>>;
+
+/// Device events `handle_raw_input` reports, see `EventLoopWindowTarget::set_device_event_filter`
+/// and the following methods.
+type DeviceEvents = Rc<RefCell<raw_input::DeviceEventSubscription>>;
+
 impl<T> ThreadMsgTargetSubclassInput<T> {
   unsafe fn send_event(&self, event: Event<'_, T>) {
     self.event_loop_runner.send_event(event);
@@ -136,24 +239,28 @@ pub(crate) enum ProcResult {
 
 pub struct EventLoop<T: 'static> {
   thread_msg_sender: Sender<T>,
+  typed_thread_msg_sender: Sender<TypedUserEvent>,
   window_target: RootELW<T>,
   msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
+  shutdown_timeout: Duration,
 }
 
 pub(crate) struct PlatformSpecificEventLoopAttributes {
   pub(crate) any_thread: bool,
-  pub(crate) dpi_aware: bool,
+  pub(crate) dpi_awareness: Option<DpiAwareness>,
   pub(crate) msg_hook: Option<Box<dyn FnMut(*const c_void) -> bool + 'static>>,
   pub(crate) preferred_theme: Option<Theme>,
+  pub(crate) com_init: ComInit,
 }
 
 impl Default for PlatformSpecificEventLoopAttributes {
   fn default() -> Self {
     Self {
       any_thread: false,
-      dpi_aware: true,
+      dpi_awareness: Some(DpiAwareness::PerMonitorV2),
       msg_hook: None,
       preferred_theme: None,
+      com_init: Default::default(),
     }
   }
 }
@@ -163,7 +270,11 @@ pub struct EventLoopWindowTarget<T: 'static> {
   thread_id: u32,
   thread_msg_target: HWND,
   pub(crate) preferred_theme: Option<Theme>,
+  pub(crate) com_init: ComInit,
   pub(crate) runner_shared: EventLoopRunnerShared<T>,
+  timers: Timers,
+  global_shortcuts: GlobalShortcuts,
+  device_events: DeviceEvents,
 }
 
 impl<T: 'static> EventLoop<T> {
@@ -175,15 +286,24 @@ impl<T: 'static> EventLoop<T> {
         "Initializing the event loop outside of the main thread is a significant \
              cross-platform compatibility hazard. If you absolutely need to create an \
              EventLoop on a different thread, you can use the \
-             `EventLoopBuilderExtWindows::any_thread` function."
+             `EventLoopBuilderExtWindows::with_any_thread` function."
       );
     }
 
-    if attributes.dpi_aware {
-      become_dpi_aware();
+    if let Some(dpi_awareness) = attributes.dpi_awareness {
+      become_dpi_aware(dpi_awareness);
+    }
+
+    if attributes.com_init == ComInit::MultiThreaded {
+      // Never uninitialized, so the thread stays in the apartment for as long as it runs.
+      if let Err(error) = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) } {
+        warn!("CoInitializeEx failed: {}", error);
+      }
     }
 
     let thread_msg_target = create_event_target_window();
+    // For `WM_WTSSESSION_CHANGE`, unregistered when the window is destroyed.
+    let _ = unsafe { WTSRegisterSessionNotification(thread_msg_target, NOTIFY_FOR_THIS_SESSION) };
 
     try_app_theme(attributes.preferred_theme);
 
@@ -193,21 +313,39 @@ impl<T: 'static> EventLoop<T> {
 
     let runner_shared = Rc::new(EventLoopRunner::new(thread_msg_target, wait_thread_id));
 
-    let thread_msg_sender = subclass_event_target_window(thread_msg_target, runner_shared.clone());
-    raw_input::register_all_mice_and_keyboards_for_raw_input(thread_msg_target, Default::default());
+    let timers = Timers::default();
+    let global_shortcuts = GlobalShortcuts::default();
+    let device_events = DeviceEvents::default();
+    let (thread_msg_sender, typed_thread_msg_sender) = subclass_event_target_window(
+      thread_msg_target,
+      runner_shared.clone(),
+      timers.clone(),
+      global_shortcuts.clone(),
+      device_events.clone(),
+    );
+    raw_input::register_all_mice_and_keyboards_for_raw_input(
+      thread_msg_target,
+      &device_events.borrow(),
+    );
 
     EventLoop {
       thread_msg_sender,
+      typed_thread_msg_sender,
       window_target: RootELW {
         p: EventLoopWindowTarget {
           thread_id,
           thread_msg_target,
           runner_shared,
           preferred_theme: attributes.preferred_theme,
+          com_init: attributes.com_init,
+          timers,
+          global_shortcuts,
+          device_events,
         },
         _marker: PhantomData,
       },
       msg_hook: attributes.msg_hook.take(),
+      shutdown_timeout: crate::event_loop::DEFAULT_SHUTDOWN_TIMEOUT,
     }
   }
 
@@ -219,11 +357,20 @@ impl<T: 'static> EventLoop<T> {
   where
     F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
   {
-    let exit_code = self.run_return(event_handler);
+    let exit_code = self.run_inner(event_handler, true).exit_code();
     ::std::process::exit(exit_code);
   }
 
-  pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
+  pub fn run_return<F>(&mut self, event_handler: F) -> ExitReason
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    self.run_inner(event_handler, false)
+  }
+
+  /// Runs the event loop, destroying the windows left once it exits if `destroy_windows` is set,
+  /// which is only the case for `run` since the windows are kept in between two `run_return`.
+  fn run_inner<F>(&mut self, mut event_handler: F, destroy_windows: bool) -> ExitReason
   where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
   {
@@ -241,13 +388,14 @@ impl<T: 'static> EventLoop<T> {
 
     let runner = &self.window_target.p.runner_shared;
 
-    let exit_code = unsafe {
+    let exit_reason = unsafe {
       let mut msg = MSG::default();
 
       runner.poll();
       'main: loop {
         if !GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
-          break 'main 0;
+          // `run` kept exiting with `0` whatever the code given to `PostQuitMessage`.
+          break 'main ExitReason::Quit(0);
         }
 
         let handled = if let Some(callback) = self.msg_hook.as_deref_mut() {
@@ -255,7 +403,7 @@ impl<T: 'static> EventLoop<T> {
         } else {
           false
         };
-        if !handled {
+        if !handled && !accelerator::translate(&msg) {
           TranslateMessage(&msg);
           DispatchMessageW(&msg);
         }
@@ -267,23 +415,166 @@ impl<T: 'static> EventLoop<T> {
 
         if let ControlFlow::ExitWithCode(code) = runner.control_flow() {
           if !runner.handling_events() {
-            break 'main code;
+            break 'main ExitReason::Requested(code);
           }
         }
       }
     };
 
     unsafe {
+      if destroy_windows {
+        self.destroy_windows();
+      }
       runner.loop_destroyed();
     }
     runner.reset_runner();
-    exit_code
+    exit_reason
+  }
+
+  pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+    self.shutdown_timeout = timeout;
+  }
+
+  /// Destroys the windows which are still alive before the event loop exits, dispatching the
+  /// messages their teardown posts until none is left or `shutdown_timeout` elapsed.
+  unsafe fn destroy_windows(&self) {
+    let runner = &self.window_target.p.runner_shared;
+    let deadline = Instant::now() + self.shutdown_timeout;
+    loop {
+      let mut windows = Vec::new();
+      runner.owned_windows(|window| windows.push(window));
+      if windows.is_empty() {
+        break;
+      }
+      if Instant::now() >= deadline {
+        warn!(
+          "{} windows left after shutting the event loop down",
+          windows.len()
+        );
+        break;
+      }
+
+      for window in windows {
+        // `WM_DESTROY` is sent right away and dispatches `WindowEvent::Destroyed`.
+        if !IsWindow(window).as_bool() || DestroyWindow(window).is_err() {
+          runner.remove_window(window);
+        }
+      }
+
+      let mut msg = MSG::default();
+      while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+        // The event loop is exiting already.
+        if msg.message == WM_QUIT {
+          continue;
+        }
+        TranslateMessage(&msg);
+        DispatchMessageW(&msg);
+      }
+
+      if let Err(payload) = runner.take_panic_error() {
+        runner.reset_runner();
+        panic::resume_unwind(payload);
+      }
+    }
+  }
+
+  pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut event_handler: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let event_loop_windows_ref = &self.window_target;
+
+    unsafe {
+      self
+        .window_target
+        .p
+        .runner_shared
+        .set_event_handler(move |event, control_flow| {
+          event_handler(event, event_loop_windows_ref, control_flow);
+        });
+    }
+
+    let runner = &self.window_target.p.runner_shared;
+
+    let exit_reason = unsafe {
+      let mut msg = MSG::default();
+
+      if !runner.initialized() {
+        runner.poll();
+      }
+      // Only wait between two iterations, an unfinished one has its messages queued already.
+      let mut iterating = runner.handling_events();
+      if !iterating {
+        MsgWaitForMultipleObjectsEx(
+          None,
+          timeout.map_or(INFINITE, dur2timeout),
+          QS_ALLINPUT,
+          MWMO_INPUTAVAILABLE,
+        );
+      }
+      'main: loop {
+        if !PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+          break 'main None;
+        }
+        if msg.message == WM_QUIT {
+          break 'main Some(ExitReason::Quit(0));
+        }
+
+        let handled = if let Some(callback) = self.msg_hook.as_deref_mut() {
+          callback(&mut msg as *mut _ as *mut _)
+        } else {
+          false
+        };
+        if !handled && !accelerator::translate(&msg) {
+          TranslateMessage(&msg);
+          DispatchMessageW(&msg);
+        }
+
+        if let Err(payload) = runner.take_panic_error() {
+          runner.reset_runner();
+          panic::resume_unwind(payload);
+        }
+
+        if runner.handling_events() {
+          iterating = true;
+        } else if iterating {
+          if let ControlFlow::ExitWithCode(code) = runner.control_flow() {
+            break 'main Some(ExitReason::Requested(code));
+          }
+          // The iteration is over, `ControlFlow::Poll` queued the next one already.
+          break 'main None;
+        }
+      }
+    };
+
+    match exit_reason {
+      Some(exit_reason) => {
+        unsafe {
+          runner.loop_destroyed();
+        }
+        runner.reset_runner();
+        PumpStatus::Exit(exit_reason)
+      }
+      None => {
+        runner.clear_event_handler();
+        PumpStatus::Continue
+      }
+    }
   }
 
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
       target_window: self.window_target.p.thread_msg_target,
       event_send: self.thread_msg_sender.clone(),
+      msg_id: *USER_EVENT_MSG_ID,
+    }
+  }
+
+  pub fn create_typed_proxy(&self) -> EventLoopProxy<TypedUserEvent> {
+    EventLoopProxy {
+      target_window: self.window_target.p.thread_msg_target,
+      event_send: self.typed_thread_msg_sender.clone(),
+      msg_id: *TYPED_USER_EVENT_MSG_ID,
     }
   }
 }
@@ -311,6 +602,114 @@ impl<T> EventLoopWindowTarget<T> {
     monitor::from_point(x, y)
   }
 
+  pub fn set_timer(&self, id: TimerId, interval: Duration, repeating: bool) {
+    let timer_id = id.0 as usize;
+    self.timers.borrow_mut().insert(timer_id, (id, repeating));
+    unsafe {
+      SetTimer(
+        self.thread_msg_target,
+        timer_id,
+        dur2timeout(interval),
+        None,
+      )
+    };
+  }
+
+  pub fn cancel_timer(&self, id: TimerId) {
+    let timer_id = id.0 as usize;
+    if self.timers.borrow_mut().remove(&timer_id).is_some() {
+      unsafe {
+        let _ = KillTimer(self.thread_msg_target, timer_id);
+      }
+    }
+  }
+
+  #[inline]
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    util::keyboard_layout()
+  }
+
+  #[inline]
+  pub fn key_for_code(&self, code: KeyCode) -> Key<'static> {
+    LAYOUT_CACHE.lock().key_for_code(code)
+  }
+
+  #[inline]
+  pub fn code_for_key(&self, key: &Key<'_>) -> Option<KeyCode> {
+    LAYOUT_CACHE.lock().code_for_key(key)
+  }
+
+  #[inline]
+  pub fn key_repeat_settings(&self) -> Option<KeyRepeatSettings> {
+    util::key_repeat_settings()
+  }
+
+  pub fn register_global_shortcut(
+    &self,
+    id: GlobalShortcutId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    let vkey = keycode_to_vkey(accelerator.key, unsafe { GetKeyboardLayout(0) });
+    if vkey == VIRTUAL_KEY::default() {
+      return Err(ExternalError::Os(os_error!(super::OsError::IoError(
+        std::io::Error::new(
+          std::io::ErrorKind::InvalidInput,
+          "the key isn't on the keyboard layout",
+        )
+      ))));
+    }
+    let mut modifiers = MOD_NOREPEAT;
+    for (modifier, flag) in [
+      (ModifiersState::SHIFT, MOD_SHIFT),
+      (ModifiersState::CONTROL, MOD_CONTROL),
+      (ModifiersState::ALT, MOD_ALT),
+      (ModifiersState::SUPER, MOD_WIN),
+    ] {
+      if accelerator.mods.contains(modifier) {
+        modifiers |= flag;
+      }
+    }
+
+    let mut global_shortcuts = self.global_shortcuts.borrow_mut();
+    // Applications may only use the ids up to 0xBFFF.
+    let hotkey_id = (1..0xC000)
+      .find(|hotkey_id| !global_shortcuts.contains_key(hotkey_id))
+      .ok_or_else(|| {
+        ExternalError::Os(os_error!(super::OsError::IoError(std::io::Error::new(
+          std::io::ErrorKind::Other,
+          "too many global shortcuts",
+        ))))
+      })?;
+    unsafe {
+      RegisterHotKey(
+        self.thread_msg_target,
+        hotkey_id,
+        modifiers,
+        u32::from(vkey.0),
+      )
+    }?;
+    global_shortcuts.insert(hotkey_id, id);
+    Ok(())
+  }
+
+  pub fn unregister_global_shortcut(&self, id: GlobalShortcutId) {
+    let mut global_shortcuts = self.global_shortcuts.borrow_mut();
+    let hotkey_id = global_shortcuts
+      .iter()
+      .find(|(_, shortcut)| **shortcut == id)
+      .map(|(hotkey_id, _)| *hotkey_id);
+    if let Some(hotkey_id) = hotkey_id {
+      global_shortcuts.remove(&hotkey_id);
+      let _ = unsafe { UnregisterHotKey(self.thread_msg_target, hotkey_id) };
+    }
+  }
+
+  pub fn unregister_all_global_shortcuts(&self) {
+    for (hotkey_id, _) in self.global_shortcuts.borrow_mut().drain() {
+      let _ = unsafe { UnregisterHotKey(self.thread_msg_target, hotkey_id) };
+    }
+  }
+
   #[cfg(feature = "rwh_05")]
   pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
     rwh_05::RawDisplayHandle::Windows(rwh_05::WindowsDisplayHandle::empty())
@@ -324,13 +723,45 @@ impl<T> EventLoopWindowTarget<T> {
   }
 
   pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
-    raw_input::register_all_mice_and_keyboards_for_raw_input(self.thread_msg_target, filter);
+    let mut device_events = self.device_events.borrow_mut();
+    device_events.filter = filter;
+    raw_input::register_all_mice_and_keyboards_for_raw_input(
+      self.thread_msg_target,
+      &device_events,
+    );
+  }
+
+  pub fn set_device_event_kinds(&self, kinds: DeviceEventKinds) {
+    let mut device_events = self.device_events.borrow_mut();
+    device_events.kinds = kinds;
+    raw_input::register_all_mice_and_keyboards_for_raw_input(
+      self.thread_msg_target,
+      &device_events,
+    );
+  }
+
+  pub fn set_device_event_devices(&self, devices: Option<&[RootDeviceId]>) {
+    self.device_events.borrow_mut().devices = devices.map(<[_]>::to_vec);
   }
 
   #[inline]
   pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
     util::cursor_position().map_err(Into::into)
   }
+
+  #[inline]
+  pub fn system_appearance(&self) -> SystemAppearance {
+    dark_mode::system_appearance()
+  }
+
+  #[inline]
+  pub fn mouse_button_count(&self) -> Option<u16> {
+    // 0 if no mouse is installed.
+    match unsafe { GetSystemMetrics(SM_CMOUSEBUTTONS) } {
+      0 => None,
+      count => Some(count as u16),
+    }
+  }
 }
 
 fn main_thread_id() -> u32 {
@@ -378,6 +809,7 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
       LPARAM(cur_thread_id as _),
     );
 
+    let timer = create_wait_timer();
     let mut wait_until_opt = None;
     'main: loop {
       // Zeroing out the message ensures that the `WaitUntilInstantBox` doesn't get
@@ -406,16 +838,34 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
       if let Some(wait_until) = wait_until_opt {
         let now = Instant::now();
         if now < wait_until {
-          // MsgWaitForMultipleObjects tends to overshoot just a little bit. We subtract
-          // 1 millisecond from the requested time and spinlock for the remainder to
-          // compensate for that.
-          let resume_reason = MsgWaitForMultipleObjectsEx(
-            None,
-            dur2timeout(wait_until - now).saturating_sub(1),
-            QS_ALLEVENTS,
-            MWMO_INPUTAVAILABLE,
-          );
-          if resume_reason == WAIT_TIMEOUT {
+          let timed_out = match timer {
+            Some(timer) => {
+              // The due time is relative when negative, in 100 nanosecond intervals.
+              let intervals = ((wait_until - now).as_nanos() + 99) / 100;
+              let due_time = -(intervals.min(i64::MAX as u128) as i64);
+              let _ = SetWaitableTimer(timer, &due_time, 0, None, None, false);
+              let resume_reason = MsgWaitForMultipleObjectsEx(
+                Some(&[timer]),
+                INFINITE,
+                QS_ALLEVENTS,
+                MWMO_INPUTAVAILABLE,
+              );
+              resume_reason == WAIT_OBJECT_0
+            }
+            None => {
+              // MsgWaitForMultipleObjects tends to overshoot just a little bit. We subtract
+              // 1 millisecond from the requested time and spinlock for the remainder to
+              // compensate for that.
+              let resume_reason = MsgWaitForMultipleObjectsEx(
+                None,
+                dur2timeout(wait_until - now).saturating_sub(1),
+                QS_ALLEVENTS,
+                MWMO_INPUTAVAILABLE,
+              );
+              resume_reason == WAIT_TIMEOUT
+            }
+          };
+          if timed_out {
             let _ = PostMessageW(
               msg_window_id,
               *PROCESS_NEW_EVENTS_MSG_ID,
@@ -435,11 +885,28 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
         }
       }
     }
+
+    if let Some(timer) = timer {
+      let _ = CloseHandle(timer);
+    }
   }
 }
 
+/// Creates the high-resolution timer the wait thread sleeps on until a `ControlFlow::WaitUntil`,
+/// which isn't bound to the system timer resolution of 15.6ms by default. Returns `None` before
+/// Windows 10 1803, the wait thread then falls back to waiting with a millisecond timeout.
+unsafe fn create_wait_timer() -> Option<HANDLE> {
+  CreateWaitableTimerExW(
+    None,
+    PCWSTR::null(),
+    CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+    TIMER_ALL_ACCESS.0,
+  )
+  .ok()
+}
+
 // Implementation taken from https://github.com/rust-lang/rust/blob/db5476571d9b27c862b95c1e64764b0ac8980e23/src/libstd/sys/windows/mod.rs
-fn dur2timeout(dur: Duration) -> u32 {
+pub(crate) fn dur2timeout(dur: Duration) -> u32 {
   // Note that a duration is a (u64, u32) (seconds, nanoseconds) pair, and the
   // timeouts in windows APIs are typically u32 milliseconds. To translate, we
   // have two pieces to take care of:
@@ -476,6 +943,7 @@ impl<T> Drop for EventLoop<T> {
   }
 }
 
+#[derive(Clone)]
 pub(crate) struct EventLoopThreadExecutor {
   thread_id: u32,
   target_window: HWND,
@@ -485,6 +953,11 @@ unsafe impl Send for EventLoopThreadExecutor {}
 unsafe impl Sync for EventLoopThreadExecutor {}
 
 impl EventLoopThreadExecutor {
+  /// The thread event target window, which receives the raw input.
+  pub(super) fn target_window(&self) -> HWND {
+    self.target_window
+  }
+
   /// Check to see if we're in the parent event loop's thread.
   pub(super) fn in_event_loop_thread(&self) -> bool {
     let cur_thread_id = unsafe { GetCurrentThreadId() };
@@ -538,6 +1011,8 @@ type ThreadExecFn = Box<Box<dyn FnMut()>>;
 pub struct EventLoopProxy<T: 'static> {
   target_window: HWND,
   event_send: Sender<T>,
+  /// Message telling the thread which channel the event was sent through.
+  msg_id: u32,
 }
 unsafe impl<T: Send + 'static> Send for EventLoopProxy<T> {}
 unsafe impl<T: Send + 'static> Sync for EventLoopProxy<T> {}
@@ -547,6 +1022,7 @@ impl<T: 'static> Clone for EventLoopProxy<T> {
     Self {
       target_window: self.target_window,
       event_send: self.event_send.clone(),
+      msg_id: self.msg_id,
     }
   }
 }
@@ -554,7 +1030,7 @@ impl<T: 'static> Clone for EventLoopProxy<T> {
 impl<T: 'static> EventLoopProxy<T> {
   pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
     unsafe {
-      if PostMessageW(self.target_window, *USER_EVENT_MSG_ID, WPARAM(0), LPARAM(0)).is_ok() {
+      if PostMessageW(self.target_window, self.msg_id, WPARAM(0), LPARAM(0)).is_ok() {
         self.event_send.send(event).ok();
         Ok(())
       } else {
@@ -574,6 +1050,12 @@ lazy_static! {
             RegisterWindowMessageA(s!("Tao::WakeupMsg"))
         }
     };
+    /// Message sent by the proxies of `EventLoop::create_typed_proxy`, like `USER_EVENT_MSG_ID`.
+    static ref TYPED_USER_EVENT_MSG_ID: u32 = {
+        unsafe {
+            RegisterWindowMessageA(s!("Tao::TypedWakeupMsg"))
+        }
+    };
     /// Message sent when we want to execute a closure in the thread.
     /// WPARAM contains a Box<Box<dyn FnMut()>> that must be retrieved with `Box::from_raw`,
     /// and LPARAM is unused.
@@ -612,6 +1094,34 @@ lazy_static! {
             RegisterWindowMessageA(s!("Tao::DestroyMsg"))
         }
     };
+    /// WPARAM is a bool specifying whether the window's `FileDropHandler` should be registered as
+    /// its drop target, or revoked for `DropPolicy::Forward`.
+    pub static ref SET_DROP_TARGET_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::SetDropTarget"))
+    };
+    /// Message posted by `Window::set_title` to apply the `pending_title` of the window state, so
+    /// that the titles set until it's processed update the title bar once.
+    /// WPARAM and LPARAM are unused.
+    pub static ref SET_TITLE_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::SetTitle"))
+    };
+    /// Message posted by `Window::set_theme` to apply the `preferred_theme` of the window state.
+    /// WPARAM and LPARAM are unused.
+    pub static ref SET_THEME_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::SetTheme"))
+    };
+    /// Message sent to the thread event target by `Window::set_raw_mouse_motion`.
+    /// WPARAM is the window, and LPARAM is a bool specifying whether it gets the raw mouse motion.
+    pub static ref SET_RAW_MOUSE_MOTION_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::SetRawMouseMotion"))
+    };
+    /// Message sent to the thread event target by `Window::set_pointer_acceleration` and
+    /// `Window::set_cursor_grab`.
+    /// WPARAM is the window, and LPARAM is a bool specifying whether the cursor moves by the raw
+    /// mouse motion while it's in the foreground.
+    pub static ref SET_FLAT_POINTER_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::SetFlatPointer"))
+    };
     /// WPARAM is a bool specifying the `WindowFlags::MARKER_RETAIN_STATE_ON_SIZE` flag. See the
     /// documentation in the `window_state` module for more information.
     pub static ref SET_RETAIN_STATE_ON_SIZE_MSG_ID: u32 = unsafe {
@@ -631,7 +1141,7 @@ lazy_static! {
             lpfnWndProc: Some(util::call_default_window_proc),
             cbClsExtra: 0,
             cbWndExtra: 0,
-            hInstance:HINSTANCE(GetModuleHandleW(PCWSTR::null()).unwrap_or_default().0),
+            hInstance:HINSTANCE(util::get_instance_handle().0),
             hIcon: HICON::default(),
             hCursor: HCURSOR::default(), // must be null in order for cursor state to work properly
             hbrBackground: HBRUSH::default(),
@@ -667,7 +1177,7 @@ fn create_event_target_window() -> HWND {
       0,
       HWND::default(),
       HMENU::default(),
-      GetModuleHandleW(PCWSTR::null()).unwrap_or_default(),
+      util::get_instance_handle(),
       None,
     )
   };
@@ -685,13 +1195,37 @@ fn create_event_target_window() -> HWND {
 fn subclass_event_target_window<T>(
   window: HWND,
   event_loop_runner: EventLoopRunnerShared<T>,
-) -> Sender<T> {
+  timers: Timers,
+  global_shortcuts: GlobalShortcuts,
+  device_events: DeviceEvents,
+) -> (Sender<T>, Sender<TypedUserEvent>) {
   unsafe {
     let (tx, rx) = channel::unbounded();
+    let (typed_tx, typed_rx) = channel::unbounded();
 
+    let mut power = PowerState::default();
+    power.battery_changed();
     let subclass_input = ThreadMsgTargetSubclassInput {
       event_loop_runner,
       user_event_receiver: rx,
+      typed_user_event_receiver: typed_rx,
+      timers,
+      global_shortcuts,
+      device_events,
+      suspension: Default::default(),
+      monitors: RefCell::new(monitor::available_monitors()),
+      refresh_rates: RefCell::new(refresh_rates(&monitor::available_monitors())),
+      icc_profiles: RefCell::new(icc_profiles(&monitor::available_monitors())),
+      power: RefCell::new(power),
+      lid_notification: RegisterPowerSettingNotification(
+        HANDLE(window.0),
+        &GUID_LIDSWITCH_STATE_CHANGE,
+        DEVICE_NOTIFY_WINDOW_HANDLE.0,
+      )
+      .unwrap_or_default(),
+      appearance: Cell::new(dark_mode::system_appearance()),
+      locale: RefCell::new(util::user_locale()),
+      flat_pointer_position: Cell::new(None),
     };
     let input_ptr = Box::into_raw(Box::new(subclass_input));
     let subclass_result = SetWindowSubclass(
@@ -702,7 +1236,7 @@ fn subclass_event_target_window<T>(
     );
     assert!(subclass_result.as_bool());
 
-    tx
+    (tx, typed_tx)
   }
 }
 
@@ -735,6 +1269,27 @@ unsafe fn release_mouse(mut window_state: parking_lot::MutexGuard<'_, WindowStat
   }
 }
 
+/// Counts the clicks of `button` in a row for a mouse button message, see
+/// `MouseProperties::click_count`.
+unsafe fn click_count(
+  window_state: &Mutex<WindowState>,
+  button: MouseButton,
+  state: ElementState,
+  lparam: LPARAM,
+) -> u32 {
+  let position = (
+    util::GET_X_LPARAM(lparam) as i32,
+    util::GET_Y_LPARAM(lparam) as i32,
+  );
+  window_state
+    .lock()
+    .mouse
+    .click_count(button, state, GetMessageTime() as u32, position)
+}
+
+/// Height of a line of text scrolled by the wheel, in logical pixels, which is what browsers use.
+const PIXELS_PER_LINE: f64 = 100.0 / 3.0;
+
 const WINDOW_SUBCLASS_ID: usize = 0;
 const THREAD_EVENT_TARGET_SUBCLASS_ID: usize = 1;
 pub(crate) fn subclass_window<T>(window: HWND, subclass_input: SubclassInput<T>) {
@@ -769,6 +1324,78 @@ fn normalize_pointer_pressure(pressure: u32) -> Option<Force> {
   }
 }
 
+/// Reports changes of the pen tool and barrel button from `POINTER_PEN_INFO::penFlags`.
+unsafe fn handle_pen_flags<T>(subclass_input: &SubclassInput<T>, pen_flags: u32) {
+  let tool = if pen_flags & (PEN_FLAG_ERASER | PEN_FLAG_INVERTED) != 0 {
+    TabletTool::Eraser
+  } else {
+    TabletTool::Pen
+  };
+  let barrel_pressed = pen_flags & PEN_FLAG_BARREL != 0;
+
+  let mut w = subclass_input.window_state.lock();
+  let tool_changed = w.pen_tool.replace(tool) != Some(tool);
+  let barrel_changed =
+    std::mem::replace(&mut w.pen_barrel_pressed, barrel_pressed) != barrel_pressed;
+  drop(w);
+
+  if tool_changed {
+    subclass_input.send_event(Event::DeviceEvent {
+      device_id: DEVICE_ID,
+      event: DeviceEvent::TabletToolChanged { tool },
+    });
+  }
+  if barrel_changed {
+    subclass_input.send_event(Event::DeviceEvent {
+      device_id: DEVICE_ID,
+      event: DeviceEvent::TabletButton {
+        id: 0,
+        state: if barrel_pressed {
+          ElementState::Pressed
+        } else {
+          ElementState::Released
+        },
+      },
+    });
+  }
+}
+
+/// The state of a pen from its `WM_POINTER*` data, where the axes missing from `penMask` are `0`.
+fn pen_input(
+  position: PhysicalPosition<f64>,
+  pointer_flags: POINTER_FLAGS,
+  pen_info: &POINTER_PEN_INFO,
+) -> PenInput {
+  let has = |mask: u32| pen_info.penMask & mask != 0;
+  PenInput {
+    device_id: DEVICE_ID,
+    position,
+    in_contact: (pointer_flags & POINTER_FLAG_INCONTACT) != Default::default(),
+    pressure: if has(PEN_MASK_PRESSURE) {
+      f64::from(pen_info.pressure) / 1024.0
+    } else {
+      0.0
+    },
+    tilt_x: if has(PEN_MASK_TILT_X) {
+      f64::from(pen_info.tiltX)
+    } else {
+      0.0
+    },
+    tilt_y: if has(PEN_MASK_TILT_Y) {
+      f64::from(pen_info.tiltY)
+    } else {
+      0.0
+    },
+    twist: if has(PEN_MASK_ROTATION) {
+      f64::from(pen_info.rotation)
+    } else {
+      0.0
+    },
+    eraser: pen_info.penFlags & (PEN_FLAG_ERASER | PEN_FLAG_INVERTED) != 0,
+    barrel_buttons: pen_info.penFlags & PEN_FLAG_BARREL,
+  }
+}
+
 /// Flush redraw events for Tao's windows.
 ///
 /// Tao's API guarantees that all redraw events will be clustered together and dispatched all at
@@ -788,6 +1415,11 @@ unsafe fn flush_paint_messages<T: 'static>(
 ) -> bool {
   if !runner.redrawing() {
     runner.main_events_cleared();
+    // Peeking at every window is a system call each, skip them all when none of the windows of
+    // the thread has to be painted, which is what most iterations look like with many windows.
+    if (GetQueueStatus(QS_PAINT) >> 16) & QS_PAINT.0 == 0 {
+      return true;
+    }
     let mut msg = MSG::default();
     runner.owned_windows(|redraw_window| {
       if Some(redraw_window) == except {
@@ -861,10 +1493,32 @@ fn update_modifiers<T>(window: HWND, subclass_input: &SubclassInput<T>) -> Modif
   modifiers
 }
 
+/// Emits `WindowEvent::Occluded` if the window was shown or hidden since the last call.
+unsafe fn update_occlusion<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+  let occluded = util::is_occluded(window);
+  {
+    let mut w = subclass_input.window_state.lock();
+    if w.occluded == occluded {
+      return;
+    }
+    w.occluded = occluded;
+  }
+  subclass_input.send_event(Event::WindowEvent {
+    window_id: RootWindowId(WindowId(window.0)),
+    event: WindowEvent::Occluded(occluded),
+  });
+}
+
 unsafe fn gain_active_focus<T>(window: HWND, subclass_input: &SubclassInput<T>) {
   use crate::event::WindowEvent::Focused;
   update_modifiers(window, subclass_input);
 
+  let _ = subclass_input
+    .window_state
+    .lock()
+    .mouse
+    .refresh_cursor_clip(window);
+
   subclass_input.send_event(Event::WindowEvent {
     window_id: RootWindowId(WindowId(window.0)),
     event: Focused(true),
@@ -874,7 +1528,17 @@ unsafe fn gain_active_focus<T>(window: HWND, subclass_input: &SubclassInput<T>)
 unsafe fn lose_active_focus<T>(window: HWND, subclass_input: &SubclassInput<T>) {
   use crate::event::WindowEvent::{Focused, ModifiersChanged};
 
-  subclass_input.window_state.lock().modifiers_state = ModifiersState::empty();
+  {
+    let mut window_state = subclass_input.window_state.lock();
+    window_state.modifiers_state = ModifiersState::empty();
+    if window_state
+      .mouse
+      .cursor_flags()
+      .intersects(CursorFlags::GRABBED | CursorFlags::LOCKED)
+    {
+      let _ = util::set_cursor_clip(None);
+    }
+  }
   subclass_input.send_event(Event::WindowEvent {
     window_id: RootWindowId(WindowId(window.0)),
     event: ModifiersChanged(ModifiersState::empty()),
@@ -928,6 +1592,76 @@ unsafe extern "system" fn public_window_callback<T: 'static>(
   result
 }
 
+/// Hit-tests a decorated window whose client area covers its title bar, where the caption buttons
+/// and the top resize border are.
+unsafe fn hit_test_title_bar(
+  window: HWND,
+  window_flags: WindowFlags,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  let mut result = LRESULT(0);
+  if window_flags.contains(WindowFlags::MARKER_TITLE_BAR_OVERLAY)
+    && DwmDefWindowProc(window, WM_NCHITTEST, wparam, lparam, &mut result).as_bool()
+  {
+    return result;
+  }
+
+  let result = DefSubclassProc(window, WM_NCHITTEST, wparam, lparam);
+  if result.0 == HTCLIENT as isize && !util::is_maximized(window).unwrap_or(false) {
+    let mut rect = RECT::default();
+    let _ = GetWindowRect(window, &mut rect);
+    let (border, _) = util::title_bar_metrics(window);
+    if i32::from(util::GET_Y_LPARAM(lparam)) < rect.top + border {
+      return LRESULT(HTTOP as _);
+    }
+  }
+  result
+}
+
+/// Applies the preferred theme of the window, or the one of the event loop or the system, and emits
+/// `WindowEvent::ThemeChanged` if it changed.
+unsafe fn update_theme<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+  let preferred_theme = subclass_input.window_state.lock().preferred_theme;
+  let new_theme = try_window_theme(
+    window,
+    preferred_theme.or(subclass_input.event_loop_preferred_theme),
+  );
+  let mut window_state = subclass_input.window_state.lock();
+
+  if window_state.current_theme != new_theme {
+    window_state.current_theme = new_theme;
+    mem::drop(window_state);
+    subclass_input.send_event(Event::WindowEvent {
+      window_id: RootWindowId(WindowId(window.0)),
+      event: WindowEvent::ThemeChanged(new_theme),
+    });
+  }
+}
+
+/// Calls the hooks added with `WindowExtWindows::add_message_hook` until one of them handles the
+/// message. The list is cloned first so hooks can add or remove hooks, and a hook which gets a
+/// nested message while it runs is skipped for that message.
+fn call_message_hooks<T>(
+  window: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  subclass_input: &SubclassInput<T>,
+) -> Option<LRESULT> {
+  let hooks: Vec<_> = {
+    let hooks = subclass_input.message_hooks.lock();
+    if hooks.is_empty() {
+      return None;
+    }
+    hooks.iter().map(|(_, hook)| hook.clone()).collect()
+  };
+  hooks.iter().find_map(|hook| {
+    let mut hook = hook.try_lock()?;
+    hook(window.0, msg, wparam.0, lparam.0).map(LRESULT)
+  })
+}
+
 unsafe fn public_window_callback_inner<T: 'static>(
   window: HWND,
   msg: u32,
@@ -943,6 +1677,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
     RDW_INTERNALPAINT,
   );
 
+  if let Some(result) = call_message_hooks(window, msg, wparam, lparam, subclass_input) {
+    return result;
+  }
+
   let mut result = ProcResult::DefSubclassProc;
 
   // Send new modifiers before sending key events.
@@ -970,7 +1708,16 @@ unsafe fn public_window_callback_inner<T: 'static>(
       let mut key_event_builders =
         crate::platform_impl::platform::keyboard::KEY_EVENT_BUILDERS.lock();
       if let Some(key_event_builder) = key_event_builders.get_mut(&WindowId(window.0)) {
-        key_event_builder.process_message(window, msg, wparam, lparam, &mut result)
+        let mut events =
+          key_event_builder.process_message(window, msg, wparam, lparam, &mut result);
+        for event in &mut events {
+          key_event_builder.repeats.count(&mut event.event);
+          let typed_text = event.event.text.is_some();
+          key_event_builder
+            .dead_keys
+            .update(&mut event.event, typed_text);
+        }
+        events
       } else {
         Vec::new()
       }
@@ -997,6 +1744,12 @@ unsafe fn public_window_callback_inner<T: 'static>(
     if !is_ime_related {
       return;
     }
+    for ime in composition_events(window, msg, lparam) {
+      subclass_input.send_event(Event::WindowEvent {
+        window_id: RootWindowId(WindowId(window.0)),
+        event: WindowEvent::Ime(ime),
+      });
+    }
     let text = {
       let mut window_state = subclass_input.window_state.lock();
       window_state
@@ -1050,6 +1803,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
         window_id: RootWindowId(WindowId(window.0)),
         event: DecorationsClick,
       });
+
+      // The system move loop is what snaps and shakes windows, so move the window ourselves
+      // when either is disabled.
+      if wparam.0 == HTCAPTION as _ && !IsZoomed(window).as_bool() {
+        let mut w = subclass_input.window_state.lock();
+        if !w
+          .system_gestures
+          .contains(SystemGestures::SNAP | SystemGestures::SHAKE)
+        {
+          let mut cursor = POINT::default();
+          let mut rect = RECT::default();
+          if GetCursorPos(&mut cursor).is_ok() && GetWindowRect(window, &mut rect).is_ok() {
+            w.manual_move = Some(POINT {
+              x: cursor.x - rect.left,
+              y: cursor.y - rect.top,
+            });
+            capture_mouse(window, &mut w);
+            result = ProcResult::Value(LRESULT(0));
+          }
+        }
+      }
+    }
+
+    win32wm::WM_NCLBUTTONDBLCLK => {
+      if wparam.0 == HTCAPTION as _
+        && !subclass_input
+          .window_state
+          .lock()
+          .system_gestures
+          .contains(SystemGestures::TITLEBAR_DOUBLE_CLICK)
+      {
+        result = ProcResult::Value(LRESULT(0));
+      }
     }
 
     win32wm::WM_CLOSE => {
@@ -1064,6 +1850,11 @@ unsafe fn public_window_callback_inner<T: 'static>(
     win32wm::WM_DESTROY => {
       use crate::event::WindowEvent::Destroyed;
       let _ = RevokeDragDrop(window);
+      keyboard_grab::remove_window(window);
+      let has_app_user_model_id = subclass_input.window_state.lock().has_app_user_model_id;
+      if has_app_user_model_id {
+        let _ = set_app_user_model_id(window, "");
+      }
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0)),
         event: Destroyed,
@@ -1072,12 +1863,114 @@ unsafe fn public_window_callback_inner<T: 'static>(
       result = ProcResult::Value(LRESULT(0));
     }
 
+    win32wm::WM_DWMSENDICONICTHUMBNAIL => {
+      let max_width = util::HIWORD(lparam.0 as u32) as u32;
+      let max_height = util::LOWORD(lparam.0 as u32) as u32;
+      if let Some(bitmap) = &subclass_input.window_state.lock().peek_bitmap {
+        peek::set_thumbnail(window, bitmap, max_width, max_height);
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
+    win32wm::WM_DWMSENDICONICLIVEPREVIEWBITMAP => {
+      let started = {
+        let mut w = subclass_input.window_state.lock();
+        if let Some(bitmap) = &w.peek_bitmap {
+          peek::set_live_preview(window, bitmap);
+        }
+        !std::mem::replace(&mut w.peeking, true)
+      };
+      if started {
+        SetTimer(window, peek::PEEK_TIMER_ID, peek::PEEK_TIMER_INTERVAL, None);
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::ThumbnailPeekStarted,
+        });
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
+    win32wm::WM_TIMER if wparam.0 == icon::ICON_FRAME_TIMER_ID => {
+      let frame = {
+        let mut w = subclass_input.window_state.lock();
+        if w.icon_frames.is_empty() {
+          None
+        } else {
+          w.icon_frame = (w.icon_frame + 1) % w.icon_frames.len();
+          Some(w.icon_frames[w.icon_frame].clone())
+        }
+      };
+      match frame {
+        Some(frame) => {
+          frame.inner.set_for_window(window, IconType::Small);
+          frame.inner.set_for_window(window, IconType::Big);
+        }
+        None => {
+          let _ = KillTimer(window, icon::ICON_FRAME_TIMER_ID);
+        }
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
+    win32wm::WM_TIMER if wparam.0 == icon::CURSOR_FRAME_TIMER_ID => {
+      let (frame, in_window) = {
+        let mut w = subclass_input.window_state.lock();
+        let in_window = w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
+        (w.next_custom_cursor_frame(), in_window)
+      };
+      match frame {
+        Some((hcursor, delay)) => {
+          if in_window {
+            SetCursor(hcursor);
+          }
+          set_cursor_frame_timer(window, delay);
+        }
+        None => {
+          let _ = KillTimer(window, icon::CURSOR_FRAME_TIMER_ID);
+        }
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
+    win32wm::WM_TIMER if wparam.0 == peek::PEEK_TIMER_ID => {
+      if !peek::is_cursor_over_thumbnail() {
+        let _ = KillTimer(window, peek::PEEK_TIMER_ID);
+        subclass_input.window_state.lock().peeking = false;
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::ThumbnailPeekEnded,
+        });
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
     win32wm::WM_NCDESTROY => {
+      subclass_input.window_state.lock().destroyed = true;
+      accelerator::remove_window(window);
       remove_window_subclass::<T>(window);
       subclass_input.subclass_removed.set(true);
       result = ProcResult::Value(LRESULT(0));
     }
 
+    win32wm::WM_COMMAND => {
+      // The high word is 1 for the commands of accelerators.
+      let accelerator = if util::HIWORD(wparam.0 as u32) == 1 {
+        accelerator::find(window, util::LOWORD(wparam.0 as u32))
+      } else {
+        None
+      };
+      match accelerator {
+        Some(id) => {
+          subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window.0)),
+            event: WindowEvent::Accelerator(id),
+          });
+          result = ProcResult::Value(LRESULT(0));
+        }
+        None => result = ProcResult::DefSubclassProc,
+      }
+    }
+
     win32wm::WM_PAINT => {
       if subclass_input.event_loop_runner.should_buffer() {
         // this branch can happen in response to `UpdateWindow`, if win32 decides to
@@ -1085,7 +1978,23 @@ unsafe fn public_window_callback_inner<T: 'static>(
         RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
       } else {
         let managing_redraw = flush_paint_messages(Some(window), &subclass_input.event_loop_runner);
+        let frame_missed = subclass_input
+          .window_state
+          .lock()
+          .jank_detector
+          .as_mut()
+          .and_then(|jank_detector| jank_detector.redraw_requested(window));
+        if let Some((count, duration)) = frame_missed {
+          subclass_input.send_event(Event::FrameMissed {
+            window_id: RootWindowId(WindowId(window.0)),
+            count,
+            duration,
+          });
+        }
         subclass_input.send_event(Event::RedrawRequested(RootWindowId(WindowId(window.0))));
+        if let Some(jank_detector) = subclass_input.window_state.lock().jank_detector.as_mut() {
+          jank_detector.redraw_finished();
+        }
         if managing_redraw {
           subclass_input.event_loop_runner.redraw_events_cleared();
           process_control_flow(&subclass_input.event_loop_runner);
@@ -1095,6 +2004,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
     win32wm::WM_WINDOWPOSCHANGING => {
       let mut window_state = subclass_input.window_state.lock();
+      let mut moved_to_monitor = false;
 
       if let Some(ref mut fullscreen) = window_state.fullscreen {
         let window_pos = &mut *(lparam.0 as *mut WINDOWPOS);
@@ -1152,11 +2062,21 @@ unsafe fn public_window_callback_inner<T: 'static>(
                   window_pos.cx = new_monitor_rect.right - new_monitor_rect.left;
                   window_pos.cy = new_monitor_rect.bottom - new_monitor_rect.top;
                 }
+                // The monitor is unknown until the first move after `set_fullscreen`.
+                moved_to_monitor = fullscreen_monitor.is_some();
                 *fullscreen_monitor = Some(crate::monitor::MonitorHandle {
                   inner: MonitorHandle::new(new_monitor),
                 });
               }
             }
+            Fullscreen::Spanning(ref monitors) => {
+              if let Some(spanning_rect) = monitor::spanning_rect(monitors) {
+                window_pos.x = spanning_rect.left;
+                window_pos.y = spanning_rect.top;
+                window_pos.cx = spanning_rect.right - spanning_rect.left;
+                window_pos.cy = spanning_rect.bottom - spanning_rect.top;
+              }
+            }
             Fullscreen::Exclusive(ref video_mode) => {
               let old_monitor = video_mode.video_mode.monitor.hmonitor();
               if let Ok(old_monitor_info) = monitor::get_monitor_info(old_monitor) {
@@ -1177,6 +2097,16 @@ unsafe fn public_window_callback_inner<T: 'static>(
         window_pos.hwndInsertAfter = HWND_BOTTOM;
       }
 
+      // The user moved the fullscreen window to another monitor, e.g. with Win+Shift+Arrow.
+      if moved_to_monitor {
+        let fullscreen = window_state.fullscreen.clone();
+        drop(window_state);
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::FullscreenStateChanged(fullscreen),
+        });
+      }
+
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1193,6 +2123,17 @@ unsafe fn public_window_callback_inner<T: 'static>(
         });
       }
 
+      // Minimizing, restoring, showing and hiding the window all go through here.
+      update_occlusion(window, subclass_input);
+
+      if (*windowpos).flags & (SWP_NOMOVE | SWP_NOSIZE) != (SWP_NOMOVE | SWP_NOSIZE) {
+        let _ = subclass_input
+          .window_state
+          .lock()
+          .mouse
+          .refresh_cursor_clip(window);
+      }
+
       // This is necessary for us to still get sent WM_SIZE.
       result = ProcResult::DefSubclassProc;
     }
@@ -1232,6 +2173,16 @@ unsafe fn public_window_callback_inner<T: 'static>(
       }
       if wparam.0 == SC_MINIMIZE as _ {
         let mut w = subclass_input.window_state.lock();
+        if w.intercept_minimize {
+          drop(w);
+          use crate::event::WindowEvent::MinimizeRequested;
+          subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window.0)),
+            event: MinimizeRequested,
+          });
+          result = ProcResult::Value(LRESULT(0));
+          return;
+        }
         w.set_window_flags_in_place(|f| f.set(WindowFlags::MINIMIZED, true));
       }
       // Send `WindowEvent::Minimized` here if we decide to implement one
@@ -1249,6 +2200,23 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
     win32wm::WM_MOUSEMOVE => {
       use crate::event::WindowEvent::{CursorEntered, CursorMoved};
+
+      let manual_move = subclass_input.window_state.lock().manual_move;
+      if let Some(offset) = manual_move {
+        let mut cursor = POINT::default();
+        if GetCursorPos(&mut cursor).is_ok() {
+          let _ = SetWindowPos(
+            window,
+            HWND::default(),
+            cursor.x - offset.x,
+            cursor.y - offset.y,
+            0,
+            0,
+            SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+          );
+        }
+      }
+
       let mouse_was_outside_window = {
         let mut w = subclass_input.window_state.lock();
 
@@ -1323,10 +2291,23 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_MOUSEWHEEL => {
-      use crate::event::MouseScrollDelta::LineDelta;
+      use crate::event::MouseScrollDelta::{LineDelta, PixelDelta};
 
-      let value = f32::from(util::GET_WHEEL_DELTA_WPARAM(wparam));
-      let value = value / WHEEL_DELTA as f32;
+      let wheel_delta = util::GET_WHEEL_DELTA_WPARAM(wparam);
+      let value = f32::from(wheel_delta) / WHEEL_DELTA as f32;
+      let (delta, source) = if i32::from(wheel_delta) % WHEEL_DELTA as i32 == 0 {
+        (LineDelta(0.0, value), ScrollSource::Wheel)
+      } else {
+        let scale_factor = subclass_input.window_state.lock().scale_factor;
+        let pixels = f64::from(value)
+          * f64::from(util::wheel_scroll_amount(false))
+          * PIXELS_PER_LINE
+          * scale_factor;
+        (
+          PixelDelta(PhysicalPosition::new(0.0, pixels)),
+          ScrollSource::Touchpad,
+        )
+      };
 
       let modifiers = update_modifiers(window, subclass_input);
 
@@ -1334,8 +2315,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
         window_id: RootWindowId(WindowId(window.0)),
         event: WindowEvent::MouseWheel {
           device_id: DEVICE_ID,
-          delta: LineDelta(0.0, value),
+          delta,
           phase: TouchPhase::Moved,
+          momentum: false,
+          source,
           modifiers,
         },
       });
@@ -1344,10 +2327,23 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_MOUSEHWHEEL => {
-      use crate::event::MouseScrollDelta::LineDelta;
+      use crate::event::MouseScrollDelta::{LineDelta, PixelDelta};
 
-      let value = f32::from(util::GET_WHEEL_DELTA_WPARAM(wparam));
-      let value = value / WHEEL_DELTA as f32;
+      let wheel_delta = util::GET_WHEEL_DELTA_WPARAM(wparam);
+      let value = f32::from(wheel_delta) / WHEEL_DELTA as f32;
+      let (delta, source) = if i32::from(wheel_delta) % WHEEL_DELTA as i32 == 0 {
+        (LineDelta(value, 0.0), ScrollSource::Wheel)
+      } else {
+        let scale_factor = subclass_input.window_state.lock().scale_factor;
+        let pixels = f64::from(value)
+          * f64::from(util::wheel_scroll_amount(true))
+          * PIXELS_PER_LINE
+          * scale_factor;
+        (
+          PixelDelta(PhysicalPosition::new(pixels, 0.0)),
+          ScrollSource::Touchpad,
+        )
+      };
 
       let modifiers = update_modifiers(window, subclass_input);
 
@@ -1355,8 +2351,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
         window_id: RootWindowId(WindowId(window.0)),
         event: WindowEvent::MouseWheel {
           device_id: DEVICE_ID,
-          delta: LineDelta(value, 0.0),
+          delta,
           phase: TouchPhase::Moved,
+          momentum: false,
+          source,
           modifiers,
         },
       });
@@ -1375,6 +2373,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, Left, Pressed, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1383,6 +2382,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Left,
+          click_count,
           modifiers,
         },
       });
@@ -1392,8 +2392,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
     win32wm::WM_LBUTTONUP => {
       use crate::event::{ElementState::Released, MouseButton::Left, WindowEvent::MouseInput};
 
-      release_mouse(subclass_input.window_state.lock());
+      {
+        let mut w = subclass_input.window_state.lock();
+        if w.manual_move.take().is_some() {
+          w.dragging = false;
+        }
+        release_mouse(w);
+      }
 
+      let click_count = click_count(&subclass_input.window_state, Left, Released, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1402,6 +2409,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Left,
+          click_count,
           modifiers,
         },
       });
@@ -1413,6 +2421,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       capture_mouse(window, &mut *subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, Right, Pressed, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1421,6 +2430,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Right,
+          click_count,
           modifiers,
         },
       });
@@ -1432,6 +2442,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       release_mouse(subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, Right, Released, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1440,6 +2451,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Right,
+          click_count,
           modifiers,
         },
       });
@@ -1451,6 +2463,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, Middle, Pressed, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1459,6 +2472,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Middle,
+          click_count,
           modifiers,
         },
       });
@@ -1470,6 +2484,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       release_mouse(subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, Middle, Released, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1478,6 +2493,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Middle,
+          click_count,
           modifiers,
         },
       });
@@ -1485,11 +2501,13 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_XBUTTONDOWN => {
-      use crate::event::{ElementState::Pressed, MouseButton::Other, WindowEvent::MouseInput};
-      let xbutton = util::GET_XBUTTON_WPARAM(wparam);
+      use crate::event::{ElementState::Pressed, WindowEvent::MouseInput};
+      // XBUTTON1 and XBUTTON2 are the back and forward buttons.
+      let button = MouseButton::from_index(util::GET_XBUTTON_WPARAM(wparam) + 2);
 
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, button, Pressed, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1497,7 +2515,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
         event: MouseInput {
           device_id: DEVICE_ID,
           state: Pressed,
-          button: Other(xbutton),
+          button,
+          click_count,
           modifiers,
         },
       });
@@ -1505,11 +2524,13 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_XBUTTONUP => {
-      use crate::event::{ElementState::Released, MouseButton::Other, WindowEvent::MouseInput};
-      let xbutton = util::GET_XBUTTON_WPARAM(wparam);
+      use crate::event::{ElementState::Released, WindowEvent::MouseInput};
+      // XBUTTON1 and XBUTTON2 are the back and forward buttons.
+      let button = MouseButton::from_index(util::GET_XBUTTON_WPARAM(wparam) + 2);
 
       release_mouse(subclass_input.window_state.lock());
 
+      let click_count = click_count(&subclass_input.window_state, button, Released, lparam);
       let modifiers = update_modifiers(window, subclass_input);
 
       subclass_input.send_event(Event::WindowEvent {
@@ -1517,7 +2538,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
         event: MouseInput {
           device_id: DEVICE_ID,
           state: Released,
-          button: Other(xbutton),
+          button,
+          click_count,
           modifiers,
         },
       });
@@ -1530,7 +2552,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
       // can happen if `SetCapture` is called on our window when it already has the mouse
       // capture.
       if lparam.0 != window.0 {
-        subclass_input.window_state.lock().mouse.capture_count = 0;
+        let mut w = subclass_input.window_state.lock();
+        w.mouse.capture_count = 0;
+        w.manual_move = None;
       }
       result = ProcResult::Value(LRESULT(0));
     }
@@ -1577,6 +2601,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
               force: None, // WM_TOUCH doesn't support pressure information
               id: input.dwID as u64,
               device_id: DEVICE_ID,
+              pointer_kind: PointerKind::Touch,
             }),
           });
         }
@@ -1670,6 +2695,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
             continue;
           }
 
+          let mut pen = None;
           let force = match pointer_info.pointerType {
             win32wm::PT_TOUCH => {
               let mut touch_info = mem::MaybeUninit::uninit();
@@ -1685,7 +2711,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
               let mut pen_info = mem::MaybeUninit::uninit();
               GET_POINTER_PEN_INFO.and_then(|GetPointerPenInfo| {
                 if GetPointerPenInfo(pointer_info.pointerId, pen_info.as_mut_ptr()).as_bool() {
-                  normalize_pointer_pressure(pen_info.assume_init().pressure)
+                  let pen_info = pen_info.assume_init();
+                  handle_pen_flags(subclass_input, pen_info.penFlags);
+                  pen = Some(pen_info);
+                  normalize_pointer_pressure(pen_info.pressure)
                 } else {
                   None
                 }
@@ -1713,8 +2742,24 @@ unsafe fn public_window_callback_inner<T: 'static>(
               force,
               id: pointer_info.pointerId as u64,
               device_id: DEVICE_ID,
+              pointer_kind: match pointer_info.pointerType {
+                win32wm::PT_TOUCH | win32wm::PT_TOUCHPAD => PointerKind::Touch,
+                win32wm::PT_PEN => PointerKind::Pen,
+                win32wm::PT_MOUSE => PointerKind::Mouse,
+                _ => PointerKind::Unknown,
+              },
             }),
           });
+          if let Some(pen_info) = pen {
+            subclass_input.send_event(Event::WindowEvent {
+              window_id: RootWindowId(WindowId(window.0)),
+              event: WindowEvent::PenInput(pen_input(
+                location,
+                pointer_info.pointerFlags,
+                &pen_info,
+              )),
+            });
+          }
         }
 
         SkipPointerFrameMessages(pointer_id);
@@ -1733,6 +2778,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           lose_active_focus(window, subclass_input);
         }
       }
+      // Switching virtual desktops deactivates the window without moving or hiding it.
+      update_occlusion(window, subclass_input);
       result = ProcResult::DefWindowProc;
     }
 
@@ -1744,6 +2791,14 @@ unsafe fn public_window_callback_inner<T: 'static>(
       result = ProcResult::Value(LRESULT(0));
     }
 
+    win32wm::WM_INPUTLANGCHANGE => {
+      subclass_input.send_event(Event::InputLocaleChanged {
+        input_locale: format!("{:08X}", lparam.0 as u32),
+      });
+      subclass_input.send_event(Event::KeyboardLayoutChanged(util::keyboard_layout()));
+      result = ProcResult::DefWindowProc;
+    }
+
     win32wm::WM_KILLFOCUS => {
       let active_focus_changed = subclass_input.window_state.lock().set_focused(false);
       if active_focus_changed {
@@ -1760,7 +2815,14 @@ unsafe fn public_window_callback_inner<T: 'static>(
         // `WM_MOUSEMOVE` seems to come after `WM_SETCURSOR` for a given cursor movement.
         let in_client_area = u32::from(util::LOWORD(lparam.0 as u32)) == HTCLIENT;
         if in_client_area {
-          Some(window_state.mouse.cursor)
+          match &window_state.mouse.custom_cursor {
+            Some((_, handle)) => Some(HCURSOR(handle.as_raw_handle().0)),
+            None => LoadCursorW(
+              HMODULE::default(),
+              window_state.mouse.cursor.to_windows_cursor(),
+            )
+            .ok(),
+          }
         } else {
           None
         }
@@ -1768,9 +2830,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       match set_cursor_to {
         Some(cursor) => {
-          if let Ok(cursor) = LoadCursorW(HMODULE::default(), cursor.to_windows_cursor()) {
-            SetCursor(cursor);
-          }
+          SetCursor(cursor);
           result = ProcResult::Value(LRESULT(0));
         }
         None => result = ProcResult::DefWindowProc,
@@ -1781,9 +2841,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
       let mmi = lparam.0 as *mut MINMAXINFO;
 
       let window_state = subclass_input.window_state.lock();
-      let is_decorated = window_state
-        .window_flags()
-        .contains(WindowFlags::MARKER_DECORATIONS);
+      let window_flags = window_state.window_flags();
+      let is_decorated = window_flags.contains(WindowFlags::MARKER_DECORATIONS);
+      let title_bar_hidden = window_flags.contains(WindowFlags::MARKER_TITLE_BAR_HIDDEN);
 
       let size_constraints = window_state.size_constraints;
 
@@ -1800,7 +2860,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
             .to_physical(window_state.scale_factor)
             .value,
         );
-        let (width, height): (u32, u32) = util::adjust_size(window, min_size, is_decorated).into();
+        let (width, height): (u32, u32) =
+          util::adjust_size(window, min_size, is_decorated, title_bar_hidden).into();
         (*mmi).ptMinTrackSize = POINT {
           x: width as i32,
           y: height as i32,
@@ -1819,7 +2880,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
             .to_physical(window_state.scale_factor)
             .value,
         );
-        let (width, height): (u32, u32) = util::adjust_size(window, max_size, is_decorated).into();
+        let (width, height): (u32, u32) =
+          util::adjust_size(window, max_size, is_decorated, title_bar_hidden).into();
         (*mmi).ptMaxTrackSize = POINT {
           x: width as i32,
           y: height as i32,
@@ -1842,22 +2904,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
       let new_scale_factor = dpi_to_scale_factor(new_dpi_x);
       let old_scale_factor: f64;
 
-      let (allow_resize, is_decorated) = {
+      let (allow_resize, window_flags) = {
         let mut window_state = subclass_input.window_state.lock();
-        old_scale_factor = window_state.scale_factor;
-        window_state.scale_factor = new_scale_factor;
+        old_scale_factor = window_state.dpi_scale_factor;
+        window_state.dpi_scale_factor = new_scale_factor;
 
         if (new_scale_factor - old_scale_factor).abs() < f64::EPSILON {
           result = ProcResult::Value(LRESULT(0));
           return;
         }
 
+        // The next `WM_SETCURSOR` picks up the new handle.
+        window_state.refresh_custom_cursor();
+
+        // The application does its own scaling, so the window keeps its size.
+        if window_state.scale_factor_override.is_some() {
+          result = ProcResult::Value(LRESULT(0));
+          return;
+        }
+        window_state.scale_factor = new_scale_factor;
+
         let window_flags = window_state.window_flags();
         (
           window_state.fullscreen.is_none() && !window_flags.contains(WindowFlags::MAXIMIZED),
-          window_flags.contains(WindowFlags::MARKER_DECORATIONS),
+          window_flags,
         )
       };
+      let is_decorated = window_flags.contains(WindowFlags::MARKER_DECORATIONS);
+      let title_bar_hidden = window_flags.contains(WindowFlags::MARKER_TITLE_BAR_HIDDEN);
+
+      // The caption buttons follow the new height of the title bar.
+      if window_flags.contains(WindowFlags::MARKER_TITLE_BAR_OVERLAY) {
+        util::extend_frame_into_title_bar(window);
+      }
 
       let mut style = WINDOW_STYLE(GetWindowLongW(window, GWL_STYLE) as u32);
       // if the window isn't decorated, remove `WS_SIZEBOX` and `WS_CAPTION` so
@@ -1881,9 +2960,12 @@ unsafe fn public_window_callback_inner<T: 'static>(
       // let margin_right: i32;
       // let margin_bottom: i32;
       {
-        let adjusted_rect =
+        let mut adjusted_rect =
           util::adjust_window_rect_with_styles(window, style, style_ex, suggested_rect)
             .unwrap_or(suggested_rect);
+        if title_bar_hidden {
+          adjusted_rect.top = suggested_rect.top;
+        }
         margin_left = suggested_rect.left - adjusted_rect.left;
         margin_top = suggested_rect.top - adjusted_rect.top;
         // margin_right = adjusted_rect.right - suggested_rect.right;
@@ -1959,6 +3041,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
         conservative_rect =
           util::adjust_window_rect_with_styles(window, style, style_ex, conservative_rect)
             .unwrap_or(conservative_rect);
+        if title_bar_hidden {
+          conservative_rect.top = suggested_ul.1;
+        }
 
         // If we're dragging the window, offset the window so that the cursor's
         // relative horizontal position in the title bar is preserved.
@@ -2048,36 +3133,46 @@ unsafe fn public_window_callback_inner<T: 'static>(
         SWP_NOZORDER | SWP_NOACTIVATE,
       );
 
+      let surface_regions = subclass_input.window_state.lock().surface_regions.clone();
+      for (region, rect) in surface_regions {
+        surface_region::place(region, rect, new_scale_factor);
+      }
+
       result = ProcResult::Value(LRESULT(0));
     }
 
     win32wm::WM_WININICHANGE => {
-      use crate::event::WindowEvent::ThemeChanged;
+      use crate::event::WindowEvent::CursorThemeChanged;
+
+      if wparam.0 == SPI_SETCURSORS.0 as usize {
+        let cursor = subclass_input.window_state.lock().refresh_custom_cursor();
+        if let Some(cursor) = cursor {
+          SetCursor(cursor);
+        }
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: CursorThemeChanged,
+        });
+      }
 
       let preferred_theme = subclass_input.window_state.lock().preferred_theme;
 
       if preferred_theme.is_none() {
-        let new_theme = try_window_theme(
-          window,
-          preferred_theme.or(subclass_input.event_loop_preferred_theme),
-        );
-        let mut window_state = subclass_input.window_state.lock();
-
-        if window_state.current_theme != new_theme {
-          window_state.current_theme = new_theme;
-          mem::drop(window_state);
-          subclass_input.send_event(Event::WindowEvent {
-            window_id: RootWindowId(WindowId(window.0)),
-            event: ThemeChanged(new_theme),
-          });
-        }
+        update_theme(window, subclass_input);
       }
     }
 
     win32wm::WM_NCCALCSIZE => {
       let window_flags = subclass_input.window_state.lock().window_flags();
 
-      if wparam == WPARAM(0) || window_flags.contains(WindowFlags::MARKER_DECORATIONS) {
+      if wparam != WPARAM(0)
+        && window_flags
+          .contains(WindowFlags::MARKER_DECORATIONS | WindowFlags::MARKER_TITLE_BAR_HIDDEN)
+      {
+        result = ProcResult::Value(util::calc_size_without_title_bar(window, lparam, || {
+          DefSubclassProc(window, msg, wparam, lparam)
+        }));
+      } else if wparam == WPARAM(0) || window_flags.contains(WindowFlags::MARKER_DECORATIONS) {
         result = ProcResult::DefSubclassProc;
       } else {
         // adjust the maximized borderless window so it doesn't cover the taskbar
@@ -2126,6 +3221,14 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_NCHITTEST => {
+      let window_flags = subclass_input.window_state.lock().window_flags();
+      if window_flags
+        .contains(WindowFlags::MARKER_DECORATIONS | WindowFlags::MARKER_TITLE_BAR_HIDDEN)
+      {
+        result = ProcResult::Value(hit_test_title_bar(window, window_flags, wparam, lparam));
+        return;
+      }
+
       let window_state = subclass_input.window_state.lock();
       // Allow resizing unmaximized borderless window
       if !util::is_maximized(window).unwrap_or(false)
@@ -2166,15 +3269,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
       if msg == *DESTROY_MSG_ID {
         let _ = DestroyWindow(window);
         result = ProcResult::Value(LRESULT(0));
+      } else if msg == *SET_TITLE_MSG_ID {
+        let title = subclass_input.window_state.lock().pending_title.take();
+        if let Some(title) = title {
+          let text = util::encode_wide(title);
+          let _ = SetWindowTextW(window, PCWSTR::from_raw(text.as_ptr()));
+        }
+        result = ProcResult::Value(LRESULT(0));
+      } else if msg == *SET_THEME_MSG_ID {
+        update_theme(window, subclass_input);
+        // Repaint the title bar, which otherwise keeps its colors until the window is activated.
+        RedrawWindow(window, None, HRGN::default(), RDW_FRAME | RDW_INVALIDATE);
+        result = ProcResult::Value(LRESULT(0));
       } else if msg == *SET_RETAIN_STATE_ON_SIZE_MSG_ID {
         let mut window_state = subclass_input.window_state.lock();
         window_state.set_window_flags_in_place(|f| {
           f.set(WindowFlags::MARKER_RETAIN_STATE_ON_SIZE, wparam.0 != 0)
         });
         result = ProcResult::Value(LRESULT(0));
+      } else if msg == *SET_DROP_TARGET_MSG_ID {
+        if let Some(file_drop_handler) = &subclass_input.file_drop_handler {
+          if wparam.0 != 0 {
+            let _ = RegisterDragDrop(window, file_drop_handler);
+          } else {
+            let _ = RevokeDragDrop(window);
+          }
+        }
+        result = ProcResult::Value(LRESULT(0));
       } else if msg == *S_U_TASKBAR_RESTART {
         let window_state = subclass_input.window_state.lock();
         set_skip_taskbar(window, window_state.skip_taskbar);
+        if let Some((icon, description)) = &window_state.overlay_icon {
+          set_overlay_icon(window, Some(icon), description);
+        }
       }
     }
   };
@@ -2191,6 +3318,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
   }
 }
 
+/// ICC profiles of the `monitors` which have one.
+fn icc_profiles(monitors: &VecDeque<MonitorHandle>) -> HashMap<MonitorHandle, IccProfile> {
+  monitors
+    .iter()
+    .filter_map(|monitor| Some((monitor.clone(), monitor.icc_profile()?)))
+    .collect()
+}
+
+/// Emits `MonitorIccProfileChanged` for the monitors whose profile changed since the last check.
+/// There's no message for it, so this is called when the display or system settings change.
+unsafe fn check_icc_profiles<T>(subclass_input: &ThreadMsgTargetSubclassInput<T>) {
+  let icc_profiles = icc_profiles(&subclass_input.monitors.borrow());
+  let previous = subclass_input.icc_profiles.replace(icc_profiles.clone());
+  for (monitor, icc_profile) in icc_profiles {
+    if previous
+      .get(&monitor)
+      .map_or(false, |previous| *previous != icc_profile)
+    {
+      subclass_input.send_event(Event::MonitorIccProfileChanged(RootMonitorHandle {
+        inner: monitor,
+      }));
+    }
+  }
+}
+
+/// Refresh rates of the `monitors` which report one.
+fn refresh_rates(monitors: &VecDeque<MonitorHandle>) -> HashMap<MonitorHandle, u32> {
+  monitors
+    .iter()
+    .filter_map(|monitor| Some((monitor.clone(), monitor.refresh_rate_millihertz()?)))
+    .collect()
+}
+
 unsafe extern "system" fn thread_event_target_callback<T: 'static>(
   window: HWND,
   msg: u32,
@@ -2208,6 +3368,10 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
   // the git blame and history would be preserved.
   let callback = || match msg {
     win32wm::WM_NCDESTROY => {
+      let _ = WTSUnRegisterSessionNotification(window);
+      if !subclass_input.lid_notification.is_invalid() {
+        let _ = UnregisterPowerSettingNotification(subclass_input.lid_notification);
+      }
       remove_event_target_window_subclass::<T>(window);
       subclass_removed = true;
       RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
@@ -2248,10 +3412,14 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
         _ => unreachable!(),
       };
 
-      subclass_input.send_event(Event::DeviceEvent {
-        device_id: wrap_device_id(lparam.0),
-        event,
-      });
+      let device_id = wrap_device_id(lparam.0);
+      if subclass_input
+        .device_events
+        .borrow()
+        .accepts_device(device_id)
+      {
+        subclass_input.send_event(Event::DeviceEvent { device_id, event });
+      }
       RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
 
       LRESULT(0)
@@ -2266,6 +3434,119 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       DefSubclassProc(window, msg, wparam, lparam)
     }
 
+    win32wm::WM_POWERBROADCAST => {
+      let (power_event, event) = match wparam.0 as u32 {
+        PBT_APMSUSPEND => (
+          Some(PowerEvent::Suspending),
+          subclass_input.suspension.borrow_mut().set_sleeping(true),
+        ),
+        PBT_APMRESUMEAUTOMATIC => (
+          Some(PowerEvent::Resuming),
+          subclass_input.suspension.borrow_mut().set_sleeping(false),
+        ),
+        PBT_APMPOWERSTATUSCHANGE => (subclass_input.power.borrow_mut().battery_changed(), None),
+        PBT_POWERSETTINGCHANGE => {
+          let setting = &*(lparam.0 as *const POWERBROADCAST_SETTING);
+          (
+            subclass_input.power.borrow_mut().setting_changed(setting),
+            None,
+          )
+        }
+        _ => (None, None),
+      };
+      if let Some(power_event) = power_event {
+        subclass_input.send_event(Event::PowerEvent(power_event));
+      }
+      if let Some(event) = event {
+        subclass_input.send_event(event);
+      }
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
+    win32wm::WM_WTSSESSION_CHANGE => {
+      let event = match wparam.0 as u32 {
+        WTS_SESSION_LOCK => subclass_input.suspension.borrow_mut().set_locked(true),
+        WTS_SESSION_UNLOCK => subclass_input.suspension.borrow_mut().set_locked(false),
+        _ => None,
+      };
+      if let Some(event) = event {
+        subclass_input.send_event(event);
+      }
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
+    win32wm::WM_SETTINGCHANGE | win32wm::WM_DWMCOLORIZATIONCOLORCHANGED => {
+      let appearance = dark_mode::system_appearance();
+      if subclass_input.appearance.replace(appearance) != appearance {
+        subclass_input.send_event(Event::SystemAppearanceChanged(appearance));
+      }
+      let area = PCWSTR::from_raw(lparam.0 as *const u16);
+      if msg == WM_SETTINGCHANGE
+        && !area.is_null()
+        && area.to_string().map_or(false, |a| a == "intl")
+      {
+        let locale = util::user_locale();
+        if subclass_input.locale.replace(locale.clone()) != locale {
+          if let Some(locale) = locale {
+            subclass_input.send_event(Event::LocaleChanged { locale });
+          }
+        }
+      }
+      if msg == WM_SETTINGCHANGE {
+        check_icc_profiles(&subclass_input);
+      }
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
+    win32wm::WM_TIMECHANGE => {
+      subclass_input.send_event(Event::SystemTimeChanged);
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
+    win32wm::WM_HOTKEY => {
+      let id = subclass_input
+        .global_shortcuts
+        .borrow()
+        .get(&(wparam.0 as i32))
+        .copied();
+      if let Some(id) = id {
+        subclass_input.send_event(Event::GlobalShortcut(id));
+      }
+      RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
+      LRESULT(0)
+    }
+    win32wm::WM_DISPLAYCHANGE => {
+      let monitors = monitor::available_monitors();
+      let previous = subclass_input.monitors.replace(monitors.clone());
+      for monitor in previous
+        .iter()
+        .filter(|monitor| !monitors.contains(monitor))
+      {
+        subclass_input.send_event(Event::MonitorDisconnected(RootMonitorHandle {
+          inner: monitor.clone(),
+        }));
+      }
+      for monitor in monitors
+        .iter()
+        .filter(|monitor| !previous.contains(monitor))
+      {
+        subclass_input.send_event(Event::MonitorConnected(RootMonitorHandle {
+          inner: monitor.clone(),
+        }));
+      }
+      let refresh_rates = refresh_rates(&monitors);
+      let previous = subclass_input.refresh_rates.replace(refresh_rates.clone());
+      for (monitor, refresh_rate) in refresh_rates {
+        if previous
+          .get(&monitor)
+          .map_or(false, |previous| *previous != refresh_rate)
+        {
+          subclass_input.send_event(Event::MonitorRefreshRateChanged {
+            monitor: RootMonitorHandle { inner: monitor },
+            refresh_rate_millihertz: refresh_rate,
+          });
+        }
+      }
+      check_icc_profiles(&subclass_input);
+      subclass_input.send_event(Event::MonitorConfigurationChanged);
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
     _ if msg == *USER_EVENT_MSG_ID => {
       if let Ok(event) = subclass_input.user_event_receiver.recv() {
         subclass_input.send_event(Event::UserEvent(event));
@@ -2273,12 +3554,56 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
       LRESULT(0)
     }
+    win32wm::WM_TIMER => {
+      let timer = subclass_input.timers.borrow().get(&wparam.0).copied();
+      if let Some((id, repeating)) = timer {
+        if !repeating {
+          subclass_input.timers.borrow_mut().remove(&wparam.0);
+          let _ = KillTimer(window, wparam.0);
+        }
+        subclass_input.send_event(Event::Timer(id));
+      }
+      RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
+      LRESULT(0)
+    }
+    _ if msg == *TYPED_USER_EVENT_MSG_ID => {
+      if let Ok(event) = subclass_input.typed_user_event_receiver.recv() {
+        subclass_input.send_event(Event::TypedUserEvent(event));
+      }
+      RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
+      LRESULT(0)
+    }
     _ if msg == *EXEC_MSG_ID => {
       let mut function: ThreadExecFn = Box::from_raw(wparam.0 as *mut _);
       function();
       RedrawWindow(window, None, HRGN::default(), RDW_INTERNALPAINT);
       LRESULT(0)
     }
+    _ if msg == *SET_RAW_MOUSE_MOTION_MSG_ID => {
+      let target = HWND(wparam.0 as _);
+      let mut device_events = subclass_input.device_events.borrow_mut();
+      device_events
+        .raw_mouse_motion
+        .retain(|hwnd| *hwnd != target && IsWindow(*hwnd).as_bool());
+      if lparam.0 != 0 {
+        device_events.raw_mouse_motion.push(target);
+      }
+      raw_input::register_all_mice_and_keyboards_for_raw_input(window, &device_events);
+      LRESULT(0)
+    }
+    _ if msg == *SET_FLAT_POINTER_MSG_ID => {
+      let target = HWND(wparam.0 as _);
+      let mut device_events = subclass_input.device_events.borrow_mut();
+      device_events
+        .flat_pointer
+        .retain(|hwnd| *hwnd != target && IsWindow(*hwnd).as_bool());
+      if lparam.0 != 0 {
+        device_events.flat_pointer.push(target);
+      }
+      subclass_input.flat_pointer_position.set(None);
+      raw_input::register_all_mice_and_keyboards_for_raw_input(window, &device_events);
+      LRESULT(0)
+    }
     _ if msg == *PROCESS_NEW_EVENTS_MSG_ID => {
       let _ = PostThreadMessageW(
         subclass_input.event_loop_runner.wait_thread_id(),
@@ -2329,6 +3654,37 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
   result
 }
 
+/// Moves the cursor of `window` by the raw mouse motion, one pixel per count, which replaces the
+/// accelerated motion the system already applied for the same input.
+unsafe fn move_flat_pointer<T: 'static>(
+  subclass_input: &ThreadMsgTargetSubclassInput<T>,
+  window: HWND,
+  dx: i32,
+  dy: i32,
+) {
+  let mut position = match subclass_input.flat_pointer_position.get() {
+    Some((hwnd, position)) if hwnd == window => position,
+    _ => {
+      let mut position = POINT::default();
+      if GetCursorPos(&mut position).is_err() {
+        return;
+      }
+      position
+    }
+  };
+  // The window confines the cursor while it's grabbed.
+  let mut clip = RECT::default();
+  if GetClipCursor(&mut clip).is_err() {
+    return;
+  }
+  position.x = (position.x + dx).min(clip.right - 1).max(clip.left);
+  position.y = (position.y + dy).min(clip.bottom - 1).max(clip.top);
+  subclass_input
+    .flat_pointer_position
+    .set(Some((window, position)));
+  let _ = SetCursorPos(position.x, position.y);
+}
+
 unsafe fn handle_raw_input<T: 'static>(
   subclass_input: &ThreadMsgTargetSubclassInput<T>,
   data: RAWINPUT,
@@ -2340,11 +3696,47 @@ unsafe fn handle_raw_input<T: 'static>(
   };
 
   let device_id = wrap_device_id(data.header.hDevice.0 as _);
+  let accepts = |kind| {
+    subclass_input
+      .device_events
+      .borrow()
+      .accepts(device_id, kind)
+  };
 
   if data.header.dwType == RIM_TYPEMOUSE.0 {
     let mouse = data.data.mouse;
 
-    if util::has_flag(mouse.usFlags, MOUSE_MOVE_RELATIVE as u16) {
+    if util::has_flag(mouse.usFlags, MOUSE_MOVE_RELATIVE as u16)
+      && (mouse.lLastX != 0 || mouse.lLastY != 0)
+    {
+      let foreground = GetForegroundWindow();
+      if subclass_input
+        .device_events
+        .borrow()
+        .raw_mouse_motion
+        .contains(&foreground)
+      {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(foreground.0)),
+          event: WindowEvent::RawMouseMotion {
+            device_id,
+            delta: (mouse.lLastX as f64, mouse.lLastY as f64),
+          },
+        });
+      }
+      if subclass_input
+        .device_events
+        .borrow()
+        .flat_pointer
+        .contains(&foreground)
+      {
+        move_flat_pointer(subclass_input, foreground, mouse.lLastX, mouse.lLastY);
+      }
+    }
+
+    if util::has_flag(mouse.usFlags, MOUSE_MOVE_RELATIVE as u16)
+      && accepts(DeviceEventKinds::MOUSE_MOTION)
+    {
       let x = mouse.lLastX as f64;
       let y = mouse.lLastY as f64;
 
@@ -2373,7 +3765,8 @@ unsafe fn handle_raw_input<T: 'static>(
     if util::has_flag(
       mouse.Anonymous.Anonymous.usButtonFlags,
       RI_MOUSE_WHEEL as u16,
-    ) {
+    ) && accepts(DeviceEventKinds::MOUSE_WHEEL)
+    {
       // We must cast to SHORT first, becaues `usButtonData` must be interpreted as signed.
       let delta = mouse.Anonymous.Anonymous.usButtonData as i16 as f32 / WHEEL_DELTA as f32;
       subclass_input.send_event(Event::DeviceEvent {
@@ -2384,8 +3777,11 @@ unsafe fn handle_raw_input<T: 'static>(
       });
     }
 
-    let button_state =
-      raw_input::get_raw_mouse_button_state(mouse.Anonymous.Anonymous.usButtonFlags);
+    let button_state = if accepts(DeviceEventKinds::MOUSE_BUTTON) {
+      raw_input::get_raw_mouse_button_state(mouse.Anonymous.Anonymous.usButtonFlags)
+    } else {
+      Default::default()
+    };
     // Left, middle, and right, respectively.
     for (index, state) in button_state.iter().enumerate() {
       if let Some(state) = *state {
@@ -2399,7 +3795,7 @@ unsafe fn handle_raw_input<T: 'static>(
         });
       }
     }
-  } else if data.header.dwType == RIM_TYPEKEYBOARD.0 {
+  } else if data.header.dwType == RIM_TYPEKEYBOARD.0 && accepts(DeviceEventKinds::KEYBOARD) {
     let keyboard = data.data.keyboard;
 
     let pressed = keyboard.Message == WM_KEYDOWN || keyboard.Message == WM_SYSKEYDOWN;
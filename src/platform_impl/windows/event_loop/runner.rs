@@ -20,7 +20,7 @@ use crate::{
   dpi::PhysicalSize,
   event::{Event, StartCause, WindowEvent},
   event_loop::ControlFlow,
-  platform_impl::platform::util,
+  platform_impl::platform::{jump_list, util},
   window::WindowId,
 };
 
@@ -33,6 +33,8 @@ pub(crate) struct EventLoopRunner<T: 'static> {
   control_flow: Cell<ControlFlow>,
   runner_state: Cell<RunnerState>,
   last_events_cleared: Cell<Instant>,
+  /// Whether the event loop is being woken up by an event sent through an `EventLoopProxy`.
+  user_event_wakeup: Cell<bool>,
 
   event_handler: Cell<Option<Box<dyn FnMut(Event<'_, T>, &mut ControlFlow)>>>,
   event_buffer: RefCell<VecDeque<BufferedEvent<T>>>,
@@ -75,6 +77,7 @@ impl<T> EventLoopRunner<T> {
       control_flow: Cell::new(ControlFlow::Poll),
       panic_error: Cell::new(None),
       last_events_cleared: Cell::new(Instant::now()),
+      user_event_wakeup: Cell::new(false),
       event_handler: Cell::new(None),
       event_buffer: RefCell::new(VecDeque::new()),
       owned_windows: Cell::new(HashSet::new()),
@@ -92,6 +95,11 @@ impl<T> EventLoopRunner<T> {
     assert!(old_event_handler.is_none());
   }
 
+  /// Drops the event handler between two `pump_events`, events are buffered until the next one.
+  pub(crate) fn clear_event_handler(&self) {
+    self.event_handler.set(None);
+  }
+
   pub(crate) fn reset_runner(&self) {
     let EventLoopRunner {
       thread_msg_target: _,
@@ -100,6 +108,7 @@ impl<T> EventLoopRunner<T> {
       panic_error,
       control_flow,
       last_events_cleared: _,
+      user_event_wakeup: _,
       event_handler,
       event_buffer: _,
       owned_windows: _,
@@ -140,6 +149,10 @@ impl<T> EventLoopRunner<T> {
     self.runner_state.get() != RunnerState::Idle
   }
 
+  pub fn initialized(&self) -> bool {
+    self.runner_state.get() != RunnerState::Uninitialized
+  }
+
   pub fn should_buffer(&self) -> bool {
     let handler = self.event_handler.take();
     let should_buffer = handler.is_none();
@@ -220,7 +233,12 @@ impl<T> EventLoopRunner<T> {
         .borrow_mut()
         .push_back(BufferedEvent::from_event(event))
     } else {
+      self.user_event_wakeup.set(matches!(
+        event,
+        Event::UserEvent(_) | Event::TypedUserEvent(_)
+      ));
       self.move_state_to(RunnerState::HandlingMainEvents);
+      self.user_event_wakeup.set(false);
       self.call_event_handler(event);
       self.dispatch_buffered_events();
     }
@@ -370,30 +388,40 @@ impl<T> EventLoopRunner<T> {
   }
 
   unsafe fn call_new_events(&self, init: bool) {
+    let start = self.last_events_cleared.get();
     let start_cause = match (init, self.control_flow()) {
       (true, _) => StartCause::Init,
       (false, ControlFlow::Poll) => StartCause::Poll,
-      (false, ControlFlow::ExitWithCode(_)) | (false, ControlFlow::Wait) => {
-        StartCause::WaitCancelled {
-          requested_resume: None,
-          start: self.last_events_cleared.get(),
+      (false, ControlFlow::WaitUntil(requested_resume)) if Instant::now() >= requested_resume => {
+        StartCause::ResumeTimeReached {
+          requested_resume,
+          start,
         }
       }
-      (false, ControlFlow::WaitUntil(requested_resume)) => {
-        if Instant::now() < requested_resume {
-          StartCause::WaitCancelled {
-            requested_resume: Some(requested_resume),
-            start: self.last_events_cleared.get(),
+      (false, control_flow) => {
+        let requested_resume = match control_flow {
+          ControlFlow::WaitUntil(requested_resume) => Some(requested_resume),
+          _ => None,
+        };
+        if self.user_event_wakeup.get() {
+          StartCause::UserEvent {
+            requested_resume,
+            start,
           }
         } else {
-          StartCause::ResumeTimeReached {
+          StartCause::WaitCancelled {
             requested_resume,
-            start: self.last_events_cleared.get(),
+            start,
           }
         }
       }
     };
     self.call_event_handler(Event::NewEvents(start_cause));
+    if init {
+      if let Some(arguments) = jump_list::launch_arguments() {
+        self.call_event_handler(Event::JumpListItemActivated { arguments });
+      }
+    }
     self.dispatch_buffered_events();
     RedrawWindow(
       self.thread_msg_target,
@@ -440,6 +468,7 @@ impl<T> BufferedEvent<T> {
           new_inner_size.width as _,
           new_inner_size.height as _,
           true,
+          false,
         );
       }
     }
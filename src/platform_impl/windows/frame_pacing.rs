@@ -0,0 +1,132 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Redraws aligned with DWM composition.
+//!
+//! Windows with frame pacing enabled don't redraw when requested, but are queued for a background
+//! thread which waits for the next composition pass with `DwmFlush` and then redraws them all.
+//!
+//! Windows with jank detection enabled compare the time between their redraws with the refresh
+//! period of their monitor instead.
+
+use std::{
+  collections::HashSet,
+  sync::Once,
+  thread,
+  time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex};
+use windows::Win32::{
+  Foundation::HWND,
+  Graphics::{
+    Dwm::DwmFlush,
+    Gdi::{RedrawWindow, HRGN, RDW_INTERNALPAINT},
+  },
+};
+
+use super::monitor::{self, MonitorHandle};
+
+lazy_static! {
+  static ref PENDING_REDRAWS: Mutex<HashSet<isize>> = Mutex::new(HashSet::new());
+  static ref REDRAW_REQUESTED: Condvar = Condvar::new();
+}
+
+/// Redraws `hwnd` at the next composition pass.
+pub fn request_redraw(hwnd: HWND) {
+  static START: Once = Once::new();
+  START.call_once(|| {
+    thread::Builder::new()
+      .name("tao frame pacing".into())
+      .spawn(run)
+      .expect("Failed to spawn the frame pacing thread");
+  });
+
+  PENDING_REDRAWS.lock().insert(hwnd.0);
+  REDRAW_REQUESTED.notify_one();
+}
+
+fn run() {
+  loop {
+    {
+      let mut pending = PENDING_REDRAWS.lock();
+      while pending.is_empty() {
+        REDRAW_REQUESTED.wait(&mut pending);
+      }
+    }
+
+    if unsafe { DwmFlush() }.is_err() {
+      // Composition is unavailable, fall back to a typical refresh rate.
+      thread::sleep(Duration::from_millis(16));
+    }
+
+    let pending = std::mem::take(&mut *PENDING_REDRAWS.lock());
+    for hwnd in pending {
+      unsafe {
+        RedrawWindow(HWND(hwnd), None, HRGN::default(), RDW_INTERNALPAINT);
+      }
+    }
+  }
+}
+
+/// Counts the refresh periods a window missed between two of its redraws.
+///
+/// A redraw is only compared with the previous one when it was requested before a refresh period
+/// went by after the previous one finished, so a window which stops redrawing for a while doesn't
+/// report the pause as missed frames.
+#[derive(Default)]
+pub struct JankDetector {
+  /// Monitor of the window at the previous redraw, and its refresh period.
+  monitor: Option<(MonitorHandle, Duration)>,
+  previous_redraw: Option<Instant>,
+  redraw_finished: Option<Instant>,
+  /// First `request_redraw` since the previous redraw.
+  requested_at: Option<Instant>,
+}
+
+impl JankDetector {
+  pub fn request_redraw(&mut self) {
+    self.requested_at.get_or_insert_with(Instant::now);
+  }
+
+  /// Returns how many frames were missed since the previous redraw, and how long they lasted.
+  pub fn redraw_requested(&mut self, hwnd: HWND) -> Option<(u32, Duration)> {
+    let now = Instant::now();
+    let previous_redraw = self.previous_redraw.replace(now);
+    let requested_at = self.requested_at.take();
+    let period = self.refresh_period(hwnd)?;
+    if requested_at? > self.redraw_finished? + period {
+      return None;
+    }
+
+    let elapsed = now.duration_since(previous_redraw?);
+    let frames = (elapsed.as_secs_f64() / period.as_secs_f64()).round() as u64;
+    if frames <= 1 {
+      return None;
+    }
+    Some((
+      (frames - 1).min(u32::MAX as u64) as u32,
+      elapsed.saturating_sub(period),
+    ))
+  }
+
+  pub fn redraw_finished(&mut self) {
+    self.redraw_finished = Some(Instant::now());
+  }
+
+  fn refresh_period(&mut self, hwnd: HWND) -> Option<Duration> {
+    let current_monitor = monitor::current_monitor(hwnd);
+    if let Some((monitor, period)) = &self.monitor {
+      if *monitor == current_monitor {
+        return Some(*period);
+      }
+    }
+    let millihertz = current_monitor
+      .refresh_rate_millihertz()
+      .filter(|&millihertz| millihertz > 0)?;
+    let period = Duration::from_nanos(1_000_000_000_000 / u64::from(millihertz));
+    self.monitor = Some((current_monitor, period));
+    Some(period)
+  }
+}
@@ -8,6 +8,7 @@ use windows::{
   core::PCWSTR,
   Win32::{
     Foundation::{HMODULE, HWND, LPARAM, WPARAM},
+    Graphics::Gdi::{CreateBitmap, DeleteObject},
     System::LibraryLoader::*,
     UI::WindowsAndMessaging::*,
   },
@@ -44,12 +45,27 @@ impl RgbaIcon {
         rgba.as_ptr(),
       )
     };
-    Ok(WinIcon::from_handle(
-      handle.map_err(|_| BadIcon::OsError(io::Error::last_os_error()))?,
-    ))
+    let handle = handle.map_err(|_| BadIcon::OsError(io::Error::last_os_error()))?;
+    Ok(WinIcon {
+      inner: Arc::new(RaiiIcon {
+        handle,
+        width: self.width,
+      }),
+      variants: Vec::new(),
+    })
   }
 }
 
+/// Timer that advances the frames of [`Window::set_window_icon_frames`].
+///
+/// [`Window::set_window_icon_frames`]: crate::window::Window::set_window_icon_frames
+pub const ICON_FRAME_TIMER_ID: usize = 0x6963_6f6e;
+
+/// Timer that advances the frames of an animated [`CustomCursor`].
+///
+/// [`CustomCursor`]: crate::icon::CustomCursor
+pub const CURSOR_FRAME_TIMER_ID: usize = 0x6375_7273;
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum IconType {
@@ -60,11 +76,15 @@ pub enum IconType {
 #[derive(Debug)]
 struct RaiiIcon {
   handle: HICON,
+  /// Width of icons created from RGBA data, or 0 when it is picked by the system.
+  width: u32,
 }
 
 #[derive(Clone)]
 pub struct WinIcon {
   inner: Arc<RaiiIcon>,
+  /// Other sizes of the same icon, see `Icon::with_variant`.
+  variants: Vec<Arc<RaiiIcon>>,
 }
 
 unsafe impl Send for WinIcon {}
@@ -128,20 +148,119 @@ impl WinIcon {
     rgba_icon.into_windows_icon()
   }
 
+  pub fn add_variant(&mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+    let rgba_icon = RgbaIcon::from_rgba(rgba, width, height)?;
+    self.variants.push(rgba_icon.into_windows_icon()?.inner);
+    Ok(())
+  }
+
   pub fn set_for_window(&self, hwnd: HWND, icon_type: IconType) {
+    let handle = self.handle_for(&icon_type);
     unsafe {
-      SendMessageW(
-        hwnd,
-        WM_SETICON,
-        WPARAM(icon_type as _),
-        LPARAM(self.as_raw_handle().0),
+      SendMessageW(hwnd, WM_SETICON, WPARAM(icon_type as _), LPARAM(handle.0));
+    }
+  }
+
+  /// Picks the smallest size at least as large as the system size of `icon_type`, or the largest
+  /// size if none is.
+  pub fn handle_for(&self, icon_type: &IconType) -> HICON {
+    if self.variants.is_empty() {
+      return self.as_raw_handle();
+    }
+    let target = unsafe {
+      GetSystemMetrics(match icon_type {
+        IconType::Small => SM_CXSMICON,
+        IconType::Big => SM_CXICON,
+      })
+    } as u32;
+    let icons = || once(&self.inner).chain(&self.variants);
+    icons()
+      .filter(|icon| icon.width >= target)
+      .min_by_key(|icon| icon.width)
+      .or_else(|| icons().max_by_key(|icon| icon.width))
+      .unwrap()
+      .handle
+  }
+
+  /// Creates a cursor from `image`. Cursors are icons with a hotspot, so they share `WinIcon`.
+  pub fn cursor_from_image(image: &CursorImage) -> Result<Self, BadIcon> {
+    let RgbaIcon {
+      rgba,
+      width,
+      height,
+    } = &image.rgba;
+    let mut bgra = rgba.clone();
+    let pixel_count = bgra.len() / PIXEL_SIZE;
+    let pixels =
+      unsafe { std::slice::from_raw_parts_mut(bgra.as_mut_ptr() as *mut Pixel, pixel_count) };
+    for pixel in pixels {
+      pixel.to_bgra();
+    }
+
+    // The mask is unused for 32bpp cursors but still required, rows are padded to 16 bits.
+    let mask_stride = ((*width as usize + 15) / 16) * 2;
+    let and_mask = vec![0u8; mask_stride * *height as usize];
+
+    unsafe {
+      let hbm_color = CreateBitmap(
+        *width as i32,
+        *height as i32,
+        1,
+        (PIXEL_SIZE * 8) as u32,
+        Some(bgra.as_ptr() as *const _),
       );
+      let hbm_mask = CreateBitmap(
+        *width as i32,
+        *height as i32,
+        1,
+        1,
+        Some(and_mask.as_ptr() as *const _),
+      );
+      let icon_info = ICONINFO {
+        fIcon: false.into(),
+        xHotspot: image.hotspot_x,
+        yHotspot: image.hotspot_y,
+        hbmMask: hbm_mask,
+        hbmColor: hbm_color,
+      };
+      let handle = CreateIconIndirect(&icon_info);
+      DeleteObject(hbm_color);
+      DeleteObject(hbm_mask);
+      Ok(WinIcon::from_handle(
+        handle.map_err(|_| BadIcon::OsError(io::Error::last_os_error()))?,
+      ))
+    }
+  }
+
+  /// Loads a cursor from a `.cur` or `.ani` file, at the default size of cursors.
+  pub fn cursor_from_path<P: AsRef<Path>>(path: P) -> Result<Self, BadIcon> {
+    let wide_path: Vec<u16> = path
+      .as_ref()
+      .as_os_str()
+      .encode_wide()
+      .chain(once(0))
+      .collect();
+
+    let handle = unsafe {
+      LoadImageW(
+        HMODULE::default(),
+        PCWSTR::from_raw(wide_path.as_ptr()),
+        IMAGE_CURSOR,
+        0,
+        0,
+        LR_DEFAULTSIZE | LR_LOADFROMFILE,
+      )
     }
+    .map(|handle| HICON(handle.0));
+    Ok(WinIcon::from_handle(
+      handle.map_err(|_| BadIcon::OsError(io::Error::last_os_error()))?,
+    ))
   }
 
   fn from_handle(handle: HICON) -> Self {
     Self {
-      inner: Arc::new(RaiiIcon { handle }),
+      inner: Arc::new(RaiiIcon { handle, width: 0 }),
+      variants: Vec::new(),
     }
   }
 }
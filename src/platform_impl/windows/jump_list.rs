@@ -0,0 +1,169 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Jump list of the application's taskbar button, see `JumpList`.
+//!
+//! Entries are shell links to the executable, whose command line starts with [`JUMP_LIST_ARG`] so
+//! that the launched instance can tell it was started from the jump list.
+
+use std::{env, io};
+
+use windows::{
+  core::{ComInterface, PCWSTR},
+  Win32::{
+    Storage::EnhancedStorage::PKEY_Title,
+    System::{
+      Com::{StructuredStorage::*, *},
+      Variant::VT_LPWSTR,
+    },
+    UI::Shell::{Common::*, PropertiesSystem::IPropertyStore, *},
+  },
+};
+
+use crate::{
+  error::OsError as RootOsError,
+  platform::windows::{JumpList, JumpListItem},
+  platform_impl::platform::{util, window::com_initialized, OsError},
+};
+
+/// First argument of the command line of jump list entries.
+const JUMP_LIST_ARG: &str = "--tao-jump-list-item";
+
+pub fn set_jump_list(jump_list: &JumpList) -> Result<(), RootOsError> {
+  unsafe { set_jump_list_inner(jump_list) }.map_err(|e| os_error!(OsError::IoError(e)))
+}
+
+unsafe fn set_jump_list_inner(jump_list: &JumpList) -> io::Result<()> {
+  com_initialized();
+  let list: ICustomDestinationList =
+    CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+  let mut max_slots = 0;
+  let removed: IObjectArray = list.BeginList(&mut max_slots)?;
+
+  // Appending an item the user removed from the menu fails the whole category.
+  let mut removed_arguments = Vec::new();
+  for i in 0..removed.GetCount()? {
+    if let Ok(link) = removed.GetAt::<IShellLinkW>(i) {
+      let mut arguments = [0u16; 1024];
+      if link.GetArguments(&mut arguments).is_ok() {
+        let len = arguments
+          .iter()
+          .position(|&c| c == 0)
+          .unwrap_or(arguments.len());
+        removed_arguments.push(util::wchar_to_string(&arguments[..len]));
+      }
+    }
+  }
+
+  let recent_items = jump_list
+    .recent_items
+    .iter()
+    .filter(|item| !removed_arguments.contains(&command_line(&item.arguments)));
+  if let Some(collection) = create_collection(recent_items)? {
+    let category = util::encode_wide("Recent");
+    list.AppendCategory(PCWSTR::from_raw(category.as_ptr()), &collection)?;
+  }
+  if let Some(collection) = create_collection(jump_list.tasks.iter())? {
+    list.AddUserTasks(&collection)?;
+  }
+
+  Ok(list.CommitList()?)
+}
+
+unsafe fn create_collection<'a>(
+  items: impl Iterator<Item = &'a JumpListItem>,
+) -> io::Result<Option<IObjectArray>> {
+  let collection: IObjectCollection =
+    CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+  let mut empty = true;
+  for item in items {
+    collection.AddObject(&create_link(item)?)?;
+    empty = false;
+  }
+  if empty {
+    Ok(None)
+  } else {
+    Ok(Some(collection.cast()?))
+  }
+}
+
+unsafe fn create_link(item: &JumpListItem) -> io::Result<IShellLinkW> {
+  let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+  let exe = util::encode_wide(env::current_exe()?);
+  link.SetPath(PCWSTR::from_raw(exe.as_ptr()))?;
+  let arguments = util::encode_wide(command_line(&item.arguments));
+  link.SetArguments(PCWSTR::from_raw(arguments.as_ptr()))?;
+  if let Some(description) = &item.description {
+    let description = util::encode_wide(description);
+    link.SetDescription(PCWSTR::from_raw(description.as_ptr()))?;
+  }
+  match &item.icon {
+    Some((path, index)) => {
+      let path = util::encode_wide(path);
+      link.SetIconLocation(PCWSTR::from_raw(path.as_ptr()), *index)?;
+    }
+    None => link.SetIconLocation(PCWSTR::from_raw(exe.as_ptr()), 0)?,
+  }
+
+  // The text of an entry is its title property, not its description.
+  let store: IPropertyStore = link.cast()?;
+  let title = util::encode_wide(&item.title);
+  let mut value = PROPVARIANT::default();
+  (*value.Anonymous.Anonymous).vt = VT_LPWSTR;
+  (*value.Anonymous.Anonymous).Anonymous.pwszVal = SHStrDupW(PCWSTR::from_raw(title.as_ptr()))?;
+  let result = store.SetValue(&PKEY_Title, &value);
+  let _ = PropVariantClear(&mut value);
+  result?;
+  store.Commit()?;
+
+  Ok(link)
+}
+
+/// Quotes the arguments of an entry the way `CommandLineToArgvW` splits them.
+fn command_line(arguments: &[String]) -> String {
+  let mut command_line = String::from(JUMP_LIST_ARG);
+  for argument in arguments {
+    command_line.push(' ');
+    if !argument.is_empty() && !argument.contains(|c: char| c == ' ' || c == '\t' || c == '"') {
+      command_line.push_str(argument);
+      continue;
+    }
+    command_line.push('"');
+    let mut backslashes = 0;
+    for c in argument.chars() {
+      match c {
+        '\\' => backslashes += 1,
+        '"' => {
+          // Backslashes are only special before a quote, which needs one of its own.
+          command_line.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+          backslashes = 0;
+        }
+        _ => {
+          command_line.extend(std::iter::repeat('\\').take(backslashes));
+          backslashes = 0;
+        }
+      }
+      if c != '\\' {
+        command_line.push(c);
+      }
+    }
+    // Same for the closing quote.
+    command_line.extend(std::iter::repeat('\\').take(backslashes * 2));
+    command_line.push('"');
+  }
+  command_line
+}
+
+/// Returns the arguments of the jump list entry the application was launched from, if any.
+pub fn launch_arguments() -> Option<Vec<String>> {
+  let mut args = env::args_os().skip(1);
+  if args.next()? != JUMP_LIST_ARG {
+    return None;
+  }
+  Some(
+    args
+      .map(|argument| argument.to_string_lossy().into_owned())
+      .collect(),
+  )
+}
@@ -20,7 +20,7 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
   event::{ElementState, KeyEvent},
-  keyboard::{Key, KeyCode, KeyLocation, NativeKeyCode},
+  keyboard::{DeadKeyState, Key, KeyCode, KeyLocation, NativeKeyCode, RepeatCounter},
   platform_impl::{
     platform::{
       event_loop::ProcResult,
@@ -72,6 +72,8 @@ lazy_static! {
 #[derive(Default)]
 pub struct KeyEventBuilder {
   event_info: Option<PartialKeyEventInfo>,
+  pub(crate) repeats: RepeatCounter,
+  pub(crate) dead_keys: DeadKeyState,
 }
 impl KeyEventBuilder {
   /// Call this function for every window message.
@@ -464,6 +466,7 @@ impl KeyEventBuilder {
       vkey: vk,
       logical_key: PartialLogicalKey::This(logical_key.clone()),
       key_without_modifiers,
+      input_locale: layout.input_locale,
       key_state,
       scancode,
       is_repeat: false,
@@ -510,6 +513,7 @@ struct PartialKeyEventInfo {
   logical_key: PartialLogicalKey,
 
   key_without_modifiers: Key<'static>,
+  input_locale: &'static str,
 
   /// The UTF-16 code units of the text that was produced by the keypress event.
   /// This take all modifiers into account. Including CTRL
@@ -539,6 +543,7 @@ impl PartialKeyEventInfo {
     };
     let code = KeyCode::from_scancode(scancode as u32);
     let location = get_location(scancode, layout.hkl);
+    let input_locale = layout.input_locale;
 
     let kbd_state = get_kbd_state();
     let mods = WindowsModifiers::active_modifiers(&kbd_state);
@@ -605,6 +610,7 @@ impl PartialKeyEventInfo {
       key_state: state,
       logical_key,
       key_without_modifiers,
+      input_locale,
       is_repeat: lparam_struct.is_repeat,
       code,
       location,
@@ -661,9 +667,12 @@ impl PartialKeyEventInfo {
       location: self.location,
       state: self.key_state,
       repeat: self.is_repeat,
+      repeat_count: 0,
+      dead_key_pending: false,
       platform_specific: KeyEventExtra {
         text_with_all_modifiers: char_with_all_modifiers,
         key_without_modifiers: self.key_without_modifiers,
+        input_locale: Some(self.input_locale),
       },
     }
   }
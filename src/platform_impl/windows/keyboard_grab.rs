@@ -0,0 +1,128 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keyboard grab support.
+//!
+//! Shortcuts like Alt+Tab or the Windows key are handled by the shell before they reach any
+//! window, so a low-level keyboard hook is installed while at least one window has the keyboard
+//! grabbed. When the grabbed window is in the foreground, the hook swallows those shortcuts, and the
+//! media and volume keys, and re-posts them to the window as regular key messages.
+
+use std::{collections::HashSet, io};
+
+use parking_lot::Mutex;
+use windows::Win32::{
+  Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+  UI::{
+    Input::KeyboardAndMouse::*,
+    WindowsAndMessaging::{self as win32wm, *},
+  },
+};
+
+use super::util;
+
+#[derive(Default)]
+struct KeyboardGrab {
+  hook: Option<HHOOK>,
+  windows: HashSet<isize>,
+}
+
+unsafe impl Send for KeyboardGrab {}
+
+lazy_static! {
+  static ref KEYBOARD_GRAB: Mutex<KeyboardGrab> = Mutex::new(KeyboardGrab::default());
+}
+
+/// Grabs or releases the keyboard for `hwnd`.
+///
+/// Must be called from the thread running the event loop, since the hook procedure is invoked
+/// on the thread that installed it.
+pub fn set_keyboard_grab(hwnd: HWND, grab: bool) -> Result<(), io::Error> {
+  let mut state = KEYBOARD_GRAB.lock();
+  if grab {
+    if state.hook.is_none() {
+      let hook = unsafe {
+        SetWindowsHookExW(
+          WH_KEYBOARD_LL,
+          Some(low_level_keyboard_proc),
+          HINSTANCE(util::get_instance_handle().0),
+          0,
+        )
+      }?;
+      state.hook = Some(hook);
+    }
+    state.windows.insert(hwnd.0);
+  } else {
+    state.windows.remove(&hwnd.0);
+    if state.windows.is_empty() {
+      if let Some(hook) = state.hook.take() {
+        unsafe { UnhookWindowsHookEx(hook) }?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Releases the keyboard grab of a window that is being destroyed.
+pub fn remove_window(hwnd: HWND) {
+  let _ = set_keyboard_grab(hwnd, false);
+}
+
+/// Shortcuts that the shell would otherwise consume before the window sees them.
+fn is_system_shortcut(info: &KBDLLHOOKSTRUCT) -> bool {
+  let vk = VIRTUAL_KEY(info.vkCode as u16);
+  let alt_down = info.flags.0 & LLKHF_ALTDOWN.0 != 0;
+  let ctrl_down = unsafe { GetAsyncKeyState(i32::from(VK_CONTROL.0)) } < 0;
+
+  match vk {
+    VK_LWIN | VK_RWIN => true,
+    VK_TAB | VK_SPACE => alt_down,
+    VK_ESCAPE => alt_down || ctrl_down,
+    VK_VOLUME_MUTE | VK_VOLUME_DOWN | VK_VOLUME_UP => true,
+    VK_MEDIA_NEXT_TRACK | VK_MEDIA_PREV_TRACK | VK_MEDIA_STOP | VK_MEDIA_PLAY_PAUSE => true,
+    _ => false,
+  }
+}
+
+unsafe extern "system" fn low_level_keyboard_proc(
+  code: i32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  if code == HC_ACTION as i32 {
+    let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+    let foreground = GetForegroundWindow();
+    let grabbed = KEYBOARD_GRAB.lock().windows.contains(&foreground.0);
+
+    if grabbed && info.flags.0 & LLKHF_INJECTED.0 == 0 && is_system_shortcut(info) {
+      forward_key_message(foreground, wparam.0 as u32, info);
+      return LRESULT(1);
+    }
+  }
+
+  CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Re-posts a key event swallowed by the hook as the key message the window would have received.
+unsafe fn forward_key_message(hwnd: HWND, msg: u32, info: &KBDLLHOOKSTRUCT) {
+  let released = matches!(msg, win32wm::WM_KEYUP | win32wm::WM_SYSKEYUP);
+
+  let mut lparam = 1 | ((info.scanCode & 0xFF) << 16);
+  if info.flags.0 & LLKHF_EXTENDED.0 != 0 {
+    lparam |= 1 << 24;
+  }
+  if info.flags.0 & LLKHF_ALTDOWN.0 != 0 {
+    lparam |= 1 << 29;
+  }
+  if released {
+    lparam |= (1 << 30) | (1 << 31);
+  }
+
+  let _ = PostMessageW(
+    hwnd,
+    msg,
+    WPARAM(info.vkCode as usize),
+    LPARAM(lparam as isize),
+  );
+}
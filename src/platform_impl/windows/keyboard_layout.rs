@@ -157,6 +157,9 @@ impl WindowsModifiers {
 pub(crate) struct Layout {
   pub hkl: HKL,
 
+  /// `hkl` formatted as hexadecimal, reported by `KeyEvent::input_locale`.
+  pub input_locale: &'static str,
+
   /// Maps numpad keys from Windows virtual key to a `Key`.
   ///
   /// This is useful because some numpad keys generate different charcaters based on the locale.
@@ -250,6 +253,38 @@ impl LayoutCache {
     }
   }
 
+  /// Returns the key `keycode` produces without modifiers on the current layout.
+  pub fn key_for_code(&mut self, keycode: KeyCode) -> Key<'static> {
+    let scancode = match keycode.to_scancode() {
+      Some(scancode) => scancode as ExScancode,
+      None => return Key::Unidentified(NativeKeyCode::Unidentified),
+    };
+    let (hkl, layout) = self.get_current_layout();
+    let vkey = keycode_to_vkey(keycode, hkl);
+    layout.get_key(WindowsModifiers::empty(), false, vkey, scancode, keycode)
+  }
+
+  /// Returns the key with the lowest scancode which produces `key` without modifiers on the
+  /// current layout.
+  pub fn code_for_key(&mut self, key: &Key<'_>) -> Option<KeyCode> {
+    if let Key::Unidentified(_) = key {
+      return None;
+    }
+    let (_, layout) = self.get_current_layout();
+    let mut codes: Vec<(u32, KeyCode)> = layout
+      .keys
+      .get(&WindowsModifiers::empty())
+      .into_iter()
+      .flat_map(|keys| keys.keys())
+      .filter_map(|code| Some((code.to_scancode()?, *code)))
+      .collect();
+    codes.sort_unstable_by_key(|(scancode, _)| *scancode);
+    codes
+      .into_iter()
+      .map(|(_, code)| code)
+      .find(|code| self.key_for_code(*code) == *key)
+  }
+
   pub fn get_agnostic_mods(&mut self) -> ModifiersState {
     let (_, layout) = self.get_current_layout();
     let filter_out_altgr = layout.has_alt_graph && key_pressed(VK_RMENU);
@@ -273,6 +308,7 @@ impl LayoutCache {
   fn prepare_layout(strings: &mut HashSet<&'static str>, locale_id: HKL) -> Layout {
     let mut layout = Layout {
       hkl: locale_id,
+      input_locale: get_or_insert_str(strings, format!("{:08X}", locale_id.0 as u32)),
       numlock_on_keys: Default::default(),
       numlock_off_keys: Default::default(),
       keys: Default::default(),
@@ -517,7 +553,7 @@ fn is_numpad_specific(vk: VIRTUAL_KEY) -> bool {
   )
 }
 
-fn keycode_to_vkey(keycode: KeyCode, hkl: HKL) -> VIRTUAL_KEY {
+pub(crate) fn keycode_to_vkey(keycode: KeyCode, hkl: HKL) -> VIRTUAL_KEY {
   let primary_lang_id = util::PRIMARYLANGID(hkl);
   let is_korean = primary_lang_id == LANG_KOREAN;
   let is_japanese = primary_lang_id == LANG_JAPANESE;
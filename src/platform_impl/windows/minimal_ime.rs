@@ -2,10 +2,17 @@ use std::mem::MaybeUninit;
 
 use windows::Win32::{
   Foundation::{HWND, LPARAM, LRESULT, WPARAM},
-  UI::WindowsAndMessaging::{self as win32wm, *},
+  Globalization::HIMC,
+  UI::{
+    Input::Ime::{
+      ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext, GCS_COMPSTR, GCS_CURSORPOS,
+      GCS_RESULTSTR, IME_COMPOSITION_STRING,
+    },
+    WindowsAndMessaging::{self as win32wm, *},
+  },
 };
 
-use crate::platform_impl::platform::event_loop::ProcResult;
+use crate::{event::Ime, platform_impl::platform::event_loop::ProcResult};
 
 pub fn is_msg_ime_related(msg_kind: u32) -> bool {
   matches!(
@@ -86,3 +93,58 @@ impl MinimalIme {
     None
   }
 }
+
+/// Returns the `WindowEvent::Ime` events of a composition message.
+///
+/// The messages are only observed, the default window procedure still turns the committed text
+/// into `WM_IME_CHAR` messages for `MinimalIme`.
+pub fn composition_events(hwnd: HWND, msg_kind: u32, lparam: LPARAM) -> Vec<Ime> {
+  let mut events = Vec::new();
+  match msg_kind {
+    win32wm::WM_IME_COMPOSITION => unsafe {
+      let flags = lparam.0 as u32;
+      let himc = ImmGetContext(hwnd);
+      if himc.is_invalid() {
+        return events;
+      }
+      if flags & GCS_RESULTSTR.0 != 0 {
+        if let Some(text) = composition_string(himc, GCS_RESULTSTR) {
+          events.push(Ime::Commit(String::from_utf16_lossy(&text)));
+        }
+      }
+      if flags & GCS_COMPSTR.0 != 0 {
+        if let Some(text) = composition_string(himc, GCS_COMPSTR) {
+          let cursor = if flags & GCS_CURSORPOS.0 != 0 {
+            // The cursor position is the number of UTF-16 units before it.
+            let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, None, 0);
+            usize::try_from(cursor).ok().map(|cursor| {
+              let cursor = String::from_utf16_lossy(&text[..cursor.min(text.len())]).len();
+              (cursor, cursor)
+            })
+          } else {
+            None
+          };
+          events.push(Ime::Preedit(String::from_utf16_lossy(&text), cursor));
+        }
+      }
+      ImmReleaseContext(hwnd, himc);
+    },
+    win32wm::WM_IME_ENDCOMPOSITION => events.push(Ime::Preedit(String::new(), None)),
+    _ => (),
+  }
+  events
+}
+
+unsafe fn composition_string(himc: HIMC, kind: IME_COMPOSITION_STRING) -> Option<Vec<u16>> {
+  // The length is in bytes.
+  let len = ImmGetCompositionStringW(himc, kind, None, 0);
+  let mut text = vec![0u16; usize::try_from(len).ok()? / 2];
+  let len = ImmGetCompositionStringW(
+    himc,
+    kind,
+    Some(text.as_mut_ptr() as *mut _),
+    (text.len() * 2) as u32,
+  );
+  text.truncate(usize::try_from(len).ok()? / 2);
+  Some(text)
+}
@@ -6,22 +6,31 @@
 
 use windows::Win32::{
   Foundation::{HANDLE, HWND},
-  UI::WindowsAndMessaging::HMENU,
+  UI::WindowsAndMessaging::{CS_HREDRAW, CS_OWNDC, CS_VREDRAW, HMENU},
 };
 
 pub(crate) use self::{
+  data_format::DataFormat,
   event_loop::{
     EventLoop, EventLoopProxy, EventLoopWindowTarget, PlatformSpecificEventLoopAttributes,
   },
   icon::WinIcon,
+  jump_list::set_jump_list,
   keycode::{keycode_from_scancode, keycode_to_scancode},
   monitor::{MonitorHandle, VideoMode},
-  window::Window,
+  surface_region::SurfaceRegion,
+  window::{CloseToken, Window},
+  window_state::CustomStyles,
 };
 
+#[cfg(feature = "notifications")]
+pub(crate) use self::notification::Notifier;
+
 pub use self::icon::WinIcon as PlatformIcon;
 
-use crate::{event::DeviceId as RootDeviceId, icon::Icon, keyboard::Key};
+use crate::{
+  event::DeviceId as RootDeviceId, icon::Icon, keyboard::Key, platform::windows::DpiAwareness,
+};
 mod keycode;
 
 #[non_exhaustive]
@@ -39,10 +48,14 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub taskbar_icon: Option<Icon>,
   pub skip_taskbar: bool,
   pub window_classname: String,
+  pub window_class_style: u32,
+  pub window_class_icon: Option<Icon>,
   pub no_redirection_bitmap: bool,
   pub drag_and_drop: bool,
   pub decoration_shadow: bool,
   pub rtl: bool,
+  pub custom_styles: CustomStyles,
+  pub dpi_awareness: Option<DpiAwareness>,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -55,8 +68,12 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       drag_and_drop: true,
       skip_taskbar: false,
       window_classname: "Window Class".to_string(),
+      window_class_style: (CS_HREDRAW | CS_VREDRAW | CS_OWNDC).0,
+      window_class_icon: None,
       decoration_shadow: true,
       rtl: false,
+      custom_styles: CustomStyles::default(),
+      dpi_awareness: None,
     }
   }
 }
@@ -136,6 +153,7 @@ fn wrap_device_id(id: isize) -> RootDeviceId {
 pub struct KeyEventExtra {
   pub text_with_all_modifiers: Option<&'static str>,
   pub key_without_modifiers: Key<'static>,
+  pub input_locale: Option<&'static str>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -151,15 +169,24 @@ impl WindowId {
 
 #[macro_use]
 mod util;
+mod accelerator;
 mod dark_mode;
+mod data_format;
 mod dpi;
 mod drop_handler;
 mod event_loop;
+mod frame_pacing;
 mod icon;
+mod jump_list;
 mod keyboard;
+mod keyboard_grab;
 mod keyboard_layout;
 mod minimal_ime;
 mod monitor;
+#[cfg(feature = "notifications")]
+mod notification;
+mod peek;
 mod raw_input;
+mod surface_region;
 mod window;
 mod window_state;
@@ -3,26 +3,50 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use windows::{
-  core::PCWSTR,
+  core::{w, ComInterface, PCWSTR, PWSTR},
   Win32::{
-    Foundation::{BOOL, HWND, LPARAM, POINT, RECT},
-    Graphics::Gdi::*,
+    Devices::Display::{
+      DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+      DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+      DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_PATH_INFO,
+      DISPLAYCONFIG_SOURCE_DEVICE_NAME, DISPLAYCONFIG_TARGET_DEVICE_NAME, QDC_ONLY_ACTIVE_PATHS,
+    },
+    Foundation::{BOOL, HWND, LPARAM, MAX_PATH, POINT, RECT},
+    Graphics::{
+      Dxgi::{
+        Common::{
+          DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020, DXGI_COLOR_SPACE_RGB_FULL_G22_NONE_P709,
+        },
+        CreateDXGIFactory1, IDXGIFactory1, IDXGIOutput6, DXGI_OUTPUT_DESC1,
+      },
+      Gdi::*,
+    },
+    System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY},
+    UI::{ColorSystem::GetICMProfileW, WindowsAndMessaging::MONITORINFOF_PRIMARY},
   },
 };
 
 use std::{
   collections::{BTreeSet, VecDeque},
   io, mem,
+  path::PathBuf,
 };
 
 use super::util;
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  error::ExternalError,
+  monitor::{
+    ColorInfo, ColorSpace, IccProfile, MonitorHandle as RootMonitorHandle,
+    VideoMode as RootVideoMode,
+  },
+  platform_impl::edid::{self, Edid},
   platform_impl::platform::{
     dpi::{dpi_to_scale_factor, get_monitor_dpi},
     window::Window,
+    OsError,
   },
+  window::Rect,
 };
 
 #[derive(Clone)]
@@ -31,7 +55,8 @@ pub struct VideoMode {
   pub(crate) bit_depth: u16,
   pub(crate) refresh_rate: u16,
   pub(crate) monitor: MonitorHandle,
-  pub(crate) native_video_mode: DEVMODEW,
+  // Boxed to keep `Fullscreen`, which `WindowEvent::FullscreenStateChanged` carries, small.
+  pub(crate) native_video_mode: Box<DEVMODEW>,
 }
 
 impl PartialEq for VideoMode {
@@ -171,6 +196,136 @@ pub(crate) fn get_monitor_info(hmonitor: HMONITOR) -> Result<MONITORINFOEXW, io:
   }
 }
 
+/// Finds the DXGI output which drives `hmonitor`, to read its color capabilities.
+fn get_output_desc(hmonitor: HMONITOR) -> Option<DXGI_OUTPUT_DESC1> {
+  unsafe {
+    let factory: IDXGIFactory1 = CreateDXGIFactory1().ok()?;
+    let mut adapter_index = 0;
+    while let Ok(adapter) = factory.EnumAdapters1(adapter_index) {
+      adapter_index += 1;
+      let mut output_index = 0;
+      while let Ok(output) = adapter.EnumOutputs(output_index) {
+        output_index += 1;
+        // `IDXGIOutput6` is only available since Windows 10 1803.
+        let output = match output.cast::<IDXGIOutput6>() {
+          Ok(output) => output,
+          Err(_) => continue,
+        };
+        let mut desc = mem::zeroed::<DXGI_OUTPUT_DESC1>();
+        if output.GetDesc1(&mut desc).is_ok() && desc.Monitor == hmonitor {
+          return Some(desc);
+        }
+      }
+    }
+    None
+  }
+}
+
+/// Set in the flags of `DISPLAYCONFIG_TARGET_DEVICE_NAME` when its EDID ids are valid.
+const EDID_IDS_VALID: u32 = 0x4;
+
+/// Finds the active display configuration path from the GDI device `device_name` to a monitor.
+/// The first one is returned when the device is mirrored to several monitors.
+fn get_display_path(device_name: &str) -> Option<DISPLAYCONFIG_PATH_INFO> {
+  unsafe {
+    let (mut path_count, mut mode_count) = (0, 0);
+    GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count).ok()?;
+    let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+    let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+    QueryDisplayConfig(
+      QDC_ONLY_ACTIVE_PATHS,
+      &mut path_count,
+      paths.as_mut_ptr(),
+      &mut mode_count,
+      modes.as_mut_ptr(),
+      None,
+    )
+    .ok()?;
+    paths.truncate(path_count as usize);
+
+    for path in paths {
+      let mut source = DISPLAYCONFIG_SOURCE_DEVICE_NAME {
+        header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+          r#type: DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+          size: mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32,
+          adapterId: path.sourceInfo.adapterId,
+          id: path.sourceInfo.id,
+        },
+        ..Default::default()
+      };
+      if DisplayConfigGetDeviceInfo(&mut source.header) == 0
+        && util::wchar_ptr_to_string(PCWSTR::from_raw(source.viewGdiDeviceName.as_ptr()))
+          == device_name
+      {
+        return Some(path);
+      }
+    }
+    None
+  }
+}
+
+/// Finds the display configuration target, i.e. the monitor, driven by the GDI device
+/// `device_name`.
+fn get_target_name(device_name: &str) -> Option<DISPLAYCONFIG_TARGET_DEVICE_NAME> {
+  let path = get_display_path(device_name)?;
+  let mut target = DISPLAYCONFIG_TARGET_DEVICE_NAME {
+    header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+      r#type: DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+      size: mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32,
+      adapterId: path.targetInfo.adapterId,
+      id: path.targetInfo.id,
+    },
+    ..Default::default()
+  };
+  if unsafe { DisplayConfigGetDeviceInfo(&mut target.header) } == 0 {
+    Some(target)
+  } else {
+    None
+  }
+}
+
+/// Reads the EDID the system stored for the monitor at `device_path`, e.g.
+/// `\\?\DISPLAY#DEL4085#5&2a4b2b4&0&UID4353#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}`.
+fn get_edid(device_path: &str) -> Option<Edid> {
+  let mut parts = device_path.trim_start_matches("\\\\?\\").split('#');
+  let (class, hardware_id, instance_id) = (parts.next()?, parts.next()?, parts.next()?);
+  let subkey = util::encode_wide(format!(
+    "SYSTEM\\CurrentControlSet\\Enum\\{}\\{}\\{}\\Device Parameters",
+    class, hardware_id, instance_id
+  ));
+  let get_value = |data: Option<*mut std::ffi::c_void>, size: &mut u32| unsafe {
+    RegGetValueW(
+      HKEY_LOCAL_MACHINE,
+      PCWSTR::from_raw(subkey.as_ptr()),
+      w!("EDID"),
+      RRF_RT_REG_BINARY,
+      None,
+      data,
+      Some(size),
+    )
+  };
+  // The EDID has a variable number of extension blocks after the base block.
+  let mut size = 0;
+  get_value(None, &mut size).ok()?;
+  let mut data = vec![0u8; size as usize];
+  get_value(Some(data.as_mut_ptr() as _), &mut size).ok()?;
+  Edid::parse(&data[..size as usize])
+}
+
+/// Returns the bounding rectangle of `monitors`, in virtual screen coordinates.
+pub(crate) fn spanning_rect(monitors: &[RootMonitorHandle]) -> Option<RECT> {
+  monitors
+    .iter()
+    .filter_map(|monitor| get_monitor_info(monitor.inner.hmonitor()).ok())
+    .map(|info| info.monitorInfo.rcMonitor)
+    .reduce(|a, b| RECT {
+      left: a.left.min(b.left),
+      top: a.top.min(b.top),
+      right: a.right.max(b.right),
+      bottom: a.bottom.max(b.bottom),
+    })
+}
+
 impl MonitorHandle {
   pub(crate) fn new(hmonitor: HMONITOR) -> Self {
     MonitorHandle(hmonitor.0)
@@ -194,6 +349,13 @@ impl MonitorHandle {
     HMONITOR(self.0)
   }
 
+  #[inline]
+  pub fn is_primary(&self) -> bool {
+    get_monitor_info(self.hmonitor())
+      .map(|info| info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0)
+      .unwrap_or(false)
+  }
+
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
     let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
@@ -214,6 +376,20 @@ impl MonitorHandle {
     }
   }
 
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
+    let work = monitor_info.monitorInfo.rcWork;
+    Rect {
+      position: PhysicalPosition::new(work.left, work.top).into(),
+      size: PhysicalSize::new(
+        (work.right - work.left) as u32,
+        (work.bottom - work.top) as u32,
+      )
+      .into(),
+    }
+  }
+
   #[inline]
   pub fn scale_factor(&self) -> f64 {
     dpi_to_scale_factor(get_monitor_dpi(self.hmonitor()).unwrap_or(96))
@@ -254,7 +430,7 @@ impl MonitorHandle {
             bit_depth: mode.dmBitsPerPel as u16,
             refresh_rate: mode.dmDisplayFrequency as u16,
             monitor: self.clone(),
-            native_video_mode: mode,
+            native_video_mode: Box::new(mode),
           },
         });
       }
@@ -262,4 +438,117 @@ impl MonitorHandle {
 
     modes.into_iter()
   }
+
+  pub fn set_video_mode(&self, video_mode: &VideoMode) -> Result<(), ExternalError> {
+    // `CDS_FULLSCREEN` makes the change temporary, it's undone when the process exits.
+    self.change_display_settings(Some(&video_mode.native_video_mode), CDS_FULLSCREEN)
+  }
+
+  pub fn reset_video_mode(&self) -> Result<(), ExternalError> {
+    // Without a mode, the one stored in the registry is restored.
+    self.change_display_settings(None, CDS_TYPE(0))
+  }
+
+  fn change_display_settings(
+    &self,
+    mode: Option<&DEVMODEW>,
+    flags: CDS_TYPE,
+  ) -> Result<(), ExternalError> {
+    let monitor_info = get_monitor_info(self.hmonitor())
+      .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e))))?;
+    let res = unsafe {
+      ChangeDisplaySettingsExW(
+        PCWSTR::from_raw(monitor_info.szDevice.as_ptr()),
+        mode.map(|mode| mode as *const _),
+        HWND::default(),
+        flags,
+        None,
+      )
+    };
+    if res != DISP_CHANGE_SUCCESSFUL {
+      return Err(ExternalError::Os(os_error!(OsError::IoError(
+        io::Error::new(
+          io::ErrorKind::Other,
+          format!("ChangeDisplaySettingsExW failed with {}", res.0),
+        )
+      ))));
+    }
+    Ok(())
+  }
+
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    let rate = get_display_path(&self.name()?)?.targetInfo.refreshRate;
+    if rate.Denominator == 0 {
+      return None;
+    }
+    Some((u64::from(rate.Numerator) * 1000 / u64::from(rate.Denominator)) as u32)
+  }
+
+  pub fn variable_refresh_rate(&self) -> Option<bool> {
+    None
+  }
+
+  pub fn manufacturer(&self) -> Option<String> {
+    let target = self.target_name()?;
+    // The id is stored with the byte order of the EDID, big-endian.
+    edid::manufacturer_id(target.edidManufactureId.swap_bytes())
+  }
+
+  pub fn model(&self) -> Option<u32> {
+    self
+      .target_name()
+      .map(|target| u32::from(target.edidProductCodeId))
+  }
+
+  pub fn serial_number(&self) -> Option<String> {
+    let target = get_target_name(&self.name()?)?;
+    let device_path =
+      util::wchar_ptr_to_string(PCWSTR::from_raw(target.monitorDevicePath.as_ptr()));
+    get_edid(&device_path)?.serial_number
+  }
+
+  /// Display configuration target of the monitor, if it has valid EDID ids.
+  fn target_name(&self) -> Option<DISPLAYCONFIG_TARGET_DEVICE_NAME> {
+    let target = get_target_name(&self.name()?)?;
+    if unsafe { target.flags.Anonymous.value } & EDID_IDS_VALID == 0 {
+      return None;
+    }
+    Some(target)
+  }
+
+  pub fn color_info(&self) -> ColorInfo {
+    let mut info = ColorInfo::default();
+    if let Some(desc) = get_output_desc(self.hmonitor()) {
+      info.bits_per_color = Some(desc.BitsPerColor);
+      info.max_luminance = Some(desc.MaxLuminance).filter(|nits| *nits > 0.0);
+      info.hdr_enabled = desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020;
+      info.color_space = match desc.ColorSpace {
+        DXGI_COLOR_SPACE_RGB_FULL_G22_NONE_P709 => ColorSpace::Srgb,
+        DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020 => ColorSpace::Rec2020,
+        _ => ColorSpace::Unknown,
+      };
+    }
+    info
+  }
+
+  pub fn icc_profile(&self) -> Option<IccProfile> {
+    let monitor_info = get_monitor_info(self.hmonitor()).ok()?;
+    let device = PCWSTR::from_raw(monitor_info.szDevice.as_ptr());
+    let mut path = [0u16; MAX_PATH as usize];
+    let mut len = path.len() as u32;
+    unsafe {
+      let hdc = CreateDCW(device, device, PCWSTR::null(), None);
+      if hdc.is_invalid() {
+        return None;
+      }
+      let found = GetICMProfileW(hdc, &mut len, PWSTR(path.as_mut_ptr()));
+      DeleteDC(hdc);
+      if !found.as_bool() {
+        return None;
+      }
+    }
+    Some(IccProfile::Path(PathBuf::from(util::wchar_ptr_to_string(
+      PCWSTR::from_raw(path.as_ptr()),
+    ))))
+  }
 }
@@ -0,0 +1,83 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Toast notifications, see `Notifier::show`.
+
+use std::sync::Arc;
+
+use windows::{
+  core::HSTRING,
+  Data::Xml::Dom::XmlDocument,
+  Foundation::TypedEventHandler,
+  Win32::{System::Com::CoTaskMemFree, UI::Shell::GetCurrentProcessExplicitAppUserModelID},
+  UI::Notifications::{ToastNotification, ToastNotificationManager, ToastNotifier},
+};
+
+use crate::{
+  error::ExternalError,
+  notification::{ActivationHandler, Notification, NotificationId},
+};
+
+/// Application user model id of PowerShell, which unpackaged applications without an id of their
+/// own commonly show their toasts with.
+const POWERSHELL_APP_ID: &str =
+  "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+
+pub struct Notifier {
+  on_activated: Arc<ActivationHandler>,
+}
+
+impl Notifier {
+  pub fn new<T>(
+    _target: &super::EventLoopWindowTarget<T>,
+    on_activated: ActivationHandler,
+  ) -> Self {
+    Self {
+      on_activated: Arc::new(on_activated),
+    }
+  }
+
+  pub fn show(&self, id: NotificationId, notification: &Notification) -> Result<(), ExternalError> {
+    let xml = format!(
+      "<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>",
+      escape(&notification.title),
+      escape(&notification.body),
+    );
+    let document = XmlDocument::new()?;
+    document.LoadXml(&HSTRING::from(xml))?;
+    let toast = ToastNotification::CreateToastNotification(&document)?;
+
+    // The handler is called on a thread of the thread pool, as long as the process is running.
+    let on_activated = self.on_activated.clone();
+    toast.Activated(&TypedEventHandler::new(move |_, _| {
+      on_activated(id);
+      Ok(())
+    }))?;
+    notifier()?.Show(&toast)?;
+    Ok(())
+  }
+}
+
+fn notifier() -> windows::core::Result<ToastNotifier> {
+  let app_id = unsafe {
+    match GetCurrentProcessExplicitAppUserModelID() {
+      Ok(app_id) if !app_id.is_null() => {
+        let owned = app_id.to_string().ok();
+        CoTaskMemFree(Some(app_id.0 as _));
+        owned
+      }
+      _ => None,
+    }
+  };
+  let app_id = app_id.unwrap_or_else(|| POWERSHELL_APP_ID.to_owned());
+  ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))
+}
+
+/// Escapes `text` for the content of an XML element.
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
@@ -0,0 +1,148 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Custom taskbar thumbnail and peek bitmaps.
+//!
+//! DWM only asks a window for these bitmaps, and therefore only tells it that a peek started, once
+//! the window has opted into iconic representations, so that is only enabled while a custom bitmap
+//! is set. DWM never reports the end of a peek, which is instead detected with a timer that checks
+//! whether the cursor has left the taskbar thumbnail.
+
+use std::{mem, ptr, slice};
+
+use windows::Win32::{
+  Foundation::{BOOL, HANDLE, HWND, POINT},
+  Graphics::{Dwm::*, Gdi::*},
+  UI::WindowsAndMessaging::*,
+};
+
+use crate::icon::RgbaIcon;
+
+/// Timer used to poll for the end of a peek.
+pub const PEEK_TIMER_ID: usize = 0x7065_656b;
+/// Interval of [`PEEK_TIMER_ID`], in milliseconds.
+pub const PEEK_TIMER_INTERVAL: u32 = 100;
+
+/// Makes DWM request thumbnail and peek bitmaps from the window instead of capturing it.
+pub fn set_iconic_representation(hwnd: HWND, enabled: bool) {
+  let value = BOOL::from(enabled);
+  unsafe {
+    for attribute in [DWMWA_FORCE_ICONIC_REPRESENTATION, DWMWA_HAS_ICONIC_BITMAP] {
+      let _ = DwmSetWindowAttribute(
+        hwnd,
+        attribute,
+        &value as *const BOOL as _,
+        mem::size_of::<BOOL>() as u32,
+      );
+    }
+    if enabled {
+      // Drop the bitmaps DWM may have cached from a previous call.
+      let _ = DwmInvalidateIconicBitmaps(hwnd);
+    }
+  }
+}
+
+/// Answers `WM_DWMSENDICONICTHUMBNAIL`, scaling the bitmap down to fit the requested size.
+pub fn set_thumbnail(hwnd: HWND, bitmap: &RgbaIcon, max_width: u32, max_height: u32) {
+  let scale = f64::min(
+    1.0,
+    f64::min(
+      max_width as f64 / bitmap.width as f64,
+      max_height as f64 / bitmap.height as f64,
+    ),
+  );
+  let width = ((bitmap.width as f64 * scale) as u32).max(1);
+  let height = ((bitmap.height as f64 * scale) as u32).max(1);
+
+  unsafe {
+    if let Some(hbitmap) = create_bitmap(bitmap, width, height) {
+      let _ = DwmSetIconicThumbnail(hwnd, hbitmap, 0);
+      DeleteObject(hbitmap);
+    }
+  }
+}
+
+/// Answers `WM_DWMSENDICONICLIVEPREVIEWBITMAP`.
+pub fn set_live_preview(hwnd: HWND, bitmap: &RgbaIcon) {
+  unsafe {
+    if let Some(hbitmap) = create_bitmap(bitmap, bitmap.width, bitmap.height) {
+      let _ = DwmSetIconicLivePreviewBitmap(hwnd, hbitmap, None, 0);
+      DeleteObject(hbitmap);
+    }
+  }
+}
+
+/// Whether the cursor is over a taskbar thumbnail, which is where a peek is held.
+pub fn is_cursor_over_thumbnail() -> bool {
+  unsafe {
+    let mut point = POINT::default();
+    if GetCursorPos(&mut point).is_err() {
+      return false;
+    }
+    let root = GetAncestor(WindowFromPoint(point), GA_ROOT);
+    let mut class_name = [0u16; 64];
+    let len = GetClassNameW(root, &mut class_name);
+    String::from_utf16_lossy(&class_name[..len as usize]) == "TaskListThumbnailWnd"
+  }
+}
+
+/// Creates a top-down 32bpp DIB of the given size with premultiplied BGRA pixels, as DWM expects.
+///
+/// Returns `None` rather than reading out of bounds if either size is empty or `bitmap` doesn't
+/// hold all of its pixels, since this runs in the window procedure.
+unsafe fn create_bitmap(bitmap: &RgbaIcon, width: u32, height: u32) -> Option<HBITMAP> {
+  let (src_width, src_height) = (bitmap.width as usize, bitmap.height as usize);
+  let src_len = src_width.checked_mul(src_height)?.checked_mul(4)?;
+  let (width, height) = (width as usize, height as usize);
+  let len = width.checked_mul(height)?.checked_mul(4)?;
+  if src_len == 0 || len == 0 || bitmap.rgba.len() < src_len {
+    return None;
+  }
+
+  let info = BITMAPINFO {
+    bmiHeader: BITMAPINFOHEADER {
+      biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+      biWidth: i32::try_from(width).ok()?,
+      biHeight: -i32::try_from(height).ok()?,
+      biPlanes: 1,
+      biBitCount: 32,
+      biCompression: BI_RGB.0,
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let mut bits = ptr::null_mut();
+  let hbitmap = CreateDIBSection(
+    HDC::default(),
+    &info,
+    DIB_RGB_COLORS,
+    &mut bits,
+    HANDLE::default(),
+    0,
+  )
+  .ok()?;
+
+  let pixels = slice::from_raw_parts_mut(bits as *mut u8, len);
+  for y in 0..height {
+    let src_y = y * src_height / height;
+    for x in 0..width {
+      let src_x = x * src_width / width;
+      let src = (src_y * src_width + src_x) * 4;
+      let dst = (y * width + x) * 4;
+      let [r, g, b, a] = [
+        bitmap.rgba[src],
+        bitmap.rgba[src + 1],
+        bitmap.rgba[src + 2],
+        bitmap.rgba[src + 3],
+      ];
+      let premultiply = |c: u8| (c as u32 * a as u32 / 255) as u8;
+      pixels[dst] = premultiply(b);
+      pixels[dst + 1] = premultiply(g);
+      pixels[dst + 2] = premultiply(r);
+      pixels[dst + 3] = a;
+    }
+  }
+
+  Some(hbitmap)
+}
@@ -13,7 +13,41 @@ use windows::Win32::{
   },
 };
 
-use crate::{event::ElementState, event_loop::DeviceEventFilter, platform_impl::platform::util};
+use crate::{
+  event::{DeviceId, ElementState},
+  event_loop::{DeviceEventFilter, DeviceEventKinds},
+  platform_impl::platform::util,
+};
+
+/// Device events `EventLoopWindowTarget` subscribed to.
+#[derive(Default)]
+pub struct DeviceEventSubscription {
+  pub filter: DeviceEventFilter,
+  pub kinds: DeviceEventKinds,
+  /// Devices to report the events of, all of them if `None`.
+  pub devices: Option<Vec<DeviceId>>,
+  /// Windows which get `WindowEvent::RawMouseMotion` while they're focused, which keeps the mice
+  /// registered whatever the filter and kinds are.
+  pub raw_mouse_motion: Vec<HWND>,
+  /// Windows which move the cursor by the raw mouse motion while they're focused, see
+  /// `AccelerationProfile::Flat`.
+  pub flat_pointer: Vec<HWND>,
+}
+
+impl DeviceEventSubscription {
+  pub fn accepts_device(&self, device_id: DeviceId) -> bool {
+    self
+      .devices
+      .as_ref()
+      .map_or(true, |devices| devices.contains(&device_id))
+  }
+
+  pub fn accepts(&self, device_id: DeviceId, kind: DeviceEventKinds) -> bool {
+    self.filter != DeviceEventFilter::Always
+      && self.kinds.contains(kind)
+      && self.accepts_device(device_id)
+  }
+}
 
 #[allow(dead_code)]
 pub fn get_raw_input_device_list() -> Option<Vec<RAWINPUTDEVICELIST>> {
@@ -125,37 +159,59 @@ pub fn register_raw_input_devices(devices: &[RAWINPUTDEVICE]) -> bool {
 }
 
 pub fn register_all_mice_and_keyboards_for_raw_input(
-  mut window_handle: HWND,
-  filter: DeviceEventFilter,
+  window_handle: HWND,
+  subscription: &DeviceEventSubscription,
 ) -> bool {
   // RIDEV_DEVNOTIFY: receive hotplug events
   // RIDEV_INPUTSINK: receive events even if we're not in the foreground
   // RIDEV_REMOVE: don't receive device events (requires NULL hwndTarget)
-  let flags = match filter {
-    DeviceEventFilter::Always => {
-      window_handle = HWND(0);
-      RIDEV_REMOVE
-    }
-    DeviceEventFilter::Unfocused => RIDEV_DEVNOTIFY,
-    DeviceEventFilter::Never => RIDEV_DEVNOTIFY | RIDEV_INPUTSINK,
+  let flags = match subscription.filter {
+    DeviceEventFilter::Always => None,
+    DeviceEventFilter::Unfocused => Some(RIDEV_DEVNOTIFY),
+    DeviceEventFilter::Never => Some(RIDEV_DEVNOTIFY | RIDEV_INPUTSINK),
+  };
+  // The raw mouse motion of the focused window only needs the input of the foreground.
+  let focused_mouse =
+    !subscription.raw_mouse_motion.is_empty() || !subscription.flat_pointer.is_empty();
+  let mouse_flags = match flags {
+    None if focused_mouse => Some(RAWINPUTDEVICE_FLAGS(0)),
+    flags => flags,
   };
 
-  let devices: [RAWINPUTDEVICE; 2] = [
-    RAWINPUTDEVICE {
-      usUsagePage: HID_USAGE_PAGE_GENERIC,
-      usUsage: HID_USAGE_GENERIC_MOUSE,
-      dwFlags: flags,
-      hwndTarget: window_handle,
-    },
-    RAWINPUTDEVICE {
-      usUsagePage: HID_USAGE_PAGE_GENERIC,
-      usUsage: HID_USAGE_GENERIC_KEYBOARD,
-      dwFlags: flags,
-      hwndTarget: window_handle,
-    },
-  ];
-
-  register_raw_input_devices(&devices)
+  let mut registered = true;
+  for (usage, flags, subscribed) in [
+    (
+      HID_USAGE_GENERIC_MOUSE,
+      mouse_flags,
+      subscription.kinds.intersects(DeviceEventKinds::MOUSE) || focused_mouse,
+    ),
+    (
+      HID_USAGE_GENERIC_KEYBOARD,
+      flags,
+      subscription.kinds.contains(DeviceEventKinds::KEYBOARD),
+    ),
+  ] {
+    match flags {
+      Some(flags) if subscribed => {
+        registered &= register_raw_input_devices(&[RAWINPUTDEVICE {
+          usUsagePage: HID_USAGE_PAGE_GENERIC,
+          usUsage: usage,
+          dwFlags: flags,
+          hwndTarget: window_handle,
+        }]);
+      }
+      _ => {
+        // Fails if the device wasn't registered, which is fine.
+        register_raw_input_devices(&[RAWINPUTDEVICE {
+          usUsagePage: HID_USAGE_PAGE_GENERIC,
+          usUsage: usage,
+          dwFlags: RIDEV_REMOVE,
+          hwndTarget: HWND(0),
+        }]);
+      }
+    }
+  }
+  registered
 }
 
 pub fn get_raw_input_data(handle: HRAWINPUT) -> Option<RAWINPUT> {
@@ -0,0 +1,169 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Child windows reserved for embedders, see `Window::create_surface_region`.
+//!
+//! The parent keeps the rect of each of its regions in its `WindowState` so that `WM_DPICHANGED`
+//! can re-apply the ones given in logical units.
+
+use std::{io, mem, sync::Arc};
+
+use parking_lot::Mutex;
+use windows::{
+  core::PCWSTR,
+  Win32::{
+    Foundation::{HINSTANCE, HWND},
+    Graphics::Gdi::HBRUSH,
+    UI::WindowsAndMessaging::*,
+  },
+};
+
+use crate::{
+  error::ExternalError,
+  platform_impl::platform::{util, window_state::WindowState, OsError},
+  window::Rect,
+};
+
+lazy_static! {
+  static ref SURFACE_REGION_CLASS: Vec<u16> = unsafe {
+    let class_name = util::encode_wide("Tao Surface Region");
+
+    let class = WNDCLASSEXW {
+      cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
+      style: Default::default(),
+      lpfnWndProc: Some(util::call_default_window_proc),
+      cbClsExtra: 0,
+      cbWndExtra: 0,
+      hInstance: HINSTANCE(util::get_instance_handle().0),
+      hIcon: HICON::default(),
+      hCursor: HCURSOR::default(),
+      // Embedders paint the whole surface, so there is nothing to erase.
+      hbrBackground: HBRUSH::default(),
+      lpszMenuName: PCWSTR::null(),
+      lpszClassName: PCWSTR::from_raw(class_name.as_ptr()),
+      hIconSm: HICON::default(),
+    };
+
+    RegisterClassExW(&class);
+
+    class_name
+  };
+}
+
+pub struct SurfaceRegion {
+  hwnd: HWND,
+  window_state: Arc<Mutex<WindowState>>,
+}
+
+impl SurfaceRegion {
+  pub fn new(
+    parent: HWND,
+    window_state: Arc<Mutex<WindowState>>,
+    rect: Rect,
+  ) -> Result<Self, ExternalError> {
+    let hwnd = unsafe {
+      CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        PCWSTR::from_raw(SURFACE_REGION_CLASS.as_ptr()),
+        PCWSTR::null(),
+        WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WS_CLIPCHILDREN,
+        0,
+        0,
+        0,
+        0,
+        parent,
+        HMENU::default(),
+        util::get_instance_handle(),
+        None,
+      )
+    };
+    if hwnd == HWND::default() {
+      return Err(ExternalError::Os(os_error!(OsError::IoError(
+        io::Error::last_os_error()
+      ))));
+    }
+
+    let scale_factor = {
+      let mut window_state = window_state.lock();
+      window_state.surface_regions.push((hwnd, rect));
+      window_state.scale_factor
+    };
+    place(hwnd, rect, scale_factor);
+
+    Ok(Self { hwnd, window_state })
+  }
+
+  pub fn set_rect(&self, rect: Rect) {
+    let scale_factor = {
+      let mut window_state = self.window_state.lock();
+      if let Some(region) = window_state
+        .surface_regions
+        .iter_mut()
+        .find(|(hwnd, _)| *hwnd == self.hwnd)
+      {
+        region.1 = rect;
+      }
+      window_state.scale_factor
+    };
+    place(self.hwnd, rect, scale_factor);
+  }
+
+  pub fn rect(&self) -> Rect {
+    let window_state = self.window_state.lock();
+    window_state
+      .surface_regions
+      .iter()
+      .find(|(hwnd, _)| *hwnd == self.hwnd)
+      .map(|(_, rect)| *rect)
+      .unwrap()
+  }
+
+  pub fn set_visible(&self, visible: bool) {
+    unsafe {
+      ShowWindow(self.hwnd, if visible { SW_SHOWNA } else { SW_HIDE });
+    }
+  }
+
+  #[cfg(feature = "rwh_06")]
+  pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+    let mut window_handle = rwh_06::Win32WindowHandle::new(unsafe {
+      // SAFETY: Handle will never be zero.
+      std::num::NonZeroIsize::new_unchecked(self.hwnd.0)
+    });
+    let hinstance = util::GetWindowLongPtrW(self.hwnd, GWLP_HINSTANCE);
+    window_handle.hinstance = std::num::NonZeroIsize::new(hinstance);
+    Ok(rwh_06::RawWindowHandle::Win32(window_handle))
+  }
+}
+
+impl Drop for SurfaceRegion {
+  fn drop(&mut self) {
+    self
+      .window_state
+      .lock()
+      .surface_regions
+      .retain(|(hwnd, _)| *hwnd != self.hwnd);
+    // Fails harmlessly if the parent, and with it the surface, is already destroyed.
+    unsafe {
+      let _ = DestroyWindow(self.hwnd);
+    }
+  }
+}
+
+/// Moves a region to `rect` at the given scale factor and below its siblings.
+pub fn place(hwnd: HWND, rect: Rect, scale_factor: f64) {
+  let position = rect.position.to_physical::<i32>(scale_factor);
+  let size = rect.size.to_physical::<u32>(scale_factor);
+  unsafe {
+    let _ = SetWindowPos(
+      hwnd,
+      HWND_BOTTOM,
+      position.x,
+      position.y,
+      size.width as i32,
+      size.height as i32,
+      SWP_NOACTIVATE,
+    );
+  }
+}
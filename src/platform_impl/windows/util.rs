@@ -10,27 +10,42 @@ use std::{
   os::windows::prelude::OsStrExt,
   slice,
   sync::atomic::{AtomicBool, Ordering},
+  time::Duration,
 };
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  window::CursorIcon,
+  keyboard::{KeyRepeatSettings, KeyboardLayout},
+  window::{CursorIcon, ImePurpose},
 };
 
 use windows::{
-  core::{HRESULT, PCSTR, PCWSTR},
+  core::{w, HRESULT, PCSTR, PCWSTR},
   Win32::{
     Foundation::{BOOL, FARPROC, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
-    Globalization::lstrlenW,
-    Graphics::Gdi::{ClientToScreen, InvalidateRgn, HMONITOR, HRGN},
-    System::LibraryLoader::*,
+    Globalization::{lstrlenW, GetUserDefaultLocaleName, LCIDToLocaleName},
+    Graphics::{
+      Dwm::{DwmExtendFrameIntoClientArea, DwmGetWindowAttribute, DWMWA_CLOAKED},
+      Gdi::{ClientToScreen, InvalidateRgn, HMONITOR, HRGN},
+    },
+    System::{
+      LibraryLoader::*,
+      Registry::{
+        RegGetValueW, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD, RRF_RT_REG_SZ,
+      },
+      WinRT::IInputPaneInterop,
+    },
     UI::{
+      Controls::MARGINS,
       HiDpi::*,
       Input::KeyboardAndMouse::*,
-      TextServices::HKL,
+      TextServices::{
+        InputScope, HKL, IS_DEFAULT, IS_EMAIL_SMTPEMAILADDRESS, IS_NUMBER, IS_PASSWORD, IS_URL,
+      },
       WindowsAndMessaging::{self as win32wm, *},
     },
   },
+  UI::ViewManagement::InputPane,
 };
 
 pub fn has_flag<T>(bitset: T, flag: T) -> bool
@@ -84,7 +99,12 @@ pub fn get_client_rect(hwnd: HWND) -> Result<RECT, io::Error> {
   Ok(rect)
 }
 
-pub fn adjust_size(hwnd: HWND, size: PhysicalSize<u32>, is_decorated: bool) -> PhysicalSize<u32> {
+pub fn adjust_size(
+  hwnd: HWND,
+  size: PhysicalSize<u32>,
+  is_decorated: bool,
+  title_bar_hidden: bool,
+) -> PhysicalSize<u32> {
   let (width, height): (u32, u32) = size.into();
   let rect = RECT {
     left: 0,
@@ -92,11 +112,17 @@ pub fn adjust_size(hwnd: HWND, size: PhysicalSize<u32>, is_decorated: bool) -> P
     top: 0,
     bottom: height as i32,
   };
-  let rect = adjust_window_rect(hwnd, rect, is_decorated).unwrap_or(rect);
+  let rect = adjust_window_rect(hwnd, rect, is_decorated, title_bar_hidden).unwrap_or(rect);
   PhysicalSize::new((rect.right - rect.left) as _, (rect.bottom - rect.top) as _)
 }
 
-pub(crate) fn set_inner_size_physical(window: HWND, x: u32, y: u32, is_decorated: bool) {
+pub(crate) fn set_inner_size_physical(
+  window: HWND,
+  x: u32,
+  y: u32,
+  is_decorated: bool,
+  title_bar_hidden: bool,
+) {
   unsafe {
     let rect = adjust_window_rect(
       window,
@@ -107,6 +133,7 @@ pub(crate) fn set_inner_size_physical(window: HWND, x: u32, y: u32, is_decorated
         right: x as i32,
       },
       is_decorated,
+      title_bar_hidden,
     )
     .expect("adjust_window_rect failed");
 
@@ -125,7 +152,12 @@ pub(crate) fn set_inner_size_physical(window: HWND, x: u32, y: u32, is_decorated
   }
 }
 
-pub fn adjust_window_rect(hwnd: HWND, rect: RECT, is_decorated: bool) -> Option<RECT> {
+pub fn adjust_window_rect(
+  hwnd: HWND,
+  rect: RECT,
+  is_decorated: bool,
+  title_bar_hidden: bool,
+) -> Option<RECT> {
   unsafe {
     let mut style = WINDOW_STYLE(GetWindowLongW(hwnd, GWL_STYLE) as u32);
     // if the window isn't decorated, remove `WS_SIZEBOX` and `WS_CAPTION` so
@@ -136,10 +168,61 @@ pub fn adjust_window_rect(hwnd: HWND, rect: RECT, is_decorated: bool) -> Option<
       style &= !WS_SIZEBOX;
     }
     let style_ex = WINDOW_EX_STYLE(GetWindowLongW(hwnd, GWL_EXSTYLE) as u32);
-    adjust_window_rect_with_styles(hwnd, style, style_ex, rect)
+    adjust_window_rect_with_styles(hwnd, style, style_ex, rect).map(|mut adjusted| {
+      // the client area covers the title bar, see `WM_NCCALCSIZE`
+      if title_bar_hidden {
+        adjusted.top = rect.top;
+      }
+      adjusted
+    })
   }
 }
 
+/// Returns the heights of the resize border and of the caption of `hwnd`, for its current DPI.
+pub fn title_bar_metrics(hwnd: HWND) -> (i32, i32) {
+  let (style, style_ex) = unsafe {
+    (
+      WINDOW_STYLE(GetWindowLongW(hwnd, GWL_STYLE) as u32),
+      WINDOW_EX_STYLE(GetWindowLongW(hwnd, GWL_EXSTYLE) as u32),
+    )
+  };
+  let rect =
+    adjust_window_rect_with_styles(hwnd, style, style_ex, RECT::default()).unwrap_or_default();
+  (rect.bottom, -rect.top - rect.bottom)
+}
+
+/// Extends the frame of `hwnd` over the client area covering its title bar, for the caption
+/// buttons to be drawn there.
+pub fn extend_frame_into_title_bar(hwnd: HWND) {
+  let (border, caption) = title_bar_metrics(hwnd);
+  let margins = MARGINS {
+    cyTopHeight: border + caption,
+    ..Default::default()
+  };
+  unsafe {
+    let _ = DwmExtendFrameIntoClientArea(hwnd, &margins);
+  }
+}
+
+/// Handles `WM_NCCALCSIZE` for a decorated window whose client area covers its title bar, with
+/// `default` calling the default procedure.
+pub unsafe fn calc_size_without_title_bar(
+  hwnd: HWND,
+  lparam: LPARAM,
+  default: impl FnOnce() -> LRESULT,
+) -> LRESULT {
+  let params = lparam.0 as *mut NCCALCSIZE_PARAMS;
+  let top = (*params).rgrc[0].top;
+  let result = default();
+  // maximized windows overflow the monitor by their resize border, which is left out
+  (*params).rgrc[0].top = if is_maximized(hwnd).unwrap_or(false) {
+    top + title_bar_metrics(hwnd).0
+  } else {
+    top
+  };
+  result
+}
+
 pub fn adjust_window_rect_with_styles(
   hwnd: HWND,
   style: WINDOW_STYLE,
@@ -189,6 +272,167 @@ pub fn set_cursor_clip(rect: Option<RECT>) -> windows::core::Result<()> {
   }
 }
 
+/// Shows or hides the touch keyboard through the `InputPane` of `hwnd`.
+pub unsafe fn set_touch_keyboard_visible(hwnd: HWND, visible: bool) -> windows::core::Result<()> {
+  let interop = windows::core::factory::<InputPane, IInputPaneInterop>()?;
+  let input_pane: InputPane = interop.GetForWindow(hwnd)?;
+  if visible {
+    input_pane.TryShow()?;
+  } else {
+    input_pane.TryHide()?;
+  }
+  Ok(())
+}
+
+/// Sets the input scope of `hwnd` matching `purpose`, which the touch keyboard follows.
+pub fn set_input_scope(hwnd: HWND, purpose: ImePurpose) -> windows::core::Result<()> {
+  let input_scope = match purpose {
+    ImePurpose::Password => IS_PASSWORD,
+    ImePurpose::Email => IS_EMAIL_SMTPEMAILADDRESS,
+    ImePurpose::Number => IS_NUMBER,
+    ImePurpose::Url => IS_URL,
+    _ => IS_DEFAULT,
+  };
+  match *SET_INPUT_SCOPE {
+    Some(set_input_scope) => unsafe { set_input_scope(hwnd, input_scope) }.ok(),
+    None => Ok(()),
+  }
+}
+
+/// BCP 47 language tag of the locale of the user's regional format settings.
+pub fn user_locale() -> Option<String> {
+  // `LOCALE_NAME_MAX_LENGTH`, including the terminating null.
+  let mut name = [0u16; 85];
+  let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+  if len > 1 {
+    Some(String::from_utf16_lossy(&name[..len as usize - 1]))
+  } else {
+    None
+  }
+}
+
+/// Returns the key repeat delay and interval set in the Control Panel.
+pub fn key_repeat_settings() -> Option<KeyRepeatSettings> {
+  let mut delay = 0u32;
+  let mut speed = 0u32;
+  unsafe {
+    SystemParametersInfoW(
+      SPI_GETKEYBOARDDELAY,
+      0,
+      Some(&mut delay as *mut _ as _),
+      SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    )
+    .ok()?;
+    SystemParametersInfoW(
+      SPI_GETKEYBOARDSPEED,
+      0,
+      Some(&mut speed as *mut _ as _),
+      SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    )
+    .ok()?;
+  }
+  // The delay goes from 0 (250 ms) to 3 (1 s), and the speed from 0 (about 2.5 repeats per
+  // second) to 31 (about 30 repeats per second).
+  let rate = 2.5 + f64::from(speed.min(31)) * 27.5 / 31.0;
+  Some(KeyRepeatSettings {
+    delay: Duration::from_millis(u64::from(delay.min(3) + 1) * 250),
+    interval: Duration::from_millis((1000.0 / rate).round() as u64),
+  })
+}
+
+/// Returns how many lines a notch of the wheel scrolls, or characters if `horizontal`, as set in
+/// the Control Panel.
+pub fn wheel_scroll_amount(horizontal: bool) -> u32 {
+  let (action, default) = if horizontal {
+    (SPI_GETWHEELSCROLLCHARS, 3)
+  } else {
+    (SPI_GETWHEELSCROLLLINES, 3)
+  };
+  let mut amount = 0u32;
+  let result = unsafe {
+    SystemParametersInfoW(
+      action,
+      0,
+      Some(&mut amount as *mut _ as _),
+      SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    )
+  };
+  match result {
+    // `WHEEL_PAGESCROLL` scrolls by pages, which don't have a size in pixels.
+    Ok(()) if amount != u32::MAX => amount,
+    _ => default,
+  }
+}
+
+/// Returns the active keyboard layout of the calling thread.
+pub fn keyboard_layout() -> KeyboardLayout {
+  let hkl = unsafe { GetKeyboardLayout(0) };
+  // `LOCALE_NAME_MAX_LENGTH`, including the terminating null.
+  let mut language = [0u16; 85];
+  let len = unsafe { LCIDToLocaleName(u32::from(LOWORD(hkl.0 as u32)), Some(&mut language), 0) };
+  let language = if len > 1 {
+    Some(String::from_utf16_lossy(&language[..len as usize - 1]))
+  } else {
+    None
+  };
+
+  // The name of the layout is only registered under its identifier.
+  let mut klid = [0u16; 9];
+  let name = if unsafe { GetKeyboardLayoutNameW(&mut klid) }.is_ok() {
+    let klid = String::from_utf16_lossy(&klid[..8]);
+    let subkey = encode_wide(format!(
+      "SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\{}",
+      klid
+    ));
+    let mut text = [0u16; 256];
+    let mut size = mem::size_of_val(&text) as u32;
+    let result = unsafe {
+      RegGetValueW(
+        HKEY_LOCAL_MACHINE,
+        PCWSTR::from_raw(subkey.as_ptr()),
+        w!("Layout Text"),
+        RRF_RT_REG_SZ,
+        None,
+        Some(text.as_mut_ptr() as _),
+        Some(&mut size),
+      )
+    };
+    match result {
+      Ok(()) => {
+        // `size` is in bytes and includes the terminating null.
+        let len = (size as usize / 2).saturating_sub(1);
+        String::from_utf16_lossy(&text[..len])
+      }
+      Err(_) => klid,
+    }
+  } else {
+    String::new()
+  };
+
+  KeyboardLayout { name, language }
+}
+
+/// Ratio between the user's cursor size accessibility setting and the default cursor size.
+pub fn cursor_size_scale() -> f64 {
+  let mut base_size = 0u32;
+  let mut len = mem::size_of::<u32>() as u32;
+  let result = unsafe {
+    RegGetValueW(
+      HKEY_CURRENT_USER,
+      w!("Control Panel\\Cursors"),
+      w!("CursorBaseSize"),
+      RRF_RT_REG_DWORD,
+      None,
+      Some(&mut base_size as *mut u32 as _),
+      Some(&mut len),
+    )
+  };
+  match result {
+    Ok(()) if base_size > 0 => base_size as f64 / 32.0,
+    _ => 1.0,
+  }
+}
+
 pub fn get_desktop_rect() -> RECT {
   unsafe {
     let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
@@ -210,6 +454,21 @@ pub fn is_visible(window: HWND) -> bool {
   unsafe { IsWindowVisible(window).as_bool() }
 }
 
+/// Whether the window can't be seen because it is minimized, hidden or cloaked by DWM, which
+/// happens when it is on another virtual desktop.
+pub fn is_occluded(window: HWND) -> bool {
+  let mut cloaked = 0u32;
+  unsafe {
+    let _ = DwmGetWindowAttribute(
+      window,
+      DWMWA_CLOAKED,
+      &mut cloaked as *mut u32 as _,
+      mem::size_of::<u32>() as u32,
+    );
+    IsIconic(window).as_bool() || !IsWindowVisible(window).as_bool() || cloaked != 0
+  }
+}
+
 pub fn is_maximized(window: HWND) -> windows::core::Result<bool> {
   let mut placement = WINDOWPLACEMENT {
     length: mem::size_of::<WINDOWPLACEMENT>() as u32,
@@ -280,6 +539,10 @@ pub type SetProcessDpiAwareness =
   unsafe extern "system" fn(value: PROCESS_DPI_AWARENESS) -> HRESULT;
 pub type SetProcessDpiAwarenessContext =
   unsafe extern "system" fn(value: DPI_AWARENESS_CONTEXT) -> BOOL;
+pub type SetThreadDpiAwarenessContext =
+  unsafe extern "system" fn(value: DPI_AWARENESS_CONTEXT) -> DPI_AWARENESS_CONTEXT;
+pub type SetThreadDpiHostingBehavior =
+  unsafe extern "system" fn(value: DPI_HOSTING_BEHAVIOR) -> DPI_HOSTING_BEHAVIOR;
 pub type GetDpiForWindow = unsafe extern "system" fn(hwnd: HWND) -> u32;
 pub type GetDpiForMonitor = unsafe extern "system" fn(
   hmonitor: HMONITOR,
@@ -288,6 +551,7 @@ pub type GetDpiForMonitor = unsafe extern "system" fn(
   dpi_y: *mut u32,
 ) -> HRESULT;
 pub type EnableNonClientDpiScaling = unsafe extern "system" fn(hwnd: HWND) -> BOOL;
+pub type SetInputScope = unsafe extern "system" fn(hwnd: HWND, inputscope: InputScope) -> HRESULT;
 pub type AdjustWindowRectExForDpi = unsafe extern "system" fn(
   rect: *mut RECT,
   dwStyle: WINDOW_STYLE,
@@ -307,10 +571,15 @@ lazy_static! {
     get_function!("user32.dll", EnableNonClientDpiScaling);
   pub static ref SET_PROCESS_DPI_AWARENESS_CONTEXT: Option<SetProcessDpiAwarenessContext> =
     get_function!("user32.dll", SetProcessDpiAwarenessContext);
+  pub static ref SET_THREAD_DPI_AWARENESS_CONTEXT: Option<SetThreadDpiAwarenessContext> =
+    get_function!("user32.dll", SetThreadDpiAwarenessContext);
+  pub static ref SET_THREAD_DPI_HOSTING_BEHAVIOR: Option<SetThreadDpiHostingBehavior> =
+    get_function!("user32.dll", SetThreadDpiHostingBehavior);
   pub static ref SET_PROCESS_DPI_AWARENESS: Option<SetProcessDpiAwareness> =
     get_function!("shcore.dll", SetProcessDpiAwareness);
   pub static ref SET_PROCESS_DPI_AWARE: Option<SetProcessDPIAware> =
     get_function!("user32.dll", SetProcessDPIAware);
+  pub static ref SET_INPUT_SCOPE: Option<SetInputScope> = get_function!("msctf.dll", SetInputScope);
 }
 
 #[allow(non_snake_case)]
@@ -11,7 +11,11 @@ use std::{
   ffi::OsStr,
   io, mem,
   os::windows::ffi::OsStrExt,
-  sync::Arc,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Weak,
+  },
+  time::{Duration, Instant},
 };
 
 use crossbeam_channel as channel;
@@ -21,37 +25,64 @@ use windows::{
     Foundation::{
       self as win32f, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, POINT, POINTS, RECT, WPARAM,
     },
+    Globalization::HIMC,
     Graphics::{
       Dwm::{DwmEnableBlurBehindWindow, DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND},
       Gdi::*,
     },
-    System::{Com::*, LibraryLoader::*, Ole::*},
+    Storage::EnhancedStorage::PKEY_AppUserModel_ID,
+    System::{
+      Com::{
+        StructuredStorage::{PropVariantClear, PROPVARIANT},
+        *,
+      },
+      Ole::*,
+      Threading::INFINITE,
+      Variant::VT_LPWSTR,
+    },
     UI::{
       Input::{Ime::*, KeyboardAndMouse::*, Touch::*},
-      Shell::{ITaskbarList4 as ITaskbarList, TaskbarList, *},
+      Shell::{
+        ITaskbarList4 as ITaskbarList,
+        PropertiesSystem::{IPropertyStore, SHGetPropertyStoreForWindow},
+        TaskbarList, *,
+      },
       WindowsAndMessaging::{self as win32wm, *},
     },
   },
 };
 
 use crate::{
-  dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+  dpi::{PhysicalPosition, PhysicalSize, Position, Rounding, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
-  icon::Icon,
+  event::{Event, WindowEvent},
+  event_loop::ControlFlow,
+  global_shortcut::{Accelerator, AcceleratorId},
+  icon::{CustomCursor, Icon, RgbaIcon},
   monitor::MonitorHandle as RootMonitorHandle,
+  platform::windows::{ComInit, TitleBarColors},
   platform_impl::platform::{
-    dark_mode::try_window_theme,
-    dpi::{dpi_to_scale_factor, hwnd_dpi},
+    accelerator,
+    dark_mode::{self, try_window_theme},
+    dpi::{dpi_to_scale_factor, hwnd_dpi, ThreadDpiContext},
     drop_handler::FileDropHandler,
-    event_loop::{self, EventLoopWindowTarget, DESTROY_MSG_ID},
+    event_loop::{
+      self, EventLoopWindowTarget, DESTROY_MSG_ID, SET_DROP_TARGET_MSG_ID, SET_FLAT_POINTER_MSG_ID,
+      SET_RAW_MOUSE_MOTION_MSG_ID, SET_THEME_MSG_ID, SET_TITLE_MSG_ID,
+    },
+    frame_pacing::{self, JankDetector},
     icon::{self, IconType},
-    monitor, util,
-    window_state::{CursorFlags, SavedWindow, WindowFlags, WindowState},
+    keyboard_grab, monitor, peek,
+    surface_region::SurfaceRegion,
+    util,
+    window_state::{CursorFlags, CustomStyles, SavedWindow, WindowFlags, WindowState},
     OsError, Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
   },
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ProgressState, ResizeDirection, Theme,
-    UserAttentionType, WindowAttributes, WindowSizeConstraints,
+    AccelerationProfile, AttributeDowngrade, AttributesReport, CursorGrabMode, CursorIcon,
+    DropPolicy, FocusPolicy, Fullscreen, ImePurpose, ProgressBarState, ProgressState, Rect,
+    ResizeDirection, SystemGestures, Theme, TitleBarStyle, UserAttentionType, WindowAttributes,
+    WindowId as RootWindowId, WindowSizeConstraints,
   },
 };
 
@@ -67,8 +98,24 @@ pub struct Window {
 
   // The events loop proxy.
   thread_executor: event_loop::EventLoopThreadExecutor,
+
+  /// The options which couldn't be honored when creating the window.
+  attributes_report: AttributesReport,
+
+  /// The hooks added with `add_message_hook`, shared with the window procedure.
+  message_hooks: MessageHooks,
+
+  /// Set by `detach`, the native window then outlives the `Window`.
+  detached: bool,
 }
 
+/// A hook added with `WindowExtWindows::add_message_hook`, called with the window handle,
+/// message, `WPARAM` and `LPARAM` of each message the window receives.
+pub(crate) type MessageHook = Box<dyn FnMut(isize, u32, usize, isize) -> Option<isize> + Send>;
+
+/// The message hooks of a window by id, in the order they were added.
+pub(crate) type MessageHooks = Arc<Mutex<Vec<(u64, Arc<Mutex<MessageHook>>)>>>;
+
 impl Window {
   pub fn new<T: 'static>(
     event_loop: &EventLoopWindowTarget<T>,
@@ -81,27 +128,36 @@ impl Window {
     // done. you owe me -- ossi
     unsafe {
       let drag_and_drop = pl_attr.drag_and_drop;
+      let com_init = event_loop.com_init;
+      if drag_and_drop && com_init == ComInit::MultiThreaded {
+        warn!("Drag and drop is unsupported in the multi-threaded COM apartment");
+      }
       init(w_attr, pl_attr, event_loop).map(|win| {
-        let file_drop_handler = if drag_and_drop {
+        let file_drop_handler = if drag_and_drop && com_init != ComInit::MultiThreaded {
           // It is ok if the initialize result is `S_FALSE` because it might happen that
-          // multiple windows are created on the same thread.
-          if let Err(error) = OleInitialize(None) {
-            match error.code() {
-              win32f::OLE_E_WRONGCOMPOBJ => {
-                panic!("OleInitialize failed! Result was: `OLE_E_WRONGCOMPOBJ`")
-              }
-              win32f::RPC_E_CHANGED_MODE => panic!(
-                "OleInitialize failed! Result was: `RPC_E_CHANGED_MODE`. \
-                Make sure other crates are not using multithreaded COM library \
-                on the same thread or disable drag and drop support."
-              ),
-              _ => (),
-            };
+          // multiple windows are created on the same thread. With `ComInit::None` the
+          // application initialized OLE itself.
+          if com_init == ComInit::ApartmentThreaded {
+            if let Err(error) = OleInitialize(None) {
+              match error.code() {
+                win32f::OLE_E_WRONGCOMPOBJ => {
+                  panic!("OleInitialize failed! Result was: `OLE_E_WRONGCOMPOBJ`")
+                }
+                win32f::RPC_E_CHANGED_MODE => panic!(
+                  "OleInitialize failed! Result was: `RPC_E_CHANGED_MODE`. \
+                  Make sure other crates are not using multithreaded COM library \
+                  on the same thread or disable drag and drop support."
+                ),
+                _ => (),
+              };
+            }
           }
 
           let file_drop_runner = event_loop.runner_shared.clone();
+          let drop_window_state = win.window_state.clone();
           let file_drop_handler: IDropTarget = FileDropHandler::new(
             win.window.0,
+            Box::new(move |filenames| drop_window_state.lock().drop_policy.accepts(filenames)),
             Box::new(move |event| {
               if let Ok(e) = event.map_nonuser_event() {
                 file_drop_runner.send_event(e)
@@ -110,8 +166,13 @@ impl Window {
           )
           .into();
 
-          assert!(RegisterDragDrop(win.window.0, &file_drop_handler).is_ok());
-          Some(file_drop_handler)
+          match RegisterDragDrop(win.window.0, &file_drop_handler) {
+            Ok(()) => Some(file_drop_handler),
+            Err(error) => {
+              warn!("RegisterDragDrop failed: {}", error);
+              None
+            }
+          }
         } else {
           None
         };
@@ -119,10 +180,11 @@ impl Window {
         let subclass_input = event_loop::SubclassInput {
           window_state: win.window_state.clone(),
           event_loop_runner: event_loop.runner_shared.clone(),
-          _file_drop_handler: file_drop_handler,
+          file_drop_handler,
           subclass_removed: Cell::new(false),
           recurse_depth: Cell::new(0),
           event_loop_preferred_theme: event_loop.preferred_theme,
+          message_hooks: win.message_hooks.clone(),
         };
 
         event_loop::subclass_window(win.window.0, subclass_input);
@@ -132,13 +194,22 @@ impl Window {
   }
 
   pub fn set_title(&self, text: &str) {
-    let text = util::encode_wide(text);
-    unsafe {
-      let _ = SetWindowTextW(self.window.0, PCWSTR::from_raw(text.as_ptr()));
+    let pending = self
+      .window_state
+      .lock()
+      .pending_title
+      .replace(text.to_string());
+    if pending.is_none() {
+      unsafe {
+        let _ = PostMessageW(self.window.0, *SET_TITLE_MSG_ID, WPARAM(0), LPARAM(0));
+      }
     }
   }
 
   pub fn title(&self) -> String {
+    if let Some(title) = &self.window_state.lock().pending_title {
+      return title.clone();
+    }
     let len = unsafe { GetWindowTextLengthW(self.window.0) };
     let mut buf = vec![0; (len + 1) as usize];
     unsafe { GetWindowTextW(self.window.0, &mut buf) };
@@ -175,13 +246,156 @@ impl Window {
     window_state.has_active_focus()
   }
 
+  #[inline]
+  pub fn raise(&self) {
+    unsafe {
+      let _ = SetWindowPos(
+        self.hwnd(),
+        HWND_TOP,
+        0,
+        0,
+        0,
+        0,
+        SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+      );
+    }
+  }
+
+  #[inline]
+  pub fn focus_without_raise(&self) {
+    let window = self.hwnd();
+    unsafe {
+      // Activating a window always brings it to front, so put it back where it was afterwards.
+      let previous = GetWindow(window, GW_HWNDPREV);
+      self.set_focus();
+      if previous != HWND::default() {
+        let _ = SetWindowPos(
+          window,
+          previous,
+          0,
+          0,
+          0,
+          0,
+          SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+      }
+    }
+  }
+
+  #[inline]
+  pub fn set_focus_policy(&self, policy: FocusPolicy) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || {
+      WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+        f.set(WindowFlags::NO_ACTIVATE, policy == FocusPolicy::Never)
+      });
+    });
+  }
+
+  #[inline]
+  pub fn set_drop_policy(&self, policy: DropPolicy) {
+    let forward = policy == DropPolicy::Forward;
+    let was_forward = {
+      let mut window_state = self.window_state.lock();
+      mem::replace(&mut window_state.drop_policy, policy) == DropPolicy::Forward
+    };
+    if forward != was_forward {
+      unsafe {
+        SendMessageW(
+          self.window.0,
+          *SET_DROP_TARGET_MSG_ID,
+          WPARAM(!forward as _),
+          LPARAM(0),
+        );
+      }
+    }
+  }
+
   #[inline]
   pub fn request_redraw(&self) {
+    let frame_pacing = {
+      let mut window_state = self.window_state.lock();
+      if let Some(jank_detector) = window_state.jank_detector.as_mut() {
+        jank_detector.request_redraw();
+      }
+      window_state.frame_pacing
+    };
+    if frame_pacing {
+      frame_pacing::request_redraw(self.window.0);
+      return;
+    }
     unsafe {
       RedrawWindow(self.window.0, None, HRGN::default(), RDW_INTERNALPAINT);
     }
   }
 
+  #[inline]
+  pub fn set_frame_pacing(&self, enabled: bool) {
+    self.window_state.lock().frame_pacing = enabled;
+  }
+
+  #[inline]
+  pub fn set_jank_detection(&self, enabled: bool) {
+    let mut window_state = self.window_state.lock();
+    if enabled != window_state.jank_detector.is_some() {
+      window_state.jank_detector = enabled.then(JankDetector::default);
+    }
+  }
+
+  pub fn applied_attributes_report(&self) -> AttributesReport {
+    self.attributes_report.clone()
+  }
+
+  pub fn run_modal(&self, handler: &mut dyn FnMut(&mut ControlFlow)) {
+    let mut control_flow = ControlFlow::Wait;
+    loop {
+      handler(&mut control_flow);
+      let timeout = match control_flow {
+        ControlFlow::ExitWithCode(_) => break,
+        ControlFlow::Poll => 0,
+        ControlFlow::Wait => INFINITE,
+        ControlFlow::WaitUntil(resume) => {
+          event_loop::dur2timeout(resume.saturating_duration_since(Instant::now()))
+        }
+      };
+      unsafe {
+        MsgWaitForMultipleObjectsEx(None, timeout, QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+        let mut msg = MSG::default();
+        while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+          // Leave the quit message to the event loop.
+          if msg.message == WM_QUIT {
+            PostQuitMessage(msg.wParam.0 as i32);
+            return;
+          }
+          TranslateMessage(&msg);
+          DispatchMessageW(&msg);
+        }
+      }
+    }
+  }
+
+  #[inline]
+  pub fn close(&self) {
+    unsafe {
+      let _ = PostMessageW(self.window.0, WM_CLOSE, WPARAM(0), LPARAM(0));
+    }
+  }
+
+  #[inline]
+  pub fn destroy(&self) {
+    self.close_token().proceed()
+  }
+
+  #[inline]
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken {
+      window: self.window.clone(),
+      window_state: Arc::downgrade(&self.window_state),
+      thread_executor: self.thread_executor.clone(),
+    }
+  }
+
   #[inline]
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
     unsafe { util::get_window_rect(self.window.0) }
@@ -251,14 +465,16 @@ impl Window {
   #[inline]
   pub fn set_inner_size(&self, size: Size) {
     let scale_factor = self.scale_factor();
-    let (width, height) = size.to_physical::<u32>(scale_factor).into();
+    // Rounding up keeps the logical size covered, see `Rounding`.
+    let (width, height) = size
+      .to_physical_with::<u32>(scale_factor, Rounding::Ceil)
+      .into();
 
     let window_state = Arc::clone(&self.window_state);
 
-    let is_decorated = window_state
-      .lock()
-      .window_flags
-      .contains(WindowFlags::MARKER_DECORATIONS);
+    let window_flags = window_state.lock().window_flags;
+    let is_decorated = window_flags.contains(WindowFlags::MARKER_DECORATIONS);
+    let title_bar_hidden = window_flags.contains(WindowFlags::MARKER_TITLE_BAR_HIDDEN);
 
     let window = self.window.clone();
     self.thread_executor.execute_in_thread(move || {
@@ -267,7 +483,7 @@ impl Window {
       });
     });
 
-    util::set_inner_size_physical(self.window.0, width, height, is_decorated);
+    util::set_inner_size_physical(self.window.0, width, height, is_decorated, title_bar_hidden);
   }
 
   #[inline]
@@ -409,7 +625,10 @@ impl Window {
 
   #[inline]
   pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-    self.window_state.lock().mouse.cursor = cursor;
+    let mut window_state = self.window_state.lock();
+    window_state.mouse.cursor = cursor;
+    window_state.mouse.custom_cursor = None;
+    drop(window_state);
     self.thread_executor.execute_in_thread(move || unsafe {
       let cursor = LoadCursorW(HMODULE::default(), cursor.to_windows_cursor()).unwrap_or_default();
       SetCursor(cursor);
@@ -417,7 +636,24 @@ impl Window {
   }
 
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+  pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+    let window = self.window.0;
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || unsafe {
+      if let Some(hcursor) = window_state.lock().set_custom_cursor(cursor.clone()) {
+        SetCursor(hcursor);
+      }
+      match cursor.frame_delay(0) {
+        Some(delay) => set_cursor_frame_timer(window, delay),
+        None => {
+          let _ = KillTimer(window, icon::CURSOR_FRAME_TIMER_ID);
+        }
+      }
+    });
+  }
+
+  #[inline]
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
     let window = self.window.clone();
     let window_state = Arc::clone(&self.window_state);
     let (tx, rx) = channel::unbounded();
@@ -426,13 +662,90 @@ impl Window {
       let result = window_state
         .lock()
         .mouse
-        .set_cursor_flags(window.0, |f| f.set(CursorFlags::GRABBED, grab))
+        .set_cursor_flags(window.0, |f| {
+          f.set(CursorFlags::GRABBED, mode == CursorGrabMode::Confined);
+          f.set(CursorFlags::LOCKED, mode == CursorGrabMode::Locked);
+        })
+        .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e))));
+      let _ = tx.send(result);
+    });
+    rx.recv().unwrap()?;
+    self.refresh_flat_pointer()
+  }
+
+  #[inline]
+  pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), ExternalError> {
+    self.window_state.lock().raw_mouse_motion = enabled;
+    unsafe {
+      PostMessageW(
+        self.thread_executor.target_window(),
+        *SET_RAW_MOUSE_MOTION_MSG_ID,
+        WPARAM(self.window.0 .0 as _),
+        LPARAM(enabled as _),
+      )
+    }
+    .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))))
+  }
+
+  #[inline]
+  pub fn set_pointer_acceleration(
+    &self,
+    profile: Option<AccelerationProfile>,
+  ) -> Result<(), ExternalError> {
+    self.window_state.lock().mouse.pointer_acceleration = profile;
+    self.refresh_flat_pointer()
+  }
+
+  /// Tells the thread event target whether to move the cursor by the raw mouse motion of the
+  /// window, see `SET_FLAT_POINTER_MSG_ID`.
+  fn refresh_flat_pointer(&self) -> Result<(), ExternalError> {
+    let flat = self.window_state.lock().mouse.flat_pointer();
+    unsafe {
+      PostMessageW(
+        self.thread_executor.target_window(),
+        *SET_FLAT_POINTER_MSG_ID,
+        WPARAM(self.window.0 .0 as _),
+        LPARAM(flat as _),
+      )
+    }
+    .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))))
+  }
+
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    let window = self.window.clone();
+    let (tx, rx) = channel::unbounded();
+
+    self.thread_executor.execute_in_thread(move || {
+      let result = keyboard_grab::set_keyboard_grab(window.0, grab)
         .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e))));
       let _ = tx.send(result);
     });
     rx.recv().unwrap()
   }
 
+  #[inline]
+  pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || {
+      if let Err(e) = util::set_input_scope(window.0, purpose) {
+        warn!("Failed to set the input scope of the window: {}", e);
+      }
+    });
+  }
+
+  pub fn set_touch_keyboard_visible(&self, visible: bool) -> Result<(), ExternalError> {
+    let window = self.window.clone();
+    let (tx, rx) = channel::unbounded();
+
+    self.thread_executor.execute_in_thread(move || {
+      let result = unsafe { util::set_touch_keyboard_visible(window.0, visible) }
+        .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))));
+      let _ = tx.send(result);
+    });
+    rx.recv().unwrap()
+  }
+
   #[inline]
   pub fn set_cursor_visible(&self, visible: bool) {
     let window = self.window.clone();
@@ -460,6 +773,13 @@ impl Window {
     self.window_state.lock().scale_factor
   }
 
+  #[inline]
+  pub fn set_scale_factor_override(&self, scale_factor: Option<f64>) {
+    let mut window_state = self.window_state.lock();
+    window_state.scale_factor_override = scale_factor;
+    window_state.scale_factor = scale_factor.unwrap_or(window_state.dpi_scale_factor);
+  }
+
   #[inline]
   pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
     let scale_factor = self.scale_factor();
@@ -577,6 +897,14 @@ impl Window {
     window_state.window_flags.contains(WindowFlags::RESIZABLE)
   }
 
+  #[inline]
+  pub fn has_redirection_bitmap(&self) -> bool {
+    let window_state = self.window_state.lock();
+    !window_state
+      .window_flags
+      .contains(WindowFlags::NO_BACK_BUFFER)
+  }
+
   #[inline]
   pub fn is_minimizable(&self) -> bool {
     let window_state = self.window_state.lock();
@@ -642,7 +970,10 @@ impl Window {
       // fullscreen
       match (&old_fullscreen, &fullscreen) {
         (&None, &Some(Fullscreen::Exclusive(ref video_mode)))
-        | (&Some(Fullscreen::Borderless(_)), &Some(Fullscreen::Exclusive(ref video_mode)))
+        | (
+          &Some(Fullscreen::Borderless(_) | Fullscreen::Spanning(_)),
+          &Some(Fullscreen::Exclusive(ref video_mode)),
+        )
         | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Exclusive(ref video_mode))) => {
           let monitor = video_mode.monitor();
 
@@ -654,12 +985,10 @@ impl Window {
           // string, so add it
           display_name.push(0);
 
-          let native_video_mode = video_mode.video_mode.native_video_mode;
-
           let res = unsafe {
             ChangeDisplaySettingsExW(
               PCWSTR::from_raw(display_name.as_ptr()),
-              Some(&native_video_mode),
+              Some(&*video_mode.video_mode.native_video_mode),
               HWND::default(),
               CDS_FULLSCREEN,
               None,
@@ -673,7 +1002,10 @@ impl Window {
           assert_eq!(res, DISP_CHANGE_SUCCESSFUL);
         }
         (&Some(Fullscreen::Exclusive(_)), &None)
-        | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Borderless(_))) => {
+        | (
+          &Some(Fullscreen::Exclusive(_)),
+          &Some(Fullscreen::Borderless(_) | Fullscreen::Spanning(_)),
+        ) => {
           let res = unsafe {
             ChangeDisplaySettingsExW(PCWSTR::null(), None, HWND::default(), CDS_FULLSCREEN, None)
           };
@@ -708,7 +1040,10 @@ impl Window {
         );
         f.set(
           WindowFlags::MARKER_BORDERLESS_FULLSCREEN,
-          matches!(fullscreen, Some(Fullscreen::Borderless(_))),
+          matches!(
+            fullscreen,
+            Some(Fullscreen::Borderless(_) | Fullscreen::Spanning(_))
+          ),
         );
       });
 
@@ -727,13 +1062,25 @@ impl Window {
           let monitor = match &fullscreen {
             Fullscreen::Exclusive(video_mode) => video_mode.monitor(),
             Fullscreen::Borderless(Some(monitor)) => monitor.clone(),
-            Fullscreen::Borderless(None) => RootMonitorHandle {
+            Fullscreen::Borderless(None) | Fullscreen::Spanning(_) => RootMonitorHandle {
               inner: monitor::current_monitor(window.0),
             },
           };
 
-          let position: (i32, i32) = monitor.position().into();
-          let size: (u32, u32) = monitor.size().into();
+          let spanning_rect = match &fullscreen {
+            Fullscreen::Spanning(monitors) => monitor::spanning_rect(monitors),
+            _ => None,
+          };
+          let (position, size): ((i32, i32), (u32, u32)) = match spanning_rect {
+            Some(rect) => (
+              (rect.left, rect.top),
+              (
+                (rect.right - rect.left) as u32,
+                (rect.bottom - rect.top) as u32,
+              ),
+            ),
+            None => (monitor.position().into(), monitor.size().into()),
+          };
 
           unsafe {
             let _ = SetWindowPos(
@@ -813,6 +1160,65 @@ impl Window {
     });
   }
 
+  #[inline]
+  pub fn style(&self) -> u32 {
+    unsafe { GetWindowLongW(self.hwnd(), GWL_STYLE) as u32 }
+  }
+
+  #[inline]
+  pub fn ex_style(&self) -> u32 {
+    unsafe { GetWindowLongW(self.hwnd(), GWL_EXSTYLE) as u32 }
+  }
+
+  pub fn set_style(&self, style: u32, mask: u32) {
+    self.set_custom_styles(move |styles| styles.set_style(style, mask))
+  }
+
+  pub fn set_ex_style(&self, ex_style: u32, mask: u32) {
+    self.set_custom_styles(move |styles| styles.set_ex_style(ex_style, mask))
+  }
+
+  fn set_custom_styles<F>(&self, f: F)
+  where
+    F: Fn(&mut CustomStyles) + Send + 'static,
+  {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+
+    self.thread_executor.execute_in_thread(move || {
+      WindowState::set_custom_styles(window_state.lock(), window.0, &f);
+    });
+  }
+
+  pub fn add_accelerator(
+    &self,
+    id: AcceleratorId,
+    accelerator: Accelerator,
+  ) -> Result<(), ExternalError> {
+    accelerator::add(self.window.0, id, accelerator)
+  }
+
+  pub fn remove_accelerator(&self, id: AcceleratorId) {
+    accelerator::remove(self.window.0, id)
+  }
+
+  pub fn add_message_hook(&self, hook: MessageHook) -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    self
+      .message_hooks
+      .lock()
+      .push((id, Arc::new(Mutex::new(hook))));
+    id
+  }
+
+  pub fn remove_message_hook(&self, id: u64) {
+    self
+      .message_hooks
+      .lock()
+      .retain(|(hook_id, _)| *hook_id != id);
+  }
+
   #[inline]
   pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
     Some(RootMonitorHandle {
@@ -844,25 +1250,175 @@ impl Window {
     self.window_state.lock().taskbar_icon = taskbar_icon;
   }
 
-  pub(crate) fn set_ime_position_physical(&self, x: i32, y: i32) {
+  pub fn set_window_icon_frames(&self, frames: Vec<Icon>, interval: Duration) {
+    let window = self.window.0;
+    let first_frame = frames.first().cloned();
+    let (window_icon, taskbar_icon) = {
+      let mut window_state = self.window_state.lock();
+      window_state.icon_frames = frames;
+      window_state.icon_frame = 0;
+      (
+        window_state.window_icon.clone(),
+        window_state.taskbar_icon.clone(),
+      )
+    };
+
+    if let Some(frame) = first_frame {
+      frame.inner.set_for_window(window, IconType::Small);
+      frame.inner.set_for_window(window, IconType::Big);
+      let interval = interval
+        .as_millis()
+        .clamp(USER_TIMER_MINIMUM as u128, USER_TIMER_MAXIMUM as u128);
+      unsafe { SetTimer(window, icon::ICON_FRAME_TIMER_ID, interval as u32, None) };
+    } else {
+      unsafe {
+        let _ = KillTimer(window, icon::ICON_FRAME_TIMER_ID);
+      }
+      match window_icon {
+        Some(icon) => icon.inner.set_for_window(window, IconType::Small),
+        None => icon::unset_for_window(window, IconType::Small),
+      }
+      match taskbar_icon {
+        Some(icon) => icon.inner.set_for_window(window, IconType::Big),
+        None => icon::unset_for_window(window, IconType::Big),
+      }
+    }
+  }
+
+  pub fn set_overlay_icon(&self, icon: Option<Icon>, description: &str) {
+    unsafe { set_overlay_icon(self.window.0, icon.as_ref(), description) };
+    // Kept to restore the overlay when the taskbar is recreated.
+    self.window_state.lock().overlay_icon = icon.map(|icon| (icon, description.to_string()));
+  }
+
+  pub fn create_surface_region(&self, rect: Rect) -> Result<SurfaceRegion, ExternalError> {
+    SurfaceRegion::new(self.hwnd(), self.window_state.clone(), rect)
+  }
+
+  #[inline]
+  pub fn sendable_handle(&self) -> Result<u64, ExternalError> {
+    Ok(self.window.0 .0 as u64)
+  }
+
+  /// Releases the `Window` without destroying the native window, which the event loop keeps
+  /// driving until it's closed, without its keyboard input and raw mouse motion.
+  pub fn detach(mut self) {
+    self.release();
+    self.detached = true;
+  }
+
+  /// Drops what the process keeps for the window besides the native window, shared by `drop` and
+  /// `detach`.
+  fn release(&self) {
+    KEY_EVENT_BUILDERS.lock().remove(&self.id());
+    let window_state = self.window_state.lock();
+    for (enabled, msg) in [
+      (window_state.raw_mouse_motion, *SET_RAW_MOUSE_MOTION_MSG_ID),
+      (window_state.mouse.flat_pointer(), *SET_FLAT_POINTER_MSG_ID),
+    ] {
+      if enabled {
+        unsafe {
+          let _ = PostMessageW(
+            self.thread_executor.target_window(),
+            msg,
+            WPARAM(self.window.0 .0 as _),
+            LPARAM(0),
+          );
+        }
+      }
+    }
+  }
+
+  pub fn adopt_from_handle(&self, handle: u64) -> Result<(), ExternalError> {
+    let parent = WindowWrapper(HWND(handle as isize));
+    if !unsafe { IsWindow(parent.0) }.as_bool() {
+      return Err(ExternalError::Os(os_error!(OsError::IoError(
+        io::Error::new(io::ErrorKind::InvalidInput, "the handle isn't a window")
+      ))));
+    }
+
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || {
+      // `SetParent` only turns the window into a real child if its style already says so.
+      WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+        f.set(WindowFlags::CHILD, true);
+        f.set(WindowFlags::POPUP, false);
+        f.set(WindowFlags::ON_TASKBAR, false);
+      });
+      unsafe {
+        SetParent(window.0, parent.0);
+        let _ = SetWindowPos(
+          window.0,
+          HWND::default(),
+          0,
+          0,
+          0,
+          0,
+          SWP_NOZORDER | SWP_NOSIZE | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+        );
+      }
+    });
+    Ok(())
+  }
+
+  pub(crate) fn set_peek_bitmap(&self, bitmap: Option<RgbaIcon>) {
+    let enabled = bitmap.is_some();
+    self.window_state.lock().peek_bitmap = bitmap;
+    peek::set_iconic_representation(self.hwnd(), enabled);
+  }
+
+  pub(crate) fn set_ime_cursor_area_physical(&self, x: i32, y: i32, width: i32, height: i32) {
     if unsafe { GetSystemMetrics(SM_IMMENABLED) } != 0 {
       let composition_form = COMPOSITIONFORM {
         dwStyle: CFS_POINT,
         ptCurrentPos: POINT { x, y },
         rcArea: RECT::default(),
       };
+      // Keeps the candidate window out of the cursor area, above it if there's no room below.
+      let candidate_form = CANDIDATEFORM {
+        dwIndex: 0,
+        dwStyle: CFS_EXCLUDE,
+        ptCurrentPos: POINT { x, y },
+        rcArea: RECT {
+          left: x,
+          top: y,
+          right: x + width,
+          bottom: y + height,
+        },
+      };
       unsafe {
         let himc = ImmGetContext(self.window.0);
         ImmSetCompositionWindow(himc, &composition_form);
+        ImmSetCandidateWindow(himc, &candidate_form);
         ImmReleaseContext(self.window.0, himc);
       }
     }
   }
 
   #[inline]
-  pub fn set_ime_position(&self, spot: Position) {
-    let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
-    self.set_ime_position_physical(x, y);
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    let scale_factor = self.scale_factor();
+    let (x, y) = position.to_physical::<i32>(scale_factor).into();
+    let (width, height) = size.to_physical::<i32>(scale_factor).into();
+    self.set_ime_cursor_area_physical(x, y, width, height);
+  }
+
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    unsafe {
+      if allowed {
+        // Restores the default input context of the window.
+        ImmAssociateContextEx(self.window.0, HIMC::default(), IACE_DEFAULT);
+      } else {
+        let himc = ImmGetContext(self.window.0);
+        if !himc.is_invalid() {
+          ImmNotifyIME(himc, NI_COMPOSITIONSTR, CPS_CANCEL, 0);
+          ImmReleaseContext(self.window.0, himc);
+        }
+        ImmAssociateContextEx(self.window.0, HIMC::default(), 0);
+      }
+    }
   }
 
   #[inline]
@@ -897,6 +1453,17 @@ impl Window {
     self.window_state.lock().current_theme
   }
 
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.window_state.lock().preferred_theme = theme;
+    unsafe {
+      let _ = PostMessageW(self.window.0, *SET_THEME_MSG_ID, WPARAM(0), LPARAM(0));
+    }
+  }
+
+  pub fn set_title_bar_colors(&self, colors: TitleBarColors) {
+    dark_mode::set_title_bar_colors(self.window.0, colors);
+  }
+
   #[inline]
   pub fn reset_dead_keys(&self) {
     // `ToUnicode` consumes the dead-key by default, so we are constructing a fake (but valid)
@@ -933,6 +1500,12 @@ impl Window {
     unsafe { set_skip_taskbar(self.hwnd(), skip) };
   }
 
+  pub fn set_app_user_model_id(&self, app_id: &str) -> Result<(), RootOsError> {
+    unsafe { set_app_user_model_id(self.hwnd(), app_id) }?;
+    self.window_state.lock().has_app_user_model_id = !app_id.is_empty();
+    Ok(())
+  }
+
   #[inline]
   pub fn set_progress_bar(&self, progress: ProgressBarState) {
     unsafe {
@@ -977,6 +1550,28 @@ impl Window {
     });
   }
 
+  #[inline]
+  pub fn set_intercept_minimize(&self, intercept: bool) {
+    self.window_state.lock().intercept_minimize = intercept;
+  }
+
+  #[inline]
+  pub fn set_system_gestures_enabled(&self, gestures: SystemGestures) {
+    self.window_state.lock().system_gestures = gestures;
+  }
+
+  #[inline]
+  pub fn set_enabled(&self, enabled: bool) {
+    unsafe {
+      let _ = EnableWindow(self.hwnd(), enabled);
+    }
+  }
+
+  #[inline]
+  pub fn is_enabled(&self) -> bool {
+    unsafe { IsWindowEnabled(self.hwnd()).as_bool() }
+  }
+
   pub fn set_content_protection(&self, enabled: bool) {
     unsafe {
       let _ = SetWindowDisplayAffinity(
@@ -994,11 +1589,16 @@ impl Window {
 impl Drop for Window {
   #[inline]
   fn drop(&mut self) {
-    KEY_EVENT_BUILDERS.lock().remove(&self.id());
+    if self.detached {
+      return;
+    }
+    self.release();
     unsafe {
       // The window must be destroyed from the same thread that created it, so we send a
       // custom message to be handled by our callback to do the actual work.
-      let _ = PostMessageW(self.window.0, *DESTROY_MSG_ID, WPARAM(0), LPARAM(0));
+      if !self.window_state.lock().destroyed {
+        let _ = PostMessageW(self.window.0, *DESTROY_MSG_ID, WPARAM(0), LPARAM(0));
+      }
     }
   }
 }
@@ -1014,16 +1614,58 @@ pub struct WindowWrapper(HWND);
 unsafe impl Sync for WindowWrapper {}
 unsafe impl Send for WindowWrapper {}
 
+/// Destroys a window from any thread, see `Window::close_token`.
+#[derive(Clone)]
+pub struct CloseToken {
+  window: WindowWrapper,
+  window_state: Weak<Mutex<WindowState>>,
+  thread_executor: event_loop::EventLoopThreadExecutor,
+}
+
+impl CloseToken {
+  pub fn proceed(self) {
+    let CloseToken {
+      window,
+      window_state,
+      thread_executor,
+    } = self;
+    // Windows are only destroyed on the event loop thread, so checking the state there keeps the
+    // handle from being reused by another window before the message is posted.
+    thread_executor.execute_in_thread(move || {
+      let alive = window_state
+        .upgrade()
+        .map_or(false, |state| !state.lock().destroyed);
+      if alive {
+        // Same as dropping the window, the actual work happens on the window's thread.
+        unsafe {
+          let _ = PostMessageW(window.0, *DESTROY_MSG_ID, WPARAM(0), LPARAM(0));
+        }
+      }
+    });
+  }
+}
+
 unsafe fn init<T: 'static>(
   attributes: WindowAttributes,
   pl_attribs: PlatformSpecificWindowBuilderAttributes,
   event_loop: &EventLoopWindowTarget<T>,
 ) -> Result<Window, RootOsError> {
   // registering the window class
-  let class_name = register_window_class(&pl_attribs.window_classname);
+  let class_name = register_window_class(&pl_attribs);
 
   let mut window_flags = WindowFlags::empty();
   window_flags.set(WindowFlags::MARKER_DECORATIONS, attributes.decorations);
+  window_flags.set(
+    WindowFlags::MARKER_TITLE_BAR_HIDDEN,
+    matches!(
+      attributes.title_bar_style,
+      TitleBarStyle::Overlay | TitleBarStyle::Hidden
+    ),
+  );
+  window_flags.set(
+    WindowFlags::MARKER_TITLE_BAR_OVERLAY,
+    attributes.title_bar_style == TitleBarStyle::Overlay,
+  );
   window_flags.set(
     WindowFlags::MARKER_UNDECORATED_SHADOW,
     pl_attribs.decoration_shadow,
@@ -1067,8 +1709,11 @@ unsafe fn init<T: 'static>(
 
   // creating the real window this time, by using the functions in `extra_functions`
   let real_window = {
-    let (style, ex_style) = window_flags.to_window_styles();
+    let (style, ex_style) = pl_attribs
+      .custom_styles
+      .apply(window_flags.to_window_styles());
     let title = util::encode_wide(&attributes.title);
+    let _dpi_context = pl_attribs.dpi_awareness.map(ThreadDpiContext::enter);
     let handle = CreateWindowExW(
       ex_style,
       PCWSTR::from_raw(class_name.as_ptr()),
@@ -1080,7 +1725,7 @@ unsafe fn init<T: 'static>(
       CW_USEDEFAULT,
       parent.unwrap_or_default(),
       pl_attribs.menu.unwrap_or_default(),
-      GetModuleHandleW(PCWSTR::null()).unwrap_or_default(),
+      util::get_instance_handle(),
       Some(Box::into_raw(Box::new(window_flags)) as _),
     );
 
@@ -1091,6 +1736,10 @@ unsafe fn init<T: 'static>(
     WindowWrapper(handle)
   };
 
+  if window_flags.contains(WindowFlags::MARKER_TITLE_BAR_OVERLAY) {
+    util::extend_frame_into_title_bar(real_window.0);
+  }
+
   // Register for touch events if applicable
   {
     let digitizer = GetSystemMetrics(SM_DIGITIZER) as u32;
@@ -1102,6 +1751,8 @@ unsafe fn init<T: 'static>(
   let dpi = hwnd_dpi(real_window.0);
   let scale_factor = dpi_to_scale_factor(dpi);
 
+  let mut attributes_report = AttributesReport::default();
+
   // making the window transparent
   if attributes.transparent && !pl_attribs.no_redirection_bitmap {
     // Empty region for the blur effect, so the window is fully transparent
@@ -1114,7 +1765,11 @@ unsafe fn init<T: 'static>(
       fTransitionOnMaximized: false.into(),
     };
 
-    let _ = DwmEnableBlurBehindWindow(real_window.0, &bb);
+    if DwmEnableBlurBehindWindow(real_window.0, &bb).is_err() {
+      attributes_report
+        .downgrades
+        .push(AttributeDowngrade::Transparency);
+    }
     DeleteObject(region);
   }
 
@@ -1127,13 +1782,14 @@ unsafe fn init<T: 'static>(
   );
 
   let window_state = {
-    let window_state = WindowState::new(
+    let mut window_state = WindowState::new(
       &attributes,
       None,
       scale_factor,
       current_theme,
       attributes.preferred_theme,
     );
+    window_state.custom_styles = pl_attribs.custom_styles;
     let window_state = Arc::new(Mutex::new(window_state));
     WindowState::set_window_flags(window_state.lock(), real_window.0, |f| *f = window_flags);
     window_state
@@ -1143,6 +1799,9 @@ unsafe fn init<T: 'static>(
     window: real_window,
     window_state,
     thread_executor: event_loop.create_thread_executor(),
+    attributes_report,
+    message_hooks: Default::default(),
+    detached: false,
   };
 
   KEY_EVENT_BUILDERS
@@ -1183,32 +1842,47 @@ unsafe fn init<T: 'static>(
     win.set_outer_position(position);
   }
 
+  if !win.attributes_report.is_empty() {
+    event_loop.runner_shared.send_event(Event::WindowEvent {
+      window_id: RootWindowId(win.id()),
+      event: WindowEvent::AttributesDowngraded(win.attributes_report.clone()),
+    });
+  }
+
   Ok(win)
 }
 
-unsafe fn register_window_class(window_classname: &str) -> Vec<u16> {
-  let class_name = util::encode_wide(window_classname);
+unsafe fn register_window_class(pl_attribs: &PlatformSpecificWindowBuilderAttributes) -> Vec<u16> {
+  let class_name = util::encode_wide(&pl_attribs.window_classname);
+  let icon = pl_attribs.window_class_icon.as_ref();
+  let icon_handle =
+    |icon_type| icon.map_or_else(HICON::default, |icon| icon.inner.handle_for(&icon_type));
 
   let class = WNDCLASSEXW {
     cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
-    style: CS_HREDRAW | CS_VREDRAW | CS_OWNDC,
+    style: WNDCLASS_STYLES(pl_attribs.window_class_style),
     lpfnWndProc: Some(window_proc),
     cbClsExtra: 0,
     cbWndExtra: 0,
-    hInstance: HINSTANCE(GetModuleHandleW(PCWSTR::null()).unwrap_or_default().0),
-    hIcon: HICON::default(),
+    hInstance: HINSTANCE(util::get_instance_handle().0),
+    hIcon: icon_handle(IconType::Big),
     hCursor: HCURSOR::default(), // must be null in order for cursor state to work properly
     hbrBackground: HBRUSH::default(),
     lpszMenuName: PCWSTR::null(),
     lpszClassName: PCWSTR::from_raw(class_name.as_ptr()),
-    hIconSm: HICON::default(),
+    hIconSm: icon_handle(IconType::Small),
   };
 
   // We ignore errors because registering the same window class twice would trigger
   //  an error, and because errors here are detected during CreateWindowEx anyway.
   // Also since there is no weird element in the struct, there is no reason for this
   //  call to fail.
-  RegisterClassExW(&class);
+  if RegisterClassExW(&class) != 0 {
+    // The class uses the icon as long as the process runs.
+    if let Some(icon) = icon {
+      mem::forget(icon.clone());
+    }
+  }
 
   class_name
 }
@@ -1229,6 +1903,11 @@ unsafe extern "system" fn window_proc(
         let window_flags = WindowFlags::from_bits_unchecked(userdata as _);
 
         if wparam == WPARAM(0) || window_flags.contains(WindowFlags::MARKER_DECORATIONS) {
+          if wparam != WPARAM(0) && window_flags.contains(WindowFlags::MARKER_TITLE_BAR_HIDDEN) {
+            return util::calc_size_without_title_bar(window, lparam, || {
+              DefWindowProcW(window, msg, wparam, lparam)
+            });
+          }
           return DefWindowProcW(window, msg, wparam, lparam);
         }
 
@@ -1355,6 +2034,27 @@ unsafe fn force_window_active(handle: HWND) {
   SetForegroundWindow(handle);
 }
 
+pub(crate) unsafe fn set_overlay_icon(hwnd: HWND, icon: Option<&Icon>, description: &str) {
+  com_initialized();
+  let taskbar_list: ITaskbarList = match CoCreateInstance(&TaskbarList, None, CLSCTX_SERVER) {
+    Ok(taskbar_list) => taskbar_list,
+    Err(_) => return,
+  };
+  let handle = icon
+    .map(|icon| icon.inner.handle_for(&IconType::Small))
+    .unwrap_or_default();
+  let description = util::encode_wide(description);
+  let _ = taskbar_list.SetOverlayIcon(hwnd, handle, PCWSTR::from_raw(description.as_ptr()));
+}
+
+/// Arms the timer showing the next frame of an animated custom cursor after `delay`.
+pub(crate) unsafe fn set_cursor_frame_timer(hwnd: HWND, delay: Duration) {
+  let delay = delay
+    .as_millis()
+    .clamp(USER_TIMER_MINIMUM as u128, USER_TIMER_MAXIMUM as u128);
+  SetTimer(hwnd, icon::CURSOR_FRAME_TIMER_ID, delay as u32, None);
+}
+
 pub(crate) unsafe fn set_skip_taskbar(hwnd: HWND, skip: bool) {
   com_initialized();
   let taskbar_list: ITaskbarList =
@@ -1366,6 +2066,23 @@ pub(crate) unsafe fn set_skip_taskbar(hwnd: HWND, skip: bool) {
   }
 }
 
+/// Sets the `System.AppUserModel.ID` property of `hwnd`, or removes it if `app_id` is empty.
+pub(crate) unsafe fn set_app_user_model_id(hwnd: HWND, app_id: &str) -> windows::core::Result<()> {
+  com_initialized();
+  let store: IPropertyStore = SHGetPropertyStoreForWindow(hwnd)?;
+  // An empty value removes the property.
+  let mut value = PROPVARIANT::default();
+  if !app_id.is_empty() {
+    let app_id = util::encode_wide(app_id);
+    (*value.Anonymous.Anonymous).vt = VT_LPWSTR;
+    (*value.Anonymous.Anonymous).Anonymous.pwszVal = SHStrDupW(PCWSTR::from_raw(app_id.as_ptr()))?;
+  }
+  let result = store.SetValue(&PKEY_AppUserModel_ID, &value);
+  let _ = PropVariantClear(&mut value);
+  result?;
+  store.Commit()
+}
+
 impl ResizeDirection {
   pub(crate) fn to_win32(&self) -> u32 {
     match self {
@@ -4,17 +4,23 @@
 
 use crate::{
   dpi::PhysicalPosition,
-  icon::Icon,
+  event::{ElementState, MouseButton, TabletTool},
+  icon::{CustomCursor, Icon, RgbaIcon},
   keyboard::ModifiersState,
-  platform_impl::platform::{event_loop, minimal_ime::MinimalIme, util},
-  window::{CursorIcon, Fullscreen, Theme, WindowAttributes, WindowSizeConstraints},
+  platform_impl::platform::{
+    event_loop, frame_pacing::JankDetector, icon::WinIcon, minimal_ime::MinimalIme, util,
+  },
+  window::{
+    AccelerationProfile, CursorIcon, DropPolicy, Fullscreen, Rect, SystemGestures, Theme,
+    WindowAttributes, WindowSizeConstraints,
+  },
 };
 use parking_lot::MutexGuard;
-use std::io;
+use std::{io, time::Duration};
 use windows::Win32::{
-  Foundation::{HWND, LPARAM, RECT, WPARAM},
+  Foundation::{HWND, LPARAM, POINT, RECT, WPARAM},
   Graphics::Gdi::{InvalidateRgn, HRGN},
-  UI::WindowsAndMessaging::*,
+  UI::{Input::KeyboardAndMouse::GetDoubleClickTime, WindowsAndMessaging::*},
 };
 
 /// Contains information about states and the window that the callback is going to use.
@@ -24,15 +30,63 @@ pub struct WindowState {
   /// Used by `WM_GETMINMAXINFO`.
   pub size_constraints: WindowSizeConstraints,
 
+  /// Title set by `set_title` which `SET_TITLE_MSG_ID` hasn't applied yet.
+  pub pending_title: Option<String>,
+
   pub window_icon: Option<Icon>,
   pub taskbar_icon: Option<Icon>,
+  /// Overlay icon and its description, see `WindowExtWindows::set_overlay_icon`.
+  pub overlay_icon: Option<(Icon, String)>,
+  /// Icons cycled through by `ICON_FRAME_TIMER_ID`, and the one currently shown.
+  pub icon_frames: Vec<Icon>,
+  pub icon_frame: usize,
 
   pub saved_window: Option<SavedWindow>,
+  /// Scale factor reported to the application, `scale_factor_override` if it's set.
   pub scale_factor: f64,
+  /// Scale factor of the DPI of the window.
+  pub dpi_scale_factor: f64,
+  pub scale_factor_override: Option<f64>,
 
   pub dragging: bool,
 
   pub skip_taskbar: bool,
+  /// Whether the window has its own application user model id, which must be removed before the
+  /// window is destroyed.
+  pub has_app_user_model_id: bool,
+
+  /// Whether the thread event target routes the raw mouse motion to the window while it's in the
+  /// foreground, see `SET_RAW_MOUSE_MOTION_MSG_ID`.
+  pub raw_mouse_motion: bool,
+
+  /// Used by `WM_SYSCOMMAND` to emit `MinimizeRequested` instead of minimizing.
+  pub intercept_minimize: bool,
+
+  /// Checked by the `FileDropHandler` when something is dragged over the window.
+  pub drop_policy: DropPolicy,
+
+  /// Bitmap handed to DWM for the taskbar thumbnail and peek, see `peek.rs`.
+  pub peek_bitmap: Option<RgbaIcon>,
+  pub peeking: bool,
+
+  /// Child windows of `Window::create_surface_region` and their rects, re-applied on DPI changes.
+  pub surface_regions: Vec<(HWND, Rect)>,
+
+  /// Last pen tool and barrel button state seen in `WM_POINTER*`, to only report changes.
+  pub pen_tool: Option<TabletTool>,
+  pub pen_barrel_pressed: bool,
+
+  /// Routes `request_redraw` through `frame_pacing.rs`.
+  pub frame_pacing: bool,
+  /// Set by `set_jank_detection` to emit `Event::FrameMissed` on redraws.
+  pub jank_detector: Option<JankDetector>,
+
+  pub system_gestures: SystemGestures,
+  /// Last value reported with `WindowEvent::Occluded`.
+  pub occluded: bool,
+  /// Cursor offset from the window origin while the title bar is dragged without the system move
+  /// loop, which is what snaps and shakes windows.
+  pub manual_move: Option<POINT>,
 
   pub modifiers_state: ModifiersState,
   pub fullscreen: Option<Fullscreen>,
@@ -43,10 +97,49 @@ pub struct WindowState {
   pub ime_handler: MinimalIme,
 
   pub window_flags: WindowFlags,
+  /// Styles set with `WindowExtWindows::set_style` and `set_ex_style`.
+  pub custom_styles: CustomStyles,
 
   // Used by WM_NCACTIVATE, WM_SETFOCUS and WM_KILLFOCUS
   pub is_active: bool,
   pub is_focused: bool,
+
+  /// Set by `WM_NCDESTROY`, after which the handle may be reused by another window.
+  pub destroyed: bool,
+}
+
+/// Bits of the styles which the application set itself, applied over the styles derived from the
+/// `WindowFlags` so that they aren't lost when those change.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CustomStyles {
+  style: u32,
+  style_mask: u32,
+  ex_style: u32,
+  ex_style_mask: u32,
+}
+
+impl CustomStyles {
+  /// Sets the bits of `mask` in the style to those of `style`.
+  pub fn set_style(&mut self, style: u32, mask: u32) {
+    self.style = (self.style & !mask) | (style & mask);
+    self.style_mask |= mask;
+  }
+
+  /// Sets the bits of `mask` in the extended style to those of `ex_style`.
+  pub fn set_ex_style(&mut self, ex_style: u32, mask: u32) {
+    self.ex_style = (self.ex_style & !mask) | (ex_style & mask);
+    self.ex_style_mask |= mask;
+  }
+
+  pub fn apply(
+    self,
+    (style, ex_style): (WINDOW_STYLE, WINDOW_EX_STYLE),
+  ) -> (WINDOW_STYLE, WINDOW_EX_STYLE) {
+    (
+      WINDOW_STYLE((style.0 & !self.style_mask) | self.style),
+      WINDOW_EX_STYLE((ex_style.0 & !self.ex_style_mask) | self.ex_style),
+    )
+  }
 }
 
 #[derive(Clone)]
@@ -57,9 +150,25 @@ pub struct SavedWindow {
 #[derive(Clone)]
 pub struct MouseProperties {
   pub cursor: CursorIcon,
+  /// Replaces `cursor` when set, along with the handle created for the current scale.
+  pub custom_cursor: Option<(CustomCursor, WinIcon)>,
+  /// Frame of `custom_cursor` shown if it's animated, advanced by `CURSOR_FRAME_TIMER_ID`.
+  pub cursor_frame: usize,
   pub capture_count: u32,
   cursor_flags: CursorFlags,
   pub last_position: Option<PhysicalPosition<f64>>,
+  pub pointer_acceleration: Option<AccelerationProfile>,
+  /// Last press of a button, see `MouseProperties::click_count`.
+  last_click: Option<Click>,
+}
+
+#[derive(Clone, Copy)]
+struct Click {
+  button: MouseButton,
+  /// Message time, in milliseconds.
+  time: u32,
+  position: (i32, i32),
+  count: u32,
 }
 
 bitflags! {
@@ -67,6 +176,7 @@ bitflags! {
         const GRABBED   = 1 << 0;
         const HIDDEN    = 1 << 1;
         const IN_WINDOW = 1 << 2;
+        const LOCKED    = 1 << 3;
     }
 }
 bitflags! {
@@ -111,6 +221,13 @@ bitflags! {
 
         const RIGHT_TO_LEFT_LAYOUT = 1 << 22;
 
+        const NO_ACTIVATE = 1 << 23;
+
+        /// Client area covering the title bar of a decorated window.
+        const MARKER_TITLE_BAR_HIDDEN = 1 << 24;
+        /// Caption buttons drawn over the client area covering the title bar.
+        const MARKER_TITLE_BAR_OVERLAY = 1 << 25;
+
         const EXCLUSIVE_FULLSCREEN_OR_MASK = WindowFlags::ALWAYS_ON_TOP.bits;
     }
 }
@@ -126,22 +243,52 @@ impl WindowState {
     WindowState {
       mouse: MouseProperties {
         cursor: CursorIcon::default(),
+        custom_cursor: None,
+        cursor_frame: 0,
         capture_count: 0,
         cursor_flags: CursorFlags::empty(),
         last_position: None,
+        pointer_acceleration: None,
+        last_click: None,
       },
 
       size_constraints: attributes.inner_size_constraints,
 
+      pending_title: None,
+
       window_icon: attributes.window_icon.clone(),
       taskbar_icon,
+      overlay_icon: None,
+      icon_frames: Vec::new(),
+      icon_frame: 0,
 
       saved_window: None,
       scale_factor,
+      dpi_scale_factor: scale_factor,
+      scale_factor_override: None,
 
       dragging: false,
 
       skip_taskbar: false,
+      has_app_user_model_id: false,
+      raw_mouse_motion: false,
+
+      intercept_minimize: attributes.intercept_minimize,
+
+      drop_policy: DropPolicy::default(),
+
+      peek_bitmap: None,
+      peeking: false,
+      surface_regions: Vec::new(),
+
+      pen_tool: None,
+      pen_barrel_pressed: false,
+
+      frame_pacing: false,
+      jank_detector: None,
+      system_gestures: SystemGestures::default(),
+      occluded: false,
+      manual_move: None,
 
       modifiers_state: ModifiersState::default(),
       fullscreen: None,
@@ -150,8 +297,11 @@ impl WindowState {
       high_surrogate: None,
       ime_handler: MinimalIme::default(),
       window_flags: WindowFlags::empty(),
+      custom_styles: CustomStyles::default(),
       is_active: false,
       is_focused: false,
+
+      destroyed: false,
     }
   }
 
@@ -166,9 +316,22 @@ impl WindowState {
     let old_flags = this.window_flags;
     f(&mut this.window_flags);
     let new_flags = this.window_flags;
+    let custom_styles = this.custom_styles;
 
     drop(this);
-    old_flags.apply_diff(window, new_flags);
+    old_flags.apply_diff(window, new_flags, custom_styles);
+  }
+
+  pub fn set_custom_styles<F>(mut this: MutexGuard<'_, Self>, window: HWND, f: F)
+  where
+    F: FnOnce(&mut CustomStyles),
+  {
+    f(&mut this.custom_styles);
+    let flags = this.window_flags.mask();
+    let custom_styles = this.custom_styles;
+
+    drop(this);
+    flags.set_window_styles(window, custom_styles);
   }
 
   pub fn set_window_flags_in_place<F>(&mut self, f: F)
@@ -178,6 +341,64 @@ impl WindowState {
     f(&mut self.window_flags);
   }
 
+  /// Sets the custom cursor, creating its handle for the current scale factor and cursor size
+  /// setting. Returns the handle to show.
+  pub fn set_custom_cursor(&mut self, cursor: CustomCursor) -> Option<HCURSOR> {
+    self.mouse.cursor_frame = 0;
+    self.show_custom_cursor(cursor)
+  }
+
+  /// Shows the next frame of the animated custom cursor. Returns its handle and how long it's
+  /// shown, or `None` if the custom cursor isn't animated anymore.
+  pub fn next_custom_cursor_frame(&mut self) -> Option<(HCURSOR, Duration)> {
+    let (cursor, _) = self.mouse.custom_cursor.as_ref()?;
+    if cursor.frames.is_empty() {
+      return None;
+    }
+    let cursor = cursor.clone();
+    self.mouse.cursor_frame = (self.mouse.cursor_frame + 1) % cursor.frames.len();
+    let delay = cursor.frame_delay(self.mouse.cursor_frame)?;
+    self
+      .show_custom_cursor(cursor)
+      .map(|hcursor| (hcursor, delay))
+  }
+
+  fn show_custom_cursor(&mut self, cursor: CustomCursor) -> Option<HCURSOR> {
+    let scale = self.dpi_scale_factor * util::cursor_size_scale();
+    let frame = cursor.frame(self.mouse.cursor_frame);
+    let handle = match &frame.path {
+      // Animated cursor files are animated by the system.
+      Some(path) => WinIcon::cursor_from_path(path),
+      None => WinIcon::cursor_from_image(frame.image_for_scale(scale)),
+    };
+    match handle {
+      Ok(handle) => {
+        let hcursor = HCURSOR(handle.as_raw_handle().0);
+        // The old handle is destroyed when it's replaced, so the new one must be shown first if the
+        // old one is the current cursor.
+        if let Some((_, old_handle)) = &self.mouse.custom_cursor {
+          unsafe {
+            if GetCursor() == HCURSOR(old_handle.as_raw_handle().0) {
+              SetCursor(hcursor);
+            }
+          }
+        }
+        self.mouse.custom_cursor = Some((cursor, handle));
+        Some(hcursor)
+      }
+      Err(e) => {
+        warn!("Failed to create custom cursor: {}", e);
+        None
+      }
+    }
+  }
+
+  /// Recreates the custom cursor handle after the scale factor or cursor size setting changed.
+  pub fn refresh_custom_cursor(&mut self) -> Option<HCURSOR> {
+    let (cursor, _) = self.mouse.custom_cursor.as_ref()?;
+    self.show_custom_cursor(cursor.clone())
+  }
+
   pub fn has_active_focus(&self) -> bool {
     self.is_active && self.is_focused
   }
@@ -198,10 +419,56 @@ impl WindowState {
 }
 
 impl MouseProperties {
+  /// Returns how many times in a row `button` was pressed within the double-click time and
+  /// rectangle of the system, this press included. A release gets the count of its press.
+  pub fn click_count(
+    &mut self,
+    button: MouseButton,
+    state: ElementState,
+    time: u32,
+    position: (i32, i32),
+  ) -> u32 {
+    let last_click = self.last_click.filter(|click| click.button == button);
+    if state == ElementState::Released {
+      return last_click.map_or(1, |click| click.count);
+    }
+
+    let (width, height, interval) = unsafe {
+      (
+        GetSystemMetrics(SM_CXDOUBLECLK),
+        GetSystemMetrics(SM_CYDOUBLECLK),
+        GetDoubleClickTime(),
+      )
+    };
+    let count = match last_click {
+      Some(click)
+        if time.wrapping_sub(click.time) <= interval
+          && (position.0 - click.position.0).abs() <= width / 2
+          && (position.1 - click.position.1).abs() <= height / 2 =>
+      {
+        click.count + 1
+      }
+      _ => 1,
+    };
+    self.last_click = Some(Click {
+      button,
+      time,
+      position,
+      count,
+    });
+    count
+  }
+
   pub fn cursor_flags(&self) -> CursorFlags {
     self.cursor_flags
   }
 
+  /// Clips the cursor again, since Windows resets the clip rect when another window is focused and
+  /// it doesn't follow the window when it moves.
+  pub fn refresh_cursor_clip(&self, window: HWND) -> Result<(), io::Error> {
+    self.cursor_flags.refresh_os_cursor(window)
+  }
+
   pub fn set_cursor_flags<F>(&mut self, window: HWND, f: F) -> Result<(), io::Error>
   where
     F: FnOnce(&mut CursorFlags),
@@ -218,6 +485,15 @@ impl MouseProperties {
 
     Ok(())
   }
+
+  /// Whether the thread event target moves the cursor by the raw mouse motion while the window is
+  /// in the foreground, see `SET_FLAT_POINTER_MSG_ID`.
+  pub fn flat_pointer(&self) -> bool {
+    self
+      .cursor_flags
+      .intersects(CursorFlags::GRABBED | CursorFlags::LOCKED)
+      && self.pointer_acceleration == Some(AccelerationProfile::Flat)
+  }
 }
 
 impl WindowFlags {
@@ -278,12 +554,15 @@ impl WindowFlags {
     if self.contains(WindowFlags::RIGHT_TO_LEFT_LAYOUT) {
       style_ex |= WS_EX_LAYOUTRTL | WS_EX_RTLREADING | WS_EX_RIGHT;
     }
+    if self.contains(WindowFlags::NO_ACTIVATE) {
+      style_ex |= WS_EX_NOACTIVATE;
+    }
 
     (style, style_ex)
   }
 
   /// Adjust the window client rectangle to the return value, if present.
-  fn apply_diff(mut self, window: HWND, mut new: WindowFlags) {
+  fn apply_diff(mut self, window: HWND, mut new: WindowFlags, custom_styles: CustomStyles) {
     self = self.mask();
     new = new.mask();
 
@@ -393,42 +672,47 @@ impl WindowFlags {
     }
 
     if diff != WindowFlags::empty() {
-      let (style, style_ex) = new.to_window_styles();
-
-      unsafe {
-        SendMessageW(
-          window,
-          *event_loop::SET_RETAIN_STATE_ON_SIZE_MSG_ID,
-          WPARAM(1),
-          LPARAM(0),
-        );
+      new.set_window_styles(window, custom_styles);
+    }
+  }
 
-        // This condition is necessary to avoid having an unrestorable window
-        if !new.contains(WindowFlags::MINIMIZED) {
-          SetWindowLongW(window, GWL_STYLE, style.0 as i32);
-          SetWindowLongW(window, GWL_EXSTYLE, style_ex.0 as i32);
-        }
+  /// Applies the styles of these flags and `custom_styles` to `window`.
+  fn set_window_styles(self, window: HWND, custom_styles: CustomStyles) {
+    let (style, style_ex) = custom_styles.apply(self.to_window_styles());
+
+    unsafe {
+      SendMessageW(
+        window,
+        *event_loop::SET_RETAIN_STATE_ON_SIZE_MSG_ID,
+        WPARAM(1),
+        LPARAM(0),
+      );
+
+      // This condition is necessary to avoid having an unrestorable window
+      if !self.contains(WindowFlags::MINIMIZED) {
+        SetWindowLongW(window, GWL_STYLE, style.0 as i32);
+        SetWindowLongW(window, GWL_EXSTYLE, style_ex.0 as i32);
+      }
 
-        let mut flags = SWP_NOZORDER | SWP_NOMOVE | SWP_NOSIZE | SWP_FRAMECHANGED;
+      let mut flags = SWP_NOZORDER | SWP_NOMOVE | SWP_NOSIZE | SWP_FRAMECHANGED;
 
-        // We generally don't want style changes here to affect window
-        // focus, but for fullscreen windows they must be activated
-        // (i.e. focused) so that they appear on top of the taskbar
-        if !new.contains(WindowFlags::MARKER_EXCLUSIVE_FULLSCREEN)
-          && !new.contains(WindowFlags::MARKER_BORDERLESS_FULLSCREEN)
-        {
-          flags |= SWP_NOACTIVATE;
-        }
-
-        // Refresh the window frame
-        let _ = SetWindowPos(window, HWND::default(), 0, 0, 0, 0, flags);
-        SendMessageW(
-          window,
-          *event_loop::SET_RETAIN_STATE_ON_SIZE_MSG_ID,
-          WPARAM(0),
-          LPARAM(0),
-        );
+      // We generally don't want style changes here to affect window
+      // focus, but for fullscreen windows they must be activated
+      // (i.e. focused) so that they appear on top of the taskbar
+      if !self.contains(WindowFlags::MARKER_EXCLUSIVE_FULLSCREEN)
+        && !self.contains(WindowFlags::MARKER_BORDERLESS_FULLSCREEN)
+      {
+        flags |= SWP_NOACTIVATE;
       }
+
+      // Refresh the window frame
+      let _ = SetWindowPos(window, HWND::default(), 0, 0, 0, 0, flags);
+      SendMessageW(
+        window,
+        *event_loop::SET_RETAIN_STATE_ON_SIZE_MSG_ID,
+        WPARAM(0),
+        LPARAM(0),
+      );
     }
   }
 }
@@ -438,9 +722,21 @@ impl CursorFlags {
     let client_rect = util::get_client_rect(window)?;
 
     if util::is_focused(window) {
-      let cursor_clip = match self.contains(CursorFlags::GRABBED) {
-        true => Some(client_rect),
-        false => None,
+      let cursor_clip = if self.contains(CursorFlags::LOCKED) {
+        // A clip rect of a single pixel keeps the cursor in place, inside the client area.
+        let position = util::cursor_position()?;
+        let x = (position.x as i32).clamp(client_rect.left, client_rect.right - 1);
+        let y = (position.y as i32).clamp(client_rect.top, client_rect.bottom - 1);
+        Some(RECT {
+          left: x,
+          top: y,
+          right: x + 1,
+          bottom: y + 1,
+        })
+      } else if self.contains(CursorFlags::GRABBED) {
+        Some(client_rect)
+      } else {
+        None
       };
 
       let rect_to_tuple = |rect: RECT| (rect.left, rect.top, rect.right, rect.bottom);
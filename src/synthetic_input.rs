@@ -0,0 +1,229 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Injection of synthetic input into an [`EventLoop`], enabled by the `synthetic-input` feature.
+//!
+//! This is meant for the end-to-end tests of applications: an [`InputInjector`] sends key presses,
+//! cursor moves, clicks or any other [`WindowEvent`] to the event loop from any thread, and they
+//! are dispatched to the event handler of [`EventLoop::run`],
+//! [`EventLoopExtRunReturn::run_return`] or [`EventLoopExtPumpEvents::pump_events`] like the
+//! events of the platform, in the order they were sent. Nothing goes through the platform, so the
+//! windows themselves don't react to the injected input, and neither does the focus or the cursor.
+//!
+//! ```no_run
+//! use tao::{
+//!   event::{ElementState, Event, MouseButton, WindowEvent},
+//!   event_loop::{ControlFlow, EventLoop},
+//!   window::WindowBuilder,
+//! };
+//!
+//! let event_loop = EventLoop::new();
+//! let window = WindowBuilder::new().build(&event_loop).unwrap();
+//! let injector = event_loop.create_input_injector();
+//! injector.click(window.id(), MouseButton::Left).unwrap();
+//!
+//! event_loop.run(move |event, _, control_flow| {
+//!   *control_flow = ControlFlow::Wait;
+//!   if let Event::WindowEvent {
+//!     event: WindowEvent::MouseInput {
+//!       state: ElementState::Released,
+//!       ..
+//!     },
+//!     ..
+//!   } = event
+//!   {
+//!     *control_flow = ControlFlow::Exit;
+//!   }
+//! });
+//! ```
+//!
+//! [`EventLoop`]: crate::event_loop::EventLoop
+//! [`EventLoop::run`]: crate::event_loop::EventLoop::run
+//! [`EventLoopExtRunReturn::run_return`]: crate::platform::run_return::EventLoopExtRunReturn::run_return
+//! [`EventLoopExtPumpEvents::pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+
+use crate::{
+  dpi::PhysicalPosition,
+  event::{DeviceId, ElementState, Event, KeyEvent, MouseButton, WindowEvent},
+  event_loop::{ControlFlow, EventLoopClosed, EventLoopProxy, EventLoopWindowTarget},
+  keyboard::{Key, KeyCode, KeyLocation, ModifiersState},
+  platform_impl,
+  window::WindowId,
+};
+
+/// An event sent by an [`InputInjector`], carried to the event loop as a typed user event.
+pub(crate) struct Injected {
+  window_id: WindowId,
+  event: WindowEvent<'static>,
+}
+
+/// Sends synthetic input to the event loop it was created by with
+/// [`EventLoop::create_input_injector`].
+///
+/// The injected events carry a dummy [`DeviceId`]. Sending fails once the event loop exited.
+///
+/// [`EventLoop::create_input_injector`]: crate::event_loop::EventLoop::create_input_injector
+#[derive(Clone, Debug)]
+pub struct InputInjector {
+  pub(crate) proxy: EventLoopProxy<Injected>,
+}
+
+impl InputInjector {
+  /// Dispatches `event` to the window `window_id`.
+  pub fn window_event(
+    &self,
+    window_id: WindowId,
+    event: WindowEvent<'static>,
+  ) -> Result<(), EventLoopClosed<()>> {
+    self
+      .proxy
+      .send_event(Injected { window_id, event })
+      .map_err(|_| EventLoopClosed(()))
+  }
+
+  /// Dispatches a `WindowEvent::KeyboardInput` for `physical_key`, typing `text` if it's pressed.
+  pub fn key(
+    &self,
+    window_id: WindowId,
+    physical_key: KeyCode,
+    logical_key: Key<'static>,
+    text: Option<&'static str>,
+    state: ElementState,
+  ) -> Result<(), EventLoopClosed<()>> {
+    let text = match state {
+      ElementState::Pressed => text,
+      _ => None,
+    };
+    let event = KeyEvent {
+      physical_key,
+      logical_key: logical_key.clone(),
+      text,
+      location: KeyLocation::Standard,
+      state,
+      repeat: false,
+      repeat_count: 0,
+      dead_key_pending: false,
+      platform_specific: key_event_extra(logical_key, text),
+    };
+    self.window_event(
+      window_id,
+      WindowEvent::KeyboardInput {
+        device_id: device_id(),
+        event,
+        is_synthetic: false,
+      },
+    )
+  }
+
+  /// Presses and releases `physical_key`, see [`InputInjector::key`].
+  pub fn tap_key(
+    &self,
+    window_id: WindowId,
+    physical_key: KeyCode,
+    logical_key: Key<'static>,
+    text: Option<&'static str>,
+  ) -> Result<(), EventLoopClosed<()>> {
+    self.key(
+      window_id,
+      physical_key,
+      logical_key.clone(),
+      text,
+      ElementState::Pressed,
+    )?;
+    self.key(
+      window_id,
+      physical_key,
+      logical_key,
+      None,
+      ElementState::Released,
+    )
+  }
+
+  /// Dispatches a `WindowEvent::CursorMoved` to `position`, relative to the top-left corner of the
+  /// window.
+  pub fn cursor_moved(
+    &self,
+    window_id: WindowId,
+    position: PhysicalPosition<f64>,
+  ) -> Result<(), EventLoopClosed<()>> {
+    #[allow(deprecated)]
+    self.window_event(
+      window_id,
+      WindowEvent::CursorMoved {
+        device_id: device_id(),
+        position,
+        modifiers: ModifiersState::empty(),
+      },
+    )
+  }
+
+  /// Dispatches a `WindowEvent::MouseInput` for `button`, as a single click.
+  pub fn mouse_input(
+    &self,
+    window_id: WindowId,
+    button: MouseButton,
+    state: ElementState,
+  ) -> Result<(), EventLoopClosed<()>> {
+    #[allow(deprecated)]
+    self.window_event(
+      window_id,
+      WindowEvent::MouseInput {
+        device_id: device_id(),
+        state,
+        button,
+        click_count: 1,
+        modifiers: ModifiersState::empty(),
+      },
+    )
+  }
+
+  /// Presses and releases `button`, where the cursor was moved last.
+  pub fn click(&self, window_id: WindowId, button: MouseButton) -> Result<(), EventLoopClosed<()>> {
+    self.mouse_input(window_id, button, ElementState::Pressed)?;
+    self.mouse_input(window_id, button, ElementState::Released)
+  }
+}
+
+fn device_id() -> DeviceId {
+  // Safe as long as it's only handed to the application, never back to the platform.
+  unsafe { DeviceId::dummy() }
+}
+
+#[cfg(not(any(target_os = "ios", target_os = "android")))]
+fn key_event_extra(
+  logical_key: Key<'static>,
+  text: Option<&'static str>,
+) -> platform_impl::KeyEventExtra {
+  platform_impl::KeyEventExtra {
+    text_with_all_modifiers: text,
+    key_without_modifiers: logical_key,
+    input_locale: None,
+  }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+fn key_event_extra(_: Key<'static>, _: Option<&'static str>) -> platform_impl::KeyEventExtra {
+  platform_impl::KeyEventExtra {}
+}
+
+/// Wraps `event_handler` so that the events sent by the injectors are dispatched as the window
+/// events they carry.
+pub(crate) fn inject<T, F>(
+  mut event_handler: F,
+) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+where
+  F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+{
+  move |event, target, control_flow| match event {
+    Event::TypedUserEvent(event) => match event.downcast::<Injected>() {
+      Ok(Injected { window_id, event }) => event_handler(
+        Event::WindowEvent { window_id, event },
+        target,
+        control_flow,
+      ),
+      Err(event) => event_handler(Event::TypedUserEvent(event), target, control_flow),
+    },
+    event => event_handler(event, target, control_flow),
+  }
+}
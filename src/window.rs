@@ -3,17 +3,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The `Window` struct and associated types.
-use std::fmt;
+use std::{collections::HashMap, fmt, mem::ManuallyDrop, ptr, sync::Mutex, time::Duration};
 
 use crate::{
-  dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Pixel, PixelUnit, Position, Size},
+  dpi::{
+    LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Pixel, PixelUnit, Position, Size,
+  },
   error::{ExternalError, NotSupportedError, OsError},
-  event_loop::EventLoopWindowTarget,
-  monitor::{MonitorHandle, VideoMode},
+  event_loop::{ControlFlow, EventLoopWindowTarget},
+  global_shortcut::{Accelerator, AcceleratorId},
+  monitor::{self, MonitorHandle, VideoMode},
   platform_impl,
 };
 
-pub use crate::icon::{BadIcon, Icon};
+pub use crate::icon::{BadIcon, CustomCursor, Icon};
 
 /// Progress State
 #[derive(Debug, Clone, Copy)]
@@ -82,6 +85,8 @@ impl Drop for Window {
     if let Some(Fullscreen::Exclusive(_)) = self.fullscreen() {
       self.set_fullscreen(None);
     }
+
+    self.release_stable_id();
   }
 }
 
@@ -106,6 +111,146 @@ impl WindowId {
   }
 }
 
+/// Identifier of a window which, unlike [`WindowId`], is the same across runs of the application.
+///
+/// Can be obtained with [`Window::stable_id`], from a key chosen by the application for what it
+/// considers the same window, e.g. `"main"` or the path of the document it shows. It can be
+/// serialized with the `serde` feature, to be stored for session restore or sent to another
+/// process, and turned back into the [`WindowId`] of the window with [`StableWindowId::window_id`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StableWindowId(u64);
+
+lazy_static! {
+  /// The window each stable id was last obtained for, until that window is dropped.
+  static ref STABLE_IDS: Mutex<HashMap<StableWindowId, WindowId>> = Default::default();
+}
+
+impl StableWindowId {
+  /// Returns the stable id `Window::stable_id` gives for `seed`, without a window.
+  ///
+  /// It's a 64-bit FNV-1a hash of `seed`, which doesn't depend on the Rust version or the
+  /// platform.
+  pub fn from_seed(seed: &str) -> Self {
+    let hash = seed.bytes().fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+      (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    StableWindowId(hash)
+  }
+
+  /// Returns the window this stable id was last obtained for with [`Window::stable_id`], if it
+  /// hasn't been dropped since.
+  pub fn window_id(&self) -> Option<WindowId> {
+    STABLE_IDS.lock().ok()?.get(self).copied()
+  }
+}
+
+/// A handle used to complete a close that was deferred from [`WindowEvent::CloseRequested`].
+///
+/// Can be obtained with [`Window::close_token`] and moved to another thread or an async task, for
+/// example one waiting on a "save changes?" dialog, which then either proceeds with the close or
+/// cancels it.
+///
+/// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+#[derive(Clone)]
+pub struct CloseToken(pub(crate) platform_impl::CloseToken);
+
+impl fmt::Debug for CloseToken {
+  fn fmt(&self, fmtr: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmtr.pad("CloseToken { .. }")
+  }
+}
+
+impl CloseToken {
+  /// Proceeds with the close, destroying the window as [`Window::destroy`] does.
+  ///
+  /// The [`Window`] should be dropped once its `WindowEvent::Destroyed` event is received.
+  #[inline]
+  pub fn proceed(self) {
+    self.0.proceed()
+  }
+
+  /// Cancels the close, leaving the window open.
+  ///
+  /// This is equivalent to dropping the token, and only exists to make the intent explicit.
+  #[inline]
+  pub fn cancel(self) {}
+}
+
+/// A native window handle that can be passed to another process, see
+/// [`Window::into_sendable_handle`] and [`Window::adopt_from_handle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SendableWindowHandle(u64);
+
+impl SendableWindowHandle {
+  /// Returns the value to send to the other process.
+  #[inline]
+  pub fn to_raw(self) -> u64 {
+    self.0
+  }
+
+  /// Rebuilds a handle from the value received from the other process.
+  #[inline]
+  pub fn from_raw(raw: u64) -> Self {
+    Self(raw)
+  }
+}
+
+/// A rectangle in a window's client area, relative to its top-left corner, or on the screen for
+/// [`MonitorHandle::work_area`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+  pub position: Position,
+  pub size: Size,
+}
+
+/// A native child surface reserved in a window's client area, which embedders such as webviews,
+/// video players or map SDKs render into through its window handle.
+///
+/// Can be obtained with [`Window::create_surface_region`]. The surface is stacked below the other
+/// child surfaces of the window, so that chrome drawn in child surfaces of its own stays on top,
+/// and a rect given in logical units is re-applied when the window's scale factor changes.
+///
+/// The surface is destroyed when the region is dropped.
+pub struct SurfaceRegion(pub(crate) platform_impl::SurfaceRegion);
+
+impl fmt::Debug for SurfaceRegion {
+  fn fmt(&self, fmtr: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmtr.pad("SurfaceRegion { .. }")
+  }
+}
+
+impl SurfaceRegion {
+  /// Moves and resizes the surface.
+  #[inline]
+  pub fn set_rect(&self, rect: Rect) {
+    self.0.set_rect(rect)
+  }
+
+  /// Returns the rect last given to [`Window::create_surface_region`] or [`SurfaceRegion::set_rect`].
+  #[inline]
+  pub fn rect(&self) -> Rect {
+    self.0.rect()
+  }
+
+  /// Shows or hides the surface.
+  #[inline]
+  pub fn set_visible(&self, visible: bool) {
+    self.0.set_visible(visible)
+  }
+}
+
+#[cfg(feature = "rwh_06")]
+impl rwh_06::HasWindowHandle for SurfaceRegion {
+  fn window_handle(&self) -> Result<rwh_06::WindowHandle<'_>, rwh_06::HandleError> {
+    let raw = self.0.raw_window_handle_rwh_06()?;
+    // SAFETY: The surface will never be deallocated while the region is alive.
+    Ok(unsafe { rwh_06::WindowHandle::borrow_raw(raw) })
+  }
+}
+
 /// Object that allows you to build windows.
 #[derive(Clone, Default)]
 pub struct WindowBuilder {
@@ -114,6 +259,9 @@ pub struct WindowBuilder {
 
   // Platform-specific configuration.
   pub(crate) platform_specific: platform_impl::PlatformSpecificWindowBuilderAttributes,
+
+  // Resolved against the connected monitors in `build`.
+  pub(crate) window_state: Option<WindowState>,
 }
 
 impl fmt::Debug for WindowBuilder {
@@ -189,6 +337,16 @@ pub struct WindowAttributes {
   /// See [`Window::set_closable`] for details.
   pub closable: bool,
 
+  /// Whether minimizing the window from the title bar, taskbar or a keyboard shortcut
+  /// emits [`WindowEvent::MinimizeRequested`] instead of minimizing it.
+  ///
+  /// The default is `false`.
+  ///
+  /// See [`Window::set_intercept_minimize`] for details.
+  ///
+  /// [`WindowEvent::MinimizeRequested`]: crate::event::WindowEvent::MinimizeRequested
+  pub intercept_minimize: bool,
+
   /// Whether the window should be set as fullscreen upon creation.
   ///
   /// The default is `None`.
@@ -220,6 +378,13 @@ pub struct WindowAttributes {
   /// The default is `true`.
   pub decorations: bool,
 
+  /// How the title bar of a decorated window is drawn.
+  ///
+  /// The default is [`TitleBarStyle::Visible`].
+  ///
+  /// See [`WindowBuilder::with_title_bar_style`] for details.
+  pub title_bar_style: TitleBarStyle,
+
   /// Whether the window should always be on top of other windows.
   ///
   /// The default is `false`.
@@ -270,12 +435,14 @@ impl Default for WindowAttributes {
       minimizable: true,
       maximizable: true,
       closable: true,
+      intercept_minimize: false,
       title: "tao window".to_owned(),
       maximized: false,
       fullscreen: None,
       visible: true,
       transparent: false,
       decorations: true,
+      title_bar_style: Default::default(),
       always_on_top: false,
       always_on_bottom: false,
       window_icon: None,
@@ -294,6 +461,62 @@ impl WindowBuilder {
     Default::default()
   }
 
+  /// Initializes a new `WindowBuilder` with the options of `config`, and default values for the
+  /// other ones.
+  ///
+  /// The options of the platform sub-structs of `config` other than the one of the target
+  /// platform are ignored. See [`WindowConfig`] for details.
+  pub fn from_config(config: &WindowConfig) -> Self {
+    let mut builder = WindowBuilder::new();
+    if let Some(title) = &config.title {
+      builder.window.title = title.clone();
+    }
+    builder.window.inner_size = config.inner_size;
+    builder.window.position = config.position;
+    if let Some(min_inner_size) = config.min_inner_size {
+      builder = builder.with_min_inner_size(min_inner_size);
+    }
+    if let Some(max_inner_size) = config.max_inner_size {
+      builder = builder.with_max_inner_size(max_inner_size);
+    }
+    if let Some(true) = config.fullscreen {
+      builder.window.fullscreen = Some(Fullscreen::Borderless(None));
+    }
+    if config.theme.is_some() {
+      builder.window.preferred_theme = config.theme;
+    }
+    if let Some(style) = config.title_bar_style {
+      builder.window.title_bar_style = style;
+    }
+
+    let window = &mut builder.window;
+    for (option, attribute) in [
+      (config.resizable, &mut window.resizable),
+      (config.minimizable, &mut window.minimizable),
+      (config.maximizable, &mut window.maximizable),
+      (config.closable, &mut window.closable),
+      (config.intercept_minimize, &mut window.intercept_minimize),
+      (config.maximized, &mut window.maximized),
+      (config.visible, &mut window.visible),
+      (config.transparent, &mut window.transparent),
+      (config.decorations, &mut window.decorations),
+      (config.always_on_top, &mut window.always_on_top),
+      (config.always_on_bottom, &mut window.always_on_bottom),
+      (config.focused, &mut window.focused),
+      (config.content_protection, &mut window.content_protection),
+      (
+        config.visible_on_all_workspaces,
+        &mut window.visible_on_all_workspaces,
+      ),
+    ] {
+      if let Some(value) = option {
+        *attribute = value;
+      }
+    }
+
+    config.platform_options(builder)
+  }
+
   /// Requests the window to be of specific dimensions.
   ///
   /// See [`Window::set_inner_size`] for details.
@@ -397,6 +620,18 @@ impl WindowBuilder {
     self
   }
 
+  /// Sets whether minimizing the window emits [`WindowEvent::MinimizeRequested`] instead.
+  ///
+  /// See [`Window::set_intercept_minimize`] for details.
+  ///
+  /// [`WindowEvent::MinimizeRequested`]: crate::event::WindowEvent::MinimizeRequested
+  /// [`Window::set_intercept_minimize`]: crate::window::Window::set_intercept_minimize
+  #[inline]
+  pub fn with_intercept_minimize(mut self, intercept: bool) -> Self {
+    self.window.intercept_minimize = intercept;
+    self
+  }
+
   /// Requests a specific title for the window.
   ///
   /// See [`Window::set_title`] for details.
@@ -459,6 +694,25 @@ impl WindowBuilder {
     self
   }
 
+  /// Sets how the title bar is drawn, when the window has decorations.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `Transparent` is the same as `Visible`. With `Overlay`, the caption buttons
+  ///   are drawn by the system behind the content, which must leave them transparent, and a menu
+  ///   bar is hidden along with the title bar.
+  /// - **macOS:** Other styles than `Visible` take precedence over
+  ///   `WindowBuilderExtMacOS::with_titlebar_transparent` and
+  ///   `WindowBuilderExtMacOS::with_titlebar_hidden`. `Hidden` also removes the borders.
+  /// - **Linux:** `Transparent` is the same as `Visible`, and `Overlay` the same as `Hidden`,
+  ///   which replaces the title bar with an empty header bar.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_title_bar_style(mut self, style: TitleBarStyle) -> Self {
+    self.window.title_bar_style = style;
+    self
+  }
+
   /// Sets whether or not the window will always be below other windows.
   ///
   /// See [`Window::set_always_on_bottom`] for details.
@@ -544,14 +798,28 @@ impl WindowBuilder {
     self
   }
 
+  /// Restores the geometry of a window saved with [`WindowState::from_window`].
+  ///
+  /// The state is applied when the window is built, against the monitors connected at that time,
+  /// and takes precedence over the position, size, maximized and fullscreen attributes of the builder.
+  /// See [`WindowState`] for details.
+  #[inline]
+  pub fn with_window_state(mut self, state: WindowState) -> WindowBuilder {
+    self.window_state = Some(state);
+    self
+  }
+
   /// Builds the window.
   ///
   /// Possible causes of error include denied permission, incompatible system, and lack of memory.
   #[inline]
   pub fn build<T: 'static>(
-    self,
+    mut self,
     window_target: &EventLoopWindowTarget<T>,
   ) -> Result<Window, OsError> {
+    if let Some(state) = self.window_state.take() {
+      state.restore(&mut self.window, window_target);
+    }
     platform_impl::Window::new(&window_target.p, self.window, self.platform_specific).map(
       |window| {
         window.request_redraw();
@@ -583,6 +851,21 @@ impl Window {
     WindowId(self.window.id())
   }
 
+  /// Returns an identifier of the window derived from `seed`, which stays the same across runs of
+  /// the application, and associates it with this window.
+  ///
+  /// [`StableWindowId::window_id`] returns the id of this window until it's dropped or the same
+  /// stable id is obtained for another window. The application is responsible for choosing a
+  /// different `seed` for each of its windows.
+  #[inline]
+  pub fn stable_id(&self, seed: &str) -> StableWindowId {
+    let stable_id = StableWindowId::from_seed(seed);
+    if let Ok(mut stable_ids) = STABLE_IDS.lock() {
+      stable_ids.insert(stable_id, self.id());
+    }
+    stable_id
+  }
+
   /// Returns the scale factor that can be used to map logical pixels to physical pixels, and vice versa.
   ///
   /// See the [`dpi`](crate::dpi) module for more information.
@@ -603,6 +886,22 @@ impl Window {
     self.window.scale_factor()
   }
 
+  /// Overrides the scale factor of the window, or restores the one of its DPI if `None`.
+  ///
+  /// This is meant for applications which do their own scaling: [`Window::scale_factor`] returns
+  /// the override, logical units given to the window are converted with it, and
+  /// `WindowEvent::ScaleFactorChanged` isn't emitted while it's set. The window keeps its size
+  /// when it's moved to a monitor with another DPI, and when the override is set or cleared.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android:** Unsupported, the platform maps logical units to pixels
+  ///   itself.
+  #[inline]
+  pub fn set_scale_factor_override(&self, scale_factor: Option<f64>) {
+    self.window.set_scale_factor_override(scale_factor)
+  }
+
   /// Emits a `WindowEvent::RedrawRequested` event in the associated event loop after all OS
   /// events have been processed by the event loop.
   ///
@@ -623,6 +922,112 @@ impl Window {
   pub fn request_redraw(&self) {
     self.window.request_redraw()
   }
+
+  /// Aligns the `RedrawRequested` events triggered by [`Window::request_redraw`] with the refresh
+  /// of the display.
+  ///
+  /// When enabled, a requested redraw is delivered once the compositor or monitor starts its next
+  /// frame instead of as soon as possible, so requesting a redraw from every `RedrawRequested`
+  /// event animates at the display's refresh rate without a separate timing thread. Disabled by
+  /// default.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Paced by DWM composition.
+  /// - **macOS:** Paced by a `CVDisplayLink` for the active displays.
+  /// - **Linux:** Paced by the GTK frame clock of the window.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_frame_pacing(&self, enabled: bool) {
+    self.window.set_frame_pacing(enabled)
+  }
+
+  /// Enables the detection of stutters, reported with [`Event::FrameMissed`].
+  ///
+  /// The presentation statistics of the platform are checked each time the window is redrawn, so
+  /// this is meant for windows which redraw continuously, like animations. Disabled by default.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Compares the time between two redraws of the window with the refresh
+  ///   period of its monitor, when the second one was requested right after the first one. Only
+  ///   stutters of this window are reported.
+  /// - **Linux:** Compares when a frame of the GTK frame clock started with when it was presented.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`Event::FrameMissed`]: crate::event::Event::FrameMissed
+  #[inline]
+  pub fn set_jank_detection(&self, enabled: bool) {
+    self.window.set_jank_detection(enabled)
+  }
+
+  /// Runs a nested event loop from the event loop's handler, e.g. while this window is shown as
+  /// a modal dialog, until `handler` sets `ControlFlow::Exit`.
+  ///
+  /// `handler` is called before each wait for events, which `control_flow` controls like it does
+  /// for the event loop, starting with `ControlFlow::Wait`. The platform keeps processing the
+  /// events of all the windows meanwhile, so they are still painted, moved and resized by the
+  /// system instead of freezing, but the events they emit are only dispatched to the event
+  /// loop's handler once the current call to it returns.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, returns right away.
+  #[inline]
+  pub fn run_modal<F: FnMut(&mut ControlFlow)>(&self, mut handler: F) {
+    self.window.run_modal(&mut handler)
+  }
+
+  /// Returns the options of the [`WindowBuilder`] which the platform couldn't honor when creating
+  /// the window.
+  ///
+  /// If there are any, they are also reported once by
+  /// [`WindowEvent::AttributesDowngraded`](crate::event::WindowEvent::AttributesDowngraded).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Always empty.
+  #[inline]
+  pub fn applied_attributes_report(&self) -> AttributesReport {
+    self.window.applied_attributes_report()
+  }
+
+  /// Requests the window to close, as if the user had pressed its close button.
+  ///
+  /// This emits a `WindowEvent::CloseRequested` event, which can be ignored to keep the window
+  /// open. Use [`Window::destroy`] to close the window unconditionally.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn close(&self) {
+    self.window.close()
+  }
+
+  /// Destroys the window without emitting `WindowEvent::CloseRequested`.
+  ///
+  /// A `WindowEvent::Destroyed` event is emitted once the window is gone, after which the
+  /// [`Window`] should be dropped; calling other functions on it has no effect.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn destroy(&self) {
+    self.window.destroy()
+  }
+
+  /// Returns a [`CloseToken`] that can complete a deferred close later, possibly from another
+  /// thread.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, [`CloseToken::proceed`] has no effect.
+  #[inline]
+  pub fn close_token(&self) -> CloseToken {
+    CloseToken(self.window.close_token())
+  }
 }
 
 /// Position and size functions.
@@ -680,6 +1085,39 @@ impl Window {
     self.window.set_outer_position(position.into())
   }
 
+  /// Returns the position of the top-left corner of the window relative to the top-left corner
+  /// of [`Window::current_monitor`], in that monitor's logical coordinates.
+  ///
+  /// See [`MonitorHandle::to_local`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / Linux(Wayland):** Always returns [`NotSupportedError`].
+  #[inline]
+  pub fn monitor_relative_position(&self) -> Result<LogicalPosition<f64>, NotSupportedError> {
+    let position = self.outer_position()?;
+    let monitor = self.current_monitor().ok_or_else(NotSupportedError::new)?;
+    Ok(monitor.to_local(position))
+  }
+
+  /// Moves the top-left corner of the window to `position`, relative to the top-left corner of
+  /// `monitor`. A logical `position` is scaled with the scale factor of `monitor` rather than the
+  /// one of the monitor the window is on.
+  ///
+  /// See [`MonitorHandle::to_global`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / Linux(Wayland):** Unsupported.
+  #[inline]
+  pub fn set_monitor_relative_position<P: Into<Position>>(
+    &self,
+    monitor: &MonitorHandle,
+    position: P,
+  ) {
+    self.set_outer_position(monitor.to_global(position))
+  }
+
   /// Returns the physical size of the window's client area.
   ///
   /// The client area is the content of the window, excluding the title bar and borders.
@@ -759,6 +1197,9 @@ impl Window {
   ///
   /// ## Platform-specific
   ///
+  /// - **Windows / Linux:** The title is applied when the event loop processes its next events,
+  ///   so calling this several times in a row, e.g. to show a progress percentage every frame,
+  ///   updates the title bar once. [`Window::title`] returns the last title set in the meantime.
   /// - **iOS / Android:** Unsupported.
   #[inline]
   pub fn set_title(&self, title: &str) {
@@ -807,6 +1248,49 @@ impl Window {
     self.window.is_focused()
   }
 
+  /// Brings the window to front without giving it focus.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn raise(&self) {
+    self.window.raise()
+  }
+
+  /// Gives the window keyboard focus without changing its stacking order.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Most window managers also raise the window.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn focus_without_raise(&self) {
+    self.window.focus_without_raise()
+  }
+
+  /// Sets whether the window can take focus, see [`FocusPolicy`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_focus_policy(&self, policy: FocusPolicy) {
+    self.window.set_focus_policy(policy)
+  }
+
+  /// Sets which file drops the window accepts, see [`DropPolicy`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Has no effect if drag and drop was disabled with
+  ///   `WindowBuilderExtWindows::with_drag_and_drop`.
+  /// - **Linux / iOS / Android:** Unsupported, tao doesn't handle file drops.
+  #[inline]
+  pub fn set_drop_policy(&self, policy: DropPolicy) {
+    self.window.set_drop_policy(policy)
+  }
+
   /// Sets whether the window is resizable or not.
   ///
   /// Note that making the window unresizable doesn't exempt you from handling `Resized`, as that event can still be
@@ -865,6 +1349,57 @@ impl Window {
     self.window.set_closable(closable)
   }
 
+  /// Sets whether the window accepts user input.
+  ///
+  /// A disabled window stays visible but ignores mouse and keyboard input,
+  /// which is useful to show a "busy" state or to block an owner window while a dialog is open.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The window content is also grayed out.
+  /// - **Android:** Unsupported.
+  #[inline]
+  pub fn set_enabled(&self, enabled: bool) {
+    self.window.set_enabled(enabled)
+  }
+
+  /// Sets whether minimizing the window from the title bar, taskbar or a keyboard shortcut
+  /// emits [`WindowEvent::MinimizeRequested`] instead of minimizing it.
+  ///
+  /// Like [`WindowEvent::CloseRequested`], the request can then be ignored, handled differently
+  /// (e.g. by hiding the window to the tray) or approved by calling [`Window::set_minimized`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The window manager minimizes the window before tao is notified,
+  ///   so the window is restored right away, which may cause a brief flicker.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::MinimizeRequested`]: crate::event::WindowEvent::MinimizeRequested
+  /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+  #[inline]
+  pub fn set_intercept_minimize(&self, intercept: bool) {
+    self.window.set_intercept_minimize(intercept)
+  }
+
+  /// Sets which [`SystemGestures`] the OS applies to the window, all of them being enabled by
+  /// default.
+  ///
+  /// This is meant for specialized windows, like a toolbar torn off from its main window, that
+  /// should not be snapped or maximized by accident.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Disabling [`SystemGestures::SNAP`] or [`SystemGestures::SHAKE`] makes tao move
+  ///   the window itself when its title bar is dragged, instead of the system move loop, except
+  ///   when the window is maximized.
+  /// - **macOS:** Only [`SystemGestures::TITLEBAR_DOUBLE_CLICK`] is supported.
+  /// - **Linux / iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_system_gestures_enabled(&self, gestures: SystemGestures) {
+    self.window.set_system_gestures_enabled(gestures)
+  }
+
   /// Sets the window to minimized or back
   ///
   /// ## Platform-specific
@@ -955,6 +1490,16 @@ impl Window {
     self.window.is_closable()
   }
 
+  /// Gets whether the window accepts user input.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, always returns `true`.
+  #[inline]
+  pub fn is_enabled(&self) -> bool {
+    self.window.is_enabled()
+  }
+
   /// Gets the window's current decoration state.
   ///
   /// ## Platform-specific
@@ -976,7 +1521,7 @@ impl Window {
   ///
   ///   `Fullscreen::Borderless` provides a borderless fullscreen window on a
   ///   separate space. This is the idiomatic way for fullscreen games to work
-  ///   on macOS. See `WindowExtMacOs::set_simple_fullscreen` if
+  ///   on macOS. See `WindowExtMacOS::set_fullscreen_mode` if
   ///   separate spaces are not preferred.
   ///
   ///   The dock and the menu bar are always disabled in fullscreen mode.
@@ -1047,26 +1592,154 @@ impl Window {
     self.window.set_window_icon(window_icon)
   }
 
-  /// Sets location of IME candidate box in client area coordinates relative to the top left.
+  /// Cycles the window and taskbar icons through `frames`, showing each one for `interval`, for
+  /// example to animate a recording indicator.
+  ///
+  /// While the frames are cycling they take precedence over [`Window::set_window_icon`]. Passing
+  /// an empty list stops the animation and restores the icons set before.
   ///
   /// ## Platform-specific
   ///
-  /// - **iOS / Android:** Unsupported.
+  /// - **iOS / Android / macOS:** Unsupported.
   #[inline]
-  pub fn set_ime_position<P: Into<Position>>(&self, position: P) {
-    self.window.set_ime_position(position.into())
+  pub fn set_window_icon_frames(&self, frames: Vec<Icon>, interval: Duration) {
+    self.window.set_window_icon_frames(frames, interval)
   }
 
-  /// Sets the taskbar progress state.
+  /// Sets location of IME candidate box in client area coordinates relative to the top left.
+  ///
+  /// Same as [`Window::set_ime_cursor_area`] with an empty area.
   ///
   /// ## Platform-specific
   ///
-  /// - **Linux / macOS**: Progress bar is app-wide and not specific to this window. Only supported desktop environments with `libunity` (e.g. GNOME).
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn set_progress_bar(&self, _progress: ProgressBarState) {
-    #[cfg(any(
-      windows,
+  pub fn set_ime_position<P: Into<Position>>(&self, position: P) {
+    self
+      .window
+      .set_ime_cursor_area(position.into(), PhysicalSize::new(0, 0).into())
+  }
+
+  /// Sets the area of the text cursor, or of the text being composed, in client area coordinates
+  /// relative to the top left. The IME places its candidate window next to the area without
+  /// covering it.
+  ///
+  /// Call it whenever the cursor moves, e.g. on each [`Ime::Preedit`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only applies to IMEs using the IMM32 API, which most do.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`Ime::Preedit`]: crate::event::Ime::Preedit
+  #[inline]
+  pub fn set_ime_cursor_area<P: Into<Position>, S: Into<Size>>(&self, position: P, size: S) {
+    self
+      .window
+      .set_ime_cursor_area(position.into(), size.into())
+  }
+
+  /// Sets whether the IME of the window is allowed, `true` by default.
+  ///
+  /// Disallow it while keys control the application rather than type text, e.g. during gameplay,
+  /// so that they don't start compositions. Disallowing it cancels the current composition. Keys
+  /// still emit [`WindowEvent::ReceivedImeText`] with the character they type.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::ReceivedImeText`]: crate::event::WindowEvent::ReceivedImeText
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    self.window.set_ime_allowed(allowed)
+  }
+
+  /// Sets the kind of text typed in the window, for the IME and the touch keyboard to adapt to it,
+  /// e.g. with a numeric layout. [`ImePurpose::Normal`] by default.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Sets the input scope of the window, which the touch keyboard follows.
+  /// - **Linux:** Sets the input purpose of the GTK input method of the window.
+  /// - **iOS:** Sets the type of the keyboard shown by [`Window::show_touch_keyboard`], with
+  ///   secure entry for [`ImePurpose::Password`].
+  /// - **macOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+    self.window.set_ime_purpose(purpose)
+  }
+
+  /// Adds `accelerator` to the window, so that pressing it while the window is focused emits
+  /// [`WindowEvent::Accelerator`] with the returned id, even when a child control, such as a
+  /// webview, has the keyboard focus. Menu shortcuts are usually implemented with accelerators.
+  ///
+  /// The key press which triggered the accelerator isn't reported as a
+  /// [`WindowEvent::KeyboardInput`].
+  ///
+  /// Fails with [`ExternalError::Os`] if `key` doesn't exist on the current keyboard layout.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Translated with `TranslateAcceleratorW` by the event loop. Accelerators with
+  ///   [`ModifiersState::SUPER`] fail with [`ExternalError::NotSupported`].
+  /// - **macOS:** Handled as a key equivalent of the window, before the main menu gets the key.
+  /// - **Linux:** Uses a `GtkAccelGroup` of the window. The key press is still reported as a
+  ///   [`WindowEvent::KeyboardInput`].
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::Accelerator`]: crate::event::WindowEvent::Accelerator
+  /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+  /// [`ModifiersState::SUPER`]: crate::keyboard::ModifiersState::SUPER
+  #[inline]
+  pub fn add_accelerator(&self, accelerator: Accelerator) -> Result<AcceleratorId, ExternalError> {
+    let id = AcceleratorId::next();
+    self.window.add_accelerator(id, accelerator)?;
+    Ok(id)
+  }
+
+  /// Removes the accelerator `id` from the window. Does nothing if it was removed already.
+  #[inline]
+  pub fn remove_accelerator(&self, id: AcceleratorId) {
+    self.window.remove_accelerator(id)
+  }
+
+  /// Shows the system touch keyboard, for apps drawing their own text fields.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Uses the `InputPane` of the window, available since Windows 10 1803.
+  /// - **Linux:** Only supported with on-screen keyboards implementing the `sm.puri.OSK0` D-Bus
+  ///   interface, such as squeekboard.
+  /// - **iOS:** Makes the view of the window the first responder. The typed text is received with
+  ///   [`WindowEvent::ReceivedImeText`], backspace as `"\u{8}"`.
+  /// - **Android:** Shows the soft input of the activity.
+  /// - **macOS:** Always returns an [`ExternalError::NotSupported`].
+  ///
+  /// [`WindowEvent::ReceivedImeText`]: crate::event::WindowEvent::ReceivedImeText
+  #[inline]
+  pub fn show_touch_keyboard(&self) -> Result<(), ExternalError> {
+    self.window.set_touch_keyboard_visible(true)
+  }
+
+  /// Hides the system touch keyboard shown with [`Window::show_touch_keyboard`].
+  ///
+  /// See [`Window::show_touch_keyboard`] for platform-specific details.
+  #[inline]
+  pub fn hide_touch_keyboard(&self) -> Result<(), ExternalError> {
+    self.window.set_touch_keyboard_visible(false)
+  }
+
+  /// Sets the taskbar progress state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux / macOS**: Progress bar is app-wide and not specific to this window. Only supported desktop environments with `libunity` (e.g. GNOME).
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_progress_bar(&self, _progress: ProgressBarState) {
+    #[cfg(any(
+      windows,
       target_os = "linux",
       target_os = "dragonfly",
       target_os = "freebsd",
@@ -1123,6 +1796,67 @@ impl Window {
     #[cfg(any(target_os = "macos", target_os = "linux"))]
     self.window.set_visible_on_all_workspaces(visible)
   }
+
+  /// Gives up ownership of the native window, returning a handle that another process, typically
+  /// a sandboxed one, can embed its own window into with [`Window::adopt_from_handle`].
+  ///
+  /// The native window isn't destroyed when the [`Window`] is consumed. It stays open, and is still
+  /// driven by this process' event loop, until the process exits or the window is closed. If an
+  /// error is returned, the window is dropped as usual.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The window doesn't report keyboard input and
+  ///   [`WindowEvent::RawMouseMotion`] anymore.
+  /// - **macOS:** Unsupported, see [`Window::adopt_from_handle`].
+  /// - **Linux(Wayland) / iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::RawMouseMotion`]: crate::event::WindowEvent::RawMouseMotion
+  pub fn into_sendable_handle(self) -> Result<SendableWindowHandle, ExternalError> {
+    let raw = self.window.sendable_handle()?;
+    self.release_stable_id();
+    // Moves the platform window out, for it to be released without closing the native window.
+    let this = ManuallyDrop::new(self);
+    let window = unsafe { ptr::read(&this.window) };
+    window.detach();
+    Ok(SendableWindowHandle(raw))
+  }
+
+  fn release_stable_id(&self) {
+    let id = self.id();
+    if let Ok(mut stable_ids) = STABLE_IDS.lock() {
+      stable_ids.retain(|_, window_id| *window_id != id);
+    }
+  }
+
+  /// Embeds this window at the top-left corner of the window behind `handle`, which is usually
+  /// owned by another process that obtained it with [`Window::into_sendable_handle`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The window becomes a `WS_CHILD` window and leaves the taskbar. Both processes'
+  ///   input queues are attached by the system while the window is embedded.
+  /// - **Linux:** The window is reparented by the event loop, a handle which isn't a window is
+  ///   only reported in the log.
+  /// - **macOS:** Unsupported. Remote layers need the embedded process to send an id back to the
+  ///   host process, which a one-way handle can't carry.
+  /// - **Linux(Wayland) / iOS / Android:** Unsupported.
+  #[inline]
+  pub fn adopt_from_handle(&self, handle: SendableWindowHandle) -> Result<(), ExternalError> {
+    self.window.adopt_from_handle(handle.0)
+  }
+
+  /// Reserves `rect` of the client area as a native child surface, see [`SurfaceRegion`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The surface is created by the event loop, its window handle is unavailable until
+  ///   then. Surfaces on Wayland have no window handle of their own.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn create_surface_region(&self, rect: Rect) -> Result<SurfaceRegion, ExternalError> {
+    self.window.create_surface_region(rect).map(SurfaceRegion)
+  }
 }
 
 /// Cursor functions.
@@ -1137,6 +1871,49 @@ impl Window {
     self.window.set_cursor_icon(cursor);
   }
 
+  /// Modifies the cursor of the window to a custom image.
+  ///
+  /// The image of `cursor` that best matches the window's scale factor and the user's cursor
+  /// size setting is used, and picked again when either changes. Calling
+  /// [`Window::set_cursor_icon`] switches back to a standard cursor.
+  ///
+  /// An animated cursor starts over from its first frame.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `.cur` and `.ani` files can be loaded with
+  ///   [`CustomCursorExtWindows::from_path`](crate::platform::windows::CustomCursorExtWindows::from_path).
+  /// - **macOS:** The image is picked by the system from all images of `cursor`.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+    self.window.set_custom_cursor(cursor);
+  }
+
+  /// Modifies the cursor of the window to a [`CursorIcon`] or a [`CustomCursor`], see
+  /// [`Window::set_cursor_icon`] and [`Window::set_custom_cursor`].
+  ///
+  /// ```no_run
+  /// # use tao::{event_loop::EventLoop, window::{CursorIcon, CustomCursor, Window}};
+  /// # let event_loop = EventLoop::new();
+  /// # let window = Window::new(&event_loop).unwrap();
+  /// // A 16x16 white square, pointing at its center.
+  /// let brush = CustomCursor::from_rgba(vec![255; 16 * 16 * 4], 16, 16, 8, 8).unwrap();
+  /// window.set_cursor(brush);
+  /// window.set_cursor(CursorIcon::Crosshair);
+  /// ```
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_cursor(&self, cursor: impl Into<Cursor>) {
+    match cursor.into() {
+      Cursor::Icon(icon) => self.set_cursor_icon(icon),
+      Cursor::Custom(cursor) => self.set_custom_cursor(cursor),
+    }
+  }
+
   /// Changes the position of the cursor in window coordinates.
   ///
   /// ## Platform-specific
@@ -1147,18 +1924,89 @@ impl Window {
     self.window.set_cursor_position(position.into())
   }
 
-  /// Grabs the cursor, preventing it from leaving the window.
+  /// Grabs the cursor, keeping it inside the window with [`CursorGrabMode::Confined`] or in place
+  /// with [`CursorGrabMode::Locked`], or releases it with [`CursorGrabMode::None`].
   ///
-  /// There's no guarantee that the cursor will be hidden. You should
+  /// The grab is released while the window isn't focused, and applied again when it's focused
+  /// back. There's no guarantee that the cursor will be hidden. You should
   /// hide it by yourself if you want so.
   ///
   /// ## Platform-specific
   ///
-  /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+  /// - **macOS:** `Confined` moves the cursor back into the window when it leaves it, so it may
+  ///   show outside of it for a moment. `Locked` stays applied while the window isn't focused.
+  /// - **Linux:** Only supported on X11.
   /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-    self.window.set_cursor_grab(grab)
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    self.window.set_cursor_grab(mode)
+  }
+
+  /// Enables [`WindowEvent::RawMouseMotion`], the unaccelerated relative motion of the mouse,
+  /// while the window is focused. Disabled by default.
+  ///
+  /// It doesn't depend on the [`DeviceEvent`](crate::event::DeviceEvent)s the event loop is
+  /// subscribed to, so it can be used in a game's window without reporting the input of all
+  /// devices to the whole application. Combine it with [`Window::set_cursor_grab`] for mouse look.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The deltas are accelerated.
+  /// - **Linux:** Only supported on X11.
+  /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  ///
+  /// [`WindowEvent::RawMouseMotion`]: crate::event::WindowEvent::RawMouseMotion
+  #[inline]
+  pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), ExternalError> {
+    self.window.set_raw_mouse_motion(enabled)
+  }
+
+  /// Grabs the keyboard, so that system shortcuts like Alt+Tab or Cmd+Tab are delivered to the
+  /// window as regular [`WindowEvent::KeyboardInput`] events instead of being handled by the
+  /// system while the window is focused.
+  ///
+  /// This is mostly useful for remote desktop clients, virtual machine viewers and kiosk or exam
+  /// applications.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Captures the Windows key, Alt+Tab, Alt+Space, Alt+Esc, Ctrl+Esc,
+  ///   Ctrl+Shift+Esc and the media and volume keys while the window is in the foreground.
+  ///   Ctrl+Alt+Del can't be captured.
+  /// - **macOS:** Takes the key events and the media keys from the system hotkeys with an event
+  ///   tap while the window is key, which needs the application to be allowed to monitor input.
+  ///   An [`ExternalError::Os`] is returned otherwise. The media keys only reach the
+  ///   [message hook](crate::event_loop::EventLoopBuilder::with_msg_hook) as `NSSystemDefined`
+  ///   events.
+  /// - **Linux:** Grabs the keyboard of the default seat. On Wayland, this relies on the
+  ///   compositor supporting the keyboard shortcuts inhibit protocol.
+  /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  ///
+  /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    self.window.set_keyboard_grab(grab)
+  }
+
+  /// Overrides the pointer acceleration while the cursor is grabbed with [`Window::set_cursor_grab`]
+  /// and the window is focused, so that cursor motion is consistent across machines.
+  ///
+  /// `None` restores the system settings.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** [`AccelerationProfile::Flat`] moves the cursor by the raw mouse motion while
+  ///   the window is in the foreground, one pixel per device count, regardless of the "Enhance
+  ///   pointer precision" and pointer speed settings, which are left untouched.
+  /// - **macOS / Linux / iOS / Android:** Unsupported, always returns an
+  ///   [`ExternalError::NotSupported`]. The acceleration of these systems can only be changed for
+  ///   every application at once, or not at all on Wayland.
+  #[inline]
+  pub fn set_pointer_acceleration(
+    &self,
+    profile: Option<AccelerationProfile>,
+  ) -> Result<(), ExternalError> {
+    self.window.set_pointer_acceleration(profile)
   }
 
   /// Modifies the cursor's visibility.
@@ -1260,11 +2108,7 @@ impl Window {
   /// **iOS:** Can only be called on the main thread.
   #[inline]
   pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
-    self
-      .window
-      .available_monitors()
-      .into_iter()
-      .map(|inner| MonitorHandle { inner })
+    monitor::sorted(self.window.available_monitors())
   }
 
   /// Returns the primary monitor of the system.
@@ -1387,6 +2231,26 @@ impl Default for CursorIcon {
   }
 }
 
+/// A standard or custom cursor, see [`Window::set_cursor`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+  Icon(CursorIcon),
+  Custom(CustomCursor),
+}
+
+impl From<CursorIcon> for Cursor {
+  fn from(icon: CursorIcon) -> Self {
+    Cursor::Icon(icon)
+  }
+}
+
+impl From<CustomCursor> for Cursor {
+  fn from(cursor: CustomCursor) -> Self {
+    Cursor::Custom(cursor)
+  }
+}
+
 /// Fullscreen modes.
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant)]
@@ -1396,10 +2260,48 @@ pub enum Fullscreen {
 
   /// Providing `None` to `Borderless` will fullscreen on the current monitor.
   Borderless(Option<MonitorHandle>),
+
+  /// Covers several monitors with a single borderless window, for video walls or multi-monitor
+  /// simulators.
+  ///
+  /// The window spans the bounding rectangle of the monitors, which should therefore be adjacent.
+  /// Monitors with differing scale factors are supported, but the window reports a single scale
+  /// factor, usually the one of the monitor containing most of it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Spans all monitors regardless of the ones given, and only on X11.
+  /// - **iOS / Android:** Unsupported.
+  Spanning(Vec<MonitorHandle>),
+}
+
+/// An option of a [`WindowBuilder`] which the platform couldn't honor.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeDowngrade {
+  /// The window is opaque, as there is no compositor to blend it with what's behind it.
+  Transparency,
+  /// The window isn't fullscreen, as `Fullscreen::Exclusive` isn't supported.
+  ExclusiveFullscreen,
+}
+
+/// The options of a [`WindowBuilder`] which the platform couldn't honor when creating a window,
+/// see [`Window::applied_attributes_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttributesReport {
+  pub downgrades: Vec<AttributeDowngrade>,
+}
+
+impl AttributesReport {
+  /// Returns whether every option was honored.
+  pub fn is_empty(&self) -> bool {
+    self.downgrades.is_empty()
+  }
 }
 
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Theme {
   Light,
   Dark,
@@ -1411,6 +2313,85 @@ impl Default for Theme {
   }
 }
 
+/// How the title bar of a window is drawn, see [`WindowBuilder::with_title_bar_style`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TitleBarStyle {
+  /// The title bar of the system.
+  Visible,
+  /// The title bar takes the background of the window, with its title and buttons.
+  Transparent,
+  /// The content extends below the title bar, which only shows its buttons, inset in the top left
+  /// corner on macOS.
+  Overlay,
+  /// No title bar, the content takes its place.
+  Hidden,
+}
+
+impl Default for TitleBarStyle {
+  fn default() -> Self {
+    TitleBarStyle::Visible
+  }
+}
+
+/// Appearance preferences of the system, beyond its [`Theme`].
+///
+/// See [`EventLoopWindowTarget::system_appearance`] and [`Event::SystemAppearanceChanged`].
+///
+/// [`EventLoopWindowTarget::system_appearance`]: crate::event_loop::EventLoopWindowTarget::system_appearance
+/// [`Event::SystemAppearanceChanged`]: crate::event::Event::SystemAppearanceChanged
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SystemAppearance {
+  /// The accent color chosen by the user, as RGBA.
+  pub accent_color: Option<[u8; 4]>,
+  /// Whether a high contrast mode is on.
+  pub high_contrast: bool,
+  /// Whether the user asked for fewer animations.
+  pub reduce_motion: bool,
+  /// Whether the user asked for translucent surfaces to be made opaque.
+  pub reduce_transparency: bool,
+}
+
+/// How the cursor is grabbed, see [`Window::set_cursor_grab`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CursorGrabMode {
+  /// The cursor moves freely.
+  None,
+  /// The cursor can't leave the inner area of the window.
+  Confined,
+  /// The cursor stays where it is, its motion is only reported by
+  /// [`DeviceEvent::MouseMotion`](crate::event::DeviceEvent::MouseMotion).
+  Locked,
+}
+
+/// Kind of text typed in a window, see [`Window::set_ime_purpose`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImePurpose {
+  /// Any text.
+  Normal,
+  /// A password, which the IME shouldn't show, learn nor suggest.
+  Password,
+  /// An email address.
+  Email,
+  /// A number.
+  Number,
+  /// A URL.
+  Url,
+}
+
+impl Default for ImePurpose {
+  fn default() -> Self {
+    ImePurpose::Normal
+  }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UserAttentionType {
@@ -1430,6 +2411,490 @@ impl Default for UserAttentionType {
   }
 }
 
+/// Whether a window can take focus.
+///
+/// See [`Window::set_focus_policy`] for details.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FocusPolicy {
+  /// The window takes focus when it is clicked or when focus is requested.
+  Normal,
+  /// The window never takes focus, not even when clicked, like a notification or an on-screen
+  /// keyboard. It can still be raised with [`Window::raise`].
+  Never,
+}
+
+impl Default for FocusPolicy {
+  fn default() -> Self {
+    FocusPolicy::Normal
+  }
+}
+
+/// Which file drops a window accepts.
+///
+/// See [`Window::set_drop_policy`] for details.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DropPolicy {
+  /// Every file drop is accepted and reported with [`WindowEvent::HoveredFile`] and
+  /// [`WindowEvent::DroppedFile`].
+  ///
+  /// [`WindowEvent::HoveredFile`]: crate::event::WindowEvent::HoveredFile
+  /// [`WindowEvent::DroppedFile`]: crate::event::WindowEvent::DroppedFile
+  AcceptAll,
+  /// A drop is only accepted if each of its files matches one of the given types. A type is either
+  /// an extension such as `"png"`, or a MIME type such as `"image/png"` or `"image/*"` which is
+  /// matched against the extensions of common file types.
+  AcceptTypes(Vec<String>),
+  /// Every drop is refused.
+  Disabled,
+  /// The window stops being a drop target, so drops go to the child views that are, such as an
+  /// embedded webview. This also lets the application register its own drop target.
+  Forward,
+}
+
+impl Default for DropPolicy {
+  fn default() -> Self {
+    DropPolicy::AcceptAll
+  }
+}
+
+#[allow(dead_code)] // Not used on every platform
+impl DropPolicy {
+  /// Whether a drop of `paths` is accepted.
+  pub(crate) fn accepts<P: AsRef<std::path::Path>>(&self, paths: &[P]) -> bool {
+    match self {
+      DropPolicy::AcceptAll => true,
+      DropPolicy::AcceptTypes(types) => paths.iter().all(|path| {
+        let extension = path
+          .as_ref()
+          .extension()
+          .and_then(|extension| extension.to_str())
+          .unwrap_or_default()
+          .to_ascii_lowercase();
+        let mime = mime_type(&extension);
+        types.iter().any(|ty| match ty.split_once('/') {
+          Some((top_level, "*")) => mime.map_or(false, |mime| {
+            mime.split('/').next() == Some(top_level.to_ascii_lowercase().as_str())
+          }),
+          Some(_) => mime.map_or(false, |mime| ty.eq_ignore_ascii_case(mime)),
+          None => ty.trim_start_matches('.').eq_ignore_ascii_case(&extension),
+        })
+      }),
+      DropPolicy::Disabled | DropPolicy::Forward => false,
+    }
+  }
+}
+
+/// MIME type of common file extensions, for [`DropPolicy::AcceptTypes`].
+fn mime_type(extension: &str) -> Option<&'static str> {
+  Some(match extension {
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "bmp" => "image/bmp",
+    "svg" => "image/svg+xml",
+    "ico" => "image/x-icon",
+    "tif" | "tiff" => "image/tiff",
+    "avif" => "image/avif",
+    "heic" => "image/heic",
+    "mp4" => "video/mp4",
+    "webm" => "video/webm",
+    "mov" => "video/quicktime",
+    "mkv" => "video/x-matroska",
+    "avi" => "video/x-msvideo",
+    "mp3" => "audio/mpeg",
+    "wav" => "audio/wav",
+    "ogg" => "audio/ogg",
+    "flac" => "audio/flac",
+    "m4a" => "audio/mp4",
+    "txt" => "text/plain",
+    "html" | "htm" => "text/html",
+    "css" => "text/css",
+    "csv" => "text/csv",
+    "md" => "text/markdown",
+    "js" => "text/javascript",
+    "json" => "application/json",
+    "xml" => "application/xml",
+    "pdf" => "application/pdf",
+    "zip" => "application/zip",
+    _ => return None,
+  })
+}
+
+/// Pointer acceleration profiles that can be applied while the cursor is grabbed, on Windows only.
+///
+/// See [`Window::set_pointer_acceleration`] for details.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AccelerationProfile {
+  /// The cursor moves proportionally to the physical device motion, without any acceleration curve.
+  Flat,
+}
+
+bitflags! {
+  /// Behaviors the OS adds to windows, which can be turned off with
+  /// [`Window::set_system_gestures_enabled`].
+  pub struct SystemGestures: u32 {
+    /// Snapping the window to the edges of the screen, or maximizing it, when it is dragged there.
+    const SNAP = 1 << 0;
+    /// Minimizing all the other windows when this one is shaken while dragged.
+    const SHAKE = 1 << 1;
+    /// Maximizing or minimizing the window when its title bar is double-clicked.
+    const TITLEBAR_DOUBLE_CLICK = 1 << 2;
+  }
+}
+
+impl Default for SystemGestures {
+  fn default() -> Self {
+    Self::all()
+  }
+}
+
+/// A snapshot of a window's geometry, meant to be persisted when the app exits and restored with
+/// [`WindowBuilder::with_window_state`] on the next launch.
+///
+/// The position is stored relative to the monitor the window was on and, like the size, in logical
+/// pixels. When restoring, the window is placed back on that monitor, scaled to its current scale
+/// factor and moved inside its bounds if it would otherwise be out of sight. If the monitor is no
+/// longer connected, the window is centered on the primary monitor instead.
+///
+/// The position and size of a maximized window are the maximized ones, since tao cannot query the
+/// geometry the window would be restored to.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowState {
+  /// The name of the monitor the window was on.
+  pub monitor: Option<String>,
+  /// The outer position of the window, relative to the top-left corner of its monitor.
+  pub position: LogicalPosition<f64>,
+  /// The inner size of the window.
+  pub size: LogicalSize<f64>,
+  /// Whether the window was maximized.
+  pub maximized: bool,
+  /// Whether the window was fullscreen.
+  pub fullscreen: bool,
+}
+
+impl WindowState {
+  /// Captures the current geometry of `window`.
+  pub fn from_window(window: &Window) -> Self {
+    let monitor = window.current_monitor();
+    let scale_factor = window.scale_factor();
+    let origin = monitor.as_ref().map(|m| m.position()).unwrap_or_default();
+    let position = window.outer_position().unwrap_or(origin);
+
+    WindowState {
+      monitor: monitor.and_then(|m| m.name()),
+      position: PhysicalPosition::new(position.x - origin.x, position.y - origin.y)
+        .to_logical(scale_factor),
+      size: window.inner_size().to_logical(scale_factor),
+      maximized: window.is_maximized(),
+      fullscreen: window.fullscreen().is_some(),
+    }
+  }
+
+  fn restore<T: 'static>(
+    &self,
+    attributes: &mut WindowAttributes,
+    window_target: &EventLoopWindowTarget<T>,
+  ) {
+    attributes.maximized = self.maximized;
+
+    let saved_monitor = self.monitor.as_ref().and_then(|name| {
+      window_target
+        .available_monitors()
+        .find(|m| m.name().as_ref() == Some(name))
+    });
+    let monitor_disappeared = saved_monitor.is_none();
+    let monitor = match saved_monitor.or_else(|| window_target.primary_monitor()) {
+      Some(monitor) => monitor,
+      None => {
+        attributes.inner_size = Some(self.size.into());
+        return;
+      }
+    };
+
+    let scale_factor = monitor.scale_factor();
+    let bounds = monitor.size();
+    let size: PhysicalSize<u32> = self.size.to_physical(scale_factor);
+    let size = PhysicalSize::new(size.width.min(bounds.width), size.height.min(bounds.height));
+    let max_x = (bounds.width - size.width) as i32;
+    let max_y = (bounds.height - size.height) as i32;
+    let offset = if monitor_disappeared {
+      PhysicalPosition::new(max_x / 2, max_y / 2)
+    } else {
+      let offset: PhysicalPosition<i32> = self.position.to_physical(scale_factor);
+      PhysicalPosition::new(offset.x.clamp(0, max_x), offset.y.clamp(0, max_y))
+    };
+    let origin = monitor.position();
+
+    attributes.position =
+      Some(PhysicalPosition::new(origin.x + offset.x, origin.y + offset.y).into());
+    attributes.inner_size = Some(size.into());
+    if self.fullscreen {
+      attributes.fullscreen = Some(Fullscreen::Borderless(Some(monitor)));
+    }
+  }
+}
+
+/// Options of a [`WindowBuilder`] described as data, to be deserialized from a manifest like a
+/// JSON or TOML file with the `serde` feature, and applied with [`WindowBuilder::from_config`].
+///
+/// Every option is optional and leaves the default of the builder when `None`, so a manifest
+/// only needs to list the ones it changes. The options which can't be described as data, like
+/// icons or parent windows, have to be set on the builder returned by `from_config`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WindowConfig {
+  /// See [`WindowBuilder::with_title`].
+  pub title: Option<String>,
+  /// See [`WindowBuilder::with_inner_size`].
+  pub inner_size: Option<Size>,
+  /// See [`WindowBuilder::with_min_inner_size`].
+  pub min_inner_size: Option<Size>,
+  /// See [`WindowBuilder::with_max_inner_size`].
+  pub max_inner_size: Option<Size>,
+  /// See [`WindowBuilder::with_position`].
+  pub position: Option<Position>,
+  /// See [`WindowBuilder::with_resizable`].
+  pub resizable: Option<bool>,
+  /// See [`WindowBuilder::with_minimizable`].
+  pub minimizable: Option<bool>,
+  /// See [`WindowBuilder::with_maximizable`].
+  pub maximizable: Option<bool>,
+  /// See [`WindowBuilder::with_closable`].
+  pub closable: Option<bool>,
+  /// See [`WindowBuilder::with_intercept_minimize`].
+  pub intercept_minimize: Option<bool>,
+  /// Whether the window is created in borderless fullscreen on the current monitor.
+  pub fullscreen: Option<bool>,
+  /// See [`WindowBuilder::with_maximized`].
+  pub maximized: Option<bool>,
+  /// See [`WindowBuilder::with_visible`].
+  pub visible: Option<bool>,
+  /// See [`WindowBuilder::with_transparent`].
+  pub transparent: Option<bool>,
+  /// See [`WindowBuilder::with_decorations`].
+  pub decorations: Option<bool>,
+  /// See [`WindowBuilder::with_title_bar_style`].
+  pub title_bar_style: Option<TitleBarStyle>,
+  /// See [`WindowBuilder::with_always_on_top`].
+  pub always_on_top: Option<bool>,
+  /// See [`WindowBuilder::with_always_on_bottom`].
+  pub always_on_bottom: Option<bool>,
+  /// See [`WindowBuilder::with_theme`].
+  pub theme: Option<Theme>,
+  /// See [`WindowBuilder::with_focused`].
+  pub focused: Option<bool>,
+  /// See [`WindowBuilder::with_content_protection`].
+  pub content_protection: Option<bool>,
+  /// See [`WindowBuilder::with_visible_on_all_workspaces`].
+  pub visible_on_all_workspaces: Option<bool>,
+  /// Options only applied on Windows.
+  pub windows: WindowConfigWindows,
+  /// Options only applied on macOS.
+  pub macos: WindowConfigMacOS,
+  /// Options only applied on Linux and the BSDs.
+  pub linux: WindowConfigLinux,
+  /// Options only applied on iOS.
+  pub ios: WindowConfigIOS,
+}
+
+/// Options of `WindowBuilderExtWindows` in a [`WindowConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WindowConfigWindows {
+  pub no_redirection_bitmap: Option<bool>,
+  pub drag_and_drop: Option<bool>,
+  pub skip_taskbar: Option<bool>,
+  pub window_classname: Option<String>,
+  pub undecorated_shadow: Option<bool>,
+  pub rtl: Option<bool>,
+}
+
+/// Options of `WindowBuilderExtMacOS` in a [`WindowConfig`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WindowConfigMacOS {
+  pub movable_by_window_background: Option<bool>,
+  pub titlebar_transparent: Option<bool>,
+  pub title_hidden: Option<bool>,
+  pub titlebar_hidden: Option<bool>,
+  pub titlebar_buttons_hidden: Option<bool>,
+  pub fullsize_content_view: Option<bool>,
+  pub resize_increments: Option<LogicalSize<f64>>,
+  pub disallow_hidpi: Option<bool>,
+  pub has_shadow: Option<bool>,
+  pub traffic_light_inset: Option<Position>,
+  pub automatic_window_tabbing: Option<bool>,
+  pub tabbing_identifier: Option<String>,
+  pub skip_taskbar: Option<bool>,
+}
+
+/// Options of `WindowBuilderExtUnix` in a [`WindowConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WindowConfigLinux {
+  pub skip_taskbar: Option<bool>,
+  pub transparent_draw: Option<bool>,
+  pub double_buffered: Option<bool>,
+  pub rgba_visual: Option<bool>,
+  pub app_paintable: Option<bool>,
+  pub cursor_moved_event: Option<bool>,
+  pub default_vbox: Option<bool>,
+}
+
+/// Options of `WindowBuilderExtIOS` in a [`WindowConfig`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WindowConfigIOS {
+  pub scale_factor: Option<f64>,
+  pub prefers_home_indicator_hidden: Option<bool>,
+  pub prefers_status_bar_hidden: Option<bool>,
+}
+
+impl WindowConfig {
+  #[cfg(target_os = "windows")]
+  fn platform_options(&self, mut builder: WindowBuilder) -> WindowBuilder {
+    use crate::platform::windows::WindowBuilderExtWindows;
+
+    let config = &self.windows;
+    if let Some(flag) = config.no_redirection_bitmap {
+      builder = builder.with_no_redirection_bitmap(flag);
+    }
+    if let Some(flag) = config.drag_and_drop {
+      builder = builder.with_drag_and_drop(flag);
+    }
+    if let Some(skip) = config.skip_taskbar {
+      builder = builder.with_skip_taskbar(skip);
+    }
+    if let Some(classname) = &config.window_classname {
+      builder = builder.with_window_classname(classname.as_str());
+    }
+    if let Some(shadow) = config.undecorated_shadow {
+      builder = builder.with_undecorated_shadow(shadow);
+    }
+    if let Some(rtl) = config.rtl {
+      builder = builder.with_rtl(rtl);
+    }
+    builder
+  }
+
+  #[cfg(target_os = "macos")]
+  fn platform_options(&self, mut builder: WindowBuilder) -> WindowBuilder {
+    use crate::platform::macos::WindowBuilderExtMacOS;
+
+    let config = &self.macos;
+    if let Some(movable) = config.movable_by_window_background {
+      builder = builder.with_movable_by_window_background(movable);
+    }
+    if let Some(transparent) = config.titlebar_transparent {
+      builder = builder.with_titlebar_transparent(transparent);
+    }
+    if let Some(hidden) = config.title_hidden {
+      builder = builder.with_title_hidden(hidden);
+    }
+    if let Some(hidden) = config.titlebar_hidden {
+      builder = builder.with_titlebar_hidden(hidden);
+    }
+    if let Some(hidden) = config.titlebar_buttons_hidden {
+      builder = builder.with_titlebar_buttons_hidden(hidden);
+    }
+    if let Some(fullsize) = config.fullsize_content_view {
+      builder = builder.with_fullsize_content_view(fullsize);
+    }
+    if let Some(increments) = config.resize_increments {
+      builder = builder.with_resize_increments(increments);
+    }
+    if let Some(disallow) = config.disallow_hidpi {
+      builder = builder.with_disallow_hidpi(disallow);
+    }
+    if let Some(has_shadow) = config.has_shadow {
+      builder = builder.with_has_shadow(has_shadow);
+    }
+    if let Some(inset) = config.traffic_light_inset {
+      builder = builder.with_traffic_light_inset(inset);
+    }
+    if let Some(automatic) = config.automatic_window_tabbing {
+      builder = builder.with_automatic_window_tabbing(automatic);
+    }
+    if let Some(identifier) = &config.tabbing_identifier {
+      builder = builder.with_tabbing_identifier(identifier);
+    }
+    if let Some(skip) = config.skip_taskbar {
+      builder = builder.with_skip_taskbar(skip);
+    }
+    builder
+  }
+
+  #[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+  ))]
+  fn platform_options(&self, mut builder: WindowBuilder) -> WindowBuilder {
+    use crate::platform::unix::WindowBuilderExtUnix;
+
+    let config = &self.linux;
+    if let Some(skip) = config.skip_taskbar {
+      builder = builder.with_skip_taskbar(skip);
+    }
+    if let Some(draw) = config.transparent_draw {
+      builder = builder.with_transparent_draw(draw);
+    }
+    if let Some(double_buffered) = config.double_buffered {
+      builder = builder.with_double_buffered(double_buffered);
+    }
+    if let Some(rgba_visual) = config.rgba_visual {
+      builder = builder.with_rgba_visual(rgba_visual);
+    }
+    if let Some(app_paintable) = config.app_paintable {
+      builder = builder.with_app_paintable(app_paintable);
+    }
+    if let Some(cursor_moved) = config.cursor_moved_event {
+      builder = builder.with_cursor_moved_event(cursor_moved);
+    }
+    if let Some(add) = config.default_vbox {
+      builder = builder.with_default_vbox(add);
+    }
+    builder
+  }
+
+  #[cfg(target_os = "ios")]
+  fn platform_options(&self, mut builder: WindowBuilder) -> WindowBuilder {
+    use crate::platform::ios::WindowBuilderExtIOS;
+
+    let config = &self.ios;
+    if let Some(scale_factor) = config.scale_factor {
+      builder = builder.with_scale_factor(scale_factor);
+    }
+    if let Some(hidden) = config.prefers_home_indicator_hidden {
+      builder = builder.with_prefers_home_indicator_hidden(hidden);
+    }
+    if let Some(hidden) = config.prefers_status_bar_hidden {
+      builder = builder.with_prefers_status_bar_hidden(hidden);
+    }
+    builder
+  }
+
+  #[cfg(target_os = "android")]
+  fn platform_options(&self, builder: WindowBuilder) -> WindowBuilder {
+    builder
+  }
+}
+
 /// Window size constraints
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
 pub struct WindowSizeConstraints {